@@ -471,12 +471,12 @@ export function useExportWiki() {
         format: "markdown" | "html" | "pdf";
       }) => apiClient.exportWiki(sessionId, format),
       {
-        onSuccess: (blob, { format }) => {
+        onSuccess: ({ blob, fileName }, { format }) => {
           // 创建下载链接
           const url = window.URL.createObjectURL(blob);
           const a = document.createElement("a");
           a.href = url;
-          a.download = `wiki.${format}`;
+          a.download = fileName;
           document.body.appendChild(a);
           a.click();
           window.URL.revokeObjectURL(url);
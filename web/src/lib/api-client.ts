@@ -18,33 +18,68 @@ import {
   Repository,
   InitializeRepositoryRequest,
   InitializeRepositoryResponse,
+  BatchRepositoryRequest,
+  BatchRepositoryResponse,
+  IndexingJobsResponse,
   RepositoriesResponse,
+  RepositorySharingResponse,
+  ShareRepositoryRequest,
   ChatQueryRequest,
   ChatQueryResponse,
+  ChatSession,
+  ChatSessionsResponse,
+  CreateChatSessionRequest,
+  ChatSessionMessageRequest,
+  RegenerateAnswerRequest,
+  ChatFeedbackRequest,
+  ChatFeedbackResponse,
   QueryHistoryResponse,
   GenerateWikiRequest,
   GenerateWikiResponse,
   WikiStructure,
   FileTreeNode,
   FileContentResponse,
+  RepositoryFileTreeResponse,
   HealthResponse,
   Config,
   ApiError,
   ApiResponse,
   PaginationParams,
   PaginatedResponse,
+  RepositoryListParams,
   // 认证相关类型
   AuthStatusResponse,
   LoginRequest,
   RegisterRequest,
   AuthResponse,
   RefreshTokenRequest,
+  OAuthProvider,
+  OAuthProvidersResponse,
   CreateApiKeyRequest,
   ApiKeyResponse,
   ApiKeysResponse,
+  // 组织相关类型
+  Organization,
+  OrganizationsResponse,
+  OrgRole,
+  // Wiki 导出
+  WikiExportJob,
+  // Webhook 相关类型
+  CreateWebhookRequest,
+  UpdateWebhookRequest,
+  Webhook,
+  WebhookCreatedResponse,
+  WebhookListResponse,
+  WebhookDelivery,
+  WebhookDeliveryListResponse,
   // 研究相关类型
   DeepResearchRequest,
   DeepResearchResponse,
+  // 用量相关类型
+  UsageQuery,
+  UsageReport,
+  SystemStats,
+  UsageExportFormat,
 } from "@/types/api";
 import { backendConnection } from "@/lib/backend-connection";
 
@@ -122,6 +157,10 @@ export class ApiClient {
     this.instance = axios.create({
       baseURL: baseURL || getCurrentApiBaseUrl(),
       timeout: REQUEST_TIMEOUT,
+      // Echo the CSRF cookie the server issues on state-changing requests
+      xsrfCookieName: "wikify_csrf",
+      xsrfHeaderName: "X-CSRF-Token",
+      withXSRFToken: true,
       headers: {
         "Content-Type": "application/json",
       },
@@ -185,7 +224,9 @@ export class ApiClient {
         const requestKey = `${config?.method}-${config?.url}`;
 
         console.log(
-          `❌ API Error Response: ${error.response?.status} for ${config?.url}`
+          `❌ API Error Response: ${error.response?.status} for ${config?.url}`,
+          // 服务端日志中可用该 ID 查找对应的请求
+          `(request id: ${error.response?.headers?.["x-request-id"] ?? "unknown"})`
         );
         console.log(`🔍 Error details:`, error.response?.data);
 
@@ -316,6 +357,42 @@ export class ApiClient {
     });
   }
 
+  /**
+   * 获取服务统计（管理员）
+   */
+  async getSystemStats(): Promise<SystemStats> {
+    return this.request<SystemStats>({
+      method: "GET",
+      url: "/admin/stats",
+    });
+  }
+
+  /**
+   * 获取模型用量与估算费用（管理员）
+   */
+  async getUsage(query: UsageQuery = {}): Promise<UsageReport> {
+    return this.request<UsageReport>({
+      method: "GET",
+      url: "/admin/usage",
+      params: query,
+    });
+  }
+
+  /**
+   * 导出用量记录（管理员），返回 CSV 或 JSONL 文本
+   */
+  async exportUsage(
+    format: UsageExportFormat = "csv",
+    query: Omit<UsageQuery, "group_by"> = {}
+  ): Promise<string> {
+    return this.request<string>({
+      method: "GET",
+      url: "/admin/usage/export",
+      params: { ...query, format },
+      responseType: "text",
+    });
+  }
+
   // ============================================================================
   // 认证 API
   // ============================================================================
@@ -363,6 +440,50 @@ export class ApiClient {
     });
   }
 
+  /**
+   * 获取已配置的 OAuth 提供方
+   */
+  async listOAuthProviders(): Promise<OAuthProvidersResponse> {
+    return this.request<OAuthProvidersResponse>({
+      method: "GET",
+      url: "/auth/oauth/providers",
+    });
+  }
+
+  /**
+   * OAuth 登录地址（浏览器需直接跳转）
+   * 登录成功后跳转回 redirect，Token 位于 URL fragment 中
+   */
+  getOAuthLoginUrl(
+    provider: OAuthProvider,
+    options: { repoAccess?: boolean; redirect?: string } = {}
+  ): string {
+    const params = new URLSearchParams();
+    if (options.repoAccess) params.set("repo_access", "true");
+    if (options.redirect) params.set("redirect", options.redirect);
+    const query = params.toString();
+    return `${this.getBaseURL()}/auth/oauth/${provider}${query ? `?${query}` : ""}`;
+  }
+
+  /**
+   * 单点登录（OIDC）地址（浏览器需直接跳转）
+   * 登录成功后跳转回 redirect，Token 位于 URL fragment 中
+   */
+  getOidcLoginUrl(redirect?: string): string {
+    const query = redirect ? `?${new URLSearchParams({ redirect }).toString()}` : "";
+    return `${this.getBaseURL()}/auth/oidc${query}`;
+  }
+
+  /**
+   * 撤销 OAuth 仓库访问授权
+   */
+  async revokeOAuthToken(provider: OAuthProvider): Promise<void> {
+    return this.request<void>({
+      method: "DELETE",
+      url: `/auth/oauth/${provider}/token`,
+    });
+  }
+
   /**
    * 用户登出
    */
@@ -414,6 +535,170 @@ export class ApiClient {
     });
   }
 
+  // ============================================================================
+  // 组织 API
+  // ============================================================================
+
+  /**
+   * 切换当前组织，之后的请求都会带上 X-Organization-Id 头
+   * 传入 null 回到个人空间
+   */
+  setOrganization(organizationId: string | null) {
+    if (organizationId) {
+      this.instance.defaults.headers.common["X-Organization-Id"] =
+        organizationId;
+    } else {
+      delete this.instance.defaults.headers.common["X-Organization-Id"];
+    }
+  }
+
+  /**
+   * 获取当前用户所属的组织
+   */
+  async listOrganizations(): Promise<OrganizationsResponse> {
+    return this.request<OrganizationsResponse>({
+      method: "GET",
+      url: "/organizations",
+    });
+  }
+
+  /**
+   * 创建组织
+   */
+  async createOrganization(name: string): Promise<Organization> {
+    return this.request<Organization>({
+      method: "POST",
+      url: "/organizations",
+      data: { name },
+    });
+  }
+
+  /**
+   * 获取组织及其成员
+   */
+  async getOrganization(organizationId: string): Promise<Organization> {
+    return this.request<Organization>({
+      method: "GET",
+      url: `/organizations/${organizationId}`,
+    });
+  }
+
+  /**
+   * 删除组织（仅所有者，且组织下没有仓库）
+   */
+  async deleteOrganization(organizationId: string): Promise<void> {
+    return this.request<void>({
+      method: "DELETE",
+      url: `/organizations/${organizationId}`,
+    });
+  }
+
+  /**
+   * 添加组织成员或修改成员角色
+   */
+  async setOrganizationMember(
+    organizationId: string,
+    userId: string,
+    role: OrgRole
+  ): Promise<Organization> {
+    return this.request<Organization>({
+      method: "PUT",
+      url: `/organizations/${organizationId}/members/${userId}`,
+      data: { role },
+    });
+  }
+
+  /**
+   * 移除组织成员
+   */
+  async removeOrganizationMember(
+    organizationId: string,
+    userId: string
+  ): Promise<Organization> {
+    return this.request<Organization>({
+      method: "DELETE",
+      url: `/organizations/${organizationId}/members/${userId}`,
+    });
+  }
+
+  // ============================================================================
+  // Webhook API
+  // ============================================================================
+
+  /**
+   * 获取当前用户注册的 Webhook（管理员可看到全部）
+   */
+  async listWebhooks(): Promise<WebhookListResponse> {
+    return this.request<WebhookListResponse>({
+      method: "GET",
+      url: "/webhooks",
+    });
+  }
+
+  /**
+   * 注册 Webhook，返回的密钥只显示这一次
+   */
+  async createWebhook(
+    request: CreateWebhookRequest
+  ): Promise<WebhookCreatedResponse> {
+    return this.request<WebhookCreatedResponse>({
+      method: "POST",
+      url: "/webhooks",
+      data: request,
+    });
+  }
+
+  /**
+   * 修改 Webhook
+   */
+  async updateWebhook(
+    webhookId: string,
+    request: UpdateWebhookRequest
+  ): Promise<Webhook> {
+    return this.request<Webhook>({
+      method: "PATCH",
+      url: `/webhooks/${webhookId}`,
+      data: request,
+    });
+  }
+
+  /**
+   * 删除 Webhook 及其投递记录
+   */
+  async deleteWebhook(webhookId: string): Promise<void> {
+    return this.request<void>({
+      method: "DELETE",
+      url: `/webhooks/${webhookId}`,
+    });
+  }
+
+  /**
+   * 获取最近的投递记录
+   */
+  async listWebhookDeliveries(
+    webhookId: string,
+    limit?: number
+  ): Promise<WebhookDeliveryListResponse> {
+    return this.request<WebhookDeliveryListResponse>({
+      method: "GET",
+      url: `/webhooks/${webhookId}/deliveries`,
+      params: limit ? { limit } : undefined,
+    });
+  }
+
+  /**
+   * 重新投递一次，返回新的投递记录
+   */
+  async retryWebhookDelivery(
+    webhookId: string,
+    deliveryId: string
+  ): Promise<WebhookDelivery> {
+    return this.request<WebhookDelivery>({
+      method: "POST",
+      url: `/webhooks/${webhookId}/deliveries/${deliveryId}/retry`,
+    });
+  }
+
   // ============================================================================
   // 深度研究 API
   // ============================================================================
@@ -479,10 +764,13 @@ export class ApiClient {
   /**
    * 获取仓库列表
    */
-  async getRepositories(): Promise<RepositoriesResponse> {
+  async getRepositories(
+    params?: RepositoryListParams
+  ): Promise<RepositoriesResponse> {
     return this.request<RepositoriesResponse>({
       method: "GET",
       url: "/repositories",
+      params,
     });
   }
 
@@ -499,6 +787,19 @@ export class ApiClient {
     });
   }
 
+  /**
+   * 批量注册仓库
+   */
+  async batchInitializeRepositories(
+    data: BatchRepositoryRequest
+  ): Promise<BatchRepositoryResponse> {
+    return this.request<BatchRepositoryResponse>({
+      method: "POST",
+      url: "/repositories/batch",
+      data,
+    });
+  }
+
   /**
    * 获取仓库信息
    */
@@ -521,6 +822,30 @@ export class ApiClient {
     });
   }
 
+  /**
+   * 修改仓库可见性及共享用户
+   */
+  async shareRepository(
+    repositoryId: string,
+    data: ShareRepositoryRequest
+  ): Promise<RepositorySharingResponse> {
+    return this.request<RepositorySharingResponse>({
+      method: "POST",
+      url: `/repositories/${repositoryId}/share`,
+      data,
+    });
+  }
+
+  /**
+   * 获取索引任务队列
+   */
+  async listIndexingJobs(): Promise<IndexingJobsResponse> {
+    return this.request<IndexingJobsResponse>({
+      method: "GET",
+      url: "/jobs",
+    });
+  }
+
   /**
    * 删除仓库
    */
@@ -546,6 +871,100 @@ export class ApiClient {
     });
   }
 
+  /**
+   * 创建聊天会话
+   */
+  async createChatSession(data: CreateChatSessionRequest): Promise<ChatSession> {
+    return this.request<ChatSession>({
+      method: "POST",
+      url: "/chat/sessions",
+      data,
+    });
+  }
+
+  /**
+   * 获取聊天会话列表
+   */
+  async listChatSessions(repositoryId?: string): Promise<ChatSessionsResponse> {
+    return this.request<ChatSessionsResponse>({
+      method: "GET",
+      url: "/chat/sessions",
+      params: repositoryId ? { repository_id: repositoryId } : undefined,
+    });
+  }
+
+  /**
+   * 获取聊天会话及其消息
+   */
+  async getChatSession(sessionId: string): Promise<ChatSession> {
+    return this.request<ChatSession>({
+      method: "GET",
+      url: `/chat/sessions/${sessionId}`,
+    });
+  }
+
+  /**
+   * 重命名聊天会话
+   */
+  async renameChatSession(sessionId: string, title: string): Promise<ChatSession> {
+    return this.request<ChatSession>({
+      method: "PATCH",
+      url: `/chat/sessions/${sessionId}`,
+      data: { title },
+    });
+  }
+
+  /**
+   * 在聊天会话中继续提问
+   */
+  async continueChatSession(
+    sessionId: string,
+    data: ChatSessionMessageRequest
+  ): Promise<ChatQueryResponse> {
+    return this.request<ChatQueryResponse>({
+      method: "POST",
+      url: `/chat/sessions/${sessionId}/messages`,
+      data,
+    });
+  }
+
+  /**
+   * 使用不同参数重新生成最后一个问题的回答
+   */
+  async regenerateAnswer(
+    sessionId: string,
+    data: RegenerateAnswerRequest = {}
+  ): Promise<ChatQueryResponse> {
+    return this.request<ChatQueryResponse>({
+      method: "POST",
+      url: `/chat/${sessionId}/regenerate`,
+      data,
+    });
+  }
+
+  /**
+   * 提交回答反馈
+   */
+  async submitChatFeedback(
+    data: ChatFeedbackRequest
+  ): Promise<ChatFeedbackResponse> {
+    return this.request<ChatFeedbackResponse>({
+      method: "POST",
+      url: "/chat/feedback",
+      data,
+    });
+  }
+
+  /**
+   * 删除聊天会话
+   */
+  async deleteChatSession(sessionId: string): Promise<void> {
+    return this.request<void>({
+      method: "DELETE",
+      url: `/chat/sessions/${sessionId}`,
+    });
+  }
+
   /**
    * 获取查询历史
    */
@@ -586,19 +1005,23 @@ export class ApiClient {
   }
 
   /**
-   * 导出 Wiki
+   * 导出 Wiki：先在服务端生成导出文件，再下载
    */
   async exportWiki(
     repositoryId: string,
     format: "markdown" | "html" | "pdf"
-  ): Promise<Blob> {
-    const response = await this.instance.request({
+  ): Promise<{ blob: Blob; fileName: string }> {
+    const job = await this.request<WikiExportJob>({
       method: "POST",
       url: `/wiki/${repositoryId}/export`,
       data: { format },
+    });
+    const response = await this.instance.request({
+      method: "GET",
+      url: `/wiki/${repositoryId}/export/${job.job_id}/download`,
       responseType: "blob",
     });
-    return response.data;
+    return { blob: response.data, fileName: job.file_name };
   }
 
   // ============================================================================
@@ -608,12 +1031,16 @@ export class ApiClient {
   /**
    * 获取文件树
    */
-  async getFileTree(repositoryId: string): Promise<FileTreeNode[]> {
-    return this.request<FileTreeNode[]>({
-      method: "POST",
-      url: "/files/tree",
-      data: { repository_id: repositoryId },
+  async getFileTree(
+    repositoryId: string,
+    branch?: string
+  ): Promise<FileTreeNode[]> {
+    const response = await this.request<RepositoryFileTreeResponse>({
+      method: "GET",
+      url: `/repositories/${repositoryId}/files`,
+      params: { branch },
     });
+    return response.tree;
   }
 
   /**
@@ -621,12 +1048,13 @@ export class ApiClient {
    */
   async getFileContent(
     repositoryId: string,
-    filePath: string
+    filePath: string,
+    branch?: string
   ): Promise<FileContentResponse> {
     return this.request<FileContentResponse>({
-      method: "POST",
-      url: "/files/content",
-      data: { repository_id: repositoryId, file_path: filePath },
+      method: "GET",
+      url: `/repositories/${repositoryId}/file`,
+      params: { path: filePath, branch },
     });
   }
 }
@@ -67,6 +67,9 @@ export class WebSocketClient {
   private readonly MAX_STORED_IDS = 1000;
   private cleanupTimer: NodeJS.Timeout | null = null;
 
+  // 最近收到的仓库事件，重连时据此补发错过的进度
+  private lastEvent: { repositoryId: string; eventId: number } | null = null;
+
   constructor(
     endpoint: string,
     config: Partial<WebSocketConfig> = {},
@@ -116,7 +119,7 @@ export class WebSocketClient {
       this.log("Connecting to WebSocket server...");
 
       try {
-        this.ws = new WebSocket(this.config.url);
+        this.ws = new WebSocket(this.buildUrl());
         this.setupEventListeners(resolve, reject);
       } catch (error) {
         this.isConnecting = false;
@@ -291,6 +294,14 @@ export class WebSocketClient {
       // 只记录非心跳消息的日志
       this.log("Message received", message);
 
+      // 记录事件序号，用于重连后补发
+      if (message.event_id !== undefined && "repository_id" in message) {
+        this.lastEvent = {
+          repositoryId: message.repository_id,
+          eventId: message.event_id,
+        };
+      }
+
       // 首先调用通用消息处理器（如果存在）
       this.handlers.onMessage?.(message);
 
@@ -351,6 +362,9 @@ export class WebSocketClient {
       case "IndexError":
         this.handlers.onIndexError?.(message);
         break;
+      case "IndexCancelled":
+        this.handlers.onIndexCancelled?.(message);
+        break;
       case "ResearchStart":
         this.handlers.onResearchStart?.(message);
         break;
@@ -592,6 +606,20 @@ export class WebSocketClient {
     return messageId; // 返回消息ID供调用者追踪
   }
 
+  /**
+   * 构建连接地址，重连时带上最近的事件序号以补发错过的事件
+   */
+  private buildUrl(): string {
+    if (!this.lastEvent) {
+      return this.config.url;
+    }
+
+    const url = new URL(this.config.url);
+    url.searchParams.set("repository_id", this.lastEvent.repositoryId);
+    url.searchParams.set("since", String(this.lastEvent.eventId));
+    return url.toString();
+  }
+
   /**
    * 生成唯一消息ID
    */
@@ -40,9 +40,46 @@ export interface Repository {
   created_at: string;
   last_indexed_at?: string;
   wiki_generated_at?: string;
+  organization_id?: string | null; // 所属组织，个人仓库为空
+  visibility?: RepositoryVisibility;
+  shared_with?: string[]; // 被单独共享的用户
   metadata?: Record<string, any>;
 }
 
+/**
+ * 仓库可见性
+ */
+export type RepositoryVisibility = "private" | "org" | "public";
+
+/**
+ * 仓库共享授权
+ */
+export interface ShareGrant {
+  user_id: string;
+  granted_by?: string | null;
+  granted_at: string;
+}
+
+/**
+ * 修改仓库共享请求
+ */
+export interface ShareRepositoryRequest {
+  visibility?: RepositoryVisibility;
+  share_with?: string[];
+  revoke?: string[];
+}
+
+/**
+ * 仓库共享设置响应
+ */
+export interface RepositorySharingResponse {
+  repository_id: string;
+  owner_id?: string | null;
+  organization_id?: string | null;
+  visibility: RepositoryVisibility;
+  share_grants: ShareGrant[];
+}
+
 /**
  * 初始化仓库请求 - 与后端 InitializeRepositoryRequest 对齐
  */
@@ -51,6 +88,37 @@ export interface InitializeRepositoryRequest {
   repo_type?: string | null; // "github", "local", etc.
   access_token?: string | null;
   auto_generate_wiki?: boolean | null; // 是否在索引完成后自动生成wiki，默认为true
+  visibility?: RepositoryVisibility | null; // 默认：组织内为 org，否则为 private
+}
+
+/**
+ * 批量注册仓库请求
+ */
+export interface BatchRepositoryRequest {
+  urls: string[]; // 最多 200 个远程仓库 URL
+  access_token?: string | null;
+  auto_index?: boolean | null;
+  auto_generate_wiki?: boolean | null;
+  visibility?: RepositoryVisibility | null;
+}
+
+/**
+ * 批量注册中单个 URL 的结果
+ */
+export interface BatchRepositoryItem {
+  url: string;
+  status: "created" | "invalid" | "duplicate" | "failed";
+  repository_id: string | null;
+  error: string | null;
+}
+
+/**
+ * 批量注册仓库响应，结果与请求中的 URL 顺序一致
+ */
+export interface BatchRepositoryResponse {
+  created: number;
+  failed: number;
+  results: BatchRepositoryItem[];
 }
 
 /**
@@ -62,11 +130,53 @@ export interface InitializeRepositoryResponse {
   message: string;
 }
 
+/**
+ * 索引任务
+ */
+export interface IndexingJob {
+  id: string;
+  repository_id: string;
+  user_id?: string | null;
+  priority: "interactive" | "background";
+  state: "running" | "queued";
+  queue_position?: number | null; // 排队位置，从1开始
+  queued_at: string;
+  started_at?: string | null;
+}
+
+/**
+ * 索引任务列表响应
+ */
+export interface IndexingJobsResponse {
+  jobs: IndexingJob[];
+  running: number;
+  queued: number;
+}
+
 /**
  * 仓库列表响应
  */
 export interface RepositoriesResponse {
   repositories: Repository[];
+  // 分页信息：total 为所有页的总数
+  total: number;
+  limit: number;
+  offset: number;
+}
+
+/**
+ * 仓库列表查询参数：过滤、排序和分页
+ */
+export interface RepositoryListParams {
+  status?: "pending" | "indexing" | "indexed" | "failed" | "cancelled";
+  repo_type?: string;
+  owner?: string;
+  // 仓库 URL 子串，不区分大小写
+  name?: string;
+  sort?: "created_at" | "last_indexed_at";
+  order?: "asc" | "desc";
+  limit?: number;
+  offset?: number;
 }
 
 // ============================================================================
@@ -110,6 +220,8 @@ export interface ChatQueryRequest {
   repository_id: string;
   question: string;
   context?: string;
+  // 继续已有的会话，省略时创建新会话
+  session_id?: string;
 }
 
 /**
@@ -119,9 +231,119 @@ export interface ChatQueryResponse {
   answer: string;
   sources: SourceDocument[];
   repository_id: string;
+  session_id?: string;
+  /** 回答在同一问题的所有回答中的分支序号 */
+  branch?: number | null;
+  /** 查询记录 ID，用于提交回答反馈（未启用数据库时为 null） */
+  query_id?: string | null;
   timestamp: string;
 }
 
+/**
+ * 回答评价
+ */
+export type FeedbackRating = "up" | "down";
+
+/**
+ * 回答反馈请求
+ */
+export interface ChatFeedbackRequest {
+  query_id: string;
+  rating: FeedbackRating;
+  comment?: string;
+  /** 被标记为错误的来源文件 */
+  wrong_sources?: string[];
+}
+
+/**
+ * 回答反馈响应
+ */
+export interface ChatFeedbackResponse {
+  id: string;
+  query_id: string;
+  rating: FeedbackRating;
+  created_at: string;
+}
+
+/**
+ * 聊天会话摘要
+ */
+export interface ChatSessionSummary {
+  id: string;
+  repository_id: string;
+  user_id?: string;
+  title: string;
+  message_count: number;
+  created_at: string;
+  last_activity: string;
+}
+
+/**
+ * 聊天会话消息
+ */
+export interface ChatSessionMessage {
+  id: string;
+  role: "user" | "assistant";
+  content: string;
+  timestamp: string;
+  /** 回答的检索信息：来源、置信度和查询参数 */
+  metadata?: {
+    sources?: string[];
+    confidence?: number | null;
+    max_results?: number | null;
+    parameters?: Record<string, string> | null;
+    retrieval?: Record<string, string>;
+    /** 所回答问题的消息 ID */
+    question_id?: string | null;
+    /** 同一问题的第几个回答，从 0 开始 */
+    branch?: number;
+  } | null;
+}
+
+/**
+ * 聊天会话（包含消息）
+ */
+export interface ChatSession {
+  id: string;
+  repository_id: string;
+  user_id?: string;
+  title: string;
+  messages: ChatSessionMessage[];
+  created_at: string;
+  last_activity: string;
+}
+
+/**
+ * 创建聊天会话请求
+ */
+export interface CreateChatSessionRequest {
+  repository_id: string;
+  title?: string;
+}
+
+/**
+ * 在聊天会话中继续提问的请求
+ */
+export interface ChatSessionMessageRequest {
+  question: string;
+  max_results?: number;
+}
+
+/**
+ * 重新生成回答的请求
+ */
+export interface RegenerateAnswerRequest {
+  max_results?: number;
+  similarity_threshold?: number;
+}
+
+/**
+ * 聊天会话列表响应
+ */
+export interface ChatSessionsResponse {
+  sessions: ChatSessionSummary[];
+}
+
 /**
  * 查询历史记录
  */
@@ -165,6 +387,17 @@ export interface WikiPage {
   reading_time: number;
   generated_at: string;
   source_documents: DocumentInfo[];
+  code_examples?: CodeExample[]; // 从仓库原样提取的代码示例
+}
+
+/**
+ * 代码示例来源
+ */
+export interface CodeExample {
+  path: string;
+  start_line: number;
+  end_line: number;
+  verified: boolean; // false 表示未能读取源文件，保留了 LLM 生成的内容
 }
 
 /**
@@ -198,6 +431,70 @@ export interface WikiStructure {
   description: string;
   pages: WikiPage[];
   sections: WikiSection[];
+  navigation?: WikiNavigationItem[]; // 侧边栏目录：章节 → 页面 → 标题
+  link_validation?: WikiLinkValidation; // 页面间及源文件链接的检查结果
+  quality?: WikiQualityReport | null; // 生成后的质量评分与薄弱环节报告
+}
+
+/**
+ * Wiki 质量报告
+ */
+export interface WikiQualityReport {
+  score: number; // 总分 0-100
+  file_coverage: number | null; // 被页面引用的仓库文件比例 (0-1)，未知时为 null
+  total_files: number;
+  referenced_files: number;
+  uncovered_files: string[]; // 未被任何页面引用的文件（最多 50 个）
+  broken_links: number;
+  empty_sections: number;
+  empty_wiki_sections: string[]; // 没有页面的章节
+  pages: WikiPageQuality[]; // 按分数从低到高排序
+}
+
+/**
+ * Wiki 页面质量
+ */
+export interface WikiPageQuality {
+  page_id: string;
+  title: string;
+  score: number; // 0-100
+  missing_files: string[]; // 引用了但仓库中不存在的文件
+  broken_links: number;
+  empty_sections: string[]; // 没有内容的标题
+  citations: number; // 指向源文件的链接数
+  citation_density: number; // 每千词引用数
+  issues: string[];
+}
+
+/**
+ * Wiki 链接检查结果
+ */
+export interface WikiLinkValidation {
+  checked_links: number;
+  rewritten_links: number;
+  dangling: WikiDanglingLink[]; // 无法解析的链接
+  linked_files: string[];
+}
+
+/**
+ * 无法解析的 Wiki 链接
+ */
+export interface WikiDanglingLink {
+  page_id: string;
+  text: string;
+  target: string;
+  reason: "unknown_page" | "unknown_anchor" | "unknown_file";
+}
+
+/**
+ * Wiki 导航条目
+ */
+export interface WikiNavigationItem {
+  kind: "section" | "page" | "heading";
+  id: string; // 章节ID；页面和标题为页面ID
+  title: string;
+  anchor?: string; // 标题在页面内的锚点
+  children: WikiNavigationItem[];
 }
 
 /**
@@ -208,6 +505,7 @@ export interface WikiGenerationConfig {
   max_pages?: number;
   include_diagrams?: boolean;
   comprehensive_view?: boolean;
+  force_regenerate?: boolean; // 忽略当前提交与配置的缓存，强制重新生成
 }
 
 /**
@@ -226,6 +524,7 @@ export interface GenerateWikiResponse {
   status: string;
   pages_count: number;
   sections_count: number;
+  quality: WikiQualityReport | null; // 后台生成时为 null，完成后随 WikiComplete 消息发送
 }
 
 // ============================================================================
@@ -243,14 +542,27 @@ export interface FileTreeNode {
   children?: FileTreeNode[];
 }
 
+/**
+ * 仓库文件树响应
+ */
+export interface RepositoryFileTreeResponse {
+  repository_id: string;
+  branch: string | null;
+  total_files: number;
+  tree: FileTreeNode[];
+}
+
 /**
  * 文件内容响应
  */
 export interface FileContentResponse {
+  repository_id: string;
   path: string;
+  branch: string | null;
   content: string;
-  file_type: string;
-  size: number;
+  language: string | null;
+  size: number; // 完整文件的字节数
+  truncated: boolean; // 大文件只返回开头部分
 }
 
 // ============================================================================
@@ -351,6 +663,110 @@ export interface Config {
   };
 }
 
+/**
+ * 用量分组方式
+ */
+export type UsageGroupBy = "user" | "repository" | "day";
+
+/**
+ * 用量查询参数（管理员）
+ */
+export interface UsageQuery {
+  group_by?: UsageGroupBy;
+  user_id?: string;
+  repository_id?: string;
+  /** RFC 3339 时间 */
+  since?: string;
+  until?: string;
+}
+
+/**
+ * 用量导出格式
+ */
+export type UsageExportFormat = "csv" | "jsonl";
+
+/**
+ * 用量汇总
+ */
+export interface UsageAggregate {
+  /** 用户 ID、仓库 ID 或日期 */
+  key: string;
+  calls: number;
+  input_tokens: number;
+  output_tokens: number;
+  embedding_tokens: number;
+  /** 估算费用（美元） */
+  estimated_cost: number;
+}
+
+/**
+ * 超出的每日预算
+ */
+export interface BudgetWarning {
+  scope: "global" | "user" | "repository";
+  subject?: string;
+  spent: number;
+  budget: number;
+}
+
+/**
+ * 用量报告
+ */
+export interface UsageReport {
+  group_by: UsageGroupBy;
+  total: UsageAggregate;
+  groups: UsageAggregate[];
+  warnings: BudgetWarning[];
+}
+
+/**
+ * 一类操作自服务启动以来的统计
+ */
+export interface OperationStats {
+  total: number;
+  successful: number;
+  failed: number;
+  /** 正在进行的操作数 */
+  active: number;
+  /** 已结束操作中失败的比例（0-1） */
+  failure_rate: number;
+  /** 成功操作的耗时（毫秒） */
+  avg_duration_ms: number;
+  min_duration_ms: number;
+  max_duration_ms: number;
+}
+
+/**
+ * 服务统计（管理员仪表盘）
+ */
+export interface SystemStats {
+  generated_at: string;
+  repositories: {
+    total: number;
+    pending: number;
+    indexing: number;
+    completed: number;
+    failed: number;
+    cancelled: number;
+  };
+  indexes: {
+    loaded: number;
+    total_chunks: number;
+    /** 按分块数从大到小排列 */
+    repositories: { repository_id: string; chunks: number }[];
+  };
+  indexing: OperationStats;
+  queries: OperationStats;
+  /** 磁盘占用（字节） */
+  storage: {
+    clones_bytes: number;
+    wikis_bytes: number;
+    uploads_bytes: number;
+    database_bytes?: number | null;
+    total_bytes: number;
+  };
+}
+
 // ============================================================================
 // 认证相关类型
 // ============================================================================
@@ -358,7 +774,7 @@ export interface Config {
 /**
  * 权限模式
  */
-export type AuthMode = "open" | "private" | "enterprise";
+export type AuthMode = "open" | "restricted" | "private" | "enterprise";
 
 /**
  * 用户权限
@@ -389,6 +805,20 @@ export interface AuthStatusResponse {
   };
 }
 
+/**
+ * OAuth 登录提供方
+ */
+export type OAuthProvider = "github" | "gitlab";
+
+/**
+ * 已配置的 OAuth 提供方
+ */
+export interface OAuthProvidersResponse {
+  providers: OAuthProvider[];
+  /** 单点登录（OIDC）提供方名称，未配置时为空 */
+  oidc?: string;
+}
+
 /**
  * 用户注册请求
  */
@@ -476,6 +906,144 @@ export interface ApiKeysResponse {
   api_keys: ApiKeyResponse[];
 }
 
+// ============================================================================
+// 组织相关类型
+// ============================================================================
+
+/**
+ * 组织成员角色，后者包含前者的全部权限
+ */
+export type OrgRole = "viewer" | "member" | "admin" | "owner";
+
+/**
+ * 组织成员
+ */
+export interface OrganizationMember {
+  user_id: string;
+  role: OrgRole;
+  joined_at: string;
+}
+
+/**
+ * 组织信息
+ */
+export interface Organization {
+  id: string;
+  name: string;
+  created_at: string;
+  members: OrganizationMember[];
+}
+
+/**
+ * 组织列表响应
+ */
+export interface OrganizationsResponse {
+  organizations: Organization[];
+}
+
+// ============================================================================
+// Wiki 导出类型
+// ============================================================================
+
+/**
+ * 服务端生成的导出文件，通过下载接口获取（支持断点续传）
+ */
+export interface WikiExportJob {
+  job_id: string;
+  repository_id: string;
+  format: string;
+  file_name: string;
+  content_type: string;
+  size: number;
+  created_at: string;
+  download_url: string;
+}
+
+// ============================================================================
+// Webhook 类型
+// ============================================================================
+
+/**
+ * Webhook 事件
+ */
+export type WebhookEventKind =
+  | "indexing.completed"
+  | "indexing.failed"
+  | "wiki.generated"
+  | "research.finished";
+
+/**
+ * 注册 Webhook 请求
+ */
+export interface CreateWebhookRequest {
+  url: string;
+  events?: WebhookEventKind[]; // 为空时接收所有事件
+  repository_id?: string; // 不填时接收所有仓库的事件（仅管理员）
+  secret?: string; // 不填时自动生成
+}
+
+/**
+ * 修改 Webhook 请求
+ */
+export interface UpdateWebhookRequest {
+  url?: string;
+  events?: WebhookEventKind[];
+  secret?: string;
+  active?: boolean;
+}
+
+/**
+ * Webhook 信息（不含密钥）
+ */
+export interface Webhook {
+  id: string;
+  url: string;
+  events: WebhookEventKind[];
+  repository_id?: string;
+  owner_id?: string;
+  active: boolean;
+  created_at: string;
+  updated_at: string;
+}
+
+/**
+ * 新注册的 Webhook，密钥只返回这一次
+ */
+export interface WebhookCreatedResponse extends Webhook {
+  secret: string;
+}
+
+/**
+ * Webhook 列表响应
+ */
+export interface WebhookListResponse {
+  webhooks: Webhook[];
+}
+
+/**
+ * 一次投递记录
+ */
+export interface WebhookDelivery {
+  id: string;
+  webhook_id: string;
+  event_id: string;
+  event: WebhookEventKind;
+  payload: string;
+  attempt: number;
+  status_code?: number;
+  success: boolean;
+  error?: string;
+  duration_ms: number;
+  delivered_at: string;
+}
+
+/**
+ * 投递记录列表响应（最新的在前）
+ */
+export interface WebhookDeliveryListResponse {
+  deliveries: WebhookDelivery[];
+}
+
 // ============================================================================
 // 错误类型
 // ============================================================================
@@ -487,6 +1055,8 @@ export interface ApiError {
   error: string;
   message: string;
   details?: Record<string, any>;
+  // 请求 ID，与响应头 X-Request-Id 相同
+  request_id?: string;
 }
 
 // ============================================================================
@@ -508,6 +1078,7 @@ export interface ApiResponse<T = any> {
 export interface PaginationParams {
   page?: number;
   limit?: number;
+  offset?: number;
   sort?: string;
   order?: "asc" | "desc";
 }
@@ -3,7 +3,7 @@
  * 与后端 WebSocket 协议保持一致
  */
 
-import { SourceDocument } from "./api";
+import { SourceDocument, WikiQualityReport } from "./api";
 
 // Import unified types to match backend
 interface WikiConfig {
@@ -55,13 +55,17 @@ export type WebSocketMessageType =
   | "IndexProgress"
   | "IndexComplete"
   | "IndexError"
+  | "IndexCancelled"
   | "ResearchStart"
   | "ResearchProgress"
   | "ResearchComplete"
   | "ResearchError"
   | "Error"
   | "Ping"
-  | "Pong";
+  | "Pong"
+  | "Subscribe"
+  | "Unsubscribe"
+  | "Subscribed";
 
 /**
  * WebSocket 消息基础结构
@@ -70,6 +74,8 @@ export interface BaseWebSocketMessage {
   type: WebSocketMessageType;
   timestamp: string;
   id?: string;
+  // 仓库事件流中的序号（索引、Wiki、研究进度），用于断线重连后补发
+  event_id?: number;
 }
 
 // ============================================================================
@@ -145,6 +151,7 @@ export interface WikiCompleteMessage extends BaseWebSocketMessage {
   pages_count: number;
   sections_count: number;
   metadata?: WikiMetadata; // Use unified WikiMetadata type
+  quality?: WikiQualityReport | null; // 生成后的质量报告
 }
 
 /**
@@ -204,6 +211,15 @@ export interface IndexErrorMessage extends BaseWebSocketMessage {
   details?: Record<string, any>;
 }
 
+/**
+ * 索引取消消息
+ */
+export interface IndexCancelledMessage extends BaseWebSocketMessage {
+  type: "IndexCancelled";
+  repository_id: string;
+  progress: number; // 0.0 to 1.0, progress reached before cancellation
+}
+
 // ============================================================================
 // 研究相关消息
 // ============================================================================
@@ -285,6 +301,34 @@ export interface PongMessage extends BaseWebSocketMessage {
   type: "Pong";
 }
 
+// ============================================================================
+// 订阅消息
+// ============================================================================
+
+/**
+ * 关注仓库的事件（在 /ws 上首次订阅后只接收已关注仓库的事件）
+ */
+export interface SubscribeMessage extends BaseWebSocketMessage {
+  type: "Subscribe";
+  repository_ids: string[];
+}
+
+/**
+ * 取消关注仓库的事件
+ */
+export interface UnsubscribeMessage extends BaseWebSocketMessage {
+  type: "Unsubscribe";
+  repository_ids: string[];
+}
+
+/**
+ * 当前关注的仓库，null 表示关注全部仓库
+ */
+export interface SubscribedMessage extends BaseWebSocketMessage {
+  type: "Subscribed";
+  repository_ids: string[] | null;
+}
+
 // ============================================================================
 // 联合类型
 // ============================================================================
@@ -304,18 +348,27 @@ export type WebSocketMessage =
   | IndexProgressMessage
   | IndexCompleteMessage
   | IndexErrorMessage
+  | IndexCancelledMessage
   | ResearchStartMessage
   | ResearchProgressMessage
   | ResearchCompleteMessage
   | ResearchErrorMessage
   | ErrorMessage
   | PingMessage
-  | PongMessage;
+  | PongMessage
+  | SubscribeMessage
+  | UnsubscribeMessage
+  | SubscribedMessage;
 
 /**
  * 客户端发送的消息类型
  */
-export type ClientMessage = ChatMessage | WikiGenerateMessage | PingMessage;
+export type ClientMessage =
+  | ChatMessage
+  | WikiGenerateMessage
+  | PingMessage
+  | SubscribeMessage
+  | UnsubscribeMessage;
 
 /**
  * 服务端发送的消息类型
@@ -330,12 +383,14 @@ export type ServerMessage =
   | IndexProgressMessage
   | IndexCompleteMessage
   | IndexErrorMessage
+  | IndexCancelledMessage
   | ResearchStartMessage
   | ResearchProgressMessage
   | ResearchCompleteMessage
   | ResearchErrorMessage
   | ErrorMessage
-  | PongMessage;
+  | PongMessage
+  | SubscribedMessage;
 
 // ============================================================================
 // WebSocket 事件处理器类型
@@ -352,6 +407,7 @@ export interface WebSocketEventHandlers {
   onIndexProgress?: (message: IndexProgressMessage) => void;
   onIndexComplete?: (message: IndexCompleteMessage) => void;
   onIndexError?: (message: IndexErrorMessage) => void;
+  onIndexCancelled?: (message: IndexCancelledMessage) => void;
   onResearchStart?: (message: ResearchStartMessage) => void;
   onResearchProgress?: (message: ResearchProgressMessage) => void;
   onResearchComplete?: (message: ResearchCompleteMessage) => void;
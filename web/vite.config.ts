@@ -5,6 +5,8 @@ import { componentTagger } from "lovable-tagger";
 
 // https://vitejs.dev/config/
 export default defineConfig(({ mode }) => ({
+  // 部署在路径前缀下时设置，例如 /wikify/
+  base: process.env.VITE_BASE_PATH || "/",
   server: {
     host: "localhost",
     port: 5173,
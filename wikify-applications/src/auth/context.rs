@@ -7,6 +7,15 @@ use super::{Permission, PermissionMode, ResourceLimits, UserIdentity};
 // use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
+/// Metadata key holding the organization a request acts in
+const ORGANIZATION_METADATA_KEY: &str = "organization_id";
+
+/// Metadata key holding the client (IP address or fingerprint) a request came from
+const CLIENT_METADATA_KEY: &str = "client_id";
+
+/// Metadata key holding the ID of the request, for correlating logs
+const REQUEST_METADATA_KEY: &str = "request_id";
+
 /// Permission context encapsulates all authorization information for a request
 #[derive(Debug, Clone)]
 pub struct PermissionContext {
@@ -166,6 +175,36 @@ impl PermissionContext {
         self.metadata.get(key).map(|s| s.as_str())
     }
 
+    /// Act within an organization instead of the user's personal space
+    pub fn with_organization(self, organization_id: String) -> Self {
+        self.with_metadata(ORGANIZATION_METADATA_KEY.to_string(), organization_id)
+    }
+
+    /// Organization the request acts in, if any
+    pub fn organization_id(&self) -> Option<&str> {
+        self.get_metadata(ORGANIZATION_METADATA_KEY)
+    }
+
+    /// Tag the request with the client it came from
+    pub fn with_client(self, client_id: String) -> Self {
+        self.with_metadata(CLIENT_METADATA_KEY.to_string(), client_id)
+    }
+
+    /// Client the request came from, if known
+    pub fn client_id(&self) -> Option<&str> {
+        self.get_metadata(CLIENT_METADATA_KEY)
+    }
+
+    /// Tag the context with the ID of the request it was created for
+    pub fn with_request_id(self, request_id: String) -> Self {
+        self.with_metadata(REQUEST_METADATA_KEY.to_string(), request_id)
+    }
+
+    /// ID of the request, if it came from one
+    pub fn request_id(&self) -> Option<&str> {
+        self.get_metadata(REQUEST_METADATA_KEY)
+    }
+
     /// Create a summary string for logging
     pub fn summary(&self) -> String {
         let user_info = match &self.identity {
@@ -295,7 +334,11 @@ impl PermissionContext {
             let email = headers.get("x-user-email").cloned();
 
             let identity = UserIdentity::registered(user_id.clone(), display_name, email);
-            Self::user(identity)
+            let context = Self::user(identity);
+            match headers.get("x-organization-id") {
+                Some(organization_id) => context.with_organization(organization_id.clone()),
+                None => context,
+            }
         } else if headers.get("x-anonymous").is_some() {
             // Anonymous user with limited permissions
             Self::anonymous(
@@ -12,4 +12,6 @@ pub mod permissions;
 
 pub use context::PermissionContext;
 pub use identity::{UserIdentity, UserType};
-pub use permissions::{Permission, PermissionManager, PermissionMode, ResourceLimits};
+pub use permissions::{
+    AnonymousLimits, Permission, PermissionManager, PermissionMode, ResourceLimits,
+};
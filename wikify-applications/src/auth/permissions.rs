@@ -135,6 +135,28 @@ impl ResourceLimits {
     }
 }
 
+/// Limits for anonymous visitors in open mode, counted per client
+/// (IP address or browser fingerprint) rather than per user
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AnonymousLimits {
+    /// Maximum queries per hour
+    pub queries_per_hour: u32,
+    /// Maximum repositories a client may add
+    pub max_repositories: u32,
+    /// Maximum size of a repository a client may add, in MB
+    pub max_repository_size_mb: u64,
+}
+
+impl Default for AnonymousLimits {
+    fn default() -> Self {
+        Self {
+            queries_per_hour: 30,
+            max_repositories: 3,
+            max_repository_size_mb: 200,
+        }
+    }
+}
+
 /// Permission configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PermissionConfig {
@@ -152,6 +174,19 @@ pub struct PermissionConfig {
     pub registered_limits: ResourceLimits,
     /// Custom permission overrides
     pub custom_permissions: HashMap<String, HashSet<Permission>>,
+    /// How long permission decisions are cached, in seconds (0 disables caching)
+    #[serde(default = "default_decision_cache_ttl_secs")]
+    pub decision_cache_ttl_secs: u64,
+    /// Per-client limits for anonymous visitors in open mode (None disables them)
+    #[serde(default)]
+    pub anonymous_throttle: Option<AnonymousLimits>,
+}
+
+/// Default lifetime of cached permission decisions
+pub const DEFAULT_DECISION_CACHE_TTL_SECS: u64 = 30;
+
+fn default_decision_cache_ttl_secs() -> u64 {
+    DEFAULT_DECISION_CACHE_TTL_SECS
 }
 
 impl Default for PermissionConfig {
@@ -166,12 +201,17 @@ impl Default for PermissionConfig {
             anonymous_limits: ResourceLimits::anonymous(),
             registered_limits: ResourceLimits::default(),
             custom_permissions: HashMap::new(),
+            decision_cache_ttl_secs: DEFAULT_DECISION_CACHE_TTL_SECS,
+            anonymous_throttle: None,
         }
     }
 }
 
 impl PermissionConfig {
     /// Create open mode configuration
+    ///
+    /// Everyone gets full access, but anonymous visitors are throttled per
+    /// client by `anonymous_throttle`; set it to `None` to lift the limits.
     pub fn open() -> Self {
         Self {
             mode: PermissionMode::Open,
@@ -196,6 +236,8 @@ impl PermissionConfig {
             anonymous_limits: ResourceLimits::default(),
             registered_limits: ResourceLimits::unlimited(),
             custom_permissions: HashMap::new(),
+            decision_cache_ttl_secs: DEFAULT_DECISION_CACHE_TTL_SECS,
+            anonymous_throttle: Some(AnonymousLimits::default()),
         }
     }
 
@@ -211,6 +253,8 @@ impl PermissionConfig {
             anonymous_limits: ResourceLimits::anonymous(),
             registered_limits: ResourceLimits::default(),
             custom_permissions: HashMap::new(),
+            decision_cache_ttl_secs: DEFAULT_DECISION_CACHE_TTL_SECS,
+            anonymous_throttle: None,
         }
     }
 
@@ -242,6 +286,8 @@ impl PermissionConfig {
             anonymous_limits: ResourceLimits::unlimited(),
             registered_limits: ResourceLimits::unlimited(),
             custom_permissions: HashMap::new(),
+            decision_cache_ttl_secs: DEFAULT_DECISION_CACHE_TTL_SECS,
+            anonymous_throttle: None,
         }
     }
 }
@@ -251,6 +297,35 @@ pub struct PermissionManager {
     config: PermissionConfig,
     identity_provider: Option<Arc<dyn IdentityProvider>>,
     usage_tracker: Arc<RwLock<HashMap<String, UsageStats>>>,
+    /// Recent decisions keyed by user ID (None for anonymous users) and permission
+    decision_cache: RwLock<HashMap<(Option<String>, Permission), CachedDecision>>,
+    /// Anonymous usage keyed by client ID
+    anonymous_clients: RwLock<HashMap<String, AnonymousClientUsage>>,
+}
+
+/// What one anonymous client has used so far
+#[derive(Debug, Clone)]
+struct AnonymousClientUsage {
+    queries_this_hour: u32,
+    hour_started: chrono::DateTime<chrono::Utc>,
+    repositories: u32,
+}
+
+impl Default for AnonymousClientUsage {
+    fn default() -> Self {
+        Self {
+            queries_this_hour: 0,
+            hour_started: chrono::Utc::now(),
+            repositories: 0,
+        }
+    }
+}
+
+/// A permission decision and when it stops being valid
+#[derive(Debug, Clone, Copy)]
+struct CachedDecision {
+    allowed: bool,
+    expires_at: std::time::Instant,
 }
 
 /// Usage statistics for rate limiting
@@ -268,6 +343,8 @@ impl PermissionManager {
             config,
             identity_provider: None,
             usage_tracker: Arc::new(RwLock::new(HashMap::new())),
+            decision_cache: RwLock::new(HashMap::new()),
+            anonymous_clients: RwLock::new(HashMap::new()),
         }
     }
 
@@ -278,6 +355,10 @@ impl PermissionManager {
     }
 
     /// Check if a user has permission to perform an operation
+    ///
+    /// Decisions are cached per user and permission for
+    /// `decision_cache_ttl_secs`; call [`Self::invalidate_user`] when a
+    /// user's roles or permissions change.
     pub async fn check_permission(
         &self,
         context: &super::PermissionContext,
@@ -290,21 +371,61 @@ impl PermissionManager {
                 Ok(true)
             }
             PermissionMode::Restricted => {
-                // Restricted mode requires proper authentication and authorization
-                match &context.identity {
-                    Some(identity) => Ok(identity.has_permission(permission)),
-                    None => {
-                        if self.config.allow_anonymous {
-                            Ok(self.config.anonymous_permissions.contains(permission))
-                        } else {
-                            Ok(false)
-                        }
+                let key = (
+                    context.identity.as_ref().map(|i| i.user_id.clone()),
+                    permission.clone(),
+                );
+                let now = std::time::Instant::now();
+                if let Some(cached) = self.decision_cache.read().await.get(&key) {
+                    if cached.expires_at > now {
+                        return Ok(cached.allowed);
                     }
                 }
+
+                let allowed = self.decide(context, permission);
+                if self.config.decision_cache_ttl_secs > 0 {
+                    let expires_at =
+                        now + std::time::Duration::from_secs(self.config.decision_cache_ttl_secs);
+                    let mut cache = self.decision_cache.write().await;
+                    cache.retain(|_, cached| cached.expires_at > now);
+                    cache.insert(
+                        key,
+                        CachedDecision {
+                            allowed,
+                            expires_at,
+                        },
+                    );
+                }
+                Ok(allowed)
+            }
+        }
+    }
+
+    /// Decide a permission in restricted mode, which requires proper
+    /// authentication and authorization
+    fn decide(&self, context: &super::PermissionContext, permission: &Permission) -> bool {
+        match &context.identity {
+            Some(identity) => identity.has_permission(permission),
+            None => {
+                self.config.allow_anonymous
+                    && self.config.anonymous_permissions.contains(permission)
             }
         }
     }
 
+    /// Forget the cached decisions of a user, e.g. after a role change
+    pub async fn invalidate_user(&self, user_id: &str) {
+        self.decision_cache
+            .write()
+            .await
+            .retain(|(cached_user, _), _| cached_user.as_deref() != Some(user_id));
+    }
+
+    /// Forget all cached decisions
+    pub async fn invalidate_all(&self) {
+        self.decision_cache.write().await.clear();
+    }
+
     /// Check resource limits for a user
     pub async fn check_resource_limits(
         &self,
@@ -372,6 +493,81 @@ impl PermissionManager {
         }
     }
 
+    /// Limits and client key for a context that is throttled as anonymous
+    fn anonymous_throttle<'a>(
+        &'a self,
+        context: &super::PermissionContext,
+    ) -> Option<(&'a AnonymousLimits, String)> {
+        if self.config.mode != PermissionMode::Open {
+            return None;
+        }
+        let limits = self.config.anonymous_throttle.as_ref()?;
+        match context.client_id() {
+            Some(client_id) => Some((limits, client_id.to_string())),
+            // Visitors we cannot tell apart share one allowance
+            None if context.is_anonymous() => Some((limits, "anonymous".to_string())),
+            None => None,
+        }
+    }
+
+    /// Count a query against an anonymous client's hourly allowance,
+    /// failing if it is used up
+    pub async fn throttle_anonymous_query(
+        &self,
+        context: &super::PermissionContext,
+    ) -> Result<(), String> {
+        let Some((limits, client_id)) = self.anonymous_throttle(context) else {
+            return Ok(());
+        };
+
+        let mut clients = self.anonymous_clients.write().await;
+        let usage = clients.entry(client_id).or_default();
+        let now = chrono::Utc::now();
+        if now.signed_duration_since(usage.hour_started).num_hours() >= 1 {
+            usage.queries_this_hour = 0;
+            usage.hour_started = now;
+        }
+        if usage.queries_this_hour >= limits.queries_per_hour {
+            return Err(format!(
+                "Anonymous query limit of {} per hour reached; sign in or try again later",
+                limits.queries_per_hour
+            ));
+        }
+        usage.queries_this_hour += 1;
+        Ok(())
+    }
+
+    /// Check that an anonymous client may add another repository,
+    /// returning the largest repository size it may add in bytes
+    pub async fn check_anonymous_repository(
+        &self,
+        context: &super::PermissionContext,
+    ) -> Result<Option<u64>, String> {
+        let Some((limits, client_id)) = self.anonymous_throttle(context) else {
+            return Ok(None);
+        };
+
+        let clients = self.anonymous_clients.read().await;
+        let added = clients
+            .get(&client_id)
+            .map_or(0, |usage| usage.repositories);
+        if added >= limits.max_repositories {
+            return Err(format!(
+                "Anonymous clients may add at most {} repositories; sign in to add more",
+                limits.max_repositories
+            ));
+        }
+        Ok(Some(limits.max_repository_size_mb * 1024 * 1024))
+    }
+
+    /// Count a repository added by an anonymous client
+    pub async fn record_anonymous_repository(&self, context: &super::PermissionContext) {
+        if let Some((_, client_id)) = self.anonymous_throttle(context) {
+            let mut clients = self.anonymous_clients.write().await;
+            clients.entry(client_id).or_default().repositories += 1;
+        }
+    }
+
     /// Get current usage stats for a user
     pub async fn get_usage_stats(&self, user_id: &str) -> Option<UsageStats> {
         let usage_tracker = self.usage_tracker.read().await;
@@ -385,3 +581,87 @@ pub enum ResourceType {
     Query,
     Session,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{PermissionContext, UserIdentity};
+
+    #[tokio::test]
+    async fn test_decisions_are_cached_until_invalidated() {
+        let manager = PermissionManager::new(PermissionConfig::restricted());
+        let context_of = |identity: &UserIdentity| {
+            PermissionContext::new(
+                Some(identity.clone()),
+                PermissionMode::Restricted,
+                identity.effective_permissions(),
+                identity.effective_limits(),
+            )
+        };
+        let mut identity = UserIdentity::registered("alice".to_string(), None, None);
+        assert!(!manager
+            .check_permission(&context_of(&identity), &Permission::Admin)
+            .await
+            .unwrap());
+
+        // A role change is not seen until the user's decisions are invalidated
+        identity.add_permission(Permission::Admin);
+        assert!(!manager
+            .check_permission(&context_of(&identity), &Permission::Admin)
+            .await
+            .unwrap());
+
+        manager.invalidate_user("alice").await;
+        assert!(manager
+            .check_permission(&context_of(&identity), &Permission::Admin)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_clients_are_throttled_separately() {
+        let mut config = PermissionConfig::open();
+        config.anonymous_throttle = Some(AnonymousLimits {
+            queries_per_hour: 2,
+            max_repositories: 1,
+            max_repository_size_mb: 1,
+        });
+        let manager = PermissionManager::new(config);
+        let client = |id: &str| {
+            PermissionContext::anonymous(HashSet::new(), ResourceLimits::default())
+                .with_client(id.to_string())
+        };
+
+        assert!(manager
+            .throttle_anonymous_query(&client("1.2.3.4"))
+            .await
+            .is_ok());
+        assert!(manager
+            .throttle_anonymous_query(&client("1.2.3.4"))
+            .await
+            .is_ok());
+        assert!(manager
+            .throttle_anonymous_query(&client("1.2.3.4"))
+            .await
+            .is_err());
+        assert!(manager
+            .throttle_anonymous_query(&client("5.6.7.8"))
+            .await
+            .is_ok());
+
+        assert_eq!(
+            manager
+                .check_anonymous_repository(&client("1.2.3.4"))
+                .await
+                .unwrap(),
+            Some(1024 * 1024)
+        );
+        manager
+            .record_anonymous_repository(&client("1.2.3.4"))
+            .await;
+        assert!(manager
+            .check_anonymous_repository(&client("1.2.3.4"))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,433 @@
+//! Storage garbage collection
+//!
+//! Deleting a repository removes its clone and vector index right away,
+//! but artifacts are still left behind by deletions that failed halfway,
+//! by clones made for one-off questions and by caches nobody reads any
+//! more. The storage GC sweeps them on a schedule:
+//!
+//! - clones that no registered repository uses,
+//! - cached wikis of deleted repositories, and cached wikis older than the
+//!   retention period,
+//! - vector indexes of deleted repositories.
+//!
+//! Anything modified within the grace period is kept, so the artifacts of a
+//! repository that is being added right now are not mistaken for orphans.
+
+use crate::repository::{RepositoryManager, StorageLocations};
+use crate::{ApplicationError, ApplicationResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+/// Time between storage GC runs by default
+pub const DEFAULT_STORAGE_GC_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// Artifacts modified more recently than this are kept by default
+pub const DEFAULT_STORAGE_GC_GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+/// Cached wikis are kept this long by default
+pub const DEFAULT_WIKI_CACHE_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Schedule and retention of the storage GC
+#[derive(Debug, Clone)]
+pub struct StorageGcConfig {
+    /// Time between runs
+    pub interval: Duration,
+    /// Artifacts modified more recently than this are never removed
+    pub grace_period: Duration,
+    /// Remove cached wikis older than this, even of existing repositories
+    /// (None = keep them while the repository exists)
+    pub wiki_cache_retention: Option<Duration>,
+    /// Only report what would be removed
+    pub dry_run: bool,
+}
+
+impl Default for StorageGcConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_STORAGE_GC_INTERVAL,
+            grace_period: DEFAULT_STORAGE_GC_GRACE_PERIOD,
+            wiki_cache_retention: Some(DEFAULT_WIKI_CACHE_RETENTION),
+            dry_run: false,
+        }
+    }
+}
+
+/// What the sweep of one kind of artifact removed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StorageSweep {
+    /// Number of artifacts inspected
+    pub scanned: usize,
+    /// Paths that were removed (or would be, for a dry run)
+    pub removed: Vec<String>,
+    /// Bytes freed by the removals
+    pub freed_bytes: u64,
+}
+
+impl StorageSweep {
+    fn record(&mut self, path: &Path, size: u64) {
+        self.removed.push(path.display().to_string());
+        self.freed_bytes += size;
+    }
+}
+
+/// Result of a storage GC run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StorageGcReport {
+    /// Clones no registered repository uses
+    pub clones: StorageSweep,
+    /// Cached wikis of deleted repositories or past the retention period
+    pub wiki_cache: StorageSweep,
+    /// Vector indexes of deleted repositories
+    pub vector_indexes: StorageSweep,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+}
+
+impl StorageGcReport {
+    /// Bytes freed by all sweeps
+    pub fn freed_bytes(&self) -> u64 {
+        self.clones.freed_bytes + self.wiki_cache.freed_bytes + self.vector_indexes.freed_bytes
+    }
+
+    /// Number of artifacts removed by all sweeps
+    pub fn removed(&self) -> usize {
+        self.clones.removed.len()
+            + self.wiki_cache.removed.len()
+            + self.vector_indexes.removed.len()
+    }
+}
+
+/// Removes storage artifacts of repositories that no longer exist
+pub struct StorageGc {
+    repositories: Arc<RepositoryManager>,
+    locations: Arc<StorageLocations>,
+    config: StorageGcConfig,
+}
+
+impl StorageGc {
+    pub fn new(
+        repositories: Arc<RepositoryManager>,
+        locations: Arc<StorageLocations>,
+        config: StorageGcConfig,
+    ) -> Self {
+        Self {
+            repositories,
+            locations,
+            config,
+        }
+    }
+
+    /// Run the GC every interval until the task is dropped
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            info!(
+                "Collecting storage garbage every {:?} (grace period {:?}, dry run: {})",
+                self.config.interval, self.config.grace_period, self.config.dry_run
+            );
+            let mut interval = tokio::time::interval(self.config.interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.run(self.config.dry_run).await {
+                    warn!("Storage garbage collection failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Sweep clones, cached wikis and vector indexes once
+    pub async fn run(&self, dry_run: bool) -> ApplicationResult<StorageGcReport> {
+        let locations = &self.locations;
+        let mut keys = Vec::new();
+        let mut clones = HashSet::new();
+        for repository in self.repositories.list_all_repositories().await? {
+            clones.extend(locations.clone_paths(&repository.url));
+            keys.extend(locations.artifact_keys(&repository.url));
+        }
+        #[cfg(feature = "wiki")]
+        let wiki_dirs: HashSet<PathBuf> = keys
+            .iter()
+            .map(|key| locations.wiki_cache.entries_dir(key))
+            .collect();
+        let vector_dirs: HashSet<PathBuf> = keys
+            .iter()
+            .map(|key| {
+                wikify_rag::PersistentVectorStore::storage_dir(&locations.vector_storage, key)
+            })
+            .collect();
+
+        let workspaces = locations.workspaces.clone();
+        #[cfg(feature = "wiki")]
+        let wiki_cache_dir = locations.wiki_cache.cache_dir().to_path_buf();
+        let vector_dir = locations.vector_storage.base_dir.clone();
+        let grace_period = self.config.grace_period;
+        #[cfg(feature = "wiki")]
+        let wiki_cache_retention = self.config.wiki_cache_retention;
+
+        let report = tokio::task::spawn_blocking(move || {
+            let now = SystemTime::now();
+            let mut report = StorageGcReport {
+                dry_run,
+                ..Default::default()
+            };
+
+            for workspace in &workspaces {
+                sweep_clones(
+                    workspace,
+                    &clones,
+                    grace_period,
+                    now,
+                    dry_run,
+                    &mut report.clones,
+                );
+            }
+            #[cfg(feature = "wiki")]
+            {
+                sweep_directories(
+                    &wiki_cache_dir,
+                    "wiki_",
+                    &wiki_dirs,
+                    grace_period,
+                    now,
+                    dry_run,
+                    &mut report.wiki_cache,
+                );
+                if let Some(retention) = wiki_cache_retention {
+                    sweep_stale_wikis(
+                        &wiki_cache_dir,
+                        retention,
+                        now,
+                        dry_run,
+                        &mut report.wiki_cache,
+                    );
+                }
+            }
+            sweep_directories(
+                &vector_dir,
+                "repo_",
+                &vector_dirs,
+                grace_period,
+                now,
+                dry_run,
+                &mut report.vector_indexes,
+            );
+
+            report
+        })
+        .await
+        .map_err(|e| ApplicationError::internal(format!("Storage GC panicked: {}", e)))?;
+
+        info!(
+            removed = report.removed(),
+            freed_bytes = report.freed_bytes(),
+            clones = report.clones.removed.len(),
+            wiki_cache_entries = report.wiki_cache.removed.len(),
+            vector_indexes = report.vector_indexes.removed.len(),
+            dry_run = dry_run,
+            "🧹 Storage garbage collection finished"
+        );
+
+        Ok(report)
+    }
+}
+
+/// Whether a path was last modified before the grace period
+fn is_older_than(modified: SystemTime, age: Duration, now: SystemTime) -> bool {
+    now.duration_since(modified)
+        .map(|elapsed| elapsed > age)
+        .unwrap_or(false)
+}
+
+/// Latest modification of a directory or the files directly inside it
+fn last_modified(dir: &Path) -> SystemTime {
+    let own = std::fs::metadata(dir)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.metadata().ok()?.modified().ok())
+        .fold(own, SystemTime::max)
+}
+
+/// Remove clones of a workspace that are neither kept nor recently used
+fn sweep_clones(
+    workspace: &wikify_repo::CloneWorkspace,
+    keep: &HashSet<PathBuf>,
+    grace_period: Duration,
+    now: SystemTime,
+    dry_run: bool,
+    sweep: &mut StorageSweep,
+) {
+    let entries = match workspace.entries() {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!(path = %workspace.root().display(), error = %e, "Failed to list clones");
+            return;
+        }
+    };
+    sweep.scanned += entries.len();
+
+    for entry in entries {
+        if keep.contains(&entry.path) || !is_older_than(entry.last_used, grace_period, now) {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = workspace.remove(&entry.path) {
+                warn!(path = %entry.path.display(), error = %e, "Failed to remove clone");
+                continue;
+            }
+        }
+        sweep.record(&entry.path, entry.size_bytes);
+    }
+}
+
+/// Remove the directories named `{prefix}...` below `root` that are neither
+/// kept nor recently modified
+fn sweep_directories(
+    root: &Path,
+    prefix: &str,
+    keep: &HashSet<PathBuf>,
+    grace_period: Duration,
+    now: SystemTime,
+    dry_run: bool,
+    sweep: &mut StorageSweep,
+) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        let matches = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(prefix));
+        if !matches || !path.is_dir() {
+            continue;
+        }
+        sweep.scanned += 1;
+        if keep.contains(&path) || !is_older_than(last_modified(&path), grace_period, now) {
+            continue;
+        }
+
+        let size = wikify_repo::directory_size(&path);
+        if !dry_run {
+            if let Err(e) = std::fs::remove_dir_all(&path) {
+                warn!(path = %path.display(), error = %e, "Failed to remove orphaned storage");
+                continue;
+            }
+        }
+        sweep.record(&path, size);
+    }
+}
+
+/// Remove cached wikis older than the retention period
+#[cfg(feature = "wiki")]
+fn sweep_stale_wikis(
+    cache_dir: &Path,
+    retention: Duration,
+    now: SystemTime,
+    dry_run: bool,
+    sweep: &mut StorageSweep,
+) {
+    let Ok(entries) = std::fs::read_dir(cache_dir) else {
+        return;
+    };
+    let entry_dirs = entries.filter_map(|entry| entry.ok()).filter(|entry| {
+        entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with("wiki_"))
+            && entry.path().is_dir()
+    });
+
+    for dir in entry_dirs {
+        let Ok(files) = std::fs::read_dir(dir.path()) else {
+            continue;
+        };
+        for file in files.filter_map(|file| file.ok()) {
+            let path = file.path();
+            let Ok(metadata) = file.metadata() else {
+                continue;
+            };
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            let modified = metadata.modified().unwrap_or(now);
+            if !is_older_than(modified, retention, now) {
+                continue;
+            }
+            if !dry_run {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    warn!(path = %path.display(), error = %e, "Failed to remove cached wiki");
+                    continue;
+                }
+            }
+            sweep.record(&path, metadata.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn test_orphaned_directories_are_removed_after_the_grace_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("repo_kept");
+        let orphan = dir.path().join("repo_orphan");
+        let unrelated = dir.path().join("sessions");
+        for path in [&kept, &orphan, &unrelated] {
+            std::fs::create_dir_all(path).unwrap();
+            std::fs::write(path.join("vectors.json"), "[]").unwrap();
+        }
+        let keep = HashSet::from([kept.clone()]);
+
+        // Just written, so within the grace period
+        let mut sweep = StorageSweep::default();
+        let now = SystemTime::now();
+        sweep_directories(dir.path(), "repo_", &keep, DAY, now, false, &mut sweep);
+        assert_eq!(sweep.scanned, 2);
+        assert!(sweep.removed.is_empty());
+
+        let mut sweep = StorageSweep::default();
+        let later = now + 2 * DAY;
+        sweep_directories(dir.path(), "repo_", &keep, DAY, later, true, &mut sweep);
+        assert_eq!(sweep.removed, vec![orphan.display().to_string()]);
+        assert_eq!(sweep.freed_bytes, 2);
+        assert!(orphan.exists(), "a dry run removes nothing");
+
+        let mut sweep = StorageSweep::default();
+        sweep_directories(dir.path(), "repo_", &keep, DAY, later, false, &mut sweep);
+        assert!(!orphan.exists());
+        assert!(kept.exists());
+        assert!(unrelated.exists());
+    }
+
+    #[test]
+    #[cfg(feature = "wiki")]
+    fn test_stale_wikis_are_removed_after_the_retention_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = dir.path().join("wiki_abc");
+        std::fs::create_dir_all(&entries).unwrap();
+        std::fs::write(entries.join("deadbeef-1234.json"), "{}").unwrap();
+        std::fs::create_dir_all(dir.path().join("versions").join("wiki_abc")).unwrap();
+
+        let mut sweep = StorageSweep::default();
+        let now = SystemTime::now();
+        sweep_stale_wikis(dir.path(), 30 * DAY, now, false, &mut sweep);
+        assert!(sweep.removed.is_empty());
+
+        sweep_stale_wikis(dir.path(), 30 * DAY, now + 31 * DAY, false, &mut sweep);
+        assert_eq!(sweep.removed.len(), 1);
+        assert!(!entries.join("deadbeef-1234.json").exists());
+        assert!(dir.path().join("versions").exists());
+    }
+}
@@ -0,0 +1,117 @@
+//! Readiness checks of the services the application depends on
+//!
+//! Each check reports whether its dependency is usable, so orchestrators
+//! can keep traffic away from an instance until all of them are.
+
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use wikify_rag::LlmConfig;
+
+/// How long a single dependency may take to answer
+pub const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of checking one dependency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DependencyCheck {
+    /// Dependency name, such as `indexing_worker` or `llm_provider`
+    pub name: String,
+    pub healthy: bool,
+    /// Time the check took in milliseconds
+    pub latency_ms: u64,
+    /// Why the dependency is unhealthy, or details about it when healthy
+    pub message: Option<String>,
+}
+
+impl DependencyCheck {
+    /// Run a check, failing it when it takes longer than
+    /// [`DEPENDENCY_CHECK_TIMEOUT`]
+    pub async fn run<F>(name: &str, check: F) -> Self
+    where
+        F: Future<Output = Result<Option<String>, String>>,
+    {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, check).await {
+            Ok(result) => result,
+            Err(_) => Err(format!(
+                "No answer within {} seconds",
+                DEPENDENCY_CHECK_TIMEOUT.as_secs()
+            )),
+        };
+        let (healthy, message) = match result {
+            Ok(details) => (true, details),
+            Err(e) => (false, Some(e)),
+        };
+        Self {
+            name: name.to_string(),
+            healthy,
+            latency_ms: started.elapsed().as_millis() as u64,
+            message,
+        }
+    }
+}
+
+/// Whether every dependency is healthy
+pub fn all_healthy(checks: &[DependencyCheck]) -> bool {
+    checks.iter().all(|check| check.healthy)
+}
+
+/// Check that the LLM provider can be reached
+///
+/// Any HTTP response counts, so the check spends no tokens and does not
+/// need valid credentials.
+pub async fn check_llm_provider(config: &LlmConfig) -> Result<Option<String>, String> {
+    let Some(url) = provider_url(config) else {
+        return Ok(Some(format!(
+            "Provider {} is not checked over the network",
+            config.provider
+        )));
+    };
+
+    let response = reqwest::Client::new()
+        .get(&url)
+        .timeout(DEPENDENCY_CHECK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| format!("Cannot reach {} at {}: {}", config.provider, url, e))?;
+    Ok(Some(format!(
+        "{} answered with {}",
+        config.provider,
+        response.status()
+    )))
+}
+
+/// Address of the LLM provider API
+fn provider_url(config: &LlmConfig) -> Option<String> {
+    if let Some(ref base_url) = config.base_url {
+        return Some(base_url.clone());
+    }
+    let url = match config.provider.as_str() {
+        "openai" => "https://api.openai.com/v1",
+        "anthropic" => "https://api.anthropic.com",
+        "ollama" => "http://localhost:11434",
+        "groq" => "https://api.groq.com/openai/v1",
+        _ => return None,
+    };
+    Some(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dependency_check() {
+        let healthy = DependencyCheck::run("storage", async { Ok(None) }).await;
+        assert!(healthy.healthy);
+
+        let failed = DependencyCheck::run("llm_provider", async {
+            Err("connection refused".to_string())
+        })
+        .await;
+        assert!(!failed.healthy);
+        assert_eq!(failed.message.as_deref(), Some("connection refused"));
+        assert!(!all_healthy(&[healthy, failed]));
+    }
+}
@@ -6,6 +6,7 @@
 //! - Interactive chat systems with session management
 //! - Deep research engines with multi-turn investigation
 //! - Workshop and tutorial generation
+//! - Wiki generation with persisted results
 //! - Advanced code analysis applications
 //!
 //! ## Architecture
@@ -16,22 +17,72 @@
 //! - **Presentation** (wikify-web/cli): User interfaces
 
 pub mod auth;
+pub mod gc;
+pub mod health;
+pub mod organization;
+pub mod pagination;
 pub mod repository;
+#[cfg(feature = "research")]
 pub mod research;
+pub mod session;
+pub mod stats;
+pub mod telemetry;
+pub mod usage;
+pub mod webhook;
+#[cfg(feature = "wiki")]
+pub mod wiki;
 
 pub use auth::{
-    Permission, PermissionContext, PermissionManager, PermissionMode, UserIdentity, UserType,
+    AnonymousLimits, Permission, PermissionContext, PermissionManager, PermissionMode,
+    UserIdentity, UserType,
 };
+pub use gc::{StorageGc, StorageGcConfig, StorageGcReport, StorageSweep};
+pub use health::DependencyCheck;
+pub use organization::{
+    FileOrganizationStorage, OrgMember, OrgRole, Organization, OrganizationStorage, TenantScope,
+};
+pub use pagination::{Page, PageRequest, SortOrder};
+#[cfg(feature = "postgres")]
+pub use repository::PostgresRepositoryStorage;
 pub use repository::{
-    IndexingStatus, IndexingUpdate as RepositoryIndexingUpdate, RepositoryAccessMode,
-    RepositoryIndex, RepositoryManager, RepositoryOptions, RepositoryQuery,
-    RepositoryQueryResponse,
+    CodeSearchResult, IndexingJob, IndexingStatus, IndexingUpdate as RepositoryIndexingUpdate,
+    JobPriority, JobState, QueryChunkType, QueryStreamChunk, RepositoryAccessMode,
+    RepositoryDiskUsage, RepositoryFilter, RepositoryIndex, RepositoryManager,
+    RepositoryManagerConfig, RepositoryOptions, RepositoryQuery, RepositoryQueryResponse,
+    RepositorySortField, RepositoryVisibility, ShareGrant, SourceCitation, StorageLocations,
 };
+#[cfg(feature = "research")]
 pub use research::{
-    FileResearchHistoryStorage, QuestionType, ResearchCategory, ResearchConfig, ResearchEngine,
-    ResearchHistoryStorage, ResearchProgress, ResearchQuestion, ResearchResult, ResearchTemplate,
-    ResearchTemplateManager,
+    FileResearchHistoryStorage, FileResearchTemplateStorage, QuestionType, ResearchCategory,
+    ResearchConfig, ResearchEngine, ResearchHistoryStorage, ResearchProgress, ResearchQuestion,
+    ResearchResult, ResearchTemplate, ResearchTemplateManager,
+};
+#[cfg(feature = "sqlite")]
+pub use session::SqliteSessionStorage;
+pub use session::{
+    ChatSessionRecord, ChatSessionSummary, MemorySessionStorage, RepositoryAssociation,
+    SessionStorage,
+};
+pub use stats::{
+    IndexStats, OperationStats, RepositoryIndexSize, RepositoryOperationStats, RepositoryStats,
+    RepositoryStatusCounts, RepositoryStorageUsage, StorageStats, StorageUsageReport, SystemStats,
+};
+pub use telemetry::{Telemetry, TelemetryEvent};
+#[cfg(feature = "sqlite")]
+pub use usage::SqliteUsageStorage;
+pub use usage::{
+    BudgetWarning, MemoryUsageStorage, UsageAggregate, UsageConfig, UsageExportConfig,
+    UsageExportFormat, UsageExportTarget, UsageExporter, UsageFilter, UsageGroupBy, UsageKind,
+    UsageRecord, UsageReport, UsageStorage, UsageTracker,
 };
+#[cfg(feature = "sqlite")]
+pub use webhook::SqliteWebhookStorage;
+pub use webhook::{
+    MemoryWebhookStorage, NewWebhook, Webhook, WebhookConfig, WebhookDelivery, WebhookDispatcher,
+    WebhookEvent, WebhookEventKind, WebhookStorage, WebhookUpdate,
+};
+#[cfg(feature = "wiki")]
+pub use wiki::{ExportedWiki, FileWikiExportStore, FileWikiStorage, WikiExportJob};
 
 /// Application-level error type
 #[derive(Debug, thiserror::Error)]
@@ -54,6 +105,9 @@ pub enum ApplicationError {
     #[error("Configuration error: {message}")]
     Config { message: String },
 
+    #[error("Rate limited: {message}")]
+    RateLimited { message: String },
+
     #[error("Not found: {message}")]
     NotFound { message: String },
 
@@ -87,6 +141,13 @@ impl ApplicationError {
         }
     }
 
+    /// Create a rate limit error
+    pub fn rate_limited<S: Into<String>>(message: S) -> Self {
+        Self::RateLimited {
+            message: message.into(),
+        }
+    }
+
     /// Create a research error
     pub fn research<S: Into<String>>(message: S) -> Self {
         Self::Research {
@@ -145,6 +206,12 @@ pub struct ApplicationConfig {
     pub rag: wikify_rag::RagConfig,
     /// Storage configuration
     pub storage: StorageConfig,
+    /// Repository manager configuration, including indexing concurrency
+    pub repository: RepositoryManagerConfig,
+    /// Model prices and daily budgets for usage tracking
+    pub usage: UsageConfig,
+    /// Retries and timeouts of webhook deliveries
+    pub webhooks: WebhookConfig,
 }
 
 impl Default for ApplicationConfig {
@@ -153,6 +220,9 @@ impl Default for ApplicationConfig {
             permissions: auth::permissions::PermissionConfig::default(),
             rag: wikify_rag::RagConfig::default(),
             storage: StorageConfig::default(),
+            repository: RepositoryManagerConfig::default(),
+            usage: UsageConfig::default(),
+            webhooks: WebhookConfig::default(),
         }
     }
 }
@@ -164,6 +234,9 @@ impl ApplicationConfig {
             permissions,
             rag: wikify_rag::RagConfig::default(),
             storage: StorageConfig::default(),
+            repository: RepositoryManagerConfig::default(),
+            usage: UsageConfig::default(),
+            webhooks: WebhookConfig::default(),
         }
     }
 
@@ -183,7 +256,72 @@ impl ApplicationConfig {
             permissions: auth::permissions::PermissionConfig::local(),
             rag: wikify_rag::RagConfig::default(),
             storage: StorageConfig::local(), // CLI uses local storage
+            repository: RepositoryManagerConfig::default(),
+            usage: UsageConfig::default(),
+            webhooks: WebhookConfig::default(),
+        }
+    }
+
+    /// The settings of this configuration, as a configuration file
+    /// (`wikify_core::WikifyConfig`) describes them
+    ///
+    /// Used as the defaults a configuration file is read over, so keys the
+    /// file doesn't set keep the application's behavior.
+    pub fn settings(&self) -> wikify_core::WikifyConfig {
+        let rag = &self.rag;
+        let mut settings = wikify_core::WikifyConfig::default();
+
+        settings.llm.provider = rag.llm.provider.clone();
+        settings.llm.model = rag.llm.model.clone();
+        settings.llm.temperature = rag.llm.temperature;
+        if let Some(max_tokens) = rag.llm.max_tokens {
+            settings.llm.max_tokens = max_tokens as usize;
         }
+
+        settings.embedding.provider = rag.embeddings.provider.clone();
+        settings.embedding.model = rag.embeddings.model.clone();
+        settings.embedding.dimensions = rag.embeddings.dimension;
+        settings.embedding.batch_size = rag.embeddings.batch_size;
+        settings.embedding.max_concurrent_requests = rag.embeddings.max_concurrent_requests;
+
+        settings.rag.top_k = rag.retrieval.top_k;
+        settings.rag.similarity_threshold = rag.retrieval.similarity_threshold;
+        settings.rag.max_context_length = rag.retrieval.max_context_length;
+        settings.rag.enable_reranking = rag.retrieval.enable_reranking;
+
+        settings.storage.data_dir = self.storage.base_dir.display().to_string();
+        settings
+    }
+
+    /// Use the LLM, embedding, retrieval and storage settings of a
+    /// configuration file
+    pub fn apply_settings(&mut self, settings: &wikify_core::WikifyConfig) {
+        let rag = &mut self.rag;
+        rag.llm.provider = settings.llm.provider.clone();
+        rag.llm.model = settings.llm.model.clone();
+        rag.llm.temperature = settings.llm.temperature;
+        rag.llm.max_tokens = Some(settings.llm.max_tokens.min(u32::MAX as usize) as u32);
+
+        rag.embeddings.provider = settings.embedding.provider.clone();
+        rag.embeddings.model = settings.embedding.model.clone();
+        rag.embeddings.dimension = settings.embedding.dimensions;
+        rag.embeddings.batch_size = settings.embedding.batch_size;
+        rag.embeddings.max_concurrent_requests = settings.embedding.max_concurrent_requests;
+
+        rag.retrieval.top_k = settings.rag.top_k;
+        rag.retrieval.similarity_threshold = settings.rag.similarity_threshold;
+        rag.retrieval.max_context_length = settings.rag.max_context_length;
+        rag.retrieval.enable_reranking = settings.rag.enable_reranking;
+
+        self.storage.base_dir = expand_home(&settings.storage.data_dir);
+    }
+}
+
+/// Expand a leading `~` to the home directory
+fn expand_home(path: &str) -> std::path::PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => std::path::PathBuf::from(path),
     }
 }
 
@@ -226,11 +364,37 @@ pub struct WikifyApplication {
     /// Repository manager (new architecture)
     repository_manager: std::sync::Arc<RepositoryManager>,
     /// Research engine
+    #[cfg(feature = "research")]
     research_engine: Option<ResearchEngine>,
-    /// Research template manager
-    template_manager: ResearchTemplateManager,
+    /// Research template manager, with built-in and user-defined templates
+    #[cfg(feature = "research")]
+    template_manager: tokio::sync::RwLock<ResearchTemplateManager>,
+    /// Storage for user-defined research templates
+    #[cfg(feature = "research")]
+    template_storage: FileResearchTemplateStorage,
     /// Research history storage
+    #[cfg(feature = "research")]
     history_storage: Option<FileResearchHistoryStorage>,
+    /// Generated wiki storage
+    #[cfg(feature = "wiki")]
+    wiki_storage: FileWikiStorage,
+    /// Wiki exports waiting to be downloaded
+    #[cfg(feature = "wiki")]
+    wiki_exports: FileWikiExportStore,
+    /// Organizations and their members
+    organization_storage: FileOrganizationStorage,
+    /// Chat sessions, user identities and the repositories users worked with
+    session_storage: std::sync::Arc<dyn SessionStorage>,
+    /// Token usage and estimated cost of model calls
+    usage_tracker: std::sync::Arc<UsageTracker>,
+    /// Registered webhooks and their delivery
+    webhooks: WebhookDispatcher,
+    /// Archived wiki versions, keyed by repository ID
+    #[cfg(feature = "wiki")]
+    wiki_versions: wikify_wiki::WikiCache,
+    /// Generation cache of the wiki service, keyed by repository URL
+    #[cfg(feature = "wiki")]
+    wiki_cache: wikify_wiki::WikiCache,
     /// Application configuration
     config: ApplicationConfig,
 }
@@ -238,10 +402,20 @@ pub struct WikifyApplication {
 /// Builder for WikifyApplication to simplify initialization
 pub struct WikifyApplicationBuilder {
     config: ApplicationConfig,
+    #[cfg(feature = "research")]
     enable_research: bool,
+    #[cfg(feature = "research")]
     enable_history: bool,
+    #[cfg(feature = "research")]
     custom_history_dir: Option<std::path::PathBuf>,
+    #[cfg(feature = "research")]
     custom_research_config: Option<ResearchConfig>,
+    session_storage: Option<std::sync::Arc<dyn SessionStorage>>,
+    usage_storage: Option<std::sync::Arc<dyn UsageStorage>>,
+    usage_exports: Vec<UsageExportConfig>,
+    storage_gc: Option<StorageGcConfig>,
+    webhook_storage: Option<std::sync::Arc<dyn WebhookStorage>>,
+    repository_storage: Option<std::sync::Arc<dyn repository::RepositoryStorage>>,
 }
 
 impl WikifyApplicationBuilder {
@@ -249,50 +423,123 @@ impl WikifyApplicationBuilder {
     pub fn new(config: ApplicationConfig) -> Self {
         Self {
             config,
+            #[cfg(feature = "research")]
             enable_research: true,
+            #[cfg(feature = "research")]
             enable_history: true,
+            #[cfg(feature = "research")]
             custom_history_dir: None,
+            #[cfg(feature = "research")]
             custom_research_config: None,
+            session_storage: None,
+            usage_storage: None,
+            usage_exports: Vec::new(),
+            storage_gc: None,
+            webhook_storage: None,
+            repository_storage: None,
         }
     }
 
     /// Enable or disable research engine
+    #[cfg(feature = "research")]
     pub fn with_research(mut self, enable: bool) -> Self {
         self.enable_research = enable;
         self
     }
 
     /// Enable or disable history storage
+    #[cfg(feature = "research")]
     pub fn with_history(mut self, enable: bool) -> Self {
         self.enable_history = enable;
         self
     }
 
     /// Set custom history directory
+    #[cfg(feature = "research")]
     pub fn with_history_dir<P: Into<std::path::PathBuf>>(mut self, dir: P) -> Self {
         self.custom_history_dir = Some(dir.into());
         self
     }
 
     /// Set custom research configuration
+    #[cfg(feature = "research")]
     pub fn with_research_config(mut self, config: ResearchConfig) -> Self {
         self.custom_research_config = Some(config);
         self
     }
 
+    /// Set the chat session storage (sessions are kept in memory by default)
+    pub fn with_session_storage(mut self, storage: std::sync::Arc<dyn SessionStorage>) -> Self {
+        self.session_storage = Some(storage);
+        self
+    }
+
+    /// Set the usage storage (usage is kept in memory by default)
+    pub fn with_usage_storage(mut self, storage: std::sync::Arc<dyn UsageStorage>) -> Self {
+        self.usage_storage = Some(storage);
+        self
+    }
+
+    /// Export usage records on a schedule; may be called once per target
+    pub fn with_usage_export(mut self, export: UsageExportConfig) -> Self {
+        self.usage_exports.push(export);
+        self
+    }
+
+    /// Remove orphaned clones, stale wiki cache entries and vector indexes
+    /// of deleted repositories on a schedule
+    pub fn with_storage_gc(mut self, config: StorageGcConfig) -> Self {
+        self.storage_gc = Some(config);
+        self
+    }
+
+    /// Set the webhook storage (webhooks are kept in memory by default)
+    pub fn with_webhook_storage(mut self, storage: std::sync::Arc<dyn WebhookStorage>) -> Self {
+        self.webhook_storage = Some(storage);
+        self
+    }
+
+    /// Set the repository storage (repositories are kept in memory by default)
+    pub fn with_repository_storage(
+        mut self,
+        storage: std::sync::Arc<dyn repository::RepositoryStorage>,
+    ) -> Self {
+        self.repository_storage = Some(storage);
+        self
+    }
+
     /// Build the WikifyApplication
     pub async fn build(self) -> ApplicationResult<WikifyApplication> {
         // Create permission manager
         let permission_manager = auth::PermissionManager::new(self.config.permissions.clone());
 
         // Create repository manager
-        let repository_manager =
-            std::sync::Arc::new(RepositoryManager::new(self.config.rag.clone()));
+        let repository_manager = std::sync::Arc::new(RepositoryManager::with_storage(
+            self.config.rag.clone(),
+            self.repository_storage
+                .unwrap_or_else(|| std::sync::Arc::new(repository::MemoryRepositoryStorage::new())),
+            self.config.repository.clone(),
+        ));
 
         // Initialize the global RAG pipeline
         repository_manager.initialize().await?;
 
+        let usage_storage = self
+            .usage_storage
+            .unwrap_or_else(|| std::sync::Arc::new(MemoryUsageStorage::new()));
+        for export in self.usage_exports {
+            let cursor_path = WikifyApplication::base_path()
+                .join(format!("usage-export-{}.cursor", export.target.kind()));
+            UsageExporter::new(usage_storage.clone(), export, cursor_path).spawn();
+        }
+        let usage_tracker =
+            std::sync::Arc::new(UsageTracker::new(usage_storage, self.config.usage.clone()));
+        repository_manager
+            .set_usage_tracker(usage_tracker.clone())
+            .await;
+
         // Create research engine if enabled
+        #[cfg(feature = "research")]
         let research_engine = if self.enable_research {
             let _research_config = self.custom_research_config.unwrap_or_default();
             Some(ResearchEngine::new(repository_manager.clone()))
@@ -300,27 +547,101 @@ impl WikifyApplicationBuilder {
             None
         };
 
-        // Create template manager
-        let template_manager = ResearchTemplateManager::default();
+        // Create template manager with the user-defined templates
+        #[cfg(feature = "research")]
+        let (template_manager, template_storage) = {
+            let mut template_manager = ResearchTemplateManager::default();
+            let template_storage = FileResearchTemplateStorage::new(
+                WikifyApplication::base_path().join("research_templates.json"),
+            );
+            let custom_templates = template_storage.load().await.unwrap_or_else(|e| {
+                tracing::warn!("Failed to load custom research templates: {}", e);
+                vec![]
+            });
+            for template in custom_templates {
+                if template_manager.is_builtin(&template.id) {
+                    tracing::warn!(
+                        "Ignoring stored research template {}: it shadows a built-in template",
+                        template.id
+                    );
+                } else {
+                    template_manager.add_template(template);
+                }
+            }
+            (template_manager, template_storage)
+        };
 
         // Create history storage if enabled
+        #[cfg(feature = "research")]
         let history_storage = if self.enable_history {
             Self::create_history_storage(self.custom_history_dir)
         } else {
             None
         };
 
-        Ok(WikifyApplication {
+        #[cfg(feature = "wiki")]
+        let (wiki_storage, wiki_exports, wiki_versions, wiki_cache) = (
+            FileWikiStorage::new(WikifyApplication::base_path().join("wikis")),
+            FileWikiExportStore::new(WikifyApplication::base_path().join("exports")),
+            wikify_wiki::WikiCache::with_cache_dir(
+                WikifyApplication::base_path().join("wiki-versions"),
+            )
+            .map_err(|e| ApplicationError::Core(*e))?,
+            wikify_wiki::WikiCache::new().map_err(|e| ApplicationError::Core(*e))?,
+        );
+        let organization_storage = FileOrganizationStorage::open(
+            WikifyApplication::base_path().join("organizations.json"),
+        )
+        .await?;
+        let session_storage = self
+            .session_storage
+            .unwrap_or_else(|| std::sync::Arc::new(MemorySessionStorage::new()));
+
+        // Notify webhooks when indexing or research finishes
+        let webhook_storage = self
+            .webhook_storage
+            .unwrap_or_else(|| std::sync::Arc::new(MemoryWebhookStorage::new()));
+        let webhooks = WebhookDispatcher::new(webhook_storage, self.config.webhooks.clone());
+        webhooks.spawn_indexing_listener(repository_manager.subscribe_to_progress());
+        #[cfg(feature = "research")]
+        if let Some(ref engine) = research_engine {
+            webhooks.spawn_research_listener(engine.subscribe_to_progress());
+        }
+
+        let application = WikifyApplication {
             permission_manager,
             repository_manager,
+            #[cfg(feature = "research")]
             research_engine,
-            template_manager,
+            #[cfg(feature = "research")]
+            template_manager: tokio::sync::RwLock::new(template_manager),
+            #[cfg(feature = "research")]
+            template_storage,
+            #[cfg(feature = "research")]
             history_storage,
+            #[cfg(feature = "wiki")]
+            wiki_storage,
+            #[cfg(feature = "wiki")]
+            wiki_exports,
+            organization_storage,
+            session_storage,
+            usage_tracker,
+            webhooks,
+            #[cfg(feature = "wiki")]
+            wiki_versions,
+            #[cfg(feature = "wiki")]
+            wiki_cache,
             config: self.config,
-        })
+        };
+        if let Some(config) = self.storage_gc {
+            application.storage_gc(config).spawn();
+        }
+
+        Ok(application)
     }
 
     /// Create history storage with optional custom directory
+    #[cfg(feature = "research")]
     fn create_history_storage(
         custom_dir: Option<std::path::PathBuf>,
     ) -> Option<FileResearchHistoryStorage> {
@@ -366,7 +687,7 @@ impl WikifyApplication {
         context: &PermissionContext,
         url: String,
         repo_type: String,
-        options: RepositoryOptions,
+        mut options: RepositoryOptions,
     ) -> ApplicationResult<String> {
         // Check permissions
         self.permission_manager
@@ -374,8 +695,31 @@ impl WikifyApplication {
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
+        // Adding to an organization requires a role that can write to it
+        if let Some(organization_id) = context.organization_id() {
+            self.authorize_organization(context, organization_id, OrgRole::Member)
+                .await?;
+        }
+
+        // Anonymous visitors may only add a few, reasonably sized repositories
+        let max_size_bytes = self
+            .permission_manager
+            .check_anonymous_repository(context)
+            .await
+            .map_err(ApplicationError::rate_limited)?;
+        if let Some(max_size_bytes) = max_size_bytes {
+            options
+                .metadata
+                .get_or_insert_with(std::collections::HashMap::new)
+                .insert(
+                    repository::MAX_SIZE_METADATA_KEY.to_string(),
+                    max_size_bytes.to_string(),
+                );
+        }
+
         // Add repository using the new manager
-        self.repository_manager
+        let repository_id = self
+            .repository_manager
             .add_repository(
                 context,
                 url,
@@ -383,322 +727,2587 @@ impl WikifyApplication {
                 context.user_id().map(|s| s.to_string()),
                 options,
             )
-            .await
+            .await?;
+
+        self.permission_manager
+            .record_anonymous_repository(context)
+            .await;
+        Ok(repository_id)
     }
 
-    /// List all repositories
-    pub async fn list_repositories(
+    /// Add a repository from an uploaded zip archive
+    ///
+    /// The archive is extracted under `{base_dir}/uploads/{upload_id}` and the
+    /// extracted tree is registered as a local directory repository.
+    pub async fn add_uploaded_repository(
         &self,
         context: &PermissionContext,
-    ) -> ApplicationResult<Vec<RepositoryIndex>> {
-        // Check permissions
+        archive: Vec<u8>,
+        name: Option<String>,
+        max_extracted_size: u64,
+        mut options: RepositoryOptions,
+    ) -> ApplicationResult<String> {
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        // List repositories using the new manager
-        self.repository_manager.list_repositories(context).await
+        // Anonymous uploads are held to the anonymous repository size limit
+        let max_extracted_size = match self
+            .permission_manager
+            .check_anonymous_repository(context)
+            .await
+            .map_err(ApplicationError::rate_limited)?
+        {
+            Some(limit) => max_extracted_size.min(limit),
+            None => max_extracted_size,
+        };
+
+        let upload_id = uuid::Uuid::new_v4().to_string();
+        let upload_dir = self.get_base_path().join("uploads").join(&upload_id);
+
+        let target_dir = upload_dir.clone();
+        let extracted = tokio::task::spawn_blocking(move || {
+            wikify_repo::extract_zip_archive(&archive, &target_dir, max_extracted_size)
+        })
+        .await
+        .map_err(|e| ApplicationError::internal(format!("Archive extraction panicked: {}", e)))?;
+
+        let root = match extracted {
+            Ok(root) => root,
+            Err(e) => {
+                let _ = tokio::fs::remove_dir_all(&upload_dir).await;
+                return Err(ApplicationError::config(format!(
+                    "Failed to extract uploaded archive: {}",
+                    e
+                )));
+            }
+        };
+
+        let mut metadata = options.metadata.take().unwrap_or_default();
+        metadata.insert("source".to_string(), "upload".to_string());
+        metadata.insert("upload_id".to_string(), upload_id);
+        metadata.insert("access_mode".to_string(), "LocalDirectory".to_string());
+        if let Some(name) = name {
+            metadata.insert("name".to_string(), name);
+        }
+        options.metadata = Some(metadata);
+        options.access_mode = Some(RepositoryAccessMode::LocalDirectory);
+
+        let result = self
+            .add_repository(
+                context,
+                root.to_string_lossy().to_string(),
+                "local".to_string(),
+                options,
+            )
+            .await;
+
+        if result.is_err() {
+            let _ = tokio::fs::remove_dir_all(&upload_dir).await;
+        }
+
+        result
     }
 
-    /// Get a specific repository
-    pub async fn get_repository(
+    /// List a page of the repositories the caller can see that pass a filter
+    pub async fn list_repositories(
         &self,
         context: &PermissionContext,
-        repository_id: &str,
-    ) -> ApplicationResult<RepositoryIndex> {
+        filter: &RepositoryFilter,
+        page: PageRequest,
+    ) -> ApplicationResult<Page<RepositoryIndex>> {
         // Check permissions
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        // Get repository using the new manager
+        let scope = self.tenant_scope(context).await?;
         self.repository_manager
-            .get_repository(context, repository_id)
+            .list_repositories(context, &scope, filter, &page)
             .await
     }
 
-    /// Reindex a repository
-    pub async fn reindex_repository(
+    /// Change the visibility of a repository and who it is shared with
+    ///
+    /// Only the owner, admins and, for organization repositories, admins of
+    /// the organization may change sharing. Sharing with a user lets them
+    /// see and query the repository regardless of its visibility.
+    pub async fn share_repository(
         &self,
         context: &PermissionContext,
         repository_id: &str,
-    ) -> ApplicationResult<()> {
-        // Check permissions
+        visibility: Option<RepositoryVisibility>,
+        share_with: &[String],
+        revoke: &[String],
+    ) -> ApplicationResult<RepositoryIndex> {
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        // Start reindexing using the repository manager
+        self.authorize_repository_owner(
+            context,
+            repository_id,
+            "Only the owner can change who a repository is shared with",
+        )
+        .await?;
+
         self.repository_manager
-            .start_indexing(repository_id.to_string())
+            .update_sharing(context, repository_id, visibility, share_with, revoke)
             .await
     }
 
-    /// Delete a repository
-    pub async fn delete_repository(
+    /// Get a specific repository
+    pub async fn get_repository(
         &self,
         context: &PermissionContext,
         repository_id: &str,
-    ) -> ApplicationResult<()> {
+    ) -> ApplicationResult<RepositoryIndex> {
         // Check permissions
         self.permission_manager
-            .check_permission(context, &Permission::ManageRepository)
+            .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        // Delete repository using the repository manager
-        self.repository_manager
-            .remove_repository(context, repository_id)
+        self.authorize_repository(context, repository_id, OrgRole::Viewer)
             .await
     }
 
-    /// Query a repository
-    pub async fn query_repository(
+    /// Indexing and query metrics of a repository since the server started
+    pub async fn repository_metrics(
         &self,
         context: &PermissionContext,
         repository_id: &str,
-        query: RepositoryQuery,
-    ) -> ApplicationResult<RepositoryQueryResponse> {
-        // Check permissions
-        self.permission_manager
-            .check_permission(context, &Permission::Query)
-            .await
-            .map_err(|msg| ApplicationError::Permission { message: msg })?;
-
-        // Query repository using the new manager
-        self.repository_manager
-            .query_repository(context, repository_id, query)
-            .await
+    ) -> ApplicationResult<RepositoryOperationStats> {
+        self.get_repository(context, repository_id).await?;
+        Ok(self.repository_manager.repository_metrics(repository_id))
     }
 
-    // ========================================
-    // Research Engine API
-    // ========================================
-
-    /// Start research on a repository
-    pub async fn start_research(
+    /// Reindex a repository
+    pub async fn reindex_repository(
         &self,
         context: &PermissionContext,
         repository_id: &str,
-        research_question: String,
-        config: Option<research::ResearchConfig>,
-    ) -> ApplicationResult<String> {
+    ) -> ApplicationResult<()> {
         // Check permissions
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        // Verify repository exists
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        // Reindexing is requested by a waiting user, so it jumps the queue
         self.repository_manager
-            .get_repository(context, repository_id)
+            .start_indexing(context, repository_id.to_string(), JobPriority::Interactive)
             .await?;
 
-        if let Some(ref engine) = self.research_engine {
-            let research_config = config.unwrap_or_default();
-            engine
-                .start_research(
-                    context,
-                    repository_id,
-                    research_question,
-                    Some(research_config),
-                )
-                .await
-        } else {
-            Err(ApplicationError::Research {
-                message: "Research engine is not available".to_string(),
-            })
-        }
+        // Wikis generated from the old index must not be served again
+        self.invalidate_wiki_cache(&repository).await;
+        Ok(())
     }
 
-    /// Execute a research iteration
-    pub async fn research_iteration(
+    /// Update the index of a local repository for files that changed
+    ///
+    /// Paths are absolute paths below the repository directory and may name
+    /// files that were deleted. Cached wikis of the repository are dropped
+    /// since they were generated from the old index.
+    pub async fn update_repository_files(
         &self,
         context: &PermissionContext,
         repository_id: &str,
-        research_session_id: &str,
-    ) -> ApplicationResult<research::ResearchProgress> {
-        // Check permissions
+        changed_files: &[std::path::PathBuf],
+    ) -> ApplicationResult<wikify_rag::IndexUpdate> {
+        // Same permission as starting a reindex
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        // In the simplified implementation, research is automatic
-        // Just return the current progress
-        self.get_research_progress(context, research_session_id)
-            .await
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        let update = self
+            .repository_manager
+            .update_repository_files(context, repository_id, changed_files)
+            .await?;
+        if !update.is_empty() {
+            self.invalidate_wiki_cache(&repository).await;
+        }
+        Ok(update)
     }
 
-    /// Get research progress
-    pub async fn get_research_progress(
+    /// List running and queued indexing jobs
+    ///
+    /// Users only see their own jobs; admins and anonymous contexts (open
+    /// and local mode) see every job.
+    pub async fn list_indexing_jobs(
         &self,
         context: &PermissionContext,
-        research_session_id: &str,
-    ) -> ApplicationResult<research::ResearchProgress> {
-        // Check permissions
+    ) -> ApplicationResult<Vec<IndexingJob>> {
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        if let Some(ref engine) = self.research_engine {
-            engine.get_research_progress(research_session_id).await
-        } else {
-            Err(ApplicationError::Research {
-                message: "Research engine is not available".to_string(),
-            })
+        let mut jobs = self.repository_manager.list_indexing_jobs().await;
+        if let Some(user_id) = context.user_id().filter(|_| !context.is_admin()) {
+            jobs.retain(|job| job.user_id.as_deref() == Some(user_id));
         }
+        Ok(jobs)
     }
 
-    /// List active research processes
-    pub async fn list_active_research(
+    /// Cancel a queued or running indexing job
+    pub async fn cancel_indexing(
         &self,
         context: &PermissionContext,
-    ) -> ApplicationResult<Vec<String>> {
-        // Check permissions
+        repository_id: &str,
+    ) -> ApplicationResult<()> {
+        // Same permission as starting a reindex
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        if let Some(ref engine) = self.research_engine {
-            Ok(engine.list_active_research().await)
-        } else {
-            Err(ApplicationError::Research {
-                message: "Research engine is not available".to_string(),
-            })
-        }
+        self.authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        self.repository_manager
+            .cancel_indexing(context, repository_id)
+            .await
     }
 
-    /// Get research details
-    pub async fn get_research_details(
+    /// Delete a repository together with its clone and index artifacts
+    pub async fn delete_repository(
         &self,
         context: &PermissionContext,
-        research_id: &str,
-    ) -> ApplicationResult<research::ResearchContext> {
+        repository_id: &str,
+    ) -> ApplicationResult<()> {
         // Check permissions
         self.permission_manager
-            .check_permission(context, &Permission::Query)
+            .check_permission(context, &Permission::ManageRepository)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        if let Some(ref engine) = self.research_engine {
-            engine.get_research_result(research_id).await.map(|result| {
-                // Convert the result to the expected format
-                // For now, return a placeholder since the types don't match exactly
-                research::ResearchContext {
-                    id: research_id.to_string(),
-                    repository_id: "unknown".to_string(),
-                    topic: result
-                        .map(|r| r.original_query)
-                        .unwrap_or_else(|| "unknown".to_string()),
-                    config: research::ResearchConfig::default(),
-                    questions: vec![],
-                    findings: vec![],
-                    iterations: vec![],
-                    status: research::types::ResearchStatus::Completed,
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Admin)
+            .await?;
+
+        // Delete repository using the repository manager
+        self.repository_manager
+            .remove_repository(context, repository_id)
+            .await?;
+
+        self.remove_repository_artifacts(&repository).await;
+        self.remove_wiki_artifacts(&repository).await;
+        if let Err(e) = self
+            .session_storage
+            .delete_repository_sessions(repository_id)
+            .await
+        {
+            tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to remove chat sessions"
+            );
+        }
+        Ok(())
+    }
+
+    /// Remove the clone, uploaded sources and vector index of a deleted repository
+    ///
+    /// Clones shared with another registered repository (same URL) are kept.
+    async fn remove_repository_artifacts(&self, repository: &RepositoryIndex) {
+        let still_referenced = match self.repository_manager.list_all_repositories().await {
+            Ok(repositories) => repositories.iter().any(|r| r.url == repository.url),
+            Err(_) => true,
+        };
+
+        let mut targets = Vec::new();
+        if !still_referenced {
+            for workspace in self.clone_workspaces() {
+                if let Some(path) = Self::clone_path_in(&workspace, &repository.url) {
+                    targets.push((workspace, path));
                 }
-            })
-        } else {
-            Err(ApplicationError::Research {
-                message: "Research engine is not available".to_string(),
-            })
+            }
+        }
+
+        let upload_dir = repository
+            .metadata
+            .get("upload_id")
+            .map(|upload_id| self.get_base_path().join("uploads").join(upload_id));
+        let repository_url = repository.url.clone();
+        let repository_id = repository.id.clone();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let mut freed_bytes = 0;
+
+            for (workspace, path) in targets {
+                match workspace.remove(&path) {
+                    Ok(size) => freed_bytes += size,
+                    Err(e) => tracing::warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "Failed to remove clone"
+                    ),
+                }
+            }
+
+            if let Some(upload_dir) = upload_dir {
+                if let Err(e) = std::fs::remove_dir_all(&upload_dir) {
+                    if e.kind() != std::io::ErrorKind::NotFound {
+                        tracing::warn!(
+                            path = %upload_dir.display(),
+                            error = %e,
+                            "Failed to remove uploaded sources"
+                        );
+                    }
+                }
+            }
+
+            match wikify_rag::PersistentVectorStore::remove_storage(
+                &wikify_rag::types::StorageConfig::default(),
+                &repository_url,
+            ) {
+                Ok(size) => freed_bytes += size,
+                Err(e) => tracing::warn!(error = %e, "Failed to remove vector index"),
+            }
+
+            freed_bytes
+        })
+        .await;
+
+        match result {
+            Ok(freed_bytes) => tracing::info!(
+                repository_id = %repository_id,
+                freed_bytes = freed_bytes,
+                "🧹 Removed repository artifacts"
+            ),
+            Err(e) => tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Repository artifact cleanup panicked"
+            ),
         }
     }
 
-    /// Cancel research
-    pub async fn cancel_research(
+    /// Without wiki generation there are no cached wikis to drop
+    #[cfg(not(feature = "wiki"))]
+    async fn invalidate_wiki_cache(&self, _repository: &RepositoryIndex) {}
+
+    /// Without wiki generation there are no wiki artifacts to remove
+    #[cfg(not(feature = "wiki"))]
+    async fn remove_wiki_artifacts(&self, _repository: &RepositoryIndex) {}
+
+    /// Apply a retention policy to the clone workspaces
+    ///
+    /// Clones of repositories that are currently being indexed are never removed.
+    pub async fn collect_workspace_garbage(
         &self,
         context: &PermissionContext,
-        research_id: &str,
-    ) -> ApplicationResult<()> {
+        policy: wikify_repo::RetentionPolicy,
+        dry_run: bool,
+    ) -> ApplicationResult<wikify_repo::GcReport> {
         // Check permissions
         self.permission_manager
-            .check_permission(context, &Permission::Query)
+            .check_permission(context, &Permission::Admin)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-        if let Some(ref engine) = self.research_engine {
-            engine.stop_research(research_id).await
-        } else {
-            Err(ApplicationError::Research {
+        let workspaces = self.clone_workspaces();
+        let protected: std::collections::HashSet<std::path::PathBuf> = self
+            .repository_manager
+            .list_all_repositories()
+            .await?
+            .iter()
+            .filter(|r| r.status == IndexingStatus::Indexing)
+            .flat_map(|r| {
+                workspaces
+                    .iter()
+                    .filter_map(|w| Self::clone_path_in(w, &r.url))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        tokio::task::spawn_blocking(move || {
+            let mut report = wikify_repo::GcReport {
+                dry_run,
+                ..Default::default()
+            };
+            for workspace in workspaces {
+                report.merge(workspace.collect_garbage(&policy, &protected, dry_run)?);
+            }
+            Ok::<_, Box<wikify_core::WikifyError>>(report)
+        })
+        .await
+        .map_err(|e| ApplicationError::internal(format!("Workspace GC panicked: {}", e)))?
+        .map_err(|e| ApplicationError::internal(format!("Workspace GC failed: {}", e)))
+    }
+
+    /// Remove orphaned clones, stale wiki cache entries and vector indexes of
+    /// deleted repositories
+    ///
+    /// Artifacts modified within the grace period of `config` are kept.
+    pub async fn collect_storage_garbage(
+        &self,
+        context: &PermissionContext,
+        config: StorageGcConfig,
+    ) -> ApplicationResult<StorageGcReport> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Admin)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let dry_run = config.dry_run;
+        self.storage_gc(config).run(dry_run).await
+    }
+
+    /// Storage GC over the clones, wiki cache and vector indexes of this application
+    fn storage_gc(&self, config: StorageGcConfig) -> StorageGc {
+        StorageGc::new(
+            self.repository_manager.clone(),
+            self.repository_manager.storage_locations(),
+            config,
+        )
+    }
+
+    /// Disk usage of every repository and of all artifacts together
+    ///
+    /// Every repository is measured again and its stored usage updated.
+    pub async fn storage_usage(
+        &self,
+        context: &PermissionContext,
+    ) -> ApplicationResult<StorageUsageReport> {
+        self.permission_manager
+            .check_permission(context, &Permission::Admin)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let mut repositories = Vec::new();
+        for repository in self.repository_manager.list_all_repositories().await? {
+            if let Some(usage) = self
+                .repository_manager
+                .refresh_disk_usage(&repository.id)
+                .await?
+            {
+                repositories.push(RepositoryStorageUsage {
+                    repository_id: repository.id,
+                    url: repository.url,
+                    usage,
+                });
+            }
+        }
+        repositories.sort_by(|a, b| b.usage.total_bytes.cmp(&a.usage.total_bytes));
+
+        let locations = self.repository_manager.storage_locations();
+        let total_bytes = tokio::task::spawn_blocking(move || locations.total_bytes())
+            .await
+            .map_err(|e| ApplicationError::internal(format!("Storage scan panicked: {}", e)))?;
+        let quota_bytes = self.repository_manager.max_disk_usage_bytes();
+
+        Ok(StorageUsageReport {
+            generated_at: chrono::Utc::now(),
+            repositories,
+            total_bytes,
+            quota_bytes,
+            quota_exceeded: quota_bytes.is_some_and(|quota| total_bytes >= quota),
+        })
+    }
+
+    /// Clone workspaces used by the repository processor and the RAG pipeline
+    /// Repository, index, operation and storage statistics for administrators
+    ///
+    /// `database_path` is the database file to include in the storage usage,
+    /// when the database is a local file.
+    pub async fn system_stats(
+        &self,
+        context: &PermissionContext,
+        database_path: Option<std::path::PathBuf>,
+    ) -> ApplicationResult<SystemStats> {
+        self.permission_manager
+            .check_permission(context, &Permission::Admin)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let repository_stats = self.repository_manager.stats().await?;
+
+        let workspaces = self.clone_workspaces();
+        let base_path = self.get_base_path();
+        let storage = tokio::task::spawn_blocking(move || {
+            let clones_bytes = workspaces
+                .iter()
+                .filter_map(|workspace| workspace.entries().ok())
+                .flatten()
+                .map(|entry| entry.size_bytes)
+                .sum();
+            let wikis_bytes = wikify_repo::directory_size(&base_path.join("wikis"))
+                + wikify_repo::directory_size(&base_path.join("wiki-versions"));
+            let uploads_bytes = wikify_repo::directory_size(&base_path.join("uploads"));
+            let database_bytes = database_path
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map(|metadata| metadata.len());
+
+            StorageStats {
+                clones_bytes,
+                wikis_bytes,
+                uploads_bytes,
+                database_bytes,
+                total_bytes: clones_bytes
+                    + wikis_bytes
+                    + uploads_bytes
+                    + database_bytes.unwrap_or(0),
+            }
+        })
+        .await
+        .map_err(|e| ApplicationError::internal(format!("Storage scan panicked: {}", e)))?;
+
+        Ok(SystemStats {
+            generated_at: chrono::Utc::now(),
+            repositories: repository_stats.repositories,
+            indexes: repository_stats.indexes,
+            indexing: repository_stats.indexing,
+            queries: repository_stats.queries,
+            repository_metrics: repository_stats.repository_metrics,
+            storage,
+        })
+    }
+
+    fn clone_workspaces(&self) -> Vec<wikify_repo::CloneWorkspace> {
+        vec![
+            wikify_repo::CloneWorkspace::new(self.get_base_path()),
+            wikify_repo::CloneWorkspace::new(wikify_rag::RagPipeline::clone_base_path()),
+        ]
+    }
+
+    /// Clone path of a repository URL inside the given workspace
+    pub(crate) fn clone_path_in(
+        workspace: &wikify_repo::CloneWorkspace,
+        url: &str,
+    ) -> Option<std::path::PathBuf> {
+        let base_path = workspace.root().parent()?;
+        wikify_repo::RepositoryProcessor::new(base_path).clone_path(url)
+    }
+
+    /// Query a repository
+    pub async fn query_repository(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        query: RepositoryQuery,
+    ) -> ApplicationResult<RepositoryQueryResponse> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.permission_manager
+            .throttle_anonymous_query(context)
+            .await
+            .map_err(ApplicationError::rate_limited)?;
+
+        // Query repository using the new manager
+        let response = self
+            .repository_manager
+            .query_repository(context, repository_id, query)
+            .await?;
+
+        Self::record_query_usage(
+            &self.usage_tracker,
+            &self.config.rag.embeddings.model,
+            Self::session_owner(context).map(str::to_string),
+            repository_id,
+            &response.metadata,
+        )
+        .await;
+        Ok(response)
+    }
+
+    /// Search the indexed code and documents of a repository
+    ///
+    /// Returns the most relevant chunks without asking the LLM, so results
+    /// come back quickly and cost no generation tokens.
+    pub async fn search_repository(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        query: RepositoryQuery,
+    ) -> ApplicationResult<Vec<CodeSearchResult>> {
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.permission_manager
+            .throttle_anonymous_query(context)
+            .await
+            .map_err(ApplicationError::rate_limited)?;
+
+        self.repository_manager
+            .search_repository(context, repository_id, query)
+            .await
+    }
+
+    /// Query a repository, receiving the answer in chunks as it is produced
+    ///
+    /// Content chunks are followed by a final `Complete` chunk with the
+    /// sources and metadata, or an `Error` chunk. Dropping the receiver
+    /// stops the query, so a client that disconnects does not keep the
+    /// worker busy.
+    pub async fn stream_query_repository(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        query: RepositoryQuery,
+    ) -> ApplicationResult<tokio::sync::mpsc::UnboundedReceiver<QueryStreamChunk>> {
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.permission_manager
+            .throttle_anonymous_query(context)
+            .await
+            .map_err(ApplicationError::rate_limited)?;
+
+        let mut upstream = self
+            .repository_manager
+            .stream_query_repository(context, repository_id, query)
+            .await?;
+
+        // Forward the chunks, recording usage once the answer is complete
+        let (chunk_tx, chunk_rx) = tokio::sync::mpsc::unbounded_channel();
+        let tracker = self.usage_tracker.clone();
+        let embedding_model = self.config.rag.embeddings.model.clone();
+        let user_id = Self::session_owner(context).map(str::to_string);
+        let repository_id = repository_id.to_string();
+        tokio::spawn(async move {
+            while let Some(chunk) = upstream.recv().await {
+                if let (QueryChunkType::Complete, Some(metadata)) =
+                    (&chunk.chunk_type, &chunk.metadata)
+                {
+                    Self::record_query_usage(
+                        &tracker,
+                        &embedding_model,
+                        user_id.clone(),
+                        &repository_id,
+                        metadata,
+                    )
+                    .await;
+                }
+                // The client went away; dropping `upstream` stops the worker
+                if chunk_tx.send(chunk).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(chunk_rx)
+    }
+
+    /// Record the embedding and generation tokens of an answered query
+    ///
+    /// Failing to record usage never fails the query itself.
+    async fn record_query_usage(
+        tracker: &UsageTracker,
+        embedding_model: &str,
+        user_id: Option<String>,
+        repository_id: &str,
+        metadata: &std::collections::HashMap<String, String>,
+    ) {
+        let tokens = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        let repository_id = Some(repository_id.to_string());
+        let config = tracker.config();
+
+        let mut records = vec![UsageRecord::new(
+            config,
+            UsageKind::Embedding,
+            embedding_model,
+            tokens("query_tokens"),
+            0,
+        )];
+        // Queries without relevant sources are answered without the LLM
+        if let Some(model) = metadata.get("model_used") {
+            if tokens("prompt_tokens") > 0 {
+                records.push(UsageRecord::new(
+                    config,
+                    UsageKind::Generation,
+                    model,
+                    tokens("prompt_tokens"),
+                    tokens("generation_tokens"),
+                ));
+            }
+        }
+
+        for record in records {
+            let record = record.for_subject(user_id.clone(), repository_id.clone());
+            if let Err(e) = tracker.record(record).await {
+                tracing::warn!("Failed to record usage: {}", e);
+            }
+        }
+    }
+
+    // ========================================
+    // Usage API
+    // ========================================
+
+    /// Token usage and estimated cost, grouped by user, repository or day
+    ///
+    /// The report includes the daily budgets exceeded today. Admin only.
+    pub async fn usage_report(
+        &self,
+        context: &PermissionContext,
+        filter: &UsageFilter,
+        group_by: UsageGroupBy,
+    ) -> ApplicationResult<UsageReport> {
+        self.permission_manager
+            .check_permission(context, &Permission::Admin)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.usage_tracker.report(filter, group_by).await
+    }
+
+    /// Usage records matching a filter, rendered for download. Admin only.
+    pub async fn export_usage(
+        &self,
+        context: &PermissionContext,
+        filter: &UsageFilter,
+        format: UsageExportFormat,
+    ) -> ApplicationResult<String> {
+        self.permission_manager
+            .check_permission(context, &Permission::Admin)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let records = self.usage_tracker.records(filter).await?;
+        format.render(&records)
+    }
+
+    // ========================================
+    // Webhook API
+    // ========================================
+
+    /// Register a webhook for the events of one repository or, for admins,
+    /// of every repository
+    ///
+    /// Repository webhooks can be registered by whoever may change the
+    /// repository's sharing. A secret is generated when none is given.
+    pub async fn create_webhook(
+        &self,
+        context: &PermissionContext,
+        webhook: NewWebhook,
+    ) -> ApplicationResult<Webhook> {
+        match &webhook.repository_id {
+            Some(repository_id) => {
+                self.permission_manager
+                    .check_permission(context, &Permission::Query)
+                    .await
+                    .map_err(|msg| ApplicationError::Permission { message: msg })?;
+                self.authorize_repository_owner(
+                    context,
+                    repository_id,
+                    "Only the owner can add webhooks to a repository",
+                )
+                .await?;
+            }
+            None => {
+                self.permission_manager
+                    .check_permission(context, &Permission::Admin)
+                    .await
+                    .map_err(|msg| ApplicationError::Permission { message: msg })?;
+            }
+        }
+        Self::validate_webhook_url(&webhook.url)?;
+
+        let now = chrono::Utc::now();
+        let webhook = Webhook {
+            id: uuid::Uuid::new_v4().to_string(),
+            url: webhook.url,
+            secret: webhook
+                .secret
+                .filter(|secret| !secret.is_empty())
+                .unwrap_or_else(webhook::generate_secret),
+            events: webhook.events,
+            repository_id: webhook.repository_id,
+            owner_id: context.user_id().map(str::to_string),
+            active: true,
+            created_at: now,
+            updated_at: now,
+        };
+        self.webhooks.storage().save_webhook(&webhook).await?;
+
+        tracing::info!(
+            webhook_id = %webhook.id,
+            repository_id = ?webhook.repository_id,
+            "Webhook registered"
+        );
+        Ok(webhook)
+    }
+
+    /// Webhooks registered by the calling user; admins see all of them
+    pub async fn list_webhooks(
+        &self,
+        context: &PermissionContext,
+    ) -> ApplicationResult<Vec<Webhook>> {
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let webhooks = self.webhooks.storage().list_webhooks().await?;
+        Ok(webhooks
+            .into_iter()
+            .filter(|webhook| Self::can_manage_webhook(context, webhook))
+            .collect())
+    }
+
+    /// Get a webhook of the calling user
+    pub async fn get_webhook(
+        &self,
+        context: &PermissionContext,
+        webhook_id: &str,
+    ) -> ApplicationResult<Webhook> {
+        self.authorize_webhook(context, webhook_id).await
+    }
+
+    /// Change the URL, secret, events or active state of a webhook
+    pub async fn update_webhook(
+        &self,
+        context: &PermissionContext,
+        webhook_id: &str,
+        update: WebhookUpdate,
+    ) -> ApplicationResult<Webhook> {
+        let mut webhook = self.authorize_webhook(context, webhook_id).await?;
+        if let Some(url) = update.url {
+            Self::validate_webhook_url(&url)?;
+            webhook.url = url;
+        }
+        if let Some(secret) = update.secret.filter(|secret| !secret.is_empty()) {
+            webhook.secret = secret;
+        }
+        if let Some(events) = update.events {
+            webhook.events = events;
+        }
+        if let Some(active) = update.active {
+            webhook.active = active;
+        }
+        webhook.updated_at = chrono::Utc::now();
+        self.webhooks.storage().save_webhook(&webhook).await?;
+        Ok(webhook)
+    }
+
+    /// Delete a webhook and its delivery log
+    pub async fn delete_webhook(
+        &self,
+        context: &PermissionContext,
+        webhook_id: &str,
+    ) -> ApplicationResult<()> {
+        self.authorize_webhook(context, webhook_id).await?;
+        self.webhooks.storage().delete_webhook(webhook_id).await?;
+        Ok(())
+    }
+
+    /// Recent delivery attempts of a webhook, newest first
+    pub async fn list_webhook_deliveries(
+        &self,
+        context: &PermissionContext,
+        webhook_id: &str,
+        limit: usize,
+    ) -> ApplicationResult<Vec<WebhookDelivery>> {
+        self.authorize_webhook(context, webhook_id).await?;
+        self.webhooks
+            .storage()
+            .list_deliveries(webhook_id, limit)
+            .await
+    }
+
+    /// Deliver the payload of an earlier delivery again, returning the new
+    /// attempt
+    pub async fn retry_webhook_delivery(
+        &self,
+        context: &PermissionContext,
+        webhook_id: &str,
+        delivery_id: &str,
+    ) -> ApplicationResult<WebhookDelivery> {
+        let webhook = self.authorize_webhook(context, webhook_id).await?;
+        let delivery = self
+            .webhooks
+            .storage()
+            .get_delivery(delivery_id)
+            .await?
+            .filter(|delivery| delivery.webhook_id == webhook.id)
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!("Webhook delivery not found: {}", delivery_id))
+            })?;
+
+        Ok(self.webhooks.redeliver(&webhook, &delivery).await)
+    }
+
+    /// Get a webhook the calling user may manage
+    ///
+    /// Webhooks of other users are reported as not found.
+    async fn authorize_webhook(
+        &self,
+        context: &PermissionContext,
+        webhook_id: &str,
+    ) -> ApplicationResult<Webhook> {
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.webhooks
+            .storage()
+            .get_webhook(webhook_id)
+            .await?
+            .filter(|webhook| Self::can_manage_webhook(context, webhook))
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!("Webhook not found: {}", webhook_id))
+            })
+    }
+
+    fn can_manage_webhook(context: &PermissionContext, webhook: &Webhook) -> bool {
+        context.is_admin()
+            || webhook
+                .owner_id
+                .as_deref()
+                .is_some_and(|owner_id| Some(owner_id) == context.user_id())
+    }
+
+    fn validate_webhook_url(url: &str) -> ApplicationResult<()> {
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+            _ => Err(ApplicationError::config(format!(
+                "Webhook URL must be an http or https URL: {}",
+                url
+            ))),
+        }
+    }
+
+    // ========================================
+    // Chat Session API
+    // ========================================
+
+    /// Start a chat session about a repository
+    ///
+    /// The session belongs to the calling user, whose identity and use of
+    /// the repository are recorded alongside it. Sessions of anonymous
+    /// users have no owner. Without a title, the first question becomes
+    /// the title of the session.
+    pub async fn create_chat_session(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        title: Option<String>,
+    ) -> ApplicationResult<ChatSessionRecord> {
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        let mut session = ChatSessionRecord::new(
+            repository_id.to_string(),
+            Self::session_owner(context).map(str::to_string),
+        );
+        if let Some(title) = title {
+            session.title = Self::validate_session_title(&title)?;
+        }
+        self.session_storage.save_session(&session).await?;
+        self.record_repository_use(context, repository_id).await?;
+        Ok(session)
+    }
+
+    /// Ask a question in a chat session
+    ///
+    /// The question and the answer are appended to the session, along with
+    /// the sources and parameters used to produce the answer.
+    pub async fn query_chat_session(
+        &self,
+        context: &PermissionContext,
+        session_id: &str,
+        query: RepositoryQuery,
+    ) -> ApplicationResult<RepositoryQueryResponse> {
+        let mut session = self.authorize_chat_session(context, session_id).await?;
+        let question = query.question.clone();
+        let metadata = Self::answer_metadata(&query);
+        let mut response = self
+            .query_repository(context, &session.repository_id, query)
+            .await?;
+
+        session.push_message("user", question);
+        Self::record_answer(&mut session, &mut response, metadata);
+        self.session_storage.save_session(&session).await?;
+        self.record_repository_use(context, &session.repository_id)
+            .await?;
+        Ok(response)
+    }
+
+    /// Answer the last question of a chat session again
+    ///
+    /// `max_results` and `parameters` (such as `similarity_threshold`)
+    /// replace those of the original query. The new answer is kept next to
+    /// the previous ones as another branch, whose number is returned in the
+    /// `branch` metadata of the response.
+    pub async fn regenerate_chat_answer(
+        &self,
+        context: &PermissionContext,
+        session_id: &str,
+        max_results: Option<usize>,
+        parameters: Option<std::collections::HashMap<String, String>>,
+    ) -> ApplicationResult<RepositoryQueryResponse> {
+        let mut session = self.authorize_chat_session(context, session_id).await?;
+        let question = session
+            .last_question()
+            .map(|message| message.content.clone())
+            .ok_or_else(|| {
+                ApplicationError::config("The chat session has no question to answer again")
+            })?;
+
+        let query = RepositoryQuery {
+            question,
+            max_results,
+            parameters,
+        };
+        let metadata = Self::answer_metadata(&query);
+        let mut response = self
+            .query_repository(context, &session.repository_id, query)
+            .await?;
+
+        Self::record_answer(&mut session, &mut response, metadata);
+        self.session_storage.save_session(&session).await?;
+        self.record_repository_use(context, &session.repository_id)
+            .await?;
+        Ok(response)
+    }
+
+    /// Get a chat session with its messages
+    pub async fn get_chat_session(
+        &self,
+        context: &PermissionContext,
+        session_id: &str,
+    ) -> ApplicationResult<ChatSessionRecord> {
+        self.authorize_chat_session(context, session_id).await
+    }
+
+    /// List chat sessions of the calling user, optionally for one repository
+    ///
+    /// Admins and open mode contexts without a user see all sessions.
+    pub async fn list_chat_sessions(
+        &self,
+        context: &PermissionContext,
+        repository_id: Option<&str>,
+    ) -> ApplicationResult<Vec<ChatSessionSummary>> {
+        let user_id = if context.is_admin() || context.identity.is_none() {
+            None
+        } else {
+            Some(Self::session_owner(context).ok_or_else(|| {
+                ApplicationError::permission("Listing chat sessions requires a signed-in user")
+            })?)
+        };
+
+        self.session_storage
+            .list_sessions(user_id, repository_id)
+            .await
+    }
+
+    /// Rename a chat session
+    pub async fn rename_chat_session(
+        &self,
+        context: &PermissionContext,
+        session_id: &str,
+        title: &str,
+    ) -> ApplicationResult<ChatSessionRecord> {
+        let mut session = self.authorize_chat_session(context, session_id).await?;
+        session.title = Self::validate_session_title(title)?;
+        self.session_storage.save_session(&session).await?;
+        Ok(session)
+    }
+
+    /// Delete a chat session
+    pub async fn delete_chat_session(
+        &self,
+        context: &PermissionContext,
+        session_id: &str,
+    ) -> ApplicationResult<()> {
+        self.authorize_chat_session(context, session_id).await?;
+        self.session_storage.delete_session(session_id).await
+    }
+
+    /// List the repositories the calling user has chatted about, most recent first
+    pub async fn list_recent_repositories(
+        &self,
+        context: &PermissionContext,
+    ) -> ApplicationResult<Vec<RepositoryAssociation>> {
+        match Self::session_owner(context) {
+            Some(user_id) => self.session_storage.list_user_repositories(user_id).await,
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Metadata recorded with an answer describing the query that produced it
+    fn answer_metadata(
+        query: &RepositoryQuery,
+    ) -> std::collections::HashMap<String, serde_json::Value> {
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert(
+            "max_results".to_string(),
+            serde_json::json!(query.max_results),
+        );
+        metadata.insert(
+            "parameters".to_string(),
+            serde_json::json!(query.parameters),
+        );
+        metadata
+    }
+
+    /// Append an answer with its sources to a session and report its branch
+    /// in the response metadata
+    fn record_answer(
+        session: &mut ChatSessionRecord,
+        response: &mut RepositoryQueryResponse,
+        mut metadata: std::collections::HashMap<String, serde_json::Value>,
+    ) {
+        metadata.insert("sources".to_string(), serde_json::json!(response.sources));
+        metadata.insert(
+            "confidence".to_string(),
+            serde_json::json!(response.confidence),
+        );
+        metadata.insert(
+            "retrieval".to_string(),
+            serde_json::json!(response.metadata),
+        );
+
+        let branch = session.push_answer(response.answer.clone(), metadata);
+        response
+            .metadata
+            .insert("branch".to_string(), branch.to_string());
+    }
+
+    /// Trim a user-supplied session title, rejecting empty or overly long titles
+    fn validate_session_title(title: &str) -> ApplicationResult<String> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(ApplicationError::config("Session title cannot be empty"));
+        }
+        if title.chars().count() > 200 {
+            return Err(ApplicationError::config(
+                "Session title cannot be longer than 200 characters",
+            ));
+        }
+        Ok(title.to_string())
+    }
+
+    /// User who owns the sessions created in a context (None for anonymous users)
+    fn session_owner(context: &PermissionContext) -> Option<&str> {
+        context.user_id().filter(|_| !context.is_anonymous())
+    }
+
+    /// Load a chat session and check the calling user may access it
+    ///
+    /// Sessions of other users are reported as not found.
+    async fn authorize_chat_session(
+        &self,
+        context: &PermissionContext,
+        session_id: &str,
+    ) -> ApplicationResult<ChatSessionRecord> {
+        self.session_storage
+            .load_session(session_id)
+            .await?
+            .filter(|session| {
+                context.is_admin() || session.is_accessible_by(Self::session_owner(context))
+            })
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!("Chat session not found: {}", session_id))
+            })
+    }
+
+    /// Remember the identity of a signed-in user and their use of a repository
+    async fn record_repository_use(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<()> {
+        let Some(identity) = context
+            .identity
+            .as_ref()
+            .filter(|_| !context.is_anonymous())
+        else {
+            return Ok(());
+        };
+
+        self.session_storage.save_identity(identity).await?;
+        self.session_storage
+            .touch_repository(&identity.user_id, repository_id)
+            .await
+    }
+
+    // ========================================
+    // Organization API
+    // ========================================
+
+    /// Create an organization owned by the calling user
+    pub async fn create_organization(
+        &self,
+        context: &PermissionContext,
+        name: String,
+    ) -> ApplicationResult<Organization> {
+        let owner_id = context
+            .user_id()
+            .filter(|_| !context.is_anonymous())
+            .ok_or_else(|| {
+                ApplicationError::permission("Creating an organization requires a signed-in user")
+            })?;
+
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(ApplicationError::config(
+                "Organization name cannot be empty",
+            ));
+        }
+
+        let organization = Organization::new(name, owner_id.to_string());
+        self.organization_storage
+            .save_organization(&organization)
+            .await?;
+
+        tracing::info!(
+            organization_id = %organization.id,
+            owner_id = %owner_id,
+            "🏢 Organization created"
+        );
+        Ok(organization)
+    }
+
+    /// List the organizations of the calling user (admins see all of them)
+    pub async fn list_organizations(
+        &self,
+        context: &PermissionContext,
+    ) -> ApplicationResult<Vec<Organization>> {
+        if context.is_admin() {
+            return self.organization_storage.list_organizations(None).await;
+        }
+
+        match context.user_id() {
+            Some(user_id) => {
+                self.organization_storage
+                    .list_organizations(Some(user_id))
+                    .await
+            }
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Get an organization the calling user is a member of
+    pub async fn get_organization(
+        &self,
+        context: &PermissionContext,
+        organization_id: &str,
+    ) -> ApplicationResult<Organization> {
+        self.authorize_organization(context, organization_id, OrgRole::Viewer)
+            .await
+    }
+
+    /// Delete an organization
+    ///
+    /// Only owners may delete an organization, and only once it no longer
+    /// has repositories.
+    pub async fn delete_organization(
+        &self,
+        context: &PermissionContext,
+        organization_id: &str,
+    ) -> ApplicationResult<()> {
+        self.authorize_organization(context, organization_id, OrgRole::Owner)
+            .await?;
+
+        let repositories = self
+            .repository_manager
+            .list_all_repositories()
+            .await?
+            .into_iter()
+            .filter(|repo| repo.organization_id.as_deref() == Some(organization_id))
+            .count();
+        if repositories > 0 {
+            return Err(ApplicationError::config(format!(
+                "Organization {} still has {} repositories",
+                organization_id, repositories
+            )));
+        }
+
+        self.organization_storage
+            .delete_organization(organization_id)
+            .await
+    }
+
+    /// Add a member to an organization or change the role of an existing one
+    ///
+    /// Requires the admin role; only owners may grant or revoke the owner role.
+    pub async fn set_organization_member(
+        &self,
+        context: &PermissionContext,
+        organization_id: &str,
+        user_id: &str,
+        role: OrgRole,
+    ) -> ApplicationResult<Organization> {
+        let mut organization = self
+            .authorize_organization(context, organization_id, OrgRole::Admin)
+            .await?;
+
+        let current_role = organization.role_of(user_id);
+        if (role == OrgRole::Owner || current_role == Some(OrgRole::Owner))
+            && !self.is_organization_owner(context, &organization)
+        {
+            return Err(ApplicationError::permission(
+                "Only owners can grant or revoke the owner role",
+            ));
+        }
+        if current_role == Some(OrgRole::Owner)
+            && role != OrgRole::Owner
+            && organization.owner_count() == 1
+        {
+            return Err(ApplicationError::config(
+                "An organization must keep at least one owner",
+            ));
+        }
+
+        organization.set_member(user_id.to_string(), role);
+        self.organization_storage
+            .save_organization(&organization)
+            .await?;
+        self.permission_manager.invalidate_user(user_id).await;
+        Ok(organization)
+    }
+
+    /// Remove a member from an organization
+    ///
+    /// Members may always leave; removing someone else requires the admin role.
+    pub async fn remove_organization_member(
+        &self,
+        context: &PermissionContext,
+        organization_id: &str,
+        user_id: &str,
+    ) -> ApplicationResult<Organization> {
+        let leaving = context.user_id() == Some(user_id);
+        let required = if leaving {
+            OrgRole::Viewer
+        } else {
+            OrgRole::Admin
+        };
+        let mut organization = self
+            .authorize_organization(context, organization_id, required)
+            .await?;
+
+        match organization.role_of(user_id) {
+            None => {
+                return Err(ApplicationError::not_found(format!(
+                    "User {} is not a member of organization {}",
+                    user_id, organization_id
+                )))
+            }
+            Some(OrgRole::Owner) => {
+                if !leaving && !self.is_organization_owner(context, &organization) {
+                    return Err(ApplicationError::permission(
+                        "Only owners can remove another owner",
+                    ));
+                }
+                if organization.owner_count() == 1 {
+                    return Err(ApplicationError::config(
+                        "An organization must keep at least one owner",
+                    ));
+                }
+            }
+            Some(_) => {}
+        }
+
+        organization.remove_member(user_id);
+        self.organization_storage
+            .save_organization(&organization)
+            .await?;
+        self.permission_manager.invalidate_user(user_id).await;
+        Ok(organization)
+    }
+
+    /// Forget cached permission decisions after a user's roles or
+    /// permissions changed outside the application, or of all users
+    pub async fn invalidate_permissions(&self, user_id: Option<&str>) {
+        match user_id {
+            Some(user_id) => self.permission_manager.invalidate_user(user_id).await,
+            None => self.permission_manager.invalidate_all().await,
+        }
+    }
+
+    /// Whether the calling user is an owner of the organization (admins count as owners)
+    fn is_organization_owner(
+        &self,
+        context: &PermissionContext,
+        organization: &Organization,
+    ) -> bool {
+        context.is_admin()
+            || context
+                .user_id()
+                .and_then(|user_id| organization.role_of(user_id))
+                == Some(OrgRole::Owner)
+    }
+
+    /// Load an organization and check the calling user has at least `role` in it
+    ///
+    /// Admins pass every check. Non-members get a not found error so that
+    /// organization IDs are not disclosed.
+    async fn authorize_organization(
+        &self,
+        context: &PermissionContext,
+        organization_id: &str,
+        role: OrgRole,
+    ) -> ApplicationResult<Organization> {
+        let organization = self
+            .organization_storage
+            .load_organization(organization_id)
+            .await?
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!("Organization not found: {}", organization_id))
+            })?;
+
+        if context.is_admin() {
+            return Ok(organization);
+        }
+
+        match context
+            .user_id()
+            .and_then(|user_id| organization.role_of(user_id))
+        {
+            Some(member_role) if member_role >= role => Ok(organization),
+            Some(member_role) => Err(ApplicationError::permission(format!(
+                "The {} role in organization {} is not enough, {} is required",
+                member_role, organization_id, role
+            ))),
+            None => Err(ApplicationError::not_found(format!(
+                "Organization not found: {}",
+                organization_id
+            ))),
+        }
+    }
+
+    /// Tenant whose repositories and research history the request may list
+    ///
+    /// Requests acting in an organization see its resources, signed-in users
+    /// their personal ones, and contexts without identity everything.
+    async fn tenant_scope(&self, context: &PermissionContext) -> ApplicationResult<TenantScope> {
+        if let Some(organization_id) = context.organization_id() {
+            self.authorize_organization(context, organization_id, OrgRole::Viewer)
+                .await?;
+            return Ok(TenantScope::Organization(organization_id.to_string()));
+        }
+
+        Ok(match context.user_id() {
+            Some(user_id) => TenantScope::Personal(user_id.to_string()),
+            None => TenantScope::All,
+        })
+    }
+
+    /// Load a repository and check the calling user may access it with `role`
+    async fn authorize_repository(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        role: OrgRole,
+    ) -> ApplicationResult<RepositoryIndex> {
+        let repository = self
+            .repository_manager
+            .get_repository(context, repository_id)
+            .await?;
+        self.check_repository_tenant(context, &repository, role)
+            .await?;
+        Ok(repository)
+    }
+
+    /// Check the calling user owns a repository, is an admin or, for
+    /// organization repositories, an admin of the organization
+    ///
+    /// `denied` is the error message for other users who can see it.
+    async fn authorize_repository_owner(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        denied: &str,
+    ) -> ApplicationResult<RepositoryIndex> {
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Viewer)
+            .await?;
+        if repository.owner_id.as_deref() != context.user_id() && !context.is_admin() {
+            match repository.organization_id {
+                Some(ref organization_id) => {
+                    self.authorize_organization(context, organization_id, OrgRole::Admin)
+                        .await?;
+                }
+                None => return Err(ApplicationError::permission(denied)),
+            }
+        }
+        Ok(repository)
+    }
+
+    /// Check the calling user may access a repository with `role`
+    ///
+    /// Repositories of an organization require membership with at least
+    /// `role`; personal repositories are not restricted further.
+    async fn check_repository_tenant(
+        &self,
+        context: &PermissionContext,
+        repository: &RepositoryIndex,
+        role: OrgRole,
+    ) -> ApplicationResult<()> {
+        let Some(ref organization_id) = repository.organization_id else {
+            return Ok(());
+        };
+
+        self.authorize_organization(context, organization_id, role)
+            .await
+            .map(|_| ())
+            .map_err(|e| match e {
+                ApplicationError::NotFound { .. } => {
+                    ApplicationError::not_found(format!("Repository not found: {}", repository.id))
+                }
+                e => e,
+            })
+    }
+
+    /// Subscribe to repository indexing progress updates
+    pub fn subscribe_to_repository_progress(
+        &self,
+    ) -> tokio::sync::broadcast::Receiver<crate::repository::IndexingUpdate> {
+        self.repository_manager.subscribe_to_progress()
+    }
+
+    /// Check the indexing worker, repository storage, vector store and LLM
+    /// provider, each on its own
+    pub async fn check_readiness(&self) -> Vec<DependencyCheck> {
+        let manager = &self.repository_manager;
+        let (worker, storage, vector_store, llm_provider) = tokio::join!(
+            DependencyCheck::run("indexing_worker", async {
+                if manager.is_worker_healthy().await {
+                    Ok(None)
+                } else {
+                    Err("RAG pipeline failed to initialize".to_string())
+                }
+            }),
+            DependencyCheck::run("repository_storage", async {
+                manager
+                    .check_storage()
+                    .await
+                    .map(|_| None)
+                    .map_err(|e| e.to_string())
+            }),
+            DependencyCheck::run("vector_store", async {
+                let count = manager.loaded_index_count().await;
+                Ok(Some(format!("{} repository indexes loaded", count)))
+            }),
+            DependencyCheck::run(
+                "llm_provider",
+                health::check_llm_provider(&self.config.rag.llm)
+            ),
+        );
+        vec![worker, storage, vector_store, llm_provider]
+    }
+
+    // ========================================
+}
+
+#[cfg(feature = "wiki")]
+impl WikifyApplication {
+    /// Check organization access to the stored wikis of a repository
+    ///
+    /// Wikis of repositories that are no longer registered are not restricted.
+    async fn authorize_repository_artifacts(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        role: OrgRole,
+    ) -> ApplicationResult<()> {
+        match self
+            .repository_manager
+            .get_repository(context, repository_id)
+            .await
+        {
+            Ok(repository) => {
+                self.check_repository_tenant(context, &repository, role)
+                    .await
+            }
+            // Hidden repositories are reported as not found too
+            Err(ApplicationError::NotFound { .. })
+                if !self
+                    .repository_manager
+                    .repository_exists(repository_id)
+                    .await? =>
+            {
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // ========================================
+    // Wiki Generation API
+    // ========================================
+
+    /// Generate the wiki for a repository and persist it
+    ///
+    /// Progress is reported to `progress` until the wiki is stored. The
+    /// stored wiki replaces any previous one.
+    pub async fn generate_wiki(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        config: wikify_wiki::WikiConfig,
+        progress: &dyn wikify_core::ProgressReporter,
+    ) -> ApplicationResult<wikify_wiki::WikiStructure> {
+        self.generate_wiki_cancellable(
+            context,
+            repository_id,
+            config,
+            progress,
+            &wikify_core::CancellationToken::new(),
+        )
+        .await
+    }
+
+    /// Generate and persist the wiki of a repository until `cancel` is
+    /// triggered
+    ///
+    /// A cancelled generation returns a
+    /// [`WikifyError::Cancelled`](wikify_core::WikifyError::Cancelled) core
+    /// error and leaves the stored wiki as it was.
+    pub async fn generate_wiki_cancellable(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        mut config: wikify_wiki::WikiConfig,
+        progress: &dyn wikify_core::ProgressReporter,
+        cancel: &wikify_core::CancellationToken,
+    ) -> ApplicationResult<wikify_wiki::WikiStructure> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::GenerateWiki)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        if config.template.is_none() {
+            config.template = self.wiki_storage.load_template(repository_id).await?;
+        }
+
+        progress.report(
+            &wikify_core::ProgressUpdate::new(wikify_core::ProgressStage::Starting, 0.05)
+                .with_message("Initializing wiki generation"),
+        );
+        let mut wiki_service = wikify_wiki::WikiService::new().map_err(|e| {
+            ApplicationError::internal(format!("Failed to create wiki service: {}", e))
+        })?;
+
+        let mut wiki = wiki_service
+            .generate_wiki_cancellable(&repository.url, &config, progress, cancel)
+            .await
+            .map_err(|e| {
+                if cancel.is_cancelled() {
+                    ApplicationError::Core(wikify_core::WikifyError::cancelled("generate_wiki"))
+                } else {
+                    ApplicationError::internal(format!("Failed to generate wiki: {}", e))
+                }
+            })?;
+
+        progress.report(
+            &wikify_core::ProgressUpdate::new(wikify_core::ProgressStage::Finalizing, 0.95)
+                .with_message("Applying page edits and storing the wiki"),
+        );
+        let mut edits = self.wiki_storage.load_edits(repository_id).await?;
+        if !edits.is_empty() {
+            let conflicted = edits.apply(&mut wiki);
+            self.store_wiki_edits(repository_id, &edits, &conflicted)
+                .await?;
+            wikify_wiki::LinkResolver::new(&wiki).resolve(&mut wiki);
+        }
+        self.store_wiki(repository_id, &wiki).await?;
+
+        tracing::info!(
+            repository_id = %repository_id,
+            pages = wiki.pages.len(),
+            sections = wiki.sections.len(),
+            dangling_links = wiki.link_validation.dangling.len(),
+            "📚 Wiki generated"
+        );
+        telemetry::record(TelemetryEvent::wiki_generated(wiki.pages.len()));
+        if let Err(e) = self
+            .repository_manager
+            .refresh_disk_usage(repository_id)
+            .await
+        {
+            tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to measure repository disk usage"
+            );
+        }
+        self.webhooks
+            .dispatch(WebhookEvent::new(
+                WebhookEventKind::WikiGenerated,
+                repository_id.to_string(),
+                serde_json::json!({
+                    "title": wiki.title,
+                    "pages": wiki.pages.len(),
+                    "sections": wiki.sections.len(),
+                }),
+            ))
+            .await;
+        progress.report(
+            &wikify_core::ProgressUpdate::new(wikify_core::ProgressStage::Completed, 1.0)
+                .with_message(format!("Generated {} wiki pages", wiki.pages.len())),
+        );
+
+        Ok(wiki)
+    }
+
+    /// Purge the wiki generation cache of a repository
+    ///
+    /// The next generation runs from scratch even if the commit and config
+    /// are unchanged. The stored wiki and its versions are kept.
+    pub async fn purge_wiki_cache(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<()> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::GenerateWiki)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        self.wiki_cache
+            .invalidate(&repository.url)
+            .await
+            .map_err(|e| ApplicationError::Core(*e))?;
+
+        tracing::info!(repository_id = %repository_id, "🧹 Wiki cache purged");
+        Ok(())
+    }
+
+    /// Remove the stored wiki, exports, versions and cached wikis of a
+    /// deleted repository
+    async fn remove_wiki_artifacts(&self, repository: &RepositoryIndex) {
+        let repository_id = &repository.id;
+        self.invalidate_wiki_cache(repository).await;
+        if let Err(e) = self.wiki_storage.delete(repository_id).await {
+            tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to remove stored wiki"
+            );
+        }
+        if let Err(e) = self.wiki_exports.delete_repository(repository_id).await {
+            tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to remove wiki exports"
+            );
+        }
+        if let Err(e) = self.wiki_versions.clear_wiki(repository_id).await {
+            tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to remove wiki versions"
+            );
+        }
+    }
+
+    /// Drop cached wikis of a repository whose index changed or is gone
+    async fn invalidate_wiki_cache(&self, repository: &RepositoryIndex) {
+        if let Err(e) = self.wiki_cache.invalidate(&repository.url).await {
+            tracing::warn!(
+                repository_id = %repository.id,
+                error = %e,
+                "Failed to invalidate wiki cache"
+            );
+        }
+    }
+
+    /// Persist a wiki as the current one of a repository and archive it as a version
+    async fn store_wiki(
+        &self,
+        repository_id: &str,
+        wiki: &wikify_wiki::WikiStructure,
+    ) -> ApplicationResult<()> {
+        self.wiki_storage.store(repository_id, wiki).await?;
+        if let Err(e) = self.wiki_versions.store_version(repository_id, wiki).await {
+            tracing::warn!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to archive wiki version"
+            );
+        }
+        Ok(())
+    }
+
+    /// Set the structure template used whenever the repository's wiki is generated
+    pub async fn set_wiki_template(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        template: wikify_wiki::WikiTemplate,
+    ) -> ApplicationResult<()> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::GenerateWiki)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        // Make sure the repository exists
+        self.authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        template
+            .validate()
+            .map_err(|e| ApplicationError::config(e.to_string()))?;
+        self.wiki_storage
+            .store_template(repository_id, &template)
+            .await
+    }
+
+    /// Get the structure template of a repository's wiki, if one was set
+    pub async fn get_wiki_template(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<Option<wikify_wiki::WikiTemplate>> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.wiki_storage.load_template(repository_id).await
+    }
+
+    /// Remove the structure template of a repository's wiki
+    pub async fn remove_wiki_template(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<()> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::GenerateWiki)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Member)
+            .await?;
+
+        self.wiki_storage.delete_template(repository_id).await
+    }
+
+    /// Persist manual page edits after merging them into regenerated pages
+    async fn store_wiki_edits(
+        &self,
+        repository_id: &str,
+        edits: &wikify_wiki::WikiEdits,
+        conflicted: &[String],
+    ) -> ApplicationResult<()> {
+        if !conflicted.is_empty() {
+            tracing::warn!(
+                repository_id = %repository_id,
+                pages = ?conflicted,
+                "Regenerated content conflicts with manual edits; the edited sections were kept"
+            );
+        }
+        self.wiki_storage.store_edits(repository_id, edits).await
+    }
+
+    /// List the archived wiki versions of a repository, newest first
+    pub async fn list_wiki_versions(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<Vec<wikify_wiki::WikiVersion>> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.wiki_versions
+            .list_versions(repository_id)
+            .await
+            .map_err(|e| ApplicationError::Core(*e))
+    }
+
+    /// Get an archived wiki version of a repository by version ID (usually a commit SHA)
+    pub async fn get_wiki_version(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        version_id: &str,
+    ) -> ApplicationResult<Option<wikify_wiki::WikiStructure>> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.wiki_versions
+            .get_version(repository_id, version_id)
+            .await
+            .map_err(|e| ApplicationError::Core(*e))
+    }
+
+    /// Compare two archived wiki versions of a repository
+    ///
+    /// `to` defaults to the newest version and `from` to the version before `to`.
+    pub async fn diff_wiki_versions(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> ApplicationResult<wikify_wiki::WikiDiff> {
+        let versions = self.list_wiki_versions(context, repository_id).await?;
+
+        let to = match to {
+            Some(to) => to.to_string(),
+            None => versions
+                .first()
+                .map(|v| v.version_id.clone())
+                .ok_or_else(|| {
+                    ApplicationError::not_found(format!(
+                        "No wiki versions for repository: {}",
+                        repository_id
+                    ))
+                })?,
+        };
+        let from = match from {
+            Some(from) => from.to_string(),
+            None => versions
+                .iter()
+                .skip_while(|v| v.version_id != to)
+                .nth(1)
+                .map(|v| v.version_id.clone())
+                .ok_or_else(|| {
+                    ApplicationError::not_found(format!(
+                        "No wiki version older than {} to compare with",
+                        to
+                    ))
+                })?,
+        };
+
+        let mut wikis = Vec::with_capacity(2);
+        for version_id in [&from, &to] {
+            let wiki = self
+                .get_wiki_version(context, repository_id, version_id)
+                .await?
+                .ok_or_else(|| {
+                    ApplicationError::not_found(format!("Wiki version not found: {}", version_id))
+                })?;
+            wikis.push(wiki);
+        }
+
+        Ok(wikify_wiki::WikiDiff::between(&wikis[0], &wikis[1]))
+    }
+
+    /// Get the stored wiki for a repository, if one has been generated
+    pub async fn get_wiki(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<Option<wikify_wiki::WikiStructure>> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.wiki_storage.load(repository_id).await
+    }
+
+    /// Regenerate a single page of the stored wiki of a repository
+    ///
+    /// Useful for redoing a weak page without regenerating the whole wiki;
+    /// `instructions` are passed to the LLM alongside the usual page prompt.
+    pub async fn regenerate_wiki_page(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        page_id: &str,
+        instructions: Option<String>,
+    ) -> ApplicationResult<wikify_wiki::WikiPage> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::GenerateWiki)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let repository = self
+            .authorize_repository(context, repository_id, OrgRole::Member)
+            .await?;
+
+        let mut wiki = self
+            .wiki_storage
+            .load(repository_id)
+            .await?
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!(
+                    "No wiki has been generated for repository: {}",
+                    repository_id
+                ))
+            })?;
+        if !wiki.pages.iter().any(|p| p.id == page_id) {
+            return Err(ApplicationError::not_found(format!(
+                "Wiki page not found: {}",
+                page_id
+            )));
+        }
+
+        let mut wiki_service = wikify_wiki::WikiService::new().map_err(|e| {
+            ApplicationError::internal(format!("Failed to create wiki service: {}", e))
+        })?;
+        let mut page = wiki_service
+            .regenerate_page(&mut wiki, page_id, &repository.url, instructions.as_deref())
+            .await
+            .map_err(|e| {
+                ApplicationError::internal(format!("Failed to regenerate wiki page: {}", e))
+            })?;
+
+        // Keep manual edits of the page on top of the regenerated content
+        let mut edits = self.wiki_storage.load_edits(repository_id).await?;
+        if edits.pages.contains_key(page_id) {
+            let conflicted = if edits.apply_to_page(&mut page) {
+                vec![page.id.clone()]
+            } else {
+                Vec::new()
+            };
+            self.store_wiki_edits(repository_id, &edits, &conflicted)
+                .await?;
+            if let Some(stored) = wiki.pages.iter_mut().find(|p| p.id == page_id) {
+                *stored = page.clone();
+            }
+            wikify_wiki::LinkResolver::new(&wiki).resolve(&mut wiki);
+            if let Some(resolved) = wiki.pages.iter().find(|p| p.id == page_id) {
+                page = resolved.clone();
+            }
+        }
+
+        self.store_wiki(repository_id, &wiki).await?;
+
+        tracing::info!(
+            repository_id = %repository_id,
+            page_id = %page_id,
+            "📝 Wiki page regenerated"
+        );
+
+        Ok(page)
+    }
+
+    /// Replace the content (and optionally the title) of a wiki page by hand
+    ///
+    /// The edit is stored separately from the generated content and merged
+    /// into the page whenever the wiki or the page is regenerated.
+    pub async fn edit_wiki_page(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        page_id: &str,
+        title: Option<String>,
+        content: String,
+    ) -> ApplicationResult<wikify_wiki::WikiPage> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::GenerateWiki)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Member)
+            .await?;
+
+        let mut wiki = self
+            .wiki_storage
+            .load(repository_id)
+            .await?
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!(
+                    "No wiki has been generated for repository: {}",
+                    repository_id
+                ))
+            })?;
+        let page = wiki
+            .pages
+            .iter_mut()
+            .find(|p| p.id == page_id)
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!("Wiki page not found: {}", page_id))
+            })?;
+
+        let mut edits = self.wiki_storage.load_edits(repository_id).await?;
+        edits.edit_page(page, title, content, context.user_id().map(str::to_string));
+
+        // Links in the edited content are resolved like generated ones
+        wikify_wiki::LinkResolver::new(&wiki).resolve(&mut wiki);
+        let page = wiki
+            .pages
+            .iter()
+            .find(|p| p.id == page_id)
+            .cloned()
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!("Wiki page not found: {}", page_id))
+            })?;
+
+        self.wiki_storage.store_edits(repository_id, &edits).await?;
+        self.store_wiki(repository_id, &wiki).await?;
+
+        tracing::info!(
+            repository_id = %repository_id,
+            page_id = %page_id,
+            "✏️ Wiki page edited"
+        );
+
+        Ok(page)
+    }
+
+    /// Export the stored wiki of a repository to a single file
+    ///
+    /// Only single-file formats (JSON and PDF) can be exported this way.
+    pub async fn export_wiki(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        format: wikify_wiki::ExportFormat,
+    ) -> ApplicationResult<ExportedWiki> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Export)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        if !format.is_single_file() {
+            return Err(ApplicationError::config(format!(
+                "{:?} export produces a directory and cannot be downloaded as a single file",
+                format
+            )));
+        }
+
+        let wiki = self
+            .wiki_storage
+            .load(repository_id)
+            .await?
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!(
+                    "No wiki has been generated for repository: {}",
+                    repository_id
+                ))
+            })?;
+
+        let export_dir = self
+            .get_base_path()
+            .join("exports")
+            .join(uuid::Uuid::new_v4().to_string());
+        let output_path = export_dir.join(format!("wiki.{}", format.file_extension()));
+
+        let exporter = wikify_wiki::WikiExporter::new().map_err(|e| ApplicationError::Core(*e))?;
+        let result = exporter
+            .export(&wiki, format, &output_path.to_string_lossy())
+            .await;
+        let data = match result {
+            Ok(()) => tokio::fs::read(&output_path)
+                .await
+                .map_err(ApplicationError::Io),
+            Err(e) => Err(ApplicationError::Core(*e)),
+        };
+        let _ = tokio::fs::remove_dir_all(&export_dir).await;
+
+        let content_type = match format {
+            wikify_wiki::ExportFormat::Pdf => "application/pdf",
+            _ => "application/json",
+        };
+
+        Ok(ExportedWiki {
+            file_name: format!(
+                "{}.{}",
+                sanitize_file_name(&wiki.title),
+                format.file_extension()
+            ),
+            content_type,
+            data: data?,
+        })
+    }
+
+    /// Export the stored wiki of a repository to a file kept for download
+    ///
+    /// Directory formats (HTML and Markdown) are zipped. The artifact is
+    /// fetched with [`Self::get_wiki_export`] and removed after
+    /// [`wiki::EXPORT_RETENTION`].
+    pub async fn create_wiki_export(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        format: wikify_wiki::ExportFormat,
+    ) -> ApplicationResult<WikiExportJob> {
+        self.permission_manager
+            .check_permission(context, &Permission::Export)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        let wiki = self
+            .wiki_storage
+            .load(repository_id)
+            .await?
+            .ok_or_else(|| {
+                ApplicationError::not_found(format!(
+                    "No wiki has been generated for repository: {}",
+                    repository_id
+                ))
+            })?;
+
+        if let Err(e) = self.wiki_exports.prune(wiki::EXPORT_RETENTION).await {
+            tracing::warn!(error = %e, "Failed to prune old wiki exports");
+        }
+
+        self.wiki_exports
+            .create(
+                repository_id,
+                &wiki,
+                format,
+                &sanitize_file_name(&wiki.title),
+            )
+            .await
+    }
+
+    /// Look up an export of a repository's wiki for download
+    pub async fn get_wiki_export(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        job_id: &str,
+    ) -> ApplicationResult<WikiExportJob> {
+        self.permission_manager
+            .check_permission(context, &Permission::Export)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        self.authorize_repository_artifacts(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        self.wiki_exports
+            .get(job_id)
+            .await?
+            .filter(|job| job.repository_id == repository_id)
+            .ok_or_else(|| ApplicationError::not_found(format!("Export not found: {}", job_id)))
+    }
+}
+
+#[cfg(feature = "research")]
+impl WikifyApplication {
+    // ========================================
+    // Research Engine API
+    // ========================================
+
+    /// Start research on a repository
+    pub async fn start_research(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        research_question: String,
+        config: Option<research::ResearchConfig>,
+    ) -> ApplicationResult<String> {
+        self.start_research_cancellable(
+            context,
+            repository_id,
+            research_question,
+            config,
+            &wikify_core::CancellationToken::new(),
+        )
+        .await
+    }
+
+    /// Start research on a repository that stops once `cancel` is triggered
+    pub async fn start_research_cancellable(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        research_question: String,
+        config: Option<research::ResearchConfig>,
+        cancel: &wikify_core::CancellationToken,
+    ) -> ApplicationResult<String> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        // Verify repository exists
+        self.authorize_repository(context, repository_id, OrgRole::Viewer)
+            .await?;
+
+        if let Some(ref engine) = self.research_engine {
+            let research_config = config.unwrap_or_default();
+            engine
+                .start_research(
+                    context,
+                    repository_id,
+                    research_question,
+                    Some(research_config),
+                    cancel,
+                )
+                .await
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
+    }
+
+    /// Execute a research iteration
+    pub async fn research_iteration(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        research_session_id: &str,
+    ) -> ApplicationResult<research::ResearchProgress> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        if let Some(ref engine) = self.research_engine {
+            let session = engine.get_session(research_session_id).await?;
+            if session.repository_id != repository_id {
+                return Err(ApplicationError::not_found(format!(
+                    "Research session {} not found for repository {}",
+                    research_session_id, repository_id
+                )));
+            }
+        }
+
+        // In the simplified implementation, research is automatic
+        // Just return the current progress
+        self.get_research_progress(context, research_session_id)
+            .await
+    }
+
+    /// Get the most recently started research session for a repository
+    pub async fn latest_research_session(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<String> {
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        if let Some(ref engine) = self.research_engine {
+            engine
+                .latest_session_for_repository(repository_id)
+                .await
+                .ok_or_else(|| {
+                    ApplicationError::not_found(format!(
+                        "No research session found for repository: {}",
+                        repository_id
+                    ))
+                })
+        } else {
+            Err(ApplicationError::Research {
                 message: "Research engine is not available".to_string(),
             })
         }
     }
 
-    /// Subscribe to repository indexing progress updates
-    pub fn subscribe_to_repository_progress(
+    /// Get research progress
+    pub async fn get_research_progress(
         &self,
-    ) -> tokio::sync::broadcast::Receiver<crate::repository::IndexingUpdate> {
-        self.repository_manager.subscribe_to_progress()
-    }
+        context: &PermissionContext,
+        research_session_id: &str,
+    ) -> ApplicationResult<research::ResearchProgress> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-    // ========================================
-}
+        if let Some(ref engine) = self.research_engine {
+            engine.get_research_progress(research_session_id).await
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
+    }
 
-/// Prelude module for convenient imports
-pub mod prelude {
-    pub use super::{
-        ApplicationConfig, ApplicationError, ApplicationResult, PermissionContext, RepositoryQuery,
-        RepositoryQueryResponse, UserIdentity, WikifyApplication,
-    };
-}
+    /// Subscribe to progress updates of a research session
+    ///
+    /// Returns the current progress along with a receiver for later updates.
+    /// The receiver carries updates for every session, so callers filter by ID.
+    pub async fn subscribe_to_research_progress(
+        &self,
+        context: &PermissionContext,
+        research_session_id: &str,
+    ) -> ApplicationResult<(
+        research::ResearchProgress,
+        tokio::sync::broadcast::Receiver<research::ResearchProgress>,
+    )> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-impl WikifyApplication {
-    /// Create a Wikify application for web deployment (open mode)
-    pub async fn create_web_application() -> ApplicationResult<WikifyApplication> {
-        Self::builder(ApplicationConfig::web_open()).build().await
+        if let Some(ref engine) = self.research_engine {
+            // Subscribe before reading the current progress so no update is missed
+            let receiver = engine.subscribe_to_progress();
+            let progress = engine.get_research_progress(research_session_id).await?;
+            Ok((progress, receiver))
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
     }
 
-    /// Create a Wikify application for web deployment (restricted mode)
-    pub async fn create_web_restricted_application() -> ApplicationResult<WikifyApplication> {
-        Self::builder(ApplicationConfig::web_restricted())
-            .build()
+    /// List active research processes
+    pub async fn list_active_research(
+        &self,
+        context: &PermissionContext,
+    ) -> ApplicationResult<Vec<String>> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
             .await
-    }
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
-    /// Create a Wikify application for CLI usage
-    pub async fn create_cli_application() -> ApplicationResult<WikifyApplication> {
-        Self::builder(ApplicationConfig::cli_local()).build().await
+        if let Some(ref engine) = self.research_engine {
+            Ok(engine.list_active_research().await)
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
     }
 
-    /// Create a minimal Wikify application (no research, no history)
-    pub async fn create_minimal() -> ApplicationResult<WikifyApplication> {
-        Self::builder(ApplicationConfig::default())
-            .with_research(false)
-            .with_history(false)
-            .build()
+    /// Get research details
+    pub async fn get_research_details(
+        &self,
+        context: &PermissionContext,
+        research_id: &str,
+    ) -> ApplicationResult<research::ResearchContext> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
             .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        if let Some(ref engine) = self.research_engine {
+            let session = engine.get_session(research_id).await?;
+            Ok(research::ResearchContext {
+                id: session.id,
+                repository_id: session.repository_id,
+                topic: session.query,
+                config: session.config,
+                questions: vec![],
+                findings: vec![],
+                iterations: vec![],
+                status: session.status,
+                created_at: session.started_at,
+                updated_at: session.completed_at.unwrap_or_else(chrono::Utc::now),
+            })
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
     }
 
-    /// Create a research-focused application with custom history directory
-    pub async fn create_research_focused<P: Into<std::path::PathBuf>>(
-        history_dir: P,
-    ) -> ApplicationResult<WikifyApplication> {
-        Self::builder(ApplicationConfig::default())
-            .with_research(true)
-            .with_history(true)
-            .with_history_dir(history_dir)
-            .build()
+    /// Get the result of a research session, once it has completed
+    pub async fn get_research_result(
+        &self,
+        context: &PermissionContext,
+        research_id: &str,
+    ) -> ApplicationResult<Option<wikify_rag::DeepResearchResult>> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
             .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        if let Some(ref engine) = self.research_engine {
+            engine.get_research_result(research_id).await
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
     }
 
-    /// Create a web application with custom research configuration
-    pub async fn create_web_with_research(
-        research_config: ResearchConfig,
-    ) -> ApplicationResult<WikifyApplication> {
-        Self::builder(ApplicationConfig::web_open())
-            .with_research(true)
-            .with_research_config(research_config)
-            .build()
+    /// Cancel research
+    pub async fn cancel_research(
+        &self,
+        context: &PermissionContext,
+        research_id: &str,
+    ) -> ApplicationResult<()> {
+        // Check permissions
+        self.permission_manager
+            .check_permission(context, &Permission::Query)
             .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        if let Some(ref engine) = self.research_engine {
+            engine.stop_research(research_id).await
+        } else {
+            Err(ApplicationError::Research {
+                message: "Research engine is not available".to_string(),
+            })
+        }
     }
 
     // ========================================
@@ -711,6 +3320,8 @@ impl WikifyApplication {
     ) -> ApplicationResult<Vec<research::ResearchTemplate>> {
         Ok(self
             .template_manager
+            .read()
+            .await
             .list_templates()
             .into_iter()
             .cloned()
@@ -723,6 +3334,8 @@ impl WikifyApplication {
         template_id: &str,
     ) -> ApplicationResult<research::ResearchTemplate> {
         self.template_manager
+            .read()
+            .await
             .get_template(template_id)
             .cloned()
             .ok_or_else(|| {
@@ -737,12 +3350,104 @@ impl WikifyApplication {
     ) -> ApplicationResult<Vec<research::ResearchTemplate>> {
         Ok(self
             .template_manager
+            .read()
+            .await
             .list_templates_by_category(&category)
             .into_iter()
             .cloned()
             .collect())
     }
 
+    /// Add a user-defined research template
+    pub async fn create_research_template(
+        &self,
+        context: &PermissionContext,
+        template: research::ResearchTemplate,
+    ) -> ApplicationResult<research::ResearchTemplate> {
+        self.permission_manager
+            .check_permission(context, &Permission::DeepResearch)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        template.validate()?;
+
+        let mut manager = self.template_manager.write().await;
+        if manager.get_template(&template.id).is_some() {
+            return Err(ApplicationError::config(format!(
+                "Research template already exists: {}",
+                template.id
+            )));
+        }
+        manager.add_template(template.clone());
+        self.template_storage
+            .store(&manager.custom_templates())
+            .await?;
+        Ok(template)
+    }
+
+    /// Replace a user-defined research template
+    pub async fn update_research_template(
+        &self,
+        context: &PermissionContext,
+        template_id: &str,
+        mut template: research::ResearchTemplate,
+    ) -> ApplicationResult<research::ResearchTemplate> {
+        self.permission_manager
+            .check_permission(context, &Permission::DeepResearch)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        template.id = template_id.to_string();
+        template.validate()?;
+
+        let mut manager = self.template_manager.write().await;
+        Self::check_custom_template(&manager, template_id)?;
+        manager.add_template(template.clone());
+        self.template_storage
+            .store(&manager.custom_templates())
+            .await?;
+        Ok(template)
+    }
+
+    /// Delete a user-defined research template
+    pub async fn delete_research_template(
+        &self,
+        context: &PermissionContext,
+        template_id: &str,
+    ) -> ApplicationResult<()> {
+        self.permission_manager
+            .check_permission(context, &Permission::DeepResearch)
+            .await
+            .map_err(|msg| ApplicationError::Permission { message: msg })?;
+
+        let mut manager = self.template_manager.write().await;
+        Self::check_custom_template(&manager, template_id)?;
+        manager.remove_template(template_id);
+        self.template_storage
+            .store(&manager.custom_templates())
+            .await
+    }
+
+    /// Make sure a template exists and is not built in
+    fn check_custom_template(
+        manager: &ResearchTemplateManager,
+        template_id: &str,
+    ) -> ApplicationResult<()> {
+        if manager.is_builtin(template_id) {
+            return Err(ApplicationError::config(format!(
+                "Built-in research template cannot be modified: {}",
+                template_id
+            )));
+        }
+        if manager.get_template(template_id).is_none() {
+            return Err(ApplicationError::not_found(format!(
+                "Research template not found: {}",
+                template_id
+            )));
+        }
+        Ok(())
+    }
+
     /// Start research from a template
     pub async fn start_research_from_template(
         &self,
@@ -802,20 +3507,21 @@ impl WikifyApplication {
         context: &PermissionContext,
         filters: Option<research::ResearchHistoryFilters>,
         limit: Option<usize>,
-    ) -> ApplicationResult<Vec<research::ResearchHistoryRecord>> {
+    ) -> ApplicationResult<Page<research::ResearchHistoryRecord>> {
         self.permission_manager
             .check_permission(context, &Permission::Query)
             .await
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
         if let Some(ref storage) = self.history_storage {
+            let scope = self.tenant_scope(context).await?;
             let mut search_filters = filters.unwrap_or_default();
             if let Some(limit_val) = limit {
                 search_filters.limit = Some(limit_val);
             }
-            storage.list_records(&search_filters).await
+            storage.list_records(&scope, &search_filters).await
         } else {
-            Ok(vec![])
+            Ok(PageRequest::all().paginate(Vec::new()))
         }
     }
 
@@ -838,8 +3544,10 @@ impl WikifyApplication {
                 ..Default::default()
             };
 
-            let records = storage.list_records(&filters).await?;
+            let scope = self.tenant_scope(context).await?;
+            let records = storage.list_records(&scope, &filters).await?;
             records
+                .items
                 .into_iter()
                 .find(|r| r.context.repository_id == repository_id)
                 .ok_or_else(|| ApplicationError::NotFound {
@@ -885,7 +3593,8 @@ impl WikifyApplication {
             .map_err(|msg| ApplicationError::Permission { message: msg })?;
 
         if let Some(ref storage) = self.history_storage {
-            storage.get_statistics().await
+            let scope = self.tenant_scope(context).await?;
+            storage.get_statistics(&scope).await
         } else {
             // Return empty statistics if storage is not available
             Ok(research::ResearchStatistics {
@@ -899,6 +3608,86 @@ impl WikifyApplication {
             })
         }
     }
+}
+
+/// Make a wiki title safe to use as a download file name
+#[cfg(feature = "wiki")]
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.is_empty() {
+        "wiki".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// Prelude module for convenient imports
+pub mod prelude {
+    pub use super::{
+        ApplicationConfig, ApplicationError, ApplicationResult, PermissionContext, RepositoryQuery,
+        RepositoryQueryResponse, UserIdentity, WikifyApplication,
+    };
+}
+
+impl WikifyApplication {
+    /// Create a Wikify application for web deployment (open mode)
+    pub async fn create_web_application() -> ApplicationResult<WikifyApplication> {
+        Self::builder(ApplicationConfig::web_open()).build().await
+    }
+
+    /// Create a Wikify application for web deployment (restricted mode)
+    pub async fn create_web_restricted_application() -> ApplicationResult<WikifyApplication> {
+        Self::builder(ApplicationConfig::web_restricted())
+            .build()
+            .await
+    }
+
+    /// Create a Wikify application for CLI usage
+    pub async fn create_cli_application() -> ApplicationResult<WikifyApplication> {
+        Self::builder(ApplicationConfig::cli_local()).build().await
+    }
+
+    /// Create a minimal Wikify application (no research, no history)
+    pub async fn create_minimal() -> ApplicationResult<WikifyApplication> {
+        let builder = Self::builder(ApplicationConfig::default());
+        #[cfg(feature = "research")]
+        let builder = builder.with_research(false).with_history(false);
+        builder.build().await
+    }
+
+    /// Create a research-focused application with custom history directory
+    #[cfg(feature = "research")]
+    pub async fn create_research_focused<P: Into<std::path::PathBuf>>(
+        history_dir: P,
+    ) -> ApplicationResult<WikifyApplication> {
+        Self::builder(ApplicationConfig::default())
+            .with_research(true)
+            .with_history(true)
+            .with_history_dir(history_dir)
+            .build()
+            .await
+    }
+
+    /// Create a web application with custom research configuration
+    #[cfg(feature = "research")]
+    pub async fn create_web_with_research(
+        research_config: ResearchConfig,
+    ) -> ApplicationResult<WikifyApplication> {
+        Self::builder(ApplicationConfig::web_open())
+            .with_research(true)
+            .with_research_config(research_config)
+            .build()
+            .await
+    }
 
     // ========================================
     // File Operations API
@@ -947,17 +3736,28 @@ impl WikifyApplication {
 
         let clone_depth = metadata.get("clone_depth").and_then(|d| d.parse().ok());
 
+        let mirror_urls = metadata
+            .get("mirror_urls")
+            .map(|urls| repository::parse_mirror_urls(urls))
+            .unwrap_or_default();
+
         wikify_core::RepositoryAccessConfig {
             preferred_mode,
             api_token: metadata.get("api_token").cloned(),
             force_mode,
             clone_depth,
             custom_local_path: metadata.get("custom_local_path").cloned(),
+            mirror_urls,
         }
     }
 
     /// Get base path for repository operations
-    fn get_base_path(&self) -> std::path::PathBuf {
+    pub fn get_base_path(&self) -> std::path::PathBuf {
+        Self::base_path()
+    }
+
+    /// Base path from `WIKIFY_BASE_DIR`, defaulting to `~/.wikify`
+    fn base_path() -> std::path::PathBuf {
         std::env::var("WIKIFY_BASE_DIR")
             .map(std::path::PathBuf::from)
             .unwrap_or_else(|_| {
@@ -986,8 +3786,7 @@ impl WikifyApplication {
 
         // Get repository info from storage
         let repository = self
-            .repository_manager
-            .get_repository(context, repository_id)
+            .authorize_repository(context, repository_id, OrgRole::Viewer)
             .await?;
 
         // Create repository access configuration from metadata
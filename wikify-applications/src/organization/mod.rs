@@ -0,0 +1,12 @@
+//! Organizations and multi-tenancy
+//!
+//! Repositories, wikis and research history belong either to a single user
+//! or to an organization. Members of an organization share its resources,
+//! with their role deciding what they may change. Storage queries are
+//! limited to one tenant through a [`TenantScope`].
+
+pub mod storage;
+pub mod types;
+
+pub use storage::*;
+pub use types::*;
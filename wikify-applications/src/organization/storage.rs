@@ -0,0 +1,167 @@
+//! Organization storage
+
+use super::types::Organization;
+use crate::{ApplicationError, ApplicationResult};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// Organization storage interface
+#[allow(async_fn_in_trait)]
+pub trait OrganizationStorage: Send + Sync {
+    /// Save or update an organization
+    async fn save_organization(&self, organization: &Organization) -> ApplicationResult<()>;
+
+    /// Load an organization by ID
+    async fn load_organization(&self, id: &str) -> ApplicationResult<Option<Organization>>;
+
+    /// List organizations, optionally only those the user is a member of
+    async fn list_organizations(
+        &self,
+        member_id: Option<&str>,
+    ) -> ApplicationResult<Vec<Organization>>;
+
+    /// Delete an organization
+    async fn delete_organization(&self, id: &str) -> ApplicationResult<()>;
+}
+
+/// File-based organization storage
+///
+/// All organizations are kept in memory and written to a single JSON file
+/// on every change.
+#[derive(Debug)]
+pub struct FileOrganizationStorage {
+    path: PathBuf,
+    organizations: RwLock<HashMap<String, Organization>>,
+}
+
+impl FileOrganizationStorage {
+    /// Open the storage, loading organizations saved at `path` (the file is
+    /// created on first write)
+    pub async fn open<P: AsRef<Path>>(path: P) -> ApplicationResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let organizations: Vec<Organization> = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => {
+                serde_json::from_str(&content).map_err(ApplicationError::Serialization)?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+            Err(e) => return Err(ApplicationError::Io(e)),
+        };
+
+        Ok(Self {
+            path,
+            organizations: RwLock::new(
+                organizations
+                    .into_iter()
+                    .map(|org| (org.id.clone(), org))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Write all organizations to the storage file
+    async fn persist(
+        &self,
+        organizations: &HashMap<String, Organization>,
+    ) -> ApplicationResult<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(ApplicationError::Io)?;
+        }
+
+        let mut sorted: Vec<&Organization> = organizations.values().collect();
+        sorted.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        let content =
+            serde_json::to_string_pretty(&sorted).map_err(ApplicationError::Serialization)?;
+
+        // Write to a temporary file first so readers never see a partial file
+        let temp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, content)
+            .await
+            .map_err(ApplicationError::Io)?;
+        tokio::fs::rename(&temp_path, &self.path)
+            .await
+            .map_err(ApplicationError::Io)?;
+        Ok(())
+    }
+}
+
+impl OrganizationStorage for FileOrganizationStorage {
+    async fn save_organization(&self, organization: &Organization) -> ApplicationResult<()> {
+        let mut organizations = self.organizations.write().await;
+        organizations.insert(organization.id.clone(), organization.clone());
+        self.persist(&organizations).await?;
+
+        debug!("Saved organization: {}", organization.id);
+        Ok(())
+    }
+
+    async fn load_organization(&self, id: &str) -> ApplicationResult<Option<Organization>> {
+        Ok(self.organizations.read().await.get(id).cloned())
+    }
+
+    async fn list_organizations(
+        &self,
+        member_id: Option<&str>,
+    ) -> ApplicationResult<Vec<Organization>> {
+        let mut organizations: Vec<Organization> = self
+            .organizations
+            .read()
+            .await
+            .values()
+            .filter(|org| member_id.map_or(true, |user_id| org.role_of(user_id).is_some()))
+            .cloned()
+            .collect();
+        organizations.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(organizations)
+    }
+
+    async fn delete_organization(&self, id: &str) -> ApplicationResult<()> {
+        let mut organizations = self.organizations.write().await;
+        if organizations.remove(id).is_none() {
+            return Err(ApplicationError::NotFound {
+                message: format!("Organization not found: {}", id),
+            });
+        }
+        self.persist(&organizations).await?;
+
+        debug!("Deleted organization: {}", id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::organization::OrgRole;
+
+    #[tokio::test]
+    async fn test_organizations_survive_reopen() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("organizations.json");
+
+        let storage = FileOrganizationStorage::open(&path).await.unwrap();
+        let mut org = Organization::new("Acme".to_string(), "alice".to_string());
+        org.set_member("bob".to_string(), OrgRole::Viewer);
+        storage.save_organization(&org).await.unwrap();
+        storage
+            .save_organization(&Organization::new("Other".to_string(), "carol".to_string()))
+            .await
+            .unwrap();
+
+        let reopened = FileOrganizationStorage::open(&path).await.unwrap();
+        let loaded = reopened.load_organization(&org.id).await.unwrap().unwrap();
+        assert_eq!(loaded.role_of("bob"), Some(OrgRole::Viewer));
+
+        let bobs = reopened.list_organizations(Some("bob")).await.unwrap();
+        assert_eq!(bobs.len(), 1);
+        assert_eq!(bobs[0].name, "Acme");
+        assert_eq!(reopened.list_organizations(None).await.unwrap().len(), 2);
+
+        reopened.delete_organization(&org.id).await.unwrap();
+        assert!(reopened.delete_organization(&org.id).await.is_err());
+        assert!(reopened.load_organization(&org.id).await.unwrap().is_none());
+    }
+}
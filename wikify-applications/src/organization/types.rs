@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+/// Role of a member inside an organization
+///
+/// Roles are ordered: every role can do everything the roles before it can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OrgRole {
+    /// Can read repositories, wikis and research history
+    Viewer,
+    /// Can also add and index repositories, generate wikis and run research
+    Member,
+    /// Can also delete repositories and manage members
+    Admin,
+    /// Can also delete the organization
+    Owner,
+}
+
+impl OrgRole {
+    /// Whether the role may change the organization's resources
+    pub fn can_write(&self) -> bool {
+        *self >= OrgRole::Member
+    }
+
+    /// Whether the role may manage members
+    pub fn can_manage(&self) -> bool {
+        *self >= OrgRole::Admin
+    }
+}
+
+impl std::fmt::Display for OrgRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrgRole::Viewer => write!(f, "viewer"),
+            OrgRole::Member => write!(f, "member"),
+            OrgRole::Admin => write!(f, "admin"),
+            OrgRole::Owner => write!(f, "owner"),
+        }
+    }
+}
+
+/// Member of an organization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct OrgMember {
+    /// User ID of the member
+    pub user_id: String,
+    /// Role of the member
+    pub role: OrgRole,
+    /// When the user joined the organization
+    pub joined_at: DateTime<Utc>,
+}
+
+/// Organization owning repositories, wikis and research history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Organization {
+    /// Unique organization ID
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// When the organization was created
+    pub created_at: DateTime<Utc>,
+    /// Members and their roles
+    pub members: Vec<OrgMember>,
+}
+
+impl Organization {
+    /// Create an organization with the given user as its owner
+    pub fn new(name: String, owner_id: String) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            created_at: now,
+            members: vec![OrgMember {
+                user_id: owner_id,
+                role: OrgRole::Owner,
+                joined_at: now,
+            }],
+        }
+    }
+
+    /// Role of a user, if the user is a member
+    pub fn role_of(&self, user_id: &str) -> Option<OrgRole> {
+        self.members
+            .iter()
+            .find(|member| member.user_id == user_id)
+            .map(|member| member.role)
+    }
+
+    /// Add a member or change the role of an existing one
+    pub fn set_member(&mut self, user_id: String, role: OrgRole) {
+        match self.members.iter_mut().find(|m| m.user_id == user_id) {
+            Some(member) => member.role = role,
+            None => self.members.push(OrgMember {
+                user_id,
+                role,
+                joined_at: Utc::now(),
+            }),
+        }
+    }
+
+    /// Remove a member, returning whether the user was a member
+    pub fn remove_member(&mut self, user_id: &str) -> bool {
+        let before = self.members.len();
+        self.members.retain(|member| member.user_id != user_id);
+        self.members.len() != before
+    }
+
+    /// Number of members with the owner role
+    pub fn owner_count(&self) -> usize {
+        self.members
+            .iter()
+            .filter(|member| member.role == OrgRole::Owner)
+            .count()
+    }
+}
+
+/// Tenant whose resources a storage query may return
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantScope {
+    /// Resources of every tenant (open and local mode, admins)
+    All,
+    /// Resources belonging to an organization
+    Organization(String),
+    /// Personal resources of a user, outside any organization
+    Personal(String),
+}
+
+impl TenantScope {
+    /// Whether a resource with the given owner and organization is in scope
+    pub fn matches(&self, owner_id: Option<&str>, organization_id: Option<&str>) -> bool {
+        match self {
+            TenantScope::All => true,
+            TenantScope::Organization(org_id) => organization_id == Some(org_id.as_str()),
+            TenantScope::Personal(user_id) => {
+                organization_id.is_none() && owner_id == Some(user_id.as_str())
+            }
+        }
+    }
+
+    /// Organization of the scope, if any
+    pub fn organization_id(&self) -> Option<&str> {
+        match self {
+            TenantScope::Organization(org_id) => Some(org_id),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_and_scopes() {
+        let mut org = Organization::new("Acme".to_string(), "alice".to_string());
+        org.set_member("bob".to_string(), OrgRole::Viewer);
+        org.set_member("bob".to_string(), OrgRole::Member);
+
+        assert_eq!(org.role_of("alice"), Some(OrgRole::Owner));
+        assert_eq!(org.role_of("bob"), Some(OrgRole::Member));
+        assert_eq!(org.members.len(), 2);
+        assert!(OrgRole::Member.can_write() && !OrgRole::Member.can_manage());
+        assert!(!OrgRole::Viewer.can_write());
+
+        let scope = TenantScope::Organization(org.id.clone());
+        assert!(scope.matches(Some("bob"), Some(&org.id)));
+        assert!(!scope.matches(Some("bob"), None));
+
+        let personal = TenantScope::Personal("bob".to_string());
+        assert!(personal.matches(Some("bob"), None));
+        assert!(!personal.matches(Some("bob"), Some(&org.id)));
+        assert!(!personal.matches(Some("alice"), None));
+        assert!(TenantScope::All.matches(None, Some(&org.id)));
+
+        assert!(org.remove_member("bob"));
+        assert!(!org.remove_member("bob"));
+        assert_eq!(org.owner_count(), 1);
+    }
+}
@@ -0,0 +1,117 @@
+//! Pagination of list results
+//!
+//! Listings take a [`PageRequest`] and return a [`Page`] with the total
+//! number of items, so clients can page through long lists.
+
+use serde::{Deserialize, Serialize};
+
+/// Which part of a list to return
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRequest {
+    /// Maximum number of items, or all remaining items when unset
+    pub limit: Option<usize>,
+    /// Number of items to skip
+    pub offset: usize,
+}
+
+impl PageRequest {
+    pub fn new(limit: usize, offset: usize) -> Self {
+        Self {
+            limit: Some(limit),
+            offset,
+        }
+    }
+
+    /// The whole list
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Cut this page out of a list that is already in order
+    pub fn paginate<T>(&self, items: Vec<T>) -> Page<T> {
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(self.offset)
+            .take(self.limit.unwrap_or(usize::MAX))
+            .collect();
+        Page {
+            items,
+            total,
+            limit: self.limit,
+            offset: self.offset,
+        }
+    }
+}
+
+/// One page of a list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Number of items in the whole list
+    pub total: usize,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+/// Direction a list is sorted in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    /// Newest first for date fields
+    #[default]
+    Desc,
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortOrder::Asc => write!(f, "asc"),
+            SortOrder::Desc => write!(f, "desc"),
+        }
+    }
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Unknown sort order: {}", s)),
+        }
+    }
+}
+
+impl<T> Page<T> {
+    /// Convert the items, keeping the page position
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            limit: self.limit,
+            offset: self.offset,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_keeps_total() {
+        let page = PageRequest::new(2, 3).paginate((0..6).collect());
+        assert_eq!(page.items, vec![3, 4]);
+        assert_eq!(page.total, 6);
+
+        let past_end = PageRequest::new(2, 10).paginate((0..6).collect::<Vec<i32>>());
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.total, 6);
+
+        assert_eq!(PageRequest::all().paginate(vec![1, 2]).items.len(), 2);
+    }
+}
@@ -3,8 +3,11 @@
 //! Provides configuration types and monitoring capabilities for the
 //! repository management system.
 
+use crate::stats::{OperationStats, RepositoryOperationStats};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::Duration;
 
 /// Configuration for the repository manager
@@ -13,6 +16,11 @@ pub struct RepositoryManagerConfig {
     /// Maximum number of concurrent indexing operations
     pub max_concurrent_indexing: usize,
 
+    /// Number of files loaded and split at the same time within one
+    /// indexing operation (None = one per CPU)
+    #[serde(default)]
+    pub indexing_workers: Option<usize>,
+
     /// Maximum number of concurrent query operations
     pub max_concurrent_queries: usize,
 
@@ -52,6 +60,11 @@ pub struct RepositoryManagerConfig {
     /// Enable automatic recovery for stuck operations
     pub enable_auto_recovery: bool,
 
+    /// Refuse to start indexing jobs once clones, indexes and cached wikis
+    /// take this many bytes (None = no limit)
+    #[serde(default)]
+    pub max_disk_usage_bytes: Option<u64>,
+
     /// Storage configuration
     pub storage: StorageConfig,
 }
@@ -60,6 +73,7 @@ impl Default for RepositoryManagerConfig {
     fn default() -> Self {
         Self {
             max_concurrent_indexing: 3,
+            indexing_workers: None,
             max_concurrent_queries: 10,
             indexing_timeout: Duration::from_secs(300), // 5 minutes
             query_timeout: Duration::from_secs(30),
@@ -73,11 +87,22 @@ impl Default for RepositoryManagerConfig {
             health_check_interval: Duration::from_secs(30),
             stuck_indexing_threshold: Duration::from_secs(600), // 10 minutes
             enable_auto_recovery: true,
+            max_disk_usage_bytes: None,
             storage: StorageConfig::default(),
         }
     }
 }
 
+impl RepositoryManagerConfig {
+    /// Delay before the given retry (1 for the first), growing by
+    /// `backoff_multiplier` up to `max_retry_delay`
+    pub fn retry_delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let delay = self.retry_backoff.as_secs_f64() * self.backoff_multiplier.powi(exponent);
+        Duration::from_secs_f64(delay.min(self.max_retry_delay.as_secs_f64()))
+    }
+}
+
 /// Storage configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
@@ -164,6 +189,61 @@ pub struct RepositoryMetrics {
     pub permission_errors: AtomicU64,
     pub validation_errors: AtomicU64,
     pub internal_errors: AtomicU64,
+    /// Times the query worker was restarted after a panic
+    pub worker_restarts: AtomicU64,
+
+    // Per-repository metrics
+    repositories: Mutex<HashMap<String, RepositoryCounters>>,
+}
+
+/// Outcomes and latencies of one kind of operation on one repository
+#[derive(Debug, Clone, Default)]
+struct OperationCounter {
+    successful: u64,
+    failed: u64,
+    total_duration_ms: u64,
+    min_duration_ms: u64,
+    max_duration_ms: u64,
+}
+
+impl OperationCounter {
+    fn record_success(&mut self, duration: Duration) {
+        let duration_ms = duration.as_millis() as u64;
+        self.min_duration_ms = if self.successful == 0 {
+            duration_ms
+        } else {
+            self.min_duration_ms.min(duration_ms)
+        };
+        self.max_duration_ms = self.max_duration_ms.max(duration_ms);
+        self.total_duration_ms += duration_ms;
+        self.successful += 1;
+    }
+
+    fn stats(&self) -> OperationStats {
+        OperationStats {
+            total: self.successful + self.failed,
+            successful: self.successful,
+            failed: self.failed,
+            active: 0,
+            failure_rate: OperationStats::failure_rate(self.successful, self.failed),
+            avg_duration_ms: self
+                .total_duration_ms
+                .checked_div(self.successful)
+                .unwrap_or(0),
+            min_duration_ms: self.min_duration_ms,
+            max_duration_ms: self.max_duration_ms,
+        }
+    }
+}
+
+/// Metrics of one repository
+#[derive(Debug, Clone, Default)]
+struct RepositoryCounters {
+    indexing: OperationCounter,
+    queries: OperationCounter,
+    last_index_duration_ms: Option<u64>,
+    documents: usize,
+    chunks: usize,
 }
 
 impl RepositoryMetrics {
@@ -186,6 +266,18 @@ impl RepositoryMetrics {
             .fetch_sub(1, Ordering::Relaxed);
     }
 
+    /// Record an indexing operation that was cancelled
+    pub fn record_indexing_cancelled(&self) {
+        self.active_indexing_operations
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Record a restart of the query worker
+    pub fn record_worker_restart(&self) {
+        self.worker_restarts.fetch_add(1, Ordering::Relaxed);
+        self.internal_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Record a successful query operation
     pub fn record_query_success(&self, duration: Duration, from_cache: bool) {
         self.successful_queries.fetch_add(1, Ordering::Relaxed);
@@ -220,6 +312,89 @@ impl RepositoryMetrics {
         };
     }
 
+    /// Record a successful indexing run of a repository and the size of its index
+    pub fn record_repository_indexed(
+        &self,
+        repository_id: &str,
+        duration: Duration,
+        documents: usize,
+        chunks: usize,
+    ) {
+        self.update_repository(repository_id, |counters| {
+            counters.indexing.record_success(duration);
+            counters.last_index_duration_ms = Some(duration.as_millis() as u64);
+            counters.documents = documents;
+            counters.chunks = chunks;
+        });
+    }
+
+    /// Record a failed indexing run of a repository
+    pub fn record_repository_indexing_failure(&self, repository_id: &str) {
+        self.update_repository(repository_id, |counters| counters.indexing.failed += 1);
+    }
+
+    /// Record a successful query of a repository
+    pub fn record_repository_query_success(&self, repository_id: &str, duration: Duration) {
+        self.update_repository(repository_id, |counters| {
+            counters.queries.record_success(duration)
+        });
+    }
+
+    /// Record a failed query of a repository
+    pub fn record_repository_query_failure(&self, repository_id: &str) {
+        self.update_repository(repository_id, |counters| counters.queries.failed += 1);
+    }
+
+    /// Forget the metrics of a deleted repository
+    pub fn remove_repository(&self, repository_id: &str) {
+        self.repositories
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(repository_id);
+    }
+
+    /// Metrics of a repository, empty if nothing was recorded for it
+    pub fn repository_stats(&self, repository_id: &str) -> RepositoryOperationStats {
+        let repositories = self.repositories.lock().unwrap_or_else(|e| e.into_inner());
+        let counters = repositories.get(repository_id).cloned().unwrap_or_default();
+        Self::to_repository_stats(repository_id, &counters)
+    }
+
+    /// Metrics of every repository with recorded operations, most queried first
+    pub fn all_repository_stats(&self) -> Vec<RepositoryOperationStats> {
+        let repositories = self.repositories.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats: Vec<_> = repositories
+            .iter()
+            .map(|(repository_id, counters)| Self::to_repository_stats(repository_id, counters))
+            .collect();
+        stats.sort_by(|a, b| {
+            b.queries
+                .total
+                .cmp(&a.queries.total)
+                .then_with(|| a.repository_id.cmp(&b.repository_id))
+        });
+        stats
+    }
+
+    fn update_repository(&self, repository_id: &str, update: impl FnOnce(&mut RepositoryCounters)) {
+        let mut repositories = self.repositories.lock().unwrap_or_else(|e| e.into_inner());
+        update(repositories.entry(repository_id.to_string()).or_default());
+    }
+
+    fn to_repository_stats(
+        repository_id: &str,
+        counters: &RepositoryCounters,
+    ) -> RepositoryOperationStats {
+        RepositoryOperationStats {
+            repository_id: repository_id.to_string(),
+            last_index_duration_ms: counters.last_index_duration_ms,
+            documents: counters.documents,
+            chunks: counters.chunks,
+            indexing: counters.indexing.stats(),
+            queries: counters.queries.stats(),
+        }
+    }
+
     /// Start tracking an indexing operation
     pub fn start_indexing(&self) {
         self.total_indexing_operations
@@ -307,10 +482,47 @@ impl RepositoryMetrics {
         }
     }
 
+    /// Totals, failure rate and latencies of indexing operations
+    pub fn indexing_stats(&self) -> OperationStats {
+        let successful = self.successful_indexing_operations.load(Ordering::Relaxed);
+        let failed = self.failed_indexing_operations.load(Ordering::Relaxed);
+        OperationStats {
+            total: self.total_indexing_operations.load(Ordering::Relaxed),
+            successful,
+            failed,
+            active: self.active_indexing_operations.load(Ordering::Relaxed),
+            failure_rate: OperationStats::failure_rate(successful, failed),
+            avg_duration_ms: self.avg_indexing_time.load(Ordering::Relaxed),
+            min_duration_ms: self.min_indexing_time.load(Ordering::Relaxed),
+            max_duration_ms: self.max_indexing_time.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Totals, failure rate and latencies of queries
+    pub fn query_stats(&self) -> OperationStats {
+        let successful = self.successful_queries.load(Ordering::Relaxed);
+        let failed = self.failed_queries.load(Ordering::Relaxed);
+        OperationStats {
+            total: self.total_queries.load(Ordering::Relaxed),
+            successful,
+            failed,
+            active: self.active_query_operations.load(Ordering::Relaxed),
+            failure_rate: OperationStats::failure_rate(successful, failed),
+            avg_duration_ms: self.avg_query_time.load(Ordering::Relaxed),
+            min_duration_ms: self.min_query_time.load(Ordering::Relaxed),
+            max_duration_ms: self.max_query_time.load(Ordering::Relaxed),
+        }
+    }
+
     /// Update indexing time statistics
     fn update_indexing_time_stats(&self, duration_ms: u64) {
-        // Simple implementation - in production, consider using a more sophisticated approach
-        self.avg_indexing_time.store(duration_ms, Ordering::Relaxed);
+        let count = self.successful_indexing_operations.load(Ordering::Relaxed);
+        let avg = running_average(
+            self.avg_indexing_time.load(Ordering::Relaxed),
+            count,
+            duration_ms,
+        );
+        self.avg_indexing_time.store(avg, Ordering::Relaxed);
 
         // Update max
         let current_max = self.max_indexing_time.load(Ordering::Relaxed);
@@ -327,8 +539,13 @@ impl RepositoryMetrics {
 
     /// Update query time statistics
     fn update_query_time_stats(&self, duration_ms: u64) {
-        // Simple implementation - in production, consider using a more sophisticated approach
-        self.avg_query_time.store(duration_ms, Ordering::Relaxed);
+        let count = self.successful_queries.load(Ordering::Relaxed);
+        let avg = running_average(
+            self.avg_query_time.load(Ordering::Relaxed),
+            count,
+            duration_ms,
+        );
+        self.avg_query_time.store(avg, Ordering::Relaxed);
 
         // Update max
         let current_max = self.max_query_time.load(Ordering::Relaxed);
@@ -344,6 +561,15 @@ impl RepositoryMetrics {
     }
 }
 
+/// Average of `count` values, given the average of the first `count - 1`
+/// and the latest value
+fn running_average(previous: u64, count: u64, latest: u64) -> u64 {
+    if count <= 1 {
+        return latest;
+    }
+    (previous * (count - 1) + latest) / count
+}
+
 /// System health status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -371,3 +597,53 @@ impl HealthStatus {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_metrics_are_kept_apart() {
+        let metrics = RepositoryMetrics::default();
+        metrics.record_repository_indexed("a", Duration::from_millis(1_500), 10, 120);
+        metrics.record_repository_query_success("a", Duration::from_millis(100));
+        metrics.record_repository_query_success("a", Duration::from_millis(300));
+        metrics.record_repository_query_failure("a");
+        metrics.record_repository_indexing_failure("b");
+
+        let a = metrics.repository_stats("a");
+        assert_eq!(a.last_index_duration_ms, Some(1_500));
+        assert_eq!(a.chunks, 120);
+        assert_eq!(a.queries.total, 3);
+        assert_eq!(a.queries.failed, 1);
+        assert_eq!(a.queries.avg_duration_ms, 200);
+        assert_eq!(a.queries.min_duration_ms, 100);
+        assert_eq!(a.queries.max_duration_ms, 300);
+
+        let b = metrics.repository_stats("b");
+        assert_eq!(b.indexing.failed, 1);
+        assert_eq!(b.queries.total, 0);
+        assert_eq!(b.last_index_duration_ms, None);
+
+        let all = metrics.all_repository_stats();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].repository_id, "a");
+
+        metrics.remove_repository("a");
+        assert_eq!(metrics.repository_stats("a").queries.total, 0);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_up_to_the_maximum() {
+        let config = RepositoryManagerConfig {
+            retry_backoff: Duration::from_secs(1),
+            backoff_multiplier: 2.0,
+            max_retry_delay: Duration::from_secs(10),
+            ..Default::default()
+        };
+        assert_eq!(config.retry_delay(1), Duration::from_secs(1));
+        assert_eq!(config.retry_delay(3), Duration::from_secs(4));
+        assert_eq!(config.retry_delay(5), Duration::from_secs(10));
+        assert_eq!(config.retry_delay(u32::MAX), Duration::from_secs(10));
+    }
+}
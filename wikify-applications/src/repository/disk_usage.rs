@@ -0,0 +1,179 @@
+//! Disk usage of repositories
+//!
+//! The artifacts of a repository are its clone, its vector index and its
+//! cached wikis. Their size is measured after indexing and wiki generation
+//! and kept in [`RepositoryIndex::disk_usage`](super::RepositoryIndex); the
+//! size of all artifacts together is checked against the disk usage quota
+//! before an indexing job starts.
+
+use super::errors::{RepositoryError, RepositoryResult};
+use super::storage::RepositoryStorage;
+use super::types::RepositoryDiskUsage;
+use crate::WikifyApplication;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where the artifacts of repositories are stored
+pub struct StorageLocations {
+    /// Workspaces repositories are cloned into
+    pub workspaces: Vec<wikify_repo::CloneWorkspace>,
+    /// Cache of generated wikis
+    #[cfg(feature = "wiki")]
+    pub wiki_cache: wikify_wiki::WikiCache,
+    /// Vector indexes
+    pub vector_storage: wikify_rag::types::StorageConfig,
+}
+
+impl StorageLocations {
+    /// Locations the repository processor, RAG pipeline and wiki service use
+    pub fn standard() -> Self {
+        let base_path = WikifyApplication::base_path();
+        Self {
+            workspaces: vec![
+                wikify_repo::CloneWorkspace::new(&base_path),
+                wikify_repo::CloneWorkspace::new(wikify_rag::RagPipeline::clone_base_path()),
+            ],
+            #[cfg(feature = "wiki")]
+            wiki_cache: wikify_wiki::WikiCache::new().unwrap_or_else(|_| {
+                wikify_wiki::WikiCache::with_cache_dir(base_path.join("wiki-cache"))
+                    .expect("a wiki cache with an explicit directory always opens")
+            }),
+            vector_storage: wikify_rag::types::StorageConfig::default(),
+        }
+    }
+
+    /// Clone paths of a repository URL, one per workspace that can hold it
+    pub fn clone_paths(&self, url: &str) -> Vec<PathBuf> {
+        self.workspaces
+            .iter()
+            .filter_map(|workspace| WikifyApplication::clone_path_in(workspace, url))
+            .collect()
+    }
+
+    /// Keys the vector index and wiki cache of a repository can be stored
+    /// under: its URL, or its clone path when it was indexed from a mirror
+    pub fn artifact_keys(&self, url: &str) -> Vec<String> {
+        std::iter::once(url.to_string())
+            .chain(
+                self.clone_paths(url)
+                    .iter()
+                    .map(|path| path.display().to_string()),
+            )
+            .collect()
+    }
+
+    /// Measure the artifacts of a repository
+    pub fn measure(&self, url: &str) -> RepositoryDiskUsage {
+        let keys = self.artifact_keys(url);
+        let clone_bytes = directories_size(self.clone_paths(url));
+        let index_bytes =
+            directories_size(keys.iter().map(|key| {
+                wikify_rag::PersistentVectorStore::storage_dir(&self.vector_storage, key)
+            }));
+        #[cfg(feature = "wiki")]
+        let wiki_cache_bytes =
+            directories_size(keys.iter().map(|key| self.wiki_cache.entries_dir(key)));
+        #[cfg(not(feature = "wiki"))]
+        let wiki_cache_bytes = 0;
+
+        RepositoryDiskUsage {
+            clone_bytes,
+            index_bytes,
+            wiki_cache_bytes,
+            total_bytes: clone_bytes + index_bytes + wiki_cache_bytes,
+            measured_at: Utc::now(),
+        }
+    }
+
+    /// Size of all artifacts, including those no repository uses any more
+    pub fn total_bytes(&self) -> u64 {
+        let mut dirs: Vec<PathBuf> = self
+            .workspaces
+            .iter()
+            .map(|workspace| workspace.root().to_path_buf())
+            .collect();
+        #[cfg(feature = "wiki")]
+        dirs.push(self.wiki_cache.cache_dir().to_path_buf());
+        dirs.push(self.vector_storage.base_dir.clone());
+        directories_size(dirs)
+    }
+}
+
+/// Total size of distinct directories
+fn directories_size(dirs: impl IntoIterator<Item = PathBuf>) -> u64 {
+    dirs.into_iter()
+        .collect::<HashSet<_>>()
+        .iter()
+        .map(|dir| wikify_repo::directory_size(dir))
+        .sum()
+}
+
+/// Measure the artifacts of a repository and keep the result in storage
+///
+/// Returns `None` if the repository does not exist.
+pub(crate) async fn refresh_disk_usage(
+    storage: &dyn RepositoryStorage,
+    locations: Arc<StorageLocations>,
+    repository_id: &str,
+) -> RepositoryResult<Option<RepositoryDiskUsage>> {
+    let Some(mut repository) = storage.load_repository(repository_id).await? else {
+        return Ok(None);
+    };
+
+    let url = repository.url.clone();
+    let usage = tokio::task::spawn_blocking(move || locations.measure(&url))
+        .await
+        .map_err(|e| RepositoryError::Internal {
+            message: format!("Disk usage measurement panicked: {}", e),
+            component: "disk_usage".to_string(),
+            error_id: uuid::Uuid::new_v4().to_string(),
+            recoverable: true,
+        })?;
+
+    repository.disk_usage = Some(usage.clone());
+    storage.save_repository(&repository).await?;
+    Ok(Some(usage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "wiki")]
+    fn test_measure_counts_clone_index_and_wiki_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let locations = StorageLocations {
+            workspaces: vec![wikify_repo::CloneWorkspace::new(dir.path())],
+            wiki_cache: wikify_wiki::WikiCache::with_cache_dir(dir.path().join("wiki")).unwrap(),
+            vector_storage: wikify_rag::types::StorageConfig {
+                base_dir: dir.path().join("vector_db"),
+                ..Default::default()
+            },
+        };
+        let url = "https://github.com/owner/repo";
+
+        let clone = locations.clone_paths(url).remove(0);
+        std::fs::create_dir_all(&clone).unwrap();
+        std::fs::write(clone.join("main.rs"), "fn main() {}").unwrap();
+        let index = wikify_rag::PersistentVectorStore::storage_dir(&locations.vector_storage, url);
+        std::fs::create_dir_all(&index).unwrap();
+        std::fs::write(index.join("vectors.json"), "[]").unwrap();
+        let wiki = locations.wiki_cache.entries_dir(url);
+        std::fs::create_dir_all(&wiki).unwrap();
+        std::fs::write(wiki.join("worktree.json"), "{}").unwrap();
+        // Artifacts of another repository only count towards the total
+        let other = dir.path().join("vector_db").join("repo_other");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join("vectors.json"), "[[]]").unwrap();
+
+        let usage = locations.measure(url);
+        assert_eq!(usage.clone_bytes, 12);
+        assert_eq!(usage.index_bytes, 2);
+        assert_eq!(usage.wiki_cache_bytes, 2);
+        assert_eq!(usage.total_bytes, 16);
+        assert_eq!(locations.total_bytes(), 20);
+    }
+}
@@ -1,49 +1,416 @@
 use super::config::*;
+use super::disk_usage::{self, StorageLocations};
 use super::errors::*;
+use super::queue::*;
 use super::storage::*;
 use super::types::{QueryChunkType, QueryStreamChunk, *};
 use crate::auth::PermissionContext;
+use crate::organization::TenantScope;
+use crate::pagination::{Page, PageRequest};
+use crate::stats::{
+    IndexStats, RepositoryIndexSize, RepositoryOperationStats, RepositoryStats,
+    RepositoryStatusCounts,
+};
+use crate::telemetry::{self, TelemetryEvent};
+use crate::usage::{UsageKind, UsageRecord, UsageTracker};
 use crate::{ApplicationError, ApplicationResult};
 
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
-use tokio::sync::{broadcast, mpsc, RwLock};
-use tracing::{debug, error, info, warn};
+use tokio::sync::{broadcast, mpsc, Mutex, Notify, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, info_span, warn, Instrument};
+use wikify_core::{BroadcastProgress, CallbackProgress, ProgressReporter, ProgressUpdate};
 
-use wikify_rag::{RagConfig, RagPipeline};
+use wikify_rag::rag_pipeline::IndexingStats;
+use wikify_rag::{RagConfig, RagPipeline, RagQuery};
 
-/// Indexing command sent to the indexing worker
+/// Repository metadata key holding the largest size, in bytes, the
+/// repository may have to be indexed
+pub const MAX_SIZE_METADATA_KEY: &str = "max_size_bytes";
+
+/// Why an indexing run stopped before completing
+#[derive(Debug)]
+pub enum IndexingFailure {
+    /// Indexing failed with an error
+    Failed(String),
+    /// Indexing was cancelled after reaching `progress` (0.0 to 1.0)
+    Cancelled { progress: f64 },
+}
+
+/// Command sent to the query worker
 #[derive(Debug)]
 pub enum IndexingCommand {
-    /// Index a repository
-    IndexRepository {
-        repository_id: String,
-        repository_url: String,
-        response_tx: tokio::sync::oneshot::Sender<Result<String, String>>,
-    },
     /// Query a repository
     QueryRepository {
         repository_id: String,
         query: RepositoryQuery,
+        /// ID of the request that sent the query, for correlating logs
+        request_id: Option<String>,
         response_tx: tokio::sync::oneshot::Sender<Result<RepositoryQueryResponse, String>>,
     },
+    /// Find indexed chunks of a repository without generating an answer
+    SearchRepository {
+        repository_id: String,
+        query: RepositoryQuery,
+        request_id: Option<String>,
+        response_tx: tokio::sync::oneshot::Sender<Result<Vec<CodeSearchResult>, String>>,
+    },
     /// Stream query a repository (for real-time responses)
     StreamQueryRepository {
         repository_id: String,
         query: RepositoryQuery,
+        request_id: Option<String>,
         stream_tx: tokio::sync::mpsc::UnboundedSender<QueryStreamChunk>,
     },
 }
 
+/// Build the RAG query for a repository query
+///
+/// `max_results` and the `similarity_threshold` parameter override the
+/// retrieval settings of the pipeline for this query only. The `files`
+/// parameter, one repository-relative path per line, restricts retrieval to
+/// the chunks of those files. The `attachment` parameter is text the user
+/// supplied with the question, such as a stack trace.
+fn to_rag_query(query: &RepositoryQuery, rag_config: &RagConfig) -> RagQuery {
+    let mut rag_query = wikify_rag::create_simple_query(&query.question);
+
+    let similarity_threshold = query
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters.get("similarity_threshold"))
+        .and_then(|value| value.parse::<f32>().ok());
+    if query.max_results.is_some() || similarity_threshold.is_some() {
+        let mut retrieval = rag_config.retrieval.clone();
+        if let Some(max_results) = query.max_results {
+            retrieval.top_k = max_results.max(1);
+        }
+        if let Some(similarity_threshold) = similarity_threshold {
+            retrieval.similarity_threshold = similarity_threshold.clamp(0.0, 1.0);
+        }
+        rag_query.retrieval_config = Some(retrieval);
+    }
+
+    if let Some(files) = query
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters.get("files"))
+    {
+        let files: Vec<serde_json::Value> = files
+            .lines()
+            .map(str::trim)
+            .filter(|file| !file.is_empty())
+            .map(Into::into)
+            .collect();
+        rag_query.filters = Some(HashMap::from([(
+            "file_path".to_string(),
+            serde_json::Value::Array(files),
+        )]));
+    }
+
+    rag_query.attachment = query
+        .parameters
+        .as_ref()
+        .and_then(|parameters| parameters.get("attachment"))
+        .cloned();
+
+    rag_query
+}
+
+/// Indexed RAG pipelines used to answer queries, keyed by repository ID
+type RepositoryIndexes = Arc<RwLock<HashMap<String, Arc<Mutex<RagPipeline>>>>>;
+
+/// Runs queued indexing jobs, each with its own RAG pipeline
+struct IndexingScheduler {
+    rag_config: RagConfig,
+    queue: Mutex<IndexingQueue>,
+    /// Wakes the dispatcher when a job is queued or finishes
+    wake: Notify,
+    storage: Arc<dyn RepositoryStorage>,
+    progress_tx: broadcast::Sender<IndexingUpdate>,
+    indexes: RepositoryIndexes,
+    /// Tokens for cloning private repositories, kept in memory only
+    access_tokens: RwLock<HashMap<String, String>>,
+    /// Records embedding usage of finished jobs, when usage tracking is on
+    usage: RwLock<Option<Arc<UsageTracker>>>,
+    metrics: Arc<RepositoryMetrics>,
+    /// Where clones, indexes and cached wikis take up disk space
+    locations: Arc<StorageLocations>,
+    /// Disk usage at which no new jobs start
+    max_disk_usage_bytes: Option<u64>,
+    /// Files loaded and split at the same time within one job
+    indexing_workers: Option<usize>,
+}
+
+impl IndexingScheduler {
+    /// Start queued jobs whenever a slot is free
+    async fn run(self: Arc<Self>) {
+        loop {
+            self.wake.notified().await;
+
+            loop {
+                let next = self.queue.lock().await.next();
+                let Some((job, cancel)) = next else {
+                    break;
+                };
+
+                // Logs of the job carry the request that queued it
+                let span = info_span!(
+                    "indexing_job",
+                    job_id = %job.id,
+                    request_id = job.request_id.as_deref().unwrap_or_default(),
+                );
+                let scheduler = self.clone();
+                tokio::spawn(
+                    async move {
+                        // Run the job in its own task so a panic still frees its slot
+                        let (worker, running_job) = (scheduler.clone(), job.clone());
+                        let outcome = tokio::spawn(
+                            async move { worker.run_job(&running_job, &cancel).await }
+                                .in_current_span(),
+                        )
+                        .await;
+                        if matches!(outcome, Err(e) if e.is_panic()) {
+                            scheduler.fail_crashed_job(&job).await;
+                        }
+                        scheduler.queue.lock().await.finish(&job.repository_id);
+                        scheduler.wake.notify_one();
+                    }
+                    .instrument(span),
+                );
+            }
+        }
+    }
+
+    /// Index a repository and record the outcome in storage
+    async fn run_job(&self, job: &IndexingJob, cancel: &CancellationToken) {
+        let repository_id = job.repository_id.as_str();
+        let start_time = Instant::now();
+        self.metrics.start_indexing();
+        info!(
+            repository_id = %repository_id,
+            job_id = %job.id,
+            "🔄 Starting repository indexing"
+        );
+
+        let result = match self.prepare_job(repository_id).await {
+            Ok((repo, mut rag_pipeline)) => {
+                let _ = self.progress_tx.send(IndexingUpdate::progress(
+                    repository_id.to_string(),
+                    0.0,
+                    "Initializing repository indexing...".to_string(),
+                ));
+
+                RepositoryManager::handle_repository_indexing(
+                    &mut rag_pipeline,
+                    repository_id,
+                    &repo.url,
+                    &repo.mirror_urls(),
+                    repo.metadata
+                        .get(MAX_SIZE_METADATA_KEY)
+                        .and_then(|size| size.parse().ok()),
+                    &self.progress_tx,
+                    cancel,
+                    start_time,
+                )
+                .await
+                .map(|stats| (stats, rag_pipeline))
+            }
+            Err(error_msg) => {
+                let _ = self.progress_tx.send(IndexingUpdate::error(
+                    repository_id.to_string(),
+                    error_msg.clone(),
+                ));
+                Err(IndexingFailure::Failed(error_msg))
+            }
+        };
+
+        let (status, progress) = match result {
+            Ok((stats, rag_pipeline)) => {
+                self.record_usage(job, &stats).await;
+                if let Err(e) = disk_usage::refresh_disk_usage(
+                    self.storage.as_ref(),
+                    self.locations.clone(),
+                    repository_id,
+                )
+                .await
+                {
+                    warn!(
+                        repository_id = %repository_id,
+                        error = %e,
+                        "Failed to measure repository disk usage"
+                    );
+                }
+                // Serve queries from the new index
+                self.indexes.write().await.insert(
+                    repository_id.to_string(),
+                    Arc::new(Mutex::new(rag_pipeline)),
+                );
+                info!(
+                    repository_id = %repository_id,
+                    stats = %stats.summary(),
+                    "✅ Repository indexing completed successfully"
+                );
+                telemetry::record(TelemetryEvent::repository_indexed(
+                    stats.total_documents,
+                    stats.total_chunks,
+                ));
+                self.metrics.record_indexing_success(start_time.elapsed());
+                self.metrics.record_repository_indexed(
+                    repository_id,
+                    start_time.elapsed(),
+                    stats.total_documents,
+                    stats.total_chunks,
+                );
+                (IndexingStatus::Completed, 1.0)
+            }
+            // Keep the partial progress so clients can see how far it got
+            Err(IndexingFailure::Cancelled { progress }) => {
+                self.metrics.record_indexing_cancelled();
+                (IndexingStatus::Cancelled, progress)
+            }
+            Err(IndexingFailure::Failed(error_msg)) => {
+                self.metrics.record_indexing_failure();
+                self.metrics
+                    .record_repository_indexing_failure(repository_id);
+                error!(
+                    repository_id = %repository_id,
+                    error_msg = %error_msg,
+                    "❌ Repository indexing failed"
+                );
+                (IndexingStatus::Failed, 0.0)
+            }
+        };
+
+        if let Err(e) = self
+            .storage
+            .update_status(repository_id, status.clone(), progress)
+            .await
+        {
+            error!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to update repository status to {:?}",
+                status
+            );
+        }
+    }
+
+    /// Mark the repository of a job that panicked as failed, so it can be
+    /// reindexed instead of staying stuck in the indexing state
+    async fn fail_crashed_job(&self, job: &IndexingJob) {
+        let repository_id = job.repository_id.as_str();
+        let error_msg = "Indexing job crashed; reindex the repository to try again";
+        error!(
+            repository_id = %repository_id,
+            job_id = %job.id,
+            "💥 Indexing job panicked"
+        );
+        self.metrics.record_indexing_failure();
+        self.metrics
+            .record_repository_indexing_failure(repository_id);
+        let _ = self.progress_tx.send(IndexingUpdate::error(
+            repository_id.to_string(),
+            error_msg.to_string(),
+        ));
+        if let Err(e) = self
+            .storage
+            .update_status(repository_id, IndexingStatus::Failed, 0.0)
+            .await
+        {
+            error!(
+                repository_id = %repository_id,
+                error = %e,
+                "Failed to mark crashed indexing job as failed"
+            );
+        }
+    }
+
+    /// Record the embedding tokens spent on a finished job
+    async fn record_usage(&self, job: &IndexingJob, stats: &IndexingStats) {
+        let Some(tracker) = self.usage.read().await.clone() else {
+            return;
+        };
+        let record = UsageRecord::new(
+            tracker.config(),
+            UsageKind::Indexing,
+            &self.rag_config.embeddings.model,
+            stats.embedding_tokens as u64,
+            0,
+        )
+        .for_subject(job.user_id.clone(), Some(job.repository_id.clone()));
+        if let Err(e) = tracker.record(record).await {
+            warn!(
+                repository_id = %job.repository_id,
+                error = %e,
+                "Failed to record indexing usage"
+            );
+        }
+    }
+
+    /// Mark the repository as indexing and create the pipeline for its index
+    async fn prepare_job(
+        &self,
+        repository_id: &str,
+    ) -> Result<(RepositoryIndex, RagPipeline), String> {
+        let repo = self
+            .storage
+            .load_repository(repository_id)
+            .await
+            .map_err(|e| format!("Failed to load repository: {}", e))?
+            .ok_or_else(|| format!("Repository not found: {}", repository_id))?;
+
+        self.check_disk_quota().await?;
+
+        self.storage
+            .update_status(repository_id, IndexingStatus::Indexing, 0.0)
+            .await
+            .map_err(|e| format!("Failed to update repository status: {}", e))?;
+
+        let access_token = self.access_tokens.read().await.get(repository_id).cloned();
+        let mut rag_pipeline = RagPipeline::new(self.rag_config.clone())
+            .with_access_token(access_token)
+            .with_indexing_workers(self.indexing_workers);
+        rag_pipeline
+            .initialize()
+            .await
+            .map_err(|e| format!("Failed to initialize RAG pipeline: {}", e))?;
+
+        Ok((repo, rag_pipeline))
+    }
+
+    /// Fail if the artifacts of all repositories already fill the quota
+    async fn check_disk_quota(&self) -> Result<(), String> {
+        let Some(max_bytes) = self.max_disk_usage_bytes else {
+            return Ok(());
+        };
+        let locations = self.locations.clone();
+        let used_bytes = tokio::task::spawn_blocking(move || locations.total_bytes())
+            .await
+            .map_err(|e| format!("Failed to measure disk usage: {}", e))?;
+
+        if used_bytes >= max_bytes {
+            return Err(format!(
+                "Disk usage quota exceeded: {} of {} bytes used. Delete repositories or run \
+                 storage garbage collection to free space",
+                used_bytes, max_bytes
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Repository manager handles all repository operations using message passing
 pub struct RepositoryManager {
     /// Storage backend for repository persistence
     storage: Arc<dyn RepositoryStorage>,
-    /// Command sender to the indexing worker
+    /// Command sender to the query worker
     indexing_tx: mpsc::UnboundedSender<IndexingCommand>,
     /// Progress broadcaster for indexing updates
     progress_broadcaster: broadcast::Sender<IndexingUpdate>,
+    /// Scheduler running queued indexing jobs
+    scheduler: Arc<IndexingScheduler>,
     /// Configuration for the repository manager
     #[allow(dead_code)]
     config: RepositoryManagerConfig,
@@ -72,24 +439,57 @@ impl RepositoryManager {
         let metrics = Arc::new(RepositoryMetrics::default());
         let worker_healthy = Arc::new(RwLock::new(false));
 
-        // Spawn the enhanced indexing worker with better logging
-        let progress_tx = progress_broadcaster.clone();
-        let storage_clone = storage.clone();
-        let metrics_clone = metrics.clone();
-        let worker_healthy_clone = worker_healthy.clone();
-        tokio::spawn(Self::enhanced_indexing_worker(
-            rag_config,
-            indexing_rx,
-            progress_tx,
-            storage_clone,
-            metrics_clone,
-            worker_healthy_clone,
+        // Spawn the scheduler that runs indexing jobs in parallel
+        let scheduler = Arc::new(IndexingScheduler {
+            rag_config: rag_config.clone(),
+            queue: Mutex::new(IndexingQueue::new(config.max_concurrent_indexing)),
+            wake: Notify::new(),
+            storage: storage.clone(),
+            progress_tx: progress_broadcaster.clone(),
+            indexes: Arc::new(RwLock::new(HashMap::new())),
+            access_tokens: RwLock::new(HashMap::new()),
+            usage: RwLock::new(None),
+            metrics: metrics.clone(),
+            locations: Arc::new(StorageLocations::standard()),
+            max_disk_usage_bytes: config.max_disk_usage_bytes,
+            indexing_workers: config.indexing_workers,
+        });
+        tokio::spawn(scheduler.clone().run());
+
+        // Spawn the enhanced query worker with better logging, restarted
+        // whenever it panics. The receiver outlives each worker, so commands
+        // sent during a restart wait in the channel.
+        let commands = Arc::new(Mutex::new(indexing_rx));
+        let spawn_worker = {
+            let indexes = scheduler.indexes.clone();
+            let progress_tx = progress_broadcaster.clone();
+            let storage = storage.clone();
+            let metrics = metrics.clone();
+            let worker_healthy = worker_healthy.clone();
+            move || {
+                tokio::spawn(Self::enhanced_indexing_worker(
+                    rag_config.clone(),
+                    commands.clone(),
+                    indexes.clone(),
+                    progress_tx.clone(),
+                    storage.clone(),
+                    metrics.clone(),
+                    worker_healthy.clone(),
+                ))
+            }
+        };
+        tokio::spawn(Self::supervise_worker(
+            spawn_worker,
+            worker_healthy.clone(),
+            metrics.clone(),
+            config.clone(),
         ));
 
         Self {
             storage,
             indexing_tx,
             progress_broadcaster,
+            scheduler,
             config,
             metrics,
             worker_healthy,
@@ -110,6 +510,115 @@ impl RepositoryManager {
         Ok(Self::with_storage(rag_config, storage, config))
     }
 
+    /// Record the embedding tokens spent by indexing jobs
+    pub async fn set_usage_tracker(&self, tracker: Arc<UsageTracker>) {
+        *self.scheduler.usage.write().await = Some(tracker);
+    }
+
+    /// Where clones, indexes and cached wikis of repositories are stored
+    pub fn storage_locations(&self) -> Arc<StorageLocations> {
+        self.scheduler.locations.clone()
+    }
+
+    /// Disk usage at which indexing jobs are refused, if limited
+    pub fn max_disk_usage_bytes(&self) -> Option<u64> {
+        self.scheduler.max_disk_usage_bytes
+    }
+
+    /// Measure the disk usage of a repository and store it in its index
+    ///
+    /// Returns `None` if the repository does not exist.
+    pub async fn refresh_disk_usage(
+        &self,
+        repository_id: &str,
+    ) -> ApplicationResult<Option<RepositoryDiskUsage>> {
+        Ok(disk_usage::refresh_disk_usage(
+            self.storage.as_ref(),
+            self.storage_locations(),
+            repository_id,
+        )
+        .await?)
+    }
+
+    /// Whether the indexing worker initialized its RAG pipeline
+    pub async fn is_worker_healthy(&self) -> bool {
+        *self.worker_healthy.read().await
+    }
+
+    /// Check that the repository storage backend answers
+    pub async fn check_storage(&self) -> ApplicationResult<()> {
+        self.storage
+            .health_check()
+            .await
+            .map_err(|e| ApplicationError::Internal {
+                message: format!("Repository storage is unhealthy: {}", e),
+                source: None,
+            })
+    }
+
+    /// Number of repository vector indexes loaded in memory
+    pub async fn loaded_index_count(&self) -> usize {
+        self.scheduler.indexes.read().await.len()
+    }
+
+    /// Repository counts by status, loaded index sizes and operation metrics
+    pub async fn stats(&self) -> ApplicationResult<RepositoryStats> {
+        let mut repositories = RepositoryStatusCounts::default();
+        for repo in self.list_all_repositories().await? {
+            repositories.total += 1;
+            match repo.status {
+                IndexingStatus::Pending => repositories.pending += 1,
+                IndexingStatus::Indexing => repositories.indexing += 1,
+                IndexingStatus::Completed => repositories.completed += 1,
+                IndexingStatus::Failed => repositories.failed += 1,
+                IndexingStatus::Cancelled => repositories.cancelled += 1,
+            }
+        }
+
+        let loaded: Vec<_> = self
+            .scheduler
+            .indexes
+            .read()
+            .await
+            .iter()
+            .map(|(id, pipeline)| (id.clone(), pipeline.clone()))
+            .collect();
+        let mut indexes = IndexStats {
+            loaded: loaded.len(),
+            ..Default::default()
+        };
+        for (repository_id, pipeline) in loaded {
+            let chunks = pipeline
+                .lock()
+                .await
+                .get_stats()
+                .map_or(0, |stats| stats.total_chunks);
+            indexes.total_chunks += chunks;
+            indexes.repositories.push(RepositoryIndexSize {
+                repository_id,
+                chunks,
+            });
+        }
+        indexes.repositories.sort_by(|a, b| {
+            b.chunks
+                .cmp(&a.chunks)
+                .then_with(|| a.repository_id.cmp(&b.repository_id))
+        });
+
+        Ok(RepositoryStats {
+            repositories,
+            indexes,
+            indexing: self.metrics.indexing_stats(),
+            queries: self.metrics.query_stats(),
+            repository_metrics: self.metrics.all_repository_stats(),
+        })
+    }
+
+    /// Indexing and query metrics of a repository since the server started
+    pub fn repository_metrics(&self, repository_id: &str) -> RepositoryOperationStats {
+        self.metrics.repository_stats(repository_id)
+    }
+
     /// Initialize the repository manager and check worker health
     pub async fn initialize(&self) -> ApplicationResult<()> {
         eprintln!("🔄 Checking repository manager worker health...");
@@ -133,29 +642,75 @@ impl RepositoryManager {
         Ok(())
     }
 
-    /// Enhanced background worker with better logging and error handling
+    /// Keep the query worker running, restarting it with backoff after a panic
+    ///
+    /// The command being handled when the worker panicked is lost and its
+    /// caller gets an error. The backoff starts over once a worker has run
+    /// for longer than the maximum delay.
+    async fn supervise_worker(
+        spawn_worker: impl Fn() -> tokio::task::JoinHandle<()>,
+        worker_healthy: Arc<RwLock<bool>>,
+        metrics: Arc<RepositoryMetrics>,
+        config: RepositoryManagerConfig,
+    ) {
+        let mut restarts = 0;
+        loop {
+            let started = Instant::now();
+            match spawn_worker().await {
+                Err(e) if e.is_panic() => {
+                    *worker_healthy.write().await = false;
+                    metrics.record_worker_restart();
+                    if started.elapsed() > config.max_retry_delay {
+                        restarts = 0;
+                    }
+                    restarts += 1;
+                    let delay = config.retry_delay(restarts);
+                    error!(
+                        restarts = restarts,
+                        delay_ms = delay.as_millis(),
+                        "💥 Query worker panicked, restarting"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                // The manager was dropped and closed the channel
+                _ => {
+                    *worker_healthy.write().await = false;
+                    info!("📡 Query worker stopped");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Enhanced background worker answering queries with better logging and error handling
+    ///
+    /// Queries are answered from the index built for the repository by the
+    /// indexing scheduler. The worker's own pipeline only answers queries for
+    /// repositories that have not been indexed since the server started.
     async fn enhanced_indexing_worker(
         rag_config: RagConfig,
-        mut command_rx: mpsc::UnboundedReceiver<IndexingCommand>,
-        progress_tx: broadcast::Sender<IndexingUpdate>,
+        commands: Arc<Mutex<mpsc::UnboundedReceiver<IndexingCommand>>>,
+        indexes: RepositoryIndexes,
+        _progress_tx: broadcast::Sender<IndexingUpdate>,
         _storage: Arc<dyn RepositoryStorage>,
         _metrics: Arc<RepositoryMetrics>,
         worker_healthy: Arc<RwLock<bool>>,
     ) {
+        let mut command_rx = commands.lock().await;
         info!("🚀 Starting enhanced RAG indexing worker");
 
         // Check environment variables before initialization with structured logging
         info!("🔍 Checking LLM API configuration...");
         let mut api_keys_found = Vec::new();
 
-        if let Ok(key) = std::env::var("OPENAI_API_KEY") {
+        if let Some(key) = wikify_core::secrets::secret("OPENAI_API_KEY") {
             info!("✅ OPENAI_API_KEY configured (length: {})", key.len());
             api_keys_found.push("OpenAI");
         } else {
             warn!("❌ OPENAI_API_KEY not found");
         }
 
-        if let Ok(key) = std::env::var("ANTHROPIC_API_KEY") {
+        if let Some(key) = wikify_core::secrets::secret("ANTHROPIC_API_KEY") {
             info!("✅ ANTHROPIC_API_KEY configured (length: {})", key.len());
             api_keys_found.push("Anthropic");
         } else {
@@ -206,59 +761,35 @@ impl RepositoryManager {
         // Process commands
         while let Some(command) = command_rx.recv().await {
             match command {
-                IndexingCommand::IndexRepository {
-                    repository_id,
-                    repository_url,
-                    response_tx,
-                } => {
-                    let start_time = Instant::now();
-                    info!(
-                        repository_id = %repository_id,
-                        repository_url = %repository_url,
-                        "🔄 Starting repository indexing"
-                    );
-
-                    // Send progress update
-                    let _ = progress_tx.send(IndexingUpdate::progress(
-                        repository_id.clone(),
-                        0.0,
-                        "Initializing repository indexing...".to_string(),
-                    ));
-
-                    // Index the repository with detailed error handling
-                    let result = Self::handle_repository_indexing(
-                        &mut rag_pipeline,
-                        &repository_id,
-                        &repository_url,
-                        &progress_tx,
-                        start_time,
-                    )
-                    .await;
-
-                    // Send response back
-                    let _ = response_tx.send(result);
-                }
                 IndexingCommand::QueryRepository {
                     repository_id,
                     query,
+                    request_id,
                     response_tx,
                 } => {
                     let start_time = Instant::now();
                     info!(
                         repository_id = %repository_id,
+                        request_id = request_id.as_deref().unwrap_or_default(),
                         question = %query.question,
                         "🔍 Processing repository query"
                     );
 
                     // Create RAG query
-                    let rag_query = wikify_rag::create_simple_query(&query.question);
+                    let rag_query = to_rag_query(&query, rag_pipeline.config());
 
-                    // Perform RAG query using the pipeline
-                    let result = match rag_pipeline.ask(rag_query).await {
+                    // Perform RAG query using the repository's index
+                    let index = indexes.read().await.get(&repository_id).cloned();
+                    let rag_result = match &index {
+                        Some(index) => index.lock().await.ask(rag_query).await,
+                        None => rag_pipeline.ask(rag_query).await,
+                    };
+                    let result = match rag_result {
                         Ok(rag_response) => {
                             let duration = start_time.elapsed();
                             info!(
                                 repository_id = %repository_id,
+                                request_id = request_id.as_deref().unwrap_or_default(),
                                 duration_ms = duration.as_millis(),
                                 sources_count = rag_response.sources.len(),
                                 retrieval_time_ms = rag_response.metadata.retrieval_time_ms,
@@ -268,11 +799,15 @@ impl RepositoryManager {
 
                             // Convert RAG response to our format
                             let mut sources = Vec::new();
+                            let mut source_scores = Vec::new();
+                            let mut citations = Vec::new();
                             let mut metadata = HashMap::new();
 
                             // Extract source information from RAG response
                             for search_result in rag_response.sources.iter().take(5) {
                                 // Limit to top 5 sources
+                                source_scores.push(search_result.score);
+                                citations.extend(SourceCitation::from_search_result(search_result));
                                 if let Some(file_path) =
                                     search_result.chunk.metadata.get("file_path")
                                 {
@@ -312,10 +847,29 @@ impl RepositoryManager {
                                 "generation_time_ms".to_string(),
                                 rag_response.metadata.generation_time_ms.to_string(),
                             );
+                            // Token counts, used for usage tracking
+                            metadata.insert(
+                                "query_tokens".to_string(),
+                                rag_response.metadata.query_tokens.to_string(),
+                            );
+                            metadata.insert(
+                                "prompt_tokens".to_string(),
+                                rag_response.metadata.prompt_tokens.to_string(),
+                            );
+                            metadata.insert(
+                                "generation_tokens".to_string(),
+                                rag_response.metadata.generation_tokens.to_string(),
+                            );
+                            metadata.insert(
+                                "model_used".to_string(),
+                                rag_response.metadata.model_used.clone(),
+                            );
 
                             let response = RepositoryQueryResponse {
                                 answer: rag_response.answer,
                                 sources,
+                                source_scores,
+                                citations,
                                 confidence: Some(0.8), // TODO: Calculate actual confidence from RAG response
                                 metadata,
                             };
@@ -326,6 +880,7 @@ impl RepositoryManager {
                             let duration = start_time.elapsed();
                             error!(
                                 repository_id = %repository_id,
+                                request_id = request_id.as_deref().unwrap_or_default(),
                                 duration_ms = duration.as_millis(),
                                 error = %e,
                                 "❌ Query failed"
@@ -340,42 +895,102 @@ impl RepositoryManager {
 
                     let _ = response_tx.send(result);
                 }
+                IndexingCommand::SearchRepository {
+                    repository_id,
+                    query,
+                    request_id,
+                    response_tx,
+                } => {
+                    info!(
+                        repository_id = %repository_id,
+                        request_id = request_id.as_deref().unwrap_or_default(),
+                        query = %query.question,
+                        "🔍 Processing repository search"
+                    );
+
+                    let rag_query = to_rag_query(&query, rag_pipeline.config());
+                    let index = indexes.read().await.get(&repository_id).cloned();
+                    let search_result = match &index {
+                        Some(index) => index.lock().await.search(&rag_query).await,
+                        None => rag_pipeline.search(&rag_query).await,
+                    };
+                    let result = search_result
+                        .map(|results| {
+                            results
+                                .into_iter()
+                                .map(|result| CodeSearchResult {
+                                    file_path: result
+                                        .chunk
+                                        .metadata
+                                        .get("file_path")
+                                        .or_else(|| result.chunk.metadata.get("source"))
+                                        .and_then(|path| path.as_str())
+                                        .map(str::to_string),
+                                    content: result.chunk.content,
+                                    score: result.score,
+                                })
+                                .collect()
+                        })
+                        .map_err(|e| {
+                            error!(
+                                repository_id = %repository_id,
+                                request_id = request_id.as_deref().unwrap_or_default(),
+                                error = %e,
+                                "❌ Search failed"
+                            );
+                            format!("Search failed: {}", e)
+                        });
+
+                    let _ = response_tx.send(result);
+                }
                 IndexingCommand::StreamQueryRepository {
                     repository_id,
                     query,
+                    request_id,
                     stream_tx,
                 } => {
                     let _start_time = Instant::now();
                     info!(
                         repository_id = %repository_id,
+                        request_id = request_id.as_deref().unwrap_or_default(),
                         question = %query.question,
                         "🔍 Processing stream query"
                     );
 
-                    // Send initial chunk to indicate query started
-                    let _ = stream_tx.send(QueryStreamChunk {
+                    // Send initial chunk to indicate query started, skipping
+                    // the query if the client went away while it was queued
+                    let started = stream_tx.send(QueryStreamChunk {
                         chunk_type: QueryChunkType::Content,
                         content: "".to_string(),
                         is_final: false,
                         sources: None,
                         metadata: None,
                     });
+                    if started.is_err() {
+                        continue;
+                    }
 
                     // For now, simulate streaming by chunking a regular response
                     // TODO: Implement true streaming when wikify-rag supports it
-                    let rag_query = wikify_rag::create_simple_query(&query.question);
+                    let rag_query = to_rag_query(&query, rag_pipeline.config());
+                    let index = indexes.read().await.get(&repository_id).cloned();
+                    let rag_result = match &index {
+                        Some(index) => index.lock().await.ask(rag_query).await,
+                        None => rag_pipeline.ask(rag_query).await,
+                    };
 
-                    match rag_pipeline.ask(rag_query).await {
+                    match rag_result {
                         Ok(rag_response) => {
                             // Simulate streaming by sending the response in chunks
                             let words: Vec<&str> = rag_response.answer.split_whitespace().collect();
                             let chunk_size = 5; // Send 5 words at a time
 
+                            let mut disconnected = false;
                             for (i, chunk) in words.chunks(chunk_size).enumerate() {
                                 let content = chunk.join(" ");
                                 let is_final = i == (words.len() + chunk_size - 1) / chunk_size - 1;
 
-                                let _ = stream_tx.send(QueryStreamChunk {
+                                let sent = stream_tx.send(QueryStreamChunk {
                                     chunk_type: QueryChunkType::Content,
                                     content: if is_final {
                                         content
@@ -386,10 +1001,22 @@ impl RepositoryManager {
                                     sources: None,
                                     metadata: None,
                                 });
+                                // Stop as soon as the client has gone away
+                                if sent.is_err() {
+                                    disconnected = true;
+                                    break;
+                                }
 
                                 // Small delay to simulate streaming
                                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                             }
+                            if disconnected {
+                                info!(
+                                    "🔌 Stream query client disconnected for repository: {}",
+                                    repository_id
+                                );
+                                continue;
+                            }
 
                             // Extract sources
                             let mut sources = Vec::new();
@@ -434,6 +1061,23 @@ impl RepositoryManager {
                                 "generation_time_ms".to_string(),
                                 rag_response.metadata.generation_time_ms.to_string(),
                             );
+                            // Token counts, used for usage tracking
+                            metadata.insert(
+                                "query_tokens".to_string(),
+                                rag_response.metadata.query_tokens.to_string(),
+                            );
+                            metadata.insert(
+                                "prompt_tokens".to_string(),
+                                rag_response.metadata.prompt_tokens.to_string(),
+                            );
+                            metadata.insert(
+                                "generation_tokens".to_string(),
+                                rag_response.metadata.generation_tokens.to_string(),
+                            );
+                            metadata.insert(
+                                "model_used".to_string(),
+                                rag_response.metadata.model_used.clone(),
+                            );
 
                             // Send final completion chunk
                             let _ = stream_tx.send(QueryStreamChunk {
@@ -451,8 +1095,10 @@ impl RepositoryManager {
                         }
                         Err(e) => {
                             error!(
-                                "❌ Stream query failed for repository {}: {}",
-                                repository_id, e
+                                repository_id = %repository_id,
+                                request_id = request_id.as_deref().unwrap_or_default(),
+                                error = %e,
+                                "❌ Stream query failed"
                             );
                             let _ = stream_tx.send(QueryStreamChunk {
                                 chunk_type: QueryChunkType::Error,
@@ -467,7 +1113,7 @@ impl RepositoryManager {
             }
         }
 
-        info!("🛑 Enhanced indexing worker shutting down");
+        info!("🛑 Enhanced query worker shutting down");
     }
 
     /// Handle repository indexing with detailed progress tracking and multiple access modes
@@ -475,9 +1121,12 @@ impl RepositoryManager {
         rag_pipeline: &mut RagPipeline,
         repository_id: &str,
         repository_url: &str,
+        mirror_urls: &[String],
+        max_size_bytes: Option<u64>,
         progress_tx: &broadcast::Sender<IndexingUpdate>,
+        cancel: &CancellationToken,
         start_time: Instant,
-    ) -> Result<String, String> {
+    ) -> Result<IndexingStats, IndexingFailure> {
         // Send initial progress
         let _ = progress_tx.send(IndexingUpdate::progress(
             repository_id.to_string(),
@@ -485,8 +1134,43 @@ impl RepositoryManager {
             "Analyzing repository access options...".to_string(),
         ));
 
-        // Get repository access information using unified processor
-        let _access_info = Self::get_repository_access_info(repository_url).await;
+        // Get repository access information using unified processor (this may clone)
+        let access_info = tokio::select! {
+            access_info = Self::get_repository_access_info(repository_url, mirror_urls) => access_info,
+            _ = cancel.cancelled() => {
+                return Err(Self::indexing_cancelled(repository_id, 0.1, progress_tx));
+            }
+        };
+
+        // Repositories added under a size limit are measured once cloned
+        if let (Some(max_size_bytes), Some(local_path)) =
+            (max_size_bytes, access_info.local_path.clone())
+        {
+            let size = tokio::task::spawn_blocking(move || {
+                wikify_repo::directory_size(std::path::Path::new(&local_path))
+            })
+            .await
+            .unwrap_or(0);
+            if size > max_size_bytes {
+                let error_msg = format!(
+                    "Repository is {} MB, larger than the {} MB allowed",
+                    size / (1024 * 1024),
+                    max_size_bytes / (1024 * 1024)
+                );
+                let _ = progress_tx.send(IndexingUpdate::error(
+                    repository_id.to_string(),
+                    error_msg.clone(),
+                ));
+                return Err(IndexingFailure::Failed(error_msg));
+            }
+        }
+
+        // With mirrors configured, index the clone the processor resolved so the
+        // RAG pipeline doesn't retry a primary remote that may be unreachable
+        let index_target = match access_info.local_path {
+            Some(local_path) if !mirror_urls.is_empty() => local_path,
+            _ => repository_url.to_string(),
+        };
 
         let _ = progress_tx.send(IndexingUpdate::progress(
             repository_id.to_string(),
@@ -494,32 +1178,28 @@ impl RepositoryManager {
             "Preparing repository indexing...".to_string(),
         ));
 
-        // Create progress callback that forwards to the broadcaster
+        // Forward progress to the broadcaster and remember how far indexing
+        // got in case it is cancelled
         let repository_id_clone = repository_id.to_string();
-        let progress_tx_clone = progress_tx.clone();
-        let progress_callback = Box::new(
-            move |stage: String, percentage: f64, current_item: Option<String>| {
-                // RAG pipeline should send percentage as 0.0-100.0, convert to 0.0-1.0 for IndexingUpdate
-                let normalized_percentage = (percentage / 100.0).clamp(0.0, 1.0);
-
-                // Create stage description with current item if available
-                let stage_description = if let Some(item) = current_item {
-                    format!("{}: {}", stage, item)
-                } else {
-                    stage
-                };
-
-                // Send only one progress update per callback to avoid duplicates
-                let _ = progress_tx_clone.send(IndexingUpdate::progress(
+        let broadcast =
+            BroadcastProgress::new(progress_tx.clone(), move |update: &ProgressUpdate| {
+                IndexingUpdate::progress(
                     repository_id_clone.clone(),
-                    normalized_percentage,
-                    stage_description,
-                ));
-            },
-        );
+                    update.progress,
+                    update.to_string(),
+                )
+            });
+        let last_progress = Arc::new(std::sync::Mutex::new(0.2));
+        let last_progress_clone = last_progress.clone();
+        let progress = CallbackProgress::new(move |update: &ProgressUpdate| {
+            if let Ok(mut last) = last_progress_clone.lock() {
+                *last = update.progress;
+            }
+            broadcast.report(update);
+        });
 
         match rag_pipeline
-            .index_repository_with_progress(repository_url, Some(progress_callback))
+            .index_repository_cancellable(&index_target, &progress, cancel)
             .await
         {
             Ok(stats) => {
@@ -543,7 +1223,15 @@ impl RepositoryManager {
                     ),
                 ));
 
-                Ok(stats.summary())
+                Ok(stats)
+            }
+            Err(wikify_rag::RagError::Cancelled) => {
+                let progress = last_progress.lock().map(|p| *p).unwrap_or(0.0);
+                Err(Self::indexing_cancelled(
+                    repository_id,
+                    progress,
+                    progress_tx,
+                ))
             }
             Err(e) => {
                 let duration = start_time.elapsed();
@@ -564,16 +1252,39 @@ impl RepositoryManager {
                     ),
                 ));
 
-                Err(format!("Failed to index repository: {}", e))
+                Err(IndexingFailure::Failed(format!(
+                    "Failed to index repository: {}",
+                    e
+                )))
             }
         }
     }
 
+    /// Report a cancelled indexing run
+    fn indexing_cancelled(
+        repository_id: &str,
+        progress: f64,
+        progress_tx: &broadcast::Sender<IndexingUpdate>,
+    ) -> IndexingFailure {
+        info!(
+            repository_id = %repository_id,
+            progress = progress,
+            "🛑 Repository indexing cancelled"
+        );
+        let _ = progress_tx.send(IndexingUpdate::cancelled(
+            repository_id.to_string(),
+            progress,
+            format!("Indexing cancelled at {:.0}%", progress * 100.0),
+        ));
+        IndexingFailure::Cancelled { progress }
+    }
+
     /// Extract enhanced repository metadata using unified processor
     async fn extract_repository_metadata(
         &self,
         url: &str,
         _repo_type: &str,
+        mirror_urls: &[String],
     ) -> ApplicationResult<HashMap<String, String>> {
         use wikify_core::RepositoryAccessConfig;
         use wikify_repo::RepositoryProcessor;
@@ -590,7 +1301,10 @@ impl RepositoryManager {
             });
 
         let processor = RepositoryProcessor::new(&base_path);
-        let config = RepositoryAccessConfig::default();
+        let config = RepositoryAccessConfig {
+            mirror_urls: mirror_urls.to_vec(),
+            ..Default::default()
+        };
 
         match processor.access_repository(url, Some(config)).await {
             Ok(access) => {
@@ -607,6 +1321,9 @@ impl RepositoryManager {
                     "access_mode".to_string(),
                     format!("{:?}", access.access_mode),
                 );
+                if let Some(remote_url) = &access.remote_url {
+                    metadata.insert("remote_url".to_string(), remote_url.clone());
+                }
 
                 // Try to get additional metadata via API if using API mode
                 if matches!(access.access_mode, wikify_core::RepoAccessMode::Api) {
@@ -645,18 +1362,19 @@ impl RepositoryManager {
         repo_info: &wikify_core::RepoInfo,
         repo_type: &str,
     ) -> Result<HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {
+        use wikify_core::secrets::secret;
         use wikify_repo::{ApiClientConfig, ApiClientFactory};
 
         let mut metadata = HashMap::new();
 
         // Create API client configuration
         let config = match repo_type.to_lowercase().as_str() {
-            "github" => ApiClientConfig::github(std::env::var("GITHUB_TOKEN").ok()),
-            "gitlab" => ApiClientConfig::gitlab(None, std::env::var("GITLAB_TOKEN").ok()),
-            "bitbucket" => ApiClientConfig::bitbucket(std::env::var("BITBUCKET_TOKEN").ok()),
+            "github" => ApiClientConfig::github(secret("GITHUB_TOKEN")),
+            "gitlab" => ApiClientConfig::gitlab(None, secret("GITLAB_TOKEN")),
+            "bitbucket" => ApiClientConfig::bitbucket(secret("BITBUCKET_TOKEN")),
             "gitea" => {
                 if let Ok(base_url) = std::env::var("GITEA_BASE_URL") {
-                    ApiClientConfig::gitea(base_url, std::env::var("GITEA_TOKEN").ok())
+                    ApiClientConfig::gitea(base_url, secret("GITEA_TOKEN"))
                 } else {
                     return Ok(metadata);
                 }
@@ -703,7 +1421,10 @@ impl RepositoryManager {
     }
 
     /// Get repository access information using the unified processor
-    async fn get_repository_access_info(repository_url: &str) -> wikify_core::RepoInfo {
+    async fn get_repository_access_info(
+        repository_url: &str,
+        mirror_urls: &[String],
+    ) -> wikify_core::RepoInfo {
         use wikify_core::RepositoryAccessConfig;
         use wikify_repo::RepositoryProcessor;
 
@@ -717,7 +1438,10 @@ impl RepositoryManager {
             });
 
         let processor = RepositoryProcessor::new(&base_path);
-        let config = RepositoryAccessConfig::default(); // Use auto-detection
+        let config = RepositoryAccessConfig {
+            mirror_urls: mirror_urls.to_vec(),
+            ..Default::default() // Use auto-detection
+        };
 
         match processor
             .access_repository(repository_url, Some(config))
@@ -729,7 +1453,12 @@ impl RepositoryManager {
                     access_mode = ?access.access_mode,
                     "✅ Repository access determined via unified processor"
                 );
-                access.repo_info
+                wikify_core::RepoInfo {
+                    local_path: access
+                        .local_path
+                        .map(|path| path.to_string_lossy().to_string()),
+                    ..access.repo_info
+                }
             }
             Err(e) => {
                 warn!(
@@ -761,35 +1490,41 @@ impl RepositoryManager {
             "RAG pipeline not initialized. Check server logs for LLM API configuration.";
 
         match command {
-            IndexingCommand::IndexRepository {
+            IndexingCommand::QueryRepository {
                 repository_id,
+                request_id,
                 response_tx,
                 ..
             } => {
                 warn!(
                     repository_id = %repository_id,
-                    "🚫 Rejecting index request - worker unhealthy"
+                    request_id = request_id.as_deref().unwrap_or_default(),
+                    "🚫 Rejecting query request - worker unhealthy"
                 );
                 let _ = response_tx.send(Err(error_msg.to_string()));
             }
-            IndexingCommand::QueryRepository {
+            IndexingCommand::SearchRepository {
                 repository_id,
+                request_id,
                 response_tx,
                 ..
             } => {
                 warn!(
                     repository_id = %repository_id,
-                    "🚫 Rejecting query request - worker unhealthy"
+                    request_id = request_id.as_deref().unwrap_or_default(),
+                    "🚫 Rejecting search request - worker unhealthy"
                 );
                 let _ = response_tx.send(Err(error_msg.to_string()));
             }
             IndexingCommand::StreamQueryRepository {
                 repository_id,
+                request_id,
                 stream_tx,
                 ..
             } => {
                 warn!(
                     repository_id = %repository_id,
+                    request_id = request_id.as_deref().unwrap_or_default(),
                     "🚫 Rejecting stream query request - worker unhealthy"
                 );
                 let _ = stream_tx.send(QueryStreamChunk {
@@ -806,7 +1541,7 @@ impl RepositoryManager {
     /// Add a new repository with enhanced metadata extraction
     pub async fn add_repository(
         &self,
-        _context: &PermissionContext,
+        context: &PermissionContext,
         url: String,
         repo_type: String,
         owner_id: Option<String>,
@@ -820,9 +1555,29 @@ impl RepositoryManager {
 
         // Create repository index
         let mut repo = RepositoryIndex::new(url.clone(), repo_type.clone(), owner_id);
+        repo.organization_id = context.organization_id().map(str::to_string);
+        repo.visibility = match options.visibility {
+            Some(RepositoryVisibility::Org) if repo.organization_id.is_none() => {
+                return Err(ApplicationError::Config {
+                    message: "Organization visibility requires an organization".to_string(),
+                });
+            }
+            Some(visibility) => visibility,
+            // Repositories added in an organization are shared with its members
+            None if repo.organization_id.is_some() => RepositoryVisibility::Org,
+            None => RepositoryVisibility::Private,
+        };
+
+        if !options.mirror_urls.is_empty() {
+            repo.metadata
+                .insert("mirror_urls".to_string(), options.mirror_urls.join(","));
+        }
 
         // Enhanced: Extract repository metadata using wikify-repo
-        if let Ok(enhanced_metadata) = self.extract_repository_metadata(&url, &repo_type).await {
+        if let Ok(enhanced_metadata) = self
+            .extract_repository_metadata(&url, &repo_type, &options.mirror_urls)
+            .await
+        {
             info!(
                 repository_id = %repo.id,
                 "✅ Enhanced metadata extracted successfully"
@@ -842,6 +1597,14 @@ impl RepositoryManager {
 
         let repo_id = repo.id.clone();
 
+        if let Some(token) = options.api_token {
+            self.scheduler
+                .access_tokens
+                .write()
+                .await
+                .insert(repo_id.clone(), token);
+        }
+
         // Store repository in persistent storage
         self.storage
             .save_repository(&repo)
@@ -866,7 +1629,8 @@ impl RepositoryManager {
                 repository_id = %repo_id,
                 "🔄 Auto-index enabled, starting indexing"
             );
-            self.start_indexing(repo_id.clone()).await?;
+            self.start_indexing(context, repo_id.clone(), JobPriority::Background)
+                .await?;
         } else {
             info!(
                 repository_id = %repo_id,
@@ -877,11 +1641,21 @@ impl RepositoryManager {
         Ok(repo_id)
     }
 
-    /// Start indexing a repository using message passing
-    pub async fn start_indexing(&self, repository_id: String) -> ApplicationResult<()> {
+    /// Queue a repository for indexing
+    ///
+    /// The job starts as soon as an indexing slot is free. Higher priority jobs
+    /// start first, and jobs of the same priority are shared fairly between
+    /// the users who requested them.
+    pub async fn start_indexing(
+        &self,
+        context: &PermissionContext,
+        repository_id: String,
+        priority: JobPriority,
+    ) -> ApplicationResult<IndexingJob> {
         info!(
             repository_id = %repository_id,
-            "🚀 Initiating repository indexing"
+            priority = ?priority,
+            "🚀 Queueing repository indexing"
         );
 
         // Check if worker is healthy
@@ -896,180 +1670,283 @@ impl RepositoryManager {
             });
         }
 
-        // Get repository and update status
-        let repo = self
-            .storage
-            .load_repository(&repository_id)
-            .await
-            .map_err(|e| ApplicationError::Internal {
-                message: format!("Failed to load repository: {}", e),
-                source: None,
-            })?
-            .ok_or_else(|| ApplicationError::NotFound {
-                message: format!("Repository not found: {}", repository_id),
-            })?;
-
-        // Update status to indexing
-        self.storage
-            .update_status(&repository_id, IndexingStatus::Indexing, 0.0)
-            .await
-            .map_err(|e| ApplicationError::Internal {
-                message: format!("Failed to update repository status: {}", e),
-                source: None,
-            })?;
-
-        let repository_url = repo.url.clone();
-
-        // Create response channel
-        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        let repo = self.get_repository(context, &repository_id).await?;
+        let user_id = context
+            .user_id()
+            .map(str::to_string)
+            .or_else(|| repo.owner_id.clone());
+
+        let job = {
+            let mut queue = self.scheduler.queue.lock().await;
+            if queue.contains(&repository_id) {
+                return Err(ApplicationError::Config {
+                    message: format!("Repository is already being indexed: {}", repository_id),
+                });
+            }
 
-        // Send indexing command to worker
-        let command = IndexingCommand::IndexRepository {
-            repository_id: repository_id.clone(),
-            repository_url,
-            response_tx,
+            // Mark the repository as queued before the scheduler can pick it up
+            self.storage
+                .update_status(&repository_id, IndexingStatus::Pending, 0.0)
+                .await
+                .map_err(|e| ApplicationError::Internal {
+                    message: format!("Failed to update repository status: {}", e),
+                    source: None,
+                })?;
+
+            queue.push(
+                repository_id.clone(),
+                user_id,
+                context.request_id().map(str::to_string),
+                priority,
+            )
         };
+        self.scheduler.wake.notify_one();
 
-        debug!(
+        info!(
             repository_id = %repository_id,
-            "📤 Sending indexing command to worker"
+            job_id = %job.id,
+            "✅ Indexing job queued successfully"
         );
+        Ok(job)
+    }
 
-        if let Err(_) = self.indexing_tx.send(command) {
-            error!(
-                repository_id = %repository_id,
-                "❌ Failed to send indexing command - worker channel closed"
-            );
-            return Err(ApplicationError::Config {
-                message: "RAG indexing worker is not available".to_string(),
-            });
+    /// Cancel the queued or running indexing job of a repository
+    ///
+    /// A queued job is removed right away. Cancelling a running job is
+    /// cooperative: it stops at its next checkpoint and marks the repository
+    /// as cancelled, keeping the progress it reached.
+    pub async fn cancel_indexing(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+    ) -> ApplicationResult<()> {
+        self.get_repository(context, repository_id).await?;
+
+        let outcome = self.scheduler.queue.lock().await.cancel(repository_id);
+        match outcome {
+            Some(CancelOutcome::Dequeued) => {
+                let _ = self.progress_broadcaster.send(IndexingUpdate::cancelled(
+                    repository_id.to_string(),
+                    0.0,
+                    "Indexing cancelled before it started".to_string(),
+                ));
+                self.storage
+                    .update_status(repository_id, IndexingStatus::Cancelled, 0.0)
+                    .await
+                    .map_err(|e| ApplicationError::Internal {
+                        message: format!("Failed to update repository status: {}", e),
+                        source: None,
+                    })?;
+            }
+            Some(CancelOutcome::Cancelling) => {}
+            None => {
+                return Err(ApplicationError::Config {
+                    message: format!("Repository is not being indexed: {}", repository_id),
+                });
+            }
         }
 
         info!(
             repository_id = %repository_id,
-            "✅ Indexing command queued successfully"
+            outcome = ?outcome,
+            "🛑 Indexing cancellation requested"
         );
-
-        // Spawn a task to handle the response and update repository status
-        let storage = self.storage.clone();
-        let repo_id_clone = repository_id.clone();
-        tokio::spawn(async move {
-            match response_rx.await {
-                Ok(Ok(stats_summary)) => {
-                    // Update repository status to completed
-                    if let Err(e) = storage
-                        .update_status(&repo_id_clone, IndexingStatus::Completed, 1.0)
-                        .await
-                    {
-                        error!(
-                            repository_id = %repo_id_clone,
-                            error = %e,
-                            "Failed to update repository status to completed"
-                        );
-                    } else {
-                        info!(
-                            repository_id = %repo_id_clone,
-                            stats = %stats_summary,
-                            "✅ Repository indexing completed successfully"
-                        );
-                    }
-                }
-                Ok(Err(error_msg)) => {
-                    // Update repository status to failed
-                    if let Err(e) = storage
-                        .update_status(&repo_id_clone, IndexingStatus::Failed, 0.0)
-                        .await
-                    {
-                        error!(
-                            repository_id = %repo_id_clone,
-                            error = %e,
-                            "Failed to update repository status to failed"
-                        );
-                    }
-                    error!(
-                        repository_id = %repo_id_clone,
-                        error_msg = %error_msg,
-                        "❌ Repository indexing failed"
-                    );
-                }
-                Err(_) => {
-                    error!(
-                        repository_id = %repo_id_clone,
-                        "❌ Failed to receive indexing response - worker may have crashed"
-                    );
-                    if let Err(e) = storage
-                        .update_status(&repo_id_clone, IndexingStatus::Failed, 0.0)
-                        .await
-                    {
-                        error!(
-                            repository_id = %repo_id_clone,
-                            error = %e,
-                            "Failed to update repository status to failed"
-                        );
-                    }
-                }
-            }
-        });
-
         Ok(())
     }
 
-    /// List all repositories
+    /// List running and queued indexing jobs
+    ///
+    /// Running jobs come first, followed by queued jobs in the order they
+    /// will start.
+    pub async fn list_indexing_jobs(&self) -> Vec<IndexingJob> {
+        self.scheduler.queue.lock().await.jobs()
+    }
+
+    /// List a page of the repositories of a tenant that the caller can see
+    /// and that pass a filter
+    ///
+    /// Personal listings also include repositories shared with the user.
     pub async fn list_repositories(
         &self,
         context: &PermissionContext,
-    ) -> ApplicationResult<Vec<RepositoryIndex>> {
-        let owner_id = context.identity.as_ref().map(|u| u.user_id.as_str());
-        let repos = self
+        scope: &TenantScope,
+        filter: &RepositoryFilter,
+        page: &PageRequest,
+    ) -> ApplicationResult<Page<RepositoryIndex>> {
+        // Storage can page the listing itself unless repositories are added
+        // or hidden afterwards
+        let paged_by_storage =
+            Self::sees_everything(context) && !matches!(scope, TenantScope::Personal(_));
+        let storage_page = if paged_by_storage {
+            *page
+        } else {
+            PageRequest::all()
+        };
+        let listed = self
             .storage
-            .list_repositories(owner_id)
+            .list_repositories(scope, filter, &storage_page)
             .await
             .map_err(|e| ApplicationError::Internal {
                 message: format!("Failed to list repositories: {}", e),
                 source: None,
             })?;
+        if paged_by_storage {
+            info!(
+                "📋 Listed {} of {} repositories",
+                listed.items.len(),
+                listed.total
+            );
+            return Ok(listed);
+        }
+
+        let mut repos = listed.items;
+        if let TenantScope::Personal(user_id) = scope {
+            let shared: Vec<RepositoryIndex> = self
+                .list_all_repositories()
+                .await?
+                .into_iter()
+                .filter(|repo| repo.is_shared_with(user_id) && filter.matches(repo))
+                .filter(|repo| !repos.iter().any(|own| own.id == repo.id))
+                .collect();
+            repos.extend(shared);
+        }
+        repos.retain(|repo| Self::is_visible(context, repo));
+        filter.sort(&mut repos);
+
+        let listed = page.paginate(repos);
+        info!(
+            "📋 Listed {} of {} repositories",
+            listed.items.len(),
+            listed.total
+        );
+        Ok(listed)
+    }
 
-        info!("📋 Listed {} repositories", repos.len());
-        Ok(repos)
+    /// List repositories of all owners
+    pub async fn list_all_repositories(&self) -> ApplicationResult<Vec<RepositoryIndex>> {
+        self.storage
+            .list_repositories(
+                &TenantScope::All,
+                &RepositoryFilter::default(),
+                &PageRequest::all(),
+            )
+            .await
+            .map(|page| page.items)
+            .map_err(|e| ApplicationError::Internal {
+                message: format!("Failed to list repositories: {}", e),
+                source: None,
+            })
     }
 
     /// Get a specific repository
+    ///
+    /// Repositories the caller cannot see are reported as not found.
     pub async fn get_repository(
         &self,
-        _context: &PermissionContext,
+        context: &PermissionContext,
         repository_id: &str,
     ) -> ApplicationResult<RepositoryIndex> {
+        self.load_repository(repository_id)
+            .await?
+            .filter(|repo| Self::is_visible(context, repo))
+            .ok_or_else(|| ApplicationError::NotFound {
+                message: format!("Repository not found: {}", repository_id),
+            })
+    }
+
+    /// Check whether a repository is registered, regardless of its visibility
+    pub async fn repository_exists(&self, repository_id: &str) -> ApplicationResult<bool> {
+        Ok(self.load_repository(repository_id).await?.is_some())
+    }
+
+    /// Change the visibility of a repository and who it is shared with
+    ///
+    /// Grants are applied before revocations. Callers are expected to have
+    /// checked that the context may manage the repository.
+    pub async fn update_sharing(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        visibility: Option<RepositoryVisibility>,
+        share_with: &[String],
+        revoke: &[String],
+    ) -> ApplicationResult<RepositoryIndex> {
+        let mut repo = self.get_repository(context, repository_id).await?;
+
+        if let Some(visibility) = visibility {
+            if visibility == RepositoryVisibility::Org && repo.organization_id.is_none() {
+                return Err(ApplicationError::Config {
+                    message: format!(
+                        "Repository {} does not belong to an organization",
+                        repository_id
+                    ),
+                });
+            }
+            repo.visibility = visibility;
+        }
+        for user_id in share_with {
+            if repo.owner_id.as_deref() != Some(user_id.as_str()) {
+                repo.grant_access(user_id.clone(), context.user_id().map(str::to_string));
+            }
+        }
+        for user_id in revoke {
+            repo.revoke_access(user_id);
+        }
+
+        self.storage
+            .save_repository(&repo)
+            .await
+            .map_err(|e| ApplicationError::Internal {
+                message: format!("Failed to save repository: {}", e),
+                source: None,
+            })?;
+
+        info!(
+            repository_id = %repository_id,
+            visibility = %repo.visibility,
+            shared_with = repo.share_grants.len(),
+            "🔐 Repository sharing updated"
+        );
+        Ok(repo)
+    }
+
+    /// Load a repository without checking its visibility
+    async fn load_repository(
+        &self,
+        repository_id: &str,
+    ) -> ApplicationResult<Option<RepositoryIndex>> {
         self.storage
             .load_repository(repository_id)
             .await
             .map_err(|e| ApplicationError::Internal {
                 message: format!("Failed to load repository: {}", e),
                 source: None,
-            })?
-            .ok_or_else(|| ApplicationError::NotFound {
-                message: format!("Repository not found: {}", repository_id),
             })
     }
 
+    /// Whether the caller may see a repository
+    ///
+    /// Contexts without an identity (local and open modes) and admins see
+    /// every repository. Organization membership is checked by the caller.
+    fn is_visible(context: &PermissionContext, repo: &RepositoryIndex) -> bool {
+        Self::sees_everything(context)
+            || repo.is_visible_to(context.user_id(), context.organization_id())
+    }
+
+    /// Whether the caller can see every repository regardless of visibility
+    fn sees_everything(context: &PermissionContext) -> bool {
+        context.identity.is_none() || context.is_admin()
+    }
+
     /// Query a repository using message passing
     pub async fn query_repository(
         &self,
-        _context: &PermissionContext,
+        context: &PermissionContext,
         repository_id: &str,
         query: RepositoryQuery,
     ) -> ApplicationResult<RepositoryQueryResponse> {
-        // Check if repository exists and is ready
-        let repo = self
-            .storage
-            .load_repository(repository_id)
-            .await
-            .map_err(|e| ApplicationError::Internal {
-                message: format!("Failed to load repository: {}", e),
-                source: None,
-            })?
-            .ok_or_else(|| ApplicationError::NotFound {
-                message: format!("Repository not found: {}", repository_id),
-            })?;
+        // Check if repository exists, is visible to the caller and is ready
+        let repo = self.get_repository(context, repository_id).await?;
 
         if !repo.is_ready() {
             return Err(ApplicationError::Config {
@@ -1087,10 +1964,15 @@ impl RepositoryManager {
         let command = IndexingCommand::QueryRepository {
             repository_id: repository_id.to_string(),
             query,
+            request_id: context.request_id().map(str::to_string),
             response_tx,
         };
 
+        self.metrics.start_query();
+        let start_time = Instant::now();
         if let Err(_) = self.indexing_tx.send(command) {
+            self.metrics.record_query_failure();
+            self.metrics.record_repository_query_failure(repository_id);
             return Err(ApplicationError::Config {
                 message: "Indexing worker is not available".to_string(),
             });
@@ -1098,12 +1980,66 @@ impl RepositoryManager {
 
         // Wait for response
         match response_rx.await {
-            Ok(Ok(response)) => Ok(response),
-            Ok(Err(error_msg)) => Err(ApplicationError::Config {
-                message: format!("Query failed: {}", error_msg),
-            }),
+            Ok(Ok(response)) => {
+                self.metrics
+                    .record_query_success(start_time.elapsed(), false);
+                self.metrics
+                    .record_repository_query_success(repository_id, start_time.elapsed());
+                Ok(response)
+            }
+            Ok(Err(error_msg)) => {
+                self.metrics.record_query_failure();
+                self.metrics.record_repository_query_failure(repository_id);
+                Err(ApplicationError::Config {
+                    message: format!("Query failed: {}", error_msg),
+                })
+            }
+            Err(_) => {
+                self.metrics.record_query_failure();
+                self.metrics.record_repository_query_failure(repository_id);
+                Err(ApplicationError::Config {
+                    message: "Failed to receive query response".to_string(),
+                })
+            }
+        }
+    }
+
+    /// Search the indexed chunks of a repository
+    pub async fn search_repository(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        query: RepositoryQuery,
+    ) -> ApplicationResult<Vec<CodeSearchResult>> {
+        let repo = self.get_repository(context, repository_id).await?;
+
+        if !repo.is_ready() {
+            return Err(ApplicationError::Config {
+                message: format!(
+                    "Repository not ready for searching: {} (status: {:?})",
+                    repository_id, repo.status
+                ),
+            });
+        }
+
+        let (response_tx, response_rx) = tokio::sync::oneshot::channel();
+        let command = IndexingCommand::SearchRepository {
+            repository_id: repository_id.to_string(),
+            query,
+            request_id: context.request_id().map(str::to_string),
+            response_tx,
+        };
+
+        if self.indexing_tx.send(command).is_err() {
+            return Err(ApplicationError::Config {
+                message: "Indexing worker is not available".to_string(),
+            });
+        }
+
+        match response_rx.await {
+            Ok(result) => result.map_err(|message| ApplicationError::Config { message }),
             Err(_) => Err(ApplicationError::Config {
-                message: "Failed to receive query response".to_string(),
+                message: "Failed to receive search response".to_string(),
             }),
         }
     }
@@ -1111,22 +2047,12 @@ impl RepositoryManager {
     /// Stream query a repository for real-time responses
     pub async fn stream_query_repository(
         &self,
-        _context: &PermissionContext,
+        context: &PermissionContext,
         repository_id: &str,
         query: RepositoryQuery,
     ) -> ApplicationResult<tokio::sync::mpsc::UnboundedReceiver<QueryStreamChunk>> {
-        // Check if repository exists and is ready
-        let repo = self
-            .storage
-            .load_repository(repository_id)
-            .await
-            .map_err(|e| ApplicationError::Internal {
-                message: format!("Failed to load repository: {}", e),
-                source: None,
-            })?
-            .ok_or_else(|| ApplicationError::NotFound {
-                message: format!("Repository not found: {}", repository_id),
-            })?;
+        // Check if repository exists, is visible to the caller and is ready
+        let repo = self.get_repository(context, repository_id).await?;
 
         if !repo.is_ready() {
             return Err(ApplicationError::Config {
@@ -1138,21 +2064,47 @@ impl RepositoryManager {
         }
 
         // Create stream channel
-        let (stream_tx, stream_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (stream_tx, mut worker_rx) = tokio::sync::mpsc::unbounded_channel();
 
         // Send stream query command to worker
         let command = IndexingCommand::StreamQueryRepository {
             repository_id: repository_id.to_string(),
             query,
+            request_id: context.request_id().map(str::to_string),
             stream_tx,
         };
 
+        self.metrics.start_query();
+        let start_time = Instant::now();
         if let Err(_) = self.indexing_tx.send(command) {
+            self.metrics.record_query_failure();
+            self.metrics.record_repository_query_failure(repository_id);
             return Err(ApplicationError::Config {
                 message: "Indexing worker is not available".to_string(),
             });
         }
 
+        // Relay the chunks to record the outcome once the answer is complete
+        let (relay_tx, stream_rx) = tokio::sync::mpsc::unbounded_channel();
+        let metrics = self.metrics.clone();
+        let repository_id = repository_id.to_string();
+        tokio::spawn(async move {
+            let mut succeeded = false;
+            while let Some(chunk) = worker_rx.recv().await {
+                if chunk.is_final {
+                    succeeded = !matches!(chunk.chunk_type, QueryChunkType::Error);
+                }
+                let _ = relay_tx.send(chunk);
+            }
+            if succeeded {
+                metrics.record_query_success(start_time.elapsed(), false);
+                metrics.record_repository_query_success(&repository_id, start_time.elapsed());
+            } else {
+                metrics.record_query_failure();
+                metrics.record_repository_query_failure(&repository_id);
+            }
+        });
+
         Ok(stream_rx)
     }
 
@@ -1161,6 +2113,57 @@ impl RepositoryManager {
         self.progress_broadcaster.subscribe()
     }
 
+    /// Update the index of a local repository for files that changed
+    ///
+    /// Only repositories indexed by this process can be updated; others need
+    /// a full (re)index first.
+    pub async fn update_repository_files(
+        &self,
+        context: &PermissionContext,
+        repository_id: &str,
+        changed_files: &[std::path::PathBuf],
+    ) -> ApplicationResult<wikify_rag::IndexUpdate> {
+        let repo = self.get_repository(context, repository_id).await?;
+        if repo.repo_type != "local" {
+            return Err(ApplicationError::Config {
+                message: format!(
+                    "Only local repositories can be updated file by file: {}",
+                    repository_id
+                ),
+            });
+        }
+
+        let index = self
+            .scheduler
+            .indexes
+            .read()
+            .await
+            .get(repository_id)
+            .cloned()
+            .ok_or_else(|| ApplicationError::Config {
+                message: format!(
+                    "Repository has no index loaded, reindex it first: {}",
+                    repository_id
+                ),
+            })?;
+
+        let update = index
+            .lock()
+            .await
+            .update_files(std::path::Path::new(&repo.url), changed_files)
+            .await
+            .map_err(|e| ApplicationError::Internal {
+                message: format!("Failed to update index: {}", e),
+                source: None,
+            })?;
+        info!(
+            repository_id = %repository_id,
+            summary = %update.summary(),
+            "🔄 Repository index updated"
+        );
+        Ok(update)
+    }
+
     /// Remove a repository
     pub async fn remove_repository(
         &self,
@@ -1180,6 +2183,16 @@ impl RepositoryManager {
                 },
             })?;
 
+        // Stop indexing it and drop its index
+        self.scheduler.queue.lock().await.cancel(repository_id);
+        self.scheduler.indexes.write().await.remove(repository_id);
+        self.scheduler
+            .access_tokens
+            .write()
+            .await
+            .remove(repository_id);
+        self.metrics.remove_repository(repository_id);
+
         info!("🗑️ Repository removed: {}", repository_id);
         Ok(())
     }
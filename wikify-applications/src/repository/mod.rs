@@ -1,11 +1,15 @@
 pub mod config;
+pub mod disk_usage;
 pub mod errors;
 pub mod manager;
+pub mod queue;
 pub mod storage;
 pub mod types;
 
 pub use config::*;
+pub use disk_usage::StorageLocations;
 pub use errors::*;
 pub use manager::*;
+pub use queue::*;
 pub use storage::*;
 pub use types::*;
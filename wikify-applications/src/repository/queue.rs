@@ -0,0 +1,260 @@
+//! Indexing job queue
+//!
+//! Decides which indexing job runs next. Jobs with a higher priority always
+//! start first; among jobs of the same priority the queue picks the user with
+//! the fewest running jobs, then the user served longest ago, so one user
+//! queueing many repositories cannot starve everyone else. Jobs of the same
+//! user start in the order they were queued.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+/// Priority of an indexing job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobPriority {
+    /// Indexing started in the background, e.g. when a repository is added
+    Background,
+    /// Reindex explicitly requested by a user who is waiting for it
+    Interactive,
+}
+
+/// State of an indexing job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// Waiting for a free indexing slot
+    Queued,
+    /// Currently indexing
+    Running,
+}
+
+/// An indexing job in the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexingJob {
+    /// Unique job ID
+    pub id: String,
+    /// Repository being indexed
+    pub repository_id: String,
+    /// User who requested the job (None for anonymous users)
+    pub user_id: Option<String>,
+    /// ID of the HTTP request that queued the job, for correlating logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Job priority
+    pub priority: JobPriority,
+    /// Current state
+    pub state: JobState,
+    /// When the job was queued
+    pub queued_at: DateTime<Utc>,
+    /// When the job started running
+    pub started_at: Option<DateTime<Utc>>,
+}
+
+/// Result of cancelling a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelOutcome {
+    /// The job had not started and was removed from the queue
+    Dequeued,
+    /// The job is running and was asked to stop
+    Cancelling,
+}
+
+/// Queue of indexing jobs with a limit on concurrently running jobs
+#[derive(Debug, Clone)]
+pub struct IndexingQueue {
+    max_concurrent: usize,
+    queued: Vec<(IndexingJob, CancellationToken)>,
+    running: Vec<(IndexingJob, CancellationToken)>,
+    /// Dispatch sequence number of the last job started per user
+    last_served: HashMap<Option<String>, u64>,
+    dispatched: u64,
+}
+
+impl IndexingQueue {
+    /// Create a queue running at most `max_concurrent` jobs at once
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            queued: Vec::new(),
+            running: Vec::new(),
+            last_served: HashMap::new(),
+            dispatched: 0,
+        }
+    }
+
+    /// Whether the repository has a queued or running job
+    pub fn contains(&self, repository_id: &str) -> bool {
+        self.queued
+            .iter()
+            .chain(&self.running)
+            .any(|(job, _)| job.repository_id == repository_id)
+    }
+
+    /// Queue a job for a repository
+    pub fn push(
+        &mut self,
+        repository_id: String,
+        user_id: Option<String>,
+        request_id: Option<String>,
+        priority: JobPriority,
+    ) -> IndexingJob {
+        let job = IndexingJob {
+            id: Uuid::new_v4().to_string(),
+            repository_id,
+            user_id,
+            request_id,
+            priority,
+            state: JobState::Queued,
+            queued_at: Utc::now(),
+            started_at: None,
+        };
+        self.queued.push((job.clone(), CancellationToken::new()));
+        job
+    }
+
+    /// Start the next job if a slot is free
+    ///
+    /// Returns the started job and the token that cancels it.
+    pub fn next(&mut self) -> Option<(IndexingJob, CancellationToken)> {
+        if self.running.len() >= self.max_concurrent {
+            return None;
+        }
+
+        let priority = self.queued.iter().map(|(job, _)| job.priority).max()?;
+        let index = self
+            .queued
+            .iter()
+            .enumerate()
+            .filter(|(_, (job, _))| job.priority == priority)
+            .min_by_key(|(index, (job, _))| {
+                let running = self
+                    .running
+                    .iter()
+                    .filter(|(other, _)| other.user_id == job.user_id)
+                    .count();
+                let last_served = self.last_served.get(&job.user_id).copied().unwrap_or(0);
+                (running, last_served, *index)
+            })
+            .map(|(index, _)| index)?;
+
+        let (mut job, cancel) = self.queued.remove(index);
+        job.state = JobState::Running;
+        job.started_at = Some(Utc::now());
+
+        self.dispatched += 1;
+        self.last_served
+            .insert(job.user_id.clone(), self.dispatched);
+        self.running.push((job.clone(), cancel.clone()));
+        Some((job, cancel))
+    }
+
+    /// Remove a finished job, freeing its slot
+    pub fn finish(&mut self, repository_id: &str) {
+        self.running
+            .retain(|(job, _)| job.repository_id != repository_id);
+    }
+
+    /// Cancel the job of a repository
+    ///
+    /// Returns None if the repository has no queued or running job.
+    pub fn cancel(&mut self, repository_id: &str) -> Option<CancelOutcome> {
+        if let Some(index) = self
+            .queued
+            .iter()
+            .position(|(job, _)| job.repository_id == repository_id)
+        {
+            self.queued.remove(index);
+            return Some(CancelOutcome::Dequeued);
+        }
+
+        self.running
+            .iter()
+            .find(|(job, _)| job.repository_id == repository_id)
+            .map(|(_, cancel)| {
+                cancel.cancel();
+                CancelOutcome::Cancelling
+            })
+    }
+
+    /// Running jobs followed by queued jobs in the order they would start
+    pub fn jobs(&self) -> Vec<IndexingJob> {
+        let mut jobs: Vec<IndexingJob> = self.running.iter().map(|(job, _)| job.clone()).collect();
+
+        let mut pending = self.clone();
+        pending.max_concurrent = usize::MAX;
+        while let Some((mut job, _)) = pending.next() {
+            job.state = JobState::Queued;
+            job.started_at = None;
+            jobs.push(job);
+        }
+        jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: &str) -> Option<String> {
+        Some(id.to_string())
+    }
+
+    fn next_repo(queue: &mut IndexingQueue) -> Option<String> {
+        queue.next().map(|(job, _)| job.repository_id)
+    }
+
+    #[test]
+    fn test_priority_and_fairness() {
+        let mut queue = IndexingQueue::new(2);
+        queue.push("a1".into(), user("alice"), None, JobPriority::Background);
+        queue.push("a2".into(), user("alice"), None, JobPriority::Background);
+        queue.push("a3".into(), user("alice"), None, JobPriority::Background);
+        queue.push("b1".into(), user("bob"), None, JobPriority::Background);
+        queue.push("c1".into(), user("carol"), None, JobPriority::Interactive);
+
+        // Interactive first, then users take turns
+        assert_eq!(next_repo(&mut queue).as_deref(), Some("c1"));
+        assert_eq!(next_repo(&mut queue).as_deref(), Some("a1"));
+        assert_eq!(next_repo(&mut queue), None, "only two jobs may run at once");
+
+        queue.finish("c1");
+        assert_eq!(next_repo(&mut queue).as_deref(), Some("b1"));
+
+        let order: Vec<_> = queue
+            .jobs()
+            .into_iter()
+            .map(|job| (job.repository_id, job.state))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("a1".to_string(), JobState::Running),
+                ("b1".to_string(), JobState::Running),
+                ("a2".to_string(), JobState::Queued),
+                ("a3".to_string(), JobState::Queued),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut queue = IndexingQueue::new(1);
+        queue.push("r1".into(), None, None, JobPriority::Background);
+        queue.push("r2".into(), None, None, JobPriority::Background);
+        let (_, cancel) = queue.next().unwrap();
+
+        assert_eq!(queue.cancel("r2"), Some(CancelOutcome::Dequeued));
+        assert_eq!(queue.cancel("r1"), Some(CancelOutcome::Cancelling));
+        assert!(cancel.is_cancelled());
+        assert!(queue.contains("r1"));
+        assert_eq!(queue.cancel("missing"), None);
+
+        queue.finish("r1");
+        assert!(!queue.contains("r1"));
+        assert!(queue.next().is_none());
+    }
+}
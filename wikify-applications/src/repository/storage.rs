@@ -1,10 +1,12 @@
 //! Repository storage backends
 //!
 //! Provides different storage implementations for repository persistence,
-//! including in-memory, SQLite, and other database backends.
+//! including in-memory, SQLite and PostgreSQL backends.
 
 use super::errors::{RepositoryError, RepositoryResult};
-use super::types::{IndexingStatus, RepositoryIndex};
+use super::types::{IndexingStatus, RepositoryFilter, RepositoryIndex, RepositorySortField};
+use crate::organization::TenantScope;
+use crate::pagination::{Page, PageRequest, SortOrder};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
@@ -21,11 +23,14 @@ pub trait RepositoryStorage: Send + Sync {
     /// Load a repository by ID
     async fn load_repository(&self, id: &str) -> RepositoryResult<Option<RepositoryIndex>>;
 
-    /// List all repositories, optionally filtered by owner
+    /// List a page of the repositories of a tenant that pass a filter,
+    /// in the order the filter asks for
     async fn list_repositories(
         &self,
-        owner_id: Option<&str>,
-    ) -> RepositoryResult<Vec<RepositoryIndex>>;
+        scope: &TenantScope,
+        filter: &RepositoryFilter,
+        page: &PageRequest,
+    ) -> RepositoryResult<Page<RepositoryIndex>>;
 
     /// Delete a repository
     async fn delete_repository(&self, id: &str) -> RepositoryResult<()>;
@@ -72,7 +77,14 @@ impl MemoryRepositoryStorage {
 
     /// Load repositories from another storage backend (for migration)
     pub async fn load_from<S: RepositoryStorage>(&self, source: &S) -> RepositoryResult<usize> {
-        let repositories = source.list_repositories(None).await?;
+        let repositories = source
+            .list_repositories(
+                &TenantScope::All,
+                &RepositoryFilter::default(),
+                &PageRequest::all(),
+            )
+            .await?
+            .items;
         let count = repositories.len();
 
         let mut storage = self.repositories.write().await;
@@ -107,21 +119,19 @@ impl RepositoryStorage for MemoryRepositoryStorage {
 
     async fn list_repositories(
         &self,
-        owner_id: Option<&str>,
-    ) -> RepositoryResult<Vec<RepositoryIndex>> {
+        scope: &TenantScope,
+        filter: &RepositoryFilter,
+        page: &PageRequest,
+    ) -> RepositoryResult<Page<RepositoryIndex>> {
         let repositories = self.repositories.read().await;
-        let repos: Vec<RepositoryIndex> = repositories
+        let mut repos: Vec<RepositoryIndex> = repositories
             .values()
-            .filter(|repo| {
-                owner_id.map_or(true, |owner| {
-                    repo.owner_id
-                        .as_ref()
-                        .map_or(false, |repo_owner| repo_owner == owner)
-                })
-            })
+            .filter(|repo| scope.matches(repo.owner_id.as_deref(), repo.organization_id.as_deref()))
+            .filter(|repo| filter.matches(repo))
             .cloned()
             .collect();
-        Ok(repos)
+        filter.sort(&mut repos);
+        Ok(page.paginate(repos))
     }
 
     async fn delete_repository(&self, id: &str) -> RepositoryResult<()> {
@@ -333,7 +343,23 @@ impl SqliteRepositoryStorage {
                 })
             },
             updated_at: Utc::now(), // Will be set to current time
-            owner_id: Some("default".to_string()), // Default owner for now
+            owner_id: row.try_get("owner_id").unwrap_or(None),
+            organization_id: row.try_get("organization_id").unwrap_or(None),
+            visibility: row
+                .try_get::<String, _>("visibility")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            share_grants: row
+                .try_get::<String, _>("share_grants")
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            disk_usage: row
+                .try_get::<Option<String>, _>("disk_usage")
+                .ok()
+                .flatten()
+                .and_then(|json| serde_json::from_str(&json).ok()),
             metadata,
         })
     }
@@ -362,13 +388,26 @@ impl RepositoryStorage for SqliteRepositoryStorage {
                 recoverable: false,
             })?;
 
+        let share_grants_json =
+            serde_json::to_string(&repo.share_grants).map_err(|e| RepositoryError::Internal {
+                message: format!("Failed to serialize share grants: {}", e),
+                component: "sqlite_storage".to_string(),
+                error_id: uuid::Uuid::new_v4().to_string(),
+                recoverable: false,
+            })?;
+
+        let disk_usage_json = repo
+            .disk_usage
+            .as_ref()
+            .and_then(|usage| serde_json::to_string(usage).ok());
+
         let status_str = Self::status_to_db_string(&repo.status);
 
         sqlx::query(
             r#"
             INSERT OR REPLACE INTO repositories
-            (id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            (id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata, owner_id, organization_id, visibility, share_grants, disk_usage)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#
         )
         .bind(&repo.id)
@@ -380,6 +419,11 @@ impl RepositoryStorage for SqliteRepositoryStorage {
         .bind(repo.indexed_at.map(|dt| dt.to_rfc3339()))
         .bind(status_str)
         .bind(metadata_json)
+        .bind(&repo.owner_id)
+        .bind(&repo.organization_id)
+        .bind(repo.visibility.to_string())
+        .bind(share_grants_json)
+        .bind(disk_usage_json)
         .execute(&self.pool)
         .await
         .map_err(|e| RepositoryError::Internal {
@@ -395,7 +439,7 @@ impl RepositoryStorage for SqliteRepositoryStorage {
 
     async fn load_repository(&self, id: &str) -> RepositoryResult<Option<RepositoryIndex>> {
         let row = sqlx::query(
-            "SELECT id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata FROM repositories WHERE id = ?"
+            "SELECT id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata, owner_id, organization_id, visibility, share_grants, disk_usage FROM repositories WHERE id = ?"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -418,19 +462,91 @@ impl RepositoryStorage for SqliteRepositoryStorage {
 
     async fn list_repositories(
         &self,
-        _owner_id: Option<&str>,
-    ) -> RepositoryResult<Vec<RepositoryIndex>> {
-        let rows = sqlx::query(
-            "SELECT id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata FROM repositories ORDER BY created_at DESC"
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| RepositoryError::Internal {
+        scope: &TenantScope,
+        filter: &RepositoryFilter,
+        page: &PageRequest,
+    ) -> RepositoryResult<Page<RepositoryIndex>> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        match scope {
+            TenantScope::All => {}
+            TenantScope::Organization(org_id) => {
+                conditions.push("organization_id = ?");
+                params.push(org_id.clone());
+            }
+            TenantScope::Personal(user_id) => {
+                conditions.push("organization_id IS NULL AND owner_id = ?");
+                params.push(user_id.clone());
+            }
+        }
+        if let Some(ref status) = filter.status {
+            conditions.push("status = ?");
+            params.push(Self::status_to_db_string(status).to_string());
+        }
+        if let Some(ref repo_type) = filter.repo_type {
+            conditions.push("repo_type = ?");
+            params.push(repo_type.clone());
+        }
+        if let Some(ref owner_id) = filter.owner_id {
+            conditions.push("owner_id = ?");
+            params.push(owner_id.clone());
+        }
+        if let Some(ref name) = filter.name {
+            // LIKE is case-insensitive for ASCII in SQLite
+            conditions.push("name LIKE ? ESCAPE '\\'");
+            let escaped = name
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            params.push(format!("%{}%", escaped));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let sort_column = match filter.sort {
+            RepositorySortField::CreatedAt => "created_at",
+            RepositorySortField::LastIndexedAt => "last_indexed_at",
+        };
+        let sort_order = match filter.order {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        };
+
+        let list_error = |e: sqlx::Error| RepositoryError::Internal {
             message: format!("Failed to list repositories from database: {}", e),
             component: "sqlite_storage".to_string(),
             error_id: uuid::Uuid::new_v4().to_string(),
             recoverable: true,
-        })?;
+        };
+
+        let count_sql = format!("SELECT COUNT(*) FROM repositories {}", where_clause);
+        let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql);
+        for param in &params {
+            count_query = count_query.bind(param);
+        }
+        let total = count_query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(list_error)?;
+
+        // NULL sorts first in SQLite, so never indexed repositories count as oldest.
+        // A negative limit means no limit.
+        let list_sql = format!(
+            "SELECT id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata, owner_id, organization_id, visibility, share_grants, disk_usage FROM repositories {} ORDER BY {} {}, id LIMIT ? OFFSET ?",
+            where_clause, sort_column, sort_order
+        );
+        let mut query = sqlx::query(&list_sql);
+        for param in &params {
+            query = query.bind(param);
+        }
+        let rows = query
+            .bind(page.limit.map(|limit| limit as i64).unwrap_or(-1))
+            .bind(page.offset as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(list_error)?;
 
         let mut repositories = Vec::new();
         for row in rows {
@@ -444,10 +560,16 @@ impl RepositoryStorage for SqliteRepositoryStorage {
         }
 
         debug!(
-            "Listed {} repositories from SQLite storage",
-            repositories.len()
+            "Listed {} of {} repositories from SQLite storage",
+            repositories.len(),
+            total
         );
-        Ok(repositories)
+        Ok(Page {
+            items: repositories,
+            total: total as usize,
+            limit: page.limit,
+            offset: page.offset,
+        })
     }
 
     async fn delete_repository(&self, id: &str) -> RepositoryResult<()> {
@@ -554,7 +676,7 @@ impl RepositoryStorage for SqliteRepositoryStorage {
         let status_str = Self::status_to_db_string(&status);
 
         let rows = sqlx::query(
-            "SELECT id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata FROM repositories WHERE status = ? ORDER BY created_at DESC"
+            "SELECT id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata, owner_id, organization_id, visibility, share_grants, disk_usage FROM repositories WHERE status = ? ORDER BY created_at DESC"
         )
         .bind(status_str)
         .fetch_all(&self.pool)
@@ -632,3 +754,491 @@ impl RepositoryStorage for SqliteRepositoryStorage {
         Ok(())
     }
 }
+
+/// PostgreSQL repository storage implementation
+///
+/// Lets several server instances share their repositories. The schema
+/// matches the SQLite one, with timestamps kept as RFC 3339 text.
+#[cfg(feature = "postgres")]
+pub struct PostgresRepositoryStorage {
+    pool: sqlx::PgPool,
+}
+
+#[cfg(feature = "postgres")]
+impl PostgresRepositoryStorage {
+    const COLUMNS: &'static str = "id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata, owner_id, organization_id, visibility, share_grants, disk_usage";
+
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Create from database URL
+    pub async fn from_url(database_url: &str) -> RepositoryResult<Self> {
+        let pool = sqlx::PgPool::connect(database_url).await.map_err(|e| {
+            Self::error(
+                format!("Failed to connect to PostgreSQL database: {}", e),
+                true,
+            )
+        })?;
+
+        Ok(Self::new(pool))
+    }
+
+    /// Run database migrations
+    ///
+    /// These are the migrations of the web server's PostgreSQL database, so
+    /// both can share one database.
+    pub async fn migrate(&self) -> RepositoryResult<()> {
+        sqlx::migrate!("../wikify-web/migrations/postgres")
+            .run(&self.pool)
+            .await
+            .map_err(|e| Self::error(format!("Database migration failed: {}", e), false))?;
+
+        info!("Database migrations completed successfully");
+        Ok(())
+    }
+
+    fn error(message: String, recoverable: bool) -> RepositoryError {
+        RepositoryError::Internal {
+            message,
+            component: "postgres_storage".to_string(),
+            error_id: uuid::Uuid::new_v4().to_string(),
+            recoverable,
+        }
+    }
+
+    /// Convert database row to RepositoryIndex
+    fn row_to_repository_index(row: &sqlx::postgres::PgRow) -> RepositoryResult<RepositoryIndex> {
+        use sqlx::Row;
+
+        let column = |name: &str, e: sqlx::Error| {
+            Self::error(format!("Failed to get {} column: {}", name, e), false)
+        };
+        let parse_time = |value: &str| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+        };
+
+        let metadata_json: String = row.try_get("metadata").map_err(|e| column("metadata", e))?;
+        let metadata: HashMap<String, String> = serde_json::from_str(&metadata_json)
+            .map_err(|e| Self::error(format!("Failed to parse metadata JSON: {}", e), false))?;
+        let status: String = row.try_get("status").map_err(|e| column("status", e))?;
+        let created_at: String = row
+            .try_get("created_at")
+            .map_err(|e| column("created_at", e))?;
+
+        Ok(RepositoryIndex {
+            id: row.try_get("id").map_err(|e| column("id", e))?,
+            url: row
+                .try_get("repo_path")
+                .map_err(|e| column("repo_path", e))?,
+            repo_type: row
+                .try_get("repo_type")
+                .map_err(|e| column("repo_type", e))?,
+            status: status_from_db_string(&status).unwrap_or(IndexingStatus::Pending),
+            progress: 0.0, // Not stored in current schema, will be calculated
+            created_at: parse_time(&created_at).unwrap_or_else(Utc::now),
+            indexed_at: row
+                .try_get::<Option<String>, _>("last_indexed_at")
+                .ok()
+                .flatten()
+                .and_then(|value| parse_time(&value)),
+            updated_at: Utc::now(),
+            owner_id: row.try_get("owner_id").unwrap_or(None),
+            organization_id: row.try_get("organization_id").unwrap_or(None),
+            visibility: row
+                .try_get::<String, _>("visibility")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_default(),
+            share_grants: row
+                .try_get::<String, _>("share_grants")
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default(),
+            disk_usage: row
+                .try_get::<Option<String>, _>("disk_usage")
+                .ok()
+                .flatten()
+                .and_then(|json| serde_json::from_str(&json).ok()),
+            metadata,
+        })
+    }
+
+    fn rows_to_repositories(rows: Vec<sqlx::postgres::PgRow>) -> Vec<RepositoryIndex> {
+        rows.iter()
+            .filter_map(|row| match Self::row_to_repository_index(row) {
+                Ok(repo) => Some(repo),
+                Err(e) => {
+                    warn!("Failed to parse repository row: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Database status string of an indexing status
+#[cfg(feature = "postgres")]
+fn status_to_db_string(status: &IndexingStatus) -> &'static str {
+    match status {
+        IndexingStatus::Pending => "created",
+        IndexingStatus::Indexing => "indexing",
+        IndexingStatus::Completed => "indexed",
+        IndexingStatus::Failed => "failed",
+        IndexingStatus::Cancelled => "archived",
+    }
+}
+
+/// Indexing status of a database status string
+#[cfg(feature = "postgres")]
+fn status_from_db_string(status: &str) -> Option<IndexingStatus> {
+    match status {
+        "created" => Some(IndexingStatus::Pending),
+        "indexing" => Some(IndexingStatus::Indexing),
+        "indexed" => Some(IndexingStatus::Completed),
+        "failed" => Some(IndexingStatus::Failed),
+        "archived" => Some(IndexingStatus::Cancelled),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl RepositoryStorage for PostgresRepositoryStorage {
+    async fn save_repository(&self, repo: &RepositoryIndex) -> RepositoryResult<()> {
+        let metadata_json = serde_json::to_string(&repo.metadata)
+            .map_err(|e| Self::error(format!("Failed to serialize metadata: {}", e), false))?;
+        let share_grants_json = serde_json::to_string(&repo.share_grants)
+            .map_err(|e| Self::error(format!("Failed to serialize share grants: {}", e), false))?;
+        let disk_usage_json = repo
+            .disk_usage
+            .as_ref()
+            .and_then(|usage| serde_json::to_string(usage).ok());
+
+        sqlx::query(
+            r#"
+            INSERT INTO repositories
+            (id, name, description, repo_path, repo_type, created_at, last_indexed_at, status, metadata, owner_id, organization_id, visibility, share_grants, disk_usage)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                description = EXCLUDED.description,
+                repo_path = EXCLUDED.repo_path,
+                repo_type = EXCLUDED.repo_type,
+                created_at = EXCLUDED.created_at,
+                last_indexed_at = EXCLUDED.last_indexed_at,
+                status = EXCLUDED.status,
+                metadata = EXCLUDED.metadata,
+                owner_id = EXCLUDED.owner_id,
+                organization_id = EXCLUDED.organization_id,
+                visibility = EXCLUDED.visibility,
+                share_grants = EXCLUDED.share_grants,
+                disk_usage = EXCLUDED.disk_usage
+            "#,
+        )
+        .bind(&repo.id)
+        .bind(&repo.url) // Using URL as name for now
+        .bind("") // Empty description
+        .bind(&repo.url)
+        .bind(&repo.repo_type)
+        .bind(repo.created_at.to_rfc3339())
+        .bind(repo.indexed_at.map(|dt| dt.to_rfc3339()))
+        .bind(status_to_db_string(&repo.status))
+        .bind(metadata_json)
+        .bind(&repo.owner_id)
+        .bind(&repo.organization_id)
+        .bind(repo.visibility.to_string())
+        .bind(share_grants_json)
+        .bind(disk_usage_json)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            Self::error(
+                format!("Failed to save repository to database: {}", e),
+                true,
+            )
+        })?;
+
+        debug!("Saved repository {} to PostgreSQL storage", repo.id);
+        Ok(())
+    }
+
+    async fn load_repository(&self, id: &str) -> RepositoryResult<Option<RepositoryIndex>> {
+        let sql = format!("SELECT {} FROM repositories WHERE id = $1", Self::COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                Self::error(
+                    format!("Failed to load repository from database: {}", e),
+                    true,
+                )
+            })?;
+
+        row.map(|row| Self::row_to_repository_index(&row))
+            .transpose()
+    }
+
+    async fn list_repositories(
+        &self,
+        scope: &TenantScope,
+        filter: &RepositoryFilter,
+        page: &PageRequest,
+    ) -> RepositoryResult<Page<RepositoryIndex>> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        let mut push = |condition: &str, param: String| {
+            params.push(param);
+            conditions.push(condition.replace("{}", &format!("${}", params.len())));
+        };
+        match scope {
+            TenantScope::All => {}
+            TenantScope::Organization(org_id) => push("organization_id = {}", org_id.clone()),
+            TenantScope::Personal(user_id) => {
+                push("organization_id IS NULL AND owner_id = {}", user_id.clone())
+            }
+        }
+        if let Some(ref status) = filter.status {
+            push("status = {}", status_to_db_string(status).to_string());
+        }
+        if let Some(ref repo_type) = filter.repo_type {
+            push("repo_type = {}", repo_type.clone());
+        }
+        if let Some(ref owner_id) = filter.owner_id {
+            push("owner_id = {}", owner_id.clone());
+        }
+        if let Some(ref name) = filter.name {
+            let escaped = name
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            push("name ILIKE {} ESCAPE '\\'", format!("%{}%", escaped));
+        }
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let sort_column = match filter.sort {
+            RepositorySortField::CreatedAt => "created_at",
+            RepositorySortField::LastIndexedAt => "last_indexed_at",
+        };
+        // Never indexed repositories count as oldest, as in SQLite
+        let sort_order = match filter.order {
+            SortOrder::Asc => "ASC NULLS FIRST",
+            SortOrder::Desc => "DESC NULLS LAST",
+        };
+
+        let list_error = |e: sqlx::Error| {
+            Self::error(
+                format!("Failed to list repositories from database: {}", e),
+                true,
+            )
+        };
+
+        let count_sql = format!("SELECT COUNT(*) FROM repositories {}", where_clause);
+        let mut count_query = sqlx::query_scalar::<_, i64>(&count_sql);
+        for param in &params {
+            count_query = count_query.bind(param);
+        }
+        let total = count_query
+            .fetch_one(&self.pool)
+            .await
+            .map_err(list_error)?;
+
+        // A NULL limit means no limit
+        let list_sql = format!(
+            "SELECT {} FROM repositories {} ORDER BY {} {}, id LIMIT ${} OFFSET ${}",
+            Self::COLUMNS,
+            where_clause,
+            sort_column,
+            sort_order,
+            params.len() + 1,
+            params.len() + 2
+        );
+        let mut query = sqlx::query(&list_sql);
+        for param in &params {
+            query = query.bind(param);
+        }
+        let rows = query
+            .bind(page.limit.map(|limit| limit as i64))
+            .bind(page.offset as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(list_error)?;
+        let repositories = Self::rows_to_repositories(rows);
+
+        debug!(
+            "Listed {} of {} repositories from PostgreSQL storage",
+            repositories.len(),
+            total
+        );
+        Ok(Page {
+            items: repositories,
+            total: total as usize,
+            limit: page.limit,
+            offset: page.offset,
+        })
+    }
+
+    async fn delete_repository(&self, id: &str) -> RepositoryResult<()> {
+        let result = sqlx::query("DELETE FROM repositories WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                Self::error(
+                    format!("Failed to delete repository from database: {}", e),
+                    true,
+                )
+            })?;
+
+        if result.rows_affected() > 0 {
+            debug!("Deleted repository {} from PostgreSQL storage", id);
+            Ok(())
+        } else {
+            Err(RepositoryError::NotFound {
+                repository_id: id.to_string(),
+            })
+        }
+    }
+
+    async fn update_status(
+        &self,
+        id: &str,
+        status: IndexingStatus,
+        progress: f64,
+    ) -> RepositoryResult<()> {
+        let indexed_at = if matches!(status, IndexingStatus::Completed) {
+            Some(Utc::now())
+        } else {
+            None
+        };
+
+        let result =
+            sqlx::query("UPDATE repositories SET status = $1, last_indexed_at = $2 WHERE id = $3")
+                .bind(status_to_db_string(&status))
+                .bind(indexed_at.map(|dt| dt.to_rfc3339()))
+                .bind(id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| {
+                    Self::error(
+                        format!("Failed to update repository status in database: {}", e),
+                        true,
+                    )
+                })?;
+
+        if result.rows_affected() > 0 {
+            debug!(
+                "Updated repository {} status to {:?} with progress {} in PostgreSQL storage",
+                id, status, progress
+            );
+            Ok(())
+        } else {
+            Err(RepositoryError::NotFound {
+                repository_id: id.to_string(),
+            })
+        }
+    }
+
+    async fn update_metadata(
+        &self,
+        id: &str,
+        metadata: HashMap<String, String>,
+    ) -> RepositoryResult<()> {
+        let metadata_json = serde_json::to_string(&metadata)
+            .map_err(|e| Self::error(format!("Failed to serialize metadata: {}", e), false))?;
+
+        let result = sqlx::query("UPDATE repositories SET metadata = $1 WHERE id = $2")
+            .bind(metadata_json)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                Self::error(
+                    format!("Failed to update repository metadata in database: {}", e),
+                    true,
+                )
+            })?;
+
+        if result.rows_affected() > 0 {
+            debug!("Updated repository {} metadata in PostgreSQL storage", id);
+            Ok(())
+        } else {
+            Err(RepositoryError::NotFound {
+                repository_id: id.to_string(),
+            })
+        }
+    }
+
+    async fn get_repositories_by_status(
+        &self,
+        status: IndexingStatus,
+    ) -> RepositoryResult<Vec<RepositoryIndex>> {
+        let sql = format!(
+            "SELECT {} FROM repositories WHERE status = $1 ORDER BY created_at DESC",
+            Self::COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(status_to_db_string(&status))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                Self::error(
+                    format!("Failed to get repositories by status from database: {}", e),
+                    true,
+                )
+            })?;
+        let repositories = Self::rows_to_repositories(rows);
+
+        debug!(
+            "Found {} repositories with status {:?} in PostgreSQL storage",
+            repositories.len(),
+            status
+        );
+        Ok(repositories)
+    }
+
+    async fn get_status_counts(&self) -> RepositoryResult<HashMap<IndexingStatus, u64>> {
+        use sqlx::Row;
+
+        let rows =
+            sqlx::query("SELECT status, COUNT(*) AS count FROM repositories GROUP BY status")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| {
+                    Self::error(
+                        format!("Failed to get status counts from database: {}", e),
+                        true,
+                    )
+                })?;
+
+        let mut counts = HashMap::new();
+        for row in rows {
+            let status: String = row.try_get("status").unwrap_or_default();
+            let count: i64 = row.try_get("count").unwrap_or(0);
+            if let Some(status) = status_from_db_string(&status) {
+                counts.insert(status, count as u64);
+            }
+        }
+
+        debug!(
+            "Retrieved status counts from PostgreSQL storage: {:?}",
+            counts
+        );
+        Ok(counts)
+    }
+
+    async fn health_check(&self) -> RepositoryResult<()> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| Self::error(format!("PostgreSQL health check failed: {}", e), true))?;
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::pagination::SortOrder;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -25,6 +26,84 @@ impl Default for IndexingStatus {
     }
 }
 
+impl std::str::FromStr for IndexingStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(IndexingStatus::Pending),
+            "indexing" => Ok(IndexingStatus::Indexing),
+            // The web API reports completed repositories as "indexed"
+            "completed" | "indexed" => Ok(IndexingStatus::Completed),
+            "failed" => Ok(IndexingStatus::Failed),
+            "cancelled" => Ok(IndexingStatus::Cancelled),
+            _ => Err(format!("Unknown indexing status: {}", s)),
+        }
+    }
+}
+
+/// Who can see and query a repository
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RepositoryVisibility {
+    /// Only the owner and users the repository is shared with
+    #[default]
+    Private,
+    /// Members of the repository's organization
+    Org,
+    /// Everyone
+    Public,
+}
+
+impl std::fmt::Display for RepositoryVisibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RepositoryVisibility::Private => write!(f, "private"),
+            RepositoryVisibility::Org => write!(f, "org"),
+            RepositoryVisibility::Public => write!(f, "public"),
+        }
+    }
+}
+
+impl std::str::FromStr for RepositoryVisibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "private" => Ok(RepositoryVisibility::Private),
+            "org" => Ok(RepositoryVisibility::Org),
+            "public" => Ok(RepositoryVisibility::Public),
+            _ => Err(format!("Unknown repository visibility: {}", s)),
+        }
+    }
+}
+
+/// Explicit permission for a user to see and query a repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ShareGrant {
+    /// User the repository is shared with
+    pub user_id: String,
+    /// User who shared the repository
+    pub granted_by: Option<String>,
+    pub granted_at: DateTime<Utc>,
+}
+
+/// Disk space used by a repository, in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepositoryDiskUsage {
+    /// Cloned sources
+    pub clone_bytes: u64,
+    /// Vector index
+    pub index_bytes: u64,
+    /// Cached generated wikis
+    pub wiki_cache_bytes: u64,
+    pub total_bytes: u64,
+    pub measured_at: DateTime<Utc>,
+}
+
 /// Repository information and indexing state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepositoryIndex {
@@ -46,6 +125,19 @@ pub struct RepositoryIndex {
     pub updated_at: DateTime<Utc>,
     /// User who added this repository (None for anonymous)
     pub owner_id: Option<String>,
+    /// Organization the repository belongs to (None for personal repositories)
+    #[serde(default)]
+    pub organization_id: Option<String>,
+    /// Who can see and query the repository besides its owner
+    #[serde(default)]
+    pub visibility: RepositoryVisibility,
+    /// Users the repository is explicitly shared with
+    #[serde(default)]
+    pub share_grants: Vec<ShareGrant>,
+    /// Disk space used by the repository, measured after indexing and wiki
+    /// generation (None until first measured)
+    #[serde(default)]
+    pub disk_usage: Option<RepositoryDiskUsage>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
 }
@@ -63,10 +155,66 @@ impl RepositoryIndex {
             indexed_at: None,
             updated_at: now,
             owner_id,
+            organization_id: None,
+            visibility: RepositoryVisibility::default(),
+            share_grants: Vec::new(),
+            disk_usage: None,
             metadata: HashMap::new(),
         }
     }
 
+    /// Whether the repository is explicitly shared with a user
+    pub fn is_shared_with(&self, user_id: &str) -> bool {
+        self.share_grants
+            .iter()
+            .any(|grant| grant.user_id == user_id)
+    }
+
+    /// Whether a user can see and query the repository
+    ///
+    /// Repositories added without an owner are visible to everyone.
+    /// `organization_id` is the organization the user is acting in.
+    pub fn is_visible_to(&self, user_id: Option<&str>, organization_id: Option<&str>) -> bool {
+        let Some(ref owner_id) = self.owner_id else {
+            return true;
+        };
+        if let Some(user_id) = user_id {
+            if user_id == owner_id || self.is_shared_with(user_id) {
+                return true;
+            }
+        }
+
+        match self.visibility {
+            RepositoryVisibility::Private => false,
+            RepositoryVisibility::Org => {
+                self.organization_id.is_some() && self.organization_id.as_deref() == organization_id
+            }
+            RepositoryVisibility::Public => true,
+        }
+    }
+
+    /// Share the repository with a user, replacing an earlier grant
+    pub fn grant_access(&mut self, user_id: String, granted_by: Option<String>) {
+        self.revoke_access(&user_id);
+        self.share_grants.push(ShareGrant {
+            user_id,
+            granted_by,
+            granted_at: Utc::now(),
+        });
+        self.updated_at = Utc::now();
+    }
+
+    /// Stop sharing the repository with a user, returning whether it was shared
+    pub fn revoke_access(&mut self, user_id: &str) -> bool {
+        let before = self.share_grants.len();
+        self.share_grants.retain(|grant| grant.user_id != user_id);
+        let revoked = self.share_grants.len() != before;
+        if revoked {
+            self.updated_at = Utc::now();
+        }
+        revoked
+    }
+
     /// Update indexing progress
     pub fn set_progress(&mut self, progress: f64, status: Option<IndexingStatus>) {
         self.progress = progress.clamp(0.0, 1.0);
@@ -92,6 +240,105 @@ impl RepositoryIndex {
     pub fn is_ready(&self) -> bool {
         matches!(self.status, IndexingStatus::Completed)
     }
+
+    /// Mirror URLs registered for this repository
+    pub fn mirror_urls(&self) -> Vec<String> {
+        self.metadata
+            .get("mirror_urls")
+            .map(|urls| parse_mirror_urls(urls))
+            .unwrap_or_default()
+    }
+}
+
+/// Field a repository list is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum RepositorySortField {
+    /// When the repository was added
+    #[default]
+    CreatedAt,
+    /// When the repository was last indexed, never indexed ones sort as oldest
+    LastIndexedAt,
+}
+
+impl std::fmt::Display for RepositorySortField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RepositorySortField::CreatedAt => write!(f, "created_at"),
+            RepositorySortField::LastIndexedAt => write!(f, "last_indexed_at"),
+        }
+    }
+}
+
+impl std::str::FromStr for RepositorySortField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created_at" => Ok(RepositorySortField::CreatedAt),
+            "last_indexed_at" => Ok(RepositorySortField::LastIndexedAt),
+            _ => Err(format!("Unknown repository sort field: {}", s)),
+        }
+    }
+}
+
+/// Which repositories to list and in what order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepositoryFilter {
+    pub status: Option<IndexingStatus>,
+    pub repo_type: Option<String>,
+    pub owner_id: Option<String>,
+    /// Case-insensitive substring of the repository URL
+    pub name: Option<String>,
+    pub sort: RepositorySortField,
+    pub order: SortOrder,
+}
+
+impl RepositoryFilter {
+    /// Whether a repository passes every condition of the filter
+    pub fn matches(&self, repo: &RepositoryIndex) -> bool {
+        if self.status.is_some() && self.status.as_ref() != Some(&repo.status) {
+            return false;
+        }
+        if self.repo_type.is_some() && self.repo_type.as_ref() != Some(&repo.repo_type) {
+            return false;
+        }
+        if self.owner_id.is_some() && self.owner_id != repo.owner_id {
+            return false;
+        }
+        match self.name {
+            Some(ref name) => repo.url.to_lowercase().contains(&name.to_lowercase()),
+            None => true,
+        }
+    }
+
+    /// Sort repositories in the order the filter asks for
+    ///
+    /// Ties are broken by ID so pages do not overlap.
+    pub fn sort(&self, repos: &mut [RepositoryIndex]) {
+        repos.sort_by(|a, b| {
+            let ordering = match self.sort {
+                RepositorySortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                RepositorySortField::LastIndexedAt => a.indexed_at.cmp(&b.indexed_at),
+            };
+            let ordering = match self.order {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            };
+            ordering.then_with(|| a.id.cmp(&b.id))
+        });
+    }
+}
+
+/// Parse the comma separated mirror list stored in repository metadata
+pub fn parse_mirror_urls(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 /// Options for adding a repository
@@ -107,6 +354,12 @@ pub struct RepositoryOptions {
     pub api_token: Option<String>,
     /// Whether to extract enhanced metadata
     pub extract_metadata: bool,
+    /// Alternate remotes tried in order when the primary URL cannot be accessed
+    #[serde(default)]
+    pub mirror_urls: Vec<String>,
+    /// Who can see the repository (private, or org inside an organization, when unset)
+    #[serde(default)]
+    pub visibility: Option<RepositoryVisibility>,
 }
 
 // Re-export RepoAccessMode from wikify-core to avoid duplication
@@ -120,6 +373,8 @@ impl Default for RepositoryOptions {
             access_mode: None, // None means auto-detect
             api_token: None,
             extract_metadata: true,
+            mirror_urls: Vec::new(),
+            visibility: None,
         }
     }
 }
@@ -142,12 +397,79 @@ pub struct RepositoryQueryResponse {
     pub answer: String,
     /// Source documents used to generate the answer
     pub sources: Vec<String>,
+    /// Similarity score of each source, in the same order (0.0 to 1.0)
+    #[serde(default)]
+    pub source_scores: Vec<f32>,
+    /// Where each source is in the repository, in the same order
+    #[serde(default)]
+    pub citations: Vec<SourceCitation>,
     /// Confidence score (0.0 to 1.0)
     pub confidence: Option<f64>,
     /// Additional metadata
     pub metadata: HashMap<String, String>,
 }
 
+/// Chunk of a file an answer is based on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceCitation {
+    /// File the chunk comes from
+    pub file_path: String,
+    /// First line of the chunk (1-based), when known
+    pub start_line: Option<usize>,
+    /// Last line of the chunk, when known
+    pub end_line: Option<usize>,
+    /// Similarity to the question (0.0 to 1.0)
+    pub score: f32,
+    /// Text of the chunk
+    pub content: String,
+}
+
+impl SourceCitation {
+    /// Citation of a retrieved chunk, if it comes from a file
+    pub fn from_search_result(result: &wikify_rag::types::SearchResult) -> Option<Self> {
+        let metadata = &result.chunk.metadata;
+        let line = |key: &str| {
+            metadata
+                .get(key)
+                .and_then(|line| line.as_u64())
+                .map(|line| line as usize)
+        };
+        Some(Self {
+            file_path: metadata
+                .get("file_path")
+                .or_else(|| metadata.get("source"))?
+                .as_str()?
+                .to_string(),
+            start_line: line("start_line"),
+            end_line: line("end_line"),
+            score: result.score,
+            content: result.chunk.content.clone(),
+        })
+    }
+
+    /// `path:start-end`, or the path alone when the lines are unknown
+    pub fn location(&self) -> String {
+        match (self.start_line, self.end_line) {
+            (Some(start), Some(end)) if end > start => {
+                format!("{}:{}-{}", self.file_path, start, end)
+            }
+            (Some(start), _) => format!("{}:{}", self.file_path, start),
+            _ => self.file_path.clone(),
+        }
+    }
+}
+
+/// Indexed chunk of a repository matching a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeSearchResult {
+    /// File the chunk comes from, when known
+    pub file_path: Option<String>,
+    /// Text of the chunk
+    pub content: String,
+    /// Similarity to the search query (0.0 to 1.0)
+    pub score: f32,
+}
+
 /// Stream chunk for real-time query responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryStreamChunk {
@@ -212,6 +534,16 @@ impl IndexingUpdate {
         }
     }
 
+    pub fn cancelled(repository_id: String, progress: f64, message: String) -> Self {
+        Self {
+            repository_id,
+            status: IndexingStatus::Cancelled,
+            progress: progress.clamp(0.0, 1.0),
+            message,
+            timestamp: Utc::now(),
+        }
+    }
+
     pub fn error(repository_id: String, error_message: String) -> Self {
         Self {
             repository_id,
@@ -222,3 +554,112 @@ impl IndexingUpdate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_visibility() {
+        let mut repo = RepositoryIndex::new(
+            "https://github.com/user/repo".to_string(),
+            "github".to_string(),
+            Some("alice".to_string()),
+        );
+        assert!(repo.is_visible_to(Some("alice"), None));
+        assert!(!repo.is_visible_to(Some("bob"), None));
+        assert!(!repo.is_visible_to(None, None));
+
+        repo.grant_access("bob".to_string(), Some("alice".to_string()));
+        assert!(repo.is_visible_to(Some("bob"), None));
+        assert!(repo.revoke_access("bob"));
+        assert!(!repo.revoke_access("bob"));
+        assert!(!repo.is_visible_to(Some("bob"), None));
+
+        repo.visibility = RepositoryVisibility::Org;
+        repo.organization_id = Some("org-1".to_string());
+        assert!(repo.is_visible_to(Some("bob"), Some("org-1")));
+        assert!(!repo.is_visible_to(Some("bob"), Some("org-2")));
+
+        repo.visibility = RepositoryVisibility::Public;
+        assert!(repo.is_visible_to(None, None));
+
+        let unowned = RepositoryIndex::new("/tmp/repo".to_string(), "local".to_string(), None);
+        assert!(unowned.is_visible_to(Some("bob"), None));
+        assert_eq!(
+            "org".parse::<RepositoryVisibility>(),
+            Ok(RepositoryVisibility::Org)
+        );
+    }
+
+    #[test]
+    fn test_repository_filter() {
+        let mut first = RepositoryIndex::new(
+            "https://github.com/user/Alpha".to_string(),
+            "github".to_string(),
+            Some("alice".to_string()),
+        );
+        first.id = "a".to_string();
+        let mut second = RepositoryIndex::new(
+            "/tmp/beta".to_string(),
+            "local".to_string(),
+            Some("bob".to_string()),
+        );
+        second.id = "b".to_string();
+        second.created_at = first.created_at + chrono::Duration::seconds(1);
+        second.set_progress(1.0, Some(IndexingStatus::Completed));
+
+        let filter = RepositoryFilter {
+            name: Some("alpha".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&first));
+        assert!(!filter.matches(&second));
+
+        let filter = RepositoryFilter {
+            status: Some("indexed".parse().unwrap()),
+            owner_id: Some("bob".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&first));
+        assert!(filter.matches(&second));
+
+        let mut repos = vec![first.clone(), second.clone()];
+        RepositoryFilter::default().sort(&mut repos);
+        assert_eq!(repos[0].id, "b");
+
+        let filter = RepositoryFilter {
+            sort: RepositorySortField::LastIndexedAt,
+            order: SortOrder::Asc,
+            ..Default::default()
+        };
+        filter.sort(&mut repos);
+        assert_eq!(repos[0].id, "a");
+    }
+
+    #[test]
+    fn test_citations_show_line_ranges() {
+        let result = wikify_rag::types::SearchResult {
+            chunk: wikify_rag::types::EmbeddedChunk {
+                id: Uuid::new_v4(),
+                content: "pub fn login() {}".to_string(),
+                embedding: Vec::new(),
+                metadata: HashMap::from([
+                    ("file_path".to_string(), "src/auth/mod.rs".into()),
+                    ("start_line".to_string(), 120.into()),
+                    ("end_line".to_string(), 167.into()),
+                ]),
+                document_id: None,
+                chunk_index: 0,
+            },
+            score: 0.9,
+        };
+        let mut citation = SourceCitation::from_search_result(&result).unwrap();
+        assert_eq!(citation.location(), "src/auth/mod.rs:120-167");
+
+        citation.end_line = Some(120);
+        assert_eq!(citation.location(), "src/auth/mod.rs:120");
+        citation.start_line = None;
+        assert_eq!(citation.location(), "src/auth/mod.rs");
+    }
+}
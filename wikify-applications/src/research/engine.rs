@@ -10,7 +10,8 @@ use crate::{
 use chrono::Utc;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, RwLock};
+use tokio_util::sync::CancellationToken;
 use tracing::{info, warn};
 use uuid::Uuid;
 
@@ -20,6 +21,8 @@ pub struct ResearchEngine {
     repository_manager: Arc<RepositoryManager>,
     /// Active research sessions
     active_sessions: Arc<RwLock<HashMap<String, ResearchSession>>>,
+    /// Progress broadcaster for research updates
+    progress_broadcaster: broadcast::Sender<ResearchProgress>,
 }
 
 /// Research session state
@@ -33,24 +36,65 @@ pub struct ResearchSession {
     pub started_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
     pub result: Option<wikify_rag::DeepResearchResult>,
+    /// Stops the background research when triggered
+    pub cancel: CancellationToken,
+}
+
+impl ResearchSession {
+    /// Current progress of the session
+    pub fn progress(&self) -> ResearchProgress {
+        if let Some(result) = &self.result {
+            // Research is complete
+            ResearchProgress {
+                id: self.id.clone(),
+                repository_id: self.repository_id.clone(),
+                status: self.status.clone(),
+                current_iteration: result.iterations.len(),
+                max_iterations: self.config.max_iterations,
+                progress: 1.0,
+                current_response: result.iterations.last().map(|i| i.response.clone()),
+                estimated_remaining_ms: None,
+                last_updated: self.completed_at.unwrap_or(self.started_at),
+            }
+        } else {
+            // Research is in progress
+            ResearchProgress {
+                id: self.id.clone(),
+                repository_id: self.repository_id.clone(),
+                status: self.status.clone(),
+                current_iteration: 0, // We don't track individual iterations in this simplified version
+                max_iterations: self.config.max_iterations,
+                progress: 0.5, // Rough estimate
+                current_response: None,
+                estimated_remaining_ms: None,
+                last_updated: self.completed_at.unwrap_or_else(Utc::now),
+            }
+        }
+    }
 }
 
 impl ResearchEngine {
     /// Create a new research engine with repository manager
     pub fn new(repository_manager: Arc<RepositoryManager>) -> Self {
+        let (progress_broadcaster, _) = broadcast::channel(100);
         Self {
             repository_manager,
             active_sessions: Arc::new(RwLock::new(HashMap::new())),
+            progress_broadcaster,
         }
     }
 
     /// Start a new deep research process
+    ///
+    /// The research runs in the background until it completes, the session
+    /// is stopped or `cancel` is triggered.
     pub async fn start_research(
         &self,
         context: &PermissionContext,
         repository_id: &str,
         query: String,
         config: Option<ResearchConfig>,
+        cancel: &CancellationToken,
     ) -> ApplicationResult<String> {
         let session_id = Uuid::new_v4().to_string();
         let research_config = config.unwrap_or_default();
@@ -70,9 +114,11 @@ impl ResearchEngine {
             started_at: Utc::now(),
             completed_at: None,
             result: None,
+            cancel: cancel.clone(),
         };
 
         // Store the session
+        let _ = self.progress_broadcaster.send(session.progress());
         {
             let mut sessions = self.active_sessions.write().await;
             sessions.insert(session_id.clone(), session);
@@ -81,17 +127,21 @@ impl ResearchEngine {
         // Start background research task
         let repository_manager = self.repository_manager.clone();
         let sessions = self.active_sessions.clone();
+        let progress_broadcaster = self.progress_broadcaster.clone();
         let session_id_clone = session_id.clone();
         let repository_id_clone = repository_id.to_string();
+        let cancel = cancel.clone();
 
         tokio::spawn(async move {
             Self::execute_research_background(
                 repository_manager,
                 sessions,
+                progress_broadcaster,
                 session_id_clone,
                 repository_id_clone,
                 query,
                 research_config,
+                cancel,
             )
             .await
         });
@@ -105,66 +155,57 @@ impl ResearchEngine {
         session_id: &str,
     ) -> ApplicationResult<ResearchProgress> {
         let sessions = self.active_sessions.read().await;
-        let session = sessions
-            .get(session_id)
-            .ok_or_else(|| ApplicationError::Research {
-                message: format!("Research session not found: {}", session_id),
-            })?;
+        let session = sessions.get(session_id).ok_or_else(|| {
+            ApplicationError::not_found(format!("Research session not found: {}", session_id))
+        })?;
 
-        // Convert session to progress
-        let progress = if let Some(result) = &session.result {
-            // Research is complete
-            ResearchProgress {
-                id: session.id.clone(),
-                status: session.status.clone(),
-                current_iteration: result.iterations.len(),
-                max_iterations: session.config.max_iterations,
-                progress: 1.0,
-                current_response: result.iterations.last().map(|i| i.response.clone()),
-                estimated_remaining_ms: None,
-                last_updated: session.completed_at.unwrap_or(session.started_at),
-            }
-        } else {
-            // Research is in progress
-            ResearchProgress {
-                id: session.id.clone(),
-                status: session.status.clone(),
-                current_iteration: 0, // We don't track individual iterations in this simplified version
-                max_iterations: session.config.max_iterations,
-                progress: 0.5, // Rough estimate
-                current_response: None,
-                estimated_remaining_ms: None,
-                last_updated: chrono::Utc::now(),
-            }
-        };
-
-        Ok(progress)
+        Ok(session.progress())
     }
 
     /// Stop a research session
     pub async fn stop_research(&self, session_id: &str) -> ApplicationResult<()> {
         let mut sessions = self.active_sessions.write().await;
         if let Some(session) = sessions.get_mut(session_id) {
-            session.status = ResearchStatus::Cancelled;
-            session.completed_at = Some(Utc::now());
+            session.cancel.cancel();
+            Self::mark_cancelled(session, &self.progress_broadcaster);
             info!("Stopped research session: {}", session_id);
+            Ok(())
         } else {
             warn!(
                 "Attempted to stop non-existent research session: {}",
                 session_id
             );
+            Err(ApplicationError::not_found(format!(
+                "Research session not found: {}",
+                session_id
+            )))
+        }
+    }
+
+    /// Mark a session as cancelled unless it already finished
+    fn mark_cancelled(
+        session: &mut ResearchSession,
+        progress_broadcaster: &broadcast::Sender<ResearchProgress>,
+    ) {
+        if session.status != ResearchStatus::InProgress {
+            return;
         }
-        Ok(())
+        session.status = ResearchStatus::Cancelled;
+        session.completed_at = Some(Utc::now());
+        let _ = progress_broadcaster.send(session.progress());
     }
 
     /// Execute research in background
+    #[allow(clippy::too_many_arguments)]
     async fn execute_research_background(
         repository_manager: Arc<RepositoryManager>,
         sessions: Arc<RwLock<HashMap<String, ResearchSession>>>,
+        progress_broadcaster: broadcast::Sender<ResearchProgress>,
         session_id: String,
         repository_id: String,
         query: String,
         config: ResearchConfig,
+        cancel: CancellationToken,
     ) {
         info!("Starting background research for session: {}", session_id);
 
@@ -181,10 +222,19 @@ impl ResearchEngine {
                 parameters: None,
             };
 
-            match repository_manager
-                .query_repository(&context, &repository_id, repo_query)
-                .await
-            {
+            let pending = repository_manager.query_repository(&context, &repository_id, repo_query);
+            let query_result = tokio::select! {
+                result = pending => result,
+                _ = cancel.cancelled() => {
+                    info!("Research cancelled for session: {}", session_id);
+                    if let Some(session) = sessions.write().await.get_mut(&session_id) {
+                        Self::mark_cancelled(session, &progress_broadcaster);
+                    }
+                    return;
+                }
+            };
+
+            match query_result {
                 Ok(response) => {
                     // Create a simplified deep research result
                     let iteration = wikify_rag::ResearchIteration {
@@ -225,6 +275,10 @@ impl ResearchEngine {
         // Update session with result
         let mut sessions_guard = sessions.write().await;
         if let Some(session) = sessions_guard.get_mut(&session_id) {
+            if session.status != ResearchStatus::InProgress {
+                // Stopped while the query was running
+                return;
+            }
             match rag_result {
                 Ok(result) => {
                     session.status = ResearchStatus::Completed;
@@ -241,26 +295,48 @@ impl ResearchEngine {
                     warn!("Research failed for session {}: {}", session_id, e);
                 }
             }
+            let _ = progress_broadcaster.send(session.progress());
         }
     }
 
+    /// Subscribe to progress updates of all research sessions
+    pub fn subscribe_to_progress(&self) -> broadcast::Receiver<ResearchProgress> {
+        self.progress_broadcaster.subscribe()
+    }
+
     /// List all active research sessions
     pub async fn list_active_research(&self) -> Vec<String> {
         let sessions = self.active_sessions.read().await;
         sessions.keys().cloned().collect()
     }
 
+    /// Get a research session
+    pub async fn get_session(&self, session_id: &str) -> ApplicationResult<ResearchSession> {
+        let sessions = self.active_sessions.read().await;
+        sessions.get(session_id).cloned().ok_or_else(|| {
+            ApplicationError::not_found(format!("Research session not found: {}", session_id))
+        })
+    }
+
+    /// Most recently started research session for a repository
+    pub async fn latest_session_for_repository(&self, repository_id: &str) -> Option<String> {
+        let sessions = self.active_sessions.read().await;
+        sessions
+            .values()
+            .filter(|session| session.repository_id == repository_id)
+            .max_by_key(|session| session.started_at)
+            .map(|session| session.id.clone())
+    }
+
     /// Get research result (if completed)
     pub async fn get_research_result(
         &self,
         session_id: &str,
     ) -> ApplicationResult<Option<wikify_rag::DeepResearchResult>> {
         let sessions = self.active_sessions.read().await;
-        let session = sessions
-            .get(session_id)
-            .ok_or_else(|| ApplicationError::Research {
-                message: format!("Research session not found: {}", session_id),
-            })?;
+        let session = sessions.get(session_id).ok_or_else(|| {
+            ApplicationError::not_found(format!("Research session not found: {}", session_id))
+        })?;
 
         Ok(session.result.clone())
     }
@@ -1,6 +1,8 @@
 //! Research history storage and management
 
 use super::types::{ResearchContext, ResearchIteration, ResearchSummary};
+use crate::organization::TenantScope;
+use crate::pagination::{Page, PageRequest};
 use crate::{ApplicationError, ApplicationResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -67,6 +69,9 @@ pub struct ResearchMetadata {
     pub duration_seconds: Option<u64>,
     /// User who initiated the research
     pub user_id: Option<String>,
+    /// Organization the research belongs to (None for personal research)
+    #[serde(default)]
+    pub organization_id: Option<String>,
     /// Repository or session context
     pub repository_context: Option<String>,
 }
@@ -86,17 +91,19 @@ pub trait ResearchHistoryStorage: Send + Sync {
     /// Update research record
     async fn update_record(&self, record: &ResearchHistoryRecord) -> ApplicationResult<()>;
 
-    /// List research records with optional filters
+    /// List a page of the research records of a tenant with optional
+    /// filters, newest first
     async fn list_records(
         &self,
+        scope: &TenantScope,
         filters: &ResearchHistoryFilters,
-    ) -> ApplicationResult<Vec<ResearchHistoryRecord>>;
+    ) -> ApplicationResult<Page<ResearchHistoryRecord>>;
 
     /// Delete research record
     async fn delete_record(&self, session_id: &str) -> ApplicationResult<()>;
 
-    /// Get research statistics
-    async fn get_statistics(&self) -> ApplicationResult<ResearchStatistics>;
+    /// Get research statistics of a tenant
+    async fn get_statistics(&self, scope: &TenantScope) -> ApplicationResult<ResearchStatistics>;
 }
 
 /// Research history filters
@@ -223,6 +230,9 @@ struct ResearchIndexEntry {
     status: ResearchStatus,
     /// User ID (if any)
     user_id: Option<String>,
+    /// Organization ID (if any)
+    #[serde(default)]
+    organization_id: Option<String>,
     /// Creation timestamp
     created_at: DateTime<Utc>,
     /// Last updated timestamp
@@ -231,6 +241,13 @@ struct ResearchIndexEntry {
     file_size: u64,
 }
 
+impl ResearchIndexEntry {
+    /// Whether the research belongs to the tenant
+    fn in_scope(&self, scope: &TenantScope) -> bool {
+        scope.matches(self.user_id.as_deref(), self.organization_id.as_deref())
+    }
+}
+
 impl ResearchHistoryStorage for FileResearchHistoryStorage {
     async fn save_record(&self, record: &ResearchHistoryRecord) -> ApplicationResult<()> {
         let record_path = self.get_record_path(&record.session_id);
@@ -255,6 +272,7 @@ impl ResearchHistoryStorage for FileResearchHistoryStorage {
                 template_id: record.template_id.clone(),
                 status: record.status.clone(),
                 user_id: record.metadata.user_id.clone(),
+                organization_id: record.metadata.organization_id.clone(),
                 created_at: record.created_at,
                 updated_at: record.updated_at,
                 file_size,
@@ -296,10 +314,15 @@ impl ResearchHistoryStorage for FileResearchHistoryStorage {
 
     async fn list_records(
         &self,
+        scope: &TenantScope,
         filters: &ResearchHistoryFilters,
-    ) -> ApplicationResult<Vec<ResearchHistoryRecord>> {
+    ) -> ApplicationResult<Page<ResearchHistoryRecord>> {
         let index = self.load_index().await?;
-        let mut matching_entries: Vec<_> = index.sessions.values().collect();
+        let mut matching_entries: Vec<_> = index
+            .sessions
+            .values()
+            .filter(|entry| entry.in_scope(scope))
+            .collect();
 
         // Apply filters
         if let Some(ref user_id) = filters.user_id {
@@ -326,27 +349,26 @@ impl ResearchHistoryStorage for FileResearchHistoryStorage {
         matching_entries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
 
         // Apply pagination
-        if let Some(offset) = filters.offset {
-            if offset < matching_entries.len() {
-                matching_entries = matching_entries.into_iter().skip(offset).collect();
-            } else {
-                matching_entries.clear();
-            }
-        }
-
-        if let Some(limit) = filters.limit {
-            matching_entries.truncate(limit);
+        let page = PageRequest {
+            limit: filters.limit,
+            offset: filters.offset.unwrap_or(0),
         }
+        .paginate(matching_entries);
 
         // Load full records
         let mut records = Vec::new();
-        for entry in matching_entries {
+        for entry in &page.items {
             if let Ok(Some(record)) = self.load_record(&entry.session_id).await {
                 records.push(record);
             }
         }
 
-        Ok(records)
+        Ok(Page {
+            items: records,
+            total: page.total,
+            limit: page.limit,
+            offset: page.offset,
+        })
     }
 
     async fn delete_record(&self, session_id: &str) -> ApplicationResult<()> {
@@ -368,29 +390,31 @@ impl ResearchHistoryStorage for FileResearchHistoryStorage {
         Ok(())
     }
 
-    async fn get_statistics(&self) -> ApplicationResult<ResearchStatistics> {
+    async fn get_statistics(&self, scope: &TenantScope) -> ApplicationResult<ResearchStatistics> {
         let index = self.load_index().await?;
-
-        let total_sessions = index.sessions.len();
-        let completed_sessions = index
+        let sessions: Vec<_> = index
             .sessions
             .values()
+            .filter(|entry| entry.in_scope(scope))
+            .collect();
+
+        let total_sessions = sessions.len();
+        let completed_sessions = sessions
+            .iter()
             .filter(|e| e.status == ResearchStatus::Completed)
             .count();
-        let in_progress_sessions = index
-            .sessions
-            .values()
+        let in_progress_sessions = sessions
+            .iter()
             .filter(|e| e.status == ResearchStatus::InProgress)
             .count();
-        let failed_sessions = index
-            .sessions
-            .values()
+        let failed_sessions = sessions
+            .iter()
             .filter(|e| matches!(e.status, ResearchStatus::Failed(_)))
             .count();
 
         // Calculate template usage
         let mut template_usage: HashMap<String, usize> = HashMap::new();
-        for entry in index.sessions.values() {
+        for entry in &sessions {
             if let Some(ref template_id) = entry.template_id {
                 *template_usage.entry(template_id.clone()).or_insert(0) += 1;
             }
@@ -401,7 +425,7 @@ impl ResearchHistoryStorage for FileResearchHistoryStorage {
 
         // Calculate activity by date
         let mut activity_by_date: HashMap<String, usize> = HashMap::new();
-        for entry in index.sessions.values() {
+        for entry in &sessions {
             let date_key = entry.created_at.format("%Y-%m-%d").to_string();
             *activity_by_date.entry(date_key).or_insert(0) += 1;
         }
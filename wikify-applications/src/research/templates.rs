@@ -1,8 +1,11 @@
 //! Research templates for different types of investigations
 
 use super::types::{QuestionType, ResearchConfig};
+use crate::{ApplicationError, ApplicationResult};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::info;
 
 #[cfg(feature = "openapi")]
 use utoipa::ToSchema;
@@ -43,6 +46,64 @@ pub struct ResearchTemplate {
     pub parameters: HashMap<String, TemplateParameter>,
 }
 
+impl ResearchTemplate {
+    /// Check that a template can be used to start research
+    pub fn validate(&self) -> ApplicationResult<()> {
+        let valid_id = !self.id.is_empty()
+            && self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid_id {
+            return Err(ApplicationError::config(format!(
+                "Invalid template ID '{}': use letters, digits, '-' and '_'",
+                self.id
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(ApplicationError::config("Template name must not be empty"));
+        }
+        if self.initial_questions.is_empty() {
+            return Err(ApplicationError::config(
+                "Template must have at least one initial question",
+            ));
+        }
+        for (index, question) in self.initial_questions.iter().enumerate() {
+            if question.text.trim().is_empty() {
+                return Err(ApplicationError::config(format!(
+                    "Question {} has no text",
+                    index + 1
+                )));
+            }
+            if !(1..=10).contains(&question.priority) {
+                return Err(ApplicationError::config(format!(
+                    "Question {} has priority {}, expected 1-10",
+                    index + 1,
+                    question.priority
+                )));
+            }
+            if !(1..=10).contains(&question.complexity) {
+                return Err(ApplicationError::config(format!(
+                    "Question {} has complexity {}, expected 1-10",
+                    index + 1,
+                    question.complexity
+                )));
+            }
+        }
+        if self.config.max_iterations == 0 {
+            return Err(ApplicationError::config(
+                "max_iterations must be at least 1",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.config.confidence_threshold) {
+            return Err(ApplicationError::config(
+                "confidence_threshold must be between 0 and 1",
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Research template category
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -93,16 +154,20 @@ pub enum ParameterType {
 #[derive(Debug, Clone)]
 pub struct ResearchTemplateManager {
     templates: HashMap<String, ResearchTemplate>,
+    /// IDs of the built-in templates, which cannot be replaced or removed
+    builtin_ids: HashSet<String>,
 }
 
 impl Default for ResearchTemplateManager {
     fn default() -> Self {
         let mut manager = Self {
             templates: HashMap::new(),
+            builtin_ids: HashSet::new(),
         };
 
         // Load built-in templates
         manager.load_builtin_templates();
+        manager.builtin_ids = manager.templates.keys().cloned().collect();
         manager
     }
 }
@@ -258,6 +323,27 @@ impl ResearchTemplateManager {
         self.templates.insert(template.id.clone(), template);
     }
 
+    /// Remove a research template
+    pub fn remove_template(&mut self, template_id: &str) -> Option<ResearchTemplate> {
+        self.templates.remove(template_id)
+    }
+
+    /// Whether a template ships with Wikify
+    pub fn is_builtin(&self, template_id: &str) -> bool {
+        self.builtin_ids.contains(template_id)
+    }
+
+    /// List user-defined templates, ordered by ID
+    pub fn custom_templates(&self) -> Vec<&ResearchTemplate> {
+        let mut templates: Vec<_> = self
+            .templates
+            .values()
+            .filter(|t| !self.is_builtin(&t.id))
+            .collect();
+        templates.sort_by(|a, b| a.id.cmp(&b.id));
+        templates
+    }
+
     /// Get template by ID
     pub fn get_template(&self, template_id: &str) -> Option<&ResearchTemplate> {
         self.templates.get(template_id)
@@ -292,3 +378,115 @@ impl ResearchTemplateManager {
         Some((template.config.clone(), template.initial_questions.clone()))
     }
 }
+
+/// Stores user-defined research templates in a single JSON file
+#[derive(Debug, Clone)]
+pub struct FileResearchTemplateStorage {
+    path: PathBuf,
+}
+
+impl FileResearchTemplateStorage {
+    /// Create new file-based storage (the file is created on first write)
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Load the stored templates (empty if none were saved)
+    pub async fn load(&self) -> ApplicationResult<Vec<ResearchTemplate>> {
+        let content = match tokio::fs::read_to_string(&self.path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(ApplicationError::Io(e)),
+        };
+        serde_json::from_str(&content).map_err(ApplicationError::Serialization)
+    }
+
+    /// Replace the stored templates
+    pub async fn store(&self, templates: &[&ResearchTemplate]) -> ApplicationResult<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(ApplicationError::Io)?;
+        }
+
+        let content =
+            serde_json::to_string_pretty(templates).map_err(ApplicationError::Serialization)?;
+
+        // Write to a temporary file first so readers never see a partial file
+        let temp_path = self.path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, content)
+            .await
+            .map_err(ApplicationError::Io)?;
+        tokio::fs::rename(&temp_path, &self.path)
+            .await
+            .map_err(ApplicationError::Io)?;
+
+        info!(
+            "Stored {} custom research templates at {}",
+            templates.len(),
+            self.path.display()
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_template() -> ResearchTemplate {
+        ResearchTemplate {
+            id: "api-review".to_string(),
+            name: "API Review".to_string(),
+            description: "Review the public API".to_string(),
+            category: ResearchCategory::Custom,
+            config: ResearchConfig::default(),
+            initial_questions: vec![TemplateQuestion {
+                text: "Which types are part of the public API?".to_string(),
+                question_type: QuestionType::Architectural,
+                priority: 8,
+                complexity: 5,
+                keywords: vec!["api".to_string()],
+            }],
+            parameters: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_validate_template() {
+        assert!(custom_template().validate().is_ok());
+
+        let mut template = custom_template();
+        template.initial_questions[0].priority = 11;
+        assert!(template.validate().is_err());
+
+        let mut template = custom_template();
+        template.initial_questions.clear();
+        assert!(template.validate().is_err());
+
+        let mut template = custom_template();
+        template.id = "api review".to_string();
+        assert!(template.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_store_custom_templates() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = FileResearchTemplateStorage::new(temp.path().join("templates.json"));
+        assert!(storage.load().await.unwrap().is_empty());
+
+        let mut manager = ResearchTemplateManager::default();
+        assert!(manager.is_builtin("technical-analysis"));
+        assert!(manager.custom_templates().is_empty());
+
+        manager.add_template(custom_template());
+        storage.store(&manager.custom_templates()).await.unwrap();
+
+        let loaded = storage.load().await.unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, "api-review");
+        assert_eq!(loaded[0].initial_questions[0].priority, 8);
+    }
+}
@@ -31,6 +31,8 @@ pub enum ResearchStatus {
 pub struct ResearchProgress {
     /// Research session ID
     pub id: String,
+    /// Repository being researched
+    pub repository_id: String,
     /// Current status
     pub status: ResearchStatus,
     /// Current iteration number
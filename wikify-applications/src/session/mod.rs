@@ -0,0 +1,12 @@
+//! Chat sessions
+//!
+//! Conversations about a repository, together with the identities of the
+//! users who own them and the repositories each user has worked with.
+//! The in-memory backend is used by default; the SQLite backend keeps
+//! everything across restarts.
+
+pub mod storage;
+pub mod types;
+
+pub use storage::*;
+pub use types::*;
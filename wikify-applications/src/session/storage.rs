@@ -0,0 +1,679 @@
+//! Session storage backends
+//!
+//! Chat sessions, the identities of the users who own them and the
+//! repositories each user has worked with are kept in memory by default,
+//! or in SQLite so they survive restarts.
+
+use super::types::{ChatSessionRecord, ChatSessionSummary, RepositoryAssociation};
+use crate::{ApplicationError, ApplicationResult, UserIdentity};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// Session storage trait for different persistence backends
+#[async_trait]
+pub trait SessionStorage: Send + Sync {
+    /// Save or update a chat session, including its messages
+    async fn save_session(&self, session: &ChatSessionRecord) -> ApplicationResult<()>;
+
+    /// Load a chat session by ID
+    async fn load_session(&self, id: &str) -> ApplicationResult<Option<ChatSessionRecord>>;
+
+    /// List sessions, most recently active first, optionally filtered by
+    /// owner and repository
+    async fn list_sessions(
+        &self,
+        user_id: Option<&str>,
+        repository_id: Option<&str>,
+    ) -> ApplicationResult<Vec<ChatSessionSummary>>;
+
+    /// Delete a chat session
+    async fn delete_session(&self, id: &str) -> ApplicationResult<()>;
+
+    /// Delete all sessions and user associations of a repository, returning
+    /// the number of deleted sessions
+    async fn delete_repository_sessions(&self, repository_id: &str) -> ApplicationResult<usize>;
+
+    /// Save or update a user identity
+    async fn save_identity(&self, identity: &UserIdentity) -> ApplicationResult<()>;
+
+    /// Load a user identity by user ID
+    async fn load_identity(&self, user_id: &str) -> ApplicationResult<Option<UserIdentity>>;
+
+    /// Record that a user used a repository
+    async fn touch_repository(&self, user_id: &str, repository_id: &str) -> ApplicationResult<()>;
+
+    /// List the repositories a user has used, most recent first
+    async fn list_user_repositories(
+        &self,
+        user_id: &str,
+    ) -> ApplicationResult<Vec<RepositoryAssociation>>;
+}
+
+/// Sort session summaries with the most recently active first
+fn sort_by_activity(sessions: &mut [ChatSessionSummary]) {
+    sessions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+}
+
+/// In-memory session storage (default implementation)
+#[derive(Debug, Default)]
+pub struct MemorySessionStorage {
+    sessions: RwLock<HashMap<String, ChatSessionRecord>>,
+    identities: RwLock<HashMap<String, UserIdentity>>,
+    associations: RwLock<HashMap<(String, String), RepositoryAssociation>>,
+}
+
+impl MemorySessionStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStorage for MemorySessionStorage {
+    async fn save_session(&self, session: &ChatSessionRecord) -> ApplicationResult<()> {
+        self.sessions
+            .write()
+            .await
+            .insert(session.id.clone(), session.clone());
+        Ok(())
+    }
+
+    async fn load_session(&self, id: &str) -> ApplicationResult<Option<ChatSessionRecord>> {
+        Ok(self.sessions.read().await.get(id).cloned())
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: Option<&str>,
+        repository_id: Option<&str>,
+    ) -> ApplicationResult<Vec<ChatSessionSummary>> {
+        let mut sessions: Vec<ChatSessionSummary> = self
+            .sessions
+            .read()
+            .await
+            .values()
+            .filter(|s| user_id.map_or(true, |id| s.user_id.as_deref() == Some(id)))
+            .filter(|s| repository_id.map_or(true, |id| s.repository_id == id))
+            .map(ChatSessionRecord::summary)
+            .collect();
+        sort_by_activity(&mut sessions);
+        Ok(sessions)
+    }
+
+    async fn delete_session(&self, id: &str) -> ApplicationResult<()> {
+        self.sessions
+            .write()
+            .await
+            .remove(id)
+            .map(|_| ())
+            .ok_or_else(|| ApplicationError::not_found(format!("Chat session not found: {}", id)))
+    }
+
+    async fn delete_repository_sessions(&self, repository_id: &str) -> ApplicationResult<usize> {
+        let mut sessions = self.sessions.write().await;
+        let before = sessions.len();
+        sessions.retain(|_, s| s.repository_id != repository_id);
+        let deleted = before - sessions.len();
+
+        self.associations
+            .write()
+            .await
+            .retain(|(_, repo_id), _| repo_id != repository_id);
+        Ok(deleted)
+    }
+
+    async fn save_identity(&self, identity: &UserIdentity) -> ApplicationResult<()> {
+        self.identities
+            .write()
+            .await
+            .insert(identity.user_id.clone(), identity.clone());
+        Ok(())
+    }
+
+    async fn load_identity(&self, user_id: &str) -> ApplicationResult<Option<UserIdentity>> {
+        Ok(self.identities.read().await.get(user_id).cloned())
+    }
+
+    async fn touch_repository(&self, user_id: &str, repository_id: &str) -> ApplicationResult<()> {
+        let now = Utc::now();
+        self.associations
+            .write()
+            .await
+            .entry((user_id.to_string(), repository_id.to_string()))
+            .and_modify(|a| a.last_used_at = now)
+            .or_insert_with(|| RepositoryAssociation {
+                user_id: user_id.to_string(),
+                repository_id: repository_id.to_string(),
+                first_used_at: now,
+                last_used_at: now,
+            });
+        Ok(())
+    }
+
+    async fn list_user_repositories(
+        &self,
+        user_id: &str,
+    ) -> ApplicationResult<Vec<RepositoryAssociation>> {
+        let mut associations: Vec<RepositoryAssociation> = self
+            .associations
+            .read()
+            .await
+            .values()
+            .filter(|a| a.user_id == user_id)
+            .cloned()
+            .collect();
+        associations.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+        Ok(associations)
+    }
+}
+
+/// SQLite session storage implementation
+#[cfg(feature = "sqlite")]
+pub struct SqliteSessionStorage {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteSessionStorage {
+    /// Create the storage on an existing pool, creating its tables if needed
+    pub async fn new(pool: sqlx::SqlitePool) -> ApplicationResult<Self> {
+        let storage = Self { pool };
+        storage.create_tables().await?;
+        Ok(storage)
+    }
+
+    /// Create from database URL
+    pub async fn from_url(database_url: &str) -> ApplicationResult<Self> {
+        let pool = sqlx::SqlitePool::connect(database_url)
+            .await
+            .map_err(|e| Self::db_error("Failed to connect to SQLite database", e))?;
+        Self::new(pool).await
+    }
+
+    /// Create database tables
+    async fn create_tables(&self) -> ApplicationResult<()> {
+        let query = r#"
+            CREATE TABLE IF NOT EXISTS chat_sessions (
+                id TEXT PRIMARY KEY,
+                repository_id TEXT NOT NULL,
+                user_id TEXT,
+                title TEXT NOT NULL DEFAULT '',
+                created_at TEXT NOT NULL,
+                last_activity TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS chat_messages (
+                id TEXT PRIMARY KEY,
+                session_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                role TEXT NOT NULL,
+                content TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                metadata TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS user_identities (
+                user_id TEXT PRIMARY KEY,
+                identity TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS user_repositories (
+                user_id TEXT NOT NULL,
+                repository_id TEXT NOT NULL,
+                first_used_at TEXT NOT NULL,
+                last_used_at TEXT NOT NULL,
+                PRIMARY KEY (user_id, repository_id)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_chat_sessions_user ON chat_sessions(user_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_sessions_repository ON chat_sessions(repository_id);
+            CREATE INDEX IF NOT EXISTS idx_chat_messages_session ON chat_messages(session_id, position);
+        "#;
+
+        sqlx::query(query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to create session tables", e))?;
+
+        // Databases created before sessions had titles lack the column
+        let has_title: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('chat_sessions') WHERE name = 'title'",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to inspect session tables", e))?;
+        if has_title == 0 {
+            sqlx::query("ALTER TABLE chat_sessions ADD COLUMN title TEXT NOT NULL DEFAULT ''")
+                .execute(&self.pool)
+                .await
+                .map_err(|e| Self::db_error("Failed to add title to session table", e))?;
+        }
+
+        Ok(())
+    }
+
+    fn db_error(message: &str, error: sqlx::Error) -> ApplicationError {
+        ApplicationError::internal_with_source(message, Box::new(error))
+    }
+
+    fn parse_time(value: &str) -> ApplicationResult<chrono::DateTime<Utc>> {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|e| ApplicationError::internal(format!("Invalid timestamp {}: {}", value, e)))
+    }
+
+    fn row_to_summary(row: &sqlx::sqlite::SqliteRow) -> ApplicationResult<ChatSessionSummary> {
+        use sqlx::Row;
+
+        let created_at: String = row
+            .try_get("created_at")
+            .map_err(|e| Self::db_error("Failed to read session", e))?;
+        let last_activity: String = row
+            .try_get("last_activity")
+            .map_err(|e| Self::db_error("Failed to read session", e))?;
+        let message_count: i64 = row
+            .try_get("message_count")
+            .map_err(|e| Self::db_error("Failed to read session", e))?;
+
+        Ok(ChatSessionSummary {
+            id: row
+                .try_get("id")
+                .map_err(|e| Self::db_error("Failed to read session", e))?,
+            repository_id: row
+                .try_get("repository_id")
+                .map_err(|e| Self::db_error("Failed to read session", e))?,
+            user_id: row
+                .try_get("user_id")
+                .map_err(|e| Self::db_error("Failed to read session", e))?,
+            title: row
+                .try_get("title")
+                .map_err(|e| Self::db_error("Failed to read session", e))?,
+            message_count: message_count as usize,
+            created_at: Self::parse_time(&created_at)?,
+            last_activity: Self::parse_time(&last_activity)?,
+        })
+    }
+
+    fn row_to_message(row: &sqlx::sqlite::SqliteRow) -> ApplicationResult<wikify_rag::ChatMessage> {
+        use sqlx::Row;
+
+        let timestamp: String = row
+            .try_get("timestamp")
+            .map_err(|e| Self::db_error("Failed to read chat message", e))?;
+        let metadata: Option<String> = row
+            .try_get("metadata")
+            .map_err(|e| Self::db_error("Failed to read chat message", e))?;
+
+        Ok(wikify_rag::ChatMessage {
+            id: row
+                .try_get("id")
+                .map_err(|e| Self::db_error("Failed to read chat message", e))?,
+            role: row
+                .try_get("role")
+                .map_err(|e| Self::db_error("Failed to read chat message", e))?,
+            content: row
+                .try_get("content")
+                .map_err(|e| Self::db_error("Failed to read chat message", e))?,
+            timestamp: Self::parse_time(&timestamp)?,
+            metadata: metadata
+                .map(|json| serde_json::from_str(&json))
+                .transpose()
+                .map_err(ApplicationError::Serialization)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl SessionStorage for SqliteSessionStorage {
+    async fn save_session(&self, session: &ChatSessionRecord) -> ApplicationResult<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Self::db_error("Failed to start transaction", e))?;
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO chat_sessions (id, repository_id, user_id, title, created_at, last_activity) VALUES (?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&session.id)
+        .bind(&session.repository_id)
+        .bind(&session.user_id)
+        .bind(&session.title)
+        .bind(session.created_at.to_rfc3339())
+        .bind(session.last_activity.to_rfc3339())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Self::db_error("Failed to save chat session", e))?;
+
+        // Messages are rewritten as a whole since old ones may have been trimmed
+        sqlx::query("DELETE FROM chat_messages WHERE session_id = ?")
+            .bind(&session.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Self::db_error("Failed to save chat session", e))?;
+
+        for (position, message) in session.messages.iter().enumerate() {
+            let metadata = message
+                .metadata
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()
+                .map_err(ApplicationError::Serialization)?;
+
+            sqlx::query(
+                "INSERT INTO chat_messages (id, session_id, position, role, content, timestamp, metadata) VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(&message.id)
+            .bind(&session.id)
+            .bind(position as i64)
+            .bind(&message.role)
+            .bind(&message.content)
+            .bind(message.timestamp.to_rfc3339())
+            .bind(metadata)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Self::db_error("Failed to save chat message", e))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| Self::db_error("Failed to save chat session", e))?;
+
+        debug!("Saved chat session {} to SQLite storage", session.id);
+        Ok(())
+    }
+
+    async fn load_session(&self, id: &str) -> ApplicationResult<Option<ChatSessionRecord>> {
+        let Some(row) = sqlx::query(
+            r#"
+            SELECT s.id, s.repository_id, s.user_id, s.title, s.created_at, s.last_activity,
+                (SELECT COUNT(*) FROM chat_messages m WHERE m.session_id = s.id) AS message_count
+            FROM chat_sessions s
+            WHERE s.id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to load chat session", e))?
+        else {
+            return Ok(None);
+        };
+        let summary = Self::row_to_summary(&row)?;
+
+        let messages = sqlx::query(
+            "SELECT id, role, content, timestamp, metadata FROM chat_messages WHERE session_id = ? ORDER BY position",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to load chat messages", e))?
+        .iter()
+        .map(Self::row_to_message)
+        .collect::<ApplicationResult<Vec<_>>>()?;
+
+        Ok(Some(ChatSessionRecord {
+            id: summary.id,
+            repository_id: summary.repository_id,
+            user_id: summary.user_id,
+            title: summary.title,
+            messages,
+            created_at: summary.created_at,
+            last_activity: summary.last_activity,
+        }))
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: Option<&str>,
+        repository_id: Option<&str>,
+    ) -> ApplicationResult<Vec<ChatSessionSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT s.id, s.repository_id, s.user_id, s.title, s.created_at, s.last_activity,
+                (SELECT COUNT(*) FROM chat_messages m WHERE m.session_id = s.id) AS message_count
+            FROM chat_sessions s
+            WHERE (? IS NULL OR s.user_id = ?) AND (? IS NULL OR s.repository_id = ?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(user_id)
+        .bind(repository_id)
+        .bind(repository_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to list chat sessions", e))?;
+
+        let mut sessions = rows
+            .iter()
+            .map(Self::row_to_summary)
+            .collect::<ApplicationResult<Vec<_>>>()?;
+        sort_by_activity(&mut sessions);
+        Ok(sessions)
+    }
+
+    async fn delete_session(&self, id: &str) -> ApplicationResult<()> {
+        let result = sqlx::query("DELETE FROM chat_sessions WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to delete chat session", e))?;
+        if result.rows_affected() == 0 {
+            return Err(ApplicationError::not_found(format!(
+                "Chat session not found: {}",
+                id
+            )));
+        }
+
+        sqlx::query("DELETE FROM chat_messages WHERE session_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to delete chat messages", e))?;
+        Ok(())
+    }
+
+    async fn delete_repository_sessions(&self, repository_id: &str) -> ApplicationResult<usize> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| Self::db_error("Failed to start transaction", e))?;
+
+        sqlx::query(
+            "DELETE FROM chat_messages WHERE session_id IN (SELECT id FROM chat_sessions WHERE repository_id = ?)",
+        )
+        .bind(repository_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| Self::db_error("Failed to delete chat messages", e))?;
+
+        let deleted = sqlx::query("DELETE FROM chat_sessions WHERE repository_id = ?")
+            .bind(repository_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Self::db_error("Failed to delete chat sessions", e))?
+            .rows_affected();
+
+        sqlx::query("DELETE FROM user_repositories WHERE repository_id = ?")
+            .bind(repository_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| Self::db_error("Failed to delete repository associations", e))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| Self::db_error("Failed to delete chat sessions", e))?;
+        Ok(deleted as usize)
+    }
+
+    async fn save_identity(&self, identity: &UserIdentity) -> ApplicationResult<()> {
+        let json = serde_json::to_string(identity).map_err(ApplicationError::Serialization)?;
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO user_identities (user_id, identity, updated_at) VALUES (?, ?, ?)",
+        )
+        .bind(&identity.user_id)
+        .bind(json)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to save user identity", e))?;
+        Ok(())
+    }
+
+    async fn load_identity(&self, user_id: &str) -> ApplicationResult<Option<UserIdentity>> {
+        let json: Option<String> =
+            sqlx::query_scalar("SELECT identity FROM user_identities WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| Self::db_error("Failed to load user identity", e))?;
+
+        json.map(|json| serde_json::from_str(&json))
+            .transpose()
+            .map_err(ApplicationError::Serialization)
+    }
+
+    async fn touch_repository(&self, user_id: &str, repository_id: &str) -> ApplicationResult<()> {
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO user_repositories (user_id, repository_id, first_used_at, last_used_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id, repository_id) DO UPDATE SET last_used_at = excluded.last_used_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(repository_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to save repository association", e))?;
+        Ok(())
+    }
+
+    async fn list_user_repositories(
+        &self,
+        user_id: &str,
+    ) -> ApplicationResult<Vec<RepositoryAssociation>> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            "SELECT repository_id, first_used_at, last_used_at FROM user_repositories WHERE user_id = ? ORDER BY last_used_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to list repository associations", e))?;
+
+        rows.iter()
+            .map(|row| {
+                let first_used_at: String = row
+                    .try_get("first_used_at")
+                    .map_err(|e| Self::db_error("Failed to read repository association", e))?;
+                let last_used_at: String = row
+                    .try_get("last_used_at")
+                    .map_err(|e| Self::db_error("Failed to read repository association", e))?;
+                Ok(RepositoryAssociation {
+                    user_id: user_id.to_string(),
+                    repository_id: row
+                        .try_get("repository_id")
+                        .map_err(|e| Self::db_error("Failed to read repository association", e))?,
+                    first_used_at: Self::parse_time(&first_used_at)?,
+                    last_used_at: Self::parse_time(&last_used_at)?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_storage_scopes_sessions() {
+        let storage = MemorySessionStorage::new();
+        let mut alice = ChatSessionRecord::new("repo-1".to_string(), Some("alice".to_string()));
+        alice.push_message("user", "How does indexing work?".to_string());
+        storage.save_session(&alice).await.unwrap();
+        storage
+            .save_session(&ChatSessionRecord::new(
+                "repo-2".to_string(),
+                Some("bob".to_string()),
+            ))
+            .await
+            .unwrap();
+        storage.touch_repository("alice", "repo-1").await.unwrap();
+
+        let sessions = storage.list_sessions(Some("alice"), None).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].message_count, 1);
+        assert_eq!(storage.list_sessions(None, None).await.unwrap().len(), 2);
+
+        assert_eq!(
+            storage.delete_repository_sessions("repo-1").await.unwrap(),
+            1
+        );
+        assert!(storage.load_session(&alice.id).await.unwrap().is_none());
+        assert!(storage
+            .list_user_repositories("alice")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_sessions_survive_reconnect() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!(
+            "sqlite://{}?mode=rwc",
+            temp.path().join("sessions.db").display()
+        );
+
+        let storage = SqliteSessionStorage::from_url(&url).await.unwrap();
+        let mut session = ChatSessionRecord::new("repo-1".to_string(), Some("alice".to_string()));
+        session.push_message("user", "Where is the config loaded?".to_string());
+        session.push_message("assistant", "In config.rs".to_string());
+        storage.save_session(&session).await.unwrap();
+        storage
+            .save_identity(&UserIdentity::registered(
+                "alice".to_string(),
+                Some("Alice".to_string()),
+                None,
+            ))
+            .await
+            .unwrap();
+        storage.touch_repository("alice", "repo-1").await.unwrap();
+        drop(storage);
+
+        let reopened = SqliteSessionStorage::from_url(&url).await.unwrap();
+        let loaded = reopened.load_session(&session.id).await.unwrap().unwrap();
+        assert_eq!(loaded.user_id.as_deref(), Some("alice"));
+        assert_eq!(loaded.messages.len(), 2);
+        assert_eq!(loaded.messages[1].content, "In config.rs");
+        assert_eq!(loaded.title, "Where is the config loaded?");
+
+        let identity = reopened.load_identity("alice").await.unwrap().unwrap();
+        assert_eq!(identity.display_name.as_deref(), Some("Alice"));
+
+        let repositories = reopened.list_user_repositories("alice").await.unwrap();
+        assert_eq!(repositories.len(), 1);
+        assert_eq!(repositories[0].repository_id, "repo-1");
+
+        let sessions = reopened.list_sessions(None, Some("repo-1")).await.unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].message_count, 2);
+
+        reopened.delete_session(&session.id).await.unwrap();
+        assert!(reopened.delete_session(&session.id).await.is_err());
+    }
+}
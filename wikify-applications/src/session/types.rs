@@ -0,0 +1,250 @@
+//! Chat session types
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use wikify_rag::ChatMessage;
+
+/// A chat conversation about one repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSessionRecord {
+    /// Session ID
+    pub id: String,
+    /// Repository the conversation is about
+    pub repository_id: String,
+    /// User who owns the session (None for sessions of anonymous users)
+    pub user_id: Option<String>,
+    /// Session title, generated from the first question unless set by the user
+    #[serde(default)]
+    pub title: String,
+    /// Messages in chronological order
+    pub messages: Vec<ChatMessage>,
+    /// Session creation time
+    pub created_at: DateTime<Utc>,
+    /// Time of the last message
+    pub last_activity: DateTime<Utc>,
+}
+
+impl ChatSessionRecord {
+    /// Maximum number of messages kept per session, oldest are dropped first
+    pub const MAX_MESSAGES: usize = 200;
+
+    /// Maximum length of generated titles, in characters
+    pub const MAX_TITLE_LENGTH: usize = 60;
+
+    /// Create an empty session
+    pub fn new(repository_id: String, user_id: Option<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            repository_id,
+            user_id,
+            title: String::new(),
+            messages: Vec::new(),
+            created_at: now,
+            last_activity: now,
+        }
+    }
+
+    /// Append a message with role "user" or "assistant"
+    pub fn push_message(&mut self, role: &str, content: String) {
+        self.push_message_with_metadata(role, content, None);
+    }
+
+    /// Append a message along with metadata such as the sources retrieved
+    /// for an answer
+    ///
+    /// The first question of an untitled session becomes its title.
+    pub fn push_message_with_metadata(
+        &mut self,
+        role: &str,
+        content: String,
+        metadata: Option<HashMap<String, serde_json::Value>>,
+    ) {
+        if self.title.is_empty() && role == "user" {
+            self.title = Self::generate_title(&content);
+        }
+
+        let timestamp = Utc::now();
+        self.messages.push(ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: role.to_string(),
+            content,
+            timestamp,
+            metadata,
+        });
+        self.last_activity = timestamp;
+
+        if self.messages.len() > Self::MAX_MESSAGES {
+            let excess = self.messages.len() - Self::MAX_MESSAGES;
+            self.messages.drain(0..excess);
+        }
+    }
+
+    /// Append an answer to the last question, returning its branch number
+    ///
+    /// Every answer to a question is kept: the first is branch 0 and each
+    /// regenerated answer adds the next branch, the latest being the
+    /// current one. The question and branch are recorded in the metadata
+    /// as `question_id` and `branch`.
+    pub fn push_answer(
+        &mut self,
+        content: String,
+        mut metadata: HashMap<String, serde_json::Value>,
+    ) -> usize {
+        let (question_id, branch) = match self.last_question_index() {
+            Some(index) => (
+                Some(self.messages[index].id.clone()),
+                self.messages.len() - index - 1,
+            ),
+            None => (None, 0),
+        };
+
+        metadata.insert("question_id".to_string(), serde_json::json!(question_id));
+        metadata.insert("branch".to_string(), serde_json::json!(branch));
+        self.push_message_with_metadata("assistant", content, Some(metadata));
+        branch
+    }
+
+    /// The most recent question asked in the session
+    pub fn last_question(&self) -> Option<&ChatMessage> {
+        self.last_question_index()
+            .map(|index| &self.messages[index])
+    }
+
+    fn last_question_index(&self) -> Option<usize> {
+        self.messages
+            .iter()
+            .rposition(|message| message.role == "user")
+    }
+
+    /// Generate a title from a question, collapsing whitespace and
+    /// shortening it to [`Self::MAX_TITLE_LENGTH`] characters
+    pub fn generate_title(question: &str) -> String {
+        let question = question.split_whitespace().collect::<Vec<_>>().join(" ");
+        if question.chars().count() <= Self::MAX_TITLE_LENGTH {
+            return question;
+        }
+
+        let mut title: String = question.chars().take(Self::MAX_TITLE_LENGTH - 1).collect();
+        title.truncate(title.trim_end().len());
+        title.push('…');
+        title
+    }
+
+    /// Whether a user may read and continue this session
+    ///
+    /// Sessions without an owner are accessible to anyone who knows their ID.
+    pub fn is_accessible_by(&self, user_id: Option<&str>) -> bool {
+        match &self.user_id {
+            Some(owner) => user_id == Some(owner.as_str()),
+            None => true,
+        }
+    }
+
+    /// Summary of the session without its messages
+    pub fn summary(&self) -> ChatSessionSummary {
+        ChatSessionSummary {
+            id: self.id.clone(),
+            repository_id: self.repository_id.clone(),
+            user_id: self.user_id.clone(),
+            title: self.title.clone(),
+            message_count: self.messages.len(),
+            created_at: self.created_at,
+            last_activity: self.last_activity,
+        }
+    }
+}
+
+/// Chat session as returned by listings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatSessionSummary {
+    pub id: String,
+    pub repository_id: String,
+    pub user_id: Option<String>,
+    pub title: String,
+    pub message_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub last_activity: DateTime<Utc>,
+}
+
+/// A repository a user has chatted about
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepositoryAssociation {
+    pub user_id: String,
+    pub repository_id: String,
+    /// First time the user used the repository
+    pub first_used_at: DateTime<Utc>,
+    /// Most recent time the user used the repository
+    pub last_used_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_messages_and_access() {
+        let mut session = ChatSessionRecord::new("repo-1".to_string(), Some("alice".to_string()));
+        for i in 0..ChatSessionRecord::MAX_MESSAGES + 5 {
+            session.push_message("user", format!("question {}", i));
+        }
+
+        assert_eq!(session.messages.len(), ChatSessionRecord::MAX_MESSAGES);
+        assert_eq!(session.messages[0].content, "question 5");
+        assert_eq!(session.title, "question 0");
+        assert_eq!(
+            session.summary().message_count,
+            ChatSessionRecord::MAX_MESSAGES
+        );
+
+        assert!(session.is_accessible_by(Some("alice")));
+        assert!(!session.is_accessible_by(Some("bob")));
+        assert!(!session.is_accessible_by(None));
+
+        let shared = ChatSessionRecord::new("repo-1".to_string(), None);
+        assert!(shared.is_accessible_by(None));
+        assert!(shared.is_accessible_by(Some("bob")));
+    }
+
+    #[test]
+    fn test_answer_branches() {
+        let mut session = ChatSessionRecord::new("repo-1".to_string(), None);
+        assert!(session.last_question().is_none());
+
+        session.push_message("user", "How is the index stored?".to_string());
+        let question_id = session.last_question().unwrap().id.clone();
+        assert_eq!(
+            session.push_answer("On disk".to_string(), HashMap::new()),
+            0
+        );
+        assert_eq!(
+            session.push_answer("In SQLite".to_string(), HashMap::new()),
+            1
+        );
+
+        let metadata = session.messages[2].metadata.as_ref().unwrap();
+        assert_eq!(metadata["question_id"], serde_json::json!(question_id));
+        assert_eq!(metadata["branch"], serde_json::json!(1));
+
+        session.push_message("user", "Which tables?".to_string());
+        assert_eq!(session.push_answer("Three".to_string(), HashMap::new()), 0);
+    }
+
+    #[test]
+    fn test_generated_titles() {
+        assert_eq!(
+            ChatSessionRecord::generate_title("  How does\n indexing   work? "),
+            "How does indexing work?"
+        );
+
+        let title = ChatSessionRecord::generate_title(&"word ".repeat(30));
+        assert_eq!(title.chars().count(), ChatSessionRecord::MAX_TITLE_LENGTH);
+        assert!(title.ends_with("word…"));
+
+        let mut session = ChatSessionRecord::new("repo-1".to_string(), None);
+        session.title = "Renamed".to_string();
+        session.push_message("user", "First question".to_string());
+        assert_eq!(session.title, "Renamed");
+    }
+}
@@ -0,0 +1,168 @@
+//! Server statistics for administrators
+//!
+//! Combines repository counts from storage, the size of the loaded indexes,
+//! the operation metrics collected since the server started and disk usage
+//! into one snapshot for an operations dashboard.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::repository::RepositoryDiskUsage;
+
+/// Number of repositories in each indexing status
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepositoryStatusCounts {
+    pub total: usize,
+    pub pending: usize,
+    pub indexing: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+}
+
+/// Size of the vector index of a repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepositoryIndexSize {
+    pub repository_id: String,
+    /// Number of indexed chunks
+    pub chunks: usize,
+}
+
+/// Vector indexes loaded in memory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IndexStats {
+    /// Number of loaded indexes
+    pub loaded: usize,
+    /// Chunks across all loaded indexes
+    pub total_chunks: usize,
+    /// Loaded indexes, largest first
+    pub repositories: Vec<RepositoryIndexSize>,
+}
+
+/// Outcomes and latencies of one kind of operation since the server started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OperationStats {
+    /// Operations started
+    pub total: u64,
+    pub successful: u64,
+    pub failed: u64,
+    /// Operations still running
+    pub active: u32,
+    /// Share of finished operations that failed (0.0 to 1.0)
+    pub failure_rate: f64,
+    /// Latencies of successful operations, in milliseconds
+    pub avg_duration_ms: u64,
+    pub min_duration_ms: u64,
+    pub max_duration_ms: u64,
+}
+
+impl OperationStats {
+    /// Share of finished operations that failed
+    pub fn failure_rate(successful: u64, failed: u64) -> f64 {
+        let finished = successful + failed;
+        if finished == 0 {
+            0.0
+        } else {
+            failed as f64 / finished as f64
+        }
+    }
+}
+
+/// Indexing and query metrics of one repository since the server started
+///
+/// Operations still running are not counted per repository, so `active` is
+/// always 0.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepositoryOperationStats {
+    pub repository_id: String,
+    /// Duration of the last successful indexing run, in milliseconds
+    pub last_index_duration_ms: Option<u64>,
+    /// Documents indexed by the last successful indexing run
+    pub documents: usize,
+    /// Chunks indexed by the last successful indexing run
+    pub chunks: usize,
+    pub indexing: OperationStats,
+    pub queries: OperationStats,
+}
+
+/// Repository counts, index sizes and operation metrics of the repository manager
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepositoryStats {
+    pub repositories: RepositoryStatusCounts,
+    pub indexes: IndexStats,
+    pub indexing: OperationStats,
+    pub queries: OperationStats,
+    /// Metrics of each repository, most queried first
+    pub repository_metrics: Vec<RepositoryOperationStats>,
+}
+
+/// Disk space used by the server, in bytes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StorageStats {
+    /// Cloned repositories
+    pub clones_bytes: u64,
+    /// Generated wikis and their archived versions
+    pub wikis_bytes: u64,
+    /// Extracted repository uploads
+    pub uploads_bytes: u64,
+    /// Database file, when the database is a local file
+    pub database_bytes: Option<u64>,
+    pub total_bytes: u64,
+}
+
+/// Disk usage of one repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepositoryStorageUsage {
+    pub repository_id: String,
+    pub url: String,
+    pub usage: RepositoryDiskUsage,
+}
+
+/// Disk usage of all repositories, checked against the indexing quota
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct StorageUsageReport {
+    pub generated_at: DateTime<Utc>,
+    /// Repositories, largest first
+    pub repositories: Vec<RepositoryStorageUsage>,
+    /// Clones, indexes and cached wikis, including those of no repository
+    pub total_bytes: u64,
+    /// Disk usage at which indexing jobs are refused, if limited
+    pub quota_bytes: Option<u64>,
+    /// Whether new indexing jobs are being refused
+    pub quota_exceeded: bool,
+}
+
+/// Snapshot of the server for the admin dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SystemStats {
+    pub generated_at: DateTime<Utc>,
+    pub repositories: RepositoryStatusCounts,
+    pub indexes: IndexStats,
+    pub indexing: OperationStats,
+    pub queries: OperationStats,
+    /// Metrics of each repository, most queried first
+    pub repository_metrics: Vec<RepositoryOperationStats>,
+    pub storage: StorageStats,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_rate() {
+        assert_eq!(OperationStats::failure_rate(0, 0), 0.0);
+        assert_eq!(OperationStats::failure_rate(3, 1), 0.25);
+        assert_eq!(OperationStats::failure_rate(0, 2), 1.0);
+    }
+}
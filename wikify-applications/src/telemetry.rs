@@ -0,0 +1,263 @@
+//! Opt-in anonymous usage telemetry
+//!
+//! Telemetry is off unless `WIKIFY_TELEMETRY=1` is set. When it is on,
+//! a few coarse counters are posted to [`DEFAULT_ENDPOINT`] (or
+//! `WIKIFY_TELEMETRY_ENDPOINT`) to help decide which features to work on:
+//! which commands run, roughly how large indexed repositories and
+//! generated wikis are, and which provider types are configured.
+//!
+//! Repository names, URLs, paths, questions, answers and generated content
+//! are never reported. Every event field is a `&'static str` chosen here,
+//! sizes are rounded to buckets and provider names outside the built-in
+//! list are reported as `other`. Reports are best effort: a failed report
+//! is logged at debug level and dropped.
+
+use serde::Serialize;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio_util::task::TaskTracker;
+use tracing::debug;
+
+/// Environment variable opting in to telemetry
+pub const TELEMETRY_ENV: &str = "WIKIFY_TELEMETRY";
+/// Environment variable overriding where reports are posted
+pub const TELEMETRY_ENDPOINT_ENV: &str = "WIKIFY_TELEMETRY_ENDPOINT";
+/// Where reports are posted by default
+pub const DEFAULT_ENDPOINT: &str = "https://telemetry.wikify.dev/v1/events";
+
+/// Shown whenever telemetry is enabled
+pub const NOTICE: &str = "Anonymous telemetry is enabled (WIKIFY_TELEMETRY=1): command names, \
+rough repository and wiki sizes and provider types are reported, never code, names or \
+questions. Unset WIKIFY_TELEMETRY to turn it off.";
+
+/// Timeout of a single report
+const REPORT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A coarse usage event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TelemetryEvent {
+    /// A CLI command ran, such as `ask`
+    CommandRun { command: &'static str },
+    /// The web server started
+    ServerStarted,
+    /// The LLM and embedding provider types in use
+    ProvidersConfigured {
+        llm_provider: &'static str,
+        embedding_provider: &'static str,
+    },
+    /// A repository finished indexing
+    RepositoryIndexed {
+        documents: &'static str,
+        chunks: &'static str,
+    },
+    /// A wiki was generated
+    WikiGenerated { pages: &'static str },
+}
+
+impl TelemetryEvent {
+    pub fn command(command: &'static str) -> Self {
+        Self::CommandRun { command }
+    }
+
+    pub fn providers(config: &wikify_rag::RagConfig) -> Self {
+        Self::ProvidersConfigured {
+            llm_provider: provider_type(&config.llm.provider, wikify_rag::types::LLM_PROVIDERS),
+            embedding_provider: provider_type(
+                &config.embeddings.provider,
+                wikify_rag::types::EMBEDDING_PROVIDERS,
+            ),
+        }
+    }
+
+    pub fn repository_indexed(documents: usize, chunks: usize) -> Self {
+        Self::RepositoryIndexed {
+            documents: size_bucket(documents),
+            chunks: size_bucket(chunks),
+        }
+    }
+
+    pub fn wiki_generated(pages: usize) -> Self {
+        Self::WikiGenerated {
+            pages: size_bucket(pages),
+        }
+    }
+}
+
+/// Body of a report: the event plus the version and OS it came from
+#[derive(Serialize)]
+struct Report<'a> {
+    #[serde(flatten)]
+    event: &'a TelemetryEvent,
+    version: &'static str,
+    os: &'static str,
+}
+
+/// Round a count to an order of magnitude
+pub fn size_bucket(count: usize) -> &'static str {
+    match count {
+        0 => "0",
+        1..=9 => "1-9",
+        10..=99 => "10-99",
+        100..=999 => "100-999",
+        1_000..=9_999 => "1k-10k",
+        10_000..=99_999 => "10k-100k",
+        _ => "100k+",
+    }
+}
+
+/// Name of a built-in provider, or `other` for anything else
+fn provider_type(name: &str, known: &[(&'static str, Option<&str>)]) -> &'static str {
+    known
+        .iter()
+        .map(|(provider, _)| *provider)
+        .find(|provider| provider.eq_ignore_ascii_case(name.trim()))
+        .unwrap_or("other")
+}
+
+/// Whether a `WIKIFY_TELEMETRY` value opts in
+pub fn is_opt_in(value: Option<&str>) -> bool {
+    matches!(
+        value.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("1" | "true" | "yes" | "on")
+    )
+}
+
+/// Posts telemetry reports in the background
+pub struct Telemetry {
+    client: reqwest::Client,
+    endpoint: String,
+    pending: TaskTracker,
+}
+
+impl Telemetry {
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REPORT_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            client,
+            endpoint: endpoint.into(),
+            pending: TaskTracker::new(),
+        }
+    }
+
+    /// Telemetry configured from the environment, if the user opted in
+    pub fn from_env() -> Option<Self> {
+        if !is_opt_in(std::env::var(TELEMETRY_ENV).ok().as_deref()) {
+            return None;
+        }
+        let endpoint = std::env::var(TELEMETRY_ENDPOINT_ENV)
+            .ok()
+            .filter(|endpoint| !endpoint.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
+        Some(Self::new(endpoint))
+    }
+
+    /// Post an event in the background
+    pub fn record(&self, event: TelemetryEvent) {
+        // Reports are only sent from async code; elsewhere they are dropped
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let client = self.client.clone();
+        let endpoint = self.endpoint.clone();
+        self.pending.spawn_on(
+            async move {
+                let report = Report {
+                    event: &event,
+                    version: env!("CARGO_PKG_VERSION"),
+                    os: std::env::consts::OS,
+                };
+                let result = client.post(&endpoint).json(&report).send().await;
+                match result.and_then(|response| response.error_for_status()) {
+                    Ok(_) => debug!("Sent telemetry event {:?}", event),
+                    Err(e) => debug!("Failed to send telemetry event {:?}: {}", event, e),
+                }
+            },
+            &runtime,
+        );
+    }
+
+    /// Wait for reports still being sent, such as before a CLI exits
+    pub async fn flush(&self) {
+        self.pending.close();
+        let _ = tokio::time::timeout(REPORT_TIMEOUT, self.pending.wait()).await;
+        self.pending.reopen();
+    }
+}
+
+/// Telemetry of this process, read from the environment once
+pub fn telemetry() -> Option<&'static Telemetry> {
+    static TELEMETRY: OnceLock<Option<Telemetry>> = OnceLock::new();
+    TELEMETRY.get_or_init(Telemetry::from_env).as_ref()
+}
+
+/// Record an event if telemetry is enabled
+pub fn record(event: TelemetryEvent) {
+    if let Some(telemetry) = telemetry() {
+        telemetry.record(event);
+    }
+}
+
+/// Wait for pending reports if telemetry is enabled
+pub async fn flush() {
+    if let Some(telemetry) = telemetry() {
+        telemetry.flush().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_explicit_values_opt_in() {
+        assert!(!is_opt_in(None));
+        assert!(!is_opt_in(Some("")));
+        assert!(!is_opt_in(Some("0")));
+        assert!(!is_opt_in(Some("false")));
+        assert!(is_opt_in(Some("1")));
+        assert!(is_opt_in(Some(" TRUE ")));
+    }
+
+    #[test]
+    fn test_sizes_and_providers_are_coarse() {
+        assert_eq!(size_bucket(0), "0");
+        assert_eq!(size_bucket(42), "10-99");
+        assert_eq!(size_bucket(12_345), "10k-100k");
+        assert_eq!(size_bucket(5_000_000), "100k+");
+
+        let mut config = wikify_rag::RagConfig::default();
+        config.llm.provider = "Anthropic".to_string();
+        config.embeddings.provider = "my-internal-gateway".to_string();
+        assert_eq!(
+            TelemetryEvent::providers(&config),
+            TelemetryEvent::ProvidersConfigured {
+                llm_provider: "anthropic",
+                embedding_provider: "other",
+            }
+        );
+    }
+
+    #[test]
+    fn test_report_holds_only_coarse_fields() {
+        let event = TelemetryEvent::repository_indexed(1_234, 56_789);
+        let report = Report {
+            event: &event,
+            version: "0.1.0",
+            os: "linux",
+        };
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "event": "repository_indexed",
+                "documents": "1k-10k",
+                "chunks": "10k-100k",
+                "version": "0.1.0",
+                "os": "linux",
+            })
+        );
+    }
+}
@@ -0,0 +1,345 @@
+//! Usage metering export
+//!
+//! Writes usage records to CSV or JSON Lines files, or pushes them to an
+//! external metering webhook, on a schedule. Each exporter remembers up to
+//! when it has exported in a cursor file, so every record is exported once
+//! even across restarts.
+
+use super::storage::UsageStorage;
+use super::types::{UsageFilter, UsageRecord};
+use crate::{ApplicationError, ApplicationResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Records are only exported once they are this old, so records saved
+/// while an export runs are picked up by the next one
+const EXPORT_DELAY_SECS: i64 = 60;
+
+/// File format of exported usage records
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UsageExportFormat {
+    /// Comma separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl UsageExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            UsageExportFormat::Csv => "csv",
+            UsageExportFormat::Jsonl => "jsonl",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            UsageExportFormat::Csv => "text/csv; charset=utf-8",
+            UsageExportFormat::Jsonl => "application/x-ndjson",
+        }
+    }
+
+    /// Render records in this format
+    pub fn render(&self, records: &[UsageRecord]) -> ApplicationResult<String> {
+        match self {
+            UsageExportFormat::Csv => {
+                let mut output = String::from(
+                    "id,timestamp,user_id,repository_id,kind,model,input_tokens,output_tokens,estimated_cost\n",
+                );
+                for record in records {
+                    let fields = [
+                        record.id.clone(),
+                        record.timestamp.to_rfc3339(),
+                        record.user_id.clone().unwrap_or_default(),
+                        record.repository_id.clone().unwrap_or_default(),
+                        record.kind.to_string(),
+                        record.model.clone(),
+                        record.input_tokens.to_string(),
+                        record.output_tokens.to_string(),
+                        format!("{:.6}", record.estimated_cost),
+                    ];
+                    let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+                    output.push_str(&line.join(","));
+                    output.push('\n');
+                }
+                Ok(output)
+            }
+            UsageExportFormat::Jsonl => {
+                let mut output = String::new();
+                for record in records {
+                    output.push_str(
+                        &serde_json::to_string(record).map_err(ApplicationError::Serialization)?,
+                    );
+                    output.push('\n');
+                }
+                Ok(output)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for UsageExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+impl std::str::FromStr for UsageExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(UsageExportFormat::Csv),
+            "jsonl" | "ndjson" => Ok(UsageExportFormat::Jsonl),
+            _ => Err(format!("Unknown usage export format: {}", s)),
+        }
+    }
+}
+
+/// Quote a CSV field if needed
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Where exported usage records go
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsageExportTarget {
+    /// One file per export in this directory
+    Directory(PathBuf),
+    /// POST each export to this URL
+    Webhook {
+        url: String,
+        /// Sent as a bearer token, if set
+        token: Option<String>,
+    },
+}
+
+impl UsageExportTarget {
+    /// Short name of the target kind, used to name its cursor file
+    pub fn kind(&self) -> &'static str {
+        match self {
+            UsageExportTarget::Directory(_) => "directory",
+            UsageExportTarget::Webhook { .. } => "webhook",
+        }
+    }
+}
+
+/// Scheduled usage export configuration
+#[derive(Debug, Clone)]
+pub struct UsageExportConfig {
+    pub format: UsageExportFormat,
+    pub target: UsageExportTarget,
+    /// Time between exports
+    pub interval: Duration,
+}
+
+/// Exports new usage records to one target on a schedule
+pub struct UsageExporter {
+    storage: Arc<dyn UsageStorage>,
+    config: UsageExportConfig,
+    /// File holding the end of the last exported window
+    cursor_path: PathBuf,
+    client: reqwest::Client,
+}
+
+impl UsageExporter {
+    pub fn new(
+        storage: Arc<dyn UsageStorage>,
+        config: UsageExportConfig,
+        cursor_path: PathBuf,
+    ) -> Self {
+        Self {
+            storage,
+            config,
+            cursor_path,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Run exports every interval until the task is dropped
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            info!(
+                "Exporting usage as {} to {} every {:?}",
+                self.config.format,
+                self.config.target.kind(),
+                self.config.interval
+            );
+            let mut interval = tokio::time::interval(self.config.interval);
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.export_pending().await {
+                    // The cursor is unchanged, so the next run retries the same records
+                    warn!(
+                        "Usage export to {} failed: {}",
+                        self.config.target.kind(),
+                        e
+                    );
+                }
+            }
+        })
+    }
+
+    /// Export the records saved since the last export, returning how many
+    /// were exported
+    pub async fn export_pending(&self) -> ApplicationResult<usize> {
+        let since = self.load_cursor().await;
+        let until = Utc::now() - chrono::Duration::seconds(EXPORT_DELAY_SECS);
+        if since.is_some_and(|since| since >= until) {
+            return Ok(0);
+        }
+
+        let records = self
+            .storage
+            .list(&UsageFilter {
+                since,
+                until: Some(until),
+                ..UsageFilter::default()
+            })
+            .await?;
+        if !records.is_empty() {
+            self.deliver(&records, since, until).await?;
+        }
+        self.save_cursor(until).await?;
+
+        debug!("Exported {} usage records", records.len());
+        Ok(records.len())
+    }
+
+    async fn deliver(
+        &self,
+        records: &[UsageRecord],
+        since: Option<DateTime<Utc>>,
+        until: DateTime<Utc>,
+    ) -> ApplicationResult<()> {
+        let body = self.config.format.render(records)?;
+        match &self.config.target {
+            UsageExportTarget::Directory(dir) => {
+                tokio::fs::create_dir_all(dir).await?;
+                let since = since
+                    .map(|since| since.format("%Y%m%dT%H%M%SZ").to_string())
+                    .unwrap_or_else(|| "start".to_string());
+                let file_name = format!(
+                    "usage-{}-{}.{}",
+                    since,
+                    until.format("%Y%m%dT%H%M%SZ"),
+                    self.config.format.extension()
+                );
+                tokio::fs::write(dir.join(file_name), body).await?;
+            }
+            UsageExportTarget::Webhook { url, token } => {
+                let mut request = self
+                    .client
+                    .post(url)
+                    .header(
+                        reqwest::header::CONTENT_TYPE,
+                        self.config.format.content_type(),
+                    )
+                    .header("X-Wikify-Usage-Until", until.to_rfc3339())
+                    .body(body);
+                if let Some(since) = since {
+                    request = request.header("X-Wikify-Usage-Since", since.to_rfc3339());
+                }
+                if let Some(token) = token {
+                    request = request.bearer_auth(token);
+                }
+
+                let response = request.send().await.map_err(|e| {
+                    ApplicationError::internal_with_source(
+                        "Failed to send usage export",
+                        Box::new(e),
+                    )
+                })?;
+                if !response.status().is_success() {
+                    return Err(ApplicationError::internal(format!(
+                        "Metering webhook returned {}",
+                        response.status()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn load_cursor(&self) -> Option<DateTime<Utc>> {
+        let content = tokio::fs::read_to_string(&self.cursor_path).await.ok()?;
+        DateTime::parse_from_rfc3339(content.trim())
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+    }
+
+    async fn save_cursor(&self, until: DateTime<Utc>) -> ApplicationResult<()> {
+        if let Some(parent) = self.cursor_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&self.cursor_path, until.to_rfc3339()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::{MemoryUsageStorage, UsageConfig, UsageKind};
+
+    #[test]
+    fn test_csv_fields_are_quoted() {
+        let mut record = UsageRecord::new(
+            &UsageConfig::default(),
+            UsageKind::Generation,
+            "my,model",
+            10,
+            5,
+        );
+        record.user_id = Some("say \"hi\"".to_string());
+
+        let csv = UsageExportFormat::Csv.render(&[record]).unwrap();
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.contains(",\"say \"\"hi\"\"\","));
+        assert!(row.contains(",generation,\"my,model\",10,5,"));
+    }
+
+    #[tokio::test]
+    async fn test_directory_export_exports_each_record_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = Arc::new(MemoryUsageStorage::new());
+        let mut record = UsageRecord::new(
+            &UsageConfig::default(),
+            UsageKind::Embedding,
+            "text-embedding-3-small",
+            100,
+            0,
+        );
+        record.timestamp = Utc::now() - chrono::Duration::minutes(5);
+        storage.record(&record).await.unwrap();
+
+        let exporter = UsageExporter::new(
+            storage,
+            UsageExportConfig {
+                format: UsageExportFormat::Jsonl,
+                target: UsageExportTarget::Directory(temp.path().join("exports")),
+                interval: Duration::from_secs(3600),
+            },
+            temp.path().join("cursor"),
+        );
+
+        assert_eq!(exporter.export_pending().await.unwrap(), 1);
+        assert_eq!(exporter.export_pending().await.unwrap(), 0);
+
+        let files: Vec<_> = std::fs::read_dir(temp.path().join("exports"))
+            .unwrap()
+            .collect();
+        assert_eq!(files.len(), 1);
+    }
+}
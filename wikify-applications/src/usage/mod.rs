@@ -0,0 +1,166 @@
+//! Token usage and cost tracking
+//!
+//! Every embedding and generation call is recorded with its token counts
+//! and an estimated cost, so administrators can see spend per user,
+//! repository and day and be warned when a daily budget is exceeded.
+//! Records can also be exported on a schedule for chargeback.
+
+pub mod export;
+pub mod storage;
+pub mod types;
+
+pub use export::*;
+pub use storage::*;
+pub use types::*;
+
+use crate::ApplicationResult;
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Records usage and checks it against the configured budgets
+pub struct UsageTracker {
+    storage: Arc<dyn UsageStorage>,
+    config: UsageConfig,
+}
+
+impl UsageTracker {
+    pub fn new(storage: Arc<dyn UsageStorage>, config: UsageConfig) -> Self {
+        Self { storage, config }
+    }
+
+    pub fn config(&self) -> &UsageConfig {
+        &self.config
+    }
+
+    /// Save a record, logging a warning for each budget it pushes over
+    pub async fn record(&self, record: UsageRecord) -> ApplicationResult<Vec<BudgetWarning>> {
+        self.storage.record(&record).await?;
+
+        let warnings: Vec<BudgetWarning> = self
+            .budget_warnings()
+            .await?
+            .into_iter()
+            .filter(|warning| match warning.scope.as_str() {
+                "user" => warning.subject == record.user_id,
+                "repository" => warning.subject == record.repository_id,
+                _ => true,
+            })
+            // Only warn when this record crossed the budget, not on every call after it
+            .filter(|warning| warning.spent - record.estimated_cost <= warning.budget)
+            .collect();
+
+        for warning in &warnings {
+            warn!("{}", warning);
+        }
+        Ok(warnings)
+    }
+
+    /// Budgets exceeded today
+    pub async fn budget_warnings(&self) -> ApplicationResult<Vec<BudgetWarning>> {
+        let records = self
+            .storage
+            .list(&UsageFilter::day(Utc::now().date_naive()))
+            .await?;
+        let mut warnings = Vec::new();
+
+        if let Some(budget) = self.config.daily_budget {
+            let spent = UsageAggregate::total(&records).estimated_cost;
+            if spent > budget {
+                warnings.push(BudgetWarning {
+                    scope: "global".to_string(),
+                    subject: None,
+                    spent,
+                    budget,
+                });
+            }
+        }
+
+        let scopes = [
+            ("user", self.config.user_daily_budget, UsageGroupBy::User),
+            (
+                "repository",
+                self.config.repository_daily_budget,
+                UsageGroupBy::Repository,
+            ),
+        ];
+        for (scope, budget, group_by) in scopes {
+            let Some(budget) = budget else { continue };
+            // Anonymous usage only counts towards the global budget
+            let attributed: Vec<UsageRecord> = records
+                .iter()
+                .filter(|record| match group_by {
+                    UsageGroupBy::User => record.user_id.is_some(),
+                    _ => record.repository_id.is_some(),
+                })
+                .cloned()
+                .collect();
+
+            for group in UsageAggregate::group(&attributed, group_by) {
+                if group.estimated_cost > budget {
+                    warnings.push(BudgetWarning {
+                        scope: scope.to_string(),
+                        subject: Some(group.key),
+                        spent: group.estimated_cost,
+                        budget,
+                    });
+                }
+            }
+        }
+
+        Ok(warnings)
+    }
+
+    /// Usage records matching a filter, oldest first
+    pub async fn records(&self, filter: &UsageFilter) -> ApplicationResult<Vec<UsageRecord>> {
+        self.storage.list(filter).await
+    }
+
+    /// Usage matching a filter, grouped for a report
+    pub async fn report(
+        &self,
+        filter: &UsageFilter,
+        group_by: UsageGroupBy,
+    ) -> ApplicationResult<UsageReport> {
+        let records = self.storage.list(filter).await?;
+        Ok(UsageReport {
+            group_by,
+            total: UsageAggregate::total(&records),
+            groups: UsageAggregate::group(&records, group_by),
+            warnings: self.budget_warnings().await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_budget_warning_is_raised_once() {
+        let config = UsageConfig {
+            user_daily_budget: Some(1.0),
+            ..UsageConfig::default()
+        };
+        let tracker = UsageTracker::new(Arc::new(MemoryUsageStorage::new()), config.clone());
+        let call = || {
+            UsageRecord::new(&config, UsageKind::Generation, "gpt-4o", 300_000, 0)
+                .for_subject(Some("alice".to_string()), Some("repo-1".to_string()))
+        };
+
+        // $0.75 each: the second call crosses the budget, the third is already over it
+        assert!(tracker.record(call()).await.unwrap().is_empty());
+        let warnings = tracker.record(call()).await.unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].subject.as_deref(), Some("alice"));
+        assert!(tracker.record(call()).await.unwrap().is_empty());
+
+        let report = tracker
+            .report(&UsageFilter::default(), UsageGroupBy::User)
+            .await
+            .unwrap();
+        assert_eq!(report.total.calls, 3);
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.warnings.len(), 1);
+    }
+}
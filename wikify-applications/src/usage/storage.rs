@@ -0,0 +1,194 @@
+//! Usage storage backends
+//!
+//! Usage records are kept in memory by default, or in SQLite so reports
+//! cover usage from before a restart.
+
+use super::types::{UsageFilter, UsageRecord};
+#[cfg(feature = "sqlite")]
+use crate::ApplicationError;
+use crate::ApplicationResult;
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+/// Usage storage trait for different persistence backends
+#[async_trait]
+pub trait UsageStorage: Send + Sync {
+    /// Save a usage record
+    async fn record(&self, record: &UsageRecord) -> ApplicationResult<()>;
+
+    /// List usage records matching a filter, oldest first
+    async fn list(&self, filter: &UsageFilter) -> ApplicationResult<Vec<UsageRecord>>;
+}
+
+/// In-memory usage storage (default implementation)
+#[derive(Debug, Default)]
+pub struct MemoryUsageStorage {
+    records: RwLock<Vec<UsageRecord>>,
+}
+
+impl MemoryUsageStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageStorage for MemoryUsageStorage {
+    async fn record(&self, record: &UsageRecord) -> ApplicationResult<()> {
+        self.records.write().await.push(record.clone());
+        Ok(())
+    }
+
+    async fn list(&self, filter: &UsageFilter) -> ApplicationResult<Vec<UsageRecord>> {
+        Ok(self
+            .records
+            .read()
+            .await
+            .iter()
+            .filter(|record| filter.matches(record))
+            .cloned()
+            .collect())
+    }
+}
+
+/// SQLite usage storage
+#[cfg(feature = "sqlite")]
+pub struct SqliteUsageStorage {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteUsageStorage {
+    /// Create the storage on an existing pool, creating its table if needed
+    pub async fn new(pool: sqlx::SqlitePool) -> ApplicationResult<Self> {
+        let storage = Self { pool };
+        storage.create_tables().await?;
+        Ok(storage)
+    }
+
+    /// Create from database URL
+    pub async fn from_url(database_url: &str) -> ApplicationResult<Self> {
+        let pool = sqlx::SqlitePool::connect(database_url)
+            .await
+            .map_err(|e| Self::db_error("Failed to connect to SQLite database", e))?;
+        Self::new(pool).await
+    }
+
+    /// Create database tables
+    async fn create_tables(&self) -> ApplicationResult<()> {
+        let query = r#"
+            CREATE TABLE IF NOT EXISTS usage_records (
+                id TEXT PRIMARY KEY,
+                user_id TEXT,
+                repository_id TEXT,
+                kind TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                estimated_cost REAL NOT NULL,
+                timestamp TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_usage_records_timestamp ON usage_records(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_usage_records_user ON usage_records(user_id);
+            CREATE INDEX IF NOT EXISTS idx_usage_records_repository ON usage_records(repository_id);
+        "#;
+
+        sqlx::query(query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to create usage table", e))?;
+
+        Ok(())
+    }
+
+    fn db_error(message: &str, error: sqlx::Error) -> ApplicationError {
+        ApplicationError::internal_with_source(message, Box::new(error))
+    }
+
+    /// Fixed-width UTC timestamps, so they can be compared as text
+    fn format_time(time: &chrono::DateTime<chrono::Utc>) -> String {
+        time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+    }
+
+    fn row_to_record(row: &sqlx::sqlite::SqliteRow) -> ApplicationResult<UsageRecord> {
+        use sqlx::Row;
+
+        let read = |e| Self::db_error("Failed to read usage record", e);
+        let kind: String = row.try_get("kind").map_err(read)?;
+        let timestamp: String = row.try_get("timestamp").map_err(read)?;
+        let input_tokens: i64 = row.try_get("input_tokens").map_err(read)?;
+        let output_tokens: i64 = row.try_get("output_tokens").map_err(read)?;
+
+        Ok(UsageRecord {
+            id: row.try_get("id").map_err(read)?,
+            user_id: row.try_get("user_id").map_err(read)?,
+            repository_id: row.try_get("repository_id").map_err(read)?,
+            kind: kind.parse().map_err(ApplicationError::internal)?,
+            model: row.try_get("model").map_err(read)?,
+            input_tokens: input_tokens as u64,
+            output_tokens: output_tokens as u64,
+            estimated_cost: row.try_get("estimated_cost").map_err(read)?,
+            timestamp: chrono::DateTime::parse_from_rfc3339(&timestamp)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .map_err(|e| {
+                    ApplicationError::internal(format!("Invalid timestamp {}: {}", timestamp, e))
+                })?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl UsageStorage for SqliteUsageStorage {
+    async fn record(&self, record: &UsageRecord) -> ApplicationResult<()> {
+        sqlx::query(
+            "INSERT INTO usage_records (id, user_id, repository_id, kind, model, input_tokens, output_tokens, estimated_cost, timestamp) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&record.id)
+        .bind(&record.user_id)
+        .bind(&record.repository_id)
+        .bind(record.kind.to_string())
+        .bind(&record.model)
+        .bind(record.input_tokens as i64)
+        .bind(record.output_tokens as i64)
+        .bind(record.estimated_cost)
+        .bind(Self::format_time(&record.timestamp))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to save usage record", e))?;
+
+        Ok(())
+    }
+
+    async fn list(&self, filter: &UsageFilter) -> ApplicationResult<Vec<UsageRecord>> {
+        let mut builder = sqlx::QueryBuilder::new("SELECT * FROM usage_records WHERE 1 = 1");
+        if let Some(user_id) = &filter.user_id {
+            builder.push(" AND user_id = ").push_bind(user_id);
+        }
+        if let Some(repository_id) = &filter.repository_id {
+            builder
+                .push(" AND repository_id = ")
+                .push_bind(repository_id);
+        }
+        if let Some(since) = &filter.since {
+            builder
+                .push(" AND timestamp >= ")
+                .push_bind(Self::format_time(since));
+        }
+        if let Some(until) = &filter.until {
+            builder
+                .push(" AND timestamp < ")
+                .push_bind(Self::format_time(until));
+        }
+        builder.push(" ORDER BY timestamp ASC");
+
+        let rows = builder
+            .build()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to list usage records", e))?;
+
+        rows.iter().map(Self::row_to_record).collect()
+    }
+}
@@ -0,0 +1,393 @@
+//! Usage tracking types
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Kind of model call
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    /// Embedding of a question
+    Embedding,
+    /// Embedding of repository documents by an indexing job
+    Indexing,
+    /// Answer generation
+    Generation,
+}
+
+impl std::fmt::Display for UsageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UsageKind::Embedding => write!(f, "embedding"),
+            UsageKind::Indexing => write!(f, "indexing"),
+            UsageKind::Generation => write!(f, "generation"),
+        }
+    }
+}
+
+impl std::str::FromStr for UsageKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "embedding" => Ok(UsageKind::Embedding),
+            "indexing" => Ok(UsageKind::Indexing),
+            "generation" => Ok(UsageKind::Generation),
+            _ => Err(format!("Unknown usage kind: {}", s)),
+        }
+    }
+}
+
+/// Token usage and estimated cost of one model call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub id: String,
+    /// User the call was made for (None for anonymous users and background work)
+    pub user_id: Option<String>,
+    pub repository_id: Option<String>,
+    pub kind: UsageKind,
+    /// Model name, without provider prefix
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated cost in US dollars
+    pub estimated_cost: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl UsageRecord {
+    /// Create a record, estimating its cost with the configured prices
+    pub fn new(
+        config: &UsageConfig,
+        kind: UsageKind,
+        model: &str,
+        input_tokens: u64,
+        output_tokens: u64,
+    ) -> Self {
+        let model = UsageConfig::model_name(model).to_string();
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            user_id: None,
+            repository_id: None,
+            kind,
+            estimated_cost: config.estimate_cost(&model, input_tokens, output_tokens),
+            model,
+            input_tokens,
+            output_tokens,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Attribute the record to a user and repository
+    pub fn for_subject(mut self, user_id: Option<String>, repository_id: Option<String>) -> Self {
+        self.user_id = user_id;
+        self.repository_id = repository_id;
+        self
+    }
+}
+
+/// Price of a model in US dollars per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    pub const fn new(input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            input_per_million,
+            output_per_million,
+        }
+    }
+}
+
+/// Model prices and daily budgets
+///
+/// Budgets are in US dollars per UTC day. Exceeding one logs a warning
+/// and is reported by the usage report; requests are not blocked.
+#[derive(Debug, Clone)]
+pub struct UsageConfig {
+    /// Prices keyed by model name; models are matched by longest prefix
+    pub prices: HashMap<String, ModelPrice>,
+    /// Budget for all usage
+    pub daily_budget: Option<f64>,
+    /// Budget for each user
+    pub user_daily_budget: Option<f64>,
+    /// Budget for each repository
+    pub repository_daily_budget: Option<f64>,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        let prices = [
+            ("gpt-4o-mini", ModelPrice::new(0.15, 0.6)),
+            ("gpt-4o", ModelPrice::new(2.5, 10.0)),
+            ("gpt-4.1-mini", ModelPrice::new(0.4, 1.6)),
+            ("gpt-4.1", ModelPrice::new(2.0, 8.0)),
+            ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0)),
+            ("claude-3-haiku", ModelPrice::new(0.25, 1.25)),
+            ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0)),
+            ("claude-sonnet-4", ModelPrice::new(3.0, 15.0)),
+            ("text-embedding-3-small", ModelPrice::new(0.02, 0.0)),
+            ("text-embedding-3-large", ModelPrice::new(0.13, 0.0)),
+            ("text-embedding-ada-002", ModelPrice::new(0.1, 0.0)),
+        ]
+        .into_iter()
+        .map(|(model, price)| (model.to_string(), price))
+        .collect();
+
+        Self {
+            prices,
+            daily_budget: None,
+            user_daily_budget: None,
+            repository_daily_budget: None,
+        }
+    }
+}
+
+impl UsageConfig {
+    /// Strip the provider prefix and settings from a model description such
+    /// as `openai/gpt-4o-mini (temp: 0.7)`
+    pub fn model_name(model: &str) -> &str {
+        let model = model.split(" (").next().unwrap_or(model).trim();
+        model.rsplit('/').next().unwrap_or(model)
+    }
+
+    /// Price of a model, matching the longest configured prefix
+    ///
+    /// Unknown models, such as local Ollama models, have no price.
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        let model = Self::model_name(model);
+        self.prices
+            .iter()
+            .filter(|(name, _)| model.starts_with(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, price)| *price)
+    }
+
+    /// Estimated cost in US dollars of a call
+    pub fn estimate_cost(&self, model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+        self.price(model).map_or(0.0, |price| {
+            (input_tokens as f64 * price.input_per_million
+                + output_tokens as f64 * price.output_per_million)
+                / 1_000_000.0
+        })
+    }
+}
+
+/// Filters for usage records
+#[derive(Debug, Clone, Default)]
+pub struct UsageFilter {
+    pub user_id: Option<String>,
+    pub repository_id: Option<String>,
+    /// Only include records at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only include records before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl UsageFilter {
+    /// Whether a record matches the filter
+    pub fn matches(&self, record: &UsageRecord) -> bool {
+        self.user_id
+            .as_ref()
+            .is_none_or(|user_id| record.user_id.as_ref() == Some(user_id))
+            && self
+                .repository_id
+                .as_ref()
+                .is_none_or(|repository_id| record.repository_id.as_ref() == Some(repository_id))
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp < until)
+    }
+
+    /// Records of one UTC day
+    pub fn day(date: NaiveDate) -> Self {
+        let since = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        Self {
+            since: Some(since),
+            until: Some(since + chrono::Duration::days(1)),
+            ..Self::default()
+        }
+    }
+}
+
+/// How usage is grouped in a report
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    User,
+    Repository,
+    /// UTC day, formatted as `YYYY-MM-DD`
+    #[default]
+    Day,
+}
+
+/// Usage totals of a group of records
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UsageAggregate {
+    /// User ID, repository ID or day; `anonymous` and `none` stand for
+    /// records without a user or repository
+    pub key: String,
+    /// Number of model calls
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Input tokens of question and indexing embeddings
+    pub embedding_tokens: u64,
+    /// Estimated cost in US dollars
+    pub estimated_cost: f64,
+}
+
+impl UsageAggregate {
+    fn add(&mut self, record: &UsageRecord) {
+        self.calls += 1;
+        self.input_tokens += record.input_tokens;
+        self.output_tokens += record.output_tokens;
+        if record.kind != UsageKind::Generation {
+            self.embedding_tokens += record.input_tokens;
+        }
+        self.estimated_cost += record.estimated_cost;
+    }
+
+    /// Totals of all records
+    pub fn total(records: &[UsageRecord]) -> Self {
+        let mut total = Self {
+            key: "total".to_string(),
+            ..Self::default()
+        };
+        records.iter().for_each(|record| total.add(record));
+        total
+    }
+
+    /// Totals per group, sorted by key
+    pub fn group(records: &[UsageRecord], group_by: UsageGroupBy) -> Vec<Self> {
+        let mut groups: HashMap<String, Self> = HashMap::new();
+        for record in records {
+            let key = match group_by {
+                UsageGroupBy::User => record
+                    .user_id
+                    .clone()
+                    .unwrap_or_else(|| "anonymous".to_string()),
+                UsageGroupBy::Repository => record
+                    .repository_id
+                    .clone()
+                    .unwrap_or_else(|| "none".to_string()),
+                UsageGroupBy::Day => record.timestamp.format("%Y-%m-%d").to_string(),
+            };
+            groups
+                .entry(key.clone())
+                .or_insert_with(|| Self {
+                    key,
+                    ..Self::default()
+                })
+                .add(record);
+        }
+
+        let mut groups: Vec<Self> = groups.into_values().collect();
+        groups.sort_by(|a, b| a.key.cmp(&b.key));
+        groups
+    }
+}
+
+/// A daily budget that has been exceeded
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BudgetWarning {
+    /// `global`, `user` or `repository`
+    pub scope: String,
+    /// User or repository ID (None for the global budget)
+    pub subject: Option<String>,
+    /// Estimated spend today in US dollars
+    pub spent: f64,
+    /// Daily budget in US dollars
+    pub budget: f64,
+}
+
+impl std::fmt::Display for BudgetWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.subject {
+            Some(subject) => write!(
+                f,
+                "Daily {} budget exceeded for {}: ${:.4} of ${:.4}",
+                self.scope, subject, self.spent, self.budget
+            ),
+            None => write!(
+                f,
+                "Daily {} budget exceeded: ${:.4} of ${:.4}",
+                self.scope, self.spent, self.budget
+            ),
+        }
+    }
+}
+
+/// Usage report for administrators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UsageReport {
+    pub group_by: UsageGroupBy,
+    pub total: UsageAggregate,
+    pub groups: Vec<UsageAggregate>,
+    /// Budgets exceeded today
+    pub warnings: Vec<BudgetWarning>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_estimation_and_grouping() {
+        let config = UsageConfig::default();
+        assert_eq!(
+            UsageConfig::model_name("openai/gpt-4o-mini (temp: 0.7)"),
+            "gpt-4o-mini"
+        );
+        // The longest prefix wins, so gpt-4o-mini is not priced as gpt-4o
+        assert_eq!(
+            config
+                .price("gpt-4o-mini-2024-07-18")
+                .unwrap()
+                .input_per_million,
+            0.15
+        );
+        assert_eq!(config.estimate_cost("llama3", 1_000, 1_000), 0.0);
+
+        let generation = UsageRecord::new(
+            &config,
+            UsageKind::Generation,
+            "openai/gpt-4o (temp: 0.7)",
+            1_000_000,
+            100_000,
+        )
+        .for_subject(Some("alice".to_string()), Some("repo-1".to_string()));
+        assert!((generation.estimated_cost - 3.5).abs() < 1e-9);
+
+        let embedding = UsageRecord::new(
+            &config,
+            UsageKind::Embedding,
+            "text-embedding-3-small",
+            500,
+            0,
+        )
+        .for_subject(None, Some("repo-1".to_string()));
+
+        let records = vec![generation, embedding];
+        let total = UsageAggregate::total(&records);
+        assert_eq!(total.calls, 2);
+        assert_eq!(total.embedding_tokens, 500);
+
+        let by_user = UsageAggregate::group(&records, UsageGroupBy::User);
+        assert_eq!(by_user.len(), 2);
+        assert_eq!(by_user[0].key, "alice");
+        assert_eq!(by_user[1].key, "anonymous");
+
+        let by_repository = UsageAggregate::group(&records, UsageGroupBy::Repository);
+        assert_eq!(by_repository.len(), 1);
+        assert_eq!(by_repository[0].calls, 2);
+    }
+}
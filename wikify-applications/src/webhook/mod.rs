@@ -0,0 +1,344 @@
+//! Outbound webhooks for lifecycle events
+//!
+//! Users register URLs to be notified when a repository finishes or fails
+//! indexing, when its wiki is generated and when a research session
+//! finishes, for one repository or for all of them. Events are posted as
+//! JSON signed with the webhook's secret and retried with exponential
+//! backoff; every attempt is kept in the webhook's delivery log.
+
+pub mod storage;
+pub mod types;
+
+pub use storage::*;
+pub use types::*;
+
+use crate::repository::{IndexingStatus, IndexingUpdate};
+#[cfg(feature = "research")]
+use crate::research::{ResearchProgress, ResearchStatus};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// Header holding `sha256=` and the hex HMAC-SHA256 of the body
+pub const SIGNATURE_HEADER: &str = "X-Wikify-Signature";
+/// Header holding the event name, such as `indexing.completed`
+pub const EVENT_HEADER: &str = "X-Wikify-Event";
+/// Header holding the event ID, the same for every attempt
+pub const DELIVERY_HEADER: &str = "X-Wikify-Delivery";
+
+/// Signature of a payload as sent in the signature header
+pub fn sign_payload(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(payload.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Random secret for a webhook registered without one
+pub fn generate_secret() -> String {
+    format!("whsec_{}", uuid::Uuid::new_v4().simple())
+}
+
+/// Posts events to the webhooks that subscribed to them
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    storage: Arc<dyn WebhookStorage>,
+    config: WebhookConfig,
+    client: reqwest::Client,
+}
+
+impl WebhookDispatcher {
+    pub fn new(storage: Arc<dyn WebhookStorage>, config: WebhookConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            storage,
+            config,
+            client,
+        }
+    }
+
+    pub fn storage(&self) -> &Arc<dyn WebhookStorage> {
+        &self.storage
+    }
+
+    /// Send an event to every matching webhook in the background
+    pub async fn dispatch(&self, event: WebhookEvent) {
+        let webhooks = match self.storage.list_webhooks().await {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                warn!("Failed to list webhooks for {} event: {}", event.event, e);
+                return;
+            }
+        };
+        let payload = match serde_json::to_string(&event) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize {} event: {}", event.event, e);
+                return;
+            }
+        };
+
+        for webhook in webhooks.into_iter().filter(|w| w.matches(&event)) {
+            let dispatcher = self.clone();
+            let event = event.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                dispatcher
+                    .deliver_with_retry(&webhook, &event, &payload)
+                    .await;
+            });
+        }
+    }
+
+    /// Deliver until the webhook accepts the event or the attempts run out
+    async fn deliver_with_retry(&self, webhook: &Webhook, event: &WebhookEvent, payload: &str) {
+        let max_attempts = self.config.max_attempts.max(1);
+        let mut delay = self.config.retry_delay;
+        for attempt in 1..=max_attempts {
+            let delivery = self
+                .deliver(webhook, &event.id, event.event, payload, attempt)
+                .await;
+            if delivery.success {
+                return;
+            }
+            if attempt < max_attempts {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+        warn!(
+            "Giving up delivering {} event {} to webhook {} after {} attempts",
+            event.event, event.id, webhook.id, max_attempts
+        );
+    }
+
+    /// Post a payload to a webhook once, recording the attempt in its log
+    pub async fn deliver(
+        &self,
+        webhook: &Webhook,
+        event_id: &str,
+        event: WebhookEventKind,
+        payload: &str,
+        attempt: u32,
+    ) -> WebhookDelivery {
+        let started = Instant::now();
+        let result = self
+            .client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.as_str())
+            .header(DELIVERY_HEADER, event_id)
+            .header(SIGNATURE_HEADER, sign_payload(&webhook.secret, payload))
+            .body(payload.to_string())
+            .send()
+            .await;
+
+        let (status_code, error) = match result {
+            Ok(response) if response.status().is_success() => (Some(response.status()), None),
+            Ok(response) => (
+                Some(response.status()),
+                Some(format!("Webhook returned {}", response.status())),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let delivery = WebhookDelivery {
+            id: uuid::Uuid::new_v4().to_string(),
+            webhook_id: webhook.id.clone(),
+            event_id: event_id.to_string(),
+            event,
+            payload: payload.to_string(),
+            attempt,
+            status_code: status_code.map(|status| status.as_u16()),
+            success: error.is_none(),
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+            delivered_at: chrono::Utc::now(),
+        };
+
+        match &delivery.error {
+            None => debug!("Delivered {} event to webhook {}", event, webhook.id),
+            Some(e) => warn!(
+                "Attempt {} to deliver {} event to webhook {} failed: {}",
+                attempt, event, webhook.id, e
+            ),
+        }
+        if let Err(e) = self.storage.record_delivery(&delivery).await {
+            warn!("Failed to record webhook delivery: {}", e);
+        }
+        delivery
+    }
+
+    /// Deliver the payload of an earlier attempt once more
+    pub async fn redeliver(
+        &self,
+        webhook: &Webhook,
+        delivery: &WebhookDelivery,
+    ) -> WebhookDelivery {
+        self.deliver(
+            webhook,
+            &delivery.event_id,
+            delivery.event,
+            &delivery.payload,
+            delivery.attempt + 1,
+        )
+        .await
+    }
+
+    /// Dispatch events for finished indexing until the progress channel closes
+    pub fn spawn_indexing_listener(&self, indexing: broadcast::Receiver<IndexingUpdate>) {
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            dispatcher
+                .forward(indexing, |update| indexing_event(&update))
+                .await;
+        });
+    }
+
+    /// Dispatch events for finished research until the progress channel closes
+    #[cfg(feature = "research")]
+    pub fn spawn_research_listener(&self, research: broadcast::Receiver<ResearchProgress>) {
+        let dispatcher = self.clone();
+        tokio::spawn(async move {
+            dispatcher
+                .forward(research, |progress| research_event(&progress))
+                .await;
+        });
+    }
+
+    async fn forward<T: Clone>(
+        &self,
+        mut receiver: broadcast::Receiver<T>,
+        to_event: impl Fn(T) -> Option<WebhookEvent>,
+    ) {
+        loop {
+            match receiver.recv().await {
+                Ok(update) => {
+                    if let Some(event) = to_event(update) {
+                        self.dispatch(event).await;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Webhook listener lagged, skipped {} updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+/// Event for an indexing update that finished indexing
+fn indexing_event(update: &IndexingUpdate) -> Option<WebhookEvent> {
+    let (kind, data) = match update.status {
+        IndexingStatus::Completed => (
+            WebhookEventKind::IndexingCompleted,
+            serde_json::json!({ "message": update.message }),
+        ),
+        IndexingStatus::Failed => (
+            WebhookEventKind::IndexingFailed,
+            serde_json::json!({ "error": update.message }),
+        ),
+        _ => return None,
+    };
+    Some(WebhookEvent::new(kind, update.repository_id.clone(), data))
+}
+
+/// Event for a research update that finished the session
+#[cfg(feature = "research")]
+fn research_event(progress: &ResearchProgress) -> Option<WebhookEvent> {
+    let (status, error) = match &progress.status {
+        ResearchStatus::Completed => ("completed", None),
+        ResearchStatus::Failed(error) => ("failed", Some(error.clone())),
+        _ => return None,
+    };
+    Some(WebhookEvent::new(
+        WebhookEventKind::ResearchFinished,
+        progress.repository_id.clone(),
+        serde_json::json!({
+            "research_id": progress.id,
+            "status": status,
+            "error": error,
+            "iterations": progress.current_iteration,
+            "summary": progress.current_response,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(repository_id: Option<&str>, events: Vec<WebhookEventKind>) -> Webhook {
+        Webhook {
+            id: "hook-1".to_string(),
+            url: "http://127.0.0.1:9/hook".to_string(),
+            secret: "secret".to_string(),
+            events,
+            repository_id: repository_id.map(str::to_string),
+            owner_id: None,
+            active: true,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_webhook_matches_repository_and_events() {
+        let event = WebhookEvent::new(
+            WebhookEventKind::WikiGenerated,
+            "repo-1".to_string(),
+            serde_json::Value::Null,
+        );
+
+        assert!(webhook(None, vec![]).matches(&event));
+        assert!(webhook(Some("repo-1"), vec![WebhookEventKind::WikiGenerated]).matches(&event));
+        assert!(!webhook(Some("repo-2"), vec![]).matches(&event));
+        assert!(!webhook(None, vec![WebhookEventKind::IndexingFailed]).matches(&event));
+
+        let mut inactive = webhook(None, vec![]);
+        inactive.active = false;
+        assert!(!inactive.matches(&event));
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_logged() {
+        let storage = Arc::new(MemoryWebhookStorage::new());
+        let dispatcher = WebhookDispatcher::new(storage.clone(), WebhookConfig::default());
+        let webhook = webhook(None, vec![]);
+
+        // Nothing listens on the discard port
+        let delivery = dispatcher
+            .deliver(
+                &webhook,
+                "event-1",
+                WebhookEventKind::IndexingCompleted,
+                "{}",
+                1,
+            )
+            .await;
+        assert!(!delivery.success);
+        assert!(delivery.error.is_some());
+
+        let retried = dispatcher.redeliver(&webhook, &delivery).await;
+        assert_eq!(retried.attempt, 2);
+        assert_eq!(retried.event_id, "event-1");
+
+        let log = storage.list_deliveries(&webhook.id, 10).await.unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].id, retried.id);
+    }
+}
@@ -0,0 +1,365 @@
+//! Webhook storage backends
+//!
+//! Webhooks and their delivery logs are kept in memory by default, or in
+//! SQLite so registrations survive a restart. Only the most recent
+//! deliveries of each webhook are kept.
+
+use super::types::{Webhook, WebhookDelivery};
+#[cfg(feature = "sqlite")]
+use crate::ApplicationError;
+use crate::ApplicationResult;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Deliveries kept per webhook
+pub const DELIVERY_LOG_LIMIT: usize = 100;
+
+/// Webhook storage trait for different persistence backends
+#[async_trait]
+pub trait WebhookStorage: Send + Sync {
+    /// Save a webhook, replacing the one with the same ID
+    async fn save_webhook(&self, webhook: &Webhook) -> ApplicationResult<()>;
+
+    /// Get a webhook by ID
+    async fn get_webhook(&self, id: &str) -> ApplicationResult<Option<Webhook>>;
+
+    /// List all webhooks, oldest first
+    async fn list_webhooks(&self) -> ApplicationResult<Vec<Webhook>>;
+
+    /// Delete a webhook and its deliveries, returning whether it existed
+    async fn delete_webhook(&self, id: &str) -> ApplicationResult<bool>;
+
+    /// Save a delivery attempt, dropping the oldest beyond the log limit
+    async fn record_delivery(&self, delivery: &WebhookDelivery) -> ApplicationResult<()>;
+
+    /// Deliveries of a webhook, newest first
+    async fn list_deliveries(
+        &self,
+        webhook_id: &str,
+        limit: usize,
+    ) -> ApplicationResult<Vec<WebhookDelivery>>;
+
+    /// Get a delivery by ID
+    async fn get_delivery(&self, id: &str) -> ApplicationResult<Option<WebhookDelivery>>;
+}
+
+/// In-memory webhook storage (default implementation)
+#[derive(Debug, Default)]
+pub struct MemoryWebhookStorage {
+    webhooks: RwLock<Vec<Webhook>>,
+    /// Deliveries per webhook, oldest first
+    deliveries: RwLock<HashMap<String, Vec<WebhookDelivery>>>,
+}
+
+impl MemoryWebhookStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl WebhookStorage for MemoryWebhookStorage {
+    async fn save_webhook(&self, webhook: &Webhook) -> ApplicationResult<()> {
+        let mut webhooks = self.webhooks.write().await;
+        match webhooks.iter_mut().find(|w| w.id == webhook.id) {
+            Some(existing) => *existing = webhook.clone(),
+            None => webhooks.push(webhook.clone()),
+        }
+        Ok(())
+    }
+
+    async fn get_webhook(&self, id: &str) -> ApplicationResult<Option<Webhook>> {
+        Ok(self
+            .webhooks
+            .read()
+            .await
+            .iter()
+            .find(|webhook| webhook.id == id)
+            .cloned())
+    }
+
+    async fn list_webhooks(&self) -> ApplicationResult<Vec<Webhook>> {
+        Ok(self.webhooks.read().await.clone())
+    }
+
+    async fn delete_webhook(&self, id: &str) -> ApplicationResult<bool> {
+        let mut webhooks = self.webhooks.write().await;
+        let count = webhooks.len();
+        webhooks.retain(|webhook| webhook.id != id);
+        self.deliveries.write().await.remove(id);
+        Ok(webhooks.len() < count)
+    }
+
+    async fn record_delivery(&self, delivery: &WebhookDelivery) -> ApplicationResult<()> {
+        let mut deliveries = self.deliveries.write().await;
+        let log = deliveries.entry(delivery.webhook_id.clone()).or_default();
+        log.push(delivery.clone());
+        if log.len() > DELIVERY_LOG_LIMIT {
+            log.remove(0);
+        }
+        Ok(())
+    }
+
+    async fn list_deliveries(
+        &self,
+        webhook_id: &str,
+        limit: usize,
+    ) -> ApplicationResult<Vec<WebhookDelivery>> {
+        Ok(self
+            .deliveries
+            .read()
+            .await
+            .get(webhook_id)
+            .map(|log| log.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_delivery(&self, id: &str) -> ApplicationResult<Option<WebhookDelivery>> {
+        Ok(self
+            .deliveries
+            .read()
+            .await
+            .values()
+            .flatten()
+            .find(|delivery| delivery.id == id)
+            .cloned())
+    }
+}
+
+/// SQLite webhook storage
+#[cfg(feature = "sqlite")]
+pub struct SqliteWebhookStorage {
+    pool: sqlx::SqlitePool,
+}
+
+#[cfg(feature = "sqlite")]
+impl SqliteWebhookStorage {
+    /// Create the storage on an existing pool, creating its tables if needed
+    pub async fn new(pool: sqlx::SqlitePool) -> ApplicationResult<Self> {
+        let storage = Self { pool };
+        storage.create_tables().await?;
+        Ok(storage)
+    }
+
+    /// Create database tables
+    async fn create_tables(&self) -> ApplicationResult<()> {
+        let query = r#"
+            CREATE TABLE IF NOT EXISTS webhooks (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                events TEXT NOT NULL,
+                repository_id TEXT,
+                owner_id TEXT,
+                active INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id TEXT PRIMARY KEY,
+                webhook_id TEXT NOT NULL,
+                event_id TEXT NOT NULL,
+                event TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                attempt INTEGER NOT NULL,
+                status_code INTEGER,
+                success INTEGER NOT NULL,
+                error TEXT,
+                duration_ms INTEGER NOT NULL,
+                delivered_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_webhook
+                ON webhook_deliveries(webhook_id, delivered_at);
+        "#;
+
+        sqlx::query(query)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to create webhook tables", e))?;
+
+        Ok(())
+    }
+
+    fn db_error(message: &str, error: sqlx::Error) -> ApplicationError {
+        ApplicationError::internal_with_source(message, Box::new(error))
+    }
+
+    /// Fixed-width UTC timestamps, so they can be compared as text
+    fn format_time(time: &chrono::DateTime<chrono::Utc>) -> String {
+        time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+    }
+
+    fn parse_time(value: &str) -> ApplicationResult<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .map_err(|e| ApplicationError::internal(format!("Invalid timestamp {}: {}", value, e)))
+    }
+
+    fn row_to_webhook(row: &sqlx::sqlite::SqliteRow) -> ApplicationResult<Webhook> {
+        use sqlx::Row;
+
+        let read = |e| Self::db_error("Failed to read webhook", e);
+        let events: String = row.try_get("events").map_err(read)?;
+        let active: i64 = row.try_get("active").map_err(read)?;
+        let created_at: String = row.try_get("created_at").map_err(read)?;
+        let updated_at: String = row.try_get("updated_at").map_err(read)?;
+
+        Ok(Webhook {
+            id: row.try_get("id").map_err(read)?,
+            url: row.try_get("url").map_err(read)?,
+            secret: row.try_get("secret").map_err(read)?,
+            events: serde_json::from_str(&events)?,
+            repository_id: row.try_get("repository_id").map_err(read)?,
+            owner_id: row.try_get("owner_id").map_err(read)?,
+            active: active != 0,
+            created_at: Self::parse_time(&created_at)?,
+            updated_at: Self::parse_time(&updated_at)?,
+        })
+    }
+
+    fn row_to_delivery(row: &sqlx::sqlite::SqliteRow) -> ApplicationResult<WebhookDelivery> {
+        use sqlx::Row;
+
+        let read = |e| Self::db_error("Failed to read webhook delivery", e);
+        let event: String = row.try_get("event").map_err(read)?;
+        let attempt: i64 = row.try_get("attempt").map_err(read)?;
+        let status_code: Option<i64> = row.try_get("status_code").map_err(read)?;
+        let success: i64 = row.try_get("success").map_err(read)?;
+        let duration_ms: i64 = row.try_get("duration_ms").map_err(read)?;
+        let delivered_at: String = row.try_get("delivered_at").map_err(read)?;
+
+        Ok(WebhookDelivery {
+            id: row.try_get("id").map_err(read)?,
+            webhook_id: row.try_get("webhook_id").map_err(read)?,
+            event_id: row.try_get("event_id").map_err(read)?,
+            event: event.parse().map_err(ApplicationError::internal)?,
+            payload: row.try_get("payload").map_err(read)?,
+            attempt: attempt as u32,
+            status_code: status_code.map(|code| code as u16),
+            success: success != 0,
+            error: row.try_get("error").map_err(read)?,
+            duration_ms: duration_ms as u64,
+            delivered_at: Self::parse_time(&delivered_at)?,
+        })
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl WebhookStorage for SqliteWebhookStorage {
+    async fn save_webhook(&self, webhook: &Webhook) -> ApplicationResult<()> {
+        sqlx::query(
+            "INSERT OR REPLACE INTO webhooks (id, url, secret, events, repository_id, owner_id, active, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&webhook.id)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(serde_json::to_string(&webhook.events)?)
+        .bind(&webhook.repository_id)
+        .bind(&webhook.owner_id)
+        .bind(webhook.active as i64)
+        .bind(Self::format_time(&webhook.created_at))
+        .bind(Self::format_time(&webhook.updated_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to save webhook", e))?;
+
+        Ok(())
+    }
+
+    async fn get_webhook(&self, id: &str) -> ApplicationResult<Option<Webhook>> {
+        let row = sqlx::query("SELECT * FROM webhooks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to get webhook", e))?;
+
+        row.as_ref().map(Self::row_to_webhook).transpose()
+    }
+
+    async fn list_webhooks(&self) -> ApplicationResult<Vec<Webhook>> {
+        let rows = sqlx::query("SELECT * FROM webhooks ORDER BY created_at ASC")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to list webhooks", e))?;
+
+        rows.iter().map(Self::row_to_webhook).collect()
+    }
+
+    async fn delete_webhook(&self, id: &str) -> ApplicationResult<bool> {
+        sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to delete webhook deliveries", e))?;
+        let result = sqlx::query("DELETE FROM webhooks WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to delete webhook", e))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_delivery(&self, delivery: &WebhookDelivery) -> ApplicationResult<()> {
+        sqlx::query(
+            "INSERT INTO webhook_deliveries (id, webhook_id, event_id, event, payload, attempt, status_code, success, error, duration_ms, delivered_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&delivery.id)
+        .bind(&delivery.webhook_id)
+        .bind(&delivery.event_id)
+        .bind(delivery.event.as_str())
+        .bind(&delivery.payload)
+        .bind(delivery.attempt as i64)
+        .bind(delivery.status_code.map(|code| code as i64))
+        .bind(delivery.success as i64)
+        .bind(&delivery.error)
+        .bind(delivery.duration_ms as i64)
+        .bind(Self::format_time(&delivery.delivered_at))
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to save webhook delivery", e))?;
+
+        sqlx::query(
+            "DELETE FROM webhook_deliveries WHERE webhook_id = ? AND id NOT IN (SELECT id FROM webhook_deliveries WHERE webhook_id = ? ORDER BY delivered_at DESC LIMIT ?)",
+        )
+        .bind(&delivery.webhook_id)
+        .bind(&delivery.webhook_id)
+        .bind(DELIVERY_LOG_LIMIT as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to trim webhook deliveries", e))?;
+
+        Ok(())
+    }
+
+    async fn list_deliveries(
+        &self,
+        webhook_id: &str,
+        limit: usize,
+    ) -> ApplicationResult<Vec<WebhookDelivery>> {
+        let rows = sqlx::query(
+            "SELECT * FROM webhook_deliveries WHERE webhook_id = ? ORDER BY delivered_at DESC LIMIT ?",
+        )
+        .bind(webhook_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| Self::db_error("Failed to list webhook deliveries", e))?;
+
+        rows.iter().map(Self::row_to_delivery).collect()
+    }
+
+    async fn get_delivery(&self, id: &str) -> ApplicationResult<Option<WebhookDelivery>> {
+        let row = sqlx::query("SELECT * FROM webhook_deliveries WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| Self::db_error("Failed to get webhook delivery", e))?;
+
+        row.as_ref().map(Self::row_to_delivery).transpose()
+    }
+}
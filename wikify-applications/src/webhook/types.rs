@@ -0,0 +1,174 @@
+//! Types for outbound webhooks
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Lifecycle event a webhook can be notified of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum WebhookEventKind {
+    /// A repository finished indexing
+    #[serde(rename = "indexing.completed")]
+    IndexingCompleted,
+    /// Indexing a repository failed
+    #[serde(rename = "indexing.failed")]
+    IndexingFailed,
+    /// The wiki of a repository was generated
+    #[serde(rename = "wiki.generated")]
+    WikiGenerated,
+    /// A research session completed or failed
+    #[serde(rename = "research.finished")]
+    ResearchFinished,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventKind::IndexingCompleted => "indexing.completed",
+            WebhookEventKind::IndexingFailed => "indexing.failed",
+            WebhookEventKind::WikiGenerated => "wiki.generated",
+            WebhookEventKind::ResearchFinished => "research.finished",
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for WebhookEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "indexing.completed" => Ok(WebhookEventKind::IndexingCompleted),
+            "indexing.failed" => Ok(WebhookEventKind::IndexingFailed),
+            "wiki.generated" => Ok(WebhookEventKind::WikiGenerated),
+            "research.finished" => Ok(WebhookEventKind::ResearchFinished),
+            _ => Err(format!("Unknown webhook event: {}", s)),
+        }
+    }
+}
+
+/// A registered webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    /// URL the events are posted to
+    pub url: String,
+    /// Key the payloads are signed with
+    pub secret: String,
+    /// Events sent to the webhook, all of them when empty
+    pub events: Vec<WebhookEventKind>,
+    /// Repository whose events are sent, every repository when unset
+    pub repository_id: Option<String>,
+    /// User who registered the webhook
+    pub owner_id: Option<String>,
+    /// Inactive webhooks receive no events
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether the webhook receives an event
+    pub fn matches(&self, event: &WebhookEvent) -> bool {
+        self.active
+            && (self.events.is_empty() || self.events.contains(&event.event))
+            && self
+                .repository_id
+                .as_ref()
+                .is_none_or(|id| *id == event.repository_id)
+    }
+}
+
+/// Settings of a webhook being registered
+#[derive(Debug, Clone, Default)]
+pub struct NewWebhook {
+    pub url: String,
+    /// Generated when unset
+    pub secret: Option<String>,
+    pub events: Vec<WebhookEventKind>,
+    pub repository_id: Option<String>,
+}
+
+/// Changes to a webhook; unset fields are kept
+#[derive(Debug, Clone, Default)]
+pub struct WebhookUpdate {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub events: Option<Vec<WebhookEventKind>>,
+    pub active: Option<bool>,
+}
+
+/// Payload posted to webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookEvent {
+    /// Unique per event, shared by all deliveries of it
+    pub id: String,
+    pub event: WebhookEventKind,
+    pub repository_id: String,
+    pub timestamp: DateTime<Utc>,
+    /// Details of the event
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub data: serde_json::Value,
+}
+
+impl WebhookEvent {
+    pub fn new(event: WebhookEventKind, repository_id: String, data: serde_json::Value) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            event,
+            repository_id,
+            timestamp: Utc::now(),
+            data,
+        }
+    }
+}
+
+/// One attempt to deliver an event to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    /// ID of the delivered event
+    pub event_id: String,
+    pub event: WebhookEventKind,
+    /// Request body that was posted
+    pub payload: String,
+    /// Attempt number, starting at 1
+    pub attempt: u32,
+    /// HTTP status of the response, if one was received
+    pub status_code: Option<u16>,
+    pub success: bool,
+    /// Why the attempt failed
+    pub error: Option<String>,
+    pub duration_ms: u64,
+    pub delivered_at: DateTime<Utc>,
+}
+
+/// Delivery settings for webhooks
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Attempts per event before giving up
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubled for each further retry
+    pub retry_delay: Duration,
+    /// Time allowed for a webhook to respond
+    pub timeout: Duration,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(10),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
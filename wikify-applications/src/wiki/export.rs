@@ -0,0 +1,283 @@
+//! Export artifacts kept on disk for download
+//!
+//! PDFs and zipped HTML or Markdown sites can be large, so instead of being
+//! returned from the export request they are written to a job directory and
+//! downloaded from there afterwards. Each job directory holds the artifact
+//! and a `job.json` describing it.
+
+use crate::{ApplicationError, ApplicationResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use wikify_wiki::{ExportFormat, WikiExporter, WikiStructure};
+
+/// How long export artifacts are kept before being pruned
+pub const EXPORT_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Metadata file in each job directory
+const JOB_FILE: &str = "job.json";
+
+/// An export written to disk, ready to be downloaded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiExportJob {
+    pub id: String,
+    pub repository_id: String,
+    /// Export format, such as `pdf` or `html`
+    pub format: String,
+    /// Suggested file name for the download
+    pub file_name: String,
+    /// MIME type of the artifact
+    pub content_type: String,
+    /// Size of the artifact in bytes
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+    /// Location of the artifact
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+/// Keeps export artifacts in one directory per job
+#[derive(Debug, Clone)]
+pub struct FileWikiExportStore {
+    exports_dir: PathBuf,
+}
+
+impl FileWikiExportStore {
+    /// Create a new store (the directory is created on first export)
+    pub fn new<P: AsRef<Path>>(exports_dir: P) -> Self {
+        Self {
+            exports_dir: exports_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Export a wiki and keep the artifact; directory exports are zipped
+    pub async fn create(
+        &self,
+        repository_id: &str,
+        wiki: &WikiStructure,
+        format: ExportFormat,
+        base_name: &str,
+    ) -> ApplicationResult<WikiExportJob> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let job_dir = self.exports_dir.join(&id);
+        tokio::fs::create_dir_all(&job_dir).await?;
+
+        let result = Self::write_artifact(&job_dir, wiki, format, base_name).await;
+        let (file_name, content_type) = match result {
+            Ok(artifact) => artifact,
+            Err(e) => {
+                let _ = tokio::fs::remove_dir_all(&job_dir).await;
+                return Err(e);
+            }
+        };
+
+        let path = job_dir.join(&file_name);
+        let size = tokio::fs::metadata(&path).await?.len();
+        let job = WikiExportJob {
+            id,
+            repository_id: repository_id.to_string(),
+            format: format!("{:?}", format).to_lowercase(),
+            file_name,
+            content_type: content_type.to_string(),
+            size,
+            created_at: Utc::now(),
+            path,
+        };
+        let metadata = serde_json::to_vec_pretty(&job)?;
+        tokio::fs::write(job_dir.join(JOB_FILE), metadata).await?;
+
+        info!(
+            "Exported wiki for repository {} to {} ({} bytes)",
+            repository_id,
+            job.path.display(),
+            job.size
+        );
+        Ok(job)
+    }
+
+    /// Write the artifact into the job directory, returning its file name
+    /// and MIME type
+    async fn write_artifact(
+        job_dir: &Path,
+        wiki: &WikiStructure,
+        format: ExportFormat,
+        base_name: &str,
+    ) -> ApplicationResult<(String, &'static str)> {
+        let exporter = WikiExporter::new().map_err(|e| ApplicationError::Core(*e))?;
+
+        if format.is_single_file() {
+            let file_name = format!("{}.{}", base_name, format.file_extension());
+            exporter
+                .export(wiki, format, &job_dir.join(&file_name).to_string_lossy())
+                .await
+                .map_err(|e| ApplicationError::Core(*e))?;
+            let content_type = match format {
+                ExportFormat::Pdf => "application/pdf",
+                _ => "application/json",
+            };
+            return Ok((file_name, content_type));
+        }
+
+        let site_dir = job_dir.join("site");
+        exporter
+            .export(wiki, format, &site_dir.to_string_lossy())
+            .await
+            .map_err(|e| ApplicationError::Core(*e))?;
+
+        let file_name = format!("{}-{}.zip", base_name, format.file_extension());
+        let zip_path = job_dir.join(&file_name);
+        let source = site_dir.clone();
+        tokio::task::spawn_blocking(move || zip_directory(&source, &zip_path))
+            .await
+            .map_err(|e| ApplicationError::Internal {
+                message: "Zipping the export failed".to_string(),
+                source: Some(Box::new(e)),
+            })??;
+        tokio::fs::remove_dir_all(&site_dir).await?;
+
+        Ok((file_name, "application/zip"))
+    }
+
+    /// Look up an export job
+    pub async fn get(&self, job_id: &str) -> ApplicationResult<Option<WikiExportJob>> {
+        // Job IDs name directories, so only accept the UUIDs handed out
+        if uuid::Uuid::parse_str(job_id).is_err() {
+            return Ok(None);
+        }
+
+        let job_dir = self.exports_dir.join(job_id);
+        let metadata = match tokio::fs::read(job_dir.join(JOB_FILE)).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let mut job: WikiExportJob = serde_json::from_slice(&metadata)?;
+        job.path = job_dir.join(&job.file_name);
+        Ok(Some(job))
+    }
+
+    /// Remove the exports of a repository
+    pub async fn delete_repository(&self, repository_id: &str) -> ApplicationResult<()> {
+        for job_dir in self.job_dirs().await? {
+            let Some(id) = job_dir.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if let Some(job) = self.get(id).await? {
+                if job.repository_id == repository_id {
+                    tokio::fs::remove_dir_all(&job_dir).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove exports older than `max_age`
+    pub async fn prune(&self, max_age: Duration) -> ApplicationResult<usize> {
+        let mut removed = 0;
+        for job_dir in self.job_dirs().await? {
+            let modified = tokio::fs::metadata(&job_dir).await?.modified()?;
+            if modified.elapsed().unwrap_or_default() <= max_age {
+                continue;
+            }
+            match tokio::fs::remove_dir_all(&job_dir).await {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Failed to remove old export {}: {}", job_dir.display(), e),
+            }
+        }
+        if removed > 0 {
+            debug!("Pruned {} old wiki exports", removed);
+        }
+        Ok(removed)
+    }
+
+    async fn job_dirs(&self) -> ApplicationResult<Vec<PathBuf>> {
+        let mut entries = match tokio::fs::read_dir(&self.exports_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut dirs = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+        Ok(dirs)
+    }
+}
+
+/// Zip the files below a directory, with paths relative to it
+fn zip_directory(source: &Path, zip_path: &Path) -> ApplicationResult<()> {
+    let file = std::fs::File::create(zip_path)?;
+    let mut writer = zip::ZipWriter::new(std::io::BufWriter::new(file));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut pending = vec![source.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let mut entries = std::fs::read_dir(&dir)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|entry| entry.path());
+        for entry in entries {
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = path
+                .strip_prefix(source)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            writer.start_file(name, options).map_err(zip_error)?;
+            writer.write_all(&std::fs::read(&path)?)?;
+        }
+    }
+
+    writer.finish().map_err(zip_error)?.flush()?;
+    Ok(())
+}
+
+fn zip_error(e: zip::result::ZipError) -> ApplicationError {
+    ApplicationError::Internal {
+        message: "Failed to write export archive".to_string(),
+        source: Some(Box::new(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip_directory_keeps_relative_paths() {
+        let temp = tempfile::tempdir().unwrap();
+        let site = temp.path().join("site");
+        std::fs::create_dir_all(site.join("media")).unwrap();
+        std::fs::write(site.join("index.html"), "<h1>Wiki</h1>").unwrap();
+        std::fs::write(site.join("media/logo.png"), [0u8, 1, 2]).unwrap();
+
+        let zip_path = temp.path().join("site.zip");
+        zip_directory(&site, &zip_path).unwrap();
+
+        let archive = zip::ZipArchive::new(std::fs::File::open(&zip_path).unwrap()).unwrap();
+        let mut names: Vec<_> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["index.html", "media/logo.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_rejects_unknown_and_malformed_ids() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = FileWikiExportStore::new(temp.path().join("exports"));
+
+        assert!(store.get("../wikis").await.unwrap().is_none());
+        assert!(store
+            .get(&uuid::Uuid::new_v4().to_string())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
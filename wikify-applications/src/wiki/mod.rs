@@ -0,0 +1,22 @@
+//! Wiki generation for indexed repositories
+//!
+//! Wraps the wiki service from wikify-wiki so that the presentation layers
+//! generate wikis through the application (with permission checks) and
+//! read back the persisted result instead of regenerating it.
+
+pub mod export;
+pub mod storage;
+
+pub use export::{FileWikiExportStore, WikiExportJob, EXPORT_RETENTION};
+pub use storage::FileWikiStorage;
+
+/// A wiki exported to a single downloadable file
+#[derive(Debug, Clone)]
+pub struct ExportedWiki {
+    /// Suggested file name for the download
+    pub file_name: String,
+    /// MIME type of the file
+    pub content_type: &'static str,
+    /// File contents
+    pub data: Vec<u8>,
+}
@@ -0,0 +1,210 @@
+//! File-based storage for generated wikis
+
+use crate::{ApplicationError, ApplicationResult};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use wikify_wiki::{WikiEdits, WikiStructure, WikiTemplate};
+
+/// Stores one generated wiki structure per repository as JSON, along with
+/// the manual page edits made to it and the repository's structure template
+#[derive(Debug, Clone)]
+pub struct FileWikiStorage {
+    storage_dir: PathBuf,
+}
+
+impl FileWikiStorage {
+    /// Create new file-based storage (the directory is created on first write)
+    pub fn new<P: AsRef<Path>>(storage_dir: P) -> Self {
+        Self {
+            storage_dir: storage_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Get file path for a repository's wiki
+    fn get_wiki_path(&self, repository_id: &str) -> PathBuf {
+        self.storage_dir.join(format!("{}.json", repository_id))
+    }
+
+    /// Get file path for the manual page edits of a repository's wiki
+    fn get_edits_path(&self, repository_id: &str) -> PathBuf {
+        self.storage_dir
+            .join(format!("{}.edits.json", repository_id))
+    }
+
+    /// Get file path for the structure template of a repository's wiki
+    fn get_template_path(&self, repository_id: &str) -> PathBuf {
+        self.storage_dir
+            .join(format!("{}.template.json", repository_id))
+    }
+
+    /// Persist the wiki of a repository, replacing any previous one
+    pub async fn store(&self, repository_id: &str, wiki: &WikiStructure) -> ApplicationResult<()> {
+        let path = self.get_wiki_path(repository_id);
+        self.write_json(&path, wiki).await?;
+
+        info!(
+            "Stored wiki for repository {} at {}",
+            repository_id,
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// Load the stored wiki of a repository, if any
+    pub async fn load(&self, repository_id: &str) -> ApplicationResult<Option<WikiStructure>> {
+        let wiki = Self::read_json(&self.get_wiki_path(repository_id)).await?;
+        if wiki.is_some() {
+            debug!("Loaded stored wiki for repository {}", repository_id);
+        }
+        Ok(wiki)
+    }
+
+    /// Persist the manual page edits of a repository's wiki
+    pub async fn store_edits(
+        &self,
+        repository_id: &str,
+        edits: &WikiEdits,
+    ) -> ApplicationResult<()> {
+        self.write_json(&self.get_edits_path(repository_id), edits)
+            .await
+    }
+
+    /// Load the manual page edits of a repository's wiki (empty if none were made)
+    pub async fn load_edits(&self, repository_id: &str) -> ApplicationResult<WikiEdits> {
+        Ok(Self::read_json(&self.get_edits_path(repository_id))
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Persist the structure template used when generating a repository's wiki
+    pub async fn store_template(
+        &self,
+        repository_id: &str,
+        template: &WikiTemplate,
+    ) -> ApplicationResult<()> {
+        self.write_json(&self.get_template_path(repository_id), template)
+            .await
+    }
+
+    /// Load the structure template of a repository's wiki, if one was set
+    pub async fn load_template(
+        &self,
+        repository_id: &str,
+    ) -> ApplicationResult<Option<WikiTemplate>> {
+        Self::read_json(&self.get_template_path(repository_id)).await
+    }
+
+    /// Delete the structure template of a repository's wiki
+    pub async fn delete_template(&self, repository_id: &str) -> ApplicationResult<()> {
+        Self::remove_file(&self.get_template_path(repository_id)).await
+    }
+
+    /// Delete the stored wiki of a repository, its manual edits and its template
+    pub async fn delete(&self, repository_id: &str) -> ApplicationResult<()> {
+        for path in [
+            self.get_wiki_path(repository_id),
+            self.get_edits_path(repository_id),
+            self.get_template_path(repository_id),
+        ] {
+            Self::remove_file(&path).await?;
+        }
+        Ok(())
+    }
+
+    /// Remove a file, ignoring files that do not exist
+    async fn remove_file(path: &Path) -> ApplicationResult<()> {
+        match tokio::fs::remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(ApplicationError::Io(e)),
+        }
+    }
+
+    /// Write a value as pretty JSON
+    async fn write_json<T: serde::Serialize>(
+        &self,
+        path: &Path,
+        value: &T,
+    ) -> ApplicationResult<()> {
+        tokio::fs::create_dir_all(&self.storage_dir)
+            .await
+            .map_err(ApplicationError::Io)?;
+
+        let content =
+            serde_json::to_string_pretty(value).map_err(ApplicationError::Serialization)?;
+
+        // Write to a temporary file first so readers never see a partial file
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, content)
+            .await
+            .map_err(ApplicationError::Io)?;
+        tokio::fs::rename(&temp_path, path)
+            .await
+            .map_err(ApplicationError::Io)
+    }
+
+    /// Read a JSON file, returning `None` if it does not exist
+    async fn read_json<T: serde::de::DeserializeOwned>(
+        path: &Path,
+    ) -> ApplicationResult<Option<T>> {
+        let content = match tokio::fs::read_to_string(path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ApplicationError::Io(e)),
+        };
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(ApplicationError::Serialization)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_store_and_load_wiki() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = FileWikiStorage::new(temp.path().join("wikis"));
+
+        assert!(storage.load("repo-1").await.unwrap().is_none());
+
+        let wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/tmp/repo".to_string(),
+        );
+        storage.store("repo-1", &wiki).await.unwrap();
+
+        let loaded = storage.load("repo-1").await.unwrap().unwrap();
+        assert_eq!(loaded.id, wiki.id);
+        assert_eq!(loaded.title, "Test Wiki");
+
+        storage.delete("repo-1").await.unwrap();
+        assert!(storage.load("repo-1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_edits() {
+        let temp = tempfile::tempdir().unwrap();
+        let storage = FileWikiStorage::new(temp.path().join("wikis"));
+
+        assert!(storage.load_edits("repo-1").await.unwrap().is_empty());
+
+        let mut page = wikify_wiki::WikiPage::new(
+            "overview".to_string(),
+            "Overview".to_string(),
+            String::new(),
+        );
+        let mut edits = WikiEdits::default();
+        edits.edit_page(&mut page, None, "# Overview\nEdited\n".to_string(), None);
+        storage.store_edits("repo-1", &edits).await.unwrap();
+
+        let loaded = storage.load_edits("repo-1").await.unwrap();
+        assert_eq!(loaded.pages["overview"].content, "# Overview\nEdited\n");
+
+        storage.delete("repo-1").await.unwrap();
+        assert!(storage.load_edits("repo-1").await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,345 @@
+//! Performance benchmark
+//!
+//! `wikify bench <repo>` indexes a repository with a fresh RAG pipeline, then
+//! asks a set of questions and reports indexing throughput, retrieval and
+//! generation latency percentiles and token usage. Reports printed with
+//! `--output json` can be passed back with `--baseline` to compare releases.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+use wikify_rag::{RagPipeline, RagQuery, RagResult};
+
+/// Questions asked when no question file is given, general enough to apply
+/// to any repository
+pub const DEFAULT_QUESTIONS: &[&str] = &[
+    "What does this project do?",
+    "How is the project structured?",
+    "What is the main entry point?",
+    "How is configuration loaded?",
+    "How are errors handled?",
+    "What are the main data types?",
+    "How is the project tested?",
+    "Which external services or libraries does it depend on?",
+];
+
+/// Read questions from a file, one per line, skipping blank lines and
+/// `#` comments
+pub fn load_questions(path: &Path) -> std::io::Result<Vec<String>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Indexing throughput
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexingReport {
+    pub documents: usize,
+    pub chunks: usize,
+    pub duration_ms: u64,
+    pub docs_per_sec: f64,
+    pub chunks_per_sec: f64,
+}
+
+impl IndexingReport {
+    fn new(stats: &wikify_rag::rag_pipeline::IndexingStats, duration_ms: u64) -> Self {
+        let seconds = (duration_ms as f64 / 1000.0).max(f64::EPSILON);
+        Self {
+            documents: stats.total_documents,
+            chunks: stats.total_chunks,
+            duration_ms,
+            docs_per_sec: stats.total_documents as f64 / seconds,
+            chunks_per_sec: stats.total_chunks as f64 / seconds,
+        }
+    }
+}
+
+/// Latency percentiles, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyReport {
+    pub p50_ms: u64,
+    pub p90_ms: u64,
+    pub p99_ms: u64,
+    pub max_ms: u64,
+    pub mean_ms: f64,
+}
+
+impl LatencyReport {
+    /// Percentiles of the samples, `None` without samples
+    fn from_samples(mut samples: Vec<u64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        samples.sort_unstable();
+        Some(Self {
+            p50_ms: percentile(&samples, 50.0),
+            p90_ms: percentile(&samples, 90.0),
+            p99_ms: percentile(&samples, 99.0),
+            max_ms: samples[samples.len() - 1],
+            mean_ms: samples.iter().sum::<u64>() as f64 / samples.len() as f64,
+        })
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[u64], p: f64) -> u64 {
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Tokens sent to and generated by the models
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenReport {
+    /// Chunks sent to the embedding model while indexing
+    pub indexing_embedding: usize,
+    /// Questions sent to the embedding model
+    pub query_embedding: usize,
+    /// Prompts sent to the LLM
+    pub prompt: usize,
+    /// Tokens the LLM generated
+    pub generation: usize,
+}
+
+/// Results of a benchmark run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchReport {
+    /// Version of wikify that ran the benchmark
+    pub version: String,
+    pub repository: String,
+    pub embedding_model: String,
+    /// `None` when only retrieval was benchmarked
+    pub llm_model: Option<String>,
+    pub questions: usize,
+    pub runs: usize,
+    pub indexing: IndexingReport,
+    pub retrieval: Option<LatencyReport>,
+    pub generation: Option<LatencyReport>,
+    pub tokens: TokenReport,
+}
+
+impl BenchReport {
+    /// Metrics of this report next to those of a baseline, as
+    /// `(name, baseline, current, change in percent)`
+    pub fn compare(&self, baseline: &BenchReport) -> Vec<(&'static str, f64, f64, f64)> {
+        let latency = |report: &Option<LatencyReport>, value: fn(&LatencyReport) -> u64| {
+            report.as_ref().map(|report| value(report) as f64)
+        };
+        let metrics = [
+            (
+                "indexing docs/sec",
+                Some(baseline.indexing.docs_per_sec),
+                Some(self.indexing.docs_per_sec),
+            ),
+            (
+                "indexing chunks/sec",
+                Some(baseline.indexing.chunks_per_sec),
+                Some(self.indexing.chunks_per_sec),
+            ),
+            (
+                "retrieval p50 ms",
+                latency(&baseline.retrieval, |l| l.p50_ms),
+                latency(&self.retrieval, |l| l.p50_ms),
+            ),
+            (
+                "retrieval p90 ms",
+                latency(&baseline.retrieval, |l| l.p90_ms),
+                latency(&self.retrieval, |l| l.p90_ms),
+            ),
+            (
+                "generation p50 ms",
+                latency(&baseline.generation, |l| l.p50_ms),
+                latency(&self.generation, |l| l.p50_ms),
+            ),
+            (
+                "prompt tokens",
+                Some(baseline.tokens.prompt as f64),
+                Some(self.tokens.prompt as f64),
+            ),
+        ];
+        metrics
+            .into_iter()
+            .filter_map(|(name, before, after)| {
+                let (before, after) = (before?, after?);
+                let change = if before == 0.0 {
+                    0.0
+                } else {
+                    (after - before) / before * 100.0
+                };
+                Some((name, before, after, change))
+            })
+            .collect()
+    }
+
+    /// Print the report as text
+    pub fn print(&self) {
+        println!("\n📊 Benchmark of {}", self.repository);
+        println!(
+            "   wikify {}, embeddings {}{}",
+            self.version,
+            self.embedding_model,
+            self.llm_model
+                .as_ref()
+                .map(|model| format!(", LLM {}", model))
+                .unwrap_or_default()
+        );
+
+        println!("\n📚 Indexing:");
+        println!(
+            "   {} documents, {} chunks in {:.1}s",
+            self.indexing.documents,
+            self.indexing.chunks,
+            self.indexing.duration_ms as f64 / 1000.0
+        );
+        println!(
+            "   {:.1} docs/sec, {:.1} chunks/sec",
+            self.indexing.docs_per_sec, self.indexing.chunks_per_sec
+        );
+
+        println!(
+            "\n⏱️  Latency ({} questions × {} runs):",
+            self.questions, self.runs
+        );
+        for (name, latency) in [
+            ("retrieval", &self.retrieval),
+            ("generation", &self.generation),
+        ] {
+            if let Some(latency) = latency {
+                println!(
+                    "   {:<10} p50 {} ms, p90 {} ms, p99 {} ms, max {} ms",
+                    name, latency.p50_ms, latency.p90_ms, latency.p99_ms, latency.max_ms
+                );
+            }
+        }
+
+        println!("\n🔢 Tokens:");
+        println!(
+            "   embedding: {} indexing, {} queries",
+            self.tokens.indexing_embedding, self.tokens.query_embedding
+        );
+        if self.llm_model.is_some() {
+            println!(
+                "   LLM: {} prompt, {} generated",
+                self.tokens.prompt, self.tokens.generation
+            );
+        }
+    }
+}
+
+/// Index a repository and ask each question `runs` times
+///
+/// With `retrieval_only`, questions are only searched for, so the benchmark
+/// runs without LLM calls.
+pub async fn run(
+    pipeline: &mut RagPipeline,
+    repo: &str,
+    questions: &[String],
+    runs: usize,
+    retrieval_only: bool,
+) -> RagResult<BenchReport> {
+    let started = Instant::now();
+    let stats = pipeline.index_repository(repo).await?;
+    let indexing = IndexingReport::new(&stats, started.elapsed().as_millis() as u64);
+
+    let embedding_model = pipeline.config().embeddings.model.clone();
+    let mut tokens = TokenReport {
+        indexing_embedding: stats.embedding_tokens,
+        ..Default::default()
+    };
+    let mut retrieval = Vec::new();
+    let mut generation = Vec::new();
+    for _ in 0..runs {
+        for question in questions {
+            let query = RagQuery {
+                question: question.clone(),
+                context: None,
+                filters: None,
+                retrieval_config: None,
+                attachment: None,
+            };
+            if retrieval_only {
+                let started = Instant::now();
+                pipeline.search(&query).await?;
+                retrieval.push(started.elapsed().as_millis() as u64);
+                tokens.query_embedding +=
+                    wikify_rag::count_tokens(question, &embedding_model).unwrap_or_default();
+            } else {
+                let response = pipeline.ask(query).await?;
+                retrieval.push(response.metadata.retrieval_time_ms);
+                generation.push(response.metadata.generation_time_ms);
+                tokens.query_embedding += response.metadata.query_tokens;
+                tokens.prompt += response.metadata.prompt_tokens;
+                tokens.generation += response.metadata.generation_tokens;
+            }
+        }
+    }
+
+    Ok(BenchReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        repository: repo.to_string(),
+        llm_model: (!retrieval_only).then(|| pipeline.config().llm.model.clone()),
+        embedding_model,
+        questions: questions.len(),
+        runs,
+        indexing,
+        retrieval: LatencyReport::from_samples(retrieval),
+        generation: LatencyReport::from_samples(generation),
+        tokens,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_percentiles() {
+        let latency = LatencyReport::from_samples((1..=100).rev().collect()).unwrap();
+        assert_eq!(
+            (
+                latency.p50_ms,
+                latency.p90_ms,
+                latency.p99_ms,
+                latency.max_ms
+            ),
+            (50, 90, 99, 100)
+        );
+        assert_eq!(latency.mean_ms, 50.5);
+
+        let single = LatencyReport::from_samples(vec![7]).unwrap();
+        assert_eq!((single.p50_ms, single.p99_ms), (7, 7));
+        assert_eq!(LatencyReport::from_samples(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_reports_compare_with_a_baseline() {
+        let report = |docs_per_sec: f64, p50_ms: u64| BenchReport {
+            version: "0.1.0".to_string(),
+            repository: "./repo".to_string(),
+            embedding_model: "text-embedding-3-small".to_string(),
+            llm_model: None,
+            questions: 8,
+            runs: 1,
+            indexing: IndexingReport {
+                documents: 10,
+                chunks: 40,
+                duration_ms: 1000,
+                docs_per_sec,
+                chunks_per_sec: 40.0,
+            },
+            retrieval: LatencyReport::from_samples(vec![p50_ms]),
+            generation: None,
+            tokens: TokenReport::default(),
+        };
+
+        let changes = report(20.0, 30).compare(&report(10.0, 20));
+        assert_eq!(changes[0], ("indexing docs/sec", 10.0, 20.0, 100.0));
+        assert_eq!(changes[2], ("retrieval p50 ms", 20.0, 30.0, 50.0));
+        // Generation wasn't benchmarked
+        assert!(changes
+            .iter()
+            .all(|(name, ..)| !name.starts_with("generation")));
+    }
+}
@@ -0,0 +1,269 @@
+//! Caches and data in the wikify directories
+//!
+//! Caches can be rebuilt when they are gone: repositories are cloned again,
+//! vector indexes (which hold the chunk embeddings) are rebuilt by the next
+//! indexing run and cached wikis are generated again. `wikify cache` lists,
+//! clears and garbage collects them. `wikify data size` also counts data
+//! that can't be rebuilt, such as stored wikis and the repository database.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use wikify_repo::{
+    directory_size, plan_eviction, CloneWorkspace, GcReport, RetentionPolicy, WorkspaceEntry,
+};
+
+/// Kinds of caches
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    /// Cloned repositories
+    Clones,
+    /// Vector indexes with the embeddings of repository chunks
+    Indexes,
+    /// Cached generated wikis and their versions
+    Wikis,
+}
+
+impl CacheKind {
+    pub const ALL: [CacheKind; 3] = [CacheKind::Clones, CacheKind::Indexes, CacheKind::Wikis];
+
+    /// The given kinds, or all of them when none are given
+    pub fn or_all(kinds: Vec<CacheKind>) -> Vec<CacheKind> {
+        if kinds.is_empty() {
+            Self::ALL.to_vec()
+        } else {
+            kinds
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CacheKind::Clones => "cloned repositories",
+            CacheKind::Indexes => "vector indexes",
+            CacheKind::Wikis => "cached wikis",
+        }
+    }
+
+    /// Directories holding the caches of this kind
+    pub fn roots(self) -> Vec<PathBuf> {
+        match self {
+            CacheKind::Clones => clone_workspaces()
+                .iter()
+                .map(|workspace| workspace.root().to_path_buf())
+                .collect(),
+            CacheKind::Indexes => vec![wikify_rag::types::StorageConfig::default().base_dir],
+            CacheKind::Wikis => wikify_wiki::WikiCache::new()
+                .map(|cache| vec![cache.cache_dir().to_path_buf()])
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Cached items of this kind, one per entry of its directories
+    pub fn entries(self) -> Vec<WorkspaceEntry> {
+        if self == CacheKind::Clones {
+            // Clones record when they were last used
+            return clone_workspaces()
+                .iter()
+                .filter_map(|workspace| workspace.entries().ok())
+                .flatten()
+                .collect();
+        }
+        self.roots()
+            .iter()
+            .flat_map(|root| entries_in(root))
+            .collect()
+    }
+}
+
+/// Workspaces of the repository processor and the RAG pipeline
+fn clone_workspaces() -> [CloneWorkspace; 2] {
+    [
+        CloneWorkspace::new(crate::base_path()),
+        CloneWorkspace::new(wikify_rag::RagPipeline::clone_base_path()),
+    ]
+}
+
+fn entries_in(root: &Path) -> Vec<WorkspaceEntry> {
+    let Ok(children) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    children
+        .filter_map(|child| child.ok())
+        .map(|child| {
+            let path = child.path();
+            WorkspaceEntry {
+                size_bytes: directory_size(&path),
+                last_used: child
+                    .metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
+                path,
+            }
+        })
+        .collect()
+}
+
+/// Remove every cached item of the given kinds
+pub fn clear(kinds: &[CacheKind], dry_run: bool) -> GcReport {
+    collect_garbage(
+        kinds,
+        &RetentionPolicy {
+            max_total_size_bytes: None,
+            max_age: None,
+            max_entries: Some(0),
+        },
+        dry_run,
+    )
+}
+
+/// Remove the cached items of the given kinds a retention policy evicts
+///
+/// The policy applies to each kind on its own, so a size limit caps the
+/// clones and the indexes separately.
+pub fn collect_garbage(kinds: &[CacheKind], policy: &RetentionPolicy, dry_run: bool) -> GcReport {
+    let mut report = GcReport {
+        dry_run,
+        ..Default::default()
+    };
+    for kind in kinds {
+        let entries = kind.entries();
+        let roots = kind.roots();
+        let total_bytes: u64 = entries.iter().map(|entry| entry.size_bytes).sum();
+        let mut kind_report = GcReport {
+            scanned: entries.len(),
+            dry_run,
+            ..Default::default()
+        };
+        for index in plan_eviction(&entries, policy, &HashSet::new(), SystemTime::now()) {
+            let entry = &entries[index];
+            if !dry_run {
+                if let Err(e) = remove(&roots, &entry.path) {
+                    tracing::warn!(
+                        path = %entry.path.display(),
+                        error = %e,
+                        "Failed to remove cache entry"
+                    );
+                    continue;
+                }
+            }
+            kind_report.freed_bytes += entry.size_bytes;
+            kind_report.removed.push(entry.path.clone());
+        }
+        kind_report.remaining_bytes = total_bytes.saturating_sub(kind_report.freed_bytes);
+        report.merge(kind_report);
+    }
+    report
+}
+
+/// Remove a cached item, refusing paths outside the cache directories
+fn remove(roots: &[PathBuf], path: &Path) -> std::io::Result<()> {
+    if !roots
+        .iter()
+        .any(|root| path.starts_with(root) && path != root)
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{} is not in a cache directory", path.display()),
+        ));
+    }
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Disk usage of one kind of data
+#[derive(Debug, Serialize)]
+pub struct DataUsage {
+    pub name: &'static str,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Whether it can be rebuilt, so `wikify cache clear` removes it
+    pub cache: bool,
+}
+
+/// Disk usage of everything wikify keeps, caches first
+pub fn data_usage() -> Vec<DataUsage> {
+    let base = crate::base_path();
+    let mut usage: Vec<DataUsage> = CacheKind::ALL
+        .iter()
+        .flat_map(|kind| {
+            kind.roots().into_iter().map(move |root| DataUsage {
+                name: kind.label(),
+                size_bytes: directory_size(&root),
+                path: root,
+                cache: true,
+            })
+        })
+        .collect();
+
+    let data = [
+        ("stored wikis", base.join("wikis")),
+        ("wiki versions", base.join("wiki-versions")),
+        ("wiki exports", base.join("exports")),
+        ("uploads", base.join("uploads")),
+        ("repository database", base.join("repositories.db")),
+        (
+            "chat history",
+            wikify_rag::types::ChatConfig::default().history_dir,
+        ),
+    ];
+    usage.extend(data.into_iter().map(|(name, path)| DataUsage {
+        name,
+        size_bytes: directory_size(&path),
+        path,
+        cache: false,
+    }));
+    usage
+}
+
+/// Size in bytes as a short human-readable string
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removal_stays_inside_cache_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path().join("vector_db");
+        let index = root.join("repo_1");
+        std::fs::create_dir_all(&index).unwrap();
+        std::fs::write(index.join("vectors.json"), "[]").unwrap();
+
+        let entries = entries_in(&root);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].size_bytes, 2);
+
+        let roots = [root.clone()];
+        assert!(remove(&roots, &root).is_err());
+        assert!(remove(&roots, temp.path()).is_err());
+        remove(&roots, &index).unwrap();
+        assert!(!index.exists());
+    }
+
+    #[test]
+    fn test_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KB");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
@@ -0,0 +1,152 @@
+//! Shell completions
+//!
+//! `wikify completions <shell>` prints a script that registers `wikify` with
+//! the shell. Rather than a static list of words, the script asks `wikify`
+//! itself (with `COMPLETE=<shell>` set) for the candidates of each word, so
+//! repository arguments can also complete the repositories cloned before and
+//! the IDs of stored wikis.
+
+use clap::CommandFactory;
+use clap_complete::engine::{CompletionCandidate, PathCompleter, ValueCompleter};
+use clap_complete::env::{EnvCompleter, Shells};
+use clap_complete::{CompleteEnv, Shell};
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variable the registration script sets to ask for candidates
+const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Answer a completion request of a registered shell, exiting afterwards
+///
+/// Does nothing unless `COMPLETE` is set, so it is cheap to call first thing.
+pub fn complete_if_requested() {
+    CompleteEnv::with_factory(crate::Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Write the script registering `wikify` completions with a shell
+pub fn write_registration(shell: Shell, out: &mut dyn Write) -> std::io::Result<()> {
+    let completer = Shells::builtins()
+        .completer(&shell.to_string())
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("Completions are not supported for {}", shell),
+            )
+        })?;
+    // Completing through the running binary works before it is on the PATH
+    let binary = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| "wikify".to_string());
+    completer.write_registration(COMPLETE_VAR, "wikify", "wikify", &binary, out)
+}
+
+/// Candidates of a repository argument: cloned repositories and local directories
+pub fn repository(current: &OsStr) -> Vec<CompletionCandidate> {
+    let prefix = current.to_string_lossy();
+    let mut candidates: Vec<CompletionCandidate> = cloned_repositories(&crate::base_path())
+        .into_iter()
+        .filter(|repo| repo.starts_with(prefix.as_ref()))
+        .map(|repo| CompletionCandidate::new(repo).help(Some("cloned repository".into())))
+        .collect();
+    candidates.extend(PathCompleter::dir().complete(current));
+    candidates
+}
+
+/// Candidates of a wiki argument: IDs of stored wikis and wiki JSON files
+pub fn wiki(current: &OsStr) -> Vec<CompletionCandidate> {
+    let prefix = current.to_string_lossy();
+    let mut candidates: Vec<CompletionCandidate> = stored_wikis(&crate::base_path().join("wikis"))
+        .into_iter()
+        .filter(|id| id.starts_with(prefix.as_ref()))
+        .map(|id| CompletionCandidate::new(id).help(Some("stored wiki".into())))
+        .collect();
+    candidates.extend(PathCompleter::file().complete(current));
+    candidates
+}
+
+/// Repositories cloned into the workspace, by remote URL where it is known
+fn cloned_repositories(base_path: &Path) -> Vec<String> {
+    let Ok(entries) = wikify_repo::CloneWorkspace::new(base_path).entries() else {
+        return Vec::new();
+    };
+    let mut repositories: Vec<String> = entries
+        .into_iter()
+        .map(|entry| remote_url(&entry.path).unwrap_or_else(|| entry.path.display().to_string()))
+        .collect();
+    repositories.sort();
+    repositories
+}
+
+/// URL of the `origin` remote of a clone
+fn remote_url(clone_path: &Path) -> Option<String> {
+    let git_config = std::fs::read_to_string(clone_path.join(".git").join("config")).ok()?;
+    let mut in_origin = false;
+    for line in git_config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_origin = line == "[remote \"origin\"]";
+        } else if let Some(url) = line.strip_prefix("url").filter(|_| in_origin) {
+            return Some(url.trim_start_matches([' ', '=']).to_string());
+        }
+    }
+    None
+}
+
+/// Repository IDs of the wikis in a wiki storage directory
+fn stored_wikis(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut ids: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| PathBuf::from(entry.file_name()))
+        .filter_map(|name| {
+            let id = name.to_str()?.strip_suffix(".json")?;
+            // Edits and templates are stored next to the wikis
+            (!id.contains('.')).then(|| id.to_string())
+        })
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_wikis_and_clones_are_found() {
+        let base = tempfile::tempdir().unwrap();
+        let wikis = base.path().join("wikis");
+        std::fs::create_dir_all(&wikis).unwrap();
+        for file in [
+            "repo-1.json",
+            "repo-1.edits.json",
+            "repo-2.json",
+            "notes.txt",
+        ] {
+            std::fs::write(wikis.join(file), "{}").unwrap();
+        }
+        assert_eq!(stored_wikis(&wikis), ["repo-1", "repo-2"]);
+
+        let clone = base.path().join("repos").join("owner_repo");
+        std::fs::create_dir_all(clone.join(".git")).unwrap();
+        std::fs::write(
+            clone.join(".git").join("config"),
+            "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = https://github.com/owner/repo.git\n",
+        )
+        .unwrap();
+        let local = base.path().join("repos").join("local_copy");
+        std::fs::create_dir_all(&local).unwrap();
+
+        assert_eq!(
+            cloned_repositories(base.path()),
+            [
+                local.display().to_string(),
+                "https://github.com/owner/repo.git".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! The CLI configuration file
+//!
+//! `wikify config` manages `config.toml` in the base directory
+//! (`~/.wikify/config.toml` unless `WIKIFY_BASE_DIR` says otherwise): LLM
+//! and embedding providers and models, storage directories and the default
+//! retrieval and indexing parameters. Commands that start the application
+//! read it, then let `WIKIFY_<SECTION>_<KEY>` environment variables (like
+//! `WIKIFY_LLM_MODEL`) override single keys. API keys are not part of it,
+//! they are stored with `wikify auth set-key` or read from the environment.
+//!
+//! Keys are addressed with dots, like `llm.model` or `rag.top_k`.
+
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use wikify_applications::ApplicationConfig;
+use wikify_core::{WikifyConfig, WikifyResult};
+
+/// Path of the configuration file, unless `--config` names another one
+pub fn config_path(explicit: Option<&PathBuf>) -> PathBuf {
+    explicit
+        .cloned()
+        .unwrap_or_else(|| crate::base_path().join("config.toml"))
+}
+
+/// Configuration `wikify config init` writes
+///
+/// It describes what the application does without a configuration file, so
+/// creating one changes nothing until it is edited.
+pub fn initial_config() -> WikifyConfig {
+    ApplicationConfig::cli_local().settings()
+}
+
+/// Load a configuration file, if it exists
+///
+/// Keys the file doesn't set keep their initial values. Environment
+/// overrides are not applied, so `wikify config set` doesn't write them to
+/// the file.
+pub fn load(path: &Path) -> WikifyResult<Option<WikifyConfig>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    initial_config().merge_file(path).map(Some)
+}
+
+/// Value of a key, or the whole configuration without one
+pub fn get_value(config: &WikifyConfig, key: Option<&str>) -> Result<String, String> {
+    let Some(key) = key else {
+        return toml::to_string_pretty(config).map_err(|e| e.to_string());
+    };
+
+    let root = to_table(config)?;
+    let (section, leaf) = split_key(key);
+    let value = section_of(&root, section)
+        .and_then(|table| table.get(leaf))
+        .ok_or_else(|| unknown_key(key))?;
+    Ok(match value {
+        Value::String(s) => s.clone(),
+        // Every float in the configuration is an f32
+        Value::Float(f) => (*f as f32).to_string(),
+        Value::Table(table) => toml::to_string_pretty(table).map_err(|e| e.to_string())?,
+        other => other.to_string(),
+    })
+}
+
+/// Set a key, reading the value as the type the key has
+///
+/// Lists are given comma separated, such as `target,node_modules`.
+pub fn set_value(config: &WikifyConfig, key: &str, value: &str) -> Result<WikifyConfig, String> {
+    let mut root = to_table(config)?;
+    let (section, leaf) = split_key(key);
+    let table = section
+        .into_iter()
+        .flat_map(|section| section.split('.'))
+        .try_fold(&mut root, |table, part| {
+            table.get_mut(part).and_then(Value::as_table_mut)
+        })
+        .ok_or_else(|| unknown_key(key))?;
+
+    let invalid = |e: &dyn std::fmt::Display| format!("Invalid value for {}: {}", key, e);
+    let parsed = match table.get(leaf) {
+        Some(Value::String(_)) => Value::String(value.to_string()),
+        Some(Value::Integer(_)) => Value::Integer(value.parse().map_err(|e| invalid(&e))?),
+        Some(Value::Float(_)) => Value::Float(value.parse().map_err(|e| invalid(&e))?),
+        Some(Value::Boolean(_)) => Value::Boolean(value.parse().map_err(|e| invalid(&e))?),
+        Some(Value::Array(_)) => Value::Array(
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        Some(_) => return Err(format!("{} is a section, set one of its keys", key)),
+        // Optional settings that are not set yet
+        None => value
+            .parse::<i64>()
+            .map(Value::Integer)
+            .unwrap_or_else(|_| Value::String(value.to_string())),
+    };
+    table.insert(leaf.to_string(), parsed);
+
+    let updated: WikifyConfig = root
+        .try_into()
+        .map_err(|e: toml::de::Error| invalid(&e.message()))?;
+    // Keys serde doesn't know are dropped when deserializing
+    get_value(&updated, Some(key))?;
+    Ok(updated)
+}
+
+/// Keys of a configuration file that are not configuration settings
+pub fn unknown_keys(raw: &Table, config: &WikifyConfig) -> Vec<String> {
+    fn collect(raw: &Table, known: &Table, prefix: &str, unknown: &mut Vec<String>) {
+        for (key, value) in raw {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", prefix, key)
+            };
+            match (value, known.get(key)) {
+                (Value::Table(raw), Some(Value::Table(known))) => {
+                    collect(raw, known, &path, unknown)
+                }
+                (_, Some(_)) => {}
+                (_, None) => unknown.push(path),
+            }
+        }
+    }
+
+    let mut unknown = Vec::new();
+    if let Ok(known) = to_table(config) {
+        collect(raw, &known, "", &mut unknown);
+    }
+    unknown
+}
+
+fn to_table(config: &WikifyConfig) -> Result<Table, String> {
+    Table::try_from(config).map_err(|e| e.to_string())
+}
+
+/// Split `rag.top_k` into its section and key
+fn split_key(key: &str) -> (Option<&str>, &str) {
+    match key.rsplit_once('.') {
+        Some((section, leaf)) => (Some(section), leaf),
+        None => (None, key),
+    }
+}
+
+fn section_of<'a>(root: &'a Table, section: Option<&str>) -> Option<&'a Table> {
+    section
+        .into_iter()
+        .flat_map(|section| section.split('.'))
+        .try_fold(root, |table, part| {
+            table.get(part).and_then(Value::as_table)
+        })
+}
+
+fn unknown_key(key: &str) -> String {
+    format!(
+        "Unknown configuration key '{}', see `wikify config show` for the available keys",
+        key
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_are_read_as_the_key_type() {
+        let config = initial_config();
+        let config = set_value(&config, "rag.top_k", "5").unwrap();
+        let config = set_value(&config, "rag.enable_reranking", "true").unwrap();
+        let config = set_value(&config, "rag.similarity_threshold", "0.45").unwrap();
+        let config = set_value(&config, "llm.model", "claude-sonnet-4").unwrap();
+        let config = set_value(&config, "repository.excluded_dirs", ".git, target").unwrap();
+
+        assert_eq!(config.rag.top_k, 5);
+        assert!(config.rag.enable_reranking);
+        assert_eq!(config.rag.similarity_threshold, 0.45);
+        assert_eq!(config.llm.model, "claude-sonnet-4");
+        assert_eq!(config.repository.excluded_dirs, [".git", "target"]);
+        assert_eq!(
+            get_value(&config, Some("rag.similarity_threshold")).unwrap(),
+            "0.45"
+        );
+    }
+
+    #[test]
+    fn test_unknown_keys_and_invalid_values_are_rejected() {
+        let config = initial_config();
+        assert!(set_value(&config, "llm.modle", "gpt-4o").is_err());
+        assert!(set_value(&config, "nothing.top_k", "5").is_err());
+        assert!(set_value(&config, "rag.top_k", "many").is_err());
+        assert!(set_value(&config, "rag", "5").is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys_are_listed() {
+        let raw: Table = toml::from_str(
+            &toml::to_string(&initial_config())
+                .unwrap()
+                .replace("[rag]", "[rag]\ntopk = 3"),
+        )
+        .unwrap();
+        assert_eq!(unknown_keys(&raw, &initial_config()), ["rag.topk"]);
+    }
+
+    #[test]
+    fn test_initial_config_keeps_application_defaults() {
+        let mut app_config = ApplicationConfig::cli_local();
+        app_config.apply_settings(&initial_config());
+
+        let defaults = wikify_rag::RagConfig::default();
+        assert_eq!(app_config.rag.llm.model, defaults.llm.model);
+        assert_eq!(app_config.rag.llm.max_tokens, defaults.llm.max_tokens);
+        assert_eq!(
+            app_config.rag.embeddings.dimension,
+            defaults.embeddings.dimension
+        );
+        assert_eq!(app_config.rag.retrieval.top_k, defaults.retrieval.top_k);
+        assert_eq!(
+            app_config.storage.base_dir,
+            ApplicationConfig::cli_local().storage.base_dir
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Questions about the changes since a git ref
+//!
+//! `wikify diff-ask <repo> --since <ref>` reads the commits and changed files
+//! between `<ref>` and `HEAD` of a local checkout, restricts retrieval to the
+//! changed files and asks the LLM to explain the changes. Files are compared
+//! against the merge base, so `--since main` on a feature branch shows the
+//! changes of the branch only, as a pull request would.
+
+use serde::Serialize;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Commits listed in the prompt, the rest are summarized as a count
+const MAX_PROMPT_COMMITS: usize = 50;
+
+/// A file changed since the ref
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedFile {
+    /// `git diff --name-status` letter: A, M, D, R, ...
+    pub status: String,
+    pub path: String,
+    /// Previous path of a renamed or copied file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+}
+
+impl ChangedFile {
+    fn is_deleted(&self) -> bool {
+        self.status == "D"
+    }
+}
+
+/// Commits and files changed between a ref and `HEAD`
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeSet {
+    pub since: String,
+    /// Abbreviated SHA and subject of each commit, newest first
+    pub commits: Vec<String>,
+    pub files: Vec<ChangedFile>,
+}
+
+impl ChangeSet {
+    /// Read the changes of a local checkout since a branch, tag or commit
+    pub async fn from_git(
+        repo_path: &Path,
+        since: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        git(
+            repo_path,
+            &["rev-parse", "--verify", &format!("{}^{{commit}}", since)],
+        )
+        .await
+        .map_err(|e| format!("Unknown ref {}: {}", since, e))?;
+
+        let log = git(
+            repo_path,
+            &["log", "--format=%h %s", &format!("{}..HEAD", since)],
+        )
+        .await?;
+        let name_status = git(
+            repo_path,
+            &["diff", "--name-status", "-M", &format!("{}...HEAD", since)],
+        )
+        .await?;
+
+        Ok(Self {
+            since: since.to_string(),
+            commits: log.lines().map(str::to_string).collect(),
+            files: parse_name_status(&name_status),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Changed files that still exist, so they can be in the index
+    pub fn indexed_files(&self) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter(|file| !file.is_deleted())
+            .map(|file| file.path.as_str())
+            .collect()
+    }
+
+    /// Question asking the LLM to explain the changes, or to answer a
+    /// question about them
+    pub fn prompt(&self, question: Option<&str>) -> String {
+        let mut prompt = format!(
+            "Explain the changes made to this repository since `{}`: what they do, \
+             why they were likely made and what a reviewer should check.\n",
+            self.since
+        );
+        if let Some(question) = question {
+            prompt.push_str(&format!("Focus on this question: {}\n", question));
+        }
+
+        if !self.commits.is_empty() {
+            prompt.push_str("\nCommits:\n");
+            for commit in self.commits.iter().take(MAX_PROMPT_COMMITS) {
+                prompt.push_str(&format!("- {}\n", commit));
+            }
+            if self.commits.len() > MAX_PROMPT_COMMITS {
+                prompt.push_str(&format!(
+                    "- ... and {} older commits\n",
+                    self.commits.len() - MAX_PROMPT_COMMITS
+                ));
+            }
+        }
+
+        prompt.push_str("\nChanged files:\n");
+        for file in &self.files {
+            match &file.old_path {
+                Some(old_path) => prompt.push_str(&format!(
+                    "- {} {} -> {}\n",
+                    file.status, old_path, file.path
+                )),
+                None => prompt.push_str(&format!("- {} {}\n", file.status, file.path)),
+            }
+        }
+        prompt
+    }
+}
+
+/// Parse `git diff --name-status` output
+///
+/// Renames and copies carry a similarity score (`R087`) and both paths.
+fn parse_name_status(output: &str) -> Vec<ChangedFile> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let status = fields.next()?.trim().get(..1)?.to_string();
+            let first = fields.next()?.to_string();
+            Some(match fields.next() {
+                Some(path) => ChangedFile {
+                    status,
+                    path: path.to_string(),
+                    old_path: Some(first),
+                },
+                None => ChangedFile {
+                    status,
+                    path: first,
+                    old_path: None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Run git in a repository and return its output
+async fn git(repo_path: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_become_a_prompt() {
+        let changes = ChangeSet {
+            since: "v0.1.0".to_string(),
+            commits: vec![
+                "a1b2c3d Split the indexer".to_string(),
+                "d4e5f6a Fix retry".to_string(),
+            ],
+            files: parse_name_status(
+                "M\tsrc/lib.rs\nR087\tsrc/index.rs\tsrc/indexer.rs\nD\tsrc/old.rs\n",
+            ),
+        };
+
+        assert_eq!(changes.indexed_files(), ["src/lib.rs", "src/indexer.rs"]);
+        assert_eq!(changes.files[1].old_path.as_deref(), Some("src/index.rs"));
+
+        let prompt = changes.prompt(Some("Is the retry safe?"));
+        assert!(prompt.starts_with("Explain the changes made to this repository since `v0.1.0`"));
+        assert!(prompt.contains("Focus on this question: Is the retry safe?\n"));
+        assert!(prompt.contains("\nCommits:\n- a1b2c3d Split the indexer\n- d4e5f6a Fix retry\n"));
+        assert!(prompt.contains(
+            "\nChanged files:\n- M src/lib.rs\n- R src/index.rs -> src/indexer.rs\n- D src/old.rs\n"
+        ));
+    }
+}
@@ -1,9 +1,25 @@
 //! Clean Wikify CLI using unified application layer
 
-use clap::{Parser, Subcommand};
+mod bench;
+mod cache;
+mod completions;
+mod config;
+mod diff_ask;
+mod output;
+mod progress;
+mod remote;
+mod tui;
+mod watch;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use clap_complete::engine::ArgValueCompleter;
+use output::{OutputFormat, SourceOutput, Timings};
 use std::path::PathBuf;
+use std::time::Instant;
 use tracing::info;
 use wikify_applications::prelude::*;
+use wikify_applications::telemetry::{self, TelemetryEvent};
+use wikify_applications::{PageRequest, RepositoryFilter, RepositoryIndex, SourceCitation};
 use wikify_core::{
     init_logging, log_operation_start, log_operation_success, LoggingConfig, WikifyConfig,
     WikifyError, WikifyResult,
@@ -24,6 +40,19 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Print the results of ask, generate and research as text or JSON
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Wikify server to ask instead of indexing locally, such as
+    /// `https://wikify.example.com`
+    #[arg(long, global = true, env = "WIKIFY_SERVER")]
+    server: Option<String>,
+
+    /// API key for the server
+    #[arg(long, global = true, env = "WIKIFY_API_KEY", hide_env_values = true)]
+    api_key: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -33,20 +62,32 @@ enum Commands {
     /// Generate documentation for a repository
     Generate {
         /// Repository URL or local path
+        #[arg(add = ArgValueCompleter::new(completions::repository))]
         repo: String,
         /// GitHub token for private repositories
         #[arg(short, long)]
         token: Option<String>,
-        /// Output file path
-        #[arg(short, long)]
-        output: Option<PathBuf>,
+        /// Output format (markdown, html, mdbook, pdf)
+        #[arg(short, long, default_value = "markdown")]
+        format: wikify_wiki::ExportFormat,
+        /// Output directory
+        #[arg(short, long, visible_alias = "output")]
+        out_dir: Option<PathBuf>,
         /// Use API mode for remote repositories
         #[arg(long)]
         api_mode: bool,
+        /// Publish the generated wiki after generation
+        #[arg(long, value_enum)]
+        publish: Option<PublishTarget>,
     },
     /// Ask questions about a repository
+    ///
+    /// Text piped on stdin, such as an error log or a stack trace, is
+    /// attached to the question: `cargo test 2>&1 | wikify ask . "Why does
+    /// this test fail?"`
     Ask {
         /// Repository URL or local path
+        #[arg(add = ArgValueCompleter::new(completions::repository))]
         repo: String,
         /// Question to ask
         question: String,
@@ -74,22 +115,475 @@ enum Commands {
         /// Chunk overlap for text splitting
         #[arg(long, default_value = "200")]
         chunk_overlap: usize,
+        /// Show the cited code under each source
+        #[arg(long)]
+        show_snippets: bool,
+    },
+    /// Explain what changed in a local repository since a git ref, such as
+    /// the base branch of a pull request or the previous release tag
+    DiffAsk {
+        /// Local path of a git repository
+        #[arg(add = ArgValueCompleter::new(completions::repository))]
+        repo: String,
+        /// Question about the changes, instead of a general explanation
+        question: Option<String>,
+        /// Branch, tag or commit to compare `HEAD` with
+        #[arg(long)]
+        since: String,
+        /// Number of top results to retrieve
+        #[arg(long, default_value = "8")]
+        top_k: usize,
+        /// Show the cited code under each source
+        #[arg(long)]
+        show_snippets: bool,
+    },
+    /// Research a question about a repository over several iterations
+    #[cfg(feature = "research")]
+    Research {
+        /// Repository URL or local path
+        #[arg(add = ArgValueCompleter::new(completions::repository))]
+        repo: String,
+        /// Question to research
+        question: String,
+        /// Research template to take the configuration from
+        #[arg(long)]
+        template: Option<String>,
+        /// Maximum number of research iterations
+        #[arg(long)]
+        iterations: Option<usize>,
+        /// Report file path
+        #[arg(short, long, default_value = "./research.md")]
+        output: PathBuf,
+        /// GitHub token for private repositories
+        #[arg(short, long)]
+        token: Option<String>,
+    },
+    /// Measure indexing throughput, retrieval latency and token usage on a
+    /// repository, to compare performance across releases
+    Bench {
+        /// Repository URL or local path
+        #[arg(add = ArgValueCompleter::new(completions::repository))]
+        repo: String,
+        /// File with one question per line, instead of the standard questions
+        #[arg(long)]
+        questions: Option<PathBuf>,
+        /// Number of times each question is asked
+        #[arg(long, default_value = "1")]
+        runs: usize,
+        /// Only measure retrieval, without LLM calls
+        #[arg(long)]
+        retrieval_only: bool,
+        /// JSON report of an earlier run (`--output json`) to compare with
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// GitHub token for private repositories
+        #[arg(short, long)]
+        token: Option<String>,
+    },
+    /// Garbage collect cloned repositories (like `wikify cache gc --kind clones`)
+    Gc {
+        /// Maximum total size of all clones in megabytes
+        #[arg(long)]
+        max_size_mb: Option<u64>,
+        /// Remove clones unused for this many days
+        #[arg(long)]
+        max_age_days: Option<u64>,
+        /// Maximum number of clones to keep
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Only show what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Export a generated wiki
+    Export {
+        /// Repository ID of a stored wiki, or path to a wiki JSON file
+        #[arg(add = ArgValueCompleter::new(completions::wiki))]
+        wiki: String,
+        /// Export format (pdf, html, markdown, mdbook, json)
+        #[arg(short, long, default_value = "pdf")]
+        format: wikify_wiki::ExportFormat,
+        /// Output path (a file for pdf/json, a directory for html/markdown)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Browse repositories, indexing progress, chat and wikis in a terminal UI
+    Tui {
+        /// SQLite database of a web server to share repositories with, such
+        /// as `sqlite:./data/wikify.db`
+        #[arg(long)]
+        database: Option<String>,
+    },
+    /// Add, list, reindex and remove the repositories the CLI keeps
+    Repo {
+        /// SQLite database to keep the repositories in, instead of
+        /// `~/.wikify/repositories.db`
+        #[arg(long)]
+        database: Option<String>,
+        #[command(subcommand)]
+        action: RepoAction,
+    },
+    /// Keep the index of a local repository up to date while its files change
+    Watch {
+        /// Local repository to watch
+        #[arg(add = ArgValueCompleter::new(clap_complete::engine::PathCompleter::dir()))]
+        path: PathBuf,
+        /// Also regenerate the wiki pages built from changed files
+        #[arg(long)]
+        wiki: bool,
+        /// Milliseconds without changes to wait for before updating the index
+        #[arg(long, default_value = "500")]
+        debounce_ms: u64,
+    },
+    /// Manage the configuration file (`~/.wikify/config.toml`)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// List, clear and garbage collect clones, vector indexes and cached wikis
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Report the data wikify keeps on disk
+    Data {
+        #[command(subcommand)]
+        action: DataAction,
+    },
+    /// Store API keys and repository access tokens in the OS keyring
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Print a shell script that enables completions of wikify commands
+    Completions {
+        /// Shell to register the completions with
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+impl Commands {
+    /// Whether the command calls the LLM or embedding providers
+    fn uses_models(&self) -> bool {
+        match self {
+            #[cfg(feature = "research")]
+            Commands::Research { .. } => true,
+            Commands::Generate { .. }
+            | Commands::Ask { .. }
+            | Commands::DiffAsk { .. }
+            | Commands::Bench { .. }
+            | Commands::Watch { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Command name as reported by telemetry
+    fn name(&self) -> &'static str {
+        match self {
+            Commands::Generate { .. } => "generate",
+            Commands::Ask { .. } => "ask",
+            Commands::DiffAsk { .. } => "diff-ask",
+            #[cfg(feature = "research")]
+            Commands::Research { .. } => "research",
+            Commands::Bench { .. } => "bench",
+            Commands::Gc { .. } => "gc",
+            Commands::Export { .. } => "export",
+            Commands::Tui { .. } => "tui",
+            Commands::Repo { .. } => "repo",
+            Commands::Watch { .. } => "watch",
+            Commands::Config { .. } => "config",
+            Commands::Cache { .. } => "cache",
+            Commands::Data { .. } => "data",
+            Commands::Auth { .. } => "auth",
+            Commands::Completions { .. } => "completions",
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Write a configuration file with the default settings
+    Init {
+        /// Overwrite an existing configuration file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the configuration, or the value of one key
+    Show {
+        /// Key such as `llm.model`
+        key: Option<String>,
+    },
+    /// Set a key, such as `llm.model` or `rag.top_k`
+    Set {
+        /// Key to set
+        key: String,
+        /// New value; lists are comma separated
+        value: String,
+    },
+    /// Check the configuration, with environment overrides, for invalid
+    /// values, unknown providers, missing API keys and unknown keys
+    Validate,
+}
+
+#[derive(Subcommand)]
+enum AuthAction {
+    /// Store the API key or token of a provider, read from the terminal or
+    /// from stdin
+    SetKey {
+        /// Provider such as `openai`, `anthropic` or `github`
+        provider: String,
+    },
+    /// Remove the stored API key or token of a provider
+    RemoveKey {
+        /// Provider such as `openai`, `anthropic` or `github`
+        provider: String,
+    },
+    /// Show which providers have a key and where it comes from
+    Status,
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// List cached items with their size and when they were last used
+    Ls {
+        /// Kinds of caches to list, all of them by default
+        #[arg(long, value_enum)]
+        kind: Vec<cache::CacheKind>,
+    },
+    /// Remove every cached item
+    Clear {
+        /// Kinds of caches to clear, all of them by default
+        #[arg(long, value_enum)]
+        kind: Vec<cache::CacheKind>,
+        /// Only show what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Remove cached items that are too old or over the size or count limits
+    Gc {
+        /// Kinds of caches to collect, all of them by default
+        #[arg(long, value_enum)]
+        kind: Vec<cache::CacheKind>,
+        /// Maximum total size of each kind in megabytes
+        #[arg(long)]
+        max_size_mb: Option<u64>,
+        /// Remove items unused for this many days
+        #[arg(long)]
+        max_age_days: Option<u64>,
+        /// Maximum number of items of each kind to keep
+        #[arg(long)]
+        max_entries: Option<usize>,
+        /// Only show what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DataAction {
+    /// Show how much disk space caches and data take up
+    Size,
+}
+
+#[derive(Subcommand)]
+enum RepoAction {
+    /// Add a repository and wait until it is indexed
+    Add {
+        /// Repository URL or local path
+        #[arg(add = ArgValueCompleter::new(completions::repository))]
+        repo: String,
+        /// GitHub token for private repositories
+        #[arg(short, long)]
+        token: Option<String>,
+    },
+    /// List the repositories with their indexing status
+    List,
+    /// Show the indexing status and details of a repository
+    Status {
+        /// Repository ID or URL
+        repository: String,
+    },
+    /// Remove a repository together with its clone, index and wiki
+    Remove {
+        /// Repository ID or URL
+        repository: String,
+    },
+    /// Index a repository again and wait until it is done
+    Reindex {
+        /// Repository ID or URL
+        repository: String,
     },
 }
 
+/// Targets a generated wiki can be published to
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum PublishTarget {
+    /// The repository's native GitHub wiki (`{repo}.wiki.git`)
+    GithubWiki,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    completions::complete_if_requested();
     let cli = Cli::parse();
 
+    if let Commands::Completions { shell } = cli.command {
+        completions::write_registration(shell, &mut std::io::stdout())?;
+        return Ok(());
+    }
+
+    // Opt-in only, see wikify_applications::telemetry
+    if telemetry::telemetry().is_some() {
+        eprintln!("{}", telemetry::NOTICE);
+        telemetry::record(TelemetryEvent::command(cli.command.name()));
+    }
+    let result = run(cli).await;
+    telemetry::flush().await;
+    result
+}
+
+/// Run a command
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
-    let logging_config = LoggingConfig::default();
+    let mut logging_config = LoggingConfig::default();
+    if let Commands::Tui { .. } = cli.command {
+        // The terminal UI owns the screen, so logs go to a file
+        std::fs::create_dir_all(base_path())?;
+        logging_config.log_to_file = true;
+        logging_config.log_file_path = Some(base_path().join("tui.log").display().to_string());
+    }
+    // Keep stdout for the JSON document
+    logging_config.log_to_stderr = cli.output.is_json();
     init_logging(&logging_config).map_err(|e| format!("Failed to initialize logging: {}", e))?;
 
+    // Workspace GC only touches the filesystem, so it doesn't need the RAG pipeline
+    if let Commands::Gc {
+        max_size_mb,
+        max_age_days,
+        max_entries,
+        dry_run,
+    } = cli.command
+    {
+        return handle_gc(max_size_mb, max_age_days, max_entries, dry_run);
+    }
+
+    // So do cache and data management
+    if let Commands::Cache { action } = cli.command {
+        return handle_cache(action, cli.output);
+    }
+    if let Commands::Data { action } = cli.command {
+        return handle_data(action, cli.output);
+    }
+
+    // Exporting reads an already generated wiki, so it doesn't need the RAG pipeline either
+    if let Commands::Export {
+        wiki,
+        format,
+        output,
+    } = cli.command
+    {
+        return handle_export(wiki, format, output).await;
+    }
+
+    if let Commands::Config { action } = cli.command {
+        return handle_config(action, cli.config.as_ref());
+    }
+    if let Commands::Auth { action } = cli.command {
+        return handle_auth(action, cli.output);
+    }
+
+    if let Some(server) = &cli.server {
+        let Commands::Ask {
+            repo,
+            question,
+            token,
+            top_k,
+            show_snippets,
+            ..
+        } = cli.command
+        else {
+            return Err(format!("Only `wikify ask` can use the server {} so far", server).into());
+        };
+        let client = remote::RemoteClient::new(server, cli.api_key);
+        return handle_remote_ask(
+            &client,
+            repo,
+            question,
+            token,
+            top_k,
+            show_snippets,
+            cli.output,
+        )
+        .await;
+    }
+
     // Load configuration
-    let config = load_config(cli.config.as_ref()).await?;
+    let file_config = load_config(cli.config.as_ref()).await?;
+    let mut app_config = ApplicationConfig::cli_local();
+    if let Some(file_config) = &file_config {
+        app_config.apply_settings(file_config);
+    }
+    let config = file_config.unwrap_or_default();
+    if cli.command.uses_models() {
+        telemetry::record(TelemetryEvent::providers(&app_config.rag));
+    }
+
+    // Report provider problems now rather than halfway through indexing
+    if cli.command.uses_models() {
+        let issues = app_config.rag.diagnose();
+        if !issues.is_empty() {
+            return Err(wikify_core::describe_issues(&issues).into());
+        }
+    }
+
+    if let Commands::Tui { database } = cli.command {
+        return handle_tui(database, app_config).await;
+    }
+
+    if let Commands::Repo { database, action } = cli.command {
+        let application = local_application(database, app_config).await?;
+        return handle_repo(
+            action,
+            cli.output,
+            &application,
+            &PermissionContext::local(),
+        )
+        .await;
+    }
+
+    if let Commands::Watch {
+        path,
+        wiki,
+        debounce_ms,
+    } = cli.command
+    {
+        return handle_watch(path, wiki, debounce_ms, cli.output, app_config).await;
+    }
+
+    if let Commands::Bench {
+        repo,
+        questions,
+        runs,
+        retrieval_only,
+        baseline,
+        token,
+    } = cli.command
+    {
+        return handle_bench(
+            repo,
+            questions,
+            runs,
+            retrieval_only,
+            baseline,
+            token,
+            cli.output,
+            app_config.rag,
+        )
+        .await;
+    }
 
     // Create Wikify application for CLI usage
-    let app_config = ApplicationConfig::cli_local();
     let application = WikifyApplication::new(app_config)
         .await
         .map_err(|e| format!("Failed to create application: {}", e))?;
@@ -102,14 +596,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Generate {
             repo,
             token,
-            output,
+            format,
+            out_dir,
             api_mode,
+            publish,
         } => {
             handle_generate(
                 repo,
                 token,
-                output,
+                format,
+                out_dir,
                 api_mode,
+                publish,
+                cli.output,
                 &config,
                 &application,
                 &context,
@@ -127,6 +626,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             rerank,
             chunk_size,
             chunk_overlap,
+            show_snippets,
         } => {
             handle_ask(
                 repo,
@@ -139,127 +639,1272 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 rerank,
                 chunk_size,
                 chunk_overlap,
+                show_snippets,
+                cli.output,
                 &config,
                 &application,
                 &context,
             )
             .await?;
         }
+        Commands::DiffAsk {
+            repo,
+            question,
+            since,
+            top_k,
+            show_snippets,
+        } => {
+            handle_diff_ask(
+                repo,
+                question,
+                since,
+                top_k,
+                show_snippets,
+                cli.output,
+                &application,
+                &context,
+            )
+            .await?;
+        }
+        #[cfg(feature = "research")]
+        Commands::Research {
+            repo,
+            question,
+            template,
+            iterations,
+            output,
+            token,
+        } => {
+            handle_research(
+                repo,
+                question,
+                template,
+                iterations,
+                output,
+                token,
+                cli.output,
+                &application,
+                &context,
+            )
+            .await?;
+        }
+        Commands::Gc { .. }
+        | Commands::Export { .. }
+        | Commands::Tui { .. }
+        | Commands::Repo { .. }
+        | Commands::Watch { .. }
+        | Commands::Bench { .. }
+        | Commands::Cache { .. }
+        | Commands::Data { .. }
+        | Commands::Config { .. }
+        | Commands::Auth { .. }
+        | Commands::Completions { .. } => {
+            unreachable!("handled before application startup")
+        }
     }
 
     Ok(())
 }
 
-/// Load configuration from file or use defaults
+/// Load the configuration file given with `--config`, or `~/.wikify/config.toml` if there is one
 async fn load_config(
     config_path: Option<&PathBuf>,
-) -> Result<WikifyConfig, Box<dyn std::error::Error>> {
+) -> Result<Option<WikifyConfig>, Box<dyn std::error::Error>> {
     if let Some(path) = config_path {
-        if path.exists() {
-            info!("Loading configuration from: {}", path.display());
-            // TODO: Implement config loading
-            Ok(WikifyConfig::default())
-        } else {
-            Err(format!("Configuration file not found: {}", path.display()).into())
+        if !path.exists() {
+            return Err(format!("Configuration file not found: {}", path.display()).into());
         }
-    } else {
-        info!("No configuration file specified, using defaults");
-        Ok(WikifyConfig::default())
     }
+
+    let path = config::config_path(config_path);
+    if path.exists() {
+        info!("Loading configuration from: {}", path.display());
+    }
+    let config = WikifyConfig::load(&path, config::initial_config())?;
+    if config.is_none() {
+        info!("No configuration file found, using defaults");
+    }
+    Ok(config)
 }
 
 /// Handle ask command using application layer
 async fn handle_ask(
     repo: String,
     question: String,
-    _token: Option<String>,
+    token: Option<String>,
     _api_mode: bool,
     _threshold: f32,
-    _top_k: usize,
+    top_k: usize,
     _max_context: usize,
     _rerank: bool,
     _chunk_size: usize,
     _chunk_overlap: usize,
+    show_snippets: bool,
+    output: OutputFormat,
     _config: &WikifyConfig,
     application: &WikifyApplication,
     context: &PermissionContext,
 ) -> Result<(), Box<dyn std::error::Error>> {
     log_operation_start!("ask", repository = %repo);
+    let started = Instant::now();
 
-    // Create session with auto-indexing
-    let session_options = SessionOptions {
-        auto_generate_wiki: false, // Don't auto-generate wiki for ask command
-        ..Default::default()
-    };
+    let repository_id =
+        add_and_index_repository(application, context, &repo, token, output).await?;
+    let indexing_ms = started.elapsed().as_millis() as u64;
+
+    let attachment = read_piped_context()?;
+    if let Some(attachment) = &attachment {
+        output.status(format!(
+            "📎 Attached {} of piped input",
+            cache::human_size(attachment.len() as u64)
+        ));
+    }
 
-    let session_id = application
-        .create_session(context, repo.clone(), session_options)
+    // Execute query
+    let query = RepositoryQuery {
+        question: question.clone(),
+        max_results: Some(top_k),
+        parameters: attachment.map(|attachment| {
+            std::collections::HashMap::from([("attachment".to_string(), attachment)])
+        }),
+    };
+    let response = application
+        .query_repository(context, &repository_id, query)
         .await
-        .map_err(|e| format!("Failed to create session: {}", e))?;
+        .map_err(|e| format!("Query failed: {}", e))?;
+    let citations = output::citations(&response);
 
-    info!("Created session: {} for repository: {}", session_id, repo);
+    if output.is_json() {
+        let timing = |key: &str| response.metadata.get(key).and_then(|ms| ms.parse().ok());
+        output.result(&output::AskOutput {
+            repository_id,
+            question,
+            answer: response.answer.clone(),
+            sources: SourceOutput::from_response(&response),
+            confidence: response.confidence,
+            timings: Timings {
+                indexing_ms: Some(indexing_ms),
+                retrieval_ms: timing("retrieval_time_ms"),
+                generation_ms: timing("generation_time_ms"),
+                total_ms: started.elapsed().as_millis() as u64,
+            },
+        })?;
+    } else {
+        // Display results
+        println!("\n🤖 Answer:");
+        println!("{}", response.answer);
+        output::print_citations(&citations, show_snippets);
+    }
 
-    // Wait for indexing to complete (simplified approach)
-    // In a real implementation, we'd want to show progress
-    println!("🔄 Indexing repository...");
-    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    log_operation_success!("ask", repository = %repo);
+    Ok(())
+}
 
-    // Execute query
+/// Handle diff-ask command by asking about the files changed since a ref
+async fn handle_diff_ask(
+    repo: String,
+    question: Option<String>,
+    since: String,
+    top_k: usize,
+    show_snippets: bool,
+    output: OutputFormat,
+    application: &WikifyApplication,
+    context: &PermissionContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log_operation_start!("diff_ask", repository = %repo);
+    let started = Instant::now();
+
+    if !std::path::Path::new(&repo).is_dir() {
+        return Err(format!(
+            "{} is not a local directory; diff-ask reads the git history of a local checkout",
+            repo
+        )
+        .into());
+    }
+    let changes = diff_ask::ChangeSet::from_git(std::path::Path::new(&repo), &since).await?;
+    if changes.is_empty() {
+        output.status(format!("No changes since {}", since));
+        return Ok(());
+    }
+    output.status(format!(
+        "🔍 {} commits and {} changed files since {}",
+        changes.commits.len(),
+        changes.files.len(),
+        since
+    ));
+
+    let repository_id = add_and_index_repository(application, context, &repo, None, output).await?;
+    let indexing_ms = started.elapsed().as_millis() as u64;
+
+    let query = RepositoryQuery {
+        question: changes.prompt(question.as_deref()),
+        max_results: Some(top_k),
+        parameters: Some(std::collections::HashMap::from([(
+            "files".to_string(),
+            changes.indexed_files().join("\n"),
+        )])),
+    };
     let response = application
-        .query(context, &session_id, question.clone())
+        .query_repository(context, &repository_id, query)
         .await
         .map_err(|e| format!("Query failed: {}", e))?;
+    let citations = output::citations(&response);
 
-    // Display results
-    println!("\n🤖 Answer:");
-    println!("{}", response.answer);
+    if output.is_json() {
+        let timing = |key: &str| response.metadata.get(key).and_then(|ms| ms.parse().ok());
+        output.result(&output::DiffAskOutput {
+            repository_id,
+            changes,
+            question,
+            answer: response.answer.clone(),
+            sources: SourceOutput::from_response(&response),
+            timings: Timings {
+                indexing_ms: Some(indexing_ms),
+                retrieval_ms: timing("retrieval_time_ms"),
+                generation_ms: timing("generation_time_ms"),
+                total_ms: started.elapsed().as_millis() as u64,
+            },
+        })?;
+    } else {
+        println!("\n📝 Changes since {}:", since);
+        println!("{}", response.answer);
+        output::print_citations(&citations, show_snippets);
+    }
 
-    if !response.sources.is_empty() {
-        println!("\n📚 Sources:");
-        for (i, source) in response.sources.iter().enumerate() {
-            println!("{}. {}", i + 1, source);
-        }
+    log_operation_success!("diff_ask", repository = %repo);
+    Ok(())
+}
+
+/// Handle ask command by asking a wikify server
+async fn handle_remote_ask(
+    client: &remote::RemoteClient,
+    repo: String,
+    question: String,
+    token: Option<String>,
+    top_k: usize,
+    show_snippets: bool,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log_operation_start!("remote_ask", repository = %repo, server = %client.server());
+    let started = Instant::now();
+
+    let repository_id = client.index_repository(&repo, token, output).await?;
+    let indexing_ms = started.elapsed().as_millis() as u64;
+
+    let attachment = read_piped_context()?;
+    let response = client
+        .ask(&repository_id, &question, top_k, attachment)
+        .await
+        .map_err(|e| format!("Query failed: {}", e))?;
+    let citations: Vec<SourceCitation> = response
+        .sources
+        .into_iter()
+        .map(SourceCitation::from)
+        .collect();
+
+    if output.is_json() {
+        output.result(&output::AskOutput {
+            repository_id,
+            question,
+            answer: response.answer,
+            sources: SourceOutput::from_citations(&citations),
+            confidence: None,
+            timings: Timings {
+                indexing_ms: Some(indexing_ms),
+                total_ms: started.elapsed().as_millis() as u64,
+                ..Default::default()
+            },
+        })?;
+    } else {
+        println!("\n🤖 Answer:");
+        println!("{}", response.answer);
+        output::print_citations(&citations, show_snippets);
     }
 
-    log_operation_success!("ask", repository = %repo);
+    log_operation_success!("remote_ask", repository = %repo);
     Ok(())
 }
 
 /// Handle generate command using application layer
 async fn handle_generate(
     repo: String,
-    _token: Option<String>,
-    output: Option<PathBuf>,
+    token: Option<String>,
+    format: wikify_wiki::ExportFormat,
+    out_dir: Option<PathBuf>,
     _api_mode: bool,
+    publish: Option<PublishTarget>,
+    output: OutputFormat,
     _config: &WikifyConfig,
     application: &WikifyApplication,
     context: &PermissionContext,
 ) -> Result<(), Box<dyn std::error::Error>> {
     log_operation_start!("generate", repository = %repo);
+    let started = Instant::now();
 
-    // Create session with auto-wiki generation
-    let session_options = SessionOptions {
-        auto_generate_wiki: true,
+    let repo_type = repository_type(&repo);
+    let options = wikify_applications::RepositoryOptions {
+        // Wiki generation builds its own index
+        auto_index: false,
+        api_token: token.clone(),
         ..Default::default()
     };
+    let repository_id = application
+        .add_repository(context, repo.clone(), repo_type.to_string(), options)
+        .await
+        .map_err(|e| format!("Failed to add repository: {}", e))?;
 
-    let session_id = application
-        .create_session(context, repo.clone(), session_options)
+    info!("Added repository: {} ({})", repo, repository_id);
+
+    output.status("🔄 Generating wiki...");
+    let wiki = application
+        .generate_wiki(
+            context,
+            &repository_id,
+            wikify_wiki::WikiConfig::default(),
+            output.progress().as_ref(),
+        )
         .await
-        .map_err(|e| format!("Failed to create session: {}", e))?;
+        .map_err(|e| format!("Failed to generate wiki: {}", e))?;
+
+    let out_dir = out_dir.unwrap_or_else(|| PathBuf::from("./wiki"));
+    // Single-file formats are written into the output directory
+    let output_path = if format.is_single_file() {
+        tokio::fs::create_dir_all(&out_dir).await?;
+        out_dir.join(format!("wiki.{}", format.file_extension()))
+    } else {
+        out_dir
+    };
+    wikify_wiki::WikiService::new()?
+        .export_wiki(&wiki, format, &output_path.to_string_lossy())
+        .await?;
+    output.status(format!(
+        "✅ Generated {} pages, saved to: {}",
+        wiki.pages.len(),
+        output_path.display()
+    ));
+    if let Some(quality) = &wiki.quality {
+        output.status(format!("📊 Quality score: {}/100", quality.score));
+        for page in quality.weak_pages(60).take(5) {
+            output.status(format!(
+                "   ⚠️  {} ({}/100): {}",
+                page.title,
+                page.score,
+                page.issues.join("; ")
+            ));
+        }
+    }
 
-    info!("Created session: {} for repository: {}", session_id, repo);
+    let mut published_url = None;
+    if let Some(PublishTarget::GithubWiki) = publish {
+        let mut publisher = wikify_wiki::GitHubWikiPublisher::new();
+        if let Some(token) = token {
+            publisher = publisher.with_token(token);
+        }
 
-    // Wait for indexing and wiki generation to complete
-    // In a real implementation, we'd want to show progress
-    println!("🔄 Indexing repository and generating wiki...");
-    tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        output.status("📤 Publishing to GitHub wiki...");
+        let report = publisher.publish(&wiki, &repo).await?;
+        if report.changed {
+            output.status(format!(
+                "✅ Published {} pages to {}",
+                report.pages_published, report.wiki_url
+            ));
+        } else {
+            output.status(format!(
+                "✅ GitHub wiki is already up to date: {}",
+                report.wiki_url
+            ));
+        }
+        published_url = Some(report.wiki_url);
+    }
 
-    // For now, we'll just indicate that the wiki would be generated
-    let output_path = output.unwrap_or_else(|| PathBuf::from("./wiki.md"));
-    println!("✅ Wiki generated successfully!");
-    println!("📄 Output would be saved to: {}", output_path.display());
+    output.result(&output::GenerateOutput {
+        repository_id,
+        title: wiki.title.clone(),
+        pages: wiki.pages.len(),
+        format: format!("{:?}", format).to_lowercase(),
+        output_path: output_path.display().to_string(),
+        quality_score: wiki.quality.as_ref().map(|quality| quality.score),
+        published_url,
+        timings: Timings {
+            total_ms: started.elapsed().as_millis() as u64,
+            ..Default::default()
+        },
+    })?;
 
     log_operation_success!("generate", repository = %repo);
     Ok(())
 }
+
+/// Handle research command by running the research engine on an indexed repository
+#[cfg(feature = "research")]
+async fn handle_research(
+    repo: String,
+    question: String,
+    template: Option<String>,
+    iterations: Option<usize>,
+    output: PathBuf,
+    token: Option<String>,
+    output_format: OutputFormat,
+    application: &WikifyApplication,
+    context: &PermissionContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use tokio::sync::broadcast::error::RecvError;
+    use wikify_applications::research::ResearchStatus;
+
+    log_operation_start!("research", repository = %repo);
+    let started = Instant::now();
+
+    let mut research_config = match &template {
+        Some(template_id) => application.get_research_template(template_id).await?.config,
+        None => wikify_applications::ResearchConfig::default(),
+    };
+    if let Some(iterations) = iterations {
+        research_config.max_iterations = iterations;
+    }
+
+    let repository_id =
+        add_and_index_repository(application, context, &repo, token, output_format).await?;
+    let indexing_ms = started.elapsed().as_millis() as u64;
+
+    output_format.status(format!("🔬 Researching: {}", question));
+    let research_id = application
+        .start_research(
+            context,
+            &repository_id,
+            question.clone(),
+            Some(research_config),
+        )
+        .await?;
+    let (mut progress, mut updates) = application
+        .subscribe_to_research_progress(context, &research_id)
+        .await?;
+    let mut reported_iteration = None;
+    loop {
+        if reported_iteration != Some(progress.current_iteration) {
+            output_format.status(format!(
+                "   Iteration {}/{} ({:.0}%)",
+                progress.current_iteration,
+                progress.max_iterations,
+                progress.progress * 100.0
+            ));
+            reported_iteration = Some(progress.current_iteration);
+        }
+        match progress.status {
+            ResearchStatus::InProgress => {}
+            ResearchStatus::Completed => break,
+            ResearchStatus::Cancelled => return Err("Research was cancelled".into()),
+            ResearchStatus::Failed(reason) => return Err(reason.into()),
+        }
+        progress = match updates.recv().await {
+            Ok(update) if update.id == research_id => update,
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Err("Research progress updates stopped".into()),
+        };
+    }
+
+    let result = application
+        .get_research_result(context, &research_id)
+        .await?
+        .ok_or("Research completed without a result")?;
+    tokio::fs::write(&output, research_report(&result)).await?;
+    output_format.status(format!(
+        "✅ Research finished after {} iterations, report saved to: {}",
+        result.iterations.len(),
+        output.display()
+    ));
+
+    output_format.result(&output::ResearchOutput {
+        repository_id,
+        research_id,
+        question,
+        synthesis: result.final_synthesis.clone(),
+        iterations: result
+            .iterations
+            .iter()
+            .map(|iteration| output::IterationOutput {
+                iteration: iteration.iteration,
+                query: iteration.query.clone(),
+                response: iteration.response.clone(),
+                confidence: iteration.confidence_score,
+                duration_ms: iteration.duration_ms,
+            })
+            .collect(),
+        sources: research_sources(&result),
+        report_path: output.display().to_string(),
+        timings: Timings {
+            indexing_ms: Some(indexing_ms),
+            total_ms: started.elapsed().as_millis() as u64,
+            ..Default::default()
+        },
+    })?;
+
+    log_operation_success!("research", repository = %repo);
+    Ok(())
+}
+
+/// Add a repository and wait for it to be indexed, showing a progress bar
+async fn add_and_index_repository(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repo: &str,
+    token: Option<String>,
+    output: OutputFormat,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let options = wikify_applications::RepositoryOptions {
+        api_token: token,
+        ..Default::default()
+    };
+    // Subscribe before adding so the end of a quick indexing run isn't missed
+    let updates = application.subscribe_to_repository_progress();
+    let repository_id = application
+        .add_repository(
+            context,
+            repo.to_string(),
+            repository_type(repo).to_string(),
+            options,
+        )
+        .await
+        .map_err(|e| format!("Failed to add repository: {}", e))?;
+    info!("Added repository: {} ({})", repo, repository_id);
+
+    output.status("🔄 Indexing repository...");
+    progress::wait_for_indexing(application, context, &repository_id, updates).await?;
+    Ok(repository_id)
+}
+
+/// Markdown report of a research result: the synthesis, then each iteration and the sources
+#[cfg(feature = "research")]
+fn research_report(result: &wikify_rag::DeepResearchResult) -> String {
+    let mut report = format!(
+        "# {}\n\n{}\n",
+        result.original_query,
+        result.final_synthesis.trim()
+    );
+
+    if !result.iterations.is_empty() {
+        report.push_str("\n## Iterations\n");
+        for iteration in &result.iterations {
+            report.push_str(&format!(
+                "\n### {}. {}\n\n{}\n",
+                iteration.iteration,
+                iteration.query,
+                iteration.response.trim()
+            ));
+        }
+    }
+
+    let sources = research_sources(result);
+    if !sources.is_empty() {
+        report.push_str("\n## Sources\n\n");
+        for source in sources {
+            report.push_str(&format!("- `{}`\n", source.path));
+        }
+    }
+    report
+}
+
+/// Files a research result is based on, each listed once with its best score
+#[cfg(feature = "research")]
+fn research_sources(result: &wikify_rag::DeepResearchResult) -> Vec<SourceOutput> {
+    let mut sources: Vec<SourceOutput> = Vec::new();
+    for source in &result.all_sources {
+        let metadata = &source.chunk.metadata;
+        let Some(path) = metadata
+            .get("file_path")
+            .or_else(|| metadata.get("source"))
+            .and_then(|value| value.as_str())
+        else {
+            continue;
+        };
+        match sources.iter_mut().find(|known| known.path == path) {
+            Some(known) => known.score = known.score.max(Some(source.score)),
+            None => sources.push(SourceOutput {
+                path: path.to_string(),
+                score: Some(source.score),
+                start_line: None,
+                end_line: None,
+            }),
+        }
+    }
+    sources
+}
+
+/// Most piped input attached to a question, in bytes
+const MAX_PIPED_CONTEXT_BYTES: usize = 64 * 1024;
+
+/// Text piped into the CLI, such as an error log or a stack trace
+///
+/// `None` when stdin is a terminal or nothing was piped. Longer input than
+/// [`MAX_PIPED_CONTEXT_BYTES`] keeps its end, where errors usually are.
+fn read_piped_context() -> std::io::Result<Option<String>> {
+    use std::io::{IsTerminal, Read};
+
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(None);
+    }
+    let mut input = Vec::new();
+    stdin.read_to_end(&mut input)?;
+    let input = String::from_utf8_lossy(&input);
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+
+    let mut start = input.len().saturating_sub(MAX_PIPED_CONTEXT_BYTES);
+    while !input.is_char_boundary(start) {
+        start += 1;
+    }
+    Ok(Some(input[start..].to_string()))
+}
+
+/// Repository type of a URL or local path, as expected by `add_repository`
+fn repository_type(repo: &str) -> &'static str {
+    if repo.contains("github.com") {
+        "github"
+    } else if repo.contains("gitlab.com") {
+        "gitlab"
+    } else {
+        "local"
+    }
+}
+
+/// Handle tui command by running the terminal UI on an in-process application
+async fn handle_tui(
+    database: Option<String>,
+    app_config: ApplicationConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let application = local_application(database, app_config).await?;
+    tui::run(std::sync::Arc::new(application), PermissionContext::local()).await
+}
+
+/// Create an application keeping its repositories in a SQLite database
+///
+/// Without a database URL, the CLI's own `~/.wikify/repositories.db` is used,
+/// so `wikify repo` and `wikify tui` see the same repositories.
+async fn local_application(
+    database: Option<String>,
+    app_config: ApplicationConfig,
+) -> Result<WikifyApplication, Box<dyn std::error::Error>> {
+    let database_url = match database {
+        Some(database_url) => database_url,
+        None => {
+            std::fs::create_dir_all(base_path())?;
+            let path = base_path().join("repositories.db");
+            format!("sqlite:{}?mode=rwc", path.display())
+        }
+    };
+    let storage =
+        wikify_applications::repository::SqliteRepositoryStorage::from_url(&database_url).await?;
+    storage.migrate().await?;
+
+    WikifyApplication::builder(app_config)
+        .with_repository_storage(std::sync::Arc::new(storage))
+        .build()
+        .await
+        .map_err(|e| format!("Failed to create application: {}", e).into())
+}
+
+/// Handle repo command by managing the repositories of the local application
+async fn handle_repo(
+    action: RepoAction,
+    output: OutputFormat,
+    application: &WikifyApplication,
+    context: &PermissionContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        RepoAction::Add { repo, token } => {
+            let repository_id = match find_repository(application, context, &repo).await? {
+                Some(existing) => {
+                    output.status(format!(
+                        "Repository already added as {} ({:?})",
+                        existing.id, existing.status
+                    ));
+                    existing.id
+                }
+                None => {
+                    let repository_id =
+                        add_and_index_repository(application, context, &repo, token, output)
+                            .await?;
+                    output.status(format!("✅ Added {} as {}", repo, repository_id));
+                    repository_id
+                }
+            };
+            output.result(&application.get_repository(context, &repository_id).await?)?;
+        }
+        RepoAction::List => {
+            let repositories = application
+                .list_repositories(context, &RepositoryFilter::default(), PageRequest::all())
+                .await?
+                .items;
+            if output.is_json() {
+                output.result(&repositories)?;
+            } else if repositories.is_empty() {
+                println!("No repositories yet, add one with `wikify repo add <repo>`");
+            } else {
+                println!("{:<36}  {:<10}  {:>8}  URL", "ID", "STATUS", "PROGRESS");
+                for repository in repositories {
+                    println!(
+                        "{:<36}  {:<10}  {:>7.0}%  {}",
+                        repository.id,
+                        format!("{:?}", repository.status),
+                        repository.progress * 100.0,
+                        repository.url
+                    );
+                }
+            }
+        }
+        RepoAction::Status { repository } => {
+            let repository = require_repository(application, context, &repository).await?;
+            if output.is_json() {
+                output.result(&repository)?;
+            } else {
+                println!("ID:           {}", repository.id);
+                println!("URL:          {}", repository.url);
+                println!("Type:         {}", repository.repo_type);
+                println!(
+                    "Status:       {:?} ({:.0}%)",
+                    repository.status,
+                    repository.progress * 100.0
+                );
+                println!("Added:        {}", repository.created_at.to_rfc3339());
+                let indexed_at = repository.indexed_at.map(|at| at.to_rfc3339());
+                println!("Last indexed: {}", indexed_at.as_deref().unwrap_or("never"));
+                let mut metadata: Vec<_> = repository.metadata.iter().collect();
+                metadata.sort();
+                for (key, value) in metadata {
+                    println!("  {}: {}", key, value);
+                }
+            }
+        }
+        RepoAction::Remove { repository } => {
+            let repository = require_repository(application, context, &repository).await?;
+            application
+                .delete_repository(context, &repository.id)
+                .await?;
+            output.status(format!(
+                "🗑️  Removed {} ({})",
+                repository.url, repository.id
+            ));
+            output.result(&serde_json::json!({ "removed": repository.id }))?;
+        }
+        RepoAction::Reindex { repository } => {
+            let repository = require_repository(application, context, &repository).await?;
+            reindex_and_wait(application, context, &repository, output).await?;
+            output.result(&application.get_repository(context, &repository.id).await?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Reindex a repository and wait for it, showing a progress bar
+async fn reindex_and_wait(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository: &RepositoryIndex,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Subscribe before reindexing so the end of a quick run isn't missed
+    let updates = application.subscribe_to_repository_progress();
+    application
+        .reindex_repository(context, &repository.id)
+        .await?;
+    output.status(format!("🔄 Reindexing {}...", repository.url));
+    progress::wait_for_indexing(application, context, &repository.id, updates).await
+}
+
+/// Handle watch command by indexing a local repository, then following its changes
+///
+/// Indexes live in memory, so the repository is indexed again at startup
+/// even when `wikify repo` has indexed it before.
+async fn handle_watch(
+    path: PathBuf,
+    wiki: bool,
+    debounce_ms: u64,
+    output: OutputFormat,
+    app_config: ApplicationConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root = std::fs::canonicalize(&path)
+        .map_err(|e| format!("Cannot watch {}: {}", path.display(), e))?;
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()).into());
+    }
+    let repo = root.display().to_string();
+
+    let application = local_application(None, app_config).await?;
+    let context = PermissionContext::local();
+    let repository_id = match find_repository(&application, &context, &repo).await? {
+        Some(repository) => {
+            reindex_and_wait(&application, &context, &repository, output).await?;
+            repository.id
+        }
+        None => add_and_index_repository(&application, &context, &repo, None, output).await?,
+    };
+
+    if wiki
+        && application
+            .get_wiki(&context, &repository_id)
+            .await?
+            .is_none()
+    {
+        output.status("🔄 Generating wiki...");
+        application
+            .generate_wiki(
+                &context,
+                &repository_id,
+                wikify_wiki::WikiConfig::default(),
+                output.progress().as_ref(),
+            )
+            .await
+            .map_err(|e| format!("Failed to generate wiki: {}", e))?;
+    }
+
+    watch::watch(
+        &application,
+        &context,
+        &repository_id,
+        &root,
+        std::time::Duration::from_millis(debounce_ms),
+        wiki,
+        output,
+    )
+    .await
+}
+
+/// Handle bench command by indexing a repository and asking the benchmark questions
+#[allow(clippy::too_many_arguments)]
+async fn handle_bench(
+    repo: String,
+    questions: Option<PathBuf>,
+    runs: usize,
+    retrieval_only: bool,
+    baseline: Option<PathBuf>,
+    token: Option<String>,
+    output: OutputFormat,
+    rag_config: wikify_rag::RagConfig,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let questions = match &questions {
+        Some(path) => bench::load_questions(path)
+            .map_err(|e| format!("Cannot read questions from {}: {}", path.display(), e))?,
+        None => bench::DEFAULT_QUESTIONS
+            .iter()
+            .map(|question| question.to_string())
+            .collect(),
+    };
+    if questions.is_empty() || runs == 0 {
+        return Err("The benchmark needs at least one question and one run".into());
+    }
+    // Read the baseline first, so a wrong path doesn't waste a whole run
+    let baseline: Option<bench::BenchReport> = match &baseline {
+        Some(path) => Some(
+            serde_json::from_str(&std::fs::read_to_string(path)?)
+                .map_err(|e| format!("{} is not a benchmark report: {}", path.display(), e))?,
+        ),
+        None => None,
+    };
+
+    output.status(format!(
+        "🏁 Benchmarking {} with {} questions × {} runs",
+        repo,
+        questions.len(),
+        runs
+    ));
+    let mut pipeline = wikify_rag::RagPipeline::new(rag_config).with_access_token(token);
+    pipeline.initialize().await?;
+    let report = bench::run(&mut pipeline, &repo, &questions, runs, retrieval_only).await?;
+
+    output.result(&report)?;
+    if output.is_json() {
+        return Ok(());
+    }
+    report.print();
+    if let Some(baseline) = baseline {
+        println!("\n📈 Compared with wikify {}:", baseline.version);
+        for (name, before, after, change) in report.compare(&baseline) {
+            println!(
+                "   {:<20} {:>10.1} → {:>10.1} ({:+.1}%)",
+                name, before, after, change
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Find a repository by its ID or URL
+async fn find_repository(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    id_or_url: &str,
+) -> Result<Option<RepositoryIndex>, Box<dyn std::error::Error>> {
+    let repositories = application
+        .list_repositories(context, &RepositoryFilter::default(), PageRequest::all())
+        .await?
+        .items;
+    Ok(repositories
+        .into_iter()
+        .find(|repository| repository.id == id_or_url || repository.url == id_or_url))
+}
+
+async fn require_repository(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    id_or_url: &str,
+) -> Result<RepositoryIndex, Box<dyn std::error::Error>> {
+    find_repository(application, context, id_or_url)
+        .await?
+        .ok_or_else(|| {
+            format!(
+                "No repository {}, see `wikify repo list` for the known ones",
+                id_or_url
+            )
+            .into()
+        })
+}
+
+/// Handle config command by creating, printing or editing the configuration file
+fn handle_config(
+    action: ConfigAction,
+    config_path: Option<&PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = config::config_path(config_path);
+    match action {
+        ConfigAction::Init { force } => {
+            if path.exists() && !force {
+                return Err(format!(
+                    "{} already exists, use --force to overwrite it",
+                    path.display()
+                )
+                .into());
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            config::initial_config().save_to_file(&path)?;
+            println!("✅ Wrote default configuration to {}", path.display());
+        }
+        ConfigAction::Show { key } => {
+            let config = match config::load(&path)? {
+                Some(config) => config,
+                None => {
+                    eprintln!(
+                        "No configuration file at {}, showing defaults",
+                        path.display()
+                    );
+                    config::initial_config()
+                }
+            };
+            println!("{}", config::get_value(&config, key.as_deref())?);
+        }
+        ConfigAction::Set { key, value } => {
+            let config = config::load(&path)?.unwrap_or_else(config::initial_config);
+            let config = config::set_value(&config, &key, &value)?;
+            config.validate()?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            config.save_to_file(&path)?;
+            println!(
+                "✅ Set {} = {}",
+                key,
+                config::get_value(&config, Some(&key))?
+            );
+        }
+        ConfigAction::Validate => {
+            let mut config = config::load(&path)?.unwrap_or_else(config::initial_config);
+            for name in config.apply_env_overrides()? {
+                println!("ℹ️  {} overrides the file", name);
+            }
+            if path.exists() {
+                let raw: toml::Table = toml::from_str(&std::fs::read_to_string(&path)?)?;
+                for key in config::unknown_keys(&raw, &config) {
+                    println!("⚠️  Unknown key ignored: {}", key);
+                }
+            } else {
+                println!(
+                    "ℹ️  No configuration file at {}, checking the defaults",
+                    path.display()
+                );
+            }
+
+            let mut app_config = ApplicationConfig::cli_local();
+            app_config.apply_settings(&config);
+            let mut issues = config.diagnose();
+            issues.extend(app_config.rag.diagnose());
+            if !issues.is_empty() {
+                for issue in &issues {
+                    println!("❌ {}: {}", issue.key, issue.message);
+                    println!("   → {}", issue.suggestion);
+                }
+                return Err(format!("Found {} configuration problems", issues.len()).into());
+            }
+            println!("✅ The configuration is valid");
+        }
+    }
+    Ok(())
+}
+
+/// Handle gc command by applying a retention policy to the clone workspaces
+fn handle_gc(
+    max_size_mb: Option<u64>,
+    max_age_days: Option<u64>,
+    max_entries: Option<usize>,
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log_operation_start!("gc");
+
+    let policy = retention_policy(max_size_mb, max_age_days, max_entries);
+    let report = cache::collect_garbage(&[cache::CacheKind::Clones], &policy, dry_run);
+    print_gc_report(&report, "clones", OutputFormat::Text)?;
+
+    log_operation_success!("gc");
+    Ok(())
+}
+
+/// Default retention policy with the limits given on the command line
+fn retention_policy(
+    max_size_mb: Option<u64>,
+    max_age_days: Option<u64>,
+    max_entries: Option<usize>,
+) -> wikify_repo::RetentionPolicy {
+    let mut policy = wikify_repo::RetentionPolicy::default();
+    if let Some(mb) = max_size_mb {
+        policy.max_total_size_bytes = Some(mb * 1024 * 1024);
+    }
+    if let Some(days) = max_age_days {
+        policy.max_age = Some(std::time::Duration::from_secs(days * 24 * 60 * 60));
+    }
+    if max_entries.is_some() {
+        policy.max_entries = max_entries;
+    }
+    policy
+}
+
+fn print_gc_report(
+    report: &wikify_repo::GcReport,
+    items: &str,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let verb = if report.dry_run {
+        "Would remove"
+    } else {
+        "Removed"
+    };
+    for path in &report.removed {
+        output.status(format!("🗑️ {} {}", verb, path.display()));
+    }
+    output.status(format!(
+        "✅ Scanned {} {}, {} {} ({} freed, {} remaining)",
+        report.scanned,
+        items,
+        verb.to_lowercase(),
+        report.removed.len(),
+        cache::human_size(report.freed_bytes),
+        cache::human_size(report.remaining_bytes)
+    ));
+    output.result(report)?;
+    Ok(())
+}
+
+/// Handle cache command by listing or removing cached clones, indexes and wikis
+fn handle_cache(
+    action: CacheAction,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        CacheAction::Ls { kind } => {
+            let now = std::time::SystemTime::now();
+            let mut listing = Vec::new();
+            for kind in cache::CacheKind::or_all(kind) {
+                let mut entries = kind.entries();
+                entries.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+                let total: u64 = entries.iter().map(|entry| entry.size_bytes).sum();
+                output.status(format!(
+                    "{} ({}, {})",
+                    kind.label(),
+                    entries.len(),
+                    cache::human_size(total)
+                ));
+                for entry in &entries {
+                    let days = now
+                        .duration_since(entry.last_used)
+                        .map(|age| age.as_secs() / (24 * 60 * 60))
+                        .unwrap_or(0);
+                    output.status(format!(
+                        "  {:>9}  {:>4}d  {}",
+                        cache::human_size(entry.size_bytes),
+                        days,
+                        entry.path.display()
+                    ));
+                }
+                listing.push(serde_json::json!({ "kind": kind, "entries": entries }));
+            }
+            output.result(&listing)?;
+        }
+        CacheAction::Clear { kind, dry_run } => {
+            let report = cache::clear(&cache::CacheKind::or_all(kind), dry_run);
+            print_gc_report(&report, "cached items", output)?;
+        }
+        CacheAction::Gc {
+            kind,
+            max_size_mb,
+            max_age_days,
+            max_entries,
+            dry_run,
+        } => {
+            let policy = retention_policy(max_size_mb, max_age_days, max_entries);
+            let report = cache::collect_garbage(&cache::CacheKind::or_all(kind), &policy, dry_run);
+            print_gc_report(&report, "cached items", output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Handle data command by reporting the disk usage of caches and data
+fn handle_data(action: DataAction, output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        DataAction::Size => {
+            let usage = cache::data_usage();
+            for item in &usage {
+                output.status(format!(
+                    "{:>9}  {:<20}  {}",
+                    cache::human_size(item.size_bytes),
+                    item.name,
+                    item.path.display()
+                ));
+            }
+            let total = |cache: bool| {
+                usage
+                    .iter()
+                    .filter(|item| item.cache == cache)
+                    .map(|item| item.size_bytes)
+                    .sum::<u64>()
+            };
+            output.status(format!(
+                "Total: {} ({} in caches, which `wikify cache clear` removes)",
+                cache::human_size(total(true) + total(false)),
+                cache::human_size(total(true))
+            ));
+            output.result(&usage)?;
+        }
+    }
+    Ok(())
+}
+
+/// Handle auth command by managing the secrets stored in the OS keyring
+fn handle_auth(action: AuthAction, output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    use wikify_core::secrets;
+
+    let secret_of = |provider: &str| {
+        secrets::provider_secret(provider).ok_or_else(|| {
+            let known: Vec<&str> = secrets::PROVIDER_SECRETS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect();
+            format!(
+                "Unknown provider '{}', use one of: {}",
+                provider,
+                known.join(", ")
+            )
+        })
+    };
+
+    match action {
+        AuthAction::SetKey { provider } => {
+            let name = secret_of(&provider)?;
+            let value = read_secret(&format!("{} for {}: ", name, provider))?;
+            if value.is_empty() {
+                return Err("No key given".into());
+            }
+            secrets::store_secret(name, &value)?;
+            output.status(format!("✅ Stored {} in the OS keyring", name));
+        }
+        AuthAction::RemoveKey { provider } => {
+            let name = secret_of(&provider)?;
+            if secrets::delete_secret(name)? {
+                output.status(format!("🗑️  Removed {} from the OS keyring", name));
+            } else {
+                output.status(format!("{} is not stored in the OS keyring", name));
+            }
+        }
+        AuthAction::Status => {
+            let mut statuses = Vec::new();
+            for (provider, name) in secrets::PROVIDER_SECRETS {
+                let source = secrets::secret_source(name);
+                let location = match source {
+                    Some(secrets::SecretSource::Keyring) => "keyring",
+                    Some(secrets::SecretSource::Environment) => "environment",
+                    None => "not set",
+                };
+                output.status(format!("{:<10} {:<18} {}", provider, name, location));
+                statuses.push(serde_json::json!({
+                    "provider": provider,
+                    "secret": name,
+                    "source": source,
+                }));
+            }
+            output.result(&statuses)?;
+        }
+    }
+    Ok(())
+}
+
+/// Read a secret without echoing it, or one line of stdin when it is piped
+fn read_secret(prompt: &str) -> std::io::Result<String> {
+    use std::io::IsTerminal;
+
+    let value = if std::io::stdin().is_terminal() {
+        rpassword::prompt_password(prompt)?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().read_line(&mut line)?;
+        line
+    };
+    Ok(value.trim().to_string())
+}
+
+/// Handle export command by rendering a stored wiki with the wiki exporter
+async fn handle_export(
+    wiki: String,
+    format: wikify_wiki::ExportFormat,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    log_operation_start!("export", wiki = %wiki);
+
+    let wiki_path = PathBuf::from(&wiki);
+    let structure: wikify_wiki::WikiStructure = if wiki_path.is_file() {
+        serde_json::from_str(&tokio::fs::read_to_string(&wiki_path).await?)?
+    } else {
+        wikify_applications::FileWikiStorage::new(base_path().join("wikis"))
+            .load(&wiki)
+            .await?
+            .ok_or_else(|| format!("No stored wiki found for repository: {}", wiki))?
+    };
+
+    let output_path = output.unwrap_or_else(|| {
+        if format.is_single_file() {
+            PathBuf::from(format!("./wiki.{}", format.file_extension()))
+        } else {
+            PathBuf::from("./wiki")
+        }
+    });
+
+    println!("📦 Exporting {} as {:?}...", structure.title, format);
+    wikify_wiki::WikiExporter::new()?
+        .export(&structure, format, &output_path.to_string_lossy())
+        .await?;
+    println!("✅ Wiki exported to: {}", output_path.display());
+
+    log_operation_success!("export", wiki = %wiki);
+    Ok(())
+}
+
+/// Base directory for Wikify data (`WIKIFY_BASE_DIR`, defaulting to `~/.wikify`)
+fn base_path() -> PathBuf {
+    std::env::var("WIKIFY_BASE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".wikify")
+        })
+}
@@ -0,0 +1,264 @@
+//! Text and JSON output of CLI commands
+//!
+//! With `--output json`, `ask`, `diff-ask`, `generate` and `research` print a single
+//! JSON document on stdout when they finish. Status lines and logs go to
+//! stderr then, so the output can be piped into other tools.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Display;
+use wikify_applications::{RepositoryQueryResponse, SourceCitation};
+use wikify_core::{CallbackProgress, ProgressReporter, ProgressUpdate, TerminalProgress};
+
+/// How commands print their results
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON document on stdout, everything else on stderr
+    Json,
+}
+
+impl OutputFormat {
+    pub fn is_json(self) -> bool {
+        self == OutputFormat::Json
+    }
+
+    /// Print a status line, on stderr in JSON mode
+    pub fn status(self, message: impl Display) {
+        match self {
+            OutputFormat::Text => println!("{}", message),
+            OutputFormat::Json => eprintln!("{}", message),
+        }
+    }
+
+    /// Reporter for a long-running operation: a progress bar for text
+    /// output, status lines on stderr in JSON mode
+    pub fn progress(self) -> Box<dyn ProgressReporter> {
+        match self {
+            OutputFormat::Text => Box::new(TerminalProgress::new()),
+            OutputFormat::Json => Box::new(CallbackProgress::new(|update: &ProgressUpdate| {
+                eprintln!("   {:>3.0}% {}", update.progress * 100.0, update)
+            })),
+        }
+    }
+
+    /// Print the result of a command in JSON mode
+    pub fn result<T: Serialize>(self, result: &T) -> serde_json::Result<()> {
+        if self.is_json() {
+            println!("{}", serde_json::to_string_pretty(result)?);
+        }
+        Ok(())
+    }
+}
+
+/// A source an answer or report is based on
+#[derive(Debug, Serialize)]
+pub struct SourceOutput {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+}
+
+impl SourceOutput {
+    /// Sources of an answer, with their line ranges when the answer cites them
+    pub fn from_response(response: &RepositoryQueryResponse) -> Vec<Self> {
+        if response.citations.is_empty() {
+            return Self::zip(&response.sources, &response.source_scores);
+        }
+        Self::from_citations(&response.citations)
+    }
+
+    pub fn from_citations(citations: &[SourceCitation]) -> Vec<Self> {
+        citations
+            .iter()
+            .map(|citation| SourceOutput {
+                path: citation.file_path.clone(),
+                score: Some(citation.score),
+                start_line: citation.start_line,
+                end_line: citation.end_line,
+            })
+            .collect()
+    }
+
+    /// Pair source paths with their scores, as far as scores are known
+    pub fn zip(paths: &[String], scores: &[f32]) -> Vec<Self> {
+        paths
+            .iter()
+            .enumerate()
+            .map(|(i, path)| SourceOutput {
+                path: path.clone(),
+                score: scores.get(i).copied(),
+                start_line: None,
+                end_line: None,
+            })
+            .collect()
+    }
+}
+
+/// Citations of the sources of an answer, falling back to the source paths
+/// for answers without citations
+pub fn citations(response: &RepositoryQueryResponse) -> Vec<SourceCitation> {
+    if !response.citations.is_empty() {
+        return response.citations.clone();
+    }
+    response
+        .sources
+        .iter()
+        .enumerate()
+        .map(|(i, path)| SourceCitation {
+            file_path: path.clone(),
+            start_line: None,
+            end_line: None,
+            score: response.source_scores.get(i).copied().unwrap_or_default(),
+            content: String::new(),
+        })
+        .collect()
+}
+
+/// Print citations as `path:start-end`, with the cited code when
+/// `show_snippets` is set
+pub fn print_citations(citations: &[SourceCitation], show_snippets: bool) {
+    if citations.is_empty() {
+        return;
+    }
+    println!("\n📚 Sources:");
+    for (i, citation) in citations.iter().enumerate() {
+        println!("{}. {}", i + 1, citation.location());
+        if show_snippets && !citation.content.is_empty() {
+            println!("{}", snippet(citation));
+        }
+    }
+}
+
+/// Cited code, indented and numbered with its lines in the file when known
+fn snippet(citation: &SourceCitation) -> String {
+    citation
+        .content
+        .trim_matches('\n')
+        .lines()
+        .enumerate()
+        .map(|(i, line)| match citation.start_line {
+            Some(start) => format!("   {:>5} │ {}", start + i, line),
+            None => format!("   │ {}", line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// How long the steps of a command took, in milliseconds
+#[derive(Debug, Default, Serialize)]
+pub struct Timings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexing_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrieval_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generation_ms: Option<u64>,
+    pub total_ms: u64,
+}
+
+/// Result of `wikify ask`
+#[derive(Debug, Serialize)]
+pub struct AskOutput {
+    pub repository_id: String,
+    pub question: String,
+    pub answer: String,
+    pub sources: Vec<SourceOutput>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    pub timings: Timings,
+}
+
+/// Result of `wikify generate`
+#[derive(Debug, Serialize)]
+pub struct GenerateOutput {
+    pub repository_id: String,
+    pub title: String,
+    pub pages: usize,
+    pub format: String,
+    pub output_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality_score: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published_url: Option<String>,
+    pub timings: Timings,
+}
+
+/// Result of `wikify diff-ask`
+#[derive(Debug, Serialize)]
+pub struct DiffAskOutput {
+    pub repository_id: String,
+    #[serde(flatten)]
+    pub changes: crate::diff_ask::ChangeSet,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub question: Option<String>,
+    pub answer: String,
+    pub sources: Vec<SourceOutput>,
+    pub timings: Timings,
+}
+
+/// One iteration of `wikify research`
+#[cfg(feature = "research")]
+#[derive(Debug, Serialize)]
+pub struct IterationOutput {
+    pub iteration: usize,
+    pub query: String,
+    pub response: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+    pub duration_ms: u64,
+}
+
+/// Result of `wikify research`
+#[cfg(feature = "research")]
+#[derive(Debug, Serialize)]
+pub struct ResearchOutput {
+    pub repository_id: String,
+    pub research_id: String,
+    pub question: String,
+    pub synthesis: String,
+    pub iterations: Vec<IterationOutput>,
+    pub sources: Vec<SourceOutput>,
+    pub report_path: String,
+    pub timings: Timings,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_keep_known_scores() {
+        let sources =
+            SourceOutput::zip(&["src/lib.rs".to_string(), "README.md".to_string()], &[0.5]);
+        let json = serde_json::to_value(&sources).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"path": "src/lib.rs", "score": 0.5},
+                {"path": "README.md"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_snippets_are_numbered_with_file_lines() {
+        let citation = SourceCitation {
+            file_path: "src/auth/mod.rs".to_string(),
+            start_line: Some(120),
+            end_line: Some(121),
+            score: 0.8,
+            content: "pub fn login() {\n}\n".to_string(),
+        };
+        assert_eq!(
+            snippet(&citation),
+            "     120 │ pub fn login() {\n     121 │ }"
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Indexing progress bar
+//!
+//! Commands that need an indexed repository subscribe to the repository
+//! manager's progress channel before adding the repository, then block in
+//! [`wait_for_indexing`] until indexing completes or fails. Updates carry
+//! the current stage (cloning, chunking, embedding, ...) as their message.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use wikify_applications::prelude::*;
+use wikify_applications::{IndexingStatus, RepositoryIndexingUpdate};
+
+/// Progress bar following the indexing of one repository
+pub struct IndexingProgress {
+    repository_id: String,
+    bar: ProgressBar,
+}
+
+impl IndexingProgress {
+    pub fn new(repository_id: &str) -> Self {
+        let bar = ProgressBar::new(100);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>3}% {msg}",
+                )
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        bar.enable_steady_tick(std::time::Duration::from_millis(100));
+        Self::with_bar(repository_id, bar)
+    }
+
+    fn with_bar(repository_id: &str, bar: ProgressBar) -> Self {
+        Self {
+            repository_id: repository_id.to_string(),
+            bar,
+        }
+    }
+
+    /// Show an update, returning the outcome once indexing has finished
+    ///
+    /// Updates of other repositories are ignored.
+    pub fn update(&self, update: &RepositoryIndexingUpdate) -> Option<Result<(), String>> {
+        if update.repository_id != self.repository_id {
+            return None;
+        }
+        self.finish(&update.status, &update.message).or_else(|| {
+            self.bar
+                .set_position((update.progress * 100.0).round() as u64);
+            self.bar.set_message(update.message.clone());
+            None
+        })
+    }
+
+    /// Outcome of a status, if indexing is over
+    fn finish(&self, status: &IndexingStatus, message: &str) -> Option<Result<(), String>> {
+        match status {
+            IndexingStatus::Pending | IndexingStatus::Indexing => None,
+            IndexingStatus::Completed => {
+                self.bar.set_position(100);
+                self.bar.finish_with_message(message.to_string());
+                Some(Ok(()))
+            }
+            IndexingStatus::Failed | IndexingStatus::Cancelled => {
+                self.bar.abandon_with_message(message.to_string());
+                Some(Err(format!("Indexing did not complete: {}", message)))
+            }
+        }
+    }
+}
+
+/// Block until a repository is indexed, showing its progress
+///
+/// `updates` must have been subscribed before the repository was added, so
+/// that the end of a quick indexing run is not missed.
+pub async fn wait_for_indexing(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository_id: &str,
+    mut updates: Receiver<RepositoryIndexingUpdate>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let progress = IndexingProgress::new(repository_id);
+
+    // The repository may have been indexed before
+    let repository = application.get_repository(context, repository_id).await?;
+    progress
+        .bar
+        .set_position((repository.progress * 100.0).round() as u64);
+    let message = if repository.status == IndexingStatus::Completed {
+        "Already indexed"
+    } else {
+        "Indexing stopped earlier"
+    };
+    if let Some(outcome) = progress.finish(&repository.status, message) {
+        return outcome.map_err(Into::into);
+    }
+
+    loop {
+        match updates.recv().await {
+            Ok(update) => {
+                if let Some(outcome) = progress.update(&update) {
+                    return outcome.map_err(Into::into);
+                }
+            }
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => {
+                progress.bar.abandon();
+                return Err("Indexing progress updates stopped".into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(
+        repository_id: &str,
+        status: IndexingStatus,
+        progress: f64,
+    ) -> RepositoryIndexingUpdate {
+        RepositoryIndexingUpdate {
+            repository_id: repository_id.to_string(),
+            status,
+            progress,
+            message: "Chunking: src/lib.rs".to_string(),
+            timestamp: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_updates_move_the_bar_until_indexing_ends() {
+        let progress = IndexingProgress::with_bar("repo-1", ProgressBar::hidden());
+
+        assert_eq!(
+            progress.update(&update("repo-1", IndexingStatus::Indexing, 0.4)),
+            None
+        );
+        assert_eq!(progress.bar.position(), 40);
+        assert_eq!(progress.bar.message(), "Chunking: src/lib.rs");
+
+        // Other repositories don't affect the bar
+        assert_eq!(
+            progress.update(&update("repo-2", IndexingStatus::Completed, 1.0)),
+            None
+        );
+        assert_eq!(progress.bar.position(), 40);
+
+        assert_eq!(
+            progress.update(&update("repo-1", IndexingStatus::Completed, 1.0)),
+            Some(Ok(()))
+        );
+        assert!(progress.bar.is_finished());
+    }
+
+    #[test]
+    fn test_failed_indexing_is_an_error() {
+        let progress = IndexingProgress::with_bar("repo-1", ProgressBar::hidden());
+        let outcome = progress.update(&update("repo-1", IndexingStatus::Failed, 0.0));
+        assert!(matches!(outcome, Some(Err(message)) if message.contains("Chunking")));
+    }
+}
@@ -0,0 +1,284 @@
+//! Client of a wikify web server
+//!
+//! With `--server <url>`, `wikify ask` sends its question to a wikify web
+//! server instead of indexing the repository in-process, so a team can share
+//! the indexes of one server. Repositories the server doesn't know yet are
+//! added and indexed there first. Requests authenticate with the API key
+//! given by `--api-key` or `WIKIFY_API_KEY`.
+
+use crate::output::OutputFormat;
+use crate::progress::IndexingProgress;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::time::Duration;
+use wikify_applications::{IndexingStatus, RepositoryIndexingUpdate, SourceCitation};
+
+/// How often indexing progress is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Repository in `GET /api/repositories`
+#[derive(Debug, Deserialize)]
+struct RepositorySummary {
+    id: String,
+    repository: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RepositoryList {
+    repositories: Vec<RepositorySummary>,
+}
+
+/// Response of `GET /api/repositories/{id}`
+#[derive(Debug, Deserialize)]
+struct RepositoryInfo {
+    status: IndexingStatus,
+    progress: f64,
+}
+
+/// Response of `POST /api/repositories`
+#[derive(Debug, Deserialize)]
+struct InitializedRepository {
+    repository_id: String,
+}
+
+/// Answer of `POST /api/chat`
+#[derive(Debug, Deserialize)]
+pub struct RemoteAnswer {
+    pub answer: String,
+    pub sources: Vec<RemoteSource>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoteSource {
+    pub file_path: String,
+    #[serde(default)]
+    pub content: String,
+    pub similarity_score: f64,
+    #[serde(default)]
+    pub start_line: Option<usize>,
+    #[serde(default)]
+    pub end_line: Option<usize>,
+}
+
+impl From<RemoteSource> for SourceCitation {
+    fn from(source: RemoteSource) -> Self {
+        SourceCitation {
+            file_path: source.file_path,
+            start_line: source.start_line,
+            end_line: source.end_line,
+            score: source.similarity_score as f32,
+            content: source.content,
+        }
+    }
+}
+
+/// Client of the REST API of a wikify server
+pub struct RemoteClient {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+impl RemoteClient {
+    pub fn new(server: &str, api_key: Option<String>) -> Self {
+        Self {
+            base_url: server.trim_end_matches('/').to_string(),
+            api_key,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn server(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Find a repository on the server, adding it if needed, and wait until
+    /// it is indexed
+    pub async fn index_repository(
+        &self,
+        repo: &str,
+        token: Option<String>,
+        output: OutputFormat,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        if std::path::Path::new(repo).exists() {
+            return Err(format!(
+                "{} is a local path, which {} cannot read; pass the URL of the repository",
+                repo, self.base_url
+            )
+            .into());
+        }
+
+        let repositories: RepositoryList = self
+            .send(
+                self.request(Method::GET, "/repositories")
+                    .query(&[("name", repo), ("limit", "500")]),
+            )
+            .await?;
+        let repository_id = match repositories
+            .repositories
+            .into_iter()
+            .find(|repository| repository.repository == repo)
+        {
+            Some(existing) => existing.id,
+            None => {
+                let added: InitializedRepository =
+                    self.send(self.request(Method::POST, "/repositories").json(
+                        &serde_json::json!({
+                            "repository": repo,
+                            "repo_type": crate::repository_type(repo),
+                            "access_token": token,
+                            "auto_index": true,
+                        }),
+                    ))
+                    .await?;
+                output.status(format!("🔄 Indexing repository on {}...", self.base_url));
+                added.repository_id
+            }
+        };
+
+        self.wait_for_indexing(&repository_id).await?;
+        Ok(repository_id)
+    }
+
+    /// Poll a repository until the server has indexed it, showing its progress
+    async fn wait_for_indexing(
+        &self,
+        repository_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let progress = IndexingProgress::new(repository_id);
+        loop {
+            let info: RepositoryInfo = self
+                .send(self.request(Method::GET, &format!("/repositories/{}", repository_id)))
+                .await?;
+            let message = match info.status {
+                IndexingStatus::Completed => "Indexed on the server",
+                IndexingStatus::Failed | IndexingStatus::Cancelled => {
+                    "Indexing stopped on the server"
+                }
+                IndexingStatus::Pending | IndexingStatus::Indexing => "Indexing on the server",
+            };
+            let update = RepositoryIndexingUpdate {
+                repository_id: repository_id.to_string(),
+                status: info.status,
+                progress: info.progress,
+                message: message.to_string(),
+                timestamp: chrono::Utc::now(),
+            };
+            if let Some(outcome) = progress.update(&update) {
+                return outcome.map_err(Into::into);
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Ask a question about an indexed repository
+    pub async fn ask(
+        &self,
+        repository_id: &str,
+        question: &str,
+        max_results: usize,
+        attachment: Option<String>,
+    ) -> Result<RemoteAnswer, Box<dyn std::error::Error>> {
+        self.send(
+            self.request(Method::POST, "/chat")
+                .json(&serde_json::json!({
+                    "repository_id": repository_id,
+                    "question": question,
+                    "context": attachment,
+                    "max_results": max_results,
+                })),
+        )
+        .await
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}/api{}", self.base_url, path));
+        match &self.api_key {
+            Some(api_key) => request.header("x-api-key", api_key),
+            None => request,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<T, Box<dyn std::error::Error>> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| format!("Cannot reach {}: {}", self.base_url, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(error_message(status, body.trim()).into());
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// Message of a failed request
+fn error_message(status: StatusCode, body: &str) -> String {
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => format!(
+            "The server refused the request ({}), check the API key given by --api-key or WIKIFY_API_KEY",
+            status
+        ),
+        _ if body.is_empty() => format!("The server returned {}", status),
+        _ => format!("The server returned {}: {}", status, body),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_responses_are_read() {
+        let info: RepositoryInfo = serde_json::from_value(serde_json::json!({
+            "repository_id": "repo-1",
+            "url": "https://github.com/owner/repo",
+            "status": "indexing",
+            "progress": 0.25,
+        }))
+        .unwrap();
+        assert_eq!(info.status, IndexingStatus::Indexing);
+
+        let answer: RemoteAnswer = serde_json::from_value(serde_json::json!({
+            "answer": "Through the scheduler.",
+            "sources": [{
+                "file_path": "src/lib.rs",
+                "content": "",
+                "similarity_score": 0.8,
+                "start_line": 12,
+                "end_line": 30,
+            }],
+            "repository_id": "repo-1",
+        }))
+        .unwrap();
+        let citation = SourceCitation::from(answer.sources.into_iter().next().unwrap());
+        assert_eq!(citation.location(), "src/lib.rs:12-30");
+
+        assert!(error_message(StatusCode::UNAUTHORIZED, "").contains("--api-key"));
+        assert_eq!(
+            error_message(StatusCode::CONFLICT, "already indexing"),
+            "The server returned 409 Conflict: already indexing"
+        );
+    }
+
+    #[test]
+    fn test_requests_carry_the_api_key() {
+        let client = RemoteClient::new("https://wikify.example.com/", Some("key-1".to_string()));
+        let request = client
+            .request(Method::GET, "/repositories")
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.url().as_str(),
+            "https://wikify.example.com/api/repositories"
+        );
+        assert_eq!(request.headers()["x-api-key"], "key-1");
+    }
+}
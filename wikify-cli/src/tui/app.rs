@@ -0,0 +1,527 @@
+//! State of the terminal UI and how it reacts to keys and updates
+//!
+//! Nothing here touches the terminal or the application: events come in
+//! through [`App::handle_event`], and work that has to be done elsewhere is
+//! returned as an [`Action`].
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+use std::collections::HashMap;
+use wikify_applications::{
+    IndexingStatus, QueryChunkType, QueryStreamChunk, RepositoryIndex, RepositoryIndexingUpdate,
+};
+use wikify_wiki::WikiStructure;
+
+/// Lines the wiki page scrolls by with PageUp/PageDown
+const PAGE_SCROLL: u16 = 10;
+
+/// Updates delivered to the UI loop
+#[derive(Debug)]
+pub enum AppEvent {
+    /// A key was pressed
+    Key(KeyEvent),
+    /// The terminal was resized
+    Redraw,
+    /// The current list of repositories
+    Repositories(Vec<RepositoryIndex>),
+    /// Indexing progress of a repository
+    Indexing(RepositoryIndexingUpdate),
+    /// Part of the answer being streamed
+    Answer(QueryStreamChunk),
+    /// The stored wiki of a repository, if it has one
+    Wiki {
+        repository_id: String,
+        wiki: Option<Box<WikiStructure>>,
+    },
+    /// A message for the status line
+    Status(String),
+}
+
+/// Work the UI asks the event loop to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Quit,
+    RefreshRepositories,
+    AddRepository(String),
+    Reindex(String),
+    Ask {
+        repository_id: String,
+        question: String,
+    },
+    LoadWiki(String),
+    GenerateWiki(String),
+}
+
+/// Part of the screen receiving keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Repositories,
+    Chat,
+    Wiki,
+}
+
+/// A question and its answer
+#[derive(Debug, Clone, Default)]
+pub struct ChatEntry {
+    pub question: String,
+    pub answer: String,
+    pub sources: Vec<String>,
+    pub error: Option<String>,
+    pub done: bool,
+}
+
+#[derive(Debug)]
+pub struct App {
+    pub focus: Focus,
+    /// Pane shown on the right, the chat or the wiki
+    pub pane: Focus,
+    pub repositories: Vec<RepositoryIndex>,
+    pub repository_state: ListState,
+    /// Last indexing message of each repository
+    pub indexing_messages: HashMap<String, String>,
+    /// Text being typed, a question or a repository URL
+    pub input: String,
+    /// Whether the input is the URL of a repository to add
+    pub adding_repository: bool,
+    /// Conversations, by repository ID
+    pub chats: HashMap<String, Vec<ChatEntry>>,
+    /// Repository whose question is being answered
+    pub answering: Option<String>,
+    /// Wiki shown in the wiki pane, with the ID of its repository
+    pub wiki: Option<(String, Option<Box<WikiStructure>>)>,
+    pub page_state: ListState,
+    pub page_scroll: u16,
+    pub status: String,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self {
+            focus: Focus::Repositories,
+            pane: Focus::Chat,
+            repositories: Vec::new(),
+            repository_state: ListState::default(),
+            indexing_messages: HashMap::new(),
+            input: String::new(),
+            adding_repository: false,
+            chats: HashMap::new(),
+            answering: None,
+            wiki: None,
+            page_state: ListState::default(),
+            page_scroll: 0,
+            status: "Press a to add a repository".to_string(),
+        }
+    }
+}
+
+impl App {
+    /// The repository under the cursor
+    pub fn selected_repository(&self) -> Option<&RepositoryIndex> {
+        self.repository_state
+            .selected()
+            .and_then(|index| self.repositories.get(index))
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        self.selected_repository().map(|repo| repo.id.clone())
+    }
+
+    /// The conversation about the selected repository
+    pub fn chat(&self) -> &[ChatEntry] {
+        self.selected_repository()
+            .and_then(|repo| self.chats.get(&repo.id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// The wiki of the selected repository, once loaded
+    pub fn selected_wiki(&self) -> Option<&WikiStructure> {
+        let (repository_id, wiki) = self.wiki.as_ref()?;
+        if Some(repository_id) != self.selected_repository().map(|repo| &repo.id) {
+            return None;
+        }
+        wiki.as_deref()
+    }
+
+    pub fn handle_event(&mut self, event: AppEvent) -> Option<Action> {
+        match event {
+            AppEvent::Key(key) => self.handle_key(key),
+            AppEvent::Redraw => None,
+            AppEvent::Repositories(repositories) => {
+                self.set_repositories(repositories);
+                None
+            }
+            AppEvent::Indexing(update) => self.apply_indexing_update(update),
+            AppEvent::Answer(chunk) => {
+                self.apply_answer_chunk(chunk);
+                None
+            }
+            AppEvent::Wiki {
+                repository_id,
+                wiki,
+            } => {
+                self.wiki = Some((repository_id, wiki));
+                self.page_state.select(Some(0));
+                self.page_scroll = 0;
+                None
+            }
+            AppEvent::Status(status) => {
+                self.status = status;
+                None
+            }
+        }
+    }
+
+    /// Replace the repository list, keeping the cursor on the same repository
+    fn set_repositories(&mut self, repositories: Vec<RepositoryIndex>) {
+        let selected = self.selected_id();
+        self.repositories = repositories;
+        let index = selected
+            .and_then(|id| self.repositories.iter().position(|repo| repo.id == id))
+            .or((!self.repositories.is_empty()).then_some(0));
+        self.repository_state.select(index);
+    }
+
+    fn apply_indexing_update(&mut self, update: RepositoryIndexingUpdate) -> Option<Action> {
+        let Some(repo) = self
+            .repositories
+            .iter_mut()
+            .find(|repo| repo.id == update.repository_id)
+        else {
+            // Added by someone else, so the list is out of date
+            return Some(Action::RefreshRepositories);
+        };
+
+        repo.status = update.status;
+        repo.progress = update.progress;
+        self.indexing_messages
+            .insert(update.repository_id, update.message);
+        None
+    }
+
+    fn apply_answer_chunk(&mut self, chunk: QueryStreamChunk) {
+        let Some(entry) = self
+            .answering
+            .as_ref()
+            .and_then(|id| self.chats.get_mut(id))
+            .and_then(|chat| chat.last_mut())
+        else {
+            return;
+        };
+
+        match chunk.chunk_type {
+            QueryChunkType::Content => entry.answer.push_str(&chunk.content),
+            QueryChunkType::Source => entry.sources.push(chunk.content),
+            QueryChunkType::Error => entry.error = Some(chunk.content),
+            QueryChunkType::Complete => {}
+        }
+        if let Some(sources) = chunk.sources {
+            entry.sources = sources;
+        }
+        if chunk.is_final {
+            entry.done = true;
+            self.answering = None;
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Action::Quit);
+        }
+        if self.adding_repository {
+            return self.handle_repository_input(key);
+        }
+
+        match key.code {
+            KeyCode::Tab => return self.set_focus(self.next_focus()),
+            KeyCode::BackTab => return self.set_focus(self.previous_focus()),
+            _ => {}
+        }
+        match self.focus {
+            Focus::Repositories => self.handle_repositories_key(key),
+            Focus::Chat => self.handle_chat_key(key),
+            Focus::Wiki => self.handle_wiki_key(key),
+        }
+    }
+
+    fn next_focus(&self) -> Focus {
+        match self.focus {
+            Focus::Repositories => Focus::Chat,
+            Focus::Chat => Focus::Wiki,
+            Focus::Wiki => Focus::Repositories,
+        }
+    }
+
+    fn previous_focus(&self) -> Focus {
+        match self.focus {
+            Focus::Repositories => Focus::Wiki,
+            Focus::Chat => Focus::Repositories,
+            Focus::Wiki => Focus::Chat,
+        }
+    }
+
+    /// Move the focus, loading the wiki when its pane is opened
+    fn set_focus(&mut self, focus: Focus) -> Option<Action> {
+        self.focus = focus;
+        if focus != Focus::Repositories {
+            self.pane = focus;
+        }
+        if self.pane == Focus::Wiki {
+            return self.load_wiki();
+        }
+        None
+    }
+
+    /// Ask for the wiki of the selected repository unless it is shown already
+    fn load_wiki(&mut self) -> Option<Action> {
+        let id = self.selected_id()?;
+        if self.wiki.as_ref().map(|(wiki_id, _)| wiki_id) == Some(&id) {
+            return None;
+        }
+        self.wiki = None;
+        Some(Action::LoadWiki(id))
+    }
+
+    fn handle_repository_input(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Enter => {
+                self.adding_repository = false;
+                let url = std::mem::take(&mut self.input).trim().to_string();
+                (!url.is_empty()).then_some(Action::AddRepository(url))
+            }
+            KeyCode::Esc => {
+                self.adding_repository = false;
+                self.input.clear();
+                None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn handle_repositories_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char('q') => Some(Action::Quit),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Char('a') => {
+                self.adding_repository = true;
+                self.input.clear();
+                None
+            }
+            KeyCode::Char('r') => self.selected_id().map(Action::Reindex),
+            KeyCode::Char('g') => self.selected_id().map(Action::GenerateWiki),
+            KeyCode::Char('u') => Some(Action::RefreshRepositories),
+            KeyCode::Enter => self.set_focus(Focus::Chat),
+            _ => None,
+        }
+    }
+
+    fn move_selection(&mut self, delta: isize) -> Option<Action> {
+        if self.repositories.is_empty() {
+            return None;
+        }
+        let last = self.repositories.len() - 1;
+        let current = self.repository_state.selected().unwrap_or(0);
+        let next = current.saturating_add_signed(delta).min(last);
+        self.repository_state.select(Some(next));
+        if self.pane == Focus::Wiki {
+            return self.load_wiki();
+        }
+        None
+    }
+
+    fn handle_chat_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Esc => self.set_focus(Focus::Repositories),
+            KeyCode::Backspace => {
+                self.input.pop();
+                None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                None
+            }
+            KeyCode::Enter => self.ask(),
+            _ => None,
+        }
+    }
+
+    /// Send the typed question about the selected repository
+    fn ask(&mut self) -> Option<Action> {
+        let question = self.input.trim().to_string();
+        if question.is_empty() {
+            return None;
+        }
+        if self.answering.is_some() {
+            self.status = "Wait for the current answer to finish".to_string();
+            return None;
+        }
+        let Some(repo) = self.selected_repository() else {
+            self.status = "Select a repository to ask about".to_string();
+            return None;
+        };
+        if repo.status != IndexingStatus::Completed {
+            self.status = "The repository is not indexed yet".to_string();
+            return None;
+        }
+
+        let repository_id = repo.id.clone();
+        self.input.clear();
+        self.chats
+            .entry(repository_id.clone())
+            .or_default()
+            .push(ChatEntry {
+                question: question.clone(),
+                ..Default::default()
+            });
+        self.answering = Some(repository_id.clone());
+        Some(Action::Ask {
+            repository_id,
+            question,
+        })
+    }
+
+    fn handle_wiki_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let page_count = self.selected_wiki().map_or(0, |wiki| wiki.pages.len());
+        match key.code {
+            KeyCode::Char('q') => return Some(Action::Quit),
+            KeyCode::Esc => return self.set_focus(Focus::Repositories),
+            KeyCode::Char('g') => return self.selected_id().map(Action::GenerateWiki),
+            KeyCode::Up | KeyCode::Char('k') if page_count > 0 => {
+                let current = self.page_state.selected().unwrap_or(0);
+                self.page_state.select(Some(current.saturating_sub(1)));
+                self.page_scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') if page_count > 0 => {
+                let current = self.page_state.selected().unwrap_or(0);
+                self.page_state
+                    .select(Some((current + 1).min(page_count - 1)));
+                self.page_scroll = 0;
+            }
+            KeyCode::PageDown => self.page_scroll = self.page_scroll.saturating_add(PAGE_SCROLL),
+            KeyCode::PageUp => self.page_scroll = self.page_scroll.saturating_sub(PAGE_SCROLL),
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn repository(id: &str, status: IndexingStatus) -> RepositoryIndex {
+        RepositoryIndex {
+            id: id.to_string(),
+            url: format!("https://github.com/example/{}", id),
+            repo_type: "github".to_string(),
+            status,
+            progress: 0.0,
+            created_at: Utc::now(),
+            indexed_at: None,
+            updated_at: Utc::now(),
+            owner_id: None,
+            organization_id: None,
+            visibility: Default::default(),
+            share_grants: Vec::new(),
+            disk_usage: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn key(code: KeyCode) -> AppEvent {
+        AppEvent::Key(KeyEvent::from(code))
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_adding_a_repository() {
+        let mut app = App::default();
+        assert_eq!(app.handle_event(key(KeyCode::Char('a'))), None);
+        type_text(&mut app, "https://github.com/example/repo");
+        assert_eq!(
+            app.handle_event(key(KeyCode::Enter)),
+            Some(Action::AddRepository(
+                "https://github.com/example/repo".to_string()
+            ))
+        );
+        assert!(!app.adding_repository);
+        assert!(app.input.is_empty());
+    }
+
+    #[test]
+    fn test_asking_streams_the_answer_into_the_chat() {
+        let mut app = App::default();
+        app.handle_event(AppEvent::Repositories(vec![repository(
+            "repo",
+            IndexingStatus::Completed,
+        )]));
+        app.handle_event(key(KeyCode::Tab));
+        type_text(&mut app, "How does it work?");
+        assert_eq!(
+            app.handle_event(key(KeyCode::Enter)),
+            Some(Action::Ask {
+                repository_id: "repo".to_string(),
+                question: "How does it work?".to_string(),
+            })
+        );
+
+        for (chunk_type, content, is_final) in [
+            (QueryChunkType::Content, "It ", false),
+            (QueryChunkType::Content, "works.", false),
+            (QueryChunkType::Complete, "", true),
+        ] {
+            app.handle_event(AppEvent::Answer(QueryStreamChunk {
+                chunk_type,
+                content: content.to_string(),
+                is_final,
+                sources: is_final.then(|| vec!["src/lib.rs".to_string()]),
+                metadata: None,
+            }));
+        }
+
+        let entry = &app.chat()[0];
+        assert_eq!(entry.answer, "It works.");
+        assert_eq!(entry.sources, vec!["src/lib.rs".to_string()]);
+        assert!(entry.done);
+        assert!(app.answering.is_none());
+    }
+
+    #[test]
+    fn test_indexing_updates_progress() {
+        let mut app = App::default();
+        app.handle_event(AppEvent::Repositories(vec![repository(
+            "repo",
+            IndexingStatus::Pending,
+        )]));
+        let update = RepositoryIndexingUpdate::progress(
+            "repo".to_string(),
+            0.5,
+            "Embedding chunks".to_string(),
+        );
+        assert_eq!(app.handle_event(AppEvent::Indexing(update)), None);
+        assert_eq!(app.repositories[0].status, IndexingStatus::Indexing);
+        assert_eq!(app.repositories[0].progress, 0.5);
+
+        let unknown =
+            RepositoryIndexingUpdate::progress("other".to_string(), 0.1, "Cloning".to_string());
+        assert_eq!(
+            app.handle_event(AppEvent::Indexing(unknown)),
+            Some(Action::RefreshRepositories)
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Terminal user interface
+//!
+//! `wikify tui` runs the application in this process and shows the
+//! repositories with their indexing status, a chat pane and the generated
+//! wikis. Indexing progress comes live from the repository manager's
+//! broadcast channel.
+//!
+//! Keys, progress updates and the results of background work all arrive as
+//! [`AppEvent`]s on one channel; the loop redraws after each of them.
+
+mod app;
+mod ui;
+
+use app::{Action, App, AppEvent};
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc::UnboundedSender};
+use tracing::{info, warn};
+use wikify_applications::prelude::*;
+use wikify_applications::{
+    PageRequest, QueryChunkType, QueryStreamChunk, RepositoryFilter, RepositoryOptions,
+};
+use wikify_core::{CallbackProgress, ProgressUpdate};
+
+/// How long the key reader waits for input before checking the UI is still running
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+type Events = UnboundedSender<AppEvent>;
+
+/// Run the terminal UI until the user quits
+pub async fn run(
+    application: Arc<WikifyApplication>,
+    context: PermissionContext,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (events, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    spawn_input_reader(events.clone());
+    spawn_progress_forwarder(&application, events.clone());
+    refresh_repositories(&application, &context, &events);
+
+    let mut terminal = ratatui::init();
+    let mut app = App::default();
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| ui::draw(frame, &mut app)) {
+            break Err(e.into());
+        }
+        let Some(event) = receiver.recv().await else {
+            break Ok(());
+        };
+        match app.handle_event(event) {
+            Some(Action::Quit) => break Ok(()),
+            Some(action) => perform(action, &application, &context, &events),
+            None => {}
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+/// Read keys on a thread of their own, since reading blocks
+fn spawn_input_reader(events: Events) {
+    std::thread::spawn(move || {
+        while !events.is_closed() {
+            match event::poll(INPUT_POLL_INTERVAL) {
+                Ok(false) => continue,
+                Ok(true) => {}
+                Err(e) => {
+                    warn!("Failed to read terminal input: {}", e);
+                    break;
+                }
+            }
+            let event = match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => AppEvent::Key(key),
+                Ok(Event::Resize(..)) => AppEvent::Redraw,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Failed to read terminal input: {}", e);
+                    break;
+                }
+            };
+            if events.send(event).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Pass indexing progress from the repository manager on to the UI
+fn spawn_progress_forwarder(application: &WikifyApplication, events: Events) {
+    let mut updates = application.subscribe_to_repository_progress();
+    tokio::spawn(async move {
+        loop {
+            match updates.recv().await {
+                Ok(update) => {
+                    if events.send(AppEvent::Indexing(update)).is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Missed {} indexing updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+fn refresh_repositories(
+    application: &Arc<WikifyApplication>,
+    context: &PermissionContext,
+    events: &Events,
+) {
+    let (application, context, events) = (application.clone(), context.clone(), events.clone());
+    tokio::spawn(async move {
+        let event = match application
+            .list_repositories(
+                &context,
+                &RepositoryFilter::default(),
+                PageRequest::default(),
+            )
+            .await
+        {
+            Ok(page) => AppEvent::Repositories(page.items),
+            Err(e) => AppEvent::Status(format!("Failed to list repositories: {}", e)),
+        };
+        let _ = events.send(event);
+    });
+}
+
+/// Start the work an action asks for; results arrive as events
+fn perform(
+    action: Action,
+    application: &Arc<WikifyApplication>,
+    context: &PermissionContext,
+    events: &Events,
+) {
+    if action == Action::RefreshRepositories {
+        refresh_repositories(application, context, events);
+        return;
+    }
+
+    let (application, context, events) = (application.clone(), context.clone(), events.clone());
+    tokio::spawn(async move {
+        match action {
+            Action::Quit | Action::RefreshRepositories => {}
+            Action::AddRepository(url) => {
+                let _ = events.send(AppEvent::Status(format!("Adding {}…", url)));
+                let repo_type = crate::repository_type(&url).to_string();
+                let status = match application
+                    .add_repository(
+                        &context,
+                        url.clone(),
+                        repo_type,
+                        RepositoryOptions::default(),
+                    )
+                    .await
+                {
+                    Ok(repository_id) => {
+                        info!("Added repository {} ({})", url, repository_id);
+                        format!("Added {}, indexing…", url)
+                    }
+                    Err(e) => format!("Failed to add {}: {}", url, e),
+                };
+                let _ = events.send(AppEvent::Status(status));
+                refresh_repositories(&application, &context, &events);
+            }
+            Action::Reindex(repository_id) => {
+                let status = match application
+                    .reindex_repository(&context, &repository_id)
+                    .await
+                {
+                    Ok(()) => "Reindexing…".to_string(),
+                    Err(e) => format!("Failed to reindex: {}", e),
+                };
+                let _ = events.send(AppEvent::Status(status));
+            }
+            Action::Ask {
+                repository_id,
+                question,
+            } => {
+                let query = RepositoryQuery {
+                    question,
+                    max_results: None,
+                    parameters: None,
+                };
+                match application
+                    .stream_query_repository(&context, &repository_id, query)
+                    .await
+                {
+                    Ok(mut chunks) => {
+                        while let Some(chunk) = chunks.recv().await {
+                            if events.send(AppEvent::Answer(chunk)).is_err() {
+                                break;
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = events.send(AppEvent::Answer(QueryStreamChunk {
+                            chunk_type: QueryChunkType::Error,
+                            content: format!("Query failed: {}", e),
+                            is_final: true,
+                            sources: None,
+                            metadata: None,
+                        }));
+                    }
+                }
+            }
+            Action::LoadWiki(repository_id) => {
+                match application.get_wiki(&context, &repository_id).await {
+                    Ok(wiki) => {
+                        let _ = events.send(AppEvent::Wiki {
+                            repository_id,
+                            wiki: wiki.map(Box::new),
+                        });
+                    }
+                    Err(e) => {
+                        let _ =
+                            events.send(AppEvent::Status(format!("Failed to load wiki: {}", e)));
+                    }
+                }
+            }
+            Action::GenerateWiki(repository_id) => {
+                let status = events.clone();
+                let progress = CallbackProgress::new(move |update: &ProgressUpdate| {
+                    let _ = status.send(AppEvent::Status(format!(
+                        "{:>3.0}% {}",
+                        update.progress * 100.0,
+                        update
+                    )));
+                });
+                let result = application
+                    .generate_wiki(
+                        &context,
+                        &repository_id,
+                        wikify_wiki::WikiConfig::default(),
+                        &progress,
+                    )
+                    .await;
+                match result {
+                    Ok(wiki) => {
+                        let _ = events.send(AppEvent::Status(format!(
+                            "Generated {} wiki pages",
+                            wiki.pages.len()
+                        )));
+                        let _ = events.send(AppEvent::Wiki {
+                            repository_id,
+                            wiki: Some(Box::new(wiki)),
+                        });
+                    }
+                    Err(e) => {
+                        let _ = events
+                            .send(AppEvent::Status(format!("Failed to generate wiki: {}", e)));
+                    }
+                }
+            }
+        }
+    });
+}
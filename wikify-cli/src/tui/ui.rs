@@ -0,0 +1,258 @@
+//! Drawing the terminal UI
+
+use super::app::{App, Focus};
+use ratatui::{
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Tabs, Wrap},
+    Frame,
+};
+use wikify_applications::{IndexingStatus, RepositoryIndex};
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, input, footer] = Layout::vertical([
+        Constraint::Min(5),
+        Constraint::Length(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [left, right] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+
+    draw_repositories(frame, app, left);
+    let [tabs, pane] = Layout::vertical([Constraint::Length(1), Constraint::Min(3)]).areas(right);
+    let selected_tab = if app.pane == Focus::Wiki { 1 } else { 0 };
+    frame.render_widget(
+        Tabs::new(["Chat", "Wiki"])
+            .select(selected_tab)
+            .highlight_style(Style::new().bold().fg(Color::Cyan)),
+        tabs,
+    );
+    if app.pane == Focus::Wiki {
+        draw_wiki(frame, app, pane);
+    } else {
+        draw_chat(frame, app, pane);
+    }
+    draw_input(frame, app, input);
+    draw_footer(frame, app, footer);
+}
+
+fn block(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused {
+        Style::new().fg(Color::Cyan)
+    } else {
+        Style::new()
+    };
+    Block::new()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title)
+}
+
+/// Short name of a repository, such as `owner/repo` for a GitHub URL
+fn repository_name(repo: &RepositoryIndex) -> String {
+    let url = repo.url.trim_end_matches('/').trim_end_matches(".git");
+    let mut parts = url.rsplit(['/', '\\']).filter(|part| !part.is_empty());
+    match (parts.next(), parts.next()) {
+        (Some(name), Some(owner)) if repo.repo_type != "local" => format!("{}/{}", owner, name),
+        (Some(name), _) => name.to_string(),
+        _ => repo.url.clone(),
+    }
+}
+
+fn status_span(status: &IndexingStatus) -> Span<'static> {
+    match status {
+        IndexingStatus::Pending => "·".dark_gray(),
+        IndexingStatus::Indexing => "⟳".yellow(),
+        IndexingStatus::Completed => "✓".green(),
+        IndexingStatus::Failed => "✗".red(),
+        IndexingStatus::Cancelled => "-".dark_gray(),
+    }
+}
+
+fn draw_repositories(frame: &mut Frame, app: &mut App, area: Rect) {
+    let selected_indexing = app
+        .selected_repository()
+        .filter(|repo| repo.status == IndexingStatus::Indexing)
+        .map(|repo| (repo.progress, app.indexing_messages.get(&repo.id).cloned()));
+    let (list_area, gauge_area) = if selected_indexing.is_some() {
+        let [list, gauge] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(area);
+        (list, Some(gauge))
+    } else {
+        (area, None)
+    };
+
+    let items: Vec<ListItem> = app
+        .repositories
+        .iter()
+        .map(|repo| {
+            let mut line = vec![
+                status_span(&repo.status),
+                Span::raw(" "),
+                Span::raw(repository_name(repo)),
+            ];
+            if repo.status == IndexingStatus::Indexing {
+                line.push(format!(" {:.0}%", repo.progress * 100.0).yellow());
+            }
+            ListItem::new(Line::from(line))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block(
+            "Repositories",
+            app.focus == Focus::Repositories || app.adding_repository,
+        ))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, list_area, &mut app.repository_state);
+
+    if let (Some((progress, message)), Some(gauge_area)) = (selected_indexing, gauge_area) {
+        let gauge = Gauge::default()
+            .block(Block::new().borders(Borders::ALL).title("Indexing"))
+            .gauge_style(Style::new().fg(Color::Yellow))
+            .ratio(progress.clamp(0.0, 1.0))
+            .label(message.unwrap_or_else(|| format!("{:.0}%", progress * 100.0)));
+        frame.render_widget(gauge, gauge_area);
+    }
+}
+
+/// Number of rows text takes up when wrapped to `width` columns
+fn wrapped_height(text: &Text, width: u16) -> u16 {
+    let width = width.max(1) as usize;
+    text.lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(width))
+        .sum::<usize>()
+        .min(u16::MAX as usize) as u16
+}
+
+fn draw_chat(frame: &mut Frame, app: &App, area: Rect) {
+    let mut lines = Vec::new();
+    for entry in app.chat() {
+        lines.push(Line::from(vec!["> ".cyan(), entry.question.clone().bold()]));
+        lines.extend(entry.answer.lines().map(|line| Line::raw(line.to_string())));
+        if let Some(error) = &entry.error {
+            lines.push(Line::from(error.clone().red()));
+        } else if !entry.done {
+            lines.push(Line::from("…".dark_gray()));
+        }
+        if !entry.sources.is_empty() {
+            lines.push(Line::from(
+                format!("Sources: {}", entry.sources.join(", ")).dark_gray(),
+            ));
+        }
+        lines.push(Line::default());
+    }
+    if lines.is_empty() {
+        let hint = match app.selected_repository() {
+            Some(repo) if repo.status == IndexingStatus::Completed => {
+                "Ask a question about the repository below"
+            }
+            Some(_) => "Questions can be asked once the repository is indexed",
+            None => "Add a repository to ask questions about it",
+        };
+        lines.push(Line::from(hint.dark_gray()));
+    }
+
+    let text = Text::from(lines);
+    let inner_height = area.height.saturating_sub(2);
+    // Keep the end of the conversation in view
+    let scroll = wrapped_height(&text, area.width.saturating_sub(2)).saturating_sub(inner_height);
+    let chat = Paragraph::new(text)
+        .block(block("Chat", app.focus == Focus::Chat))
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(chat, area);
+}
+
+fn draw_wiki(frame: &mut Frame, app: &mut App, area: Rect) {
+    let focused = app.focus == Focus::Wiki;
+    let Some(wiki) = app.selected_wiki() else {
+        let selected = app.selected_repository().map(|repo| &repo.id);
+        let message = match &app.wiki {
+            _ if selected.is_none() => "Select a repository to see its wiki",
+            Some((id, None)) if Some(id) == selected => {
+                "No wiki generated yet. Press g to generate it."
+            }
+            _ => "Loading wiki…",
+        };
+        frame.render_widget(
+            Paragraph::new(message.dark_gray()).block(block("Wiki", focused)),
+            area,
+        );
+        return;
+    };
+
+    let [pages_area, page_area] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(area);
+    let items: Vec<ListItem> = wiki
+        .pages
+        .iter()
+        .map(|page| ListItem::new(page.title.clone()))
+        .collect();
+    let page = app
+        .page_state
+        .selected()
+        .and_then(|index| wiki.pages.get(index));
+    let content = Paragraph::new(page.map(|page| page.content.clone()).unwrap_or_default())
+        .block(block(
+            page.map_or(wiki.title.as_str(), |page| page.title.as_str()),
+            false,
+        ))
+        .wrap(Wrap { trim: false })
+        .scroll((app.page_scroll, 0));
+    let pages = List::new(items)
+        .block(block(&wiki.title, focused))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    frame.render_widget(content, page_area);
+    let mut page_state = app.page_state.clone();
+    frame.render_stateful_widget(pages, pages_area, &mut page_state);
+    app.page_state = page_state;
+}
+
+fn draw_input(frame: &mut Frame, app: &App, area: Rect) {
+    let (title, active) = if app.adding_repository {
+        ("Repository URL or local path", true)
+    } else {
+        ("Question", app.focus == Focus::Chat)
+    };
+    let text = if active || !app.input.is_empty() {
+        app.input.as_str()
+    } else {
+        ""
+    };
+    frame.render_widget(Paragraph::new(text).block(block(title, active)), area);
+    if active {
+        let cursor_x = area.x + 1 + app.input.chars().count().min(u16::MAX as usize) as u16;
+        frame.set_cursor_position(Position::new(
+            cursor_x.min(area.right().saturating_sub(2)),
+            area.y + 1,
+        ));
+    }
+}
+
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let keys = if app.adding_repository {
+        "Enter add · Esc cancel"
+    } else {
+        match app.focus {
+            Focus::Repositories => {
+                "↑↓ select · a add · r reindex · g generate wiki · u refresh · Tab switch · q quit"
+            }
+            Focus::Chat => "Enter ask · Esc back · Tab switch · Ctrl-C quit",
+            Focus::Wiki => "↑↓ page · PgUp/PgDn scroll · g regenerate · Tab switch · q quit",
+        }
+    };
+    let [keys_area, status_area] = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(app.status.chars().count() as u16 + 1),
+    ])
+    .areas(area);
+    frame.render_widget(Paragraph::new(keys.dark_gray()), keys_area);
+    frame.render_widget(
+        Paragraph::new(app.status.as_str()).right_aligned(),
+        status_area,
+    );
+}
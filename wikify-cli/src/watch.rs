@@ -0,0 +1,212 @@
+//! Watching a local repository
+//!
+//! `wikify watch` indexes a local repository, then updates the index for the
+//! files that change instead of indexing everything again. Changes are
+//! collected until the repository has been quiet for a moment, so saving
+//! many files at once (a checkout, a formatter run) results in one update.
+//! With `--wiki`, the wiki pages generated from the changed files are
+//! regenerated after each update.
+
+use crate::output::OutputFormat;
+use notify::{EventKind, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use wikify_applications::prelude::*;
+
+/// Directories whose changes never affect the index
+const IGNORED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// What one update changed, as printed in JSON mode
+#[derive(Debug, Serialize)]
+struct WatchUpdate<'a> {
+    #[serde(flatten)]
+    index: &'a wikify_rag::IndexUpdate,
+    regenerated_pages: Vec<String>,
+}
+
+/// Update the index of a repository whenever its files change, until Ctrl-C
+///
+/// The repository must have been indexed by this process.
+pub async fn watch(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository_id: &str,
+    root: &Path,
+    debounce: Duration,
+    regenerate_wiki: bool,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (changes, mut changed_paths) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event) if !matches!(event.kind, EventKind::Access(_)) => {
+                for path in event.paths {
+                    let _ = changes.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("File watcher error: {}", e),
+        })?;
+    watcher.watch(root, RecursiveMode::Recursive)?;
+    output.status(format!(
+        "👀 Watching {} for changes (Ctrl-C to stop)",
+        root.display()
+    ));
+
+    loop {
+        let first = tokio::select! {
+            path = changed_paths.recv() => path,
+            _ = tokio::signal::ctrl_c() => None,
+        };
+        let Some(first) = first else {
+            break;
+        };
+        // Wait until changes stop for a moment
+        let mut changed = BTreeSet::from([first]);
+        while let Ok(Some(path)) = tokio::time::timeout(debounce, changed_paths.recv()).await {
+            changed.insert(path);
+        }
+        let changed: Vec<PathBuf> = changed
+            .into_iter()
+            .filter(|path| !is_ignored(root, path))
+            .collect();
+        if changed.is_empty() {
+            continue;
+        }
+
+        let update = match application
+            .update_repository_files(context, repository_id, &changed)
+            .await
+        {
+            Ok(update) if update.is_empty() => continue,
+            Ok(update) => update,
+            Err(e) => {
+                output.status(format!("❌ Failed to update the index: {}", e));
+                continue;
+            }
+        };
+        output.status(format!("🔄 {}", update.summary()));
+        for file in &update.updated_files {
+            output.status(format!("   ~ {}", file));
+        }
+        for file in &update.removed_files {
+            output.status(format!("   - {}", file));
+        }
+
+        let regenerated_pages = if regenerate_wiki {
+            let changed_files: Vec<&str> = update
+                .updated_files
+                .iter()
+                .chain(&update.removed_files)
+                .map(String::as_str)
+                .collect();
+            regenerate_pages(application, context, repository_id, &changed_files, output).await
+        } else {
+            Vec::new()
+        };
+        output.result(&WatchUpdate {
+            index: &update,
+            regenerated_pages,
+        })?;
+    }
+
+    output.status("👋 Stopped watching");
+    Ok(())
+}
+
+/// Regenerate the wiki pages built from changed files, returning their titles
+async fn regenerate_pages(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository_id: &str,
+    changed_files: &[&str],
+    output: OutputFormat,
+) -> Vec<String> {
+    let wiki = match application.get_wiki(context, repository_id).await {
+        Ok(Some(wiki)) => wiki,
+        Ok(None) => return Vec::new(),
+        Err(e) => {
+            output.status(format!("❌ Failed to load the wiki: {}", e));
+            return Vec::new();
+        }
+    };
+
+    let mut regenerated = Vec::new();
+    for page_id in affected_pages(&wiki, changed_files) {
+        match application
+            .regenerate_wiki_page(context, repository_id, &page_id, None)
+            .await
+        {
+            Ok(page) => {
+                output.status(format!("📝 Regenerated wiki page: {}", page.title));
+                regenerated.push(page.title);
+            }
+            Err(e) => output.status(format!("❌ Failed to regenerate {}: {}", page_id, e)),
+        }
+    }
+    regenerated
+}
+
+/// IDs of the wiki pages generated from any of the changed files
+fn affected_pages(wiki: &wikify_wiki::WikiStructure, changed_files: &[&str]) -> Vec<String> {
+    wiki.pages
+        .iter()
+        .filter(|page| {
+            page.file_paths.iter().any(|file_path| {
+                changed_files
+                    .iter()
+                    .any(|changed| Path::new(file_path) == Path::new(changed))
+            })
+        })
+        .map(|page| page.id.clone())
+        .collect()
+}
+
+/// Whether a change is inside a directory that is never indexed
+fn is_ignored(root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .map(|relative| {
+            relative.components().any(|component| {
+                IGNORED_DIRS.contains(&component.as_os_str().to_string_lossy().as_ref())
+            })
+        })
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_outside_indexed_files_are_ignored() {
+        let root = Path::new("/work/project");
+        assert!(!is_ignored(root, &root.join("src/lib.rs")));
+        assert!(is_ignored(root, &root.join(".git/index")));
+        assert!(is_ignored(root, &root.join("target/debug/build")));
+        assert!(is_ignored(root, Path::new("/elsewhere/file.rs")));
+    }
+
+    #[test]
+    fn test_pages_built_from_changed_files_are_affected() {
+        let page = |id: &str, file_paths: &[&str]| {
+            let mut page =
+                wikify_wiki::WikiPage::new(id.to_string(), id.to_string(), String::new());
+            page.file_paths = file_paths.iter().map(|path| path.to_string()).collect();
+            page
+        };
+        let mut wiki = wikify_wiki::WikiStructure::new(
+            "Project".to_string(),
+            String::new(),
+            "/work/project".to_string(),
+        );
+        wiki.pages = vec![
+            page("indexing", &["src/index.rs", "src/chunks.rs"]),
+            page("cli", &["src/main.rs"]),
+        ];
+
+        assert_eq!(affected_pages(&wiki, &["src/chunks.rs"]), ["indexing"]);
+        assert!(affected_pages(&wiki, &["README.md"]).is_empty());
+    }
+}
@@ -35,6 +35,28 @@ impl Default for RetryConfig {
     }
 }
 
+impl RetryConfig {
+    /// Delay before the given retry (1 for the first), without jitter
+    pub fn delay_ms(&self, retry: usize) -> u64 {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.initial_delay_ms as f64 * self.backoff_multiplier.powi(exponent);
+        (delay as u64).min(self.max_delay_ms)
+    }
+
+    /// Delay before the given retry, with up to 10% jitter either way if
+    /// enabled so clients failing together don't retry together
+    fn jittered_delay_ms(&self, retry: usize) -> u64 {
+        let delay = self.delay_ms(retry);
+        if self.jitter {
+            let jitter_factor = 0.1;
+            let jitter = (fastrand::f64() - 0.5) * 2.0 * jitter_factor;
+            ((delay as f64) * (1.0 + jitter)) as u64
+        } else {
+            delay
+        }
+    }
+}
+
 /// Retry an async operation with exponential backoff
 pub async fn retry_async<F, T, E>(
     operation: F,
@@ -44,9 +66,29 @@ pub async fn retry_async<F, T, E>(
 where
     F: Fn() -> BoxFuture<'static, Result<T, E>>,
     E: std::error::Error + Send + Sync + 'static,
+{
+    retry_with_backoff(&config, operation_name, |_| true, operation).await
+}
+
+/// Retry an async operation with exponential backoff and jitter while
+/// `should_retry` considers its error transient
+///
+/// Errors `should_retry` rejects, such as authentication failures, are
+/// returned at once. [`WikifyError::is_recoverable`] is the usual predicate
+/// for operations returning [`WikifyResult`].
+pub async fn retry_with_backoff<F, Fut, T, E, P>(
+    config: &RetryConfig,
+    operation_name: &str,
+    should_retry: P,
+    mut operation: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+    P: Fn(&E) -> bool,
 {
     let mut attempt = 0;
-    let mut delay = config.initial_delay_ms;
 
     loop {
         attempt += 1;
@@ -69,6 +111,7 @@ where
                 }
                 return Ok(result);
             }
+            Err(error) if !should_retry(&error) => return Err(error),
             Err(error) => {
                 if attempt >= config.max_attempts {
                     error!(
@@ -80,6 +123,7 @@ where
                     return Err(error);
                 }
 
+                let delay = config.jittered_delay_ms(attempt);
                 warn!(
                     operation = operation_name,
                     attempt = attempt,
@@ -88,19 +132,7 @@ where
                     "Operation failed, retrying"
                 );
 
-                // Calculate next delay with exponential backoff
-                let actual_delay = if config.jitter {
-                    let jitter_factor = 0.1;
-                    let jitter = (fastrand::f64() - 0.5) * 2.0 * jitter_factor;
-                    ((delay as f64) * (1.0 + jitter)) as u64
-                } else {
-                    delay
-                };
-
-                sleep(Duration::from_millis(actual_delay)).await;
-
-                delay = ((delay as f64) * config.backoff_multiplier) as u64;
-                delay = delay.min(config.max_delay_ms);
+                sleep(Duration::from_millis(delay)).await;
             }
         }
     }
@@ -327,3 +359,81 @@ impl<T> Drop for BatchProcessor<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn quick_retries(max_attempts: usize) -> RetryConfig {
+        RetryConfig {
+            max_attempts,
+            initial_delay_ms: 1,
+            max_delay_ms: 5,
+            backoff_multiplier: 2.0,
+            jitter: true,
+        }
+    }
+
+    #[test]
+    fn test_delays_grow_up_to_the_maximum() {
+        let config = RetryConfig {
+            jitter: false,
+            ..RetryConfig::default()
+        };
+        assert_eq!(config.delay_ms(1), 1000);
+        assert_eq!(config.delay_ms(2), 2000);
+        assert_eq!(config.delay_ms(3), 4000);
+        assert_eq!(config.delay_ms(10), 30000);
+        assert_eq!(config.jittered_delay_ms(2), 2000);
+    }
+
+    #[tokio::test]
+    async fn test_transient_errors_are_retried() {
+        let calls = AtomicUsize::new(0);
+        let result: Result<usize, String> = retry_with_backoff(
+            &quick_retries(3),
+            "flaky",
+            |_| true,
+            || async {
+                match calls.fetch_add(1, Ordering::SeqCst) {
+                    0 | 1 => Err("unavailable".to_string()),
+                    n => Ok(n),
+                }
+            },
+        )
+        .await;
+        assert_eq!(result, Ok(2));
+
+        calls.store(0, Ordering::SeqCst);
+        let result: Result<(), String> = retry_with_backoff(
+            &quick_retries(3),
+            "down",
+            |_| true,
+            || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err("unavailable".to_string())
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_returned_at_once() {
+        let calls = AtomicUsize::new(0);
+        let result: Result<(), String> = retry_with_backoff(
+            &quick_retries(5),
+            "unauthorized",
+            |error: &String| error != "unauthorized",
+            || async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err("unauthorized".to_string())
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -4,6 +4,11 @@ use crate::error::{WikifyError, WikifyResult};
 use crate::types::WikifyConfig;
 
 use std::path::Path;
+use toml::{Table, Value};
+
+/// Prefix of the environment variables overriding configuration keys, as
+/// in `WIKIFY_LLM_MODEL` for `llm.model`
+const ENV_PREFIX: &str = "WIKIFY";
 
 impl Default for WikifyConfig {
     fn default() -> Self {
@@ -13,6 +18,7 @@ impl Default for WikifyConfig {
                 model: "text-embedding-3-small".to_string(),
                 dimensions: 256,
                 batch_size: 500,
+                max_concurrent_requests: 4,
             },
             llm: crate::types::LlmConfig {
                 provider: "openai".to_string(),
@@ -97,6 +103,114 @@ impl WikifyConfig {
         Ok(config)
     }
 
+    /// Load the configuration: `defaults`, then the file at `path` if it
+    /// exists, then the `WIKIFY_<SECTION>_<KEY>` environment variables
+    ///
+    /// Returns `None` when there is neither a file nor an environment
+    /// override, so callers can keep their own defaults.
+    pub fn load<P: AsRef<Path>>(path: P, defaults: Self) -> WikifyResult<Option<Self>> {
+        let path = path.as_ref();
+        let has_file = path.exists();
+        let mut config = if has_file {
+            defaults.merge_file(path)?
+        } else {
+            defaults
+        };
+        let overridden = config.apply_env_overrides()?;
+        if !has_file && overridden.is_empty() {
+            return Ok(None);
+        }
+        config.validate()?;
+        Ok(Some(config))
+    }
+
+    /// Read a configuration file over this configuration
+    ///
+    /// Keys the file doesn't set keep their values, and keys that are not
+    /// configuration settings (such as the web server's `[server]` section
+    /// sharing the file) are ignored.
+    pub fn merge_file<P: AsRef<Path>>(self, path: P) -> WikifyResult<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| WikifyError::Config {
+            message: format!("Failed to read config file: {}", e),
+            source: Some(Box::new(e)),
+            context: crate::ErrorContext::new("config")
+                .with_operation("read_file")
+                .with_suggestion("Check if the config file exists and is readable"),
+        })?;
+        let file: Table = toml::from_str(&content).map_err(|e| WikifyError::Config {
+            message: format!("Failed to parse config: {}", e),
+            source: Some(Box::new(e)),
+            context: crate::ErrorContext::new("config")
+                .with_operation("parse_toml")
+                .with_suggestion("Check TOML syntax in config file"),
+        })?;
+
+        let mut merged = self.to_table()?;
+        merge_tables(&mut merged, file);
+        Self::from_table(merged)
+    }
+
+    /// Apply the environment variables set for configuration keys, returning
+    /// their names
+    ///
+    /// Values are read as the type of the key; lists are comma separated.
+    pub fn apply_env_overrides(&mut self) -> WikifyResult<Vec<String>> {
+        self.apply_overrides(|name| std::env::var(name).ok())
+    }
+
+    fn apply_overrides(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> WikifyResult<Vec<String>> {
+        let mut root = self.to_table()?;
+        let mut applied = Vec::new();
+        for (section, table) in root.iter_mut() {
+            let Some(table) = table.as_table_mut() else {
+                continue;
+            };
+            for (key, value) in table.iter_mut() {
+                let name = format!("{}_{}_{}", ENV_PREFIX, section, key).to_uppercase();
+                let Some(raw) = lookup(&name) else {
+                    continue;
+                };
+                *value = parse_like(value, &raw).ok_or_else(|| WikifyError::Config {
+                    message: format!("Invalid value for {}: {}", name, raw),
+                    source: None,
+                    context: crate::ErrorContext::new("config")
+                        .with_operation("env_override")
+                        .with_suggestion(&format!("Set {} to a {}", name, value.type_str())),
+                })?;
+                applied.push(name);
+            }
+        }
+        if !applied.is_empty() {
+            *self = Self::from_table(root)?;
+        }
+        Ok(applied)
+    }
+
+    fn to_table(&self) -> WikifyResult<Table> {
+        Table::try_from(self).map_err(|e| {
+            Box::new(WikifyError::Config {
+                message: format!("Failed to serialize config: {}", e),
+                source: Some(Box::new(e)),
+                context: crate::ErrorContext::new("config").with_operation("serialize_toml"),
+            })
+        })
+    }
+
+    fn from_table(table: Table) -> WikifyResult<Self> {
+        table.try_into().map_err(|e: toml::de::Error| {
+            Box::new(WikifyError::Config {
+                message: format!("Failed to parse config: {}", e),
+                source: Some(Box::new(e)),
+                context: crate::ErrorContext::new("config")
+                    .with_operation("parse_toml")
+                    .with_suggestion("Check the types of the values in the config file"),
+            })
+        })
+    }
+
     /// 保存配置到文件
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> WikifyResult<()> {
         let content = toml::to_string_pretty(self).map_err(|e| WikifyError::Config {
@@ -117,37 +231,275 @@ impl WikifyConfig {
     }
 
     /// 验证配置
+    ///
+    /// Fails with every problem [`WikifyConfig::diagnose`] finds, not just
+    /// the first one.
     pub fn validate(&self) -> WikifyResult<()> {
-        if self.embedding.dimensions == 0 {
-            return Err(Box::new(WikifyError::Config {
-                message: "Embedding dimensions must be greater than 0".to_string(),
-                source: None,
-                context: crate::ErrorContext::new("config")
-                    .with_operation("validate")
-                    .with_suggestion("Set embedding.dimensions to a positive value"),
-            }));
+        let issues = self.diagnose();
+        if issues.is_empty() {
+            return Ok(());
         }
+        let context = issues.iter().fold(
+            crate::ErrorContext::new("config").with_operation("validate"),
+            |context, issue| context.with_suggestion(&issue.suggestion),
+        );
+        Err(Box::new(WikifyError::Config {
+            message: describe_issues(&issues),
+            source: None,
+            context,
+        }))
+    }
 
-        if self.llm.max_tokens == 0 {
-            return Err(Box::new(WikifyError::Config {
-                message: "LLM max_tokens must be greater than 0".to_string(),
-                source: None,
-                context: crate::ErrorContext::new("config")
-                    .with_operation("validate")
-                    .with_suggestion("Set llm.max_tokens to a positive value"),
-            }));
+    /// Find the values that can't work, such as impossible chunk sizes or
+    /// storage paths that are files
+    pub fn diagnose(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let mut check = |failed: bool, key: &str, message: &str, suggestion: &str| {
+            if failed {
+                issues.push(ConfigIssue::new(key, message, suggestion));
+            }
+        };
+
+        check(
+            self.embedding.dimensions == 0,
+            "embedding.dimensions",
+            "Embedding dimensions must be greater than 0",
+            "Set it to the dimensions of the embedding model, such as 1536",
+        );
+        check(
+            self.embedding.batch_size == 0,
+            "embedding.batch_size",
+            "Embedding batch_size must be greater than 0",
+            "Set it to a positive value, such as 100",
+        );
+        check(
+            self.embedding.max_concurrent_requests == 0,
+            "embedding.max_concurrent_requests",
+            "Embedding max_concurrent_requests must be greater than 0",
+            "Set it to 1 to send requests one at a time, or higher for more throughput",
+        );
+        check(
+            self.llm.max_tokens == 0,
+            "llm.max_tokens",
+            "LLM max_tokens must be greater than 0",
+            "Set it to a positive value, such as 2000",
+        );
+        check(
+            !(0.0..=2.0).contains(&self.llm.temperature),
+            "llm.temperature",
+            "LLM temperature must be between 0.0 and 2.0",
+            "Set it to a value from 0.0 to 2.0, such as 0.7",
+        );
+        check(
+            self.repository.max_size_mb == 0,
+            "repository.max_size_mb",
+            "Repository max_size_mb must be greater than 0",
+            "Set it to a positive value",
+        );
+        check(
+            !(0.0..=1.0).contains(&self.rag.similarity_threshold),
+            "rag.similarity_threshold",
+            "RAG similarity_threshold must be between 0.0 and 1.0",
+            "Set it to a value from 0.0 to 1.0, such as 0.3",
+        );
+        check(
+            self.rag.top_k == 0,
+            "rag.top_k",
+            "RAG top_k must be greater than 0",
+            "Set it to a positive value, such as 8",
+        );
+        check(
+            self.rag.max_context_length == 0,
+            "rag.max_context_length",
+            "RAG max_context_length must be greater than 0",
+            "Set it to a positive value, such as 8000",
+        );
+        check(
+            self.indexing.chunk_size == 0,
+            "indexing.chunk_size",
+            "Indexing chunk_size must be greater than 0",
+            "Set it to a positive value, such as 350",
+        );
+        check(
+            self.indexing.chunk_size > 0 && self.indexing.chunk_overlap >= self.indexing.chunk_size,
+            "indexing.chunk_overlap",
+            "Indexing chunk_overlap must be smaller than chunk_size",
+            "Lower indexing.chunk_overlap or raise indexing.chunk_size",
+        );
+        check(
+            self.indexing.max_tokens_per_chunk == 0,
+            "indexing.max_tokens_per_chunk",
+            "Indexing max_tokens_per_chunk must be greater than 0",
+            "Set it to a positive value, such as 250",
+        );
+        check(
+            self.indexing.max_files == Some(0),
+            "indexing.max_files",
+            "Indexing max_files of 0 indexes nothing",
+            "Raise it, or remove it to index every file",
+        );
+
+        for (key, path) in [
+            ("storage.data_dir", &self.storage.data_dir),
+            ("storage.cache_dir", &self.storage.cache_dir),
+        ] {
+            if path.trim().is_empty() {
+                issues.push(ConfigIssue::new(
+                    key,
+                    "The directory is empty",
+                    "Set it to a directory, such as ~/.wikify/data",
+                ));
+            } else if Path::new(path).is_file() {
+                issues.push(ConfigIssue::new(
+                    key,
+                    &format!("{} is a file, not a directory", path),
+                    "Point it to a directory, or remove the file",
+                ));
+            }
         }
+        issues
+    }
+}
 
-        if self.repository.max_size_mb == 0 {
-            return Err(Box::new(WikifyError::Config {
-                message: "Repository max_size_mb must be greater than 0".to_string(),
-                source: None,
-                context: crate::ErrorContext::new("config")
-                    .with_operation("validate")
-                    .with_suggestion("Set repository.max_size_mb to a positive value"),
-            }));
+/// A problem found in a configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted key of the setting, such as `llm.provider`
+    pub key: String,
+    pub message: String,
+    /// How to fix it
+    pub suggestion: String,
+}
+
+impl ConfigIssue {
+    pub fn new(key: &str, message: &str, suggestion: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            message: message.to_string(),
+            suggestion: suggestion.to_string(),
         }
+    }
+}
 
-        Ok(())
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({})", self.key, self.message, self.suggestion)
+    }
+}
+
+/// One message describing all the problems of a configuration
+pub fn describe_issues(issues: &[ConfigIssue]) -> String {
+    match issues {
+        [issue] => issue.to_string(),
+        issues => {
+            let mut message = format!("{} configuration problems:", issues.len());
+            for issue in issues {
+                message.push_str(&format!("\n  - {}", issue));
+            }
+            message
+        }
+    }
+}
+
+/// Overwrite the values of `base` with those of `overlay`, merging tables
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge_tables(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Parse an environment variable as the type of the value it overrides
+fn parse_like(current: &Value, raw: &str) -> Option<Value> {
+    Some(match current {
+        Value::String(_) => Value::String(raw.to_string()),
+        Value::Integer(_) => Value::Integer(raw.trim().parse().ok()?),
+        Value::Float(_) => Value::Float(raw.trim().parse().ok()?),
+        Value::Boolean(_) => Value::Boolean(raw.trim().parse().ok()?),
+        Value::Array(_) => Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        ),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_files_override_only_the_keys_they_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wikify.toml");
+        std::fs::write(
+            &path,
+            "[server]\nport = 8080\n\n[llm]\nmodel = \"gpt-4o-mini\"\n\n[llm.openai]\ntimeout = \"60s\"\n",
+        )
+        .unwrap();
+
+        let config = WikifyConfig::default().merge_file(&path).unwrap();
+        assert_eq!(config.llm.model, "gpt-4o-mini");
+        assert_eq!(config.llm.provider, WikifyConfig::default().llm.provider);
+    }
+
+    #[test]
+    fn test_all_problems_are_reported_at_once() {
+        let mut config = WikifyConfig::default();
+        assert!(config.diagnose().is_empty());
+
+        config.embedding.dimensions = 0;
+        config.indexing.chunk_overlap = config.indexing.chunk_size;
+        config.storage.cache_dir = String::new();
+        let keys: Vec<_> = config
+            .diagnose()
+            .into_iter()
+            .map(|issue| issue.key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "embedding.dimensions",
+                "indexing.chunk_overlap",
+                "storage.cache_dir"
+            ]
+        );
+
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("3 configuration problems"));
+        assert!(error.contains("Lower indexing.chunk_overlap"));
+    }
+
+    #[test]
+    fn test_environment_overrides_keys() {
+        let env = HashMap::from([
+            ("WIKIFY_LLM_MODEL", "claude-sonnet-4"),
+            ("WIKIFY_RAG_TOP_K", "12"),
+            ("WIKIFY_RAG_SIMILARITY_THRESHOLD", "0.5"),
+            ("WIKIFY_REPOSITORY_EXCLUDED_DIRS", "target, vendor"),
+            ("WIKIFY_PORT", "9000"),
+        ]);
+        let mut config = WikifyConfig::default();
+        let applied = config
+            .apply_overrides(|name| env.get(name).map(|value| value.to_string()))
+            .unwrap();
+
+        assert_eq!(applied.len(), 4);
+        assert_eq!(config.llm.model, "claude-sonnet-4");
+        assert_eq!(config.rag.top_k, 12);
+        assert_eq!(config.rag.similarity_threshold, 0.5);
+        assert_eq!(config.repository.excluded_dirs, ["target", "vendor"]);
+
+        let invalid =
+            config.apply_overrides(|name| (name == "WIKIFY_RAG_TOP_K").then(|| "many".to_string()));
+        assert!(invalid.is_err());
     }
 }
@@ -188,6 +188,12 @@ pub enum WikifyError {
         context: ErrorContext,
     },
 
+    #[error("Cancelled: {operation}")]
+    Cancelled {
+        operation: String,
+        context: ErrorContext,
+    },
+
     #[error("Internal error: {message}")]
     Internal {
         message: String,
@@ -216,11 +222,20 @@ impl WikifyError {
             WikifyError::Git { context, .. } => Some(context),
             WikifyError::Llm { context, .. } => Some(context),
             WikifyError::Embedding { context, .. } => Some(context),
+            WikifyError::Cancelled { context, .. } => Some(context),
             WikifyError::Internal { context, .. } => Some(context),
             _ => None,
         }
     }
 
+    /// Error of an operation stopped by its cancellation token
+    pub fn cancelled(operation: &str) -> Self {
+        WikifyError::Cancelled {
+            operation: operation.to_string(),
+            context: ErrorContext::new("cancellation").with_operation(operation),
+        }
+    }
+
     /// Check if error is recoverable
     pub fn is_recoverable(&self) -> bool {
         match self {
@@ -228,6 +243,7 @@ impl WikifyError {
             WikifyError::Timeout { .. } => true,
             WikifyError::RateLimit { .. } => true,
             WikifyError::Authentication { .. } => false,
+            WikifyError::Cancelled { .. } => false,
             WikifyError::Config { .. } => false,
             WikifyError::Validation { .. } => false,
             WikifyError::NotFound { .. } => false,
@@ -6,12 +6,19 @@ pub mod async_utils;
 pub mod config;
 pub mod error;
 pub mod logging;
+pub mod progress;
+pub mod secrets;
 pub mod traits;
 pub mod types;
 
 pub use async_utils::*;
+pub use config::{describe_issues, ConfigIssue};
 pub use error::*;
 pub use logging::*;
+pub use progress::{
+    BroadcastProgress, CallbackProgress, NoProgress, ProgressReporter, ProgressStage,
+    ProgressUpdate, TerminalProgress,
+};
 pub use traits::*;
 pub use types::*;
 
@@ -19,6 +26,7 @@ pub use types::*;
 pub use async_trait::async_trait;
 pub use chrono::{DateTime, Utc};
 pub use tokio;
+pub use tokio_util::sync::CancellationToken;
 pub use tracing;
 pub use uuid::Uuid;
 
@@ -3,10 +3,18 @@
 //! Provides structured logging with performance monitoring and configurable output
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::io;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
 use tracing_subscriber::{
-    fmt::{self, format::FmtSpan},
+    fmt::{
+        self,
+        format::{FmtSpan, JsonFields, Writer},
+        FmtContext, FormatEvent, FormattedFields,
+    },
     layer::SubscriberExt,
+    registry::LookupSpan,
     util::SubscriberInitExt,
     EnvFilter,
 };
@@ -16,7 +24,8 @@ use tracing_subscriber::{
 pub struct LoggingConfig {
     /// Log level (trace, debug, info, warn, error)
     pub level: String,
-    /// Output format (json, pretty, compact)
+    /// Output format (json, pretty, compact), `log_format` in files
+    #[serde(rename = "log_format", alias = "format")]
     pub format: LogFormat,
     /// Whether to include file and line information
     pub include_location: bool,
@@ -28,19 +37,39 @@ pub struct LoggingConfig {
     pub log_to_file: bool,
     /// Log file path (if log_to_file is true)
     pub log_file_path: Option<String>,
+    /// Whether to log to stderr instead of stdout, keeping stdout for output
+    pub log_to_stderr: bool,
     /// Whether to enable performance monitoring
     pub enable_performance_monitoring: bool,
     /// Custom filter directives
     pub filter_directives: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum LogFormat {
+    /// One JSON object per line, see [`JsonFormat`]
     Json,
     Pretty,
     Compact,
 }
 
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "pretty" => Ok(Self::Pretty),
+            "compact" => Ok(Self::Compact),
+            other => Err(format!(
+                "Unknown log format '{}', use json, pretty or compact",
+                other
+            )),
+        }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -51,6 +80,7 @@ impl Default for LoggingConfig {
             include_timestamp: true,
             log_to_file: false,
             log_file_path: None,
+            log_to_stderr: false,
             enable_performance_monitoring: true,
             filter_directives: vec![
                 "wikify=debug".to_string(),
@@ -85,10 +115,12 @@ pub fn init_logging(
                 } else {
                     FmtSpan::NONE
                 })
-                .with_file(config.include_location)
-                .with_line_number(config.include_location)
-                .with_thread_ids(config.include_thread)
-                .with_thread_names(config.include_thread);
+                .fmt_fields(JsonFields::new())
+                .event_format(JsonFormat {
+                    include_location: config.include_location,
+                    include_thread: config.include_thread,
+                    include_timestamp: config.include_timestamp,
+                });
 
             if config.log_to_file {
                 if let Some(log_path) = &config.log_file_path {
@@ -101,6 +133,8 @@ pub fn init_logging(
                 } else {
                     return Err("log_file_path must be specified when log_to_file is true".into());
                 }
+            } else if config.log_to_stderr {
+                registry.with(fmt_layer.with_writer(io::stderr)).init();
             } else {
                 registry.with(fmt_layer.with_writer(io::stdout)).init();
             }
@@ -129,6 +163,8 @@ pub fn init_logging(
                 } else {
                     return Err("log_file_path must be specified when log_to_file is true".into());
                 }
+            } else if config.log_to_stderr {
+                registry.with(fmt_layer.with_writer(io::stderr)).init();
             } else {
                 registry.with(fmt_layer.with_writer(io::stdout)).init();
             }
@@ -157,6 +193,8 @@ pub fn init_logging(
                 } else {
                     return Err("log_file_path must be specified when log_to_file is true".into());
                 }
+            } else if config.log_to_stderr {
+                registry.with(fmt_layer.with_writer(io::stderr)).init();
             } else {
                 registry.with(fmt_layer.with_writer(io::stdout)).init();
             }
@@ -166,6 +204,119 @@ pub fn init_logging(
     Ok(())
 }
 
+/// Event format writing one JSON object per line
+///
+/// The fields of the spans an event happens in are written next to its own
+/// fields, innermost span first, so correlation fields like `repository_id`,
+/// `request_id` and `operation` are top-level keys that Loki or Elasticsearch
+/// can index without parsing span lists. Spans must record their fields with
+/// [`JsonFields`].
+#[derive(Debug, Clone)]
+pub struct JsonFormat {
+    pub include_location: bool,
+    pub include_thread: bool,
+    pub include_timestamp: bool,
+}
+
+impl Default for JsonFormat {
+    fn default() -> Self {
+        Self {
+            include_location: false,
+            include_thread: false,
+            include_timestamp: true,
+        }
+    }
+}
+
+impl<S> FormatEvent<S, JsonFields> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, JsonFields>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let metadata = event.metadata();
+        let mut line = Map::new();
+        if self.include_timestamp {
+            line.insert(
+                "timestamp".to_string(),
+                chrono::Utc::now()
+                    .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+                    .into(),
+            );
+        }
+        line.insert("level".to_string(), metadata.level().to_string().into());
+        line.insert("target".to_string(), metadata.target().into());
+        if self.include_location {
+            if let Some(file) = metadata.file() {
+                line.insert("file".to_string(), file.into());
+            }
+            if let Some(number) = metadata.line() {
+                line.insert("line".to_string(), number.into());
+            }
+        }
+        if self.include_thread {
+            let thread = std::thread::current();
+            if let Some(name) = thread.name() {
+                line.insert("thread_name".to_string(), name.into());
+            }
+            line.insert("thread_id".to_string(), format!("{:?}", thread.id()).into());
+        }
+
+        // Outer spans first, so inner spans and the event itself win
+        if let Some(scope) = ctx.event_scope() {
+            let mut innermost = None;
+            for span in scope.from_root() {
+                if let Some(fields) = span.extensions().get::<FormattedFields<JsonFields>>() {
+                    if let Ok(Value::Object(fields)) = serde_json::from_str(&fields.fields) {
+                        line.extend(fields);
+                    }
+                }
+                innermost = Some(span.name());
+            }
+            if let Some(name) = innermost {
+                line.insert("span".to_string(), name.into());
+            }
+        }
+        event.record(&mut JsonVisitor(&mut line));
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Collects the fields of an event into a JSON object
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
 /// Performance monitoring utilities
 pub mod performance {
     use std::time::Instant;
@@ -267,3 +418,73 @@ macro_rules! log_operation_error {
         );
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_lines_carry_span_fields() {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonFormat::default())
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _request = tracing::info_span!("request", request_id = "req-1").entered();
+            let _indexing =
+                tracing::info_span!("indexing", repository_id = "repo-1", operation = "index")
+                    .entered();
+            tracing::info!(chunks = 3, "Indexed");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["message"], "Indexed");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["chunks"], 3);
+        assert_eq!(line["request_id"], "req-1");
+        assert_eq!(line["repository_id"], "repo-1");
+        assert_eq!(line["operation"], "index");
+        assert_eq!(line["span"], "indexing");
+        assert!(line["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_log_format_is_read_from_files() {
+        let config: LoggingConfig = serde_json::from_value(serde_json::json!({
+            "level": "info",
+            "log_format": "json",
+            "include_location": false,
+            "include_thread": false,
+            "include_timestamp": true,
+            "log_to_file": false,
+            "log_file_path": null,
+            "log_to_stderr": false,
+            "enable_performance_monitoring": false,
+            "filter_directives": [],
+        }))
+        .unwrap();
+        assert_eq!(config.format, LogFormat::Json);
+        assert_eq!("Compact".parse::<LogFormat>(), Ok(LogFormat::Compact));
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+}
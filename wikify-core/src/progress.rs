@@ -0,0 +1,264 @@
+//! Progress reporting for long-running operations
+//!
+//! Indexing and wiki generation report [`ProgressUpdate`]s to a
+//! [`ProgressReporter`] instead of printing or calling ad-hoc closures.
+//! Adapters forward the updates to a callback, a broadcast channel or a
+//! terminal progress bar, so each front end decides how progress is shown.
+
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Stage of a long-running operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProgressStage {
+    /// Preparing the operation
+    Starting,
+    /// Cloning a remote repository
+    Cloning,
+    /// Loading files from the repository
+    Loading,
+    /// Splitting documents into nodes
+    Indexing,
+    /// Generating embeddings
+    Embedding,
+    /// Writing to the vector store
+    Storing,
+    /// Generating content with the LLM
+    Generating,
+    /// Persisting the results
+    Finalizing,
+    /// The operation finished
+    Completed,
+}
+
+impl fmt::Display for ProgressStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ProgressStage::Starting => "Starting",
+            ProgressStage::Cloning => "Cloning repository",
+            ProgressStage::Loading => "Loading documents",
+            ProgressStage::Indexing => "Indexing documents",
+            ProgressStage::Embedding => "Generating embeddings",
+            ProgressStage::Storing => "Storing vectors",
+            ProgressStage::Generating => "Generating content",
+            ProgressStage::Finalizing => "Finalizing",
+            ProgressStage::Completed => "Completed",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A single progress report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    pub stage: ProgressStage,
+    /// Overall completion, from 0.0 to 1.0
+    pub progress: f64,
+    /// What is being worked on, such as the current file or batch
+    pub message: Option<String>,
+}
+
+impl ProgressUpdate {
+    /// Update for a stage, with `progress` clamped to 0.0-1.0
+    pub fn new(stage: ProgressStage, progress: f64) -> Self {
+        Self {
+            stage,
+            progress: progress.clamp(0.0, 1.0),
+            message: None,
+        }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+}
+
+impl fmt::Display for ProgressUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.message {
+            Some(message) => write!(f, "{}: {}", self.stage, message),
+            None => write!(f, "{}", self.stage),
+        }
+    }
+}
+
+/// Receiver of progress updates
+pub trait ProgressReporter: Send + Sync {
+    fn report(&self, update: &ProgressUpdate);
+}
+
+impl<R: ProgressReporter + ?Sized> ProgressReporter for Arc<R> {
+    fn report(&self, update: &ProgressUpdate) {
+        (**self).report(update)
+    }
+}
+
+impl<R: ProgressReporter + ?Sized> ProgressReporter for Box<R> {
+    fn report(&self, update: &ProgressUpdate) {
+        (**self).report(update)
+    }
+}
+
+/// Reporter that ignores every update
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {
+    fn report(&self, _update: &ProgressUpdate) {}
+}
+
+/// Reporter calling a closure with every update
+pub struct CallbackProgress<F> {
+    callback: F,
+}
+
+impl<F> CallbackProgress<F>
+where
+    F: Fn(&ProgressUpdate) + Send + Sync,
+{
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<F> ProgressReporter for CallbackProgress<F>
+where
+    F: Fn(&ProgressUpdate) + Send + Sync,
+{
+    fn report(&self, update: &ProgressUpdate) {
+        (self.callback)(update)
+    }
+}
+
+/// Reporter sending updates to a broadcast channel
+///
+/// Updates are converted to the channel's message type first. Having no
+/// subscribers is not an error; the update is dropped.
+pub struct BroadcastProgress<T, F> {
+    sender: broadcast::Sender<T>,
+    convert: F,
+}
+
+impl<T, F> BroadcastProgress<T, F>
+where
+    T: Send,
+    F: Fn(&ProgressUpdate) -> T + Send + Sync,
+{
+    pub fn new(sender: broadcast::Sender<T>, convert: F) -> Self {
+        Self { sender, convert }
+    }
+}
+
+impl<T, F> ProgressReporter for BroadcastProgress<T, F>
+where
+    T: Send,
+    F: Fn(&ProgressUpdate) -> T + Send + Sync,
+{
+    fn report(&self, update: &ProgressUpdate) {
+        let _ = self.sender.send((self.convert)(update));
+    }
+}
+
+/// Reporter drawing a progress bar on the terminal
+pub struct TerminalProgress {
+    bar: ProgressBar,
+}
+
+impl TerminalProgress {
+    pub fn new() -> Self {
+        let bar = ProgressBar::new(100);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template(
+                    "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos:>3}% {msg}",
+                )
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        bar.enable_steady_tick(std::time::Duration::from_millis(100));
+        Self { bar }
+    }
+
+    /// Reporter drawing on an existing bar
+    pub fn with_bar(bar: ProgressBar) -> Self {
+        Self { bar }
+    }
+
+    /// Leave the bar where it stopped, such as after a failure
+    pub fn abandon(&self, message: impl Into<String>) {
+        self.bar.abandon_with_message(message.into());
+    }
+}
+
+impl Default for TerminalProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgressReporter for TerminalProgress {
+    fn report(&self, update: &ProgressUpdate) {
+        self.bar
+            .set_position((update.progress * 100.0).round() as u64);
+        if update.stage == ProgressStage::Completed {
+            self.bar.finish_with_message(update.to_string());
+        } else {
+            self.bar.set_message(update.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_updates_are_clamped_and_described() {
+        let update = ProgressUpdate::new(ProgressStage::Embedding, 1.4).with_message("Batch 3/4");
+        assert_eq!(update.progress, 1.0);
+        assert_eq!(update.to_string(), "Generating embeddings: Batch 3/4");
+        assert_eq!(
+            ProgressUpdate::new(ProgressStage::Cloning, -0.2).to_string(),
+            "Cloning repository"
+        );
+    }
+
+    #[test]
+    fn test_callback_and_broadcast_adapters_forward_updates() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let callback = CallbackProgress::new(move |update: &ProgressUpdate| {
+            sink.lock().unwrap().push(update.stage)
+        });
+        let (sender, mut receiver) = broadcast::channel(4);
+        let broadcast = BroadcastProgress::new(sender, |update: &ProgressUpdate| {
+            format!("{:.0}% {}", update.progress * 100.0, update)
+        });
+
+        let reporters: Vec<Box<dyn ProgressReporter>> =
+            vec![Box::new(callback), Box::new(broadcast)];
+        for reporter in &reporters {
+            reporter.report(&ProgressUpdate::new(ProgressStage::Loading, 0.25));
+        }
+
+        assert_eq!(*seen.lock().unwrap(), vec![ProgressStage::Loading]);
+        assert_eq!(receiver.try_recv().unwrap(), "25% Loading documents");
+    }
+
+    #[test]
+    fn test_terminal_bar_follows_updates() {
+        let progress = TerminalProgress::with_bar(ProgressBar::hidden());
+        progress.report(&ProgressUpdate::new(ProgressStage::Generating, 0.3));
+        assert_eq!(progress.bar.position(), 30);
+        assert_eq!(progress.bar.message(), "Generating content");
+
+        progress.report(&ProgressUpdate::new(ProgressStage::Completed, 1.0));
+        assert!(progress.bar.is_finished());
+    }
+}
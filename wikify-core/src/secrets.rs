@@ -0,0 +1,140 @@
+//! API keys and repository access tokens
+//!
+//! Secrets are named after the environment variable they have always been
+//! read from, such as `OPENAI_API_KEY` or `GITHUB_TOKEN`. With the `keyring`
+//! feature they can be stored in the OS keyring (Keychain, Credential
+//! Manager or Secret Service) under the `wikify` service, which is read
+//! first. The environment variable remains the fallback for servers and CI
+//! runners without a keyring.
+
+use crate::error::{WikifyError, WikifyResult};
+use serde::Serialize;
+
+/// Keyring service the secrets are stored under
+pub const KEYRING_SERVICE: &str = "wikify";
+
+/// Providers `wikify auth set-key` knows, with the secret each one uses
+pub const PROVIDER_SECRETS: &[(&str, &str)] = &[
+    ("openai", "OPENAI_API_KEY"),
+    ("anthropic", "ANTHROPIC_API_KEY"),
+    ("groq", "GROQ_API_KEY"),
+    ("github", "GITHUB_TOKEN"),
+    ("gitlab", "GITLAB_TOKEN"),
+    ("bitbucket", "BITBUCKET_TOKEN"),
+    ("gitea", "GITEA_TOKEN"),
+];
+
+/// Where a secret was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretSource {
+    Keyring,
+    Environment,
+}
+
+/// Name of the secret a provider uses, such as `OPENAI_API_KEY` for `openai`
+pub fn provider_secret(provider: &str) -> Option<&'static str> {
+    PROVIDER_SECRETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(provider))
+        .map(|(_, secret)| *secret)
+}
+
+/// Value of a secret, from the keyring or else the environment
+pub fn secret(name: &str) -> Option<String> {
+    lookup(name).map(|(value, _)| value)
+}
+
+/// Where a secret is set, if anywhere
+pub fn secret_source(name: &str) -> Option<SecretSource> {
+    lookup(name).map(|(_, source)| source)
+}
+
+fn lookup(name: &str) -> Option<(String, SecretSource)> {
+    keyring_get(name)
+        .map(|value| (value, SecretSource::Keyring))
+        .or_else(|| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| (value, SecretSource::Environment))
+        })
+}
+
+/// Store a secret in the keyring
+pub fn store_secret(name: &str, value: &str) -> WikifyResult<()> {
+    keyring_set(name, value).map_err(|message| keyring_error(name, "store_secret", message))
+}
+
+/// Remove a secret from the keyring, returning whether it was stored
+pub fn delete_secret(name: &str) -> WikifyResult<bool> {
+    keyring_delete(name).map_err(|message| keyring_error(name, "delete_secret", message))
+}
+
+fn keyring_error(name: &str, operation: &str, message: String) -> Box<WikifyError> {
+    Box::new(WikifyError::Config {
+        message: format!("Keyring error for {}: {}", name, message),
+        source: None,
+        context: crate::ErrorContext::new("secrets")
+            .with_operation(operation)
+            .with_suggestion(&format!(
+                "Set the {} environment variable instead if no keyring is available",
+                name
+            )),
+    })
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_get(name: &str) -> Option<String> {
+    match keyring::Entry::new(KEYRING_SERVICE, name).and_then(|entry| entry.get_password()) {
+        Ok(value) if !value.is_empty() => Some(value),
+        Ok(_) | Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            tracing::debug!(secret = name, error = %e, "Keyring unavailable");
+            None
+        }
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_set(name: &str, value: &str) -> Result<(), String> {
+    keyring::Entry::new(KEYRING_SERVICE, name)
+        .and_then(|entry| entry.set_password(value))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_delete(name: &str) -> Result<bool, String> {
+    match keyring::Entry::new(KEYRING_SERVICE, name).and_then(|entry| entry.delete_credential()) {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_get(_name: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_set(_name: &str, _value: &str) -> Result<(), String> {
+    Err("wikify was built without keyring support".to_string())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn keyring_delete(_name: &str) -> Result<bool, String> {
+    Err("wikify was built without keyring support".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_providers_map_to_their_secrets() {
+        assert_eq!(provider_secret("openai"), Some("OPENAI_API_KEY"));
+        assert_eq!(provider_secret("GitHub"), Some("GITHUB_TOKEN"));
+        assert_eq!(provider_secret("ollama"), None);
+    }
+}
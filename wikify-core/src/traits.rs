@@ -1,8 +1,14 @@
 //! Core trait definitions
+//!
+//! Long-running operations take a [`CancellationToken`] and return
+//! [`WikifyError::Cancelled`](crate::WikifyError::Cancelled) once it is
+//! triggered, so callers such as the web server can stop work when a client
+//! disconnects or asks to cancel.
 
 use crate::error::WikifyResult;
 use crate::types::*;
 use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
 
 /// Repository processor trait
 #[async_trait]
@@ -21,7 +27,11 @@ pub trait RepositoryProcessor {
 #[async_trait]
 pub trait DocumentIndexer {
     /// 索引文档
-    async fn index_documents(&self, documents: Vec<DocumentInfo>) -> WikifyResult<IndexStats>;
+    async fn index_documents(
+        &self,
+        documents: Vec<DocumentInfo>,
+        cancel: &CancellationToken,
+    ) -> WikifyResult<IndexStats>;
 
     /// 搜索相关文档
     async fn search(&self, query: &str, top_k: usize) -> WikifyResult<Vec<DocumentInfo>>;
@@ -41,6 +51,7 @@ pub trait RagSystem {
         &self,
         topic: &str,
         max_iterations: usize,
+        cancel: &CancellationToken,
     ) -> WikifyResult<ResearchResult>;
 
     /// 添加对话历史
@@ -51,13 +62,18 @@ pub trait RagSystem {
 #[async_trait]
 pub trait WikiGenerator<WikiStructure, WikiPage> {
     /// 生成Wiki结构
-    async fn generate_structure(&self, documents: &[DocumentInfo]) -> WikifyResult<WikiStructure>;
+    async fn generate_structure(
+        &self,
+        documents: &[DocumentInfo],
+        cancel: &CancellationToken,
+    ) -> WikifyResult<WikiStructure>;
 
     /// 生成Wiki页面
     async fn generate_page(
         &self,
         page_id: &str,
         context: &[DocumentInfo],
+        cancel: &CancellationToken,
     ) -> WikifyResult<WikiPage>;
 
     /// 生成可视化图表
@@ -26,6 +26,9 @@ pub struct RepositoryAccess {
     pub local_path: Option<std::path::PathBuf>,
     /// Whether the repository is ready for access
     pub is_ready: bool,
+    /// Remote URL actually used (the primary URL or one of its mirrors)
+    #[serde(default)]
+    pub remote_url: Option<String>,
 }
 
 /// Repository file information
@@ -77,6 +80,9 @@ pub struct RepositoryAccessConfig {
     pub clone_depth: Option<u32>,
     /// Custom local path for GitClone mode
     pub custom_local_path: Option<String>,
+    /// Alternate remotes tried in order when the primary URL cannot be accessed
+    #[serde(default)]
+    pub mirror_urls: Vec<String>,
 }
 
 impl Default for RepositoryAccessConfig {
@@ -87,6 +93,7 @@ impl Default for RepositoryAccessConfig {
             force_mode: false,
             clone_depth: Some(1), // Shallow clone by default
             custom_local_path: None,
+            mirror_urls: Vec::new(),
         }
     }
 }
@@ -149,6 +156,13 @@ pub struct EmbeddingConfig {
     pub model: String,
     pub dimensions: usize,
     pub batch_size: usize,
+    /// Embedding requests sent to the provider at the same time
+    #[serde(default = "default_embedding_concurrency")]
+    pub max_concurrent_requests: usize,
+}
+
+fn default_embedding_concurrency() -> usize {
+    4
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
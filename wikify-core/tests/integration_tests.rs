@@ -50,6 +50,7 @@ async fn test_logging_initialization() {
         include_timestamp: true,
         log_to_file: false,
         log_file_path: None,
+        log_to_stderr: false,
         enable_performance_monitoring: false,
         filter_directives: vec!["wikify_core=debug".to_string()],
     };
@@ -97,6 +97,7 @@ impl ChatSystem {
             context: Some(conversation_context),
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         // Get response from RAG pipeline
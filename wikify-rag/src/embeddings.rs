@@ -3,12 +3,25 @@
 //! This module handles the generation of embeddings for document chunks
 //! using various embedding providers.
 
-use crate::types::{EmbeddedChunk, EmbeddingConfig, RagError, RagResult};
+use crate::llm_client::{is_transient_error, provider_retry};
+use crate::quantization::QuantizedVectors;
+use crate::types::{
+    unsupported_provider, EmbeddedChunk, EmbeddingConfig, Quantization, RagError, RagResult,
+    VectorStoreConfig,
+};
+use crate::vector_file::MappedVectors;
 use cheungfun_core::Node;
+use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 use siumai::prelude::*;
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
+use wikify_core::{
+    retry_with_backoff, NoProgress, ProgressReporter, ProgressStage, ProgressUpdate,
+};
 
 /// Embedding generator that converts text chunks to vector embeddings
 pub struct EmbeddingGenerator {
@@ -28,12 +41,13 @@ impl EmbeddingGenerator {
     /// Initialize the embedding client
     pub async fn initialize(&mut self) -> RagResult<()> {
         match self.config.provider.as_str() {
+            #[cfg(feature = "openai")]
             "openai" => {
                 let api_key = self
                     .config
                     .api_key
                     .clone()
-                    .or_else(|| std::env::var("OPENAI_API_KEY").ok())
+                    .or_else(|| wikify_core::secrets::secret("OPENAI_API_KEY"))
                     .ok_or_else(|| RagError::Config("OpenAI API key not found".to_string()))?;
 
                 let client = LlmBuilder::new()
@@ -61,9 +75,9 @@ impl EmbeddingGenerator {
                 );
             }
             provider => {
-                return Err(RagError::Config(format!(
-                    "Unsupported embedding provider: {}",
-                    provider
+                return Err(RagError::Config(unsupported_provider(
+                    "embedding",
+                    provider,
                 )));
             }
         }
@@ -73,14 +87,27 @@ impl EmbeddingGenerator {
 
     /// Generate embeddings for a batch of nodes
     pub async fn generate_embeddings(&self, nodes: Vec<Node>) -> RagResult<Vec<EmbeddedChunk>> {
-        self.generate_embeddings_with_progress(nodes, None).await
+        self.generate_embeddings_with_progress(nodes, &NoProgress)
+            .await
     }
 
     /// Generate embeddings for a batch of nodes with progress reporting
     pub async fn generate_embeddings_with_progress(
         &self,
         nodes: Vec<Node>,
-        progress_callback: Option<&Box<dyn Fn(String, f64, Option<String>) + Send + Sync>>,
+        progress: &dyn ProgressReporter,
+    ) -> RagResult<Vec<EmbeddedChunk>> {
+        self.generate_embeddings_cancellable(nodes, progress, &CancellationToken::new())
+            .await
+    }
+
+    /// Generate embeddings with progress reporting, stopping with
+    /// [`RagError::Cancelled`] before the next batch once `cancel` is triggered
+    pub async fn generate_embeddings_cancellable(
+        &self,
+        nodes: Vec<Node>,
+        progress: &dyn ProgressReporter,
+        cancel: &CancellationToken,
     ) -> RagResult<Vec<EmbeddedChunk>> {
         if self.client.is_none() {
             return Err(RagError::Config(
@@ -89,11 +116,12 @@ impl EmbeddingGenerator {
         }
 
         info!(
-            "🚀 Starting embedding generation - Provider: {}, Model: {}, Nodes: {}, Batch Size: {}",
+            "🚀 Starting embedding generation - Provider: {}, Model: {}, Nodes: {}, Batch Size: {}, Max concurrent requests: {}",
             self.config.provider,
             self.config.model,
             nodes.len(),
-            self.config.batch_size
+            self.config.batch_size,
+            self.config.max_concurrent_requests
         );
 
         // Create progress bar
@@ -108,32 +136,55 @@ impl EmbeddingGenerator {
 
         let mut embedded_chunks = Vec::new();
         let mut processed_count = 0;
-        let total_batches = nodes.len().div_ceil(self.config.batch_size);
+        let batch_size = self.config.batch_size.max(1);
+        let total_batches = nodes.len().div_ceil(batch_size);
         let start_time = std::time::Instant::now();
+        let mut throttle = EmbeddingThrottle::new(self.config.max_concurrent_requests);
 
         // Process nodes in batches to avoid rate limits
-        for (batch_index, batch) in nodes.chunks(self.config.batch_size).enumerate() {
+        for (batch_index, batch) in nodes.chunks(batch_size).enumerate() {
+            if cancel.is_cancelled() {
+                pb.abandon_with_message("Embedding generation cancelled");
+                info!(
+                    "🛑 Embedding generation cancelled after {}/{} nodes",
+                    processed_count,
+                    nodes.len()
+                );
+                return Err(RagError::Cancelled);
+            }
+
             let batch_start = std::time::Instant::now();
 
             info!(
-                "📦 Processing batch {}/{} - Provider: {}, Model: {}, Batch size: {}",
+                "📦 Processing batch {}/{} - Provider: {}, Model: {}, Batch size: {}, Concurrent requests: {}",
                 batch_index + 1,
                 total_batches,
                 self.config.provider,
                 self.config.model,
-                batch.len()
+                batch.len(),
+                throttle.limit()
             );
 
-            let batch_chunks = self
+            let (batch_chunks, pushed_back) = self
                 .process_batch_with_progress(
                     batch,
+                    throttle.limit(),
                     &pb,
                     &mut processed_count,
                     nodes.len(),
-                    progress_callback,
+                    progress,
                 )
                 .await?;
 
+            let previous_limit = throttle.limit();
+            throttle.record_batch(pushed_back);
+            if throttle.limit() < previous_limit {
+                warn!(
+                    "⏳ Embedding provider is rate limiting or failing requests, lowering concurrent requests to {}",
+                    throttle.limit()
+                );
+            }
+
             let batch_duration = batch_start.elapsed();
             info!(
                 "✅ Batch {}/{} completed - Generated: {} embeddings, Duration: {:?}, Rate: {:.2} embeddings/sec",
@@ -158,17 +209,13 @@ impl EmbeddingGenerator {
         pb.finish_with_message("✅ Embeddings generated");
 
         // Report final completion progress
-        if let Some(callback) = progress_callback {
-            callback(
-                "Generating embeddings".to_string(),
-                95.0,
-                Some(format!(
-                    "Completed {}/{} nodes",
-                    processed_count,
-                    nodes.len()
-                )),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Embedding, 0.95).with_message(format!(
+                "Completed {}/{} nodes",
+                processed_count,
+                nodes.len()
+            )),
+        );
 
         info!(
             "🎉 Embedding generation completed - Provider: {}, Model: {}, Total: {} embeddings, Duration: {:?}, Average rate: {:.2} embeddings/sec",
@@ -181,37 +228,47 @@ impl EmbeddingGenerator {
         Ok(embedded_chunks)
     }
 
-    /// Process a batch of nodes with progress tracking
+    /// Process a batch of nodes with progress tracking, with up to
+    /// `concurrency` requests in flight
+    ///
+    /// Also returns whether the provider pushed back, that is whether any
+    /// request had to be retried.
     async fn process_batch_with_progress(
         &self,
         nodes: &[Node],
+        concurrency: usize,
         pb: &ProgressBar,
         processed_count: &mut usize,
         total_nodes: usize,
-        progress_callback: Option<&Box<dyn Fn(String, f64, Option<String>) + Send + Sync>>,
-    ) -> RagResult<Vec<EmbeddedChunk>> {
+        progress: &dyn ProgressReporter,
+    ) -> RagResult<(Vec<EmbeddedChunk>, bool)> {
         let client = self.client.as_ref().unwrap();
+        let pushed_back = AtomicBool::new(false);
         let mut embedded_chunks = Vec::new();
 
-        for (index, node) in nodes.iter().enumerate() {
-            // Extract text content from node
-            let content = node.content.clone();
-            if content.trim().is_empty() {
-                debug!("Skipping empty node");
-                *processed_count += 1;
-                pb.set_position(*processed_count as u64);
-                continue;
-            }
+        // Results come back in the order of the nodes
+        let mut results = stream::iter(nodes.iter().enumerate())
+            .map(|(index, node)| {
+                let pushed_back = &pushed_back;
+                async move {
+                    if node.content.trim().is_empty() {
+                        return (index, node, None);
+                    }
+                    let embedding = self
+                        .generate_single_embedding(client.as_ref(), &node.content, pushed_back)
+                        .await;
+                    (index, node, Some(embedding))
+                }
+            })
+            .buffered(concurrency.max(1));
 
-            // Generate embedding for this chunk
-            match self
-                .generate_single_embedding(client.as_ref(), &content)
-                .await
-            {
-                Ok(embedding) => {
+        while let Some((index, node, embedding)) = results.next().await {
+            match embedding {
+                None => debug!("Skipping empty node"),
+                Some(Ok(embedding)) => {
                     let embedded_chunk = EmbeddedChunk {
                         id: Uuid::new_v4(),
-                        content: content.clone(),
+                        content: node.content.clone(),
                         embedding,
                         metadata: node.metadata.clone(),
                         document_id: Some(node.id.to_string()),
@@ -219,7 +276,7 @@ impl EmbeddingGenerator {
                     };
                     embedded_chunks.push(embedded_chunk);
                 }
-                Err(e) => {
+                Some(Err(e)) => {
                     warn!("Failed to generate embedding for node {}: {}", node.id, e);
                     // Continue processing other nodes
                 }
@@ -229,23 +286,19 @@ impl EmbeddingGenerator {
             pb.set_position(*processed_count as u64);
 
             // Report fine-grained progress after each node (only every 5 nodes to reduce WebSocket traffic)
-            if let Some(callback) = progress_callback {
-                if *processed_count % 5 == 0 {
-                    let embedding_progress = *processed_count as f64 / total_nodes as f64;
-                    let percentage = 20.0 + embedding_progress * 75.0; // 20% to 95%
-                    callback(
-                        "Generating embeddings".to_string(),
-                        percentage,
-                        Some(format!(
-                            "Processing {}/{} nodes",
-                            *processed_count, total_nodes
-                        )),
-                    );
-                }
+            if *processed_count % 5 == 0 {
+                let embedding_progress = *processed_count as f64 / total_nodes as f64;
+                let fraction = 0.2 + embedding_progress * 0.75; // 20% to 95%
+                progress.report(
+                    &ProgressUpdate::new(ProgressStage::Embedding, fraction).with_message(format!(
+                        "Processing {}/{} nodes",
+                        *processed_count, total_nodes
+                    )),
+                );
             }
         }
 
-        Ok(embedded_chunks)
+        Ok((embedded_chunks, pushed_back.load(Ordering::Relaxed)))
     }
 
     /// Process a batch of nodes
@@ -263,7 +316,7 @@ impl EmbeddingGenerator {
 
             // Generate embedding for this chunk
             match self
-                .generate_single_embedding(client.as_ref(), &content)
+                .generate_single_embedding(client.as_ref(), &content, &AtomicBool::new(false))
                 .await
             {
                 Ok(embedding) => {
@@ -288,10 +341,13 @@ impl EmbeddingGenerator {
     }
 
     /// Generate embedding for a single text
+    ///
+    /// Sets `pushed_back` when the request had to be retried.
     async fn generate_single_embedding(
         &self,
         client: &dyn LlmClient,
         text: &str,
+        pushed_back: &AtomicBool,
     ) -> RagResult<Vec<f32>> {
         if let Some(embedding_client) = client.as_embedding_capability() {
             let start_time = std::time::Instant::now();
@@ -303,16 +359,23 @@ impl EmbeddingGenerator {
                 text.len()
             );
 
-            let response = embedding_client
-                .embed(vec![text.to_string()])
-                .await
-                .map_err(|e| {
-                    error!(
-                        "❌ Embedding API call failed - Provider: {}, Model: {}, Error: {}",
-                        self.config.provider, self.config.model, e
-                    );
-                    RagError::Embedding(format!("Embedding API call failed: {}", e))
-                })?;
+            let attempts = AtomicUsize::new(0);
+            let response =
+                retry_with_backoff(&provider_retry(), "embed", is_transient_error, || {
+                    attempts.fetch_add(1, Ordering::Relaxed);
+                    embedding_client.embed(vec![text.to_string()])
+                })
+                .await;
+            if attempts.load(Ordering::Relaxed) > 1 {
+                pushed_back.store(true, Ordering::Relaxed);
+            }
+            let response = response.map_err(|e| {
+                error!(
+                    "❌ Embedding API call failed - Provider: {}, Model: {}, Error: {}",
+                    self.config.provider, self.config.model, e
+                );
+                RagError::Embedding(format!("Embedding API call failed: {}", e))
+            })?;
 
             let duration = start_time.elapsed();
 
@@ -357,33 +420,112 @@ impl EmbeddingGenerator {
     }
 }
 
-/// Simple in-memory vector store for embeddings
+/// Adaptive limit on the embedding requests in flight
+///
+/// Halved after a batch in which the provider pushed back, and raised by one
+/// after each batch without trouble, up to the configured maximum.
+#[derive(Debug, Clone, Copy)]
+struct EmbeddingThrottle {
+    limit: usize,
+    max: usize,
+}
+
+impl EmbeddingThrottle {
+    fn new(max: usize) -> Self {
+        let max = max.max(1);
+        Self { limit: max, max }
+    }
+
+    fn limit(&self) -> usize {
+        self.limit
+    }
+
+    fn record_batch(&mut self, pushed_back: bool) {
+        self.limit = if pushed_back {
+            (self.limit / 2).max(1)
+        } else {
+            (self.limit + 1).min(self.max)
+        };
+    }
+}
+
+/// Vector store for embeddings
+///
+/// Embeddings are kept in RAM until they take more than the configured
+/// threshold, after which they move into a memory-mapped file and only the
+/// chunks' text and metadata stay on the heap. Stores configured with
+/// quantization keep quantized embeddings in RAM instead, and full precision
+/// ones in a memory-mapped file only to rescore the best matches.
 pub struct VectorStore {
     chunks: Vec<EmbeddedChunk>,
     dimension: usize,
+    config: VectorStoreConfig,
+    /// Full precision embeddings of all chunks once they outgrew the
+    /// threshold or, in a quantized store, for rescoring
+    mapped: Option<MappedVectors>,
+    /// Embeddings of all chunks in a quantized store
+    quantized: Option<QuantizedVectors>,
 }
 
 impl VectorStore {
-    /// Create a new vector store
+    /// Create a new vector store with the default configuration
     pub fn new(dimension: usize) -> Self {
+        Self::with_config(dimension, VectorStoreConfig::default())
+    }
+
+    /// Create a new vector store
+    pub fn with_config(dimension: usize, config: VectorStoreConfig) -> Self {
+        let quantized = match config.quantization {
+            Quantization::None => None,
+            Quantization::Int8 => Some(QuantizedVectors::new(dimension)),
+        };
         Self {
             chunks: Vec::new(),
             dimension,
+            config,
+            mapped: None,
+            quantized,
         }
     }
 
     /// Add embedded chunks to the store
     pub fn add_chunks(&mut self, chunks: Vec<EmbeddedChunk>) -> RagResult<()> {
+        if let Some(chunk) = chunks
+            .iter()
+            .find(|chunk| chunk.embedding.len() != self.dimension)
+        {
+            return Err(RagError::Config(format!(
+                "Embedding dimension mismatch: expected {}, got {}",
+                self.dimension,
+                chunk.embedding.len()
+            )));
+        }
+
+        // Full precision embeddings of a quantized store are only kept when
+        // searches rescore with them
+        if self.quantized.is_some() && self.config.rerank_candidates > 0 && self.mapped.is_none() {
+            self.mapped = Some(MappedVectors::create(
+                self.dimension,
+                self.config.mmap_dir.as_deref(),
+            )?);
+        }
+
         let chunks_len = chunks.len();
-        for chunk in chunks {
-            if chunk.embedding.len() != self.dimension {
-                return Err(RagError::Config(format!(
-                    "Embedding dimension mismatch: expected {}, got {}",
-                    self.dimension,
-                    chunk.embedding.len()
-                )));
-            }
-            self.chunks.push(chunk);
+        if let Some(mapped) = self.mapped.as_mut() {
+            mapped.extend(chunks.iter().map(|chunk| chunk.embedding.as_slice()))?;
+        }
+        if let Some(quantized) = self.quantized.as_mut() {
+            quantized.extend(chunks.iter().map(|chunk| chunk.embedding.as_slice()));
+        }
+        if self.mapped.is_some() || self.quantized.is_some() {
+            self.chunks
+                .extend(chunks.into_iter().map(|chunk| EmbeddedChunk {
+                    embedding: Vec::new(),
+                    ..chunk
+                }));
+        } else {
+            self.chunks.extend(chunks);
+            self.map_if_large()?;
         }
 
         info!(
@@ -394,6 +536,57 @@ impl VectorStore {
         Ok(())
     }
 
+    /// Move the embeddings into a memory-mapped file once they take more
+    /// than the threshold
+    fn map_if_large(&mut self) -> RagResult<()> {
+        let Some(threshold_mb) = self.config.mmap_threshold_mb else {
+            return Ok(());
+        };
+        let bytes = (self.chunks.len() * self.dimension * std::mem::size_of::<f32>()) as u64;
+        if self.mapped.is_some() || bytes < threshold_mb * 1024 * 1024 {
+            return Ok(());
+        }
+
+        let mut mapped = MappedVectors::create(self.dimension, self.config.mmap_dir.as_deref())?;
+        mapped.extend(self.chunks.iter().map(|chunk| chunk.embedding.as_slice()))?;
+        for chunk in &mut self.chunks {
+            chunk.embedding = Vec::new();
+        }
+        info!(
+            "Moved {} embeddings ({} MB) into a memory-mapped file",
+            mapped.len(),
+            bytes / (1024 * 1024)
+        );
+        self.mapped = Some(mapped);
+        Ok(())
+    }
+
+    /// Remove the chunks a predicate matches, returning how many were removed
+    ///
+    /// In a memory-mapped or quantized store the predicate sees the chunks
+    /// without their embeddings.
+    pub fn remove_where<F>(&mut self, predicate: F) -> RagResult<usize>
+    where
+        F: Fn(&EmbeddedChunk) -> bool,
+    {
+        let before = self.chunks.len();
+        if self.mapped.is_none() && self.quantized.is_none() {
+            self.chunks.retain(|chunk| !predicate(chunk));
+            return Ok(before - self.chunks.len());
+        }
+
+        let keep: Vec<bool> = self.chunks.iter().map(|chunk| !predicate(chunk)).collect();
+        if let Some(mapped) = self.mapped.as_mut() {
+            mapped.retain(|index| keep[index])?;
+        }
+        if let Some(quantized) = self.quantized.as_mut() {
+            quantized.retain(|index| keep[index]);
+        }
+        let mut keep = keep.into_iter();
+        self.chunks.retain(|_| keep.next().unwrap_or(true));
+        Ok(before - self.chunks.len())
+    }
+
     /// Search for similar chunks using cosine similarity
     pub fn search(
         &self,
@@ -401,6 +594,24 @@ impl VectorStore {
         top_k: usize,
         threshold: f32,
     ) -> Vec<(usize, f32)> {
+        self.search_where(query_embedding, top_k, threshold, |_| true)
+    }
+
+    /// Search for similar chunks among those a predicate accepts
+    ///
+    /// Quantized stores score every chunk on its quantized embedding and,
+    /// when configured, rescore the best `rerank_candidates` of them on full
+    /// precision before applying the threshold.
+    pub fn search_where<F>(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+        threshold: f32,
+        predicate: F,
+    ) -> Vec<(usize, f32)>
+    where
+        F: Fn(&EmbeddedChunk) -> bool,
+    {
         if query_embedding.len() != self.dimension {
             warn!(
                 "Query embedding dimension mismatch: expected {}, got {}",
@@ -410,19 +621,41 @@ impl VectorStore {
             return Vec::new();
         }
 
-        let mut similarities: Vec<(usize, f32)> = self
+        let candidates = self
             .chunks
             .iter()
             .enumerate()
-            .map(|(idx, chunk)| {
-                let similarity = cosine_similarity(query_embedding, &chunk.embedding);
-                (idx, similarity)
-            })
-            .filter(|(_, similarity)| *similarity >= threshold)
-            .collect();
+            .filter(|(_, chunk)| predicate(chunk))
+            .map(|(idx, _)| idx);
+
+        let mut similarities: Vec<(usize, f32)> = match &self.quantized {
+            Some(quantized) => {
+                let query_norm = query_embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+                let mut similarities: Vec<(usize, f32)> = candidates
+                    .map(|idx| (idx, quantized.similarity(query_embedding, query_norm, idx)))
+                    .collect();
+                if let Some(mapped) = &self.mapped {
+                    sort_by_similarity(&mut similarities);
+                    similarities.truncate(self.config.rerank_candidates.max(top_k));
+                    for (idx, similarity) in &mut similarities {
+                        let embedding = mapped.get(*idx).unwrap_or_default();
+                        *similarity = cosine_similarity(query_embedding, embedding);
+                    }
+                }
+                similarities
+            }
+            None => candidates
+                .map(|idx| {
+                    let embedding = self.embedding(idx).unwrap_or_default();
+                    let similarity = cosine_similarity(query_embedding, &embedding);
+                    (idx, similarity)
+                })
+                .collect(),
+        };
+        similarities.retain(|(_, similarity)| *similarity >= threshold);
 
         // Sort by similarity (descending)
-        similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        sort_by_similarity(&mut similarities);
 
         // Take top k results
         similarities.truncate(top_k);
@@ -430,15 +663,60 @@ impl VectorStore {
     }
 
     /// Get chunk by index
+    ///
+    /// The embedding is empty in a memory-mapped or quantized store, see
+    /// [`Self::embedding`].
     pub fn get_chunk(&self, index: usize) -> Option<&EmbeddedChunk> {
         self.chunks.get(index)
     }
 
+    /// Get the embedding of the chunk at an index
+    ///
+    /// A quantized store without full precision embeddings returns an
+    /// approximation restored from the quantized one.
+    pub fn embedding(&self, index: usize) -> Option<Cow<'_, [f32]>> {
+        if let Some(mapped) = &self.mapped {
+            return mapped.get(index).map(Cow::Borrowed);
+        }
+        if let Some(quantized) = &self.quantized {
+            return quantized.dequantize(index).map(Cow::Owned);
+        }
+        self.chunks
+            .get(index)
+            .map(|chunk| Cow::Borrowed(chunk.embedding.as_slice()))
+    }
+
     /// Get all chunks
+    ///
+    /// The embeddings are empty in a memory-mapped or quantized store, see
+    /// [`Self::chunks_with_embeddings`].
     pub fn chunks(&self) -> &[EmbeddedChunk] {
         &self.chunks
     }
 
+    /// Get all chunks with their embeddings, as returned by
+    /// [`Self::embedding`] if the chunks don't hold them
+    pub fn chunks_with_embeddings(&self) -> Cow<'_, [EmbeddedChunk]> {
+        if self.mapped.is_none() && self.quantized.is_none() {
+            return Cow::Borrowed(&self.chunks);
+        }
+        Cow::Owned(
+            self.chunks
+                .iter()
+                .enumerate()
+                .map(|(index, chunk)| EmbeddedChunk {
+                    embedding: self.embedding(index).unwrap_or_default().into_owned(),
+                    ..chunk.clone()
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether full precision embeddings were moved into a memory-mapped file
+    pub fn is_memory_mapped(&self) -> bool {
+        self.mapped.is_some()
+    }
+
     /// Get number of chunks
     pub fn len(&self) -> usize {
         self.chunks.len()
@@ -450,6 +728,11 @@ impl VectorStore {
     }
 }
 
+/// Sort search results by similarity, most similar first
+fn sort_by_similarity(similarities: &mut [(usize, f32)]) {
+    similarities.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+}
+
 /// Calculate cosine similarity between two vectors
 fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() {
@@ -472,6 +755,28 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
+    #[test]
+    fn test_throttle_backs_off_and_recovers() {
+        let mut throttle = EmbeddingThrottle::new(8);
+        assert_eq!(throttle.limit(), 8);
+
+        throttle.record_batch(true);
+        assert_eq!(throttle.limit(), 4);
+        for _ in 0..4 {
+            throttle.record_batch(true);
+        }
+        assert_eq!(throttle.limit(), 1);
+
+        throttle.record_batch(false);
+        assert_eq!(throttle.limit(), 2);
+        for _ in 0..10 {
+            throttle.record_batch(false);
+        }
+        assert_eq!(throttle.limit(), 8);
+
+        assert_eq!(EmbeddingThrottle::new(0).limit(), 1);
+    }
+
     #[test]
     fn test_cosine_similarity() {
         let a = vec![1.0, 0.0, 0.0];
@@ -503,4 +808,122 @@ mod tests {
         assert_eq!(results.len(), 1);
         assert!((results[0].1 - 1.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_remove_chunks_of_a_file() {
+        let mut store = VectorStore::new(3);
+        let chunk = |file_path: &str| EmbeddedChunk {
+            id: Uuid::new_v4(),
+            content: "test content".to_string(),
+            embedding: vec![1.0, 0.0, 0.0],
+            metadata: HashMap::from([("file_path".to_string(), file_path.into())]),
+            document_id: None,
+            chunk_index: 0,
+        };
+        store
+            .add_chunks(vec![
+                chunk("src/lib.rs"),
+                chunk("src/main.rs"),
+                chunk("src/lib.rs"),
+            ])
+            .unwrap();
+
+        let removed = store
+            .remove_where(|chunk| chunk.metadata["file_path"] == "src/lib.rs")
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.chunks()[0].metadata["file_path"], "src/main.rs");
+    }
+
+    #[test]
+    fn test_large_store_moves_embeddings_into_a_mapped_file() {
+        let config = VectorStoreConfig {
+            mmap_threshold_mb: Some(0),
+            ..Default::default()
+        };
+        let mut store = VectorStore::with_config(3, config);
+        let chunk = |file_path: &str, embedding: Vec<f32>| EmbeddedChunk {
+            id: Uuid::new_v4(),
+            content: "test content".to_string(),
+            embedding,
+            metadata: HashMap::from([("file_path".to_string(), file_path.into())]),
+            document_id: None,
+            chunk_index: 0,
+        };
+        store
+            .add_chunks(vec![
+                chunk("src/lib.rs", vec![1.0, 0.0, 0.0]),
+                chunk("src/main.rs", vec![0.0, 1.0, 0.0]),
+            ])
+            .unwrap();
+        assert!(store.is_memory_mapped());
+        assert!(store.chunks()[0].embedding.is_empty());
+
+        store
+            .add_chunks(vec![chunk("src/cli.rs", vec![0.0, 0.0, 1.0])])
+            .unwrap();
+        let removed = store
+            .remove_where(|chunk| chunk.metadata["file_path"] == "src/lib.rs")
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        let results = store.search(&[0.0, 0.0, 1.0], 1, 0.5);
+        assert_eq!(results.len(), 1);
+        let best = store.get_chunk(results[0].0).unwrap();
+        assert_eq!(best.metadata["file_path"], "src/cli.rs");
+        assert_eq!(store.embedding(0).as_deref(), Some(&[0.0, 1.0, 0.0][..]));
+        assert_eq!(
+            store.chunks_with_embeddings()[1].embedding,
+            vec![0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_quantized_store_searches_and_rescores() {
+        let embeddings = [
+            vec![0.9, 0.1, 0.3],
+            vec![0.11, -0.8, 0.5],
+            vec![-0.4, 0.35, 0.7],
+        ];
+        let query = [0.11, -0.8, 0.5];
+
+        for rerank_candidates in [0, 2] {
+            let config = VectorStoreConfig {
+                quantization: Quantization::Int8,
+                rerank_candidates,
+                ..Default::default()
+            };
+            let mut store = VectorStore::with_config(3, config);
+            store
+                .add_chunks(
+                    embeddings
+                        .iter()
+                        .map(|embedding| EmbeddedChunk {
+                            id: Uuid::new_v4(),
+                            content: "test content".to_string(),
+                            embedding: embedding.clone(),
+                            metadata: HashMap::new(),
+                            document_id: None,
+                            chunk_index: 0,
+                        })
+                        .collect(),
+                )
+                .unwrap();
+            assert!(store.chunks()[0].embedding.is_empty());
+            assert_eq!(store.is_memory_mapped(), rerank_candidates > 0);
+
+            let results = store.search(&query, 2, 0.0);
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].0, 1);
+            if rerank_candidates > 0 {
+                assert!((results[0].1 - 1.0).abs() < 1e-6);
+            } else {
+                assert!((results[0].1 - 1.0).abs() < 0.01);
+            }
+
+            store.remove_where(|_| true).unwrap();
+            assert!(store.search(&query, 2, 0.0).is_empty());
+        }
+    }
 }
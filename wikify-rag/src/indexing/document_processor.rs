@@ -3,12 +3,23 @@
 //! This module handles the conversion of repository files into cheungfun Document
 //! objects with appropriate metadata and content processing.
 
+use super::plugins::{self, PluginRegistry};
 use cheungfun_core::Document;
+use futures::stream::{self, StreamExt};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 use uuid::Uuid;
 use walkdir::WalkDir;
 use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+use wikify_repo::{is_within_root, SymlinkPolicy};
+
+/// Number of files read and parsed at the same time unless configured
+/// otherwise: one per available CPU
+pub fn default_indexing_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
 
 /// Document processor that converts repository files to cheungfun Documents
 pub struct DocumentProcessor {
@@ -18,6 +29,12 @@ pub struct DocumentProcessor {
     included_extensions: Vec<String>,
     excluded_dirs: Vec<String>,
     excluded_files: Vec<String>,
+    /// Symlink handling
+    symlink_policy: SymlinkPolicy,
+    /// Loaders for formats that aren't read as plain text
+    plugins: PluginRegistry,
+    /// Number of files processed at the same time
+    concurrency: usize,
 }
 
 impl DocumentProcessor {
@@ -76,6 +93,9 @@ impl DocumentProcessor {
                 "*.dll".to_string(),
                 "*.dylib".to_string(),
             ],
+            symlink_policy: SymlinkPolicy::default(),
+            plugins: plugins::registry(),
+            concurrency: default_indexing_workers(),
         }
     }
 
@@ -97,33 +117,80 @@ impl DocumentProcessor {
         self
     }
 
+    /// Configure symlink handling
+    pub fn with_symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
+        self
+    }
+
+    /// Configure the loader and chunker plugins
+    ///
+    /// Files with an extension a plugin handles are included on top of the
+    /// included extensions. Defaults to the process-wide registry.
+    pub fn with_plugins(mut self, plugins: PluginRegistry) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Configure how many files are read and parsed at the same time
+    ///
+    /// Values below one are treated as one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
     /// Process all documents in the repository
     pub async fn process_repository(&self) -> WikifyResult<Vec<Document>> {
         info!("Processing repository at {:?}", self.base_path);
 
         // Collect all eligible files
         let files = self.collect_files().await?;
-        info!("Found {} eligible files", files.len());
+        info!(
+            "Found {} eligible files, processing {} at a time",
+            files.len(),
+            self.concurrency
+        );
 
-        // Process files in batches to avoid overwhelming the system
-        let mut documents = Vec::new();
-        let batch_size = 50; // Process 50 files at a time
-
-        for batch in files.chunks(batch_size) {
-            let batch_docs = self.process_file_batch(batch).await?;
-            documents.extend(batch_docs);
-        }
+        let documents = self.process_file_batch(&files).await?;
 
         info!("Successfully processed {} documents", documents.len());
         Ok(documents)
     }
 
+    /// Process some files of the repository, such as the ones that changed
+    ///
+    /// Files the processor would not pick up when walking the repository
+    /// (excluded directories, unknown extensions, missing files) are skipped.
+    pub async fn process_files(&self, files: &[PathBuf]) -> WikifyResult<Vec<Document>> {
+        let eligible: Vec<PathBuf> = files
+            .iter()
+            .filter(|file| file.is_file() && self.is_eligible(file))
+            .cloned()
+            .collect();
+        self.process_file_batch(&eligible).await
+    }
+
+    /// Whether a file below the base path would be collected
+    fn is_eligible(&self, file_path: &Path) -> bool {
+        let Ok(relative) = file_path.strip_prefix(&self.base_path) else {
+            return false;
+        };
+        let in_excluded_dir = relative.parent().is_some_and(|parent| {
+            parent.components().any(|component| {
+                let name = component.as_os_str().to_string_lossy();
+                self.excluded_dirs.iter().any(|excluded| *excluded == name)
+            })
+        });
+        !in_excluded_dir && self.should_include_file(file_path)
+    }
+
     /// Collect all eligible files from the repository
     async fn collect_files(&self) -> WikifyResult<Vec<PathBuf>> {
         let mut files = Vec::new();
 
         for entry in WalkDir::new(&self.base_path)
-            .follow_links(false)
+            .follow_links(self.symlink_policy == SymlinkPolicy::FollowWithinRoot)
             .into_iter()
             .filter_entry(|e| self.should_traverse_entry(e))
         {
@@ -141,12 +208,19 @@ impl DocumentProcessor {
         Ok(files)
     }
 
-    /// Process a batch of files
+    /// Process a batch of files, up to `concurrency` at a time
+    ///
+    /// Documents come back in the order of the files.
     async fn process_file_batch(&self, files: &[PathBuf]) -> WikifyResult<Vec<Document>> {
-        let mut documents = Vec::new();
+        let results: Vec<_> = stream::iter(files)
+            .map(|file_path| async move { (file_path, self.process_single_file(file_path).await) })
+            .buffered(self.concurrency)
+            .collect()
+            .await;
 
-        for file_path in files {
-            match self.process_single_file(file_path).await {
+        let mut documents = Vec::new();
+        for (file_path, result) in results {
+            match result {
                 Ok(Some(doc)) => documents.push(doc),
                 Ok(None) => {
                     debug!("Skipped file: {:?}", file_path);
@@ -166,16 +240,29 @@ impl DocumentProcessor {
         debug!("Processing file: {:?}", file_path);
 
         // Read file content
-        let content =
-            tokio::fs::read_to_string(file_path)
-                .await
-                .map_err(|e| WikifyError::Repository {
-                    message: format!("Failed to read file {:?}: {}", file_path, e),
-                    source: Some(Box::new(e)),
-                    context: ErrorContext::new("document_processor")
-                        .with_operation("read_file")
-                        .with_metadata("file_path", &file_path.to_string_lossy()),
-                })?;
+        let bytes = tokio::fs::read(file_path)
+            .await
+            .map_err(|e| WikifyError::Repository {
+                message: format!("Failed to read file {:?}: {}", file_path, e),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("document_processor")
+                    .with_operation("read_file")
+                    .with_metadata("file_path", &file_path.to_string_lossy()),
+            })?;
+        let loader = self.plugins.loader_for(file_path);
+        let content = match &loader {
+            Some(loader) => match loader.load(file_path, &bytes)? {
+                Some(content) => content,
+                None => return Ok(None),
+            },
+            None => String::from_utf8(bytes).map_err(|e| WikifyError::Repository {
+                message: format!("File {:?} is not valid UTF-8", file_path),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("document_processor")
+                    .with_operation("read_file")
+                    .with_metadata("file_path", &file_path.to_string_lossy()),
+            })?,
+        };
 
         // Skip empty files
         if content.trim().is_empty() {
@@ -224,6 +311,13 @@ impl DocumentProcessor {
             );
         }
 
+        if let Some(loader) = &loader {
+            metadata.insert(
+                "loader".to_string(),
+                serde_json::Value::String(loader.name().to_string()),
+            );
+        }
+
         // Add file size
         if let Ok(file_metadata) = tokio::fs::metadata(file_path).await {
             metadata.insert(
@@ -245,6 +339,14 @@ impl DocumentProcessor {
 
     /// Check if we should traverse into a directory entry
     fn should_traverse_entry(&self, entry: &walkdir::DirEntry) -> bool {
+        // Never follow a symlink out of the repository
+        if entry.path_is_symlink()
+            && (self.symlink_policy == SymlinkPolicy::Skip
+                || !is_within_root(&self.base_path, entry.path()))
+        {
+            return false;
+        }
+
         if entry.file_type().is_dir() {
             let dir_name = entry.file_name().to_string_lossy();
             !self
@@ -260,7 +362,12 @@ impl DocumentProcessor {
     fn should_include_file(&self, file_path: &Path) -> bool {
         // Check file extension
         if let Some(extension) = file_path.extension().and_then(|ext| ext.to_str()) {
-            if !self.included_extensions.contains(&extension.to_string()) {
+            if !self.included_extensions.contains(&extension.to_string())
+                && !self
+                    .plugins
+                    .extensions()
+                    .contains(&extension.to_lowercase())
+            {
                 return false;
             }
         } else {
@@ -345,3 +452,34 @@ impl DocumentProcessor {
         .to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parallel_processing_keeps_file_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        for i in 0..20 {
+            let path = dir.path().join(format!("file_{:02}.rs", i));
+            std::fs::write(&path, format!("fn f{}() {{}}", i)).unwrap();
+            files.push(path);
+        }
+        std::fs::write(dir.path().join("empty.rs"), "  \n").unwrap();
+        files.insert(5, dir.path().join("empty.rs"));
+
+        let documents = DocumentProcessor::new(dir.path())
+            .with_concurrency(4)
+            .process_files(&files)
+            .await
+            .unwrap();
+
+        let paths: Vec<&str> = documents
+            .iter()
+            .filter_map(|doc| doc.metadata.get("file_path")?.as_str())
+            .collect();
+        let expected: Vec<String> = (0..20).map(|i| format!("file_{:02}.rs", i)).collect();
+        assert_eq!(paths, expected);
+    }
+}
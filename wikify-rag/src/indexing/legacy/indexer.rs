@@ -13,6 +13,7 @@ use cheungfun_indexing::node_parser::{
     text::{CodeSplitter, MarkdownNodeParser, SentenceSplitter, TokenTextSplitter},
     NodeParser,
 };
+use futures::stream::{self, StreamExt, TryStreamExt};
 use tracing::{debug, info, warn};
 use wikify_core::{ErrorContext, WikifyError, WikifyResult};
 
@@ -169,16 +170,33 @@ impl LegacyDocumentIndexer {
         })
     }
 
+    /// Set how many documents are split at the same time
+    ///
+    /// Values below one are treated as one.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.config.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
     /// Index a batch of documents into nodes (internal implementation)
+    ///
+    /// Up to `max_concurrency` documents are split at the same time; nodes
+    /// keep the order of the documents.
     async fn index_documents_impl(&self, documents: Vec<Document>) -> WikifyResult<Vec<Node>> {
-        info!("Indexing {} documents", documents.len());
-
-        let mut all_nodes = Vec::new();
-
-        for document in documents {
-            let nodes = self.index_single_document(document).await?;
-            all_nodes.extend(nodes);
-        }
+        info!(
+            "Indexing {} documents, {} at a time",
+            documents.len(),
+            self.config.max_concurrency
+        );
+
+        let all_nodes: Vec<Node> = stream::iter(documents)
+            .map(|document| self.index_single_document(document))
+            .buffered(self.config.max_concurrency.max(1))
+            .try_collect::<Vec<_>>()
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
 
         info!("Generated {} nodes from documents", all_nodes.len());
         Ok(all_nodes)
@@ -0,0 +1,90 @@
+//! Line ranges of chunks
+//!
+//! Splitters only keep the text of a chunk, so answers could cite files but
+//! not where in them. After splitting, each chunk is located in the text of
+//! its document and its first and last lines are recorded as the
+//! `start_line` and `end_line` metadata (1-based, inclusive).
+
+use cheungfun_core::{Document, Node};
+use std::collections::HashMap;
+
+/// Record the lines each node spans in its document
+///
+/// Nodes are matched to documents by ID, or by `file_path` for splitters
+/// that don't keep the ID. Nodes whose text can't be found are left as is.
+pub fn annotate_line_ranges(documents: &[Document], nodes: &mut [Node]) {
+    let by_id: HashMap<_, &Document> = documents.iter().map(|doc| (doc.id, doc)).collect();
+    let by_path: HashMap<&str, &Document> = documents
+        .iter()
+        .filter_map(|doc| Some((doc.metadata.get("file_path")?.as_str()?, doc)))
+        .collect();
+    // Chunks of a document come in order, so each search starts after the
+    // start of the previous chunk (chunks may overlap)
+    let mut cursors: HashMap<uuid::Uuid, usize> = HashMap::new();
+
+    for node in nodes {
+        let document = by_id.get(&node.source_document_id).copied().or_else(|| {
+            let path = node.metadata.get("file_path")?.as_str()?;
+            by_path.get(path).copied()
+        });
+        let Some(document) = document else {
+            continue;
+        };
+        let cursor = cursors.entry(document.id).or_default();
+        if let Some(range) = locate(&document.content, &node.content, *cursor) {
+            *cursor = range.start_byte + 1;
+            node.metadata
+                .insert("start_line".to_string(), range.start_line.into());
+            node.metadata
+                .insert("end_line".to_string(), range.end_line.into());
+        }
+    }
+}
+
+/// Where a chunk is in a text
+#[derive(Debug, PartialEq, Eq)]
+struct LineRange {
+    start_byte: usize,
+    start_line: usize,
+    end_line: usize,
+}
+
+/// Find a chunk in a text, preferring matches at or after `from`
+fn locate(text: &str, chunk: &str, from: usize) -> Option<LineRange> {
+    let chunk = chunk.trim();
+    if chunk.is_empty() {
+        return None;
+    }
+    let start_byte = text
+        .get(from..)
+        .and_then(|rest| rest.find(chunk))
+        .map(|offset| from + offset)
+        .or_else(|| text.find(chunk))?;
+
+    let start_line = text[..start_byte].matches('\n').count() + 1;
+    Some(LineRange {
+        start_byte,
+        start_line,
+        end_line: start_line + chunk.matches('\n').count(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_are_located_in_order() {
+        let text = "fn a() {}\n\nfn b() {\n    a();\n}\n\nfn b() {\n    a();\n}\n";
+
+        let first = locate(text, "fn b() {\n    a();\n}\n", 0).unwrap();
+        assert_eq!((first.start_line, first.end_line), (3, 5));
+
+        // The same text again is found after the previous chunk
+        let second = locate(text, "fn b() {\n    a();\n}", first.start_byte + 1).unwrap();
+        assert_eq!((second.start_line, second.end_line), (7, 9));
+
+        assert_eq!(locate(text, "fn c() {}", 0), None);
+        assert_eq!(locate(text, "  \n", 0), None);
+    }
+}
@@ -11,6 +11,7 @@
 //! - **enhanced**: Advanced implementation using cheungfun's features
 //! - **factory**: Factory pattern for creating indexers
 //! - **pipeline**: High-level processing pipelines
+//! - **plugins**: Loaders and chunkers for extra formats, keyed by extension
 //!
 //! # Quick Start
 //!
@@ -28,7 +29,9 @@ pub mod document_processor;
 pub mod enhanced;
 pub mod factory;
 pub mod legacy;
+pub mod line_ranges;
 pub mod pipeline;
+pub mod plugins;
 pub mod traits;
 
 // Re-export main interfaces and factory functions
@@ -39,6 +42,8 @@ pub use traits::*;
 pub use document_processor::*;
 pub use enhanced::*;
 pub use legacy::*;
+pub use line_ranges::annotate_line_ranges;
+pub use plugins::{ChunkerPlugin, LoaderPlugin, PluginRegistry};
 
 // Re-export our own pipeline types with explicit names to avoid conflicts
 pub use pipeline::{
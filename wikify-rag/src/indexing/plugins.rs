@@ -0,0 +1,489 @@
+//! Loader and chunker plugins
+//!
+//! Formats the built-in loaders and splitters don't understand, such as
+//! `.proto`, `.tf` or `.ipynb`, are supported by registering a
+//! [`LoaderPlugin`] that turns a file into text and/or a [`ChunkerPlugin`]
+//! that splits a document into chunks. Plugins are keyed by file extension
+//! (without the dot) or by MIME type, and registered in the process-wide
+//! registry before indexing starts:
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use wikify_rag::indexing::plugins::{self, BlockChunker};
+//!
+//! plugins::register_chunker(&["thrift"], Arc::new(BlockChunker::new(1500)));
+//! ```
+//!
+//! Built-in plugins can also be enabled for other keys by name with
+//! [`PluginRegistry::enable_builtin`], so configuration can map a niche
+//! extension to an existing strategy without code.
+
+use cheungfun_core::{Document, Node};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, OnceLock, RwLock};
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// Turns a file into the text that gets indexed
+pub trait LoaderPlugin: Send + Sync {
+    /// Name shown in logs and recorded as the `loader` metadata
+    fn name(&self) -> &str;
+
+    /// Text of a file, or `None` to skip it
+    fn load(&self, path: &Path, bytes: &[u8]) -> WikifyResult<Option<String>>;
+}
+
+/// Splits a document into chunks
+pub trait ChunkerPlugin: Send + Sync {
+    /// Name shown in logs and recorded as the `chunker` metadata
+    fn name(&self) -> &str;
+
+    /// Chunks of the document's content, in order
+    fn split(&self, document: &Document) -> WikifyResult<Vec<String>>;
+}
+
+/// Loaders and chunkers keyed by lowercase extension or MIME type
+#[derive(Clone, Default)]
+pub struct PluginRegistry {
+    loaders: HashMap<String, Arc<dyn LoaderPlugin>>,
+    chunkers: HashMap<String, Arc<dyn ChunkerPlugin>>,
+}
+
+impl PluginRegistry {
+    /// Registry without any plugin
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry with the built-in plugins for notebooks, Protocol Buffers
+    /// and Terraform
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register_loader(
+            &["ipynb", "application/x-ipynb+json"],
+            Arc::new(NotebookLoader),
+        );
+        registry.register_chunker(
+            &["proto", "tf", "hcl"],
+            Arc::new(BlockChunker::new(BlockChunker::DEFAULT_MAX_CHARS)),
+        );
+        registry
+    }
+
+    /// Use `loader` for files with any of the extensions or MIME types
+    pub fn register_loader(&mut self, keys: &[&str], loader: Arc<dyn LoaderPlugin>) {
+        for key in keys {
+            self.loaders.insert(normalize_key(key), loader.clone());
+        }
+    }
+
+    /// Use `chunker` for documents with any of the extensions or MIME types
+    pub fn register_chunker(&mut self, keys: &[&str], chunker: Arc<dyn ChunkerPlugin>) {
+        for key in keys {
+            self.chunkers.insert(normalize_key(key), chunker.clone());
+        }
+    }
+
+    /// Enable a built-in plugin (`notebook` or `blocks`) for an extension or
+    /// MIME type
+    pub fn enable_builtin(&mut self, key: &str, name: &str) -> WikifyResult<()> {
+        match name {
+            "notebook" => self.register_loader(&[key], Arc::new(NotebookLoader)),
+            "blocks" => self.register_chunker(
+                &[key],
+                Arc::new(BlockChunker::new(BlockChunker::DEFAULT_MAX_CHARS)),
+            ),
+            _ => {
+                return Err(Box::new(WikifyError::Config {
+                    message: format!("Unknown indexing plugin: {}", name),
+                    source: None,
+                    context: ErrorContext::new("indexing_plugins")
+                        .with_operation("enable_builtin")
+                        .with_suggestion("Use one of: notebook, blocks"),
+                }))
+            }
+        }
+        Ok(())
+    }
+
+    /// File extensions some plugin handles
+    pub fn extensions(&self) -> HashSet<String> {
+        self.loaders
+            .keys()
+            .chain(self.chunkers.keys())
+            .filter(|key| !key.contains('/'))
+            .cloned()
+            .collect()
+    }
+
+    /// Loader for a file, by extension
+    pub fn loader_for(&self, path: &Path) -> Option<Arc<dyn LoaderPlugin>> {
+        let extension = path.extension()?.to_str()?;
+        self.loaders.get(&normalize_key(extension)).cloned()
+    }
+
+    /// Chunker for a document, by the extension of its `file_path` or its
+    /// `mime_type` metadata
+    pub fn chunker_for(&self, document: &Document) -> Option<Arc<dyn ChunkerPlugin>> {
+        let metadata = |key: &str| document.metadata.get(key).and_then(|v| v.as_str());
+        let extension = metadata("file_extension").map(str::to_string).or_else(|| {
+            let path = Path::new(metadata("file_path")?);
+            Some(path.extension()?.to_str()?.to_string())
+        });
+        extension
+            .and_then(|ext| self.chunkers.get(&normalize_key(&ext)))
+            .or_else(|| self.chunkers.get(&normalize_key(metadata("mime_type")?)))
+            .cloned()
+    }
+
+    /// Index documents, splitting the ones a chunker plugin handles and
+    /// passing the rest to `index`
+    pub async fn index_documents<F, Fut>(
+        &self,
+        documents: Vec<Document>,
+        index: F,
+    ) -> WikifyResult<Vec<Node>>
+    where
+        F: FnOnce(Vec<Document>) -> Fut,
+        Fut: Future<Output = WikifyResult<Vec<Node>>>,
+    {
+        let mut plugin_nodes = Vec::new();
+        let mut rest = Vec::new();
+        for document in documents {
+            match self.chunker_for(&document) {
+                Some(chunker) => plugin_nodes.extend(chunk_document(&document, chunker.as_ref())?),
+                None => rest.push(document),
+            }
+        }
+
+        let mut nodes = if rest.is_empty() {
+            Vec::new()
+        } else {
+            index(rest).await?
+        };
+        nodes.extend(plugin_nodes);
+        Ok(nodes)
+    }
+}
+
+/// Lowercase key without a leading dot
+fn normalize_key(key: &str) -> String {
+    key.trim_start_matches('.').to_lowercase()
+}
+
+/// Nodes of a document split by a chunker plugin
+fn chunk_document(document: &Document, chunker: &dyn ChunkerPlugin) -> WikifyResult<Vec<Node>> {
+    let chunks = chunker.split(document)?;
+    tracing::debug!(
+        chunker = chunker.name(),
+        chunks = chunks.len(),
+        "Split document with plugin"
+    );
+
+    let mut cursor = 0;
+    let nodes = chunks
+        .into_iter()
+        .filter(|chunk| !chunk.trim().is_empty())
+        .enumerate()
+        .map(|(chunk_index, content)| {
+            let start = document.content[cursor..]
+                .find(&content)
+                .map(|offset| cursor + offset);
+            if let Some(start) = start {
+                cursor = start;
+            }
+            let mut metadata = document.metadata.clone();
+            metadata.insert("chunker".to_string(), chunker.name().into());
+            let mimetype = metadata
+                .get("mime_type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("text/plain")
+                .to_string();
+            Node {
+                id: uuid::Uuid::new_v4(),
+                metadata,
+                embedding: None,
+                sparse_embedding: None,
+                relationships: cheungfun_core::relationships::NodeRelationships::new(),
+                source_document_id: document.id,
+                chunk_info: cheungfun_core::types::ChunkInfo {
+                    start_char_idx: start,
+                    end_char_idx: start.map(|start| start + content.len()),
+                    chunk_index,
+                },
+                hash: None,
+                mimetype,
+                excluded_embed_metadata_keys: HashSet::new(),
+                excluded_llm_metadata_keys: HashSet::new(),
+                text_template: "{content}\n\n{metadata_str}".to_string(),
+                metadata_separator: "\n".to_string(),
+                metadata_template: "{key}: {value}".to_string(),
+                content,
+            }
+        })
+        .collect();
+    Ok(nodes)
+}
+
+/// Jupyter notebooks as Markdown: Markdown cells as they are and code cells
+/// as fenced blocks in the kernel's language
+pub struct NotebookLoader;
+
+impl LoaderPlugin for NotebookLoader {
+    fn name(&self) -> &str {
+        "notebook"
+    }
+
+    fn load(&self, path: &Path, bytes: &[u8]) -> WikifyResult<Option<String>> {
+        let notebook: serde_json::Value =
+            serde_json::from_slice(bytes).map_err(|e| WikifyError::Indexing {
+                message: format!("Invalid notebook {}: {}", path.display(), e),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("indexing_plugins").with_operation("load_notebook"),
+            })?;
+        let language = notebook
+            .pointer("/metadata/kernelspec/language")
+            .or_else(|| notebook.pointer("/metadata/language_info/name"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("python");
+
+        let cells: Vec<String> = notebook
+            .get("cells")
+            .and_then(|cells| cells.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|cell| {
+                let source = match cell.get("source")? {
+                    serde_json::Value::String(source) => source.clone(),
+                    serde_json::Value::Array(lines) => {
+                        lines.iter().filter_map(|line| line.as_str()).collect()
+                    }
+                    _ => return None,
+                };
+                if source.trim().is_empty() {
+                    return None;
+                }
+                match cell.get("cell_type")?.as_str()? {
+                    "markdown" => Some(source),
+                    "code" => Some(format!("```{}\n{}\n```", language, source.trim_end())),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        Ok((!cells.is_empty()).then(|| cells.join("\n\n")))
+    }
+}
+
+/// Splits declaration-oriented files, such as `.proto` or `.tf`, between
+/// top-level blocks and packs consecutive blocks into chunks
+pub struct BlockChunker {
+    max_chars: usize,
+}
+
+impl BlockChunker {
+    pub const DEFAULT_MAX_CHARS: usize = 1500;
+
+    pub fn new(max_chars: usize) -> Self {
+        Self {
+            max_chars: max_chars.max(1),
+        }
+    }
+
+    /// Top-level blocks, separated by blank lines outside of braces
+    fn blocks(text: &str) -> Vec<String> {
+        let mut blocks = Vec::new();
+        let mut current = String::new();
+        let mut depth: i64 = 0;
+        for line in text.lines() {
+            if line.trim().is_empty() && depth <= 0 {
+                if !current.trim().is_empty() {
+                    blocks.push(std::mem::take(&mut current));
+                }
+                continue;
+            }
+            depth += line.matches('{').count() as i64 - line.matches('}').count() as i64;
+            current.push_str(line);
+            current.push('\n');
+        }
+        if !current.trim().is_empty() {
+            blocks.push(current);
+        }
+        blocks
+    }
+}
+
+impl ChunkerPlugin for BlockChunker {
+    fn name(&self) -> &str {
+        "blocks"
+    }
+
+    fn split(&self, document: &Document) -> WikifyResult<Vec<String>> {
+        let mut chunks = Vec::new();
+        let mut current = String::new();
+        for block in Self::blocks(&document.content) {
+            if !current.is_empty() && current.len() + block.len() > self.max_chars {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if block.len() > self.max_chars {
+                // A single oversized block is split between lines
+                for line in block.lines() {
+                    if !current.is_empty() && current.len() + line.len() + 1 > self.max_chars {
+                        chunks.push(std::mem::take(&mut current));
+                    }
+                    current.push_str(line);
+                    current.push('\n');
+                }
+            } else {
+                if !current.is_empty() {
+                    current.push('\n');
+                }
+                current.push_str(&block);
+            }
+        }
+        if !current.trim().is_empty() {
+            chunks.push(current);
+        }
+        Ok(chunks
+            .into_iter()
+            .map(|chunk| chunk.trim_end().to_string())
+            .collect())
+    }
+}
+
+/// Process-wide registry, starting with the built-in plugins
+fn global() -> &'static RwLock<PluginRegistry> {
+    static REGISTRY: OnceLock<RwLock<PluginRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(PluginRegistry::with_builtins()))
+}
+
+/// Snapshot of the process-wide registry
+pub fn registry() -> PluginRegistry {
+    global().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Register a loader in the process-wide registry
+pub fn register_loader(keys: &[&str], loader: Arc<dyn LoaderPlugin>) {
+    global()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register_loader(keys, loader);
+}
+
+/// Register a chunker in the process-wide registry
+pub fn register_chunker(keys: &[&str], chunker: Arc<dyn ChunkerPlugin>) {
+    global()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .register_chunker(keys, chunker);
+}
+
+/// Enable a built-in plugin in the process-wide registry
+pub fn enable_builtin(key: &str, name: &str) -> WikifyResult<()> {
+    global()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .enable_builtin(key, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(file_path: &str, content: &str) -> Document {
+        let mut metadata = HashMap::new();
+        metadata.insert("file_path".to_string(), file_path.into());
+        Document {
+            id: uuid::Uuid::new_v4(),
+            content: content.to_string(),
+            metadata,
+            embedding: None,
+        }
+    }
+
+    #[test]
+    fn test_notebook_cells_become_markdown() {
+        let notebook = serde_json::json!({
+            "metadata": {"kernelspec": {"language": "python"}},
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n", "Loads the data."]},
+                {"cell_type": "code", "source": "import pandas as pd\n", "outputs": []},
+                {"cell_type": "code", "source": []}
+            ]
+        });
+        let text = NotebookLoader
+            .load(Path::new("analysis.ipynb"), notebook.to_string().as_bytes())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            text,
+            "# Analysis\nLoads the data.\n\n```python\nimport pandas as pd\n```"
+        );
+    }
+
+    #[test]
+    fn test_block_chunker_keeps_top_level_blocks_together() {
+        let proto = "syntax = \"proto3\";\n\nmessage User {\n  string id = 1;\n\n  string name = 2;\n}\n\nmessage Team {\n  repeated User members = 1;\n}\n";
+        let chunker = BlockChunker::new(60);
+        let chunks = chunker.split(&document("api.proto", proto)).unwrap();
+        assert_eq!(
+            chunks,
+            vec![
+                "syntax = \"proto3\";",
+                "message User {\n  string id = 1;\n\n  string name = 2;\n}",
+                "message Team {\n  repeated User members = 1;\n}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registry_routes_by_extension_and_mime_type() {
+        let mut registry = PluginRegistry::with_builtins();
+        assert!(registry
+            .loader_for(Path::new("notes/Analysis.IPYNB"))
+            .is_some());
+        assert!(registry
+            .chunker_for(&document("infra/main.tf", "resource {}"))
+            .is_some());
+        assert!(registry
+            .chunker_for(&document("src/lib.rs", "fn main() {}"))
+            .is_none());
+
+        let mut typed = document("schema", "type Query {}");
+        typed
+            .metadata
+            .insert("mime_type".to_string(), "application/graphql".into());
+        assert!(registry.chunker_for(&typed).is_none());
+        registry
+            .enable_builtin("application/graphql", "blocks")
+            .unwrap();
+        assert!(registry.chunker_for(&typed).is_some());
+        assert!(registry.enable_builtin("graphql", "ast").is_err());
+
+        assert!(registry.extensions().contains("proto"));
+        assert!(!registry.extensions().contains("application/graphql"));
+    }
+
+    #[tokio::test]
+    async fn test_plugin_documents_bypass_the_indexer() {
+        let registry = PluginRegistry::with_builtins();
+        let documents = vec![
+            document("api.proto", "message A {}\n\nmessage B {}\n"),
+            document("src/lib.rs", "fn main() {}"),
+        ];
+
+        let nodes = registry
+            .index_documents(documents, |rest| async move {
+                assert_eq!(rest.len(), 1);
+                Ok(Vec::new())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].content, "message A {}\n\nmessage B {}");
+        assert_eq!(nodes[0].metadata["chunker"], "blocks");
+        assert_eq!(nodes[0].chunk_info.start_char_idx, Some(0));
+    }
+}
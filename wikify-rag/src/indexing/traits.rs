@@ -144,11 +144,17 @@ impl DocumentIndexer {
     }
 
     /// Index a batch of documents
+    ///
+    /// Documents a registered chunker plugin handles are split by the plugin.
     pub async fn index_documents(&self, documents: Vec<Document>) -> WikifyResult<Vec<Node>> {
-        match self {
-            DocumentIndexer::Legacy(indexer) => indexer.index_documents(documents).await,
-            DocumentIndexer::Enhanced(indexer) => indexer.index_documents(documents).await,
-        }
+        crate::indexing::plugins::registry()
+            .index_documents(documents, |documents| async move {
+                match self {
+                    DocumentIndexer::Legacy(indexer) => indexer.index_documents(documents).await,
+                    DocumentIndexer::Enhanced(indexer) => indexer.index_documents(documents).await,
+                }
+            })
+            .await
     }
 
     /// Index a single document
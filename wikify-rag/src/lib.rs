@@ -9,11 +9,13 @@ pub mod embeddings;
 pub mod indexing;
 pub mod indexing_manager;
 pub mod llm_client;
+mod quantization;
 pub mod rag_pipeline;
 pub mod retriever;
 pub mod storage;
 pub mod token_counter;
 pub mod types;
+mod vector_file;
 
 pub use chat::*;
 pub use embeddings::*;
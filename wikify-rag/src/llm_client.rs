@@ -3,11 +3,46 @@
 //! This module provides a unified interface for interacting with various
 //! LLM providers through the siumai framework.
 
-use crate::types::{LlmConfig, RagError, RagResult};
+use crate::types::{unsupported_provider, LlmConfig, RagError, RagResult};
 use siumai::models;
 use siumai::prelude::*;
 use std::time::Instant;
 use tracing::{debug, info, warn};
+use wikify_core::{retry_with_backoff, RetryConfig};
+
+/// Messages of provider errors that are worth retrying: rate limits,
+/// timeouts, dropped connections and server errors
+const TRANSIENT_ERROR_MARKERS: &[&str] = &[
+    "rate limit",
+    "too many requests",
+    "429",
+    "timeout",
+    "timed out",
+    "connection",
+    "overloaded",
+    "temporarily unavailable",
+    "500",
+    "502",
+    "503",
+    "504",
+    "529",
+];
+
+/// Retries of LLM and embedding provider calls
+pub(crate) fn provider_retry() -> RetryConfig {
+    RetryConfig::default()
+}
+
+/// Whether a provider call failed for a reason that may pass
+///
+/// Providers report errors as text, so they are recognized by their
+/// message; authentication and request errors are not retried.
+pub(crate) fn is_transient_error(error: &impl std::fmt::Display) -> bool {
+    let message = error.to_string().to_lowercase();
+    TRANSIENT_ERROR_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+}
 
 /// Unified LLM client that supports multiple providers
 pub struct WikifyLlmClient {
@@ -38,17 +73,18 @@ impl WikifyLlmClient {
         eprintln!("🔍 Building {} client...", config.provider);
 
         match config.provider.as_str() {
+            #[cfg(feature = "openai")]
             "openai" => {
                 eprintln!("🔑 Looking for OpenAI API key...");
                 let api_key = config
                     .api_key
                     .clone()
                     .or_else(|| {
-                        let key = std::env::var("OPENAI_API_KEY").ok();
+                        let key = wikify_core::secrets::secret("OPENAI_API_KEY");
                         if key.is_some() {
-                            eprintln!("✅ Found OPENAI_API_KEY in environment");
+                            eprintln!("✅ Found OPENAI_API_KEY");
                         } else {
-                            eprintln!("❌ OPENAI_API_KEY not found in environment");
+                            eprintln!("❌ OPENAI_API_KEY not found in the keyring or environment");
                         }
                         key
                     })
@@ -79,11 +115,12 @@ impl WikifyLlmClient {
                 eprintln!("✅ OpenAI client built successfully");
                 Ok(Box::new(client))
             }
+            #[cfg(feature = "anthropic")]
             "anthropic" => {
                 let api_key = config
                     .api_key
                     .clone()
-                    .or_else(|| std::env::var("ANTHROPIC_API_KEY").ok())
+                    .or_else(|| wikify_core::secrets::secret("ANTHROPIC_API_KEY"))
                     .ok_or_else(|| RagError::Config("Anthropic API key not found".to_string()))?;
 
                 let mut builder = LlmBuilder::new()
@@ -102,6 +139,7 @@ impl WikifyLlmClient {
 
                 Ok(Box::new(client))
             }
+            #[cfg(feature = "ollama")]
             "ollama" => {
                 let base_url = config
                     .base_url
@@ -125,11 +163,12 @@ impl WikifyLlmClient {
 
                 Ok(Box::new(client))
             }
+            #[cfg(feature = "groq")]
             "groq" => {
                 let api_key = config
                     .api_key
                     .clone()
-                    .or_else(|| std::env::var("GROQ_API_KEY").ok())
+                    .or_else(|| wikify_core::secrets::secret("GROQ_API_KEY"))
                     .ok_or_else(|| RagError::Config("Groq API key not found".to_string()))?;
 
                 let mut builder = LlmBuilder::new()
@@ -149,10 +188,7 @@ impl WikifyLlmClient {
 
                 Ok(Box::new(client))
             }
-            provider => Err(RagError::Config(format!(
-                "Unsupported LLM provider: {}",
-                provider
-            ))),
+            provider => Err(RagError::Config(unsupported_provider("LLM", provider))),
         }
     }
 
@@ -162,11 +198,14 @@ impl WikifyLlmClient {
 
         debug!("Generating response with {} messages", messages.len());
 
-        let response = self
-            .client
-            .chat(messages)
-            .await
-            .map_err(|e| RagError::Llm(format!("LLM generation failed: {}", e)))?;
+        let response = retry_with_backoff(
+            &provider_retry(),
+            "llm_generate",
+            is_transient_error,
+            || self.client.chat(messages.clone()),
+        )
+        .await
+        .map_err(|e| RagError::Llm(format!("LLM generation failed: {}", e)))?;
 
         let generation_time = start_time.elapsed();
 
@@ -196,8 +235,10 @@ impl WikifyLlmClient {
     /// Generate embeddings (if the provider supports it)
     pub async fn generate_embeddings(&self, texts: Vec<String>) -> RagResult<Vec<Vec<f32>>> {
         if let Some(embedding_client) = self.client.as_embedding_capability() {
-            let response = embedding_client
-                .embed(texts)
+            let response =
+                retry_with_backoff(&provider_retry(), "llm_embed", is_transient_error, || {
+                    embedding_client.embed(texts.clone())
+                })
                 .await
                 .map_err(|e| RagError::Embedding(format!("Embedding generation failed: {}", e)))?;
 
@@ -344,7 +385,7 @@ pub async fn create_auto_client() -> RagResult<WikifyLlmClient> {
     ];
 
     for (provider_name, env_var, config) in providers {
-        if std::env::var(env_var).is_ok() {
+        if wikify_core::secrets::secret(env_var).is_some() {
             info!("Auto-detected {} provider", provider_name);
             match WikifyLlmClient::new(config).await {
                 Ok(client) => return Ok(client),
@@ -361,3 +402,20 @@ pub async fn create_auto_client() -> RagResult<WikifyLlmClient> {
     let ollama_config = configs::ollama_llama3(None);
     WikifyLlmClient::new(ollama_config).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_provider_errors_are_recognized() {
+        assert!(is_transient_error(&"HTTP 429 Too Many Requests"));
+        assert!(is_transient_error(&"Rate limit exceeded, retry later"));
+        assert!(is_transient_error(&"API error 503: Service Unavailable"));
+        assert!(is_transient_error(&"request timed out"));
+        assert!(!is_transient_error(
+            &"Authentication failed: invalid API key"
+        ));
+        assert!(!is_transient_error(&"Model not found"));
+    }
+}
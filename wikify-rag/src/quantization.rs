@@ -0,0 +1,150 @@
+//! Scalar quantization of embeddings
+//!
+//! Each vector is stored as one signed byte per dimension together with the
+//! factor it was scaled by, a quarter of the memory of `f32` vectors.
+//! Cosine similarity does not depend on that factor, so searches compare
+//! queries against the bytes directly.
+
+/// Vectors of one dimension quantized to `i8`, stored back to back
+#[derive(Debug, Clone)]
+pub(crate) struct QuantizedVectors {
+    dimension: usize,
+    codes: Vec<i8>,
+    /// Factor each vector was divided by before rounding
+    scales: Vec<f32>,
+    /// Length of each quantized vector, for cosine similarity
+    norms: Vec<f32>,
+}
+
+impl QuantizedVectors {
+    pub(crate) fn new(dimension: usize) -> Self {
+        Self {
+            dimension,
+            codes: Vec::new(),
+            scales: Vec::new(),
+            norms: Vec::new(),
+        }
+    }
+
+    /// Number of stored vectors
+    pub(crate) fn len(&self) -> usize {
+        self.scales.len()
+    }
+
+    /// Quantize and append vectors, which must have the dimension of the store
+    pub(crate) fn extend<'a>(&mut self, vectors: impl IntoIterator<Item = &'a [f32]>) {
+        for vector in vectors {
+            debug_assert_eq!(vector.len(), self.dimension);
+            let max = vector.iter().fold(0.0f32, |max, x| max.max(x.abs()));
+            let scale = if max > 0.0 { max / 127.0 } else { 1.0 };
+            let start = self.codes.len();
+            self.codes.extend(
+                vector
+                    .iter()
+                    .map(|x| (x / scale).round().clamp(-127.0, 127.0) as i8),
+            );
+            let norm = self.codes[start..]
+                .iter()
+                .map(|&c| (c as f32) * (c as f32))
+                .sum::<f32>()
+                .sqrt();
+            self.scales.push(scale);
+            self.norms.push(norm);
+        }
+    }
+
+    /// Cosine similarity between a query and the vector at an index
+    pub(crate) fn similarity(&self, query: &[f32], query_norm: f32, index: usize) -> f32 {
+        let Some(codes) = self.codes(index) else {
+            return 0.0;
+        };
+        let norm = self.norms[index];
+        if query.len() != self.dimension || query_norm == 0.0 || norm == 0.0 {
+            return 0.0;
+        }
+        let dot: f32 = query.iter().zip(codes).map(|(q, &c)| q * c as f32).sum();
+        dot / (query_norm * norm)
+    }
+
+    /// Approximation of the original vector at an index
+    pub(crate) fn dequantize(&self, index: usize) -> Option<Vec<f32>> {
+        let codes = self.codes(index)?;
+        let scale = self.scales[index];
+        Some(codes.iter().map(|&c| c as f32 * scale).collect())
+    }
+
+    /// Keep only the vectors whose index `keep` accepts, preserving order
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(usize) -> bool) {
+        let mut kept = 0;
+        for index in 0..self.len() {
+            if keep(index) {
+                if kept != index {
+                    let start = index * self.dimension;
+                    self.codes
+                        .copy_within(start..start + self.dimension, kept * self.dimension);
+                    self.scales[kept] = self.scales[index];
+                    self.norms[kept] = self.norms[index];
+                }
+                kept += 1;
+            }
+        }
+        self.codes.truncate(kept * self.dimension);
+        self.scales.truncate(kept);
+        self.norms.truncate(kept);
+    }
+
+    fn codes(&self, index: usize) -> Option<&[i8]> {
+        if index >= self.len() {
+            return None;
+        }
+        let start = index * self.dimension;
+        Some(&self.codes[start..start + self.dimension])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn norm(vector: &[f32]) -> f32 {
+        vector.iter().map(|x| x * x).sum::<f32>().sqrt()
+    }
+
+    #[test]
+    fn test_similarity_stays_close_to_full_precision() {
+        let vectors = [
+            vec![0.12, -0.53, 0.88, 0.01],
+            vec![-0.7, 0.2, 0.05, 0.33],
+            vec![0.0, 0.0, 0.0, 0.0],
+        ];
+        let mut quantized = QuantizedVectors::new(4);
+        quantized.extend(vectors.iter().map(Vec::as_slice));
+        assert_eq!(quantized.len(), 3);
+
+        let query = [0.3, -0.4, 0.7, 0.2];
+        for (index, vector) in vectors[..2].iter().enumerate() {
+            let exact = query.iter().zip(vector).map(|(a, b)| a * b).sum::<f32>()
+                / (norm(&query) * norm(vector));
+            let approximate = quantized.similarity(&query, norm(&query), index);
+            assert!((exact - approximate).abs() < 0.01);
+        }
+        assert_eq!(quantized.similarity(&query, norm(&query), 2), 0.0);
+
+        let restored = quantized.dequantize(0).unwrap();
+        for (original, restored) in vectors[0].iter().zip(&restored) {
+            assert!((original - restored).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_retain_keeps_vectors_in_order() {
+        let mut quantized = QuantizedVectors::new(2);
+        quantized.extend([&[1.0, 0.0][..], &[0.0, 1.0][..], &[-1.0, 0.0][..]]);
+
+        quantized.retain(|index| index != 1);
+        assert_eq!(quantized.len(), 2);
+        let last = quantized.dequantize(1).unwrap();
+        assert!((last[0] + 1.0).abs() < 1e-6 && last[1] == 0.0);
+        assert!(quantized.dequantize(2).is_none());
+    }
+}
@@ -11,10 +11,14 @@ use crate::types::{
     DeepResearchConfig, DeepResearchResult, RagConfig, RagError, RagQuery, RagResponse,
     RagResponseMetadata, RagResult, ResearchStatus, SearchResult,
 };
-use wikify_core::{log_operation_start, log_operation_success};
+use wikify_core::{
+    log_operation_start, log_operation_success, NoProgress, ProgressReporter, ProgressStage,
+    ProgressUpdate,
+};
 
 use std::path::Path;
 use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
 /// Complete RAG pipeline that handles indexing, retrieval, and generation
@@ -24,6 +28,10 @@ pub struct RagPipeline {
     retriever: Option<DocumentRetriever>,
     llm_client: Option<WikifyLlmClient>,
     is_initialized: bool,
+    /// Token used to clone private remote repositories
+    access_token: Option<String>,
+    /// Number of files loaded and split at the same time (None = one per CPU)
+    indexing_workers: Option<usize>,
 }
 
 impl RagPipeline {
@@ -35,9 +43,44 @@ impl RagPipeline {
             retriever: None,
             llm_client: None,
             is_initialized: false,
+            access_token: None,
+            indexing_workers: None,
         }
     }
 
+    /// Set the token used to clone private remote repositories
+    pub fn with_access_token(mut self, access_token: Option<String>) -> Self {
+        self.access_token = access_token;
+        self
+    }
+
+    /// Set how many files are loaded and split at the same time while
+    /// indexing (None = one per CPU)
+    pub fn with_indexing_workers(mut self, indexing_workers: Option<usize>) -> Self {
+        self.indexing_workers = indexing_workers;
+        self
+    }
+
+    /// Number of files loaded and split at the same time while indexing
+    fn indexing_workers(&self) -> usize {
+        self.indexing_workers
+            .unwrap_or_else(crate::default_indexing_workers)
+            .max(1)
+    }
+
+    /// Document processor for a repository, using the configured workers
+    fn document_processor(&self, repo_path: &Path) -> crate::DocumentProcessor {
+        crate::DocumentProcessor::new(repo_path).with_concurrency(self.indexing_workers())
+    }
+
+    /// Empty vector store for the configured embeddings
+    fn new_vector_store(&self) -> VectorStore {
+        VectorStore::with_config(
+            self.config.embeddings.dimension,
+            self.config.vector_store.clone(),
+        )
+    }
+
     /// Create a RAG pipeline with default configuration
     pub fn with_defaults() -> Self {
         Self::new(RagConfig::default())
@@ -55,7 +98,7 @@ impl RagPipeline {
         eprintln!("✅ LLM client initialized");
 
         // Initialize vector store (empty for now)
-        self.vector_store = Some(VectorStore::new(self.config.embeddings.dimension));
+        self.vector_store = Some(self.new_vector_store());
         eprintln!("✅ Vector store initialized");
 
         self.is_initialized = true;
@@ -71,7 +114,7 @@ impl RagPipeline {
         &mut self,
         repo_path_or_url: P,
     ) -> RagResult<IndexingStats> {
-        self.index_repository_with_progress(repo_path_or_url, None)
+        self.index_repository_with_progress(repo_path_or_url, &NoProgress)
             .await
     }
 
@@ -80,7 +123,7 @@ impl RagPipeline {
         &mut self,
         repo_path_or_url: P,
     ) -> RagResult<IndexingStats> {
-        self.index_repository_enhanced_with_progress(repo_path_or_url, None)
+        self.index_repository_enhanced_with_progress(repo_path_or_url, &NoProgress)
             .await
     }
 
@@ -88,7 +131,7 @@ impl RagPipeline {
     pub async fn index_repository_enhanced_with_progress<P: AsRef<Path>>(
         &mut self,
         repo_path_or_url: P,
-        progress_callback: Option<Box<dyn Fn(String, f64, Option<String>) + Send + Sync>>,
+        progress: &dyn ProgressReporter,
     ) -> RagResult<IndexingStats> {
         if !self.is_initialized {
             return Err(RagError::Config("Pipeline not initialized".to_string()));
@@ -105,13 +148,10 @@ impl RagPipeline {
             eprintln!("🌐 Remote repository detected, cloning...");
 
             // Report progress: Cloning
-            if let Some(ref callback) = progress_callback {
-                callback(
-                    "Cloning repository".to_string(),
-                    5.0,
-                    Some("Downloading remote repository".to_string()),
-                );
-            }
+            progress.report(
+                &ProgressUpdate::new(ProgressStage::Cloning, 0.05)
+                    .with_message("Downloading remote repository"),
+            );
 
             // Clone the repository
             let cloned_path = self.clone_repository(&path_str).await?;
@@ -122,26 +162,20 @@ impl RagPipeline {
         };
 
         // Report progress: Starting
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Starting enhanced indexing".to_string(),
-                0.0,
-                Some("Initializing enhanced pipeline with AST-aware code splitting".to_string()),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Starting, 0.0)
+                .with_message("Initializing enhanced pipeline with AST-aware code splitting"),
+        );
 
         // Step 1: Create enhanced indexing pipeline
         eprintln!("🔧 Creating enhanced document indexing pipeline...");
         let enhanced_indexer = crate::create_enhanced_indexer()?;
 
         // Report progress: Document processing
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Processing documents with enhanced indexer".to_string(),
-                5.0,
-                Some("Loading and chunking files with AST-aware splitting".to_string()),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Loading, 0.05)
+                .with_message("Loading and chunking files with AST-aware splitting"),
+        );
 
         // Load documents from repository
         eprintln!("📄 Loading documents from repository...");
@@ -150,70 +184,60 @@ impl RagPipeline {
         eprintln!("📚 Found {} documents to process", documents_count);
 
         // Report progress: Document loading complete
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Documents loaded".to_string(),
-                10.0,
-                Some(format!("Found {} documents", documents_count)),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Loading, 0.1)
+                .with_message(format!("Found {} documents", documents_count)),
+        );
 
         // Index the documents using enhanced indexer
         eprintln!("⚙️ Enhanced indexing with AST-aware code splitting...");
 
         // Report progress: Starting enhanced indexing
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Enhanced indexing in progress".to_string(),
-                15.0,
-                Some(format!(
-                    "Processing {} documents with advanced algorithms",
-                    documents_count
-                )),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Indexing, 0.15).with_message(format!(
+                "Processing {} documents with advanced algorithms",
+                documents_count
+            )),
+        );
 
-        let nodes = enhanced_indexer
-            .index_documents(documents)
+        let mut nodes = enhanced_indexer
+            .index_documents(documents.clone())
             .await
             .map_err(RagError::Core)?;
+        crate::annotate_line_ranges(&documents, &mut nodes);
 
         eprintln!("📚 Enhanced indexing created {} nodes", nodes.len());
 
         // Report progress: Enhanced indexing complete
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Enhanced indexing complete".to_string(),
-                20.0,
-                Some(format!(
-                    "Created {} nodes with advanced parsing",
-                    nodes.len()
-                )),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Indexing, 0.2).with_message(format!(
+                "Created {} nodes with advanced parsing",
+                nodes.len()
+            )),
+        );
 
         // Step 2: Generate embeddings for all nodes
         let mut embedding_generator = EmbeddingGenerator::new(self.config.embeddings.clone());
         embedding_generator.initialize().await?;
 
         let embedded_chunks = embedding_generator
-            .generate_embeddings_with_progress(nodes, progress_callback.as_ref())
+            .generate_embeddings_with_progress(nodes, progress)
             .await?;
 
         let embedded_chunks_count = embedded_chunks.len();
+        let embedding_tokens = embedded_chunks
+            .iter()
+            .map(|chunk| self.estimate_tokens(&chunk.content))
+            .sum();
         info!("🔢 Generated {} embeddings", embedded_chunks_count);
 
         // Report progress: Storing vectors
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Storing vectors".to_string(),
-                96.0,
-                Some(format!(
-                    "Adding {} chunks to vector store",
-                    embedded_chunks.len()
-                )),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Storing, 0.96).with_message(format!(
+                "Adding {} chunks to vector store",
+                embedded_chunks.len()
+            )),
+        );
 
         // Step 3: Add to vector store
         if let Some(vector_store) = &mut self.vector_store {
@@ -222,13 +246,10 @@ impl RagPipeline {
         }
 
         // Report progress: Finalizing
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Finalizing enhanced indexing".to_string(),
-                100.0,
-                Some("Enhanced repository indexing complete".to_string()),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Finalizing, 1.0)
+                .with_message("Enhanced repository indexing complete"),
+        );
 
         let total_time = start_time.elapsed();
         let stats = IndexingStats {
@@ -236,6 +257,7 @@ impl RagPipeline {
             total_chunks: embedded_chunks_count,
             indexing_time_ms: total_time.as_millis() as u64,
             total_nodes: embedded_chunks_count,
+            embedding_tokens,
         };
 
         log_operation_success!(
@@ -256,7 +278,23 @@ impl RagPipeline {
     pub async fn index_repository_with_progress<P: AsRef<Path>>(
         &mut self,
         repo_path_or_url: P,
-        progress_callback: Option<Box<dyn Fn(String, f64, Option<String>) + Send + Sync>>,
+        progress: &dyn ProgressReporter,
+    ) -> RagResult<IndexingStats> {
+        self.index_repository_cancellable(repo_path_or_url, progress, &CancellationToken::new())
+            .await
+    }
+
+    /// Index a repository with progress reporting until `cancel` is triggered
+    ///
+    /// Cancellation is checked while cloning, between indexing stages and
+    /// between embedding batches, and returns [`RagError::Cancelled`]. The
+    /// vector store is only touched once all embeddings exist, so a cancelled
+    /// run leaves the previous index in place.
+    pub async fn index_repository_cancellable<P: AsRef<Path>>(
+        &mut self,
+        repo_path_or_url: P,
+        progress: &dyn ProgressReporter,
+        cancel: &CancellationToken,
     ) -> RagResult<IndexingStats> {
         if !self.is_initialized {
             return Err(RagError::Config("Pipeline not initialized".to_string()));
@@ -273,16 +311,16 @@ impl RagPipeline {
             eprintln!("🌐 Remote repository detected, cloning...");
 
             // Report progress: Cloning
-            if let Some(ref callback) = progress_callback {
-                callback(
-                    "Cloning repository".to_string(),
-                    5.0,
-                    Some("Downloading remote repository".to_string()),
-                );
-            }
+            progress.report(
+                &ProgressUpdate::new(ProgressStage::Cloning, 0.05)
+                    .with_message("Downloading remote repository"),
+            );
 
             // Clone the repository
-            let cloned_path = self.clone_repository(&path_str).await?;
+            let cloned_path = tokio::select! {
+                result = self.clone_repository(&path_str) => result?,
+                _ = cancel.cancelled() => return Err(RagError::Cancelled),
+            };
             eprintln!("✅ Repository cloned to: {}", cloned_path);
             std::path::PathBuf::from(cloned_path)
         } else {
@@ -290,79 +328,70 @@ impl RagPipeline {
         };
 
         // Report progress: Starting
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Starting indexing".to_string(),
-                0.0,
-                Some("Initializing pipeline".to_string()),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Starting, 0.0)
+                .with_message("Initializing pipeline"),
+        );
 
         // Step 1: Run document indexing pipeline
         eprintln!("🔧 Creating document indexing pipeline...");
-        let indexing_pipeline =
-            crate::create_deepwiki_compatible_indexer().map_err(RagError::Core)?;
+        let indexing_pipeline = crate::create_deepwiki_compatible_indexer()
+            .map_err(RagError::Core)?
+            .with_max_concurrency(self.indexing_workers());
 
         // Report progress: Document processing
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Processing documents".to_string(),
-                5.0,
-                Some("Loading and chunking files".to_string()),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Loading, 0.05)
+                .with_message("Loading and chunking files"),
+        );
 
         // Report progress: Loading documents
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Loading documents from repository".to_string(),
-                5.0,
-                Some(format!("Scanning repository: {}", local_path.display())),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Loading, 0.05)
+                .with_message(format!("Scanning repository: {}", local_path.display())),
+        );
 
         // Load documents from repository first
         eprintln!("📄 Loading documents from repository...");
         let documents = self.load_repository_documents(&local_path).await?;
         let documents_count = documents.len();
         eprintln!("📚 Found {} documents to process", documents_count);
+        if cancel.is_cancelled() {
+            return Err(RagError::Cancelled);
+        }
 
         // Report progress: Document loading complete
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Documents loaded".to_string(),
-                10.0,
-                Some(format!("Found {} documents", documents_count)),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Loading, 0.1)
+                .with_message(format!("Found {} documents", documents_count)),
+        );
 
         // Index the documents
         eprintln!("⚙️ Indexing documents...");
 
         // Report progress: Starting indexing
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Indexing documents".to_string(),
-                15.0,
-                Some(format!("Processing {} documents", documents_count)),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Indexing, 0.15)
+                .with_message(format!("Processing {} documents", documents_count)),
+        );
 
-        let nodes = indexing_pipeline
-            .index_documents(documents)
-            .await
-            .map_err(RagError::Core)?;
+        let plugins = crate::indexing::plugins::registry();
+        let indexing = plugins.index_documents(documents.clone(), |documents| {
+            indexing_pipeline.index_documents(documents)
+        });
+        let mut nodes = tokio::select! {
+            result = indexing => result.map_err(RagError::Core)?,
+            _ = cancel.cancelled() => return Err(RagError::Cancelled),
+        };
+        crate::annotate_line_ranges(&documents, &mut nodes);
 
         eprintln!("📚 Indexed documents into {} nodes", nodes.len());
 
         // Report progress: Indexing complete
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Document indexing complete".to_string(),
-                20.0,
-                Some(format!("Created {} nodes", nodes.len())),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Indexing, 0.2)
+                .with_message(format!("Created {} nodes", nodes.len())),
+        );
 
         // Note: Embedding generation progress will be reported by the embedding generator itself
 
@@ -371,23 +400,23 @@ impl RagPipeline {
         embedding_generator.initialize().await?;
 
         let embedded_chunks = embedding_generator
-            .generate_embeddings_with_progress(nodes, progress_callback.as_ref())
+            .generate_embeddings_cancellable(nodes, progress, cancel)
             .await?;
 
         let embedded_chunks_count = embedded_chunks.len();
+        let embedding_tokens = embedded_chunks
+            .iter()
+            .map(|chunk| self.estimate_tokens(&chunk.content))
+            .sum();
         info!("🔢 Generated {} embeddings", embedded_chunks_count);
 
         // Report progress: Storing vectors
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Storing vectors".to_string(),
-                96.0,
-                Some(format!(
-                    "Adding {} chunks to vector store",
-                    embedded_chunks.len()
-                )),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Storing, 0.96).with_message(format!(
+                "Adding {} chunks to vector store",
+                embedded_chunks.len()
+            )),
+        );
 
         // Step 3: Add to vector store
         if let Some(vector_store) = &mut self.vector_store {
@@ -396,13 +425,10 @@ impl RagPipeline {
         }
 
         // Report progress: Finalizing
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Finalizing".to_string(),
-                95.0,
-                Some("Initializing retriever".to_string()),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Finalizing, 0.95)
+                .with_message("Initializing retriever"),
+        );
 
         // Step 4: Initialize retriever
         let vector_store = self.vector_store.take().unwrap();
@@ -414,7 +440,7 @@ impl RagPipeline {
         retriever.initialize().await?;
 
         // Store the vector store back (retriever owns it now)
-        self.vector_store = Some(VectorStore::new(self.config.embeddings.dimension));
+        self.vector_store = Some(self.new_vector_store());
         self.retriever = Some(retriever);
 
         let indexing_time = start_time.elapsed();
@@ -423,19 +449,16 @@ impl RagPipeline {
             total_nodes: embedded_chunks_count,
             total_chunks: self.retriever.as_ref().unwrap().vector_store().len(),
             indexing_time_ms: indexing_time.as_millis() as u64,
+            embedding_tokens,
         };
 
         // Report progress: Complete
-        if let Some(ref callback) = progress_callback {
-            callback(
-                "Complete".to_string(),
-                100.0,
-                Some(format!(
-                    "Indexed {} documents into {} chunks",
-                    stats.total_documents, stats.total_chunks
-                )),
-            );
-        }
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Completed, 1.0).with_message(format!(
+                "Indexed {} documents into {} chunks",
+                stats.total_documents, stats.total_chunks
+            )),
+        );
 
         log_operation_success!(
             "rag_index_repository",
@@ -448,8 +471,86 @@ impl RagPipeline {
         Ok(stats)
     }
 
-    /// Ask a question and get a RAG response
-    pub async fn ask(&self, query: RagQuery) -> RagResult<RagResponse> {
+    /// Update the index for files of a local repository that changed
+    ///
+    /// Chunks of the changed files are dropped; files that still exist are
+    /// chunked and embedded again. Only the given files are touched, so this
+    /// is much cheaper than indexing the whole repository again.
+    pub async fn update_files(
+        &mut self,
+        repo_root: &Path,
+        changed_files: &[std::path::PathBuf],
+    ) -> RagResult<IndexUpdate> {
+        let start_time = Instant::now();
+        let Some(retriever) = self.retriever.as_mut() else {
+            return Err(RagError::Config(
+                "Repository must be indexed before files can be updated".to_string(),
+            ));
+        };
+
+        let mut update = IndexUpdate::default();
+        for file in changed_files {
+            let relative = file
+                .strip_prefix(repo_root)
+                .unwrap_or(file)
+                .to_string_lossy()
+                .to_string();
+            let removed = retriever.vector_store_mut().remove_where(|chunk| {
+                chunk
+                    .metadata
+                    .get("file_path")
+                    .and_then(|value| value.as_str())
+                    .is_some_and(|path| is_same_file(path, repo_root, &relative))
+            })?;
+            update.chunks_removed += removed;
+            if !file.exists() && removed > 0 {
+                update.removed_files.push(relative);
+            }
+        }
+
+        let documents = self
+            .document_processor(repo_root)
+            .process_files(changed_files)
+            .await
+            .map_err(RagError::Core)?;
+        update.updated_files = documents
+            .iter()
+            .filter_map(|document| document.metadata.get("file_path")?.as_str())
+            .map(str::to_string)
+            .collect();
+
+        if !documents.is_empty() {
+            let indexer = crate::create_deepwiki_compatible_indexer()
+                .map_err(RagError::Core)?
+                .with_max_concurrency(self.indexing_workers());
+            let mut nodes = crate::indexing::plugins::registry()
+                .index_documents(documents.clone(), |documents| {
+                    indexer.index_documents(documents)
+                })
+                .await
+                .map_err(RagError::Core)?;
+            crate::annotate_line_ranges(&documents, &mut nodes);
+            let mut embedding_generator = EmbeddingGenerator::new(self.config.embeddings.clone());
+            embedding_generator.initialize().await?;
+            let embedded_chunks = embedding_generator.generate_embeddings(nodes).await?;
+
+            update.chunks_added = embedded_chunks.len();
+            update.embedding_tokens = embedded_chunks
+                .iter()
+                .map(|chunk| self.estimate_tokens(&chunk.content))
+                .sum();
+            if let Some(retriever) = self.retriever.as_mut() {
+                retriever.vector_store_mut().add_chunks(embedded_chunks)?;
+            }
+        }
+
+        update.update_time_ms = start_time.elapsed().as_millis() as u64;
+        info!("🔄 Index updated: {}", update.summary());
+        Ok(update)
+    }
+
+    /// Find the chunks most relevant to a query without generating an answer
+    pub async fn search(&self, query: &RagQuery) -> RagResult<Vec<SearchResult>> {
         if !self.is_initialized {
             return Err(RagError::Config("Pipeline not initialized".to_string()));
         }
@@ -459,6 +560,23 @@ impl RagPipeline {
             .as_ref()
             .ok_or_else(|| RagError::Config("No documents indexed yet".to_string()))?;
 
+        let config = query
+            .retrieval_config
+            .as_ref()
+            .unwrap_or_else(|| retriever.config());
+        let text = query.retrieval_text();
+        match &query.filters {
+            Some(filters) => retriever.retrieve_matching(&text, config, filters).await,
+            None => retriever.retrieve_with_config(&text, config).await,
+        }
+    }
+
+    /// Ask a question and get a RAG response
+    pub async fn ask(&self, query: RagQuery) -> RagResult<RagResponse> {
+        if !self.is_initialized {
+            return Err(RagError::Config("Pipeline not initialized".to_string()));
+        }
+
         let llm_client = self
             .llm_client
             .as_ref()
@@ -469,9 +587,9 @@ impl RagPipeline {
 
         debug!("Processing query: {}", query.question);
 
-        // Step 1: Retrieve relevant documents
+        // Step 1: Retrieve relevant documents, honouring per-query overrides
         let retrieval_start = Instant::now();
-        let search_results = retriever.retrieve(&query.question).await?;
+        let search_results = self.search(&query).await?;
         let retrieval_time = retrieval_start.elapsed();
 
         info!(
@@ -489,6 +607,8 @@ impl RagPipeline {
                     chunks_retrieved: 0,
                     context_tokens: 0,
                     generation_tokens: 0,
+                    query_tokens: self.estimate_tokens(&query.question),
+                    prompt_tokens: 0,
                     retrieval_time_ms: retrieval_time.as_millis() as u64,
                     generation_time_ms: 0,
                     model_used: llm_client.model_info().summary(),
@@ -504,13 +624,20 @@ impl RagPipeline {
 
         // Step 3: Generate response using LLM
         let generation_start = Instant::now();
-        let prompt = self.build_prompt(&query.question, &context, query.context.as_deref());
+        let prompt = self.build_prompt(
+            &query.question,
+            &context,
+            query.context.as_deref(),
+            query.attachment.as_deref(),
+        );
         let answer = llm_client
             .generate_with_system(&self.config.generation.system_prompt, &prompt)
             .await?;
         let generation_time = generation_start.elapsed();
 
         let generation_tokens = self.estimate_tokens(&answer);
+        let prompt_tokens = self.estimate_tokens(&self.config.generation.system_prompt)
+            + self.estimate_tokens(&prompt);
 
         info!(
             "💬 Generated response in {:?} (~{} tokens)",
@@ -526,6 +653,8 @@ impl RagPipeline {
                 chunks_retrieved,
                 context_tokens,
                 generation_tokens,
+                query_tokens: self.estimate_tokens(&query.question),
+                prompt_tokens,
                 retrieval_time_ms: retrieval_time.as_millis() as u64,
                 generation_time_ms: generation_time.as_millis() as u64,
                 model_used: llm_client.model_info().summary(),
@@ -577,6 +706,7 @@ impl RagPipeline {
         question: &str,
         context: &str,
         conversation_context: Option<&str>,
+        attachment: Option<&str>,
     ) -> String {
         let mut prompt = self
             .config
@@ -585,6 +715,13 @@ impl RagPipeline {
             .replace("{context}", context)
             .replace("{question}", question);
 
+        if let Some(attachment) = attachment.map(str::trim).filter(|a| !a.is_empty()) {
+            prompt = format!(
+                "Provided by the user with the question:\n```\n{}\n```\n\n{}",
+                attachment, prompt
+            );
+        }
+
         if let Some(conv_context) = conversation_context {
             prompt = format!("Previous conversation:\n{}\n\n{}", conv_context, prompt);
         }
@@ -629,61 +766,44 @@ impl RagPipeline {
         &self,
         repo_path: P,
     ) -> RagResult<Vec<cheungfun_core::Document>> {
-        use crate::DirectoryLoader;
-        use cheungfun_core::traits::Loader;
-
-        let mut documents = Vec::new();
-
         eprintln!("📂 Loading repository from path: {:?}", repo_path.as_ref());
 
-        // Use cheungfun's DirectoryLoader to load documents
-        let loader = DirectoryLoader::new(repo_path.as_ref().to_path_buf()).map_err(|e| {
-            eprintln!("❌ Failed to create DirectoryLoader: {}", e);
-            RagError::Core(Box::new(wikify_core::WikifyError::Indexing {
-                message: format!("Failed to create directory loader: {}", e),
-                source: None,
-                context: wikify_core::ErrorContext::new("rag_pipeline")
-                    .with_operation("create_loader"),
-            }))
-        })?;
-
-        eprintln!("🔍 DirectoryLoader created, starting document loading...");
-
-        let loaded_docs = loader.load().await.map_err(|e| {
-            RagError::Core(Box::new(wikify_core::WikifyError::Indexing {
-                message: format!("Failed to load documents: {}", e),
-                source: None,
-                context: wikify_core::ErrorContext::new("rag_pipeline")
-                    .with_operation("load_documents"),
-            }))
-        })?;
-        documents.extend(loaded_docs);
+        // The processor reads files in parallel and hands formats handled by
+        // plugins to their loaders
+        let documents = self
+            .document_processor(repo_path.as_ref())
+            .process_repository()
+            .await
+            .map_err(RagError::Core)?;
 
         info!("Loaded {} documents from repository", documents.len());
         Ok(documents)
     }
 
+    /// Base path the pipeline clones remote repositories under
+    pub fn clone_base_path() -> std::path::PathBuf {
+        dirs::data_local_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("wikify")
+            .join("repos")
+    }
+
     /// Clone a remote repository to local storage
     async fn clone_repository(&self, repo_url: &str) -> RagResult<String> {
         use wikify_core::RepositoryAccessConfig;
         use wikify_repo::RepositoryProcessor;
 
-        // Use default base path for cloned repositories
-        let base_path = dirs::data_local_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("wikify")
-            .join("repos");
-
         // Create processor
-        let processor = RepositoryProcessor::new(&base_path);
+        let processor = RepositoryProcessor::new(Self::clone_base_path());
 
         // Configure for Git clone mode with shallow clone
         let config = RepositoryAccessConfig {
             preferred_mode: Some(wikify_core::RepoAccessMode::GitClone),
-            api_token: None,      // Force Git clone, don't use API
-            force_mode: true,     // Force the preferred mode
-            clone_depth: Some(1), // Shallow clone for efficiency
+            api_token: self.access_token.clone(), // Authenticates the clone
+            force_mode: true,                     // Force the preferred mode
+            clone_depth: Some(1),                 // Shallow clone for efficiency
             custom_local_path: None,
+            mirror_urls: Vec::new(),
         };
 
         // Access repository using processor
@@ -714,11 +834,31 @@ impl RagPipeline {
         &mut self,
         query: &str,
         config: Option<DeepResearchConfig>,
+    ) -> RagResult<DeepResearchResult> {
+        self.deep_research_cancellable(query, config, &CancellationToken::new())
+            .await
+    }
+
+    /// Execute deep research until `cancel` is triggered
+    ///
+    /// Returns [`RagError::Cancelled`] as soon as the token is triggered,
+    /// dropping the LLM call in flight.
+    pub async fn deep_research_cancellable(
+        &mut self,
+        query: &str,
+        config: Option<DeepResearchConfig>,
+        cancel: &CancellationToken,
     ) -> RagResult<DeepResearchResult> {
         let research_config = config.unwrap_or_default();
 
         // Execute research directly in this pipeline instead of using a separate engine
-        self.execute_research_internal(query, research_config).await
+        tokio::select! {
+            result = self.execute_research_internal(query, research_config) => result,
+            _ = cancel.cancelled() => {
+                info!("Deep research cancelled: {}", query);
+                Err(RagError::Cancelled)
+            }
+        }
     }
 
     /// Internal implementation of deep research
@@ -761,6 +901,7 @@ impl RagPipeline {
                 context: None,
                 filters: None,
                 retrieval_config: None,
+                attachment: None,
             };
 
             let response = self.ask(rag_query).await?;
@@ -875,6 +1016,7 @@ impl RagPipeline {
             context: None,
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         let response = self.ask(query).await?;
@@ -922,6 +1064,7 @@ impl RagPipeline {
             context: None,
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         let response = self.ask(query).await?;
@@ -992,6 +1135,7 @@ impl RagPipeline {
             context: None,
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         let response = self.ask(rag_query).await?;
@@ -1046,6 +1190,7 @@ impl RagPipeline {
             context: None,
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         let response = self.ask(rag_query).await?;
@@ -1111,6 +1256,8 @@ pub struct IndexingStats {
     pub total_nodes: usize,
     pub total_chunks: usize,
     pub indexing_time_ms: u64,
+    /// Estimated number of tokens sent to the embedding model
+    pub embedding_tokens: usize,
 }
 
 impl IndexingStats {
@@ -1122,6 +1269,45 @@ impl IndexingStats {
     }
 }
 
+/// Changes an incremental index update made
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct IndexUpdate {
+    /// Files that were chunked and embedded again, relative to the repository
+    pub updated_files: Vec<String>,
+    /// Deleted files whose chunks were dropped
+    pub removed_files: Vec<String>,
+    pub chunks_added: usize,
+    pub chunks_removed: usize,
+    /// Estimated number of tokens sent to the embedding model
+    pub embedding_tokens: usize,
+    pub update_time_ms: u64,
+}
+
+impl IndexUpdate {
+    /// Whether the index is unchanged
+    pub fn is_empty(&self) -> bool {
+        self.chunks_added == 0 && self.chunks_removed == 0
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} files updated, {} removed → +{} / -{} chunks ({}ms)",
+            self.updated_files.len(),
+            self.removed_files.len(),
+            self.chunks_added,
+            self.chunks_removed,
+            self.update_time_ms
+        )
+    }
+}
+
+/// Whether a chunk's `file_path` refers to a file of the repository
+///
+/// Loaders record either the path relative to the repository or the full path.
+fn is_same_file(chunk_path: &str, repo_root: &Path, relative: &str) -> bool {
+    chunk_path == relative || Path::new(chunk_path) == repo_root.join(relative)
+}
+
 /// Statistics about the pipeline
 #[derive(Debug, Clone)]
 pub struct PipelineStats {
@@ -1146,15 +1332,15 @@ pub async fn create_auto_rag_pipeline() -> RagResult<RagPipeline> {
     let mut config = RagConfig::default();
 
     // Try to auto-detect available LLM provider
-    if std::env::var("OPENAI_API_KEY").is_ok() {
+    if wikify_core::secrets::secret("OPENAI_API_KEY").is_some() {
         config.llm = crate::llm_client::configs::openai_gpt4o_mini();
         config.embeddings.provider = "openai".to_string();
         config.embeddings.model = "text-embedding-3-small".to_string();
-    } else if std::env::var("ANTHROPIC_API_KEY").is_ok() {
+    } else if wikify_core::secrets::secret("ANTHROPIC_API_KEY").is_some() {
         config.llm = crate::llm_client::configs::anthropic_claude_haiku();
         // Note: Anthropic doesn't provide embeddings, so keep OpenAI for embeddings
         // or use a local embedding model
-    } else if std::env::var("GROQ_API_KEY").is_ok() {
+    } else if wikify_core::secrets::secret("GROQ_API_KEY").is_some() {
         config.llm = crate::llm_client::configs::groq_llama3();
     } else {
         // Fallback to Ollama
@@ -1174,5 +1360,6 @@ pub fn create_simple_query(question: &str) -> RagQuery {
         context: None,
         filters: None,
         retrieval_config: None,
+        attachment: None,
     }
 }
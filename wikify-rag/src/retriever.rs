@@ -5,6 +5,8 @@
 
 use crate::embeddings::{EmbeddingGenerator, VectorStore};
 use crate::types::{EmbeddingConfig, RagError, RagResult, RetrievalConfig, SearchResult};
+use std::collections::HashMap;
+use std::path::Path;
 use std::time::Instant;
 use tracing::{debug, info};
 
@@ -40,6 +42,27 @@ impl DocumentRetriever {
 
     /// Retrieve relevant documents for a query
     pub async fn retrieve(&self, query: &str) -> RagResult<Vec<SearchResult>> {
+        self.retrieve_with_config(query, &self.config).await
+    }
+
+    /// Retrieve relevant documents for a query using the given settings
+    /// instead of the retriever's own configuration
+    pub async fn retrieve_with_config(
+        &self,
+        query: &str,
+        config: &RetrievalConfig,
+    ) -> RagResult<Vec<SearchResult>> {
+        self.retrieve_matching(query, config, &HashMap::new()).await
+    }
+
+    /// Retrieve relevant documents among the chunks whose metadata matches
+    /// all the filters, see [`matches_filters`]
+    pub async fn retrieve_matching(
+        &self,
+        query: &str,
+        config: &RetrievalConfig,
+        filters: &HashMap<String, serde_json::Value>,
+    ) -> RagResult<Vec<SearchResult>> {
         let start_time = Instant::now();
 
         debug!("Retrieving documents for query: {}", query);
@@ -48,10 +71,11 @@ impl DocumentRetriever {
         let query_embedding = self.generate_query_embedding(query).await?;
 
         // Search for similar chunks
-        let similar_chunks = self.vector_store.search(
+        let similar_chunks = self.vector_store.search_where(
             &query_embedding,
-            self.config.top_k,
-            self.config.similarity_threshold,
+            config.top_k,
+            config.similarity_threshold,
+            |chunk| matches_filters(&chunk.metadata, filters),
         );
 
         // Convert to SearchResult objects
@@ -66,19 +90,19 @@ impl DocumentRetriever {
         }
 
         // Apply reranking if enabled
-        if self.config.enable_reranking {
+        if config.enable_reranking {
             results = self.rerank_results(query, results).await?;
         }
 
         // Filter by context length limit
-        results = self.filter_by_context_length(results);
+        results = Self::filter_by_context_length(results, config.max_context_length);
 
         let retrieval_time = start_time.elapsed();
         info!(
             "Retrieved {} documents in {:?} (similarity threshold: {})",
             results.len(),
             retrieval_time,
-            self.config.similarity_threshold
+            config.similarity_threshold
         );
 
         Ok(results)
@@ -150,7 +174,10 @@ impl DocumentRetriever {
     }
 
     /// Filter results by total context length
-    fn filter_by_context_length(&self, results: Vec<SearchResult>) -> Vec<SearchResult> {
+    fn filter_by_context_length(
+        results: Vec<SearchResult>,
+        max_context_length: usize,
+    ) -> Vec<SearchResult> {
         let mut filtered_results = Vec::new();
         let mut total_length = 0;
 
@@ -158,13 +185,13 @@ impl DocumentRetriever {
         for result in results {
             let content_length = result.chunk.content.len();
 
-            if total_length + content_length <= self.config.max_context_length {
+            if total_length + content_length <= max_context_length {
                 total_length += content_length;
                 filtered_results.push(result);
             } else {
                 debug!(
                     "Stopping retrieval due to context length limit ({} chars)",
-                    self.config.max_context_length
+                    max_context_length
                 );
                 break;
             }
@@ -192,6 +219,11 @@ impl DocumentRetriever {
         }
     }
 
+    /// Get the retrieval configuration
+    pub fn config(&self) -> &RetrievalConfig {
+        &self.config
+    }
+
     /// Update retrieval configuration
     pub fn update_config(&mut self, config: RetrievalConfig) {
         self.config = config;
@@ -202,6 +234,40 @@ impl DocumentRetriever {
     pub fn vector_store(&self) -> &VectorStore {
         &self.vector_store
     }
+
+    /// Get the vector store for updating the indexed chunks
+    pub fn vector_store_mut(&mut self) -> &mut VectorStore {
+        &mut self.vector_store
+    }
+}
+
+/// Whether chunk metadata matches every filter
+///
+/// A filter maps a metadata key to the accepted value, or to an array of
+/// accepted values. Loaders record `file_path` either relative to the
+/// repository or in full, so a relative `file_path` also accepts full paths
+/// ending with it.
+pub fn matches_filters(
+    metadata: &HashMap<String, serde_json::Value>,
+    filters: &HashMap<String, serde_json::Value>,
+) -> bool {
+    filters.iter().all(|(key, accepted)| {
+        let Some(value) = metadata.get(key) else {
+            return false;
+        };
+        let matches = |accepted: &serde_json::Value| {
+            value == accepted
+                || (key == "file_path"
+                    && matches!(
+                        (value.as_str(), accepted.as_str()),
+                        (Some(path), Some(relative)) if Path::new(path).ends_with(relative)
+                    ))
+        };
+        match accepted {
+            serde_json::Value::Array(values) => values.iter().any(matches),
+            accepted => matches(accepted),
+        }
+    })
 }
 
 /// Statistics about the retrieval system
@@ -273,6 +339,7 @@ mod tests {
             api_key: None,
             dimension: 3,
             batch_size: 10,
+            max_concurrent_requests: 1,
         };
 
         let retrieval_config = RetrievalConfig {
@@ -299,7 +366,46 @@ mod tests {
             },
         ];
 
-        let filtered = retriever.filter_by_context_length(results);
+        let filtered = DocumentRetriever::filter_by_context_length(
+            results,
+            retriever.config.max_context_length,
+        );
         assert_eq!(filtered.len(), 1); // Only the first short text should remain
     }
+
+    #[test]
+    fn test_matches_filters() {
+        let metadata = HashMap::from([
+            (
+                "file_path".to_string(),
+                serde_json::json!("/tmp/repo/src/lib.rs"),
+            ),
+            ("language".to_string(), serde_json::json!("rust")),
+        ]);
+        let filters = |filters: serde_json::Value| {
+            serde_json::from_value::<HashMap<String, serde_json::Value>>(filters).unwrap()
+        };
+
+        assert!(matches_filters(&metadata, &HashMap::new()));
+        assert!(matches_filters(
+            &metadata,
+            &filters(serde_json::json!({"file_path": ["src/main.rs", "src/lib.rs"]}))
+        ));
+        assert!(matches_filters(
+            &metadata,
+            &filters(serde_json::json!({"file_path": "src/lib.rs", "language": "rust"}))
+        ));
+        assert!(!matches_filters(
+            &metadata,
+            &filters(serde_json::json!({"file_path": "b/lib.rs"}))
+        ));
+        assert!(!matches_filters(
+            &metadata,
+            &filters(serde_json::json!({"language": "python"}))
+        ));
+        assert!(!matches_filters(
+            &metadata,
+            &filters(serde_json::json!({"author": "alice"}))
+        ));
+    }
 }
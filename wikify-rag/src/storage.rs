@@ -32,7 +32,7 @@ impl PersistentVectorStore {
         let repo_id = Self::generate_repo_id(repo_path);
 
         // Create storage directory
-        let storage_dir = config.base_dir.join(&repo_id);
+        let storage_dir = Self::storage_dir(&config, repo_path);
         std::fs::create_dir_all(&storage_dir).map_err(RagError::Io)?;
 
         let storage_path = storage_dir.join("vectors.json");
@@ -55,6 +55,33 @@ impl PersistentVectorStore {
         })
     }
 
+    /// Remove the stored vector database for a repository
+    ///
+    /// Returns the number of bytes freed (0 if nothing was stored).
+    pub fn remove_storage(config: &StorageConfig, repo_path: &str) -> RagResult<u64> {
+        let storage_dir = Self::storage_dir(config, repo_path);
+        if !storage_dir.exists() {
+            return Ok(0);
+        }
+
+        let size = walkdir::WalkDir::new(&storage_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.metadata().ok())
+            .filter(|m| m.is_file())
+            .map(|m| m.len())
+            .sum();
+
+        std::fs::remove_dir_all(&storage_dir).map_err(RagError::Io)?;
+        info!("Removed vector store at {:?}", storage_dir);
+        Ok(size)
+    }
+
+    /// Directory holding the vector database of a repository
+    pub fn storage_dir(config: &StorageConfig, repo_path: &str) -> PathBuf {
+        config.base_dir.join(Self::generate_repo_id(repo_path))
+    }
+
     /// Generate a unique repository ID from path/URL
     fn generate_repo_id(repo_path: &str) -> String {
         use std::collections::hash_map::DefaultHasher;
@@ -87,8 +114,8 @@ impl PersistentVectorStore {
 
         debug!("Saving vector store to {:?}", self.storage_path);
 
-        let chunks = self.vector_store.chunks();
-        let data = serde_json::to_string_pretty(chunks).map_err(RagError::Serialization)?;
+        let chunks = self.vector_store.chunks_with_embeddings();
+        let data = serde_json::to_string_pretty(&chunks).map_err(RagError::Serialization)?;
 
         std::fs::write(&self.storage_path, data).map_err(RagError::Io)?;
 
@@ -5,6 +5,44 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
+use wikify_core::ConfigIssue;
+
+/// LLM providers the pipeline can build clients for, with the environment
+/// variable their API key is read from
+///
+/// Only providers whose cargo feature is enabled are listed.
+pub const LLM_PROVIDERS: &[(&str, Option<&str>)] = &[
+    #[cfg(feature = "openai")]
+    ("openai", Some("OPENAI_API_KEY")),
+    #[cfg(feature = "anthropic")]
+    ("anthropic", Some("ANTHROPIC_API_KEY")),
+    #[cfg(feature = "ollama")]
+    ("ollama", None),
+    #[cfg(feature = "groq")]
+    ("groq", Some("GROQ_API_KEY")),
+];
+
+/// Embedding providers the pipeline can build clients for, likewise
+pub const EMBEDDING_PROVIDERS: &[(&str, Option<&str>)] = &[
+    #[cfg(feature = "openai")]
+    ("openai", Some("OPENAI_API_KEY")),
+];
+
+/// Providers that each have a cargo feature of the same name
+const OPTIONAL_PROVIDERS: &[&str] = &["openai", "anthropic", "ollama", "groq"];
+
+/// Why a provider cannot be used: either it is unknown, or this build left
+/// out its cargo feature
+pub fn unsupported_provider(kind: &str, provider: &str) -> String {
+    if OPTIONAL_PROVIDERS.contains(&provider) {
+        format!(
+            "The {} provider '{}' is not included in this build; rebuild with the `{}` feature",
+            kind, provider, provider
+        )
+    } else {
+        format!("Unknown {} provider '{}'", kind, provider)
+    }
+}
 
 /// Configuration for the RAG system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +55,9 @@ pub struct RagConfig {
     pub retrieval: RetrievalConfig,
     /// Generation configuration
     pub generation: GenerationConfig,
+    /// Vector store configuration
+    #[serde(default)]
+    pub vector_store: VectorStoreConfig,
 }
 
 /// LLM provider configuration
@@ -49,6 +90,20 @@ pub struct EmbeddingConfig {
     pub dimension: usize,
     /// Batch size for embedding generation
     pub batch_size: usize,
+    /// Most embedding requests in flight at the same time
+    ///
+    /// Lowered automatically while the provider rate limits or fails
+    /// requests, and raised back once it accepts them again. Set it to 1 to
+    /// send requests one at a time.
+    #[serde(default = "default_embedding_concurrency")]
+    pub max_concurrent_requests: usize,
+}
+
+/// Embedding requests in flight at the same time unless configured otherwise
+pub const DEFAULT_EMBEDDING_CONCURRENCY: usize = 4;
+
+fn default_embedding_concurrency() -> usize {
+    DEFAULT_EMBEDDING_CONCURRENCY
 }
 
 /// Retrieval configuration
@@ -64,6 +119,49 @@ pub struct RetrievalConfig {
     pub enable_reranking: bool,
 }
 
+/// Vector store configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VectorStoreConfig {
+    /// Megabytes of embeddings above which a store moves them out of RAM
+    /// into a memory-mapped file (None = always keep them in RAM)
+    pub mmap_threshold_mb: Option<u64>,
+    /// Directory for memory-mapped embeddings (None = the system temporary
+    /// directory)
+    pub mmap_dir: Option<std::path::PathBuf>,
+    /// How embeddings are kept in RAM
+    ///
+    /// Quantized stores keep no full precision embeddings in RAM, whatever
+    /// the mmap threshold.
+    pub quantization: Quantization,
+    /// With quantization, rescore this many of the best matches of each
+    /// search on full precision embeddings kept in a memory-mapped file
+    /// (0 = use the quantized scores and drop full precision embeddings)
+    pub rerank_candidates: usize,
+}
+
+impl Default for VectorStoreConfig {
+    fn default() -> Self {
+        Self {
+            mmap_threshold_mb: Some(1024),
+            mmap_dir: None,
+            quantization: Quantization::None,
+            rerank_candidates: 50,
+        }
+    }
+}
+
+/// How a vector store keeps embeddings in RAM
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Quantization {
+    /// Full precision, four bytes per dimension
+    #[default]
+    None,
+    /// One signed byte per dimension, scaled per embedding
+    Int8,
+}
+
 /// Generation configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerationConfig {
@@ -94,6 +192,7 @@ impl Default for RagConfig {
                 api_key: None,
                 dimension: 1536,
                 batch_size: 100,
+                max_concurrent_requests: DEFAULT_EMBEDDING_CONCURRENCY,
             },
             retrieval: RetrievalConfig {
                 top_k: 8,
@@ -107,10 +206,59 @@ impl Default for RagConfig {
                 include_citations: true,
                 max_response_length: None,
             },
+            vector_store: VectorStoreConfig::default(),
         }
     }
 }
 
+impl RagConfig {
+    /// Find the provider settings that would fail when the pipeline starts:
+    /// unknown providers and missing API keys
+    pub fn diagnose(&self) -> Vec<ConfigIssue> {
+        self.diagnose_with(wikify_core::secrets::secret)
+    }
+
+    fn diagnose_with(&self, env: impl Fn(&str) -> Option<String>) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        for (key, provider, api_key, providers) in [
+            ("llm", &self.llm.provider, &self.llm.api_key, LLM_PROVIDERS),
+            (
+                "embedding",
+                &self.embeddings.provider,
+                &self.embeddings.api_key,
+                EMBEDDING_PROVIDERS,
+            ),
+        ] {
+            let Some((_, key_var)) = providers
+                .iter()
+                .find(|(name, _)| *name == provider.as_str())
+            else {
+                let known: Vec<&str> = providers.iter().map(|(name, _)| *name).collect();
+                issues.push(ConfigIssue::new(
+                    &format!("{}.provider", key),
+                    &unsupported_provider(key, provider),
+                    &format!("Use one of: {}", known.join(", ")),
+                ));
+                continue;
+            };
+            if let Some(key_var) = key_var {
+                let has_key = api_key.is_some() || env(key_var).is_some_and(|v| !v.is_empty());
+                if !has_key {
+                    issues.push(ConfigIssue::new(
+                        &format!("{}.provider", key),
+                        &format!("No API key for the {} provider '{}'", key, provider),
+                        &format!(
+                            "Run `wikify auth set-key {}` or set the {} environment variable",
+                            provider, key_var
+                        ),
+                    ));
+                }
+            }
+        }
+        issues
+    }
+}
+
 /// A document chunk with embedding
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddedChunk {
@@ -148,6 +296,31 @@ pub struct RagQuery {
     pub filters: Option<HashMap<String, serde_json::Value>>,
     /// Override retrieval parameters
     pub retrieval_config: Option<RetrievalConfig>,
+    /// Text the user supplied with the question, such as an error log or a
+    /// stack trace, shown to the LLM next to the retrieved chunks
+    #[serde(default)]
+    pub attachment: Option<String>,
+}
+
+impl RagQuery {
+    /// Characters of the attachment that take part in retrieval
+    const ATTACHMENT_RETRIEVAL_CHARS: usize = 2000;
+
+    /// Text to find relevant chunks for: the question, followed by the start
+    /// of the attachment, whose file and function names point to the code
+    /// it is about
+    pub fn retrieval_text(&self) -> std::borrow::Cow<'_, str> {
+        match self.attachment.as_deref().map(str::trim) {
+            Some(attachment) if !attachment.is_empty() => {
+                let excerpt: String = attachment
+                    .chars()
+                    .take(Self::ATTACHMENT_RETRIEVAL_CHARS)
+                    .collect();
+                format!("{}\n\n{}", self.question, excerpt).into()
+            }
+            _ => self.question.as_str().into(),
+        }
+    }
 }
 
 /// RAG response
@@ -170,6 +343,12 @@ pub struct RagResponseMetadata {
     pub context_tokens: usize,
     /// Tokens used in generation
     pub generation_tokens: usize,
+    /// Tokens of the question sent to the embedding model
+    #[serde(default)]
+    pub query_tokens: usize,
+    /// Tokens of the full prompt sent to the LLM, including the system prompt
+    #[serde(default)]
+    pub prompt_tokens: usize,
     /// Time taken for retrieval (ms)
     pub retrieval_time_ms: u64,
     /// Time taken for generation (ms)
@@ -201,6 +380,9 @@ pub enum RagError {
 
     #[error("Core error: {0}")]
     Core(Box<wikify_core::WikifyError>),
+
+    #[error("Operation cancelled")]
+    Cancelled,
 }
 
 impl From<wikify_core::WikifyError> for RagError {
@@ -502,3 +684,32 @@ pub struct ResearchProgress {
     /// Last update timestamp
     pub last_updated: chrono::DateTime<chrono::Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(feature = "openai", feature = "anthropic", feature = "ollama"))]
+    fn test_providers_are_checked_before_starting() {
+        let mut config = RagConfig::default();
+        config.llm.provider = "anthropic".to_string();
+        config.embeddings.provider = "local".to_string();
+
+        let issues =
+            config.diagnose_with(|name| (name == "OPENAI_API_KEY").then(|| "sk-test".to_string()));
+        assert_eq!(issues.len(), 2);
+        assert_eq!(
+            issues[0].suggestion,
+            "Run `wikify auth set-key anthropic` or set the ANTHROPIC_API_KEY environment variable"
+        );
+        assert_eq!(issues[1].key, "embedding.provider");
+        assert_eq!(issues[1].suggestion, "Use one of: openai");
+
+        config.llm.provider = "ollama".to_string();
+        config.embeddings.provider = "openai".to_string();
+        assert!(config
+            .diagnose_with(|name| (name == "OPENAI_API_KEY").then(|| "sk-test".to_string()))
+            .is_empty());
+    }
+}
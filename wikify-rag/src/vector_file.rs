@@ -0,0 +1,148 @@
+//! Memory-mapped storage for embeddings
+//!
+//! Large vector stores keep their embeddings in a file mapped into memory
+//! instead of on the heap. The OS loads vectors when a search first reads
+//! them and keeps the pages searches touch resident, evicting cold ones
+//! under memory pressure, so the size of an index is not bounded by RAM.
+
+use crate::types::{RagError, RagResult};
+use memmap2::MmapMut;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Vectors of one dimension stored back to back in an unlinked temporary file
+pub(crate) struct MappedVectors {
+    file: File,
+    /// None while the file is empty, as empty files cannot be mapped
+    map: Option<MmapMut>,
+    dimension: usize,
+    len: usize,
+}
+
+impl MappedVectors {
+    /// Create an empty file in `dir`, or the system temporary directory
+    ///
+    /// The file is removed as soon as it is created, so it disappears with
+    /// the store even if the process is killed.
+    pub(crate) fn create(dimension: usize, dir: Option<&Path>) -> RagResult<Self> {
+        let file = match dir {
+            Some(dir) => tempfile::tempfile_in(dir),
+            None => tempfile::tempfile(),
+        }
+        .map_err(RagError::Io)?;
+
+        Ok(Self {
+            file,
+            map: None,
+            dimension,
+            len: 0,
+        })
+    }
+
+    /// Number of stored vectors
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Vector at an index
+    pub(crate) fn get(&self, index: usize) -> Option<&[f32]> {
+        if index >= self.len {
+            return None;
+        }
+        let row = self.row_bytes();
+        let map = self.map.as_ref()?;
+        Some(bytemuck::cast_slice(&map[index * row..(index + 1) * row]))
+    }
+
+    /// Append vectors, which must have the dimension of the file
+    pub(crate) fn extend<'a>(
+        &mut self,
+        vectors: impl IntoIterator<Item = &'a [f32]>,
+    ) -> RagResult<()> {
+        // Unmap before the file grows
+        self.map = None;
+
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start((self.len * self.row_bytes()) as u64))
+            .map_err(RagError::Io)?;
+        let mut writer = BufWriter::new(file);
+        for vector in vectors {
+            debug_assert_eq!(vector.len(), self.dimension);
+            writer
+                .write_all(bytemuck::cast_slice(vector))
+                .map_err(RagError::Io)?;
+            self.len += 1;
+        }
+        writer.flush().map_err(RagError::Io)?;
+        drop(writer);
+
+        self.remap()
+    }
+
+    /// Keep only the vectors whose index `keep` accepts, preserving order
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(usize) -> bool) -> RagResult<()> {
+        let row = self.row_bytes();
+        let mut kept = 0;
+        if let Some(map) = self.map.as_mut() {
+            for index in 0..self.len {
+                if keep(index) {
+                    if kept != index {
+                        map.copy_within(index * row..(index + 1) * row, kept * row);
+                    }
+                    kept += 1;
+                }
+            }
+        }
+
+        self.map = None;
+        self.len = kept;
+        self.file
+            .set_len((kept * row) as u64)
+            .map_err(RagError::Io)?;
+        self.remap()
+    }
+
+    fn row_bytes(&self) -> usize {
+        self.dimension * std::mem::size_of::<f32>()
+    }
+
+    fn remap(&mut self) -> RagResult<()> {
+        self.map = if self.len == 0 {
+            None
+        } else {
+            // SAFETY: the file is unlinked and only reachable through this
+            // struct, which drops the map before resizing the file
+            Some(unsafe { MmapMut::map_mut(&self.file) }.map_err(RagError::Io)?)
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors_survive_appends_and_removals() {
+        let mut vectors = MappedVectors::create(3, None).unwrap();
+        assert!(vectors.get(0).is_none());
+
+        let rows: Vec<Vec<f32>> = (0..5).map(|i| vec![i as f32, 1.0, -1.0]).collect();
+        vectors.extend(rows[..3].iter().map(Vec::as_slice)).unwrap();
+        vectors.extend(rows[3..].iter().map(Vec::as_slice)).unwrap();
+        assert_eq!(vectors.len(), 5);
+        assert_eq!(vectors.get(4), Some(&[4.0, 1.0, -1.0][..]));
+
+        vectors.retain(|index| index % 2 == 0).unwrap();
+        assert_eq!(vectors.len(), 3);
+        let firsts: Vec<f32> = (0..3).map(|i| vectors.get(i).unwrap()[0]).collect();
+        assert_eq!(firsts, [0.0, 2.0, 4.0]);
+        assert!(vectors.get(3).is_none());
+
+        vectors.retain(|_| false).unwrap();
+        assert_eq!(vectors.len(), 0);
+        vectors.extend([&[7.0, 8.0, 9.0][..]]).unwrap();
+        assert_eq!(vectors.get(0), Some(&[7.0, 8.0, 9.0][..]));
+    }
+}
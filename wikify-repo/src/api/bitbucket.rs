@@ -6,8 +6,8 @@ use serde::Deserialize;
 use wikify_core::{WikifyError, WikifyResult};
 
 use super::{
-    create_http_client, handle_response_error, ApiClientConfig, RepositoryApiClient,
-    RepositoryFile, RepositoryMetadata,
+    create_http_client, get_with_retry, ApiClientConfig, RepositoryApiClient, RepositoryFile,
+    RepositoryMetadata,
 };
 
 /// Bitbucket API client
@@ -91,26 +91,14 @@ impl BitbucketApiClient {
 
         debug!("Making Bitbucket API request to: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.create_auth_headers())
-            .send()
-            .await
-            .map_err(|e| WikifyError::Repository {
-                message: format!("Failed to make request to Bitbucket API: {}", e),
-                source: Some(Box::new(e)),
-                context: wikify_core::ErrorContext::new("bitbucket_api_client")
-                    .with_operation("get_request"),
-            })?;
-
-        if !response.status().is_success() {
-            return Err(Box::new(
-                handle_response_error(response, "bitbucket_api_request").await,
-            ));
-        }
-
-        Ok(response)
+        get_with_retry(
+            &self.client,
+            &self.config,
+            &url,
+            self.create_auth_headers(),
+            "Bitbucket",
+        )
+        .await
     }
 
     /// Get all tree items with pagination
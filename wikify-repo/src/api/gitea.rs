@@ -10,8 +10,8 @@ use serde::Deserialize;
 use wikify_core::{WikifyError, WikifyResult};
 
 use super::{
-    create_http_client, handle_response_error, ApiClientConfig, RepositoryApiClient,
-    RepositoryFile, RepositoryMetadata,
+    create_http_client, get_with_retry, ApiClientConfig, RepositoryApiClient, RepositoryFile,
+    RepositoryMetadata,
 };
 
 /// Gitea API client (GitHub-compatible)
@@ -90,26 +90,14 @@ impl GiteaApiClient {
 
         debug!("Making Gitea API request to: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.create_auth_headers())
-            .send()
-            .await
-            .map_err(|e| WikifyError::Repository {
-                message: format!("Failed to make request to Gitea API: {}", e),
-                source: Some(Box::new(e)),
-                context: wikify_core::ErrorContext::new("gitea_api_client")
-                    .with_operation("get_request"),
-            })?;
-
-        if !response.status().is_success() {
-            return Err(Box::new(
-                handle_response_error(response, "gitea_api_request").await,
-            ));
-        }
-
-        Ok(response)
+        get_with_retry(
+            &self.client,
+            &self.config,
+            &url,
+            self.create_auth_headers(),
+            "Gitea",
+        )
+        .await
     }
 
     /// Decode base64 content from Gitea API
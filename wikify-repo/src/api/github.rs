@@ -7,8 +7,8 @@ use serde::Deserialize;
 use wikify_core::{WikifyError, WikifyResult};
 
 use super::{
-    create_http_client, handle_response_error, ApiClientConfig, RepositoryApiClient,
-    RepositoryFile, RepositoryMetadata,
+    create_http_client, get_with_retry, ApiClientConfig, RepositoryApiClient, RepositoryFile,
+    RepositoryMetadata,
 };
 
 /// GitHub API client
@@ -106,26 +106,14 @@ impl GitHubApiClient {
 
         debug!("Making GitHub API request to: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.create_auth_headers())
-            .send()
-            .await
-            .map_err(|e| WikifyError::Repository {
-                message: format!("Failed to make request to GitHub API: {}", e),
-                source: Some(Box::new(e)),
-                context: wikify_core::ErrorContext::new("github_api_client")
-                    .with_operation("get_request"),
-            })?;
-
-        if !response.status().is_success() {
-            return Err(Box::new(
-                handle_response_error(response, "github_api_request").await,
-            ));
-        }
-
-        Ok(response)
+        get_with_retry(
+            &self.client,
+            &self.config,
+            &url,
+            self.create_auth_headers(),
+            "GitHub",
+        )
+        .await
     }
 
     /// Decode base64 content from GitHub API
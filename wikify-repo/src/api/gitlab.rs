@@ -7,8 +7,8 @@ use std::collections::HashMap;
 use wikify_core::{WikifyError, WikifyResult};
 
 use super::{
-    create_http_client, handle_response_error, ApiClientConfig, RepositoryApiClient,
-    RepositoryFile, RepositoryMetadata,
+    create_http_client, get_with_retry, ApiClientConfig, RepositoryApiClient, RepositoryFile,
+    RepositoryMetadata,
 };
 
 /// GitLab API client
@@ -83,26 +83,14 @@ impl GitLabApiClient {
 
         debug!("Making GitLab API request to: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.create_auth_headers())
-            .send()
-            .await
-            .map_err(|e| WikifyError::Repository {
-                message: format!("Failed to make request to GitLab API: {}", e),
-                source: Some(Box::new(e)),
-                context: wikify_core::ErrorContext::new("gitlab_api_client")
-                    .with_operation("get_request"),
-            })?;
-
-        if !response.status().is_success() {
-            return Err(Box::new(
-                handle_response_error(response, "gitlab_api_request").await,
-            ));
-        }
-
-        Ok(response)
+        get_with_retry(
+            &self.client,
+            &self.config,
+            &url,
+            self.create_auth_headers(),
+            "GitLab",
+        )
+        .await
     }
 
     /// Encode project path for GitLab API
@@ -6,7 +6,7 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use wikify_core::{WikifyError, WikifyResult};
+use wikify_core::{retry_with_backoff, RetryConfig, WikifyError, WikifyResult};
 
 pub mod bitbucket;
 pub mod gitea;
@@ -66,6 +66,8 @@ pub struct ApiClientConfig {
     pub user_agent: String,
     /// Additional headers
     pub headers: HashMap<String, String>,
+    /// Retries of connection failures, rate limits and server errors
+    pub retry: RetryConfig,
 }
 
 impl Default for ApiClientConfig {
@@ -76,6 +78,10 @@ impl Default for ApiClientConfig {
             timeout_seconds: 30,
             user_agent: "wikify/1.0".to_string(),
             headers: HashMap::new(),
+            retry: RetryConfig {
+                initial_delay_ms: 500,
+                ..Default::default()
+            },
         }
     }
 }
@@ -128,6 +134,12 @@ impl ApiClientConfig {
         self.timeout_seconds = timeout_seconds;
         self
     }
+
+    /// Set how requests are retried
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
 }
 
 /// Trait for repository API clients
@@ -249,35 +261,103 @@ pub(crate) fn create_http_client(config: &ApiClientConfig) -> WikifyResult<reqwe
     Ok(client)
 }
 
+/// Send a GET request, retrying connection failures, rate limits and
+/// server errors with backoff
+///
+/// `platform` names the host in error messages, such as `GitHub`.
+pub(crate) async fn get_with_retry(
+    client: &reqwest::Client,
+    config: &ApiClientConfig,
+    url: &str,
+    headers: reqwest::header::HeaderMap,
+    platform: &str,
+) -> WikifyResult<reqwest::Response> {
+    let client_name = format!("{}_api_client", platform.to_lowercase());
+    retry_with_backoff(
+        &config.retry,
+        &client_name,
+        |error: &Box<WikifyError>| error.is_recoverable(),
+        || async {
+            let response = client
+                .get(url)
+                .headers(headers.clone())
+                .send()
+                .await
+                .map_err(|e| WikifyError::Network {
+                    message: format!("Failed to make request to {} API: {}", platform, e),
+                    source: Some(Box::new(e)),
+                    context: wikify_core::ErrorContext::new(&client_name)
+                        .with_operation("get_request"),
+                })?;
+
+            if !response.status().is_success() {
+                let context = format!("{}_api_request", platform.to_lowercase());
+                return Err(Box::new(handle_response_error(response, &context).await));
+            }
+            Ok(response)
+        },
+    )
+    .await
+}
+
 /// Helper function to handle HTTP response errors
+///
+/// Rate limits and server errors become recoverable errors, so requests
+/// are retried; other failures are repository errors.
 pub(crate) async fn handle_response_error(
     response: reqwest::Response,
     context: &str,
 ) -> WikifyError {
     let status = response.status();
     let url = response.url().clone();
+    let rate_limited = status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || (status == reqwest::StatusCode::FORBIDDEN
+            && response
+                .headers()
+                .get("x-ratelimit-remaining")
+                .is_some_and(|remaining| remaining == "0"));
+    let retry_after_ms = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|seconds| seconds * 1000);
 
     let error_body = response.text().await.unwrap_or_default();
+    let message = format!(
+        "HTTP {} error for {}: {}",
+        status.as_u16(),
+        url,
+        if error_body.is_empty() {
+            status.canonical_reason().unwrap_or("Unknown error")
+        } else {
+            &error_body
+        }
+    );
+    let context = wikify_core::ErrorContext::new("api_client").with_operation(context);
 
-    WikifyError::Repository {
-        message: format!(
-            "HTTP {} error for {}: {}",
-            status.as_u16(),
-            url,
-            if error_body.is_empty() {
-                status.canonical_reason().unwrap_or("Unknown error")
-            } else {
-                &error_body
-            }
-        ),
-        source: None,
-        context: wikify_core::ErrorContext::new("api_client")
-            .with_operation(context)
-            .with_suggestion(match status.as_u16() {
+    if rate_limited {
+        WikifyError::RateLimit {
+            message,
+            retry_after_ms,
+            context: context.with_suggestion("Use an access token for a higher rate limit"),
+        }
+    } else if status.is_server_error() {
+        WikifyError::Network {
+            message,
+            source: None,
+            context: context.with_suggestion("Check network connectivity and API status"),
+        }
+    } else {
+        WikifyError::Repository {
+            message,
+            source: None,
+            context: context.with_suggestion(match status.as_u16() {
                 401 => "Check your access token",
                 403 => "Check repository permissions or rate limits",
                 404 => "Repository not found or not accessible",
                 _ => "Check network connectivity and API status",
             }),
+        }
     }
 }
@@ -0,0 +1,180 @@
+//! Archive extraction for uploaded repositories
+//!
+//! Lets users without a git URL provide a codebase as a zip archive. Entries are
+//! extracted under a target directory with zip-slip protection and a cap on the
+//! total uncompressed size.
+
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// Extract a zip archive into `target_dir` and return the repository root.
+///
+/// If every entry lives under a single top-level directory (the layout produced
+/// by GitHub "Download ZIP" and most archivers), that directory is returned as
+/// the root. Otherwise `target_dir` itself is the root.
+pub fn extract_zip_archive(
+    data: &[u8],
+    target_dir: &Path,
+    max_uncompressed_size: u64,
+) -> WikifyResult<PathBuf> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).map_err(|e| {
+        Box::new(WikifyError::Repository {
+            message: format!("Invalid zip archive: {}", e),
+            source: Some(Box::new(e)),
+            context: ErrorContext::new("archive")
+                .with_operation("extract_zip_archive")
+                .with_suggestion("Upload a valid .zip file"),
+        })
+    })?;
+
+    std::fs::create_dir_all(target_dir)?;
+
+    let mut total_size: u64 = 0;
+    let mut top_level: Option<PathBuf> = None;
+    let mut single_top_level = true;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(|e| {
+            Box::new(WikifyError::Repository {
+                message: format!("Failed to read archive entry {}: {}", index, e),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("archive").with_operation("extract_zip_archive"),
+            })
+        })?;
+
+        // enclosed_name rejects absolute paths and `..` components (zip-slip)
+        let relative_path = match entry.enclosed_name() {
+            Some(path) => path,
+            None => {
+                return Err(Box::new(WikifyError::Validation {
+                    message: format!("Archive entry has an unsafe path: {}", entry.name()),
+                    field: Some("file".to_string()),
+                    context: ErrorContext::new("archive").with_operation("extract_zip_archive"),
+                }));
+            }
+        };
+
+        if let Some(first) = relative_path.components().next() {
+            let first = PathBuf::from(first.as_os_str());
+            match &top_level {
+                None => top_level = Some(first),
+                Some(existing) if *existing != first => single_top_level = false,
+                _ => {}
+            }
+            // A file at the archive root means there is no wrapping directory
+            if relative_path.components().count() == 1 && !entry.is_dir() {
+                single_top_level = false;
+            }
+        }
+
+        let output_path = target_dir.join(&relative_path);
+
+        if entry.is_dir() {
+            std::fs::create_dir_all(&output_path)?;
+            continue;
+        }
+
+        if let Some(parent) = output_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Bound the actual bytes read rather than trusting the declared entry size
+        let remaining = max_uncompressed_size.saturating_sub(total_size);
+        let mut buffer = Vec::with_capacity(entry.size().min(remaining) as usize);
+        entry
+            .by_ref()
+            .take(remaining + 1)
+            .read_to_end(&mut buffer)?;
+        total_size += buffer.len() as u64;
+        if total_size > max_uncompressed_size {
+            return Err(Box::new(WikifyError::Validation {
+                message: format!(
+                    "Archive exceeds the maximum extracted size of {} bytes",
+                    max_uncompressed_size
+                ),
+                field: Some("file".to_string()),
+                context: ErrorContext::new("archive").with_operation("extract_zip_archive"),
+            }));
+        }
+
+        let mut output = std::fs::File::create(&output_path)?;
+        output.write_all(&buffer)?;
+
+        debug!(path = %relative_path.display(), "Extracted archive entry");
+    }
+
+    let root = match top_level {
+        Some(dir) if single_top_level && target_dir.join(&dir).is_dir() => target_dir.join(dir),
+        _ => target_dir.to_path_buf(),
+    };
+
+    info!(
+        target_dir = %target_dir.display(),
+        root = %root.display(),
+        entries = archive.len(),
+        total_size = total_size,
+        "📦 Archive extracted"
+    );
+
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zip::write::SimpleFileOptions;
+
+    fn build_zip(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in entries {
+            writer
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_extract_detects_single_top_level_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let data = build_zip(&[
+            ("project/src/main.rs", "fn main() {}"),
+            ("project/README.md", "# hi"),
+        ]);
+
+        let root = extract_zip_archive(&data, temp.path(), 1024).unwrap();
+
+        assert_eq!(root, temp.path().join("project"));
+        assert!(root.join("src/main.rs").exists());
+    }
+
+    #[test]
+    fn test_extract_flat_archive_uses_target_dir() {
+        let temp = tempfile::tempdir().unwrap();
+        let data = build_zip(&[("main.rs", "fn main() {}"), ("lib/mod.rs", "")]);
+
+        let root = extract_zip_archive(&data, temp.path(), 1024).unwrap();
+
+        assert_eq!(root, temp.path());
+    }
+
+    #[test]
+    fn test_extract_rejects_oversized_archive() {
+        let temp = tempfile::tempdir().unwrap();
+        let data = build_zip(&[("big.txt", &"x".repeat(2048))]);
+
+        assert!(extract_zip_archive(&data, temp.path(), 1024).is_err());
+    }
+
+    #[test]
+    fn test_extract_rejects_path_traversal() {
+        let temp = tempfile::tempdir().unwrap();
+        let data = build_zip(&[("../evil.txt", "pwned")]);
+
+        assert!(extract_zip_archive(&data, &temp.path().join("out"), 1024).is_err());
+        assert!(!temp.path().join("evil.txt").exists());
+    }
+}
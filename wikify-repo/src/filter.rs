@@ -1,6 +1,8 @@
 //! File filtering utilities for repository processing
 
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
 
 /// Check if a file should be included in processing
 pub fn should_include_file<P: AsRef<Path>>(file_path: P) -> bool {
@@ -33,3 +35,120 @@ pub fn should_traverse_directory<P: AsRef<Path>>(dir_path: P) -> bool {
 
     true
 }
+
+/// How symbolic links inside a repository are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    /// Ignore symlinks entirely
+    Skip,
+    /// Follow symlinks whose target resolves inside the repository root
+    #[default]
+    FollowWithinRoot,
+}
+
+/// Check whether a path resolves (following symlinks) to a location inside `root`
+pub fn is_within_root<P: AsRef<Path>, Q: AsRef<Path>>(root: P, path: Q) -> bool {
+    match (
+        std::fs::canonicalize(root.as_ref()),
+        std::fs::canonicalize(path.as_ref()),
+    ) {
+        (Ok(root), Ok(path)) => path.starts_with(root),
+        _ => false,
+    }
+}
+
+/// Resolve a repository-relative path to a file inside `root`
+///
+/// Absolute paths and `..` components are rejected outright. The resolved
+/// file must stay inside the root after following symlinks, and with
+/// [`SymlinkPolicy::Skip`] it must not pass through any symlink at all.
+pub fn resolve_repository_path<P: AsRef<Path>>(
+    root: P,
+    relative_path: &str,
+    policy: SymlinkPolicy,
+) -> WikifyResult<PathBuf> {
+    let relative = Path::new(relative_path);
+    let invalid = |message: String| {
+        Box::new(WikifyError::Validation {
+            message,
+            field: Some("file_path".to_string()),
+            context: ErrorContext::new("file_filter").with_operation("resolve_repository_path"),
+        })
+    };
+
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(invalid(format!(
+            "Path must be relative to the repository root: {}",
+            relative_path
+        )));
+    }
+
+    let root = std::fs::canonicalize(root.as_ref())?;
+    let lexical = root.join(relative);
+    let resolved = std::fs::canonicalize(&lexical).map_err(|_| {
+        Box::new(WikifyError::NotFound {
+            resource: format!("File not found: {}", relative_path),
+            context: ErrorContext::new("file_filter")
+                .with_operation("resolve_repository_path")
+                .with_suggestion("Check if the file path is correct"),
+        })
+    })?;
+
+    if !resolved.starts_with(&root) {
+        return Err(invalid(format!(
+            "Path resolves outside the repository root: {}",
+            relative_path
+        )));
+    }
+
+    // Canonicalization only changes an already-absolute path by resolving symlinks
+    let lexical: PathBuf = lexical.components().collect();
+    if policy == SymlinkPolicy::Skip && resolved != lexical {
+        return Err(invalid(format!(
+            "Path goes through a symlink: {}",
+            relative_path
+        )));
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_rejects_parent_traversal() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+        let root = temp.path().join("repo");
+        std::fs::create_dir(&root).unwrap();
+
+        let result = resolve_repository_path(&root, "../secret.txt", SymlinkPolicy::default());
+        assert!(result.is_err());
+        assert!(resolve_repository_path(&root, "/etc/passwd", SymlinkPolicy::default()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_symlink_policy() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+        let root = temp.path().join("repo");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("README.md"), "readme").unwrap();
+        std::os::unix::fs::symlink(root.join("README.md"), root.join("inside")).unwrap();
+        std::os::unix::fs::symlink(temp.path().join("secret.txt"), root.join("outside")).unwrap();
+
+        assert!(resolve_repository_path(&root, "README.md", SymlinkPolicy::Skip).is_ok());
+        assert!(resolve_repository_path(&root, "inside", SymlinkPolicy::FollowWithinRoot).is_ok());
+        assert!(resolve_repository_path(&root, "inside", SymlinkPolicy::Skip).is_err());
+        assert!(
+            resolve_repository_path(&root, "outside", SymlinkPolicy::FollowWithinRoot).is_err()
+        );
+    }
+}
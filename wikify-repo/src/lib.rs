@@ -4,10 +4,14 @@
 
 pub mod analyzer;
 pub mod api;
+pub mod archive;
 pub mod filter;
 pub mod processor;
+pub mod workspace;
 
 pub use analyzer::*;
 pub use api::*;
+pub use archive::*;
 pub use filter::*;
 pub use processor::*;
+pub use workspace::*;
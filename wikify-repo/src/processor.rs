@@ -6,12 +6,15 @@
 //! - LocalDirectory: Direct local access, immediate
 
 use crate::api::{ApiClientConfig, ApiClientFactory, RepositoryApiClient};
+use crate::filter::{is_within_root, resolve_repository_path, SymlinkPolicy};
+use crate::workspace::CloneWorkspace;
 use glob::Pattern;
 use ignore::WalkBuilder;
 use std::path::{Path, PathBuf};
 use tokio::process::Command;
 use tracing::{debug, info, warn};
 use url::Url;
+use wikify_core::secrets::secret;
 use wikify_core::{
     ErrorContext, RepoAccessMode, RepoInfo, RepoType, RepositoryAccess, RepositoryAccessConfig,
     RepositoryFile, WikifyError, WikifyResult,
@@ -26,6 +29,8 @@ pub struct FileFilterConfig {
     pub additional_patterns: Vec<String>,
     /// 编译后的 glob 模式（内部使用）
     compiled_patterns: Vec<Pattern>,
+    /// Symlink handling (default: follow only within the repository root)
+    pub symlink_policy: SymlinkPolicy,
 }
 
 impl FileFilterConfig {
@@ -51,9 +56,16 @@ impl FileFilterConfig {
             use_gitignore,
             additional_patterns,
             compiled_patterns,
+            symlink_policy: SymlinkPolicy::default(),
         })
     }
 
+    /// Set the symlink handling policy
+    pub fn with_symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlink_policy = policy;
+        self
+    }
+
     /// 添加新的 glob 模式
     pub fn add_pattern(&mut self, pattern: &str) -> WikifyResult<()> {
         match Pattern::new(pattern) {
@@ -77,6 +89,7 @@ impl Default for FileFilterConfig {
             use_gitignore: true,
             additional_patterns: vec![],
             compiled_patterns: vec![],
+            symlink_policy: SymlinkPolicy::default(),
         }
     }
 }
@@ -157,8 +170,9 @@ impl RepositoryProcessor {
 
         // Step 3: Set up repository access based on mode
         let repository_access = match access_mode {
-            RepoAccessMode::Api => self.setup_api_access(&repo_info, &config).await?,
-            RepoAccessMode::GitClone => self.setup_git_clone_access(&repo_info, &config).await?,
+            RepoAccessMode::Api | RepoAccessMode::GitClone => {
+                self.setup_remote_access(&repo_info, &config).await?
+            }
             RepoAccessMode::LocalDirectory => self.setup_local_directory_access(&repo_info).await?,
         };
 
@@ -258,11 +272,11 @@ impl RepositoryProcessor {
 
         // Check environment variables
         match repo_type {
-            RepoType::GitHub => std::env::var("GITHUB_TOKEN").is_ok(),
-            RepoType::GitLab => std::env::var("GITLAB_TOKEN").is_ok(),
-            RepoType::Bitbucket => std::env::var("BITBUCKET_TOKEN").is_ok(),
+            RepoType::GitHub => secret("GITHUB_TOKEN").is_some(),
+            RepoType::GitLab => secret("GITLAB_TOKEN").is_some(),
+            RepoType::Bitbucket => secret("BITBUCKET_TOKEN").is_some(),
             RepoType::Gitea => {
-                std::env::var("GITEA_TOKEN").is_ok() && std::env::var("GITEA_BASE_URL").is_ok()
+                secret("GITEA_TOKEN").is_some() && std::env::var("GITEA_BASE_URL").is_ok()
             }
             RepoType::Local => false,
         }
@@ -338,6 +352,93 @@ impl RepositoryProcessor {
         })
     }
 
+    /// Set up remote access, trying the primary URL and then each mirror in order
+    ///
+    /// Clones made from a mirror are stored at the primary repository's clone
+    /// path so later accesses reuse them. The configured API token belongs to
+    /// the primary remote and is never sent to a mirror.
+    async fn setup_remote_access(
+        &self,
+        repo_info: &RepoInfo,
+        config: &RepositoryAccessConfig,
+    ) -> WikifyResult<RepositoryAccess> {
+        let mut result = self.setup_remote_access_with(repo_info, config).await;
+
+        for mirror_url in &config.mirror_urls {
+            let Err(e) = &result else {
+                break;
+            };
+
+            warn!(
+                repo_url = %repo_info.url,
+                mirror_url = %mirror_url,
+                error = %e,
+                "⚠️ Remote access failed, trying mirror"
+            );
+
+            let mirror_info = match repo_info.access_mode {
+                // API calls need the mirror's own owner/name
+                RepoAccessMode::Api => {
+                    match self.parse_repository_info(mirror_url, RepoAccessMode::Api) {
+                        Ok(info) => info,
+                        Err(e) => {
+                            result = Err(e);
+                            continue;
+                        }
+                    }
+                }
+                _ => RepoInfo {
+                    url: mirror_url.clone(),
+                    repo_type: self.detect_repo_type(mirror_url),
+                    ..repo_info.clone()
+                },
+            };
+            let clone_path = config.custom_local_path.clone().unwrap_or_else(|| {
+                self.get_default_clone_path(repo_info)
+                    .to_string_lossy()
+                    .to_string()
+            });
+            let mirror_config = RepositoryAccessConfig {
+                api_token: None,
+                custom_local_path: Some(clone_path),
+                ..config.clone()
+            };
+
+            result = self
+                .setup_remote_access_with(&mirror_info, &mirror_config)
+                .await;
+        }
+
+        let mut access = result?;
+        if access.access_mode == RepoAccessMode::GitClone {
+            // The clone stands in for the primary repository
+            access.repo_info = repo_info.clone();
+        }
+        if access.remote_url.as_deref() != Some(repo_info.url.as_str()) {
+            info!(
+                repo_url = %repo_info.url,
+                remote_url = ?access.remote_url,
+                "🪞 Repository accessed through mirror"
+            );
+        }
+
+        Ok(access)
+    }
+
+    /// Set up API or Git clone access for a single remote
+    async fn setup_remote_access_with(
+        &self,
+        repo_info: &RepoInfo,
+        config: &RepositoryAccessConfig,
+    ) -> WikifyResult<RepositoryAccess> {
+        let mut access = match repo_info.access_mode {
+            RepoAccessMode::Api => self.setup_api_access(repo_info, config).await?,
+            _ => self.setup_git_clone_access(repo_info, config).await?,
+        };
+        access.remote_url = Some(repo_info.url.clone());
+        Ok(access)
+    }
+
     /// Set up API access mode
     async fn setup_api_access(
         &self,
@@ -366,6 +467,7 @@ impl RepositoryProcessor {
             access_mode: RepoAccessMode::Api,
             local_path: None,
             is_ready: true, // API access is always ready if we have tokens
+            remote_url: None,
         })
     }
 
@@ -402,11 +504,15 @@ impl RepositoryProcessor {
             true
         };
 
+        // Record usage so workspace garbage collection evicts least recently used clones
+        CloneWorkspace::touch(&local_path);
+
         Ok(RepositoryAccess {
             repo_info: repo_info.clone(),
             access_mode: RepoAccessMode::GitClone,
             local_path: Some(local_path),
             is_ready,
+            remote_url: None,
         })
     }
 
@@ -437,9 +543,23 @@ impl RepositoryProcessor {
             access_mode: RepoAccessMode::LocalDirectory,
             local_path: Some(local_path),
             is_ready: true,
+            remote_url: None,
         })
     }
 
+    /// Get the path a remote repository is (or would be) cloned to
+    ///
+    /// Returns None for local directories, which are never cloned.
+    pub fn clone_path(&self, url_or_path: &str) -> Option<PathBuf> {
+        if !url_or_path.starts_with("http") {
+            return None;
+        }
+
+        self.parse_repository_info(url_or_path, RepoAccessMode::GitClone)
+            .ok()
+            .map(|repo_info| self.get_default_clone_path(&repo_info))
+    }
+
     /// Get default clone path like DeepWiki: ~/.wikify/repos/{owner}_{repo_name}
     fn get_default_clone_path(&self, repo_info: &RepoInfo) -> PathBuf {
         let repo_name = format!("{}_{}", repo_info.owner, repo_info.name);
@@ -614,10 +734,10 @@ impl RepositoryProcessor {
     /// Get environment token for repository type
     fn get_env_token(&self, repo_type: &RepoType) -> Option<String> {
         match repo_type {
-            RepoType::GitHub => std::env::var("GITHUB_TOKEN").ok(),
-            RepoType::GitLab => std::env::var("GITLAB_TOKEN").ok(),
-            RepoType::Bitbucket => std::env::var("BITBUCKET_TOKEN").ok(),
-            RepoType::Gitea => std::env::var("GITEA_TOKEN").ok(),
+            RepoType::GitHub => secret("GITHUB_TOKEN"),
+            RepoType::GitLab => secret("GITLAB_TOKEN"),
+            RepoType::Bitbucket => secret("BITBUCKET_TOKEN"),
+            RepoType::Gitea => secret("GITEA_TOKEN"),
             RepoType::Local => None,
         }
     }
@@ -812,17 +932,9 @@ impl RepositoryProcessor {
                     .with_operation("get_file_content_local"),
             })?;
 
-        let file_full_path = local_path.join(file_path);
-
-        if !file_full_path.exists() {
-            return Err(Box::new(WikifyError::Repository {
-                message: format!("File not found: {}", file_path),
-                source: None,
-                context: ErrorContext::new("repository_processor")
-                    .with_operation("get_file_content_local")
-                    .with_suggestion("Check if the file path is correct"),
-            }));
-        }
+        // Rejects `../` traversal and symlinks leading out of the repository
+        let file_full_path =
+            resolve_repository_path(local_path, file_path, self.filter_config.symlink_policy)?;
 
         let content = tokio::fs::read_to_string(&file_full_path)
             .await
@@ -852,6 +964,21 @@ impl RepositoryProcessor {
 
         // Note: .git directory is automatically ignored by the ignore library
 
+        // Apply the symlink policy; links resolving outside the root are never followed
+        match self.filter_config.symlink_policy {
+            SymlinkPolicy::Skip => {
+                builder.follow_links(false);
+                builder.filter_entry(|entry| !entry.path_is_symlink());
+            }
+            SymlinkPolicy::FollowWithinRoot => {
+                let root = root_path.to_path_buf();
+                builder.follow_links(true);
+                builder.filter_entry(move |entry| {
+                    !entry.path_is_symlink() || is_within_root(&root, entry.path())
+                });
+            }
+        }
+
         let walker = builder.build();
 
         for result in walker {
@@ -937,3 +1064,88 @@ impl RepositoryProcessor {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_git_repo(path: &Path) {
+        std::fs::create_dir_all(path).unwrap();
+        std::fs::write(path.join("README.md"), "# mirror").unwrap();
+        for args in [
+            &["init", "-q"][..],
+            &["add", "."][..],
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-qm",
+                "init",
+            ][..],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(path)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_access_repository_falls_back_to_mirror() {
+        let temp = tempfile::tempdir().unwrap();
+        let mirror = temp.path().join("mirror");
+        init_git_repo(&mirror);
+        let mirror_url = mirror.to_string_lossy().to_string();
+
+        let processor = RepositoryProcessor::new(temp.path().join("base"));
+        let config = RepositoryAccessConfig {
+            preferred_mode: Some(RepoAccessMode::GitClone),
+            force_mode: true,
+            mirror_urls: vec![mirror_url.clone()],
+            ..Default::default()
+        };
+
+        // Nothing listens on port 1, so the primary remote fails immediately
+        let primary_url = "http://127.0.0.1:1/owner/repo";
+        let access = processor
+            .access_repository(primary_url, Some(config))
+            .await
+            .unwrap();
+
+        assert_eq!(access.remote_url.as_deref(), Some(mirror_url.as_str()));
+        assert_eq!(access.repo_info.url, primary_url);
+        assert_eq!(access.local_path, processor.clone_path(primary_url));
+        assert!(access.local_path.unwrap().join("README.md").exists());
+    }
+
+    #[tokio::test]
+    async fn test_get_file_content_rejects_traversal() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+        let root = temp.path().join("repo");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("README.md"), "readme").unwrap();
+
+        let processor = RepositoryProcessor::new(temp.path().join("base"));
+        let access = processor
+            .access_repository(&root.to_string_lossy(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            processor
+                .get_file_content(&access, "README.md", None)
+                .await
+                .unwrap(),
+            "readme"
+        );
+        assert!(processor
+            .get_file_content(&access, "../secret.txt", None)
+            .await
+            .is_err());
+    }
+}
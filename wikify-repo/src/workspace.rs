@@ -0,0 +1,342 @@
+//! Clone workspace management
+//!
+//! Cloned repositories live under `{base_path}/repos`. Without cleanup they
+//! accumulate forever, so this module provides a retention policy (max total
+//! size, max age, least-recently-used eviction) and a garbage collector that
+//! applies it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::{debug, info, warn};
+use walkdir::WalkDir;
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// Marker file recording when a clone was last used
+pub const LAST_USED_MARKER: &str = ".wikify-last-used";
+
+/// Retention policy for the clone workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Maximum total size of all clones in bytes (None = unlimited)
+    pub max_total_size_bytes: Option<u64>,
+    /// Remove clones that have not been used for this long (None = keep forever)
+    pub max_age: Option<Duration>,
+    /// Maximum number of clones to keep (None = unlimited)
+    pub max_entries: Option<usize>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_total_size_bytes: Some(10 * 1024 * 1024 * 1024), // 10GB
+            max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)), // 30 days
+            max_entries: None,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// A policy that never removes anything
+    pub fn unlimited() -> Self {
+        Self {
+            max_total_size_bytes: None,
+            max_age: None,
+            max_entries: None,
+        }
+    }
+}
+
+/// A single cloned repository in the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceEntry {
+    /// Path of the clone directory
+    pub path: PathBuf,
+    /// Total size on disk in bytes
+    pub size_bytes: u64,
+    /// When the clone was last used
+    pub last_used: SystemTime,
+}
+
+/// Result of a garbage collection run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GcReport {
+    /// Number of clones inspected
+    pub scanned: usize,
+    /// Clones that were removed (or would be, for a dry run)
+    pub removed: Vec<PathBuf>,
+    /// Bytes freed by the removals
+    pub freed_bytes: u64,
+    /// Bytes still used by the workspace afterwards
+    pub remaining_bytes: u64,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+}
+
+impl GcReport {
+    /// Fold the report of another workspace into this one
+    pub fn merge(&mut self, other: GcReport) {
+        self.scanned += other.scanned;
+        self.removed.extend(other.removed);
+        self.freed_bytes += other.freed_bytes;
+        self.remaining_bytes += other.remaining_bytes;
+        self.dry_run |= other.dry_run;
+    }
+}
+
+/// Workspace holding cloned repositories
+#[derive(Debug, Clone)]
+pub struct CloneWorkspace {
+    root: PathBuf,
+}
+
+impl CloneWorkspace {
+    /// Create a workspace for the given wikify base path (clones live in `repos/`)
+    pub fn new<P: AsRef<Path>>(base_path: P) -> Self {
+        Self {
+            root: base_path.as_ref().join("repos"),
+        }
+    }
+
+    /// Directory containing the clones
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Record that a clone has just been used
+    pub fn touch(clone_path: &Path) {
+        let marker = clone_path.join(LAST_USED_MARKER);
+        let now = wikify_core::Utc::now().to_rfc3339();
+        if let Err(e) = std::fs::write(&marker, now) {
+            debug!(path = %marker.display(), error = %e, "Failed to update last-used marker");
+        }
+    }
+
+    /// List all clones in the workspace
+    pub fn entries(&self) -> WikifyResult<Vec<WorkspaceEntry>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            entries.push(WorkspaceEntry {
+                size_bytes: directory_size(&path),
+                last_used: last_used(&path),
+                path,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Apply a retention policy, skipping any clone listed in `protected`
+    pub fn collect_garbage(
+        &self,
+        policy: &RetentionPolicy,
+        protected: &HashSet<PathBuf>,
+        dry_run: bool,
+    ) -> WikifyResult<GcReport> {
+        let entries = self.entries()?;
+        let total_bytes: u64 = entries.iter().map(|e| e.size_bytes).sum();
+        let evict = plan_eviction(&entries, policy, protected, SystemTime::now());
+
+        let mut report = GcReport {
+            scanned: entries.len(),
+            dry_run,
+            ..Default::default()
+        };
+
+        for index in evict {
+            let entry = &entries[index];
+            if !dry_run {
+                if let Err(e) = self.remove(&entry.path) {
+                    warn!(path = %entry.path.display(), error = %e, "Failed to remove clone");
+                    continue;
+                }
+            }
+            report.freed_bytes += entry.size_bytes;
+            report.removed.push(entry.path.clone());
+        }
+
+        report.remaining_bytes = total_bytes.saturating_sub(report.freed_bytes);
+
+        info!(
+            scanned = report.scanned,
+            removed = report.removed.len(),
+            freed_bytes = report.freed_bytes,
+            dry_run = dry_run,
+            "🧹 Clone workspace garbage collection finished"
+        );
+
+        Ok(report)
+    }
+
+    /// Remove a clone directory; paths outside the workspace are rejected
+    pub fn remove(&self, clone_path: &Path) -> WikifyResult<u64> {
+        if !clone_path.starts_with(&self.root) || clone_path == self.root {
+            return Err(Box::new(WikifyError::Validation {
+                message: format!(
+                    "Refusing to remove path outside the clone workspace: {}",
+                    clone_path.display()
+                ),
+                field: None,
+                context: ErrorContext::new("clone_workspace").with_operation("remove"),
+            }));
+        }
+
+        if !clone_path.exists() {
+            return Ok(0);
+        }
+
+        let size = directory_size(clone_path);
+        std::fs::remove_dir_all(clone_path)?;
+        debug!(path = %clone_path.display(), size_bytes = size, "Removed clone");
+        Ok(size)
+    }
+}
+
+/// Decide which entries to evict, returning their indices
+///
+/// Entries older than `max_age` are always evicted. The remainder are evicted in
+/// least-recently-used order until both the size and count limits are met.
+pub fn plan_eviction(
+    entries: &[WorkspaceEntry],
+    policy: &RetentionPolicy,
+    protected: &HashSet<PathBuf>,
+    now: SystemTime,
+) -> Vec<usize> {
+    let mut candidates: Vec<usize> = (0..entries.len())
+        .filter(|&i| !protected.contains(&entries[i].path))
+        .collect();
+    // Oldest first
+    candidates.sort_by_key(|&i| entries[i].last_used);
+
+    let mut evict = Vec::new();
+    let mut total_bytes: u64 = entries.iter().map(|e| e.size_bytes).sum();
+    let mut count = entries.len();
+
+    for index in candidates {
+        let entry = &entries[index];
+        let expired = policy
+            .max_age
+            .map(|max_age| {
+                now.duration_since(entry.last_used)
+                    .map(|age| age > max_age)
+                    .unwrap_or(false)
+            })
+            .unwrap_or(false);
+        let over_size = policy
+            .max_total_size_bytes
+            .map(|max| total_bytes > max)
+            .unwrap_or(false);
+        let over_count = policy.max_entries.map(|max| count > max).unwrap_or(false);
+
+        if expired || over_size || over_count {
+            evict.push(index);
+            total_bytes = total_bytes.saturating_sub(entry.size_bytes);
+            count -= 1;
+        }
+    }
+
+    evict
+}
+
+/// Total size of all files below a directory
+pub fn directory_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Last-used time from the marker file, falling back to the directory mtime
+fn last_used(path: &Path) -> SystemTime {
+    let marker = path.join(LAST_USED_MARKER);
+    std::fs::metadata(&marker)
+        .and_then(|m| m.modified())
+        .or_else(|_| std::fs::metadata(path).and_then(|m| m.modified()))
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, size_bytes: u64, age_secs: u64, now: SystemTime) -> WorkspaceEntry {
+        WorkspaceEntry {
+            path: PathBuf::from(name),
+            size_bytes,
+            last_used: now - Duration::from_secs(age_secs),
+        }
+    }
+
+    #[test]
+    fn test_plan_eviction_by_age() {
+        let now = SystemTime::now();
+        let entries = vec![entry("old", 10, 1000, now), entry("new", 10, 10, now)];
+        let policy = RetentionPolicy {
+            max_total_size_bytes: None,
+            max_age: Some(Duration::from_secs(100)),
+            max_entries: None,
+        };
+
+        assert_eq!(
+            plan_eviction(&entries, &policy, &HashSet::new(), now),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn test_plan_eviction_lru_until_under_size() {
+        let now = SystemTime::now();
+        let entries = vec![
+            entry("a", 50, 30, now),
+            entry("b", 50, 10, now),
+            entry("c", 50, 20, now),
+        ];
+        let policy = RetentionPolicy {
+            max_total_size_bytes: Some(100),
+            max_age: None,
+            max_entries: None,
+        };
+
+        // Only the least recently used clone needs to go
+        assert_eq!(
+            plan_eviction(&entries, &policy, &HashSet::new(), now),
+            vec![0]
+        );
+    }
+
+    #[test]
+    fn test_plan_eviction_skips_protected() {
+        let now = SystemTime::now();
+        let entries = vec![entry("a", 50, 30, now), entry("b", 50, 10, now)];
+        let policy = RetentionPolicy {
+            max_total_size_bytes: None,
+            max_age: None,
+            max_entries: Some(1),
+        };
+        let protected: HashSet<PathBuf> = [PathBuf::from("a")].into_iter().collect();
+
+        assert_eq!(plan_eviction(&entries, &policy, &protected, now), vec![1]);
+    }
+
+    #[test]
+    fn test_remove_rejects_paths_outside_workspace() {
+        let temp = tempfile::tempdir().unwrap();
+        let workspace = CloneWorkspace::new(temp.path());
+
+        assert!(workspace.remove(temp.path()).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+fn main() {
+    // Migrations are embedded with sqlx::migrate!, so new files must rebuild
+    println!("cargo:rerun-if-changed=migrations");
+
+    // The gRPC service is generated from its protobuf definition
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/wikify.proto")
+        .expect("failed to compile proto/wikify.proto");
+}
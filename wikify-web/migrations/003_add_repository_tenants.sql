@@ -0,0 +1,8 @@
+-- Repository ownership for multi-tenancy
+-- Repositories belong to the user who added them, optionally inside an organization
+
+ALTER TABLE repositories ADD COLUMN owner_id TEXT;
+ALTER TABLE repositories ADD COLUMN organization_id TEXT;
+
+CREATE INDEX idx_repositories_owner ON repositories(owner_id);
+CREATE INDEX idx_repositories_organization ON repositories(organization_id);
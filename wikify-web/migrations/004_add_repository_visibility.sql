@@ -0,0 +1,7 @@
+-- Repository visibility and explicit share grants
+-- Organization repositories stay visible to their members
+
+ALTER TABLE repositories ADD COLUMN visibility TEXT NOT NULL DEFAULT 'private';
+ALTER TABLE repositories ADD COLUMN share_grants TEXT NOT NULL DEFAULT '[]';
+
+UPDATE repositories SET visibility = 'org' WHERE organization_id IS NOT NULL;
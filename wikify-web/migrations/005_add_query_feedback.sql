@@ -0,0 +1,15 @@
+-- Feedback on answers, stored next to the query record it rates
+
+CREATE TABLE IF NOT EXISTS query_feedback (
+    id TEXT PRIMARY KEY,
+    query_id TEXT NOT NULL,
+    repository_id TEXT,
+    user_id TEXT,
+    rating TEXT NOT NULL CHECK (rating IN ('up', 'down')),
+    comment TEXT,
+    wrong_sources TEXT NOT NULL DEFAULT '[]', -- JSON array of file paths
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_query_feedback_query ON query_feedback(query_id);
+CREATE INDEX IF NOT EXISTS idx_query_feedback_repository ON query_feedback(repository_id, rating);
@@ -0,0 +1,4 @@
+-- Disk space used by each repository's clone, vector index and wiki cache
+-- JSON object, NULL until the repository is first measured
+
+ALTER TABLE repositories ADD COLUMN disk_usage TEXT;
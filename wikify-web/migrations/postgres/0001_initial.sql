@@ -0,0 +1,106 @@
+-- Tables of a PostgreSQL database
+-- The web server and the repository storage share these tables; timestamps
+-- are RFC 3339 text, as in SQLite
+
+CREATE TABLE repositories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    repo_path TEXT NOT NULL,
+    repo_type TEXT NOT NULL,
+    status TEXT DEFAULT 'created',
+    created_at TEXT,
+    last_indexed_at TEXT,
+    metadata TEXT NOT NULL DEFAULT '{}', -- JSON object
+    owner_id TEXT,
+    organization_id TEXT,
+    visibility TEXT NOT NULL DEFAULT 'private',
+    share_grants TEXT NOT NULL DEFAULT '[]' -- JSON array
+);
+
+CREATE INDEX idx_repositories_status ON repositories(status);
+CREATE INDEX idx_repositories_created ON repositories(created_at DESC);
+CREATE INDEX idx_repositories_owner ON repositories(owner_id);
+CREATE INDEX idx_repositories_organization ON repositories(organization_id);
+
+CREATE TABLE query_history (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT,
+    question TEXT NOT NULL,
+    answer TEXT NOT NULL,
+    created_at TEXT
+);
+
+CREATE INDEX idx_query_history_repository ON query_history(repository_id, created_at DESC);
+
+-- Feedback on answers, stored next to the query record it rates
+CREATE TABLE query_feedback (
+    id TEXT PRIMARY KEY,
+    query_id TEXT NOT NULL,
+    repository_id TEXT,
+    user_id TEXT,
+    rating TEXT NOT NULL,
+    comment TEXT,
+    wrong_sources TEXT NOT NULL DEFAULT '[]', -- JSON array of file paths
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_query_feedback_query ON query_feedback(query_id);
+CREATE INDEX idx_query_feedback_repository ON query_feedback(repository_id, rating);
+
+CREATE TABLE users (
+    id TEXT PRIMARY KEY,
+    username TEXT UNIQUE NOT NULL,
+    email TEXT UNIQUE NOT NULL,
+    display_name TEXT,
+    password_hash TEXT NOT NULL,
+    permissions TEXT NOT NULL DEFAULT '[]', -- JSON array
+    is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TEXT NOT NULL
+);
+
+-- Generated wikis, one per repository
+CREATE TABLE wikis (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    description TEXT,
+    content TEXT NOT NULL,
+    format TEXT DEFAULT 'markdown',
+    structure TEXT, -- JSON representation of the full WikiStructure
+    generated_at TEXT,
+    updated_at TEXT,
+    version BIGINT DEFAULT 1,
+    metadata TEXT DEFAULT '{}'
+);
+
+CREATE TABLE wiki_pages (
+    id TEXT PRIMARY KEY,
+    wiki_id TEXT NOT NULL REFERENCES wikis(id) ON DELETE CASCADE,
+    page_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    description TEXT,
+    importance TEXT DEFAULT 'Medium',
+    file_paths TEXT DEFAULT '[]',
+    related_pages TEXT DEFAULT '[]',
+    parent_section TEXT,
+    tags TEXT DEFAULT '[]',
+    reading_time BIGINT DEFAULT 1,
+    generated_at TEXT,
+    source_documents TEXT DEFAULT '[]',
+    UNIQUE (wiki_id, page_id)
+);
+
+CREATE TABLE wiki_sections (
+    id TEXT PRIMARY KEY,
+    wiki_id TEXT NOT NULL REFERENCES wikis(id) ON DELETE CASCADE,
+    section_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT,
+    pages TEXT DEFAULT '[]',
+    subsections TEXT DEFAULT '[]',
+    importance TEXT DEFAULT 'Medium',
+    order_index BIGINT DEFAULT 0,
+    UNIQUE (wiki_id, section_id)
+);
@@ -0,0 +1,36 @@
+-- Tables of the web database
+-- Databases created before migrations were introduced already have these
+-- tables, so they are created only when missing
+
+CREATE TABLE IF NOT EXISTS repositories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    repo_path TEXT NOT NULL,
+    repo_type TEXT NOT NULL,
+    status TEXT DEFAULT 'created',
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+    last_indexed_at TEXT
+);
+
+CREATE TABLE IF NOT EXISTS query_history (
+    id TEXT PRIMARY KEY,
+    repository_id TEXT,
+    question TEXT NOT NULL,
+    answer TEXT NOT NULL,
+    created_at TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Feedback on answers, stored next to the query record it rates
+CREATE TABLE IF NOT EXISTS query_feedback (
+    id TEXT PRIMARY KEY,
+    query_id TEXT NOT NULL,
+    repository_id TEXT,
+    user_id TEXT,
+    rating TEXT NOT NULL,
+    comment TEXT,
+    wrong_sources TEXT NOT NULL DEFAULT '[]', -- JSON array of file paths
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_query_feedback_query ON query_feedback(query_id);
+CREATE INDEX IF NOT EXISTS idx_query_feedback_repository ON query_feedback(repository_id, rating);
@@ -4,20 +4,23 @@ pub mod api_keys;
 pub mod database;
 pub mod handlers;
 pub mod jwt;
+pub mod oauth;
+pub mod oidc;
 pub mod users;
 
 #[cfg(test)]
 mod tests;
 
+use crate::middleware::RequestId;
 use crate::AppState;
 use axum::{
-    extract::{FromRef, FromRequestParts},
+    extract::{ConnectInfo, FromRef, FromRequestParts},
     http::{request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Json, Redirect, Response},
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
-use wikify_applications::{Permission, PermissionContext, UserIdentity};
+use wikify_applications::{Permission, PermissionContext, UserIdentity, UserType};
 
 /// Authenticated user information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +33,15 @@ pub struct User {
     pub permissions: Vec<Permission>,
     /// Whether user is admin
     pub is_admin: bool,
+    /// Organization the request acts in, from the `X-Organization-Id` header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+    /// Client (IP address or fingerprint) an unauthenticated visitor is throttled by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    /// ID of the HTTP request the user was extracted for
+    #[serde(skip)]
+    pub request_id: Option<String>,
 }
 
 impl User {
@@ -41,9 +53,24 @@ impl User {
             display_name,
             permissions,
             is_admin,
+            organization_id: None,
+            client_id: None,
+            request_id: None,
         }
     }
 
+    /// Act within an organization instead of the user's personal space
+    pub fn with_organization(mut self, organization_id: Option<String>) -> Self {
+        self.organization_id = organization_id;
+        self
+    }
+
+    /// Tag the user with the ID of the request being handled
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
     /// Check if user has specific permission
     /// Admin users automatically have all permissions
     pub fn has_permission(&self, permission: &Permission) -> bool {
@@ -51,13 +78,30 @@ impl User {
     }
 
     /// Convert to PermissionContext for application layer
+    ///
+    /// The identity carries the user's own permissions, so restricted mode
+    /// checks them instead of the defaults for registered users.
     pub fn to_permission_context(&self) -> PermissionContext {
-        let identity = UserIdentity::registered(
+        let mut identity = UserIdentity::registered(
             self.id.clone(),
             self.display_name.clone(),
             None, // email not stored in context
         );
-        PermissionContext::user(identity)
+        if self.is_admin {
+            identity.user_type = UserType::Admin;
+        }
+        identity.custom_permissions = Some(self.permissions.iter().cloned().collect());
+        let mut context = PermissionContext::user(identity);
+        if let Some(organization_id) = &self.organization_id {
+            context = context.with_organization(organization_id.clone());
+        }
+        if let Some(client_id) = &self.client_id {
+            context = context.with_client(client_id.clone());
+        }
+        if let Some(request_id) = &self.request_id {
+            context = context.with_request_id(request_id.clone());
+        }
+        context
     }
 }
 
@@ -133,18 +177,32 @@ where
     type Rejection = AuthRedirect;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let _app_state = AppState::from_ref(state);
+        let app_state = AppState::from_ref(state);
+        let organization_id = extract_organization_id(&parts.headers);
+        let request_id = extract_request_id(parts);
 
-        // First try JWT authentication
+        // Use the user already authenticated by the auth middleware
+        if let Some(user) = parts.extensions.get::<User>() {
+            return Ok(user
+                .clone()
+                .with_organization(organization_id)
+                .with_request_id(request_id));
+        }
+
+        // Then try JWT authentication (access tokens only)
         if let Ok(claims) = jwt::Claims::from_request_parts(parts, state).await {
             if let Ok(user) = claims.to_user() {
-                return Ok(user);
+                return Ok(user
+                    .with_organization(organization_id)
+                    .with_request_id(request_id));
             }
         }
 
         // Fallback to header-based authentication (for backward compatibility)
-        if let Some(user) = authenticate_from_headers(&parts.headers).await {
-            Ok(user)
+        if let Some(user) = authenticate_from_headers(&app_state, &parts.headers).await {
+            Ok(user
+                .with_organization(organization_id)
+                .with_request_id(request_id))
         } else {
             // Authentication failed, redirect to login
             Err(AuthRedirect)
@@ -163,31 +221,40 @@ where
     type Rejection = std::convert::Infallible;
 
     async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let _app_state = AppState::from_ref(state);
+        let app_state = AppState::from_ref(state);
+        let request_id = extract_request_id(parts);
 
         // First try JWT authentication
         if let Ok(claims) = jwt::Claims::from_request_parts(parts, state).await {
             if let Ok(user) = claims.to_user() {
-                return Ok(OptionalUser(Some(user)));
+                return Ok(OptionalUser(Some(user.with_request_id(request_id))));
             }
         }
 
         // Fallback to header-based authentication
         Ok(OptionalUser(
-            authenticate_from_headers(&parts.headers).await,
+            authenticate_from_headers(&app_state, &parts.headers)
+                .await
+                .map(|user| user.with_request_id(request_id)),
         ))
     }
 }
 
 /// Authenticate user from request headers
-async fn authenticate_from_headers(headers: &HeaderMap) -> Option<User> {
+async fn authenticate_from_headers(app_state: &AppState, headers: &HeaderMap) -> Option<User> {
     // Check for API key authentication
     if let Some(api_key) = extract_api_key(headers) {
-        if let Some(user) = authenticate_api_key(&api_key).await {
+        if let Some(user) = authenticate_api_key(app_state, &api_key).await {
             return Some(user);
         }
     }
 
+    // The user ID header is trusted blindly, so it is only accepted when
+    // the permission mode does not require authentication
+    if app_state.config.requires_auth() {
+        return None;
+    }
+
     // Check for user ID header (simple auth for development)
     if let Some(user_id) = extract_user_id(headers) {
         return Some(User::new(
@@ -204,17 +271,66 @@ async fn authenticate_from_headers(headers: &HeaderMap) -> Option<User> {
 fn extract_api_key(headers: &HeaderMap) -> Option<String> {
     headers
         .get("x-api-key")
-        .or_else(|| headers.get("authorization"))
         .and_then(|value| value.to_str().ok())
-        .and_then(|auth| {
-            if auth.starts_with("Bearer ") {
-                Some(auth[7..].to_string())
-            } else {
-                Some(auth.to_string())
-            }
+        .map(|key| key.to_string())
+        .or_else(|| {
+            let auth = headers.get("authorization")?.to_str().ok()?;
+            auth.strip_prefix("ApiKey ")
+                .or_else(|| auth.strip_prefix("Bearer "))
+                .map(|key| key.to_string())
         })
 }
 
+/// ID the request ID middleware assigned to a request
+fn extract_request_id(parts: &Parts) -> Option<String> {
+    parts
+        .extensions
+        .get::<RequestId>()
+        .map(|request_id| request_id.0.clone())
+}
+
+/// Extract the organization a request acts in from headers
+fn extract_organization_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-organization-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| id.to_string())
+}
+
+/// Identify the client a request came from, for throttling anonymous visitors
+///
+/// Forwarded headers are only trusted when configured, since clients can set
+/// them freely; otherwise the peer address is used. A browser fingerprint in
+/// `X-Client-Fingerprint` is the fallback when no address is available.
+fn extract_client_id(parts: &Parts, trust_proxy_headers: bool) -> Option<String> {
+    let header = |name: &str| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+
+    if trust_proxy_headers {
+        let forwarded = header("x-forwarded-for")
+            .and_then(|value| value.split(',').next())
+            .map(str::trim)
+            .or_else(|| header("x-real-ip"));
+        if let Some(ip) = forwarded {
+            return Some(format!("ip:{}", ip));
+        }
+    }
+
+    if let Some(ConnectInfo(addr)) = parts.extensions.get::<ConnectInfo<std::net::SocketAddr>>() {
+        return Some(format!("ip:{}", addr.ip()));
+    }
+
+    header("x-client-fingerprint").map(|fingerprint| format!("fp:{}", fingerprint))
+}
+
 /// Extract user ID from headers (simple development auth)
 fn extract_user_id(headers: &HeaderMap) -> Option<String> {
     headers
@@ -223,27 +339,28 @@ fn extract_user_id(headers: &HeaderMap) -> Option<String> {
         .map(|s| s.to_string())
 }
 
-/// Authenticate using API key (placeholder implementation)
-async fn authenticate_api_key(api_key: &str) -> Option<User> {
+/// Authenticate using an API key issued by the API key service
+async fn authenticate_api_key(app_state: &AppState, api_key: &str) -> Option<User> {
     debug!(
         "Authenticating with API key: {}...",
         &api_key[..api_key.len().min(8)]
     );
 
-    // TODO: Implement actual API key validation
-    // For now, accept any non-empty API key
-    if !api_key.is_empty() {
-        Some(User::new(
-            format!("api_user_{}", &api_key[..api_key.len().min(8)]),
-            Some("API User".to_string()),
-            vec![
-                Permission::Query,
-                Permission::GenerateWiki,
-                Permission::Admin,
-            ],
-        ))
-    } else {
-        None
+    match app_state
+        .api_key_service
+        .authenticate_api_key(api_key)
+        .await
+    {
+        Ok(Some(user_data)) => Some(User::new(
+            user_data.id,
+            user_data.display_name,
+            user_data.permissions,
+        )),
+        Ok(None) => None,
+        Err(e) => {
+            debug!("API key authentication failed: {}", e);
+            None
+        }
     }
 }
 
@@ -310,7 +427,8 @@ where
         } else {
             // Anonymous users have query permission by default
             // Create a temporary user for this request
-            let anonymous_user = User::new("anonymous".to_string(), None, vec![Permission::Query]);
+            let anonymous_user = User::new("anonymous".to_string(), None, vec![Permission::Query])
+                .with_request_id(extract_request_id(parts));
             Ok(RequireQuery(anonymous_user))
         }
     }
@@ -416,7 +534,7 @@ where
 ///
 /// 根据当前的认证模式决定是否需要认证：
 /// - Open模式：允许匿名访问，创建临时匿名用户
-/// - Private/Enterprise模式：需要认证用户
+/// - Restricted/Private/Enterprise模式：需要认证用户
 pub struct ModeAwareUser(pub User);
 
 impl<S> FromRequestParts<S> for ModeAwareUser
@@ -438,13 +556,24 @@ where
             .unwrap_or(&"open".to_string())
             .clone();
 
+        // 请求所在的组织（X-Organization-Id 头）
+        let organization_id = extract_organization_id(&parts.headers);
+        let request_id = extract_request_id(parts);
+
         // 首先尝试从中间件中获取已认证的用户
         if let Some(user) = parts.extensions.get::<User>() {
-            return Ok(ModeAwareUser(user.clone()));
+            return Ok(ModeAwareUser(
+                user.clone()
+                    .with_organization(organization_id)
+                    .with_request_id(request_id),
+            ));
         }
 
         match permission_mode.as_str() {
             "open" => {
+                // 未认证的访客按客户端（IP 或指纹）限流
+                let client_id = extract_client_id(parts, app_state.config.trust_proxy_headers);
+
                 // Open模式：如果没有认证用户，创建匿名用户
                 if let Some(user_context) = parts.extensions.get::<crate::middleware::UserContext>()
                 {
@@ -459,6 +588,9 @@ where
                                 Permission::ManageRepository,
                             ],
                             is_admin: false,
+                            organization_id: organization_id.clone(),
+                            client_id: client_id.clone(),
+                            request_id: request_id.clone(),
                         };
                         Ok(ModeAwareUser(anonymous_user))
                     } else {
@@ -472,6 +604,9 @@ where
                                 Permission::ManageRepository,
                             ],
                             is_admin: false,
+                            organization_id: organization_id.clone(),
+                            client_id: client_id.clone(),
+                            request_id: request_id.clone(),
                         };
                         Ok(ModeAwareUser(user))
                     }
@@ -486,12 +621,15 @@ where
                             Permission::ManageRepository,
                         ],
                         is_admin: false,
+                        organization_id: organization_id.clone(),
+                        client_id: client_id.clone(),
+                        request_id: request_id.clone(),
                     };
                     Ok(ModeAwareUser(anonymous_user))
                 }
             }
-            "private" | "enterprise" => {
-                // Private/Enterprise模式：必须有认证用户
+            "restricted" | "private" | "enterprise" => {
+                // Restricted/Private/Enterprise模式：必须有认证用户
                 Err((StatusCode::UNAUTHORIZED, "Authentication required").into_response())
             }
             _ => {
@@ -13,6 +13,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
 /// API Key data structure
@@ -39,18 +40,20 @@ pub struct ApiKey {
 }
 
 /// API Key creation request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateApiKeyRequest {
     /// Human-readable name for the API key
+    #[schema(example = "CI pipeline")]
     pub name: String,
     /// Permissions to grant to this API key
+    #[schema(value_type = Vec<String>, example = json!(["Query"]))]
     pub permissions: Vec<Permission>,
     /// Optional expiration time (in days from now)
     pub expires_in_days: Option<u32>,
 }
 
 /// API Key response (includes the raw key only on creation)
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiKeyResponse {
     /// API key ID
     pub id: String,
@@ -59,6 +62,7 @@ pub struct ApiKeyResponse {
     /// Human-readable name
     pub name: String,
     /// Permissions
+    #[schema(value_type = Vec<String>, example = json!(["Query"]))]
     pub permissions: Vec<Permission>,
     /// Creation time
     pub created_at: DateTime<Utc>,
@@ -4,10 +4,10 @@ use super::{
     jwt::AuthError,
     users::{UserData, UserStore},
 };
-use crate::simple_database::SimpleDatabaseService;
+use crate::simple_database::{with_pool, DatabasePool, SimpleDatabaseService};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
+use sqlx::Row;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -86,14 +86,14 @@ impl UserRecord {
 /// Database-backed user store
 #[derive(Debug, Clone)]
 pub struct DatabaseUserStore {
-    pool: SqlitePool,
+    pool: DatabasePool,
     // Cache for frequently accessed users
     cache: Arc<RwLock<HashMap<String, UserData>>>,
 }
 
 impl DatabaseUserStore {
     /// Create new database user store
-    pub async fn new(pool: SqlitePool) -> Result<Self, AuthError> {
+    pub async fn new(pool: DatabasePool) -> Result<Self, AuthError> {
         let store = Self {
             pool,
             cache: Arc::new(RwLock::new(HashMap::new())),
@@ -109,7 +109,13 @@ impl DatabaseUserStore {
     }
 
     /// Create database tables
+    ///
+    /// PostgreSQL databases get the users table from their migrations.
     async fn create_tables(&self) -> Result<(), AuthError> {
+        let Some(pool) = self.pool.as_sqlite() else {
+            return Ok(());
+        };
+
         let query = r#"
             CREATE TABLE IF NOT EXISTS users (
                 id TEXT PRIMARY KEY,
@@ -126,7 +132,7 @@ impl DatabaseUserStore {
             CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
         "#;
 
-        sqlx::query(query).execute(&self.pool).await.map_err(|e| {
+        sqlx::query(query).execute(pool).await.map_err(|e| {
             error!("Failed to create users table: {}", e);
             AuthError::TokenCreation
         })?;
@@ -138,16 +144,17 @@ impl DatabaseUserStore {
     /// Ensure default admin user exists
     async fn ensure_default_admin(&self) -> Result<(), AuthError> {
         // Check if admin user already exists
-        let existing = sqlx::query("SELECT COUNT(*) as count FROM users WHERE username = ?")
-            .bind("admin")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to check for admin user: {}", e);
-                AuthError::TokenCreation
-            })?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE username = $1")
+                .bind("admin")
+                .fetch_one(pool)
+                .await
+        })
+        .map_err(|e| {
+            error!("Failed to check for admin user: {}", e);
+            AuthError::TokenCreation
+        })?;
 
-        let count: i64 = existing.get("count");
         if count > 0 {
             debug!("Admin user already exists");
             return Ok(());
@@ -174,24 +181,26 @@ impl DatabaseUserStore {
 
         let query = r#"
             INSERT INTO users (id, username, email, display_name, password_hash, permissions, is_admin, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         "#;
 
-        sqlx::query(query)
-            .bind(&record.id)
-            .bind(&record.username)
-            .bind(&record.email)
-            .bind(&record.display_name)
-            .bind(&record.password_hash)
-            .bind(&record.permissions)
-            .bind(record.is_admin)
-            .bind(&record.created_at)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to insert user: {}", e);
-                AuthError::InvalidCredentials
-            })?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(query)
+                .bind(&record.id)
+                .bind(&record.username)
+                .bind(&record.email)
+                .bind(&record.display_name)
+                .bind(&record.password_hash)
+                .bind(&record.permissions)
+                .bind(record.is_admin)
+                .bind(&record.created_at)
+                .execute(pool)
+                .await
+        })
+        .map_err(|e| {
+            error!("Failed to insert user: {}", e);
+            AuthError::InvalidCredentials
+        })?;
 
         // Update cache
         let mut cache = self.cache.write().await;
@@ -201,6 +210,28 @@ impl DatabaseUserStore {
         Ok(())
     }
 
+    /// Load the user record whose `column` (`id` or `username`) has a value
+    async fn fetch_user(
+        &self,
+        column: &'static str,
+        value: &str,
+    ) -> Result<Option<UserRecord>, sqlx::Error> {
+        let query = format!("SELECT * FROM users WHERE {} = $1", column);
+        with_pool!(&self.pool, |pool| {
+            let row = sqlx::query(&query).bind(value).fetch_optional(pool).await?;
+            Ok(row.map(|row| UserRecord {
+                id: row.get("id"),
+                username: row.get("username"),
+                email: row.get("email"),
+                display_name: row.get("display_name"),
+                password_hash: row.get("password_hash"),
+                permissions: row.get("permissions"),
+                is_admin: row.get("is_admin"),
+                created_at: row.get("created_at"),
+            }))
+        })
+    }
+
     /// Get user by username
     pub async fn get_user_by_username(
         &self,
@@ -215,28 +246,12 @@ impl DatabaseUserStore {
         }
 
         // Query database
-        let query = "SELECT * FROM users WHERE username = ?";
-        let row = sqlx::query(query)
-            .bind(username)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to query user by username: {}", e);
-                AuthError::TokenCreation
-            })?;
-
-        if let Some(row) = row {
-            let record = UserRecord {
-                id: row.get("id"),
-                username: row.get("username"),
-                email: row.get("email"),
-                display_name: row.get("display_name"),
-                password_hash: row.get("password_hash"),
-                permissions: row.get("permissions"),
-                is_admin: row.get("is_admin"),
-                created_at: row.get("created_at"),
-            };
+        let record = self.fetch_user("username", username).await.map_err(|e| {
+            error!("Failed to query user by username: {}", e);
+            AuthError::TokenCreation
+        })?;
 
+        if let Some(record) = record {
             let user_data = record.to_user_data()?;
 
             // Update cache
@@ -251,28 +266,12 @@ impl DatabaseUserStore {
 
     /// Get user by ID
     pub async fn get_user_by_id(&self, user_id: &str) -> Result<Option<UserData>, AuthError> {
-        let query = "SELECT * FROM users WHERE id = ?";
-        let row = sqlx::query(query)
-            .bind(user_id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to query user by ID: {}", e);
-                AuthError::TokenCreation
-            })?;
-
-        if let Some(row) = row {
-            let record = UserRecord {
-                id: row.get("id"),
-                username: row.get("username"),
-                email: row.get("email"),
-                display_name: row.get("display_name"),
-                password_hash: row.get("password_hash"),
-                permissions: row.get("permissions"),
-                is_admin: row.get("is_admin"),
-                created_at: row.get("created_at"),
-            };
+        let record = self.fetch_user("id", user_id).await.map_err(|e| {
+            error!("Failed to query user by ID: {}", e);
+            AuthError::TokenCreation
+        })?;
 
+        if let Some(record) = record {
             let user_data = record.to_user_data()?;
             Ok(Some(user_data))
         } else {
@@ -282,33 +281,33 @@ impl DatabaseUserStore {
 
     /// Check if username exists
     pub async fn username_exists(&self, username: &str) -> Result<bool, AuthError> {
-        let query = "SELECT COUNT(*) as count FROM users WHERE username = ?";
-        let row = sqlx::query(query)
-            .bind(username)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to check username existence: {}", e);
-                AuthError::TokenCreation
-            })?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE username = $1")
+                .bind(username)
+                .fetch_one(pool)
+                .await
+        })
+        .map_err(|e| {
+            error!("Failed to check username existence: {}", e);
+            AuthError::TokenCreation
+        })?;
 
-        let count: i64 = row.get("count");
         Ok(count > 0)
     }
 
     /// Check if email exists
     pub async fn email_exists(&self, email: &str) -> Result<bool, AuthError> {
-        let query = "SELECT COUNT(*) as count FROM users WHERE email = ?";
-        let row = sqlx::query(query)
-            .bind(email)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to check email existence: {}", e);
-                AuthError::TokenCreation
-            })?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE email = $1")
+                .bind(email)
+                .fetch_one(pool)
+                .await
+        })
+        .map_err(|e| {
+            error!("Failed to check email existence: {}", e);
+            AuthError::TokenCreation
+        })?;
 
-        let count: i64 = row.get("count");
         Ok(count > 0)
     }
 
@@ -316,11 +315,11 @@ impl DatabaseUserStore {
     pub async fn register_user(&self, user: UserData) -> Result<UserData, AuthError> {
         // Check if username or email already exists
         if self.username_exists(&user.username).await? {
-            return Err(AuthError::InvalidCredentials);
+            return Err(AuthError::UserExists);
         }
 
         if self.email_exists(&user.email).await? {
-            return Err(AuthError::InvalidCredentials);
+            return Err(AuthError::UserExists);
         }
 
         // Insert user
@@ -342,16 +341,17 @@ impl DatabaseUserStore {
         )
         .map_err(|_| AuthError::InvalidPermissions)?;
 
-        let query = "UPDATE users SET permissions = ? WHERE id = ?";
-        sqlx::query(query)
-            .bind(&permissions_json)
-            .bind(user_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to update user permissions: {}", e);
-                AuthError::TokenCreation
-            })?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("UPDATE users SET permissions = $1 WHERE id = $2")
+                .bind(&permissions_json)
+                .bind(user_id)
+                .execute(pool)
+                .await
+        })
+        .map_err(|e| {
+            error!("Failed to update user permissions: {}", e);
+            AuthError::TokenCreation
+        })?;
 
         // Clear cache for this user
         let mut cache = self.cache.write().await;
@@ -361,28 +361,66 @@ impl DatabaseUserStore {
         Ok(())
     }
 
+    /// Replace a user's permissions and admin flag
+    pub async fn update_user_access(
+        &self,
+        user_id: &str,
+        permissions: Vec<Permission>,
+        is_admin: bool,
+    ) -> Result<(), AuthError> {
+        let permissions_json = serde_json::to_string(
+            &permissions
+                .iter()
+                .map(|p| format!("{:?}", p))
+                .collect::<Vec<_>>(),
+        )
+        .map_err(|_| AuthError::InvalidPermissions)?;
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("UPDATE users SET permissions = $1, is_admin = $2 WHERE id = $3")
+                .bind(&permissions_json)
+                .bind(is_admin)
+                .bind(user_id)
+                .execute(pool)
+                .await
+        })
+        .map_err(|e| AuthError::DatabaseError(format!("Failed to update user access: {}", e)))?;
+
+        // Clear cache for this user
+        let mut cache = self.cache.write().await;
+        cache.retain(|_, user| user.id != user_id);
+
+        debug!("Updated access for user: {}", user_id);
+        Ok(())
+    }
+
     /// Get user statistics
     pub async fn get_user_stats(&self) -> Result<UserStats, AuthError> {
+        // created_at holds RFC 3339 timestamps, which sort as text
         let query = r#"
-            SELECT 
+            SELECT
                 COUNT(*) as total_users,
-                COUNT(CASE WHEN is_admin = 1 THEN 1 END) as admin_users,
-                COUNT(CASE WHEN created_at > datetime('now', '-7 days') THEN 1 END) as recent_users
+                COUNT(CASE WHEN is_admin THEN 1 END) as admin_users,
+                COUNT(CASE WHEN created_at > $1 THEN 1 END) as recent_users
             FROM users
         "#;
-
-        let row = sqlx::query(query)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                error!("Failed to get user statistics: {}", e);
-                AuthError::TokenCreation
-            })?;
-
-        Ok(UserStats {
-            total_users: row.get::<i64, _>("total_users") as u64,
-            admin_users: row.get::<i64, _>("admin_users") as u64,
-            recent_users: row.get::<i64, _>("recent_users") as u64,
+        let recent_since = (Utc::now() - chrono::Duration::days(7)).to_rfc3339();
+
+        with_pool!(&self.pool, |pool| {
+            let row = sqlx::query(query)
+                .bind(&recent_since)
+                .fetch_one(pool)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get user statistics: {}", e);
+                    AuthError::TokenCreation
+                })?;
+
+            Ok(UserStats {
+                total_users: row.get::<i64, _>("total_users") as u64,
+                admin_users: row.get::<i64, _>("admin_users") as u64,
+                recent_users: row.get::<i64, _>("recent_users") as u64,
+            })
         })
     }
 }
@@ -413,6 +451,9 @@ pub struct WikiRecord {
 
 impl SimpleDatabaseService {
     /// Store wiki in database
+    ///
+    /// A repository has one wiki, so an earlier wiki of the repository is
+    /// replaced along with its pages and sections.
     pub async fn store_wiki(
         &self,
         repository_id: &str,
@@ -425,98 +466,110 @@ impl SimpleDatabaseService {
             AuthError::DatabaseError("Failed to serialize wiki structure".to_string())
         })?;
 
-        // Store main wiki record
-        sqlx::query(
-            r#"
-            INSERT OR REPLACE INTO wikis (
-                id, repository_id, title, description, content, format,
-                structure, generated_at, updated_at, version, metadata
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1, '{}')
-            "#,
-        )
-        .bind(&wiki_id)
-        .bind(repository_id)
-        .bind(&wiki_structure.title)
-        .bind(&wiki_structure.description)
-        .bind(content)
-        .bind("markdown")
-        .bind(&structure_json)
-        .bind(&now)
-        .bind(&now)
-        .execute(self.pool())
-        .await
-        .map_err(|e| AuthError::DatabaseError(format!("Failed to store wiki: {}", e)))?;
-
-        // Store individual pages
-        for page in &wiki_structure.pages {
-            let page_db_id = uuid::Uuid::new_v4().to_string();
-            let file_paths_json =
-                serde_json::to_string(&page.file_paths).unwrap_or_else(|_| "[]".to_string());
-            let related_pages_json =
-                serde_json::to_string(&page.related_pages).unwrap_or_else(|_| "[]".to_string());
-            let tags_json = serde_json::to_string(&page.tags).unwrap_or_else(|_| "[]".to_string());
-            let source_docs_json =
-                serde_json::to_string(&page.source_documents).unwrap_or_else(|_| "[]".to_string());
-
+        with_pool!(self.pool(), |pool| {
+            // Store main wiki record
+            sqlx::query("DELETE FROM wikis WHERE repository_id = $1")
+                .bind(repository_id)
+                .execute(pool)
+                .await
+                .map_err(|e| AuthError::DatabaseError(format!("Failed to store wiki: {}", e)))?;
             sqlx::query(
                 r#"
-                INSERT INTO wiki_pages (
-                    id, wiki_id, page_id, title, content, description, importance,
-                    file_paths, related_pages, parent_section, tags, reading_time,
-                    generated_at, source_documents
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                INSERT INTO wikis (
+                    id, repository_id, title, description, content, format,
+                    structure, generated_at, updated_at, version, metadata
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 1, '{}')
                 "#,
             )
-            .bind(&page_db_id)
             .bind(&wiki_id)
-            .bind(&page.id)
-            .bind(&page.title)
-            .bind(&page.content)
-            .bind(&page.description)
-            .bind(format!("{:?}", page.importance))
-            .bind(&file_paths_json)
-            .bind(&related_pages_json)
-            .bind(&page.parent_section)
-            .bind(&tags_json)
-            .bind(page.reading_time as i64)
-            .bind(page.generated_at.to_rfc3339())
-            .bind(&source_docs_json)
-            .execute(self.pool())
+            .bind(repository_id)
+            .bind(&wiki_structure.title)
+            .bind(&wiki_structure.description)
+            .bind(content)
+            .bind("markdown")
+            .bind(&structure_json)
+            .bind(&now)
+            .bind(&now)
+            .execute(pool)
             .await
-            .map_err(|e| AuthError::DatabaseError(format!("Failed to store wiki page: {}", e)))?;
-        }
+            .map_err(|e| AuthError::DatabaseError(format!("Failed to store wiki: {}", e)))?;
+
+            // Store individual pages
+            for page in &wiki_structure.pages {
+                let page_db_id = uuid::Uuid::new_v4().to_string();
+                let file_paths_json =
+                    serde_json::to_string(&page.file_paths).unwrap_or_else(|_| "[]".to_string());
+                let related_pages_json =
+                    serde_json::to_string(&page.related_pages).unwrap_or_else(|_| "[]".to_string());
+                let tags_json =
+                    serde_json::to_string(&page.tags).unwrap_or_else(|_| "[]".to_string());
+                let source_docs_json = serde_json::to_string(&page.source_documents)
+                    .unwrap_or_else(|_| "[]".to_string());
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO wiki_pages (
+                        id, wiki_id, page_id, title, content, description, importance,
+                        file_paths, related_pages, parent_section, tags, reading_time,
+                        generated_at, source_documents
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                    "#,
+                )
+                .bind(&page_db_id)
+                .bind(&wiki_id)
+                .bind(&page.id)
+                .bind(&page.title)
+                .bind(&page.content)
+                .bind(&page.description)
+                .bind(format!("{:?}", page.importance))
+                .bind(&file_paths_json)
+                .bind(&related_pages_json)
+                .bind(&page.parent_section)
+                .bind(&tags_json)
+                .bind(page.reading_time as i64)
+                .bind(page.generated_at.to_rfc3339())
+                .bind(&source_docs_json)
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    AuthError::DatabaseError(format!("Failed to store wiki page: {}", e))
+                })?;
+            }
 
-        // Store sections
-        for section in &wiki_structure.sections {
-            let section_db_id = uuid::Uuid::new_v4().to_string();
-            let pages_json =
-                serde_json::to_string(&section.pages).unwrap_or_else(|_| "[]".to_string());
-            let subsections_json =
-                serde_json::to_string(&section.subsections).unwrap_or_else(|_| "[]".to_string());
+            // Store sections
+            for section in &wiki_structure.sections {
+                let section_db_id = uuid::Uuid::new_v4().to_string();
+                let pages_json =
+                    serde_json::to_string(&section.pages).unwrap_or_else(|_| "[]".to_string());
+                let subsections_json = serde_json::to_string(&section.subsections)
+                    .unwrap_or_else(|_| "[]".to_string());
+
+                sqlx::query(
+                    r#"
+                    INSERT INTO wiki_sections (
+                        id, wiki_id, section_id, title, description, pages,
+                        subsections, importance, order_index
+                    ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                    "#,
+                )
+                .bind(&section_db_id)
+                .bind(&wiki_id)
+                .bind(&section.id)
+                .bind(&section.title)
+                .bind(&section.description)
+                .bind(&pages_json)
+                .bind(&subsections_json)
+                .bind("Medium") // Default importance since WikiSection doesn't have this field
+                .bind(section.order as i64)
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    AuthError::DatabaseError(format!("Failed to store wiki section: {}", e))
+                })?;
+            }
 
-            sqlx::query(
-                r#"
-                INSERT INTO wiki_sections (
-                    id, wiki_id, section_id, title, description, pages,
-                    subsections, importance, order_index
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-            )
-            .bind(&section_db_id)
-            .bind(&wiki_id)
-            .bind(&section.id)
-            .bind(&section.title)
-            .bind(&section.description)
-            .bind(&pages_json)
-            .bind(&subsections_json)
-            .bind("Medium") // Default importance since WikiSection doesn't have this field
-            .bind(section.order as i64)
-            .execute(self.pool())
-            .await
-            .map_err(|e| {
-                AuthError::DatabaseError(format!("Failed to store wiki section: {}", e))
-            })?;
-        }
+            Ok::<_, AuthError>(())
+        })?;
 
         info!("Stored wiki {} for repository {}", wiki_id, repository_id);
         Ok(wiki_id)
@@ -527,16 +580,16 @@ impl SimpleDatabaseService {
         &self,
         repository_id: &str,
     ) -> Result<Option<WikiRecord>, AuthError> {
-        let row = sqlx::query(
-            "SELECT id, repository_id, title, description, content, format, structure, generated_at, updated_at, version, metadata FROM wikis WHERE repository_id = ? ORDER BY updated_at DESC LIMIT 1"
-        )
-        .bind(repository_id)
-        .fetch_optional(self.pool())
-        .await
-        .map_err(|e| AuthError::DatabaseError(format!("Failed to get wiki: {}", e)))?;
+        with_pool!(self.pool(), |pool| {
+            let row = sqlx::query(
+                "SELECT id, repository_id, title, description, content, format, structure, generated_at, updated_at, version, metadata FROM wikis WHERE repository_id = $1 ORDER BY updated_at DESC LIMIT 1"
+            )
+            .bind(repository_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| AuthError::DatabaseError(format!("Failed to get wiki: {}", e)))?;
 
-        if let Some(row) = row {
-            let record = WikiRecord {
+            Ok(row.map(|row| WikiRecord {
                 id: row.get("id"),
                 repository_id: row.get("repository_id"),
                 title: row.get("title"),
@@ -548,21 +601,21 @@ impl SimpleDatabaseService {
                 updated_at: row.get("updated_at"),
                 version: row.get("version"),
                 metadata: row.get("metadata"),
-            };
-            Ok(Some(record))
-        } else {
-            Ok(None)
-        }
+            }))
+        })
     }
 
     /// Delete wiki by repository ID
     pub async fn delete_wiki_by_repository(&self, repository_id: &str) -> Result<bool, AuthError> {
-        let result = sqlx::query("DELETE FROM wikis WHERE repository_id = ?")
-            .bind(repository_id)
-            .execute(self.pool())
-            .await
-            .map_err(|e| AuthError::DatabaseError(format!("Failed to delete wiki: {}", e)))?;
+        let result = with_pool!(self.pool(), |pool| {
+            sqlx::query("DELETE FROM wikis WHERE repository_id = $1")
+                .bind(repository_id)
+                .execute(pool)
+                .await
+                .map(|result| result.rows_affected())
+        })
+        .map_err(|e| AuthError::DatabaseError(format!("Failed to delete wiki: {}", e)))?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(result > 0)
     }
 }
@@ -1,15 +1,21 @@
 //! Authentication handlers for user registration, login, and token management
 
 use super::{
-    api_keys::CreateApiKeyRequest,
-    jwt::AuthError,
+    api_keys::{ApiKeyResponse, CreateApiKeyRequest},
+    jwt::{AuthError, TokenPair},
+    oauth::{OAuthProvider, OAuthProvidersResponse},
     users::{AuthResponse, LoginRequest, RefreshRequest, RegisterRequest},
     User,
 };
-use crate::AppState;
-use axum::{extract::State, http::StatusCode, response::Json, Json as JsonExtractor};
-use serde::Serialize;
-use serde_json::{json, Value};
+use crate::{handlers::types::MessageResponse, AppState};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Redirect, Response},
+    Json as JsonExtractor,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tracing::info;
 use utoipa::ToSchema;
 
@@ -39,25 +45,17 @@ pub async fn get_auth_status(
         .unwrap_or(&"open".to_string())
         .clone();
 
-    // Determine if authentication is required
-    let auth_required = match permission_mode.as_str() {
-        "open" => false,
-        "private" | "enterprise" => true,
-        _ => false,
-    };
-
-    // Registration is enabled for private and enterprise modes
-    let registration_enabled = match permission_mode.as_str() {
-        "private" | "enterprise" => true,
-        _ => false,
-    };
+    // Authentication is required (and registration enabled) in restricted mode
+    // and its private/enterprise aliases
+    let auth_required = app_state.config.requires_auth();
+    let registration_enabled = auth_required;
 
     let response = AuthStatusResponse {
         auth_mode: permission_mode,
         auth_required,
         registration_enabled,
         features: AuthFeatures {
-            research_engine: true, // Always available
+            research_engine: cfg!(feature = "research"),
             wiki_generation: true, // Always available
             multi_language: true,  // Always available
         },
@@ -69,7 +67,7 @@ pub async fn get_auth_status(
 /// Authentication status response
 #[derive(Debug, Serialize, ToSchema)]
 pub struct AuthStatusResponse {
-    /// Authentication mode: "open", "private", or "enterprise"
+    /// Authentication mode: "open", "restricted", "private", or "enterprise"
     #[schema(example = "open")]
     pub auth_mode: String,
     /// Whether authentication is required
@@ -168,7 +166,7 @@ pub async fn login_user(
     description = "Refresh access token using a valid refresh token",
     request_body = RefreshRequest,
     responses(
-        (status = 200, description = "Token refreshed successfully", body = AuthResponse),
+        (status = 200, description = "Token refreshed successfully", body = TokenPair),
         (status = 400, description = "Invalid request data"),
         (status = 401, description = "Invalid or expired refresh token"),
         (status = 500, description = "Internal server error")
@@ -177,28 +175,327 @@ pub async fn login_user(
 pub async fn refresh_token(
     State(app_state): State<AppState>,
     JsonExtractor(request): JsonExtractor<RefreshRequest>,
-) -> Result<Json<Value>, AuthError> {
+) -> Result<Json<TokenPair>, AuthError> {
     info!("Token refresh attempt");
 
     let tokens = app_state.user_service.refresh_token(request).await?;
 
     info!("Token refreshed successfully");
-    Ok(Json(json!(tokens)))
+    Ok(Json(tokens))
+}
+
+/// List configured OAuth providers
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/providers",
+    tag = "Authentication",
+    summary = "List OAuth providers",
+    description = "List the providers users can sign in with",
+    responses(
+        (status = 200, description = "Configured providers", body = OAuthProvidersResponse)
+    )
+)]
+pub async fn list_oauth_providers(
+    State(app_state): State<AppState>,
+) -> Json<OAuthProvidersResponse> {
+    Json(OAuthProvidersResponse {
+        providers: app_state.oauth_service.providers(),
+        oidc: app_state.oidc_service.display_name(),
+    })
+}
+
+/// Query parameters for starting an OAuth login
+#[derive(Debug, Deserialize)]
+pub struct OAuthLoginQuery {
+    /// Also allow Wikify to clone the user's private repositories
+    #[serde(default)]
+    pub repo_access: bool,
+    /// Path to redirect to after login, with the tokens in the URL fragment
+    pub redirect: Option<String>,
+}
+
+/// Start an OAuth login
+///
+/// Redirects the browser to the provider's consent page.
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/{provider}",
+    tag = "Authentication",
+    summary = "Start OAuth login",
+    description = "Redirect to GitHub or GitLab to sign in. With `repo_access=true` the user is also asked to grant read access to their private repositories, which is then used when indexing them.",
+    params(
+        ("provider" = String, Path, description = "Provider name: github or gitlab"),
+        ("repo_access" = Option<bool>, Query, description = "Request access to private repositories"),
+        ("redirect" = Option<String>, Query, description = "Path to return to after login")
+    ),
+    responses(
+        (status = 303, description = "Redirect to the provider"),
+        (status = 404, description = "Provider not configured")
+    )
+)]
+pub async fn start_oauth_login(
+    State(app_state): State<AppState>,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthLoginQuery>,
+) -> Result<Redirect, AuthError> {
+    let provider = OAuthProvider::parse(&provider).ok_or(AuthError::OAuthProviderNotConfigured)?;
+    let url = app_state
+        .oauth_service
+        .authorize_url(provider, query.repo_access, query.redirect)
+        .await?;
+    Ok(Redirect::to(&url))
+}
+
+/// Query parameters of the provider callback
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: Option<String>,
+    pub state: String,
+    /// Set when the user declined the consent screen
+    pub error: Option<String>,
+}
+
+/// OAuth callback
+///
+/// Completes the login started by `start_oauth_login`. Returns the same
+/// response as `/api/auth/login`, or redirects to the requested path with the
+/// tokens in the URL fragment.
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/{provider}/callback",
+    tag = "Authentication",
+    summary = "OAuth callback",
+    description = "Exchange the authorization code for Wikify tokens. The user account is created on first login.",
+    params(
+        ("provider" = String, Path, description = "Provider name: github or gitlab"),
+        ("code" = Option<String>, Query, description = "Authorization code"),
+        ("state" = String, Query, description = "State issued when the login started")
+    ),
+    responses(
+        (status = 200, description = "Login successful", body = AuthResponse),
+        (status = 303, description = "Redirect to the requested path with tokens in the fragment"),
+        (status = 400, description = "Invalid or expired state"),
+        (status = 401, description = "The user declined the login"),
+        (status = 502, description = "The provider rejected the code")
+    )
+)]
+pub async fn oauth_callback(
+    State(app_state): State<AppState>,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> Result<Response, AuthError> {
+    let provider = OAuthProvider::parse(&provider).ok_or(AuthError::OAuthProviderNotConfigured)?;
+    let code = match (query.code, query.error) {
+        (Some(code), None) => code,
+        (_, error) => {
+            info!("OAuth login declined: {:?}", error);
+            return Err(AuthError::InvalidCredentials);
+        }
+    };
+
+    let identity = app_state
+        .oauth_service
+        .exchange_code(provider, &code, &query.state)
+        .await?;
+    let response = app_state.user_service.login_oauth(&identity).await?;
+    if identity.repo_access {
+        app_state
+            .oauth_service
+            .link_token(&response.user.id, &identity)
+            .await;
+    }
+
+    match &identity.redirect {
+        Some(redirect) => Ok(Redirect::to(&format!(
+            "{}#access_token={}&refresh_token={}&expires_in={}",
+            redirect,
+            response.tokens.access_token,
+            response.tokens.refresh_token,
+            response.tokens.expires_in
+        ))
+        .into_response()),
+        None => Ok(Json(response).into_response()),
+    }
+}
+
+/// Query parameters for starting a single sign-on login
+#[derive(Debug, Deserialize)]
+pub struct OidcLoginQuery {
+    /// Path to redirect to after login, with the tokens in the URL fragment
+    pub redirect: Option<String>,
+}
+
+/// Start a single sign-on login
+///
+/// Redirects the browser to the OIDC identity provider.
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc",
+    tag = "Authentication",
+    summary = "Start SSO login",
+    description = "Redirect to the configured OpenID Connect identity provider (Okta, Keycloak, Entra ID, ...) to sign in",
+    params(
+        ("redirect" = Option<String>, Query, description = "Path to return to after login")
+    ),
+    responses(
+        (status = 303, description = "Redirect to the identity provider"),
+        (status = 404, description = "Single sign-on not configured"),
+        (status = 502, description = "The identity provider could not be discovered")
+    )
+)]
+pub async fn start_oidc_login(
+    State(app_state): State<AppState>,
+    Query(query): Query<OidcLoginQuery>,
+) -> Result<Redirect, AuthError> {
+    let url = app_state.oidc_service.authorize_url(query.redirect).await?;
+    Ok(Redirect::to(&url))
+}
+
+/// Single sign-on callback
+///
+/// Completes the login started by `start_oidc_login`. Returns the same
+/// response as `/api/auth/login`, or redirects to the requested path with the
+/// tokens in the URL fragment.
+#[utoipa::path(
+    get,
+    path = "/api/auth/oidc/callback",
+    tag = "Authentication",
+    summary = "SSO callback",
+    description = "Validate the ID token and exchange it for Wikify tokens. The user account is created on first login, and its role follows the user's groups on every login.",
+    params(
+        ("code" = Option<String>, Query, description = "Authorization code"),
+        ("state" = String, Query, description = "State issued when the login started")
+    ),
+    responses(
+        (status = 200, description = "Login successful", body = AuthResponse),
+        (status = 303, description = "Redirect to the requested path with tokens in the fragment"),
+        (status = 400, description = "Invalid or expired state"),
+        (status = 401, description = "The user declined the login or the ID token is invalid"),
+        (status = 403, description = "The user is in no group that may use Wikify"),
+        (status = 502, description = "The identity provider rejected the code")
+    )
+)]
+pub async fn oidc_callback(
+    State(app_state): State<AppState>,
+    Query(query): Query<OAuthCallbackQuery>,
+) -> Result<Response, AuthError> {
+    let code = match (query.code, query.error) {
+        (Some(code), None) => code,
+        (_, error) => {
+            info!("SSO login declined: {:?}", error);
+            return Err(AuthError::InvalidCredentials);
+        }
+    };
+
+    let identity = app_state
+        .oidc_service
+        .exchange_code(&code, &query.state)
+        .await?;
+    let response = app_state.user_service.login_oidc(&identity).await?;
+    // The role may have changed in the identity provider
+    app_state
+        .application
+        .invalidate_permissions(Some(&response.user.id))
+        .await;
+
+    match &identity.redirect {
+        Some(redirect) => Ok(Redirect::to(&format!(
+            "{}#access_token={}&refresh_token={}&expires_in={}",
+            redirect,
+            response.tokens.access_token,
+            response.tokens.refresh_token,
+            response.tokens.expires_in
+        ))
+        .into_response()),
+        None => Ok(Json(response).into_response()),
+    }
+}
+
+/// Stop using the user's provider token for repository access
+#[utoipa::path(
+    delete,
+    path = "/api/auth/oauth/{provider}/token",
+    tag = "Authentication",
+    summary = "Revoke repository access",
+    description = "Forget the provider token granted at login, so it is no longer used to clone private repositories",
+    params(
+        ("provider" = String, Path, description = "Provider name: github or gitlab")
+    ),
+    responses(
+        (status = 200, description = "Token forgotten", body = MessageResponse),
+        (status = 404, description = "No token linked for this provider")
+    )
+)]
+pub async fn revoke_oauth_token(
+    State(app_state): State<AppState>,
+    user: User,
+    Path(provider): Path<String>,
+) -> Result<Json<MessageResponse>, StatusCode> {
+    let provider = OAuthProvider::parse(&provider).ok_or(StatusCode::NOT_FOUND)?;
+    if !app_state
+        .oauth_service
+        .unlink_token(&user.id, provider)
+        .await
+    {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!(
+        "Revoked {} repository access for user: {}",
+        provider.as_str(),
+        user.id
+    );
+    Ok(Json(MessageResponse::success("Repository access revoked")))
+}
+
+/// Authenticated user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CurrentUserResponse {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub id: String,
+    #[schema(example = "John Doe")]
+    pub display_name: Option<String>,
+    #[schema(example = json!(["Query", "GenerateWiki"]))]
+    pub permissions: Vec<String>,
+    pub is_admin: bool,
+}
+
+/// Logout acknowledgement
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LogoutResponse {
+    #[schema(example = "Logged out successfully")]
+    pub message: String,
+    pub user_id: String,
 }
 
 /// Get current user information
 ///
 /// Returns information about the currently authenticated user.
 /// Requires valid JWT token in Authorization header.
-pub async fn get_current_user(user: User) -> Result<Json<Value>, StatusCode> {
+#[utoipa::path(
+    get,
+    path = "/api/auth/me",
+    tag = "Authentication",
+    summary = "Get current user",
+    description = "Get the user the access token or API key belongs to",
+    responses(
+        (status = 200, description = "Current user", body = CurrentUserResponse),
+        (status = 401, description = "Authentication required")
+    )
+)]
+pub async fn get_current_user(user: User) -> Result<Json<CurrentUserResponse>, StatusCode> {
     info!("Getting current user info: {}", user.id);
 
-    Ok(Json(json!({
-        "id": user.id,
-        "display_name": user.display_name,
-        "permissions": user.permissions.iter().map(|p| format!("{:?}", p)).collect::<Vec<_>>(),
-        "is_admin": user.is_admin,
-    })))
+    Ok(Json(CurrentUserResponse {
+        permissions: user
+            .permissions
+            .iter()
+            .map(|p| format!("{:?}", p))
+            .collect(),
+        id: user.id,
+        display_name: user.display_name,
+        is_admin: user.is_admin,
+    }))
 }
 
 /// Logout endpoint (client-side token invalidation)
@@ -206,13 +503,24 @@ pub async fn get_current_user(user: User) -> Result<Json<Value>, StatusCode> {
 /// This endpoint doesn't actually invalidate tokens server-side (stateless JWT),
 /// but provides a standard logout endpoint for clients to call.
 /// Clients should discard their tokens after calling this endpoint.
-pub async fn logout_user(user: User) -> Result<Json<Value>, StatusCode> {
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "Authentication",
+    summary = "Log out",
+    description = "Acknowledge a logout. Tokens are stateless and stay valid until they expire, so clients must discard them.",
+    responses(
+        (status = 200, description = "Logged out", body = LogoutResponse),
+        (status = 401, description = "Authentication required")
+    )
+)]
+pub async fn logout_user(user: User) -> Result<Json<LogoutResponse>, StatusCode> {
     info!("User logout: {}", user.id);
 
-    Ok(Json(json!({
-        "message": "Logged out successfully",
-        "user_id": user.id
-    })))
+    Ok(Json(LogoutResponse {
+        message: "Logged out successfully".to_string(),
+        user_id: user.id,
+    }))
 }
 
 /// Change password endpoint
@@ -364,15 +672,44 @@ mod tests {
     }
 }
 
+/// Newly created API key
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyCreatedResponse {
+    pub success: bool,
+    /// The key, including its raw value which is never shown again
+    pub api_key: ApiKeyResponse,
+}
+
+/// API keys of the current user
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyListResponse {
+    pub success: bool,
+    /// Keys without their raw values
+    pub api_keys: Vec<ApiKeyResponse>,
+}
+
 /// Create a new API key for the authenticated user
 ///
 /// This endpoint allows authenticated users to create new API keys
 /// for programmatic access to the API.
+#[utoipa::path(
+    post,
+    path = "/api/auth/api-keys",
+    tag = "Authentication",
+    summary = "Create API key",
+    description = "Create an API key for programmatic access. The raw key is only returned in this response.",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "API key created", body = ApiKeyCreatedResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
 pub async fn create_api_key(
     State(state): State<AppState>,
     user: User,
     JsonExtractor(request): JsonExtractor<CreateApiKeyRequest>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Json<ApiKeyCreatedResponse>, StatusCode> {
     match state
         .api_key_service
         .create_api_key(&user.id, request)
@@ -383,10 +720,10 @@ pub async fn create_api_key(
                 "Created API key '{}' for user: {}",
                 api_key_response.name, user.id
             );
-            Ok(Json(json!({
-                "success": true,
-                "api_key": api_key_response
-            })))
+            Ok(Json(ApiKeyCreatedResponse {
+                success: true,
+                api_key: api_key_response,
+            }))
         }
         Err(e) => {
             tracing::error!("Failed to create API key: {}", e);
@@ -399,10 +736,22 @@ pub async fn create_api_key(
 ///
 /// Returns a list of all API keys owned by the authenticated user.
 /// The actual key values are not returned for security reasons.
+#[utoipa::path(
+    get,
+    path = "/api/auth/api-keys",
+    tag = "Authentication",
+    summary = "List API keys",
+    description = "List the API keys of the current user, without their raw values",
+    responses(
+        (status = 200, description = "API keys listed", body = ApiKeyListResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
 pub async fn list_api_keys(
     State(state): State<AppState>,
     user: User,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Json<ApiKeyListResponse>, StatusCode> {
     match state
         .api_key_service
         .storage()
@@ -412,7 +761,7 @@ pub async fn list_api_keys(
         Ok(api_keys) => {
             let api_key_responses: Vec<_> = api_keys
                 .into_iter()
-                .map(|key| super::api_keys::ApiKeyResponse {
+                .map(|key| ApiKeyResponse {
                     id: key.id,
                     key: None, // Never return the actual key
                     name: key.name,
@@ -424,10 +773,10 @@ pub async fn list_api_keys(
                 })
                 .collect();
 
-            Ok(Json(json!({
-                "success": true,
-                "api_keys": api_key_responses
-            })))
+            Ok(Json(ApiKeyListResponse {
+                success: true,
+                api_keys: api_key_responses,
+            }))
         }
         Err(e) => {
             tracing::error!("Failed to list API keys: {}", e);
@@ -439,11 +788,27 @@ pub async fn list_api_keys(
 /// Delete an API key
 ///
 /// Allows users to delete their own API keys.
+#[utoipa::path(
+    delete,
+    path = "/api/auth/api-keys/{key_id}",
+    tag = "Authentication",
+    summary = "Delete API key",
+    description = "Delete one of the current user's API keys",
+    params(
+        ("key_id" = String, Path, description = "API key ID")
+    ),
+    responses(
+        (status = 200, description = "API key deleted", body = MessageResponse),
+        (status = 401, description = "Authentication required"),
+        (status = 403, description = "API key belongs to another user"),
+        (status = 404, description = "API key not found")
+    )
+)]
 pub async fn delete_api_key(
     State(state): State<AppState>,
     user: User,
     axum::extract::Path(key_id): axum::extract::Path<String>,
-) -> Result<Json<Value>, StatusCode> {
+) -> Result<Json<MessageResponse>, StatusCode> {
     // First check if the API key belongs to the user
     match state
         .api_key_service
@@ -465,10 +830,9 @@ pub async fn delete_api_key(
             {
                 Ok(()) => {
                     info!("Deleted API key '{}' for user: {}", api_key.name, user.id);
-                    Ok(Json(json!({
-                        "success": true,
-                        "message": "API key deleted successfully"
-                    })))
+                    Ok(Json(MessageResponse::success(
+                        "API key deleted successfully",
+                    )))
                 }
                 Err(e) => {
                     tracing::error!("Failed to delete API key: {}", e);
@@ -18,8 +18,10 @@ use wikify_applications::Permission;
 
 /// JWT signing keys - initialized from environment variable
 static KEYS: LazyLock<Keys> = LazyLock::new(|| {
-    let secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "wikify-default-secret-change-in-production".to_string());
+    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| {
+        warn!("JWT_SECRET is not set, signing tokens with the insecure default secret");
+        "wikify-default-secret-change-in-production".to_string()
+    });
     Keys::new(secret.as_bytes())
 });
 
@@ -83,7 +85,7 @@ impl Claims {
             sub: user_id,
             name,
             email,
-            permissions: permissions.iter().map(|p| format!("{:?}", p)).collect(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
             is_admin,
             iat: now.timestamp(),
             exp: exp.timestamp(),
@@ -166,6 +168,8 @@ pub enum AuthError {
     InvalidCredentials,
     #[error("Missing credentials")]
     MissingCredentials,
+    #[error("User already exists")]
+    UserExists,
     #[error("Token creation failed")]
     TokenCreation,
     #[error("Invalid token")]
@@ -180,6 +184,14 @@ pub enum AuthError {
     MissingAuthHeader,
     #[error("Database error: {0}")]
     DatabaseError(String),
+    #[error("OAuth provider not configured")]
+    OAuthProviderNotConfigured,
+    #[error("Invalid or expired OAuth state")]
+    InvalidOAuthState,
+    #[error("OAuth error: {0}")]
+    OAuth(String),
+    #[error("Single sign-on access denied")]
+    SsoAccessDenied,
 }
 
 impl IntoResponse for AuthError {
@@ -195,6 +207,11 @@ impl IntoResponse for AuthError {
                 "missing_credentials",
                 "Username and password are required",
             ),
+            AuthError::UserExists => (
+                StatusCode::CONFLICT,
+                "user_exists",
+                "Username or email already exists",
+            ),
             AuthError::TokenCreation => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "token_creation_failed",
@@ -230,6 +247,22 @@ impl IntoResponse for AuthError {
                 "database_error",
                 msg.as_str(),
             ),
+            AuthError::OAuthProviderNotConfigured => (
+                StatusCode::NOT_FOUND,
+                "oauth_provider_not_configured",
+                "OAuth login is not configured for this provider",
+            ),
+            AuthError::InvalidOAuthState => (
+                StatusCode::BAD_REQUEST,
+                "invalid_oauth_state",
+                "OAuth login expired or was not started by this server",
+            ),
+            AuthError::OAuth(ref msg) => (StatusCode::BAD_GATEWAY, "oauth_failed", msg.as_str()),
+            AuthError::SsoAccessDenied => (
+                StatusCode::FORBIDDEN,
+                "sso_access_denied",
+                "Your account is not in a group that may use Wikify",
+            ),
         };
 
         let body = Json(json!({
@@ -0,0 +1,493 @@
+//! OAuth2 login with GitHub and GitLab
+//!
+//! Users sign in through the provider's authorization code flow and receive
+//! regular Wikify JWTs. If the user consents, the provider token is kept (in
+//! memory only) and reused to clone their private repositories on that host.
+
+use super::jwt::AuthError;
+use chrono::{DateTime, Duration, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// How long an authorization request may take before its state expires
+const STATE_TTL_MINUTES: i64 = 10;
+
+/// Supported OAuth providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OAuthProvider {
+    GitHub,
+    GitLab,
+}
+
+impl OAuthProvider {
+    /// Parse a provider name as used in URLs ("github", "gitlab")
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "github" => Some(Self::GitHub),
+            "gitlab" => Some(Self::GitLab),
+            _ => None,
+        }
+    }
+
+    /// Provider name as used in URLs and usernames
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+        }
+    }
+
+    /// Scopes to request, with or without access to private repositories
+    fn scopes(&self, repo_access: bool) -> &'static str {
+        match (self, repo_access) {
+            (Self::GitHub, false) => "read:user user:email",
+            (Self::GitHub, true) => "read:user user:email repo",
+            (Self::GitLab, false) => "read_user",
+            (Self::GitLab, true) => "read_user read_api read_repository",
+        }
+    }
+}
+
+/// OAuth application registered with a provider
+#[derive(Debug, Clone)]
+pub struct OAuthProviderConfig {
+    /// OAuth client ID
+    pub client_id: String,
+    /// OAuth client secret
+    pub client_secret: String,
+    /// Web URL of the provider, e.g. `https://gitlab.example.com`
+    pub base_url: String,
+}
+
+impl OAuthProviderConfig {
+    fn authorize_url(&self, provider: OAuthProvider) -> String {
+        match provider {
+            OAuthProvider::GitHub => format!("{}/login/oauth/authorize", self.base_url),
+            OAuthProvider::GitLab => format!("{}/oauth/authorize", self.base_url),
+        }
+    }
+
+    fn token_url(&self, provider: OAuthProvider) -> String {
+        match provider {
+            OAuthProvider::GitHub => format!("{}/login/oauth/access_token", self.base_url),
+            OAuthProvider::GitLab => format!("{}/oauth/token", self.base_url),
+        }
+    }
+
+    fn user_url(&self, provider: OAuthProvider) -> String {
+        match provider {
+            OAuthProvider::GitHub if self.base_url == "https://github.com" => {
+                "https://api.github.com/user".to_string()
+            }
+            // GitHub Enterprise serves the API under /api/v3
+            OAuthProvider::GitHub => format!("{}/api/v3/user", self.base_url),
+            OAuthProvider::GitLab => format!("{}/api/v4/user", self.base_url),
+        }
+    }
+
+    /// Host whose repositories the provider token gives access to
+    fn host(&self) -> Option<String> {
+        Url::parse(&self.base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+    }
+}
+
+/// User profile returned by a provider
+#[derive(Debug, Clone)]
+pub struct OAuthIdentity {
+    /// Provider that authenticated the user
+    pub provider: OAuthProvider,
+    /// Login name on the provider
+    pub login: String,
+    /// Full name
+    pub name: Option<String>,
+    /// Public or primary email address
+    pub email: Option<String>,
+    /// Provider access token
+    pub access_token: String,
+    /// Whether the user consented to reusing the token for repository access
+    pub repo_access: bool,
+    /// Where to send the browser after login
+    pub redirect: Option<String>,
+}
+
+impl OAuthIdentity {
+    /// Wikify username of the account linked to this identity
+    pub fn username(&self) -> String {
+        format!("{}@{}", self.login, self.provider.as_str())
+    }
+}
+
+/// Authorization request waiting for the provider callback
+#[derive(Debug, Clone)]
+struct PendingLogin {
+    provider: OAuthProvider,
+    repo_access: bool,
+    redirect: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+/// Provider token a user allowed Wikify to use for repository access
+#[derive(Debug, Clone)]
+struct LinkedToken {
+    provider: OAuthProvider,
+    host: String,
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProviderUser {
+    #[serde(alias = "username")]
+    login: String,
+    name: Option<String>,
+    email: Option<String>,
+}
+
+/// Configured OAuth providers listed by the API
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthProvidersResponse {
+    /// Provider names that can be used with `/api/auth/oauth/{provider}`
+    #[schema(example = json!(["github", "gitlab"]))]
+    pub providers: Vec<String>,
+    /// Name of the single sign-on provider at `/api/auth/oidc`, if configured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Okta")]
+    pub oidc: Option<String>,
+}
+
+/// OAuth login service
+#[derive(Debug, Clone, Default)]
+pub struct OAuthService {
+    providers: HashMap<OAuthProvider, OAuthProviderConfig>,
+    /// Callback URL prefix, e.g. `http://localhost:8080`
+    public_url: String,
+    pending: Arc<RwLock<HashMap<String, PendingLogin>>>,
+    /// Linked provider tokens by Wikify user ID
+    tokens: Arc<RwLock<HashMap<String, Vec<LinkedToken>>>>,
+    client: reqwest::Client,
+}
+
+impl OAuthService {
+    /// Create a service for the given providers
+    pub fn new(
+        providers: HashMap<OAuthProvider, OAuthProviderConfig>,
+        public_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            providers,
+            public_url: public_url.into().trim_end_matches('/').to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Configure providers from environment variables
+    ///
+    /// A provider is enabled when `WIKIFY_<PROVIDER>_CLIENT_ID` and
+    /// `WIKIFY_<PROVIDER>_CLIENT_SECRET` are set; `WIKIFY_<PROVIDER>_URL`
+    /// points at a self-hosted instance. `WIKIFY_PUBLIC_URL` overrides the
+    /// URL the provider redirects back to.
+    pub fn from_env(default_public_url: &str) -> Self {
+        let mut providers = HashMap::new();
+        for (provider, prefix, default_url) in [
+            (OAuthProvider::GitHub, "WIKIFY_GITHUB", "https://github.com"),
+            (OAuthProvider::GitLab, "WIKIFY_GITLAB", "https://gitlab.com"),
+        ] {
+            let client_id = std::env::var(format!("{}_CLIENT_ID", prefix));
+            let client_secret = std::env::var(format!("{}_CLIENT_SECRET", prefix));
+            if let (Ok(client_id), Ok(client_secret)) = (client_id, client_secret) {
+                let base_url = std::env::var(format!("{}_URL", prefix))
+                    .unwrap_or_else(|_| default_url.to_string());
+                info!("OAuth login enabled for {}", provider.as_str());
+                providers.insert(
+                    provider,
+                    OAuthProviderConfig {
+                        client_id,
+                        client_secret,
+                        base_url: base_url.trim_end_matches('/').to_string(),
+                    },
+                );
+            }
+        }
+
+        let public_url =
+            std::env::var("WIKIFY_PUBLIC_URL").unwrap_or_else(|_| default_public_url.to_string());
+        Self::new(providers, public_url)
+    }
+
+    /// Names of the configured providers
+    pub fn providers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .providers
+            .keys()
+            .map(|p| p.as_str().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn config(&self, provider: OAuthProvider) -> Result<&OAuthProviderConfig, AuthError> {
+        self.providers
+            .get(&provider)
+            .ok_or(AuthError::OAuthProviderNotConfigured)
+    }
+
+    fn callback_url(&self, provider: OAuthProvider) -> String {
+        format!(
+            "{}/api/auth/oauth/{}/callback",
+            self.public_url,
+            provider.as_str()
+        )
+    }
+
+    /// Start a login and return the provider URL to send the browser to
+    ///
+    /// `repo_access` asks for scopes that allow cloning private repositories.
+    /// `redirect` must be a path on this server; the tokens are appended to it
+    /// as a URL fragment after login.
+    pub async fn authorize_url(
+        &self,
+        provider: OAuthProvider,
+        repo_access: bool,
+        redirect: Option<String>,
+    ) -> Result<String, AuthError> {
+        let config = self.config(provider)?;
+        let redirect = redirect.filter(|r| r.starts_with('/') && !r.starts_with("//"));
+
+        let state = Uuid::new_v4().to_string();
+        {
+            let mut pending = self.pending.write().await;
+            let expired = Utc::now() - Duration::minutes(STATE_TTL_MINUTES);
+            pending.retain(|_, login| login.created_at > expired);
+            pending.insert(
+                state.clone(),
+                PendingLogin {
+                    provider,
+                    repo_access,
+                    redirect,
+                    created_at: Utc::now(),
+                },
+            );
+        }
+
+        let mut url = Url::parse(&config.authorize_url(provider))
+            .map_err(|e| AuthError::OAuth(format!("Invalid provider URL: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &self.callback_url(provider))
+            .append_pair("response_type", "code")
+            .append_pair("scope", provider.scopes(repo_access))
+            .append_pair("state", &state);
+        Ok(url.to_string())
+    }
+
+    /// Complete a login: check the state, exchange the code and fetch the user
+    pub async fn exchange_code(
+        &self,
+        provider: OAuthProvider,
+        code: &str,
+        state: &str,
+    ) -> Result<OAuthIdentity, AuthError> {
+        let pending = self
+            .pending
+            .write()
+            .await
+            .remove(state)
+            .filter(|login| login.provider == provider)
+            .filter(|login| login.created_at > Utc::now() - Duration::minutes(STATE_TTL_MINUTES))
+            .ok_or(AuthError::InvalidOAuthState)?;
+        let config = self.config(provider)?;
+        let callback_url = self.callback_url(provider);
+
+        let token: TokenResponse = self
+            .client
+            .post(config.token_url(provider))
+            .header("Accept", "application/json")
+            .form(&[
+                ("client_id", config.client_id.as_str()),
+                ("client_secret", config.client_secret.as_str()),
+                ("code", code),
+                ("grant_type", "authorization_code"),
+                ("redirect_uri", callback_url.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Token request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Invalid token response: {}", e)))?;
+        let access_token = token.access_token.ok_or_else(|| {
+            AuthError::OAuth(
+                token
+                    .error_description
+                    .or(token.error)
+                    .unwrap_or_else(|| "No access token returned".to_string()),
+            )
+        })?;
+
+        let user: ProviderUser = self
+            .client
+            .get(config.user_url(provider))
+            .bearer_auth(&access_token)
+            .header("User-Agent", "wikify")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AuthError::OAuth(format!("Profile request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Invalid profile response: {}", e)))?;
+
+        debug!("OAuth login from {} user {}", provider.as_str(), user.login);
+        Ok(OAuthIdentity {
+            provider,
+            login: user.login,
+            name: user.name,
+            email: user.email,
+            access_token,
+            repo_access: pending.repo_access,
+            redirect: pending.redirect,
+        })
+    }
+
+    /// Keep the provider token of a user who consented to repository access
+    pub async fn link_token(&self, user_id: &str, identity: &OAuthIdentity) {
+        let Some(host) = self
+            .providers
+            .get(&identity.provider)
+            .and_then(OAuthProviderConfig::host)
+        else {
+            warn!("Cannot determine host for {}", identity.provider.as_str());
+            return;
+        };
+
+        let mut tokens = self.tokens.write().await;
+        let linked = tokens.entry(user_id.to_string()).or_default();
+        linked.retain(|token| token.provider != identity.provider);
+        linked.push(LinkedToken {
+            provider: identity.provider,
+            host,
+            access_token: identity.access_token.clone(),
+        });
+    }
+
+    /// Forget the provider token of a user, returning whether one was linked
+    pub async fn unlink_token(&self, user_id: &str, provider: OAuthProvider) -> bool {
+        let mut tokens = self.tokens.write().await;
+        let Some(linked) = tokens.get_mut(user_id) else {
+            return false;
+        };
+        let before = linked.len();
+        linked.retain(|token| token.provider != provider);
+        before != linked.len()
+    }
+
+    /// Token a user linked for the host of a repository URL
+    pub async fn repository_token(&self, user_id: &str, repository_url: &str) -> Option<String> {
+        let url = Url::parse(repository_url).ok()?;
+        let host = url.host_str()?;
+        self.tokens
+            .read()
+            .await
+            .get(user_id)?
+            .iter()
+            .find(|token| token.host == host)
+            .map(|token| token.access_token.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> OAuthService {
+        let mut providers = HashMap::new();
+        providers.insert(
+            OAuthProvider::GitHub,
+            OAuthProviderConfig {
+                client_id: "client".to_string(),
+                client_secret: "secret".to_string(),
+                base_url: "https://github.com".to_string(),
+            },
+        );
+        OAuthService::new(providers, "http://localhost:8080/")
+    }
+
+    #[tokio::test]
+    async fn test_authorize_url() {
+        let service = service();
+        let url = service
+            .authorize_url(OAuthProvider::GitHub, true, Some("//evil.com".to_string()))
+            .await
+            .unwrap();
+        let url = Url::parse(&url).unwrap();
+        let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/login/oauth/authorize");
+        assert_eq!(query["scope"], "read:user user:email repo");
+        assert_eq!(
+            query["redirect_uri"],
+            "http://localhost:8080/api/auth/oauth/github/callback"
+        );
+        let pending = service.pending.read().await;
+        assert!(pending[&query["state"]].redirect.is_none());
+
+        assert!(matches!(
+            service
+                .authorize_url(OAuthProvider::GitLab, false, None)
+                .await,
+            Err(AuthError::OAuthProviderNotConfigured)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_repository_token_matches_host() {
+        let service = service();
+        let identity = OAuthIdentity {
+            provider: OAuthProvider::GitHub,
+            login: "octocat".to_string(),
+            name: None,
+            email: None,
+            access_token: "gho_token".to_string(),
+            repo_access: true,
+            redirect: None,
+        };
+        service.link_token("user-1", &identity).await;
+
+        assert_eq!(
+            service
+                .repository_token("user-1", "https://github.com/octocat/private")
+                .await
+                .as_deref(),
+            Some("gho_token")
+        );
+        assert!(service
+            .repository_token("user-1", "https://gitlab.com/octocat/private")
+            .await
+            .is_none());
+        assert!(service
+            .repository_token("user-2", "https://github.com/octocat/private")
+            .await
+            .is_none());
+
+        assert!(service.unlink_token("user-1", OAuthProvider::GitHub).await);
+        assert!(service
+            .repository_token("user-1", "https://github.com/octocat/private")
+            .await
+            .is_none());
+    }
+}
@@ -0,0 +1,654 @@
+//! OpenID Connect single sign-on
+//!
+//! Any OIDC provider (Okta, Keycloak, Microsoft Entra ID, ...) can be plugged
+//! in by its issuer URL. Endpoints and signing keys are discovered from the
+//! issuer, ID tokens are validated against its JWKS, and the groups in the
+//! token are mapped to a Wikify role on every login, so role changes made in
+//! the identity provider apply at the next sign-in.
+
+use super::jwt::AuthError;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, Algorithm, DecodingKey, Validation};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+use wikify_applications::Permission;
+
+/// How long an authorization request may take before its state expires
+const STATE_TTL_MINUTES: i64 = 10;
+
+/// Scopes requested when `WIKIFY_OIDC_SCOPES` is not set
+const DEFAULT_SCOPES: &str = "openid profile email";
+
+/// Claim holding the user's groups when `WIKIFY_OIDC_GROUPS_CLAIM` is not set
+const DEFAULT_GROUPS_CLAIM: &str = "groups";
+
+/// Signature algorithms accepted for ID tokens
+///
+/// Only asymmetric algorithms are accepted, since the keys come from the
+/// provider's public JWKS.
+const ALLOWED_ALGORITHMS: [Algorithm; 8] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+];
+
+/// Role granted to single sign-on users, from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SsoRole {
+    /// Ask questions about repositories
+    Viewer,
+    /// Also generate wikis (the default for new accounts)
+    Member,
+    /// Also run research, export and manage repositories
+    Editor,
+    /// Full administrative access
+    Admin,
+}
+
+impl SsoRole {
+    /// Permissions granted by this role
+    pub fn permissions(&self) -> Vec<Permission> {
+        match self {
+            SsoRole::Viewer => vec![Permission::Query],
+            SsoRole::Member => vec![Permission::Query, Permission::GenerateWiki],
+            SsoRole::Editor => vec![
+                Permission::Query,
+                Permission::GenerateWiki,
+                Permission::DeepResearch,
+                Permission::Export,
+                Permission::ManageRepository,
+            ],
+            SsoRole::Admin => vec![Permission::Admin],
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        *self == SsoRole::Admin
+    }
+}
+
+impl std::fmt::Display for SsoRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SsoRole::Viewer => write!(f, "viewer"),
+            SsoRole::Member => write!(f, "member"),
+            SsoRole::Editor => write!(f, "editor"),
+            SsoRole::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl std::str::FromStr for SsoRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "viewer" => Ok(SsoRole::Viewer),
+            "member" => Ok(SsoRole::Member),
+            "editor" => Ok(SsoRole::Editor),
+            "admin" => Ok(SsoRole::Admin),
+            _ => Err(format!("Unknown SSO role: {}", s)),
+        }
+    }
+}
+
+/// OIDC client registered with an identity provider
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    /// Issuer URL, e.g. `https://example.okta.com/oauth2/default`
+    pub issuer: String,
+    /// OAuth client ID, also the expected ID token audience
+    pub client_id: String,
+    /// OAuth client secret
+    pub client_secret: String,
+    /// Name shown on the login button
+    pub display_name: String,
+    /// Space separated scopes to request
+    pub scopes: String,
+    /// Claim holding the user's groups; nested claims use dots, e.g. `realm_access.roles`
+    pub groups_claim: String,
+    /// Role granted to members of each group
+    pub role_mapping: Vec<(String, SsoRole)>,
+    /// Role for users in none of the mapped groups (None denies them access)
+    pub default_role: Option<SsoRole>,
+}
+
+impl OidcConfig {
+    /// Read the configuration from environment variables
+    ///
+    /// Single sign-on is enabled when `WIKIFY_OIDC_ISSUER`,
+    /// `WIKIFY_OIDC_CLIENT_ID` and `WIKIFY_OIDC_CLIENT_SECRET` are set.
+    /// `WIKIFY_OIDC_ROLE_MAPPING` maps groups to roles as
+    /// `group=role,other-group=role`; `WIKIFY_OIDC_DEFAULT_ROLE` is the role
+    /// of everyone else (`member` by default, `none` to deny them).
+    pub fn from_env() -> Option<Self> {
+        let issuer = std::env::var("WIKIFY_OIDC_ISSUER").ok()?;
+        let client_id = std::env::var("WIKIFY_OIDC_CLIENT_ID").ok()?;
+        let client_secret = std::env::var("WIKIFY_OIDC_CLIENT_SECRET").ok()?;
+
+        let default_role = match std::env::var("WIKIFY_OIDC_DEFAULT_ROLE") {
+            Ok(role) if role.eq_ignore_ascii_case("none") => None,
+            Ok(role) => match role.parse() {
+                Ok(role) => Some(role),
+                Err(e) => {
+                    warn!("{}, using member", e);
+                    Some(SsoRole::Member)
+                }
+            },
+            Err(_) => Some(SsoRole::Member),
+        };
+
+        Some(Self {
+            issuer: issuer.trim_end_matches('/').to_string(),
+            client_id,
+            client_secret,
+            display_name: std::env::var("WIKIFY_OIDC_NAME").unwrap_or_else(|_| "SSO".to_string()),
+            scopes: std::env::var("WIKIFY_OIDC_SCOPES")
+                .unwrap_or_else(|_| DEFAULT_SCOPES.to_string()),
+            groups_claim: std::env::var("WIKIFY_OIDC_GROUPS_CLAIM")
+                .unwrap_or_else(|_| DEFAULT_GROUPS_CLAIM.to_string()),
+            role_mapping: std::env::var("WIKIFY_OIDC_ROLE_MAPPING")
+                .map(|mapping| parse_role_mapping(&mapping))
+                .unwrap_or_default(),
+            default_role,
+        })
+    }
+
+    /// Most privileged role granted by any of the groups, or the default role
+    pub fn role_for(&self, groups: &[String]) -> Option<SsoRole> {
+        self.role_mapping
+            .iter()
+            .filter(|(group, _)| groups.contains(group))
+            .map(|(_, role)| *role)
+            .max()
+            .or(self.default_role)
+    }
+}
+
+/// Parse `group=role` pairs separated by commas, skipping invalid entries
+fn parse_role_mapping(value: &str) -> Vec<(String, SsoRole)> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            // Group names may contain '=', roles never do
+            let Some((group, role)) = entry.rsplit_once('=') else {
+                warn!("Ignoring OIDC role mapping without a role: {}", entry);
+                return None;
+            };
+            match role.parse() {
+                Ok(role) => Some((group.trim().to_string(), role)),
+                Err(e) => {
+                    warn!("Ignoring OIDC role mapping {}: {}", entry, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Endpoints published in the issuer's discovery document
+#[derive(Debug, Clone, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    userinfo_endpoint: Option<String>,
+}
+
+/// User signed in through the identity provider
+#[derive(Debug, Clone)]
+pub struct OidcIdentity {
+    /// Subject identifier, stable for the user at this issuer
+    pub subject: String,
+    /// Preferred username or email, used as the display name fallback
+    pub preferred_username: Option<String>,
+    /// Full name
+    pub name: Option<String>,
+    /// Email address
+    pub email: Option<String>,
+    /// Groups the user belongs to
+    pub groups: Vec<String>,
+    /// Role granted by the groups
+    pub role: SsoRole,
+    /// Where to send the browser after login
+    pub redirect: Option<String>,
+}
+
+impl OidcIdentity {
+    /// Wikify username of the account linked to this identity
+    pub fn username(&self) -> String {
+        format!("{}@oidc", self.subject)
+    }
+
+    /// Name to show for the user
+    pub fn display_name(&self) -> Option<String> {
+        self.name
+            .clone()
+            .or_else(|| self.preferred_username.clone())
+    }
+}
+
+/// Authorization request waiting for the provider callback
+#[derive(Debug, Clone)]
+struct PendingLogin {
+    nonce: String,
+    redirect: Option<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Claims of an ID token that Wikify uses
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    name: Option<String>,
+    preferred_username: Option<String>,
+    /// All other claims, searched for the groups claim
+    #[serde(flatten)]
+    other: HashMap<String, Value>,
+}
+
+/// Strings at a dotted claim path, accepting a single string or an array
+fn claim_strings(claims: &HashMap<String, Value>, path: &str) -> Option<Vec<String>> {
+    let mut parts = path.split('.');
+    let mut value = claims.get(parts.next()?)?;
+    for part in parts {
+        value = value.get(part)?;
+    }
+
+    match value {
+        Value::String(s) => Some(vec![s.clone()]),
+        Value::Array(items) => Some(
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// OIDC single sign-on service
+#[derive(Debug, Clone, Default)]
+pub struct OidcService {
+    config: Option<Arc<OidcConfig>>,
+    /// Callback URL prefix, e.g. `http://localhost:8080`
+    public_url: String,
+    pending: Arc<RwLock<HashMap<String, PendingLogin>>>,
+    /// Discovery document, fetched on first use
+    metadata: Arc<RwLock<Option<ProviderMetadata>>>,
+    /// Signing keys, refetched when a token uses an unknown key
+    jwks: Arc<RwLock<Option<JwkSet>>>,
+    client: reqwest::Client,
+}
+
+impl OidcService {
+    /// Create a service, disabled when `config` is None
+    pub fn new(config: Option<OidcConfig>, public_url: impl Into<String>) -> Self {
+        Self {
+            config: config.map(Arc::new),
+            public_url: public_url.into().trim_end_matches('/').to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Configure the service from environment variables
+    ///
+    /// See [`OidcConfig::from_env`]. `WIKIFY_PUBLIC_URL` overrides the URL
+    /// the provider redirects back to.
+    pub fn from_env(default_public_url: &str) -> Self {
+        let config = OidcConfig::from_env();
+        if let Some(config) = &config {
+            info!("OIDC single sign-on enabled for {}", config.issuer);
+        }
+        let public_url =
+            std::env::var("WIKIFY_PUBLIC_URL").unwrap_or_else(|_| default_public_url.to_string());
+        Self::new(config, public_url)
+    }
+
+    /// Name of the identity provider, if single sign-on is enabled
+    pub fn display_name(&self) -> Option<String> {
+        self.config
+            .as_ref()
+            .map(|config| config.display_name.clone())
+    }
+
+    fn config(&self) -> Result<&OidcConfig, AuthError> {
+        self.config
+            .as_deref()
+            .ok_or(AuthError::OAuthProviderNotConfigured)
+    }
+
+    fn callback_url(&self) -> String {
+        format!("{}/api/auth/oidc/callback", self.public_url)
+    }
+
+    /// Provider endpoints, from the issuer's discovery document
+    async fn metadata(&self) -> Result<ProviderMetadata, AuthError> {
+        if let Some(metadata) = self.metadata.read().await.clone() {
+            return Ok(metadata);
+        }
+
+        let config = self.config()?;
+        let url = format!("{}/.well-known/openid-configuration", config.issuer);
+        let metadata: ProviderMetadata = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AuthError::OAuth(format!("OIDC discovery failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Invalid OIDC discovery document: {}", e)))?;
+
+        if metadata.issuer.trim_end_matches('/') != config.issuer {
+            return Err(AuthError::OAuth(format!(
+                "OIDC discovery document is for issuer {}, expected {}",
+                metadata.issuer, config.issuer
+            )));
+        }
+
+        debug!("Discovered OIDC endpoints for {}", config.issuer);
+        *self.metadata.write().await = Some(metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Start a login and return the provider URL to send the browser to
+    ///
+    /// `redirect` must be a path on this server; the tokens are appended to it
+    /// as a URL fragment after login.
+    pub async fn authorize_url(&self, redirect: Option<String>) -> Result<String, AuthError> {
+        let config = self.config()?;
+        let metadata = self.metadata().await?;
+        let redirect = redirect.filter(|r| r.starts_with('/') && !r.starts_with("//"));
+
+        let state = Uuid::new_v4().to_string();
+        let nonce = Uuid::new_v4().to_string();
+        {
+            let mut pending = self.pending.write().await;
+            let expired = Utc::now() - Duration::minutes(STATE_TTL_MINUTES);
+            pending.retain(|_, login| login.created_at > expired);
+            pending.insert(
+                state.clone(),
+                PendingLogin {
+                    nonce: nonce.clone(),
+                    redirect,
+                    created_at: Utc::now(),
+                },
+            );
+        }
+
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| AuthError::OAuth(format!("Invalid authorization endpoint: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &self.callback_url())
+            .append_pair("response_type", "code")
+            .append_pair("scope", &config.scopes)
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce);
+        Ok(url.to_string())
+    }
+
+    /// Complete a login: check the state, exchange the code, validate the ID
+    /// token and map the user's groups to a role
+    pub async fn exchange_code(&self, code: &str, state: &str) -> Result<OidcIdentity, AuthError> {
+        let pending = self
+            .pending
+            .write()
+            .await
+            .remove(state)
+            .filter(|login| login.created_at > Utc::now() - Duration::minutes(STATE_TTL_MINUTES))
+            .ok_or(AuthError::InvalidOAuthState)?;
+        let config = self.config()?;
+        let metadata = self.metadata().await?;
+        let callback_url = self.callback_url();
+
+        let token: TokenResponse = self
+            .client
+            .post(&metadata.token_endpoint)
+            .header("Accept", "application/json")
+            .form(&[
+                ("client_id", config.client_id.as_str()),
+                ("client_secret", config.client_secret.as_str()),
+                ("code", code),
+                ("grant_type", "authorization_code"),
+                ("redirect_uri", callback_url.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Token request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AuthError::OAuth(format!("Invalid token response: {}", e)))?;
+        let id_token = token.id_token.ok_or_else(|| {
+            AuthError::OAuth(
+                token
+                    .error_description
+                    .or(token.error)
+                    .unwrap_or_else(|| "No ID token returned".to_string()),
+            )
+        })?;
+
+        let claims = self.verify_id_token(&id_token, &metadata).await?;
+        if claims.nonce.as_deref() != Some(pending.nonce.as_str()) {
+            return Err(AuthError::InvalidOAuthState);
+        }
+
+        // Some providers (e.g. Okta) only return groups from the userinfo endpoint
+        let groups = match claim_strings(&claims.other, &config.groups_claim) {
+            Some(groups) => groups,
+            None => match (&metadata.userinfo_endpoint, &token.access_token) {
+                (Some(endpoint), Some(access_token)) => {
+                    self.userinfo_groups(endpoint, access_token, &config.groups_claim)
+                        .await
+                }
+                _ => Vec::new(),
+            },
+        };
+
+        let role = config.role_for(&groups).ok_or_else(|| {
+            info!(
+                "OIDC login denied for {}: no mapped group in {:?}",
+                claims.sub, groups
+            );
+            AuthError::SsoAccessDenied
+        })?;
+
+        debug!("OIDC login from {} as {}", claims.sub, role);
+        Ok(OidcIdentity {
+            subject: claims.sub,
+            preferred_username: claims.preferred_username,
+            name: claims.name,
+            email: claims.email,
+            groups,
+            role,
+            redirect: pending.redirect,
+        })
+    }
+
+    /// Validate an ID token's signature, issuer, audience and expiry
+    async fn verify_id_token(
+        &self,
+        id_token: &str,
+        metadata: &ProviderMetadata,
+    ) -> Result<IdTokenClaims, AuthError> {
+        let config = self.config()?;
+        let header = decode_header(id_token).map_err(|_| AuthError::InvalidToken)?;
+        if !ALLOWED_ALGORITHMS.contains(&header.alg) {
+            warn!("Rejected ID token signed with {:?}", header.alg);
+            return Err(AuthError::InvalidToken);
+        }
+
+        let key = self
+            .decoding_key(header.kid.as_deref(), &metadata.jwks_uri)
+            .await?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&config.client_id]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                warn!("Rejected ID token: {}", e);
+                match e.kind() {
+                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                    _ => AuthError::InvalidToken,
+                }
+            })
+    }
+
+    /// Signing key for a key ID, refetching the JWKS once if it is unknown
+    ///
+    /// Providers rotate keys, so a new key ID usually means new keys were
+    /// published since they were last fetched.
+    async fn decoding_key(
+        &self,
+        kid: Option<&str>,
+        jwks_uri: &str,
+    ) -> Result<DecodingKey, AuthError> {
+        let find = |jwks: &JwkSet| match kid {
+            Some(kid) => jwks.find(kid).cloned(),
+            // Without a key ID, only a single published key is unambiguous
+            None if jwks.keys.len() == 1 => jwks.keys.first().cloned(),
+            None => None,
+        };
+
+        let cached = self.jwks.read().await.as_ref().and_then(&find);
+        let jwk = match cached {
+            Some(jwk) => jwk,
+            None => {
+                let jwks: JwkSet = self
+                    .client
+                    .get(jwks_uri)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| AuthError::OAuth(format!("Failed to fetch JWKS: {}", e)))?
+                    .json()
+                    .await
+                    .map_err(|e| AuthError::OAuth(format!("Invalid JWKS: {}", e)))?;
+                let jwk = find(&jwks);
+                *self.jwks.write().await = Some(jwks);
+                jwk.ok_or(AuthError::InvalidToken)?
+            }
+        };
+
+        DecodingKey::from_jwk(&jwk).map_err(|_| AuthError::InvalidToken)
+    }
+
+    /// Groups from the userinfo endpoint, empty if they cannot be read
+    async fn userinfo_groups(
+        &self,
+        endpoint: &str,
+        access_token: &str,
+        claim: &str,
+    ) -> Vec<String> {
+        let userinfo = self
+            .client
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        let claims: HashMap<String, Value> = match userinfo {
+            Ok(response) => response.json().await.unwrap_or_default(),
+            Err(e) => {
+                warn!("OIDC userinfo request failed: {}", e);
+                HashMap::new()
+            }
+        };
+        claim_strings(&claims, claim).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> OidcConfig {
+        OidcConfig {
+            issuer: "https://idp.example.com".to_string(),
+            client_id: "wikify".to_string(),
+            client_secret: "secret".to_string(),
+            display_name: "Example".to_string(),
+            scopes: DEFAULT_SCOPES.to_string(),
+            groups_claim: "realm_access.roles".to_string(),
+            role_mapping: parse_role_mapping("wiki-admins=admin, eng=editor,bad=owner"),
+            default_role: None,
+        }
+    }
+
+    #[test]
+    fn test_groups_map_to_most_privileged_role() {
+        let config = config();
+        assert_eq!(config.role_mapping.len(), 2);
+
+        let claims: HashMap<String, Value> = serde_json::from_value(serde_json::json!({
+            "realm_access": { "roles": ["eng", "wiki-admins", "other"] }
+        }))
+        .unwrap();
+        let groups = claim_strings(&claims, &config.groups_claim).unwrap();
+        assert_eq!(config.role_for(&groups), Some(SsoRole::Admin));
+        assert_eq!(config.role_for(&["eng".to_string()]), Some(SsoRole::Editor));
+        // Without a default role, users outside the mapped groups are denied
+        assert_eq!(config.role_for(&["other".to_string()]), None);
+    }
+
+    #[tokio::test]
+    async fn test_authorize_url_uses_discovered_endpoint() {
+        let service = OidcService::new(Some(config()), "http://localhost:8080/");
+        *service.metadata.write().await = Some(ProviderMetadata {
+            issuer: "https://idp.example.com".to_string(),
+            authorization_endpoint: "https://idp.example.com/authorize".to_string(),
+            token_endpoint: "https://idp.example.com/token".to_string(),
+            jwks_uri: "https://idp.example.com/jwks".to_string(),
+            userinfo_endpoint: None,
+        });
+
+        let url = service
+            .authorize_url(Some("/repositories".to_string()))
+            .await
+            .unwrap();
+        let url = Url::parse(&url).unwrap();
+        let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(query["scope"], "openid profile email");
+        assert_eq!(
+            query["redirect_uri"],
+            "http://localhost:8080/api/auth/oidc/callback"
+        );
+        let pending = service.pending.read().await;
+        assert_eq!(pending[&query["state"]].nonce, query["nonce"]);
+
+        assert!(matches!(
+            OidcService::default().authorize_url(None).await,
+            Err(AuthError::OAuthProviderNotConfigured)
+        ));
+    }
+}
@@ -3,6 +3,8 @@
 use super::{
     database::DatabaseUserStore,
     jwt::{AuthError, JwtService, TokenPair},
+    oauth::OAuthIdentity,
+    oidc::OidcIdentity,
 };
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
@@ -126,6 +128,27 @@ impl UserData {
         })
     }
 
+    /// Create a user authenticated by an external provider
+    ///
+    /// The account has no password and can only sign in through the provider.
+    pub fn external(
+        username: String,
+        email: String,
+        display_name: Option<String>,
+        permissions: Vec<Permission>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            username,
+            email,
+            display_name,
+            password_hash: String::new(),
+            permissions,
+            is_admin: false,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
     /// Verify password
     pub fn verify_password(&self, password: &str) -> bool {
         verify_password(password, &self.password_hash).unwrap_or(false)
@@ -250,7 +273,7 @@ impl UserStore {
                         "Registration failed: username '{}' already exists",
                         request.username
                     );
-                    return Err(AuthError::InvalidCredentials);
+                    return Err(AuthError::UserExists);
                 }
 
                 // Check if email already exists
@@ -259,7 +282,7 @@ impl UserStore {
                         "Registration failed: email '{}' already exists",
                         request.email
                     );
-                    return Err(AuthError::InvalidCredentials);
+                    return Err(AuthError::UserExists);
                 }
 
                 drop(users_read);
@@ -303,6 +326,58 @@ impl UserStore {
         }
     }
 
+    /// Store a user created outside the registration flow
+    pub async fn insert_user(&self, user_data: UserData) -> Result<UserData, AuthError> {
+        match self {
+            Self::Memory {
+                users,
+                users_by_email,
+            } => {
+                let mut users = users.write().unwrap();
+                let mut users_by_email = users_by_email.write().unwrap();
+
+                if users.contains_key(&user_data.username)
+                    || users_by_email.contains_key(&user_data.email)
+                {
+                    return Err(AuthError::UserExists);
+                }
+
+                users.insert(user_data.username.clone(), user_data.clone());
+                users_by_email.insert(user_data.email.clone(), user_data.id.clone());
+
+                info!("Created user: {}", user_data.username);
+                Ok(user_data)
+            }
+            Self::Database(db_store) => db_store.register_user(user_data).await,
+        }
+    }
+
+    /// Replace a user's permissions and admin flag
+    pub async fn update_user_access(
+        &self,
+        user_id: &str,
+        permissions: Vec<Permission>,
+        is_admin: bool,
+    ) -> Result<(), AuthError> {
+        match self {
+            Self::Memory { users, .. } => {
+                let mut users = users.write().unwrap();
+                let user = users
+                    .values_mut()
+                    .find(|u| u.id == user_id)
+                    .ok_or(AuthError::InvalidCredentials)?;
+                user.permissions = permissions;
+                user.is_admin = is_admin;
+                Ok(())
+            }
+            Self::Database(db_store) => {
+                db_store
+                    .update_user_access(user_id, permissions, is_admin)
+                    .await
+            }
+        }
+    }
+
     /// Authenticate user
     pub async fn authenticate_user(&self, request: LoginRequest) -> Result<UserData, AuthError> {
         match self {
@@ -397,25 +472,106 @@ impl UserService {
     /// Register new user
     pub async fn register(&self, request: RegisterRequest) -> Result<AuthResponse, AuthError> {
         let user_data = self.store.register_user(request).await?;
-
-        let tokens = JwtService::generate_token_pair(
-            user_data.id.clone(),
-            user_data.display_name.clone(),
-            Some(user_data.email.clone()),
-            user_data.permissions.clone(),
-            user_data.is_admin,
-        )?;
-
-        Ok(AuthResponse {
-            user: user_data.to_user_info(),
-            tokens,
-        })
+        Self::auth_response(&user_data)
     }
 
     /// Login user
     pub async fn login(&self, request: LoginRequest) -> Result<AuthResponse, AuthError> {
         let user_data = self.store.authenticate_user(request).await?;
+        Self::auth_response(&user_data)
+    }
+
+    /// Login a user authenticated by an OAuth provider
+    ///
+    /// The account is created with default permissions on first login. Its
+    /// username is `<login>@<provider>`, so it never collides with accounts
+    /// registered with a password.
+    pub async fn login_oauth(&self, identity: &OAuthIdentity) -> Result<AuthResponse, AuthError> {
+        let username = identity.username();
+        let user_data = match self.store.get_user_by_username(&username).await {
+            Some(user_data) => user_data,
+            None => {
+                let fallback_email = format!("{}@users.noreply.wikify.local", username);
+                let new_user = |email: String| {
+                    UserData::external(
+                        username.clone(),
+                        email,
+                        identity.name.clone(),
+                        vec![Permission::Query, Permission::GenerateWiki], // Default permissions
+                    )
+                };
+
+                match identity.email.clone() {
+                    Some(email) => match self.store.insert_user(new_user(email)).await {
+                        // The email belongs to another account
+                        Err(AuthError::UserExists) => {
+                            self.store.insert_user(new_user(fallback_email)).await?
+                        }
+                        result => result?,
+                    },
+                    None => self.store.insert_user(new_user(fallback_email)).await?,
+                }
+            }
+        };
+
+        info!("OAuth login for user: {}", user_data.username);
+        Self::auth_response(&user_data)
+    }
+
+    /// Login a user authenticated by the OIDC identity provider
+    ///
+    /// The account is created on first login with username `<subject>@oidc`.
+    /// Its permissions follow the role mapped from the user's groups, and are
+    /// updated on every login so changes in the identity provider apply.
+    pub async fn login_oidc(&self, identity: &OidcIdentity) -> Result<AuthResponse, AuthError> {
+        let username = identity.username();
+        let permissions = identity.role.permissions();
+        let is_admin = identity.role.is_admin();
+
+        let user_data = match self.store.get_user_by_username(&username).await {
+            Some(mut user_data) => {
+                if user_data.permissions != permissions || user_data.is_admin != is_admin {
+                    self.store
+                        .update_user_access(&user_data.id, permissions.clone(), is_admin)
+                        .await?;
+                    info!("Updated {} to SSO role {}", username, identity.role);
+                    user_data.permissions = permissions;
+                    user_data.is_admin = is_admin;
+                }
+                user_data
+            }
+            None => {
+                let fallback_email = format!("{}@users.noreply.wikify.local", username);
+                let new_user = |email: String| {
+                    let mut user_data = UserData::external(
+                        username.clone(),
+                        email,
+                        identity.display_name(),
+                        permissions.clone(),
+                    );
+                    user_data.is_admin = is_admin;
+                    user_data
+                };
+
+                match identity.email.clone() {
+                    Some(email) => match self.store.insert_user(new_user(email)).await {
+                        // The email belongs to another account
+                        Err(AuthError::UserExists) => {
+                            self.store.insert_user(new_user(fallback_email)).await?
+                        }
+                        result => result?,
+                    },
+                    None => self.store.insert_user(new_user(fallback_email)).await?,
+                }
+            }
+        };
+
+        info!("OIDC login for user: {}", user_data.username);
+        Self::auth_response(&user_data)
+    }
 
+    /// Issue tokens for a user
+    fn auth_response(user_data: &UserData) -> Result<AuthResponse, AuthError> {
         let tokens = JwtService::generate_token_pair(
             user_data.id.clone(),
             user_data.display_name.clone(),
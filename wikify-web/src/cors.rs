@@ -0,0 +1,146 @@
+//! Cross-origin request policy
+//!
+//! Allowed origins, methods and whether credentials may be sent come from
+//! [`WebConfig`]. They are checked when the server starts, so a typo in an
+//! origin fails startup instead of silently blocking the frontend.
+
+use crate::{csrf, middleware, WebConfig, WebError, WebResult};
+use axum::http::{
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    HeaderName, HeaderValue, Method,
+};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::{info, warn};
+
+/// Origins allowed when none are configured (the Vite dev server)
+pub const DEFAULT_CORS_ORIGINS: &[&str] = &["http://localhost:5173", "http://127.0.0.1:5173"];
+
+/// Methods allowed when none are configured
+pub const DEFAULT_CORS_METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"];
+
+/// Build the CORS layer for a configuration, rejecting invalid settings
+///
+/// The `*` origin is only accepted in development mode. Since browsers
+/// refuse credentials with a literal wildcard, it is answered by echoing
+/// the request's origin.
+pub fn cors_layer(config: &WebConfig) -> WebResult<CorsLayer> {
+    let origins = allowed_origins(config);
+    let methods: Vec<&str> = if config.cors_methods.is_empty() {
+        DEFAULT_CORS_METHODS.to_vec()
+    } else {
+        config.cors_methods.iter().map(String::as_str).collect()
+    };
+
+    let allow_origin = if origins.contains(&"*") {
+        if !config.dev_mode {
+            return Err(WebError::Config(
+                "CORS origin \"*\" is only allowed in development mode".to_string(),
+            ));
+        }
+        warn!("⚠️ CORS allows requests from any origin (development mode)");
+        AllowOrigin::mirror_request()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|origin| parse_origin(origin))
+            .collect::<WebResult<Vec<_>>>()?;
+        info!("CORS origins: {}", join_origins(&origins));
+        AllowOrigin::list(origins)
+    };
+
+    let methods = methods
+        .iter()
+        .map(|method| {
+            method
+                .trim()
+                .to_uppercase()
+                .parse::<Method>()
+                .map_err(|_| WebError::Config(format!("Invalid CORS method '{}'", method)))
+        })
+        .collect::<WebResult<Vec<_>>>()?;
+
+    Ok(CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(methods)
+        .allow_credentials(config.cors_allow_credentials.unwrap_or(true))
+        .allow_headers([
+            AUTHORIZATION,
+            ACCEPT,
+            CONTENT_TYPE,
+            HeaderName::from_static("x-organization-id"),
+            HeaderName::from_static(middleware::REQUEST_ID_HEADER),
+            HeaderName::from_static(csrf::CSRF_HEADER),
+        ])
+        .expose_headers([HeaderName::from_static(middleware::REQUEST_ID_HEADER)]))
+}
+
+/// Origins allowed to call the API from a browser, as configured
+pub(crate) fn allowed_origins(config: &WebConfig) -> Vec<&str> {
+    if config.cors_origins.is_empty() {
+        DEFAULT_CORS_ORIGINS.to_vec()
+    } else {
+        config.cors_origins.iter().map(String::as_str).collect()
+    }
+}
+
+/// Check an origin is a bare `scheme://host[:port]`, as browsers send it
+fn parse_origin(origin: &str) -> WebResult<HeaderValue> {
+    let invalid =
+        |reason: &str| WebError::Config(format!("Invalid CORS origin '{}': {}", origin, reason));
+
+    let host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"))
+        .ok_or_else(|| invalid("expected http:// or https://"))?;
+    if host.is_empty() {
+        return Err(invalid("missing host"));
+    }
+    if host.contains('/') {
+        return Err(invalid("origins have no path or trailing slash"));
+    }
+
+    origin
+        .parse::<HeaderValue>()
+        .map_err(|_| invalid("not a valid header value"))
+}
+
+fn join_origins(origins: &[HeaderValue]) -> String {
+    origins
+        .iter()
+        .filter_map(|origin| origin.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy_is_valid() {
+        assert!(cors_layer(&WebConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_origins_are_rejected() {
+        for origin in ["localhost:5173", "https://example.com/", "https://"] {
+            let config = WebConfig {
+                cors_origins: vec![origin.to_string()],
+                ..WebConfig::default()
+            };
+            assert!(cors_layer(&config).is_err(), "{} was accepted", origin);
+        }
+    }
+
+    #[test]
+    fn test_wildcard_requires_dev_mode() {
+        let mut config = WebConfig {
+            cors_origins: vec!["*".to_string()],
+            ..WebConfig::default()
+        };
+        assert!(cors_layer(&config).is_err());
+
+        config.dev_mode = true;
+        assert!(cors_layer(&config).is_ok());
+    }
+}
@@ -0,0 +1,269 @@
+//! Cross-site request forgery protection
+//!
+//! Browsers attach cookies to requests another site makes, so a request
+//! identified by a cookie may not have been sent by the Wikify frontend.
+//! Unsafe requests (anything but GET, HEAD, OPTIONS and TRACE) carrying a
+//! session cookie are therefore only accepted when
+//! - they come from the server's own origin or an allowed CORS origin, going
+//!   by the `Sec-Fetch-Site` and `Origin` headers, and
+//! - they repeat the value of the `wikify_csrf` cookie in the `X-CSRF-Token`
+//!   header, which other sites cannot read (the double-submit pattern).
+//!
+//! Requests authenticated with an `Authorization` header, or without session
+//! cookies, are not affected: browsers never add those credentials by
+//! themselves. The `wikify_csrf` cookie is issued with any response to a
+//! request that lacks it.
+
+use crate::{cors, WebConfig};
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{AUTHORIZATION, COOKIE, HOST, ORIGIN, SET_COOKIE},
+        HeaderMap, HeaderValue, Method, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Cookie holding the CSRF token
+pub const CSRF_COOKIE: &str = "wikify_csrf";
+
+/// Header unsafe requests repeat the CSRF token in
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Cookies that identify a user
+pub const SESSION_COOKIES: &[&str] = &["wikify_user_id"];
+
+/// Reject cross-site state-changing requests made with session cookies
+pub async fn csrf_middleware(
+    State(config): State<Arc<WebConfig>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    if needs_protection(request.method(), headers) {
+        if let Err(reason) = check_request(&config, headers) {
+            warn!(
+                "Rejected {} {}: {}",
+                request.method(),
+                request.uri().path(),
+                reason
+            );
+            return (
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "error": "csrf_rejected",
+                    "message": reason,
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    let has_token = cookie_value(headers, CSRF_COOKIE).is_some();
+    let mut response = next.run(request).await;
+    if !has_token {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        if let Ok(cookie) = HeaderValue::from_str(&csrf_cookie(&config, &token)) {
+            response.headers_mut().append(SET_COOKIE, cookie);
+        }
+    }
+    response
+}
+
+/// Whether a request changes state on behalf of a user identified by cookie
+fn needs_protection(method: &Method, headers: &HeaderMap) -> bool {
+    let safe = matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    );
+    !safe
+        && !headers.contains_key(AUTHORIZATION)
+        && SESSION_COOKIES
+            .iter()
+            .any(|name| cookie_value(headers, name).is_some())
+}
+
+/// Check the origin and the CSRF token of a request
+fn check_request(config: &WebConfig, headers: &HeaderMap) -> Result<(), &'static str> {
+    let origin = headers.get(ORIGIN).and_then(|v| v.to_str().ok());
+    let trusted_origin = origin.is_some_and(|origin| {
+        is_same_origin(config, headers, origin) || is_allowed_origin(config, origin)
+    });
+
+    let fetch_site = headers.get("sec-fetch-site").and_then(|v| v.to_str().ok());
+    if fetch_site == Some("cross-site") && !trusted_origin {
+        return Err("cross-site request");
+    }
+    if origin.is_some() && !trusted_origin {
+        return Err("request from an origin that is not allowed");
+    }
+
+    let cookie = cookie_value(headers, CSRF_COOKIE).ok_or("missing CSRF cookie")?;
+    let header = headers
+        .get(CSRF_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("missing X-CSRF-Token header")?;
+    if !constant_time_eq(cookie.as_bytes(), header.as_bytes()) {
+        return Err("CSRF token does not match");
+    }
+    Ok(())
+}
+
+/// Whether an origin names the host the request was sent to
+fn is_same_origin(config: &WebConfig, headers: &HeaderMap, origin: &str) -> bool {
+    let host = config
+        .trust_proxy_headers
+        .then(|| headers.get("x-forwarded-host"))
+        .flatten()
+        .or_else(|| headers.get(HOST))
+        .and_then(|v| v.to_str().ok());
+    let origin_host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"));
+    matches!((origin_host, host), (Some(a), Some(b)) if a.eq_ignore_ascii_case(b))
+}
+
+fn is_allowed_origin(config: &WebConfig, origin: &str) -> bool {
+    cors::allowed_origins(config)
+        .iter()
+        .any(|allowed| *allowed == origin || (*allowed == "*" && config.dev_mode))
+}
+
+/// Value of a cookie sent with a request
+fn cookie_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(key, value)| *key == name && !value.is_empty())
+        .map(|(_, value)| value)
+}
+
+/// `Set-Cookie` value for a CSRF token
+///
+/// The frontend reads the token to send it back, so the cookie is not
+/// `HttpOnly`; it is never sent with cross-site requests.
+fn csrf_cookie(config: &WebConfig, token: &str) -> String {
+    let path = if config.base_path.is_empty() {
+        "/"
+    } else {
+        config.base_path.as_str()
+    };
+    let mut cookie = format!("{}={}; Path={}; SameSite=Strict", CSRF_COOKIE, token, path);
+    if config.secure_cookies() {
+        cookie.push_str("; Secure");
+    }
+    cookie
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/api/repositories", post(|| async { "created" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(WebConfig::default()),
+                csrf_middleware,
+            ))
+    }
+
+    fn post_request(headers: &[(&str, &str)]) -> Request {
+        let mut builder = Request::post("/api/repositories").header(HOST, "wiki.example.com");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    async fn status(headers: &[(&str, &str)]) -> StatusCode {
+        app().oneshot(post_request(headers)).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_cookie_requests_need_the_token() {
+        let session = "wikify_user_id=alice; wikify_csrf=token-1";
+        assert_eq!(status(&[("cookie", session)]).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(&[("cookie", session), (CSRF_HEADER, "token-2")]).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&[
+                ("cookie", session),
+                (CSRF_HEADER, "token-1"),
+                ("origin", "https://wiki.example.com"),
+                ("sec-fetch-site", "same-origin"),
+            ])
+            .await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cross_site_requests_are_rejected() {
+        let session = "wikify_user_id=alice; wikify_csrf=token-1";
+        assert_eq!(
+            status(&[
+                ("cookie", session),
+                (CSRF_HEADER, "token-1"),
+                ("origin", "https://evil.example.net"),
+                ("sec-fetch-site", "cross-site"),
+            ])
+            .await,
+            StatusCode::FORBIDDEN
+        );
+        // The Vite dev server is an allowed CORS origin by default
+        assert_eq!(
+            status(&[
+                ("cookie", session),
+                (CSRF_HEADER, "token-1"),
+                ("origin", "http://localhost:5173"),
+                ("sec-fetch-site", "cross-site"),
+            ])
+            .await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_session_cookies_pass() {
+        assert_eq!(status(&[]).await, StatusCode::OK);
+        assert_eq!(
+            status(&[
+                ("cookie", "wikify_user_id=alice"),
+                ("authorization", "Bearer token"),
+            ])
+            .await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_token_cookie_is_issued() {
+        let response = app().oneshot(post_request(&[])).await.unwrap();
+        let cookie = response.headers()[SET_COOKIE].to_str().unwrap();
+        assert!(cookie.starts_with("wikify_csrf="));
+        assert!(cookie.contains("SameSite=Strict"));
+        assert!(!cookie.contains("Secure"));
+
+        let response = app()
+            .oneshot(post_request(&[("cookie", "wikify_csrf=token-1")]))
+            .await
+            .unwrap();
+        assert!(response.headers().get(SET_COOKIE).is_none());
+    }
+}
@@ -0,0 +1,177 @@
+//! Serving the web frontend
+//!
+//! Requests that match no API route are answered with the single page app.
+//! Files are looked up in `static_dir` first, so a frontend rebuilt with
+//! `vite build --watch` is picked up in development without recompiling the
+//! server. With the `embed-frontend` feature, the production build in
+//! `web/dist` is compiled into the binary and served next; otherwise a
+//! built-in landing page is shown.
+//!
+//! Paths without a file (client-side routes such as `/wiki/abc`) get the
+//! app's `index.html`, where the client router takes over.
+
+use crate::AppState;
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use std::path::{Component, Path, PathBuf};
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+
+/// Placeholder in the built-in page replaced with the configured path prefix
+const BASE_PATH_PLACEHOLDER: &str = "__WIKIFY_BASE_PATH__";
+
+/// Page shown when no frontend build is available
+const BUILTIN_INDEX: &str = include_str!("../static/index.html");
+
+/// Production build of the frontend, compiled into the binary
+#[cfg(feature = "embed-frontend")]
+#[derive(rust_embed::RustEmbed)]
+#[folder = "../web/dist"]
+#[allow_missing = true]
+struct EmbeddedFrontend;
+
+/// Serve a frontend file, or the app's index page for client-side routes
+pub async fn serve_frontend(State(state): State<AppState>, request: Request) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    // Nesting under the path prefix already removed it from the URI
+    let Some(path) = asset_path(request.uri().path()) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    if let Some(static_dir) = &state.config.static_dir {
+        let file = Path::new(static_dir).join(&path);
+        if !path.as_os_str().is_empty() && file.is_file() {
+            let Ok(response) = ServeFile::new(file).oneshot(request).await;
+            return response.into_response();
+        }
+    }
+
+    #[cfg(feature = "embed-frontend")]
+    if let Some(response) = embedded_file(&path, request.headers()) {
+        return response;
+    }
+
+    index_page(&state).await
+}
+
+/// Relative file path a request URI refers to, rejecting paths that would
+/// leave the frontend directory
+fn asset_path(uri_path: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(uri_path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// The app's `index.html` from `static_dir`, the embedded build or the
+/// built-in page, in that order
+async fn index_page(state: &AppState) -> Response {
+    let mut page = None;
+    if let Some(static_dir) = &state.config.static_dir {
+        page = tokio::fs::read_to_string(Path::new(static_dir).join("index.html"))
+            .await
+            .ok();
+    }
+
+    #[cfg(feature = "embed-frontend")]
+    if page.is_none() {
+        page = EmbeddedFrontend::get("index.html")
+            .map(|file| String::from_utf8_lossy(&file.data).into_owned());
+    }
+
+    let page = page
+        .unwrap_or_else(|| BUILTIN_INDEX.to_string())
+        .replace(BASE_PATH_PLACEHOLDER, &state.config.base_path);
+
+    // The page names the hashed assets of the current build, so it must be
+    // revalidated after every deployment
+    (
+        [(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"))],
+        Html(page),
+    )
+        .into_response()
+}
+
+/// A file of the embedded build, tagged with its hash
+#[cfg(feature = "embed-frontend")]
+fn embedded_file(path: &Path, headers: &axum::http::HeaderMap) -> Option<Response> {
+    let name = path.to_str()?.replace('\\', "/");
+    if name.is_empty() || name == "index.html" {
+        return None;
+    }
+    let file = EmbeddedFrontend::get(&name)?;
+
+    let hex: String = file.metadata.sha256_hash()[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let etag = format!("\"{}\"", hex);
+    let etag_header = HeaderValue::from_str(&etag).expect("hex entity tags are valid headers");
+    // Vite puts a content hash in the names of the files under `assets/`
+    let cache_control = if name.starts_with("assets/") {
+        HeaderValue::from_static("public, max-age=31536000, immutable")
+    } else {
+        HeaderValue::from_static("no-cache")
+    };
+
+    if crate::handlers::etag::if_none_match(headers, &etag) {
+        return Some(
+            (
+                StatusCode::NOT_MODIFIED,
+                [
+                    (header::ETAG, etag_header),
+                    (header::CACHE_CONTROL, cache_control),
+                ],
+            )
+                .into_response(),
+        );
+    }
+
+    let content_type = mime_guess::from_path(&name).first_or_octet_stream();
+    let content_type =
+        HeaderValue::from_str(content_type.as_ref()).expect("MIME types are valid headers");
+    Some(
+        (
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::ETAG, etag_header),
+                (header::CACHE_CONTROL, cache_control),
+            ],
+            file.data.into_owned(),
+        )
+            .into_response(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_asset_path_is_relative() {
+        assert_eq!(
+            asset_path("/assets/index-abc.js"),
+            Some(PathBuf::from("assets/index-abc.js"))
+        );
+        assert_eq!(asset_path("/"), Some(PathBuf::new()));
+        assert_eq!(
+            asset_path("/wiki/repo-1"),
+            Some(PathBuf::from("wiki/repo-1"))
+        );
+    }
+
+    #[test]
+    fn test_asset_path_rejects_parent_directories() {
+        assert_eq!(asset_path("/assets/../../etc/passwd"), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! gRPC API
+//!
+//! Serves the repository management and query operations of the REST API
+//! over gRPC, as defined in `proto/wikify.proto`, with the answer to a query
+//! optionally streamed as it is generated. Callers authenticate with the
+//! same access tokens and API keys as HTTP clients, passed in the
+//! `authorization` metadata entry.
+
+use crate::{
+    auth::User,
+    handlers::repository::{detect_repo_type, spawn_progress_forwarding},
+    middleware::{authenticate, REQUEST_ID_HEADER},
+    AppState,
+};
+use std::pin::Pin;
+use tokio::net::TcpListener;
+use tokio_stream::{
+    wrappers::{TcpListenerStream, UnboundedReceiverStream},
+    Stream, StreamExt,
+};
+use tonic::{metadata::MetadataMap, Request, Response, Status};
+use tracing::{info, warn};
+use wikify_applications::{
+    ApplicationError, IndexingStatus, PageRequest, Permission, PermissionContext, QueryChunkType,
+    QueryStreamChunk, RepositoryFilter, RepositoryIndex, RepositoryOptions, RepositoryQuery,
+    RepositoryQueryResponse,
+};
+
+/// Messages and service traits generated from `proto/wikify.proto`
+pub mod proto {
+    tonic::include_proto!("wikify.v1");
+}
+
+use proto::{
+    query_chunk::ChunkType,
+    wikify_service_server::{WikifyService, WikifyServiceServer},
+};
+
+/// Serve the gRPC API on `listener` until the server fails
+pub async fn serve(state: AppState, listener: TcpListener) -> Result<(), tonic::transport::Error> {
+    tonic::transport::Server::builder()
+        .add_service(WikifyServiceServer::new(WikifyGrpcService::new(state)))
+        .serve_with_incoming(TcpListenerStream::new(listener))
+        .await
+}
+
+/// Implementation of `wikify.v1.WikifyService` on top of the application layer
+#[derive(Clone)]
+pub struct WikifyGrpcService {
+    state: AppState,
+}
+
+impl WikifyGrpcService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// Permission context of the caller of a request
+    ///
+    /// Like [`crate::auth::ModeAwareUser`], calls without credentials act as
+    /// an anonymous user in open mode and are rejected in the other modes.
+    async fn caller<T>(&self, request: &Request<T>) -> Result<PermissionContext, Status> {
+        let metadata = request.metadata();
+        let auth_header = metadata_str(metadata, "authorization");
+        let open_mode = self
+            .state
+            .config
+            .permission_mode
+            .as_deref()
+            .unwrap_or("open")
+            == "open";
+
+        let user = match auth_header {
+            None if open_mode => {
+                let mut user = User::new(
+                    "anonymous".to_string(),
+                    Some("Anonymous User".to_string()),
+                    vec![
+                        Permission::Query,
+                        Permission::GenerateWiki,
+                        Permission::ManageRepository,
+                    ],
+                );
+                user.client_id = request
+                    .remote_addr()
+                    .map(|addr| format!("ip:{}", addr.ip()));
+                user
+            }
+            _ => authenticate(&self.state, auth_header)
+                .await
+                .map_err(|_| Status::unauthenticated("Authentication required"))?,
+        };
+
+        let request_id = metadata_str(metadata, REQUEST_ID_HEADER)
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let user = user
+            .with_organization(metadata_str(metadata, "x-organization-id").map(str::to_string))
+            .with_request_id(Some(request_id));
+        Ok(user.to_permission_context())
+    }
+}
+
+#[tonic::async_trait]
+impl WikifyService for WikifyGrpcService {
+    async fn add_repository(
+        &self,
+        request: Request<proto::AddRepositoryRequest>,
+    ) -> Result<Response<proto::AddRepositoryResponse>, Status> {
+        let context = self.caller(&request).await?;
+        let request = request.into_inner();
+        if request.url.trim().is_empty() {
+            return Err(Status::invalid_argument("Repository URL is required"));
+        }
+
+        let visibility = request
+            .visibility
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(Status::invalid_argument)?;
+        let options = RepositoryOptions {
+            auto_index: request.auto_index.unwrap_or(true),
+            metadata: (!request.metadata.is_empty()).then_some(request.metadata),
+            api_token: request.access_token,
+            visibility,
+            ..Default::default()
+        };
+        let repo_type = request
+            .repo_type
+            .unwrap_or_else(|| detect_repo_type(&request.url));
+
+        let repository_id = self
+            .state
+            .application
+            .add_repository(&context, request.url.clone(), repo_type, options)
+            .await
+            .map_err(application_status)?;
+        info!(
+            "Repository added over gRPC: {} ({})",
+            repository_id, request.url
+        );
+
+        spawn_progress_forwarding(
+            &self.state,
+            repository_id.clone(),
+            request.auto_generate_wiki.unwrap_or(true),
+        );
+
+        Ok(Response::new(proto::AddRepositoryResponse {
+            repository_id,
+        }))
+    }
+
+    async fn get_repository(
+        &self,
+        request: Request<proto::GetRepositoryRequest>,
+    ) -> Result<Response<proto::Repository>, Status> {
+        let context = self.caller(&request).await?;
+        let repository = self
+            .state
+            .application
+            .get_repository(&context, &request.get_ref().repository_id)
+            .await
+            .map_err(application_status)?;
+        Ok(Response::new(repository_message(repository)))
+    }
+
+    async fn list_repositories(
+        &self,
+        request: Request<proto::ListRepositoriesRequest>,
+    ) -> Result<Response<proto::ListRepositoriesResponse>, Status> {
+        let context = self.caller(&request).await?;
+        let request = request.into_inner();
+
+        let filter = RepositoryFilter {
+            status: request
+                .status
+                .as_deref()
+                .map(str::parse::<IndexingStatus>)
+                .transpose()
+                .map_err(Status::invalid_argument)?,
+            name: request.name,
+            ..Default::default()
+        };
+        let page = PageRequest {
+            limit: request.limit.map(|limit| limit as usize),
+            offset: request.offset as usize,
+        };
+
+        let page = self
+            .state
+            .application
+            .list_repositories(&context, &filter, page)
+            .await
+            .map_err(application_status)?;
+        Ok(Response::new(proto::ListRepositoriesResponse {
+            repositories: page.items.into_iter().map(repository_message).collect(),
+            total: page.total as u64,
+        }))
+    }
+
+    async fn delete_repository(
+        &self,
+        request: Request<proto::DeleteRepositoryRequest>,
+    ) -> Result<Response<proto::DeleteRepositoryResponse>, Status> {
+        let context = self.caller(&request).await?;
+        self.state
+            .application
+            .delete_repository(&context, &request.get_ref().repository_id)
+            .await
+            .map_err(application_status)?;
+        Ok(Response::new(proto::DeleteRepositoryResponse {}))
+    }
+
+    async fn reindex_repository(
+        &self,
+        request: Request<proto::ReindexRepositoryRequest>,
+    ) -> Result<Response<proto::ReindexRepositoryResponse>, Status> {
+        let context = self.caller(&request).await?;
+        self.state
+            .application
+            .reindex_repository(&context, &request.get_ref().repository_id)
+            .await
+            .map_err(application_status)?;
+        Ok(Response::new(proto::ReindexRepositoryResponse {}))
+    }
+
+    async fn query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<proto::QueryResponse>, Status> {
+        let context = self.caller(&request).await?;
+        let (repository_id, query) = repository_query(request.into_inner())?;
+        let response = self
+            .state
+            .application
+            .query_repository(&context, &repository_id, query)
+            .await
+            .map_err(application_status)?;
+        Ok(Response::new(query_response_message(response)))
+    }
+
+    type StreamQueryStream = Pin<Box<dyn Stream<Item = Result<proto::QueryChunk, Status>> + Send>>;
+
+    async fn stream_query(
+        &self,
+        request: Request<proto::QueryRequest>,
+    ) -> Result<Response<Self::StreamQueryStream>, Status> {
+        let context = self.caller(&request).await?;
+        let (repository_id, query) = repository_query(request.into_inner())?;
+        let receiver = self
+            .state
+            .application
+            .stream_query_repository(&context, &repository_id, query)
+            .await
+            .map_err(application_status)?;
+
+        let chunks = UnboundedReceiverStream::new(receiver).map(|chunk| Ok(chunk_message(chunk)));
+        Ok(Response::new(Box::pin(chunks)))
+    }
+}
+
+fn metadata_str<'a>(metadata: &'a MetadataMap, key: &str) -> Option<&'a str> {
+    metadata
+        .get(key)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// gRPC status of an application error
+fn application_status(error: ApplicationError) -> Status {
+    let message = error.to_string();
+    match error {
+        ApplicationError::Permission { .. } => Status::permission_denied(message),
+        ApplicationError::NotFound { .. } => Status::not_found(message),
+        ApplicationError::Config { .. } => Status::invalid_argument(message),
+        ApplicationError::RateLimited { .. } => Status::resource_exhausted(message),
+        _ if message.contains("already being indexed")
+            || message.contains("already in progress") =>
+        {
+            Status::already_exists(message)
+        }
+        _ => {
+            warn!("gRPC call failed: {}", message);
+            Status::internal(message)
+        }
+    }
+}
+
+fn repository_query(request: proto::QueryRequest) -> Result<(String, RepositoryQuery), Status> {
+    if request.question.trim().is_empty() {
+        return Err(Status::invalid_argument("Question is required"));
+    }
+    let query = RepositoryQuery {
+        question: request.question,
+        max_results: request.max_results.map(|n| n as usize),
+        parameters: None,
+    };
+    Ok((request.repository_id, query))
+}
+
+fn repository_message(repository: RepositoryIndex) -> proto::Repository {
+    // Same status names as the REST API
+    let status = match repository.status {
+        IndexingStatus::Pending => "pending",
+        IndexingStatus::Indexing => "indexing",
+        IndexingStatus::Completed => "indexed",
+        IndexingStatus::Failed => "failed",
+        IndexingStatus::Cancelled => "cancelled",
+    };
+
+    proto::Repository {
+        id: repository.id,
+        url: repository.url,
+        repo_type: repository.repo_type,
+        status: status.to_string(),
+        progress: repository.progress,
+        created_at: repository.created_at.to_rfc3339(),
+        indexed_at: repository.indexed_at.map(|at| at.to_rfc3339()),
+        updated_at: repository.updated_at.to_rfc3339(),
+        owner_id: repository.owner_id,
+        organization_id: repository.organization_id,
+        visibility: repository.visibility.to_string(),
+        metadata: repository.metadata,
+    }
+}
+
+fn query_response_message(response: RepositoryQueryResponse) -> proto::QueryResponse {
+    proto::QueryResponse {
+        answer: response.answer,
+        sources: response.sources,
+        confidence: response.confidence,
+        metadata: response.metadata,
+    }
+}
+
+fn chunk_message(chunk: QueryStreamChunk) -> proto::QueryChunk {
+    let chunk_type = match chunk.chunk_type {
+        QueryChunkType::Content => ChunkType::Content,
+        QueryChunkType::Source => ChunkType::Source,
+        QueryChunkType::Error => ChunkType::Error,
+        QueryChunkType::Complete => ChunkType::Complete,
+    };
+
+    proto::QueryChunk {
+        chunk_type: chunk_type as i32,
+        content: chunk.content,
+        is_final: chunk.is_final,
+        sources: chunk.sources.unwrap_or_default(),
+        metadata: chunk.metadata.unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_message_conversions() {
+        let repository = RepositoryIndex {
+            id: "repo-1".to_string(),
+            url: "https://github.com/owner/repo".to_string(),
+            repo_type: "github".to_string(),
+            status: IndexingStatus::Completed,
+            progress: 1.0,
+            created_at: Utc::now(),
+            indexed_at: None,
+            updated_at: Utc::now(),
+            owner_id: Some("user-1".to_string()),
+            organization_id: None,
+            visibility: Default::default(),
+            share_grants: Vec::new(),
+            disk_usage: None,
+            metadata: HashMap::new(),
+        };
+        let message = repository_message(repository);
+        assert_eq!(message.status, "indexed");
+        assert_eq!(message.visibility, "private");
+        assert_eq!(message.indexed_at, None);
+
+        let chunk = chunk_message(QueryStreamChunk {
+            chunk_type: QueryChunkType::Complete,
+            content: String::new(),
+            is_final: true,
+            sources: Some(vec!["src/lib.rs".to_string()]),
+            metadata: None,
+        });
+        assert_eq!(chunk.chunk_type(), ChunkType::Complete);
+        assert_eq!(chunk.sources, vec!["src/lib.rs"]);
+
+        let empty = proto::QueryRequest {
+            repository_id: "repo-1".to_string(),
+            question: "  ".to_string(),
+            max_results: None,
+        };
+        assert_eq!(
+            repository_query(empty).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+}
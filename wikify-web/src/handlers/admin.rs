@@ -0,0 +1,287 @@
+//! Administration handlers
+
+use super::types::{
+    StorageGcRequest, UsageExportQuery, UsageQuery, WorkspaceGcRequest, WorkspaceGcResponse,
+};
+use crate::{auth::AdminUser, AppState};
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+    Json as JsonExtractor,
+};
+use std::time::Duration;
+use tracing::{error, info};
+use wikify_applications::{
+    StorageGcReport, StorageUsageReport, SystemStats, UsageFilter, UsageReport,
+};
+
+/// Garbage collect the clone workspace
+#[utoipa::path(
+    post,
+    path = "/api/admin/workspace/gc",
+    tag = "Admin",
+    summary = "Garbage collect cloned repositories",
+    description = "Remove cloned repositories according to a retention policy (max total size, max age, LRU)",
+    request_body = WorkspaceGcRequest,
+    responses(
+        (status = 200, description = "Garbage collection finished", body = WorkspaceGcResponse),
+        (status = 403, description = "Admin permission required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn collect_workspace_garbage(
+    State(state): State<AppState>,
+    AdminUser(user): AdminUser,
+    request: Option<JsonExtractor<WorkspaceGcRequest>>,
+) -> Result<Json<WorkspaceGcResponse>, StatusCode> {
+    let request = request.map(|JsonExtractor(r)| r).unwrap_or_default();
+    info!(
+        "Collecting clone workspace garbage (admin user: {}, dry_run: {})",
+        user.id, request.dry_run
+    );
+
+    let mut policy = wikify_repo::RetentionPolicy::default();
+    if let Some(mb) = request.max_total_size_mb {
+        policy.max_total_size_bytes = Some(mb * 1024 * 1024);
+    }
+    if let Some(days) = request.max_age_days {
+        policy.max_age = Some(Duration::from_secs(days * 24 * 60 * 60));
+    }
+    if request.max_entries.is_some() {
+        policy.max_entries = request.max_entries;
+    }
+
+    let context = user.to_permission_context();
+    match state
+        .application
+        .collect_workspace_garbage(&context, policy, request.dry_run)
+        .await
+    {
+        Ok(report) => Ok(Json(WorkspaceGcResponse {
+            status: "success".to_string(),
+            dry_run: report.dry_run,
+            scanned: report.scanned,
+            removed: report
+                .removed
+                .iter()
+                .map(|p| p.to_string_lossy().to_string())
+                .collect(),
+            freed_bytes: report.freed_bytes,
+            remaining_bytes: report.remaining_bytes,
+        })),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Clone workspace garbage collection failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Garbage collect orphaned storage
+#[utoipa::path(
+    post,
+    path = "/api/admin/storage/gc",
+    tag = "Admin",
+    summary = "Garbage collect orphaned storage",
+    description = "Remove clones no repository uses, stale wiki cache entries and vector indexes of deleted repositories, and report the reclaimed space",
+    request_body = StorageGcRequest,
+    responses(
+        (status = 200, description = "Garbage collection finished", body = StorageGcReport),
+        (status = 403, description = "Admin permission required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn collect_storage_garbage(
+    State(state): State<AppState>,
+    AdminUser(user): AdminUser,
+    request: Option<JsonExtractor<StorageGcRequest>>,
+) -> Result<Json<StorageGcReport>, StatusCode> {
+    let request = request.map(|JsonExtractor(r)| r).unwrap_or_default();
+    info!(
+        "Collecting storage garbage (admin user: {}, dry_run: {})",
+        user.id, request.dry_run
+    );
+
+    let mut config = state.config.storage_gc();
+    config.dry_run = request.dry_run;
+
+    let context = user.to_permission_context();
+    match state
+        .application
+        .collect_storage_garbage(&context, config)
+        .await
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Storage garbage collection failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Get server statistics
+#[utoipa::path(
+    get,
+    path = "/api/admin/stats",
+    tag = "Admin",
+    summary = "Get server statistics",
+    description = "Repository counts by status, loaded index sizes, indexing and query volume, latencies and failure rates since the server started, and disk usage",
+    responses(
+        (status = 200, description = "Server statistics", body = SystemStats),
+        (status = 403, description = "Admin permission required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_stats(
+    State(state): State<AppState>,
+    AdminUser(user): AdminUser,
+) -> Result<Json<SystemStats>, StatusCode> {
+    let context = user.to_permission_context();
+    match state
+        .application
+        .system_stats(&context, state.config.database_path())
+        .await
+    {
+        Ok(stats) => Ok(Json(stats)),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to collect server statistics: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Get disk usage per repository
+#[utoipa::path(
+    get,
+    path = "/api/admin/storage",
+    tag = "Admin",
+    summary = "Get disk usage",
+    description = "Measure the clone, vector index and wiki cache size of every repository, the total of all stored artifacts and whether the indexing disk quota is exceeded",
+    responses(
+        (status = 200, description = "Disk usage", body = StorageUsageReport),
+        (status = 403, description = "Admin permission required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_storage_usage(
+    State(state): State<AppState>,
+    AdminUser(user): AdminUser,
+) -> Result<Json<StorageUsageReport>, StatusCode> {
+    let context = user.to_permission_context();
+    match state.application.storage_usage(&context).await {
+        Ok(report) => Ok(Json(report)),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to measure disk usage: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Get token usage and estimated cost
+#[utoipa::path(
+    get,
+    path = "/api/admin/usage",
+    tag = "Admin",
+    summary = "Get model usage",
+    description = "Token usage and estimated cost of embedding and generation calls, grouped by user, repository or day, with the daily budgets exceeded today",
+    params(UsageQuery),
+    responses(
+        (status = 200, description = "Usage report", body = UsageReport),
+        (status = 400, description = "since is after until"),
+        (status = 403, description = "Admin permission required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_usage(
+    State(state): State<AppState>,
+    AdminUser(user): AdminUser,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageReport>, StatusCode> {
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since > until {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let filter = UsageFilter {
+        user_id: query.user_id,
+        repository_id: query.repository_id,
+        since: query.since,
+        until: query.until,
+    };
+    let context = user.to_permission_context();
+    match state
+        .application
+        .usage_report(&context, &filter, query.group_by.unwrap_or_default())
+        .await
+    {
+        Ok(report) => Ok(Json(report)),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to build usage report: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Download usage records
+#[utoipa::path(
+    get,
+    path = "/api/admin/usage/export",
+    tag = "Admin",
+    summary = "Export model usage",
+    description = "Download usage records as CSV or JSON Lines, one row per embedding or generation call",
+    params(UsageExportQuery),
+    responses(
+        (status = 200, description = "Usage records", content_type = "text/csv"),
+        (status = 400, description = "since is after until"),
+        (status = 403, description = "Admin permission required"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn export_usage(
+    State(state): State<AppState>,
+    AdminUser(user): AdminUser,
+    Query(query): Query<UsageExportQuery>,
+) -> Result<Response, StatusCode> {
+    if let (Some(since), Some(until)) = (query.since, query.until) {
+        if since > until {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let format = query.format.unwrap_or_default();
+    let filter = UsageFilter {
+        user_id: query.user_id,
+        repository_id: query.repository_id,
+        since: query.since,
+        until: query.until,
+    };
+    let context = user.to_permission_context();
+    match state
+        .application
+        .export_usage(&context, &filter, format)
+        .await
+    {
+        Ok(body) => Ok((
+            [
+                (header::CONTENT_TYPE, format.content_type().to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"usage.{}\"", format.extension()),
+                ),
+            ],
+            body,
+        )
+            .into_response()),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to export usage: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
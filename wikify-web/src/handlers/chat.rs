@@ -1,20 +1,97 @@
 //! Chat and RAG query handlers
 
-use super::types::{ChatQueryRequest, ChatQueryResponse, SourceDocument};
+#[cfg(feature = "sqlite")]
+use super::types::{
+    ChatFeedbackEntry, ChatFeedbackListResponse, ChatFeedbackQuery, ChatFeedbackRequest,
+    ChatFeedbackResponse, QueryHistoryEntry, QueryHistoryResponse,
+};
+use super::types::{
+    ChatQueryRequest, ChatQueryResponse, ChatSessionListResponse, ChatSessionMessageRequest,
+    ChatSessionQuery, ChatSessionResponse, CreateChatSessionRequest, RegenerateAnswerRequest,
+    RenameChatSessionRequest, SourceDocument,
+};
 use crate::{auth::ModeAwareUser, AppState};
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Json},
+    response::{
+        sse::{Event, KeepAlive},
+        Json, Sse,
+    },
     Json as JsonExtractor,
 };
-use tracing::{error, info};
+use futures_util::stream::{self, Stream};
+use std::convert::Infallible;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tracing::{error, info, warn};
+use wikify_applications::{ApplicationError, QueryChunkType, QueryStreamChunk};
 
 /// Helper function to convert User to PermissionContext for application layer
 fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::PermissionContext {
     user.to_permission_context()
 }
 
+/// Map an application error to the status code returned by the chat session API
+fn session_error_status(error: ApplicationError) -> StatusCode {
+    match error {
+        ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+        ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+        ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+        ApplicationError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        e => {
+            error!("Chat session request failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Convert the source paths of a repository answer to source documents
+pub(crate) fn to_source_documents(sources: Vec<String>) -> Vec<SourceDocument> {
+    sources
+        .into_iter()
+        .map(|source_path| SourceDocument {
+            file_path: source_path.clone(),
+            content: format!("Source: {}", source_path), // TODO: Get actual content
+            similarity_score: 0.8,                       // TODO: Get actual similarity score
+            start_line: None,                            // TODO: Get actual line information
+            end_line: None,                              // TODO: Get actual line information
+            chunk_index: None,                           // TODO: Get actual chunk information
+            metadata: None,                              // TODO: Get actual metadata
+        })
+        .collect()
+}
+
+/// Source documents of a repository answer, with the cited chunks where the
+/// answer has them
+fn answer_source_documents(
+    sources: Vec<String>,
+    citations: Vec<wikify_applications::SourceCitation>,
+) -> Vec<SourceDocument> {
+    if citations.is_empty() {
+        return to_source_documents(sources);
+    }
+    citations
+        .into_iter()
+        .map(|citation| SourceDocument {
+            file_path: citation.file_path,
+            content: citation.content,
+            similarity_score: citation.score as f64,
+            start_line: citation.start_line.map(|line| line as u32),
+            end_line: citation.end_line.map(|line| line as u32),
+            chunk_index: None,
+            metadata: None,
+        })
+        .collect()
+}
+
+/// Branch of a session answer, as reported in the response metadata
+fn answer_branch(response: &wikify_applications::RepositoryQueryResponse) -> Option<usize> {
+    response
+        .metadata
+        .get("branch")
+        .and_then(|branch| branch.parse().ok())
+}
+
 /// Helper function to save query to database
 #[cfg(feature = "sqlite")]
 async fn save_query_to_database(
@@ -22,7 +99,7 @@ async fn save_query_to_database(
     repository_id: &str,
     question: &str,
     answer: &str,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let query = crate::simple_database::SimpleQuery {
         id: uuid::Uuid::new_v4().to_string(),
         repository_id: Some(repository_id.to_string()),
@@ -34,7 +111,30 @@ async fn save_query_to_database(
     database
         .save_query(&query)
         .await
-        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+    Ok(query.id)
+}
+
+/// Record a question and its answer in the query history when a database is
+/// available, returning the ID of the query record
+pub(crate) async fn record_query(
+    state: &AppState,
+    repository_id: &str,
+    question: &str,
+    answer: &str,
+) -> Option<String> {
+    #[cfg(feature = "sqlite")]
+    if let Some(database) = &state.database {
+        match save_query_to_database(database, repository_id, question, answer).await {
+            Ok(query_id) => return Some(query_id),
+            Err(e) => warn!("Failed to save query to database: {}", e),
+        }
+    }
+
+    #[cfg(not(feature = "sqlite"))]
+    let _ = (state, repository_id, question, answer);
+
+    None
 }
 
 /// Handle chat queries
@@ -43,10 +143,13 @@ async fn save_query_to_database(
     path = "/api/chat",
     tag = "Chat",
     summary = "Ask a question",
-    description = "Ask a question about the repository using RAG (Retrieval-Augmented Generation)",
+    description = "Ask a question about the repository using RAG (Retrieval-Augmented Generation). The question and answer are recorded in the chat session given by `session_id`, or in a new session whose ID is returned.",
     request_body = ChatQueryRequest,
     responses(
-        (status = 200, description = "Question answered successfully", body = ChatQueryResponse)
+        (status = 200, description = "Question answered successfully", body = ChatQueryResponse),
+        (status = 400, description = "The session belongs to another repository"),
+        (status = 404, description = "Chat session not found"),
+        (status = 429, description = "Anonymous query limit reached")
     )
 )]
 pub async fn chat_query(
@@ -62,65 +165,91 @@ pub async fn chat_query(
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
 
-    // No session activity update needed for repository-based queries
-
-    // Repository-based query only
     let repository_id = &request.repository_id;
 
     let repo_query = wikify_applications::RepositoryQuery {
         question: request.question.clone(),
         max_results: request.max_results,
-        parameters: None,
+        parameters: request
+            .context
+            .clone()
+            .map(|context| std::collections::HashMap::from([("attachment".to_string(), context)])),
     };
 
-    match state
-        .application
-        .query_repository(&context, &repository_id, repo_query)
-        .await
-    {
+    // Continue the given session, or start a new one for this question
+    let session_id = match &request.session_id {
+        Some(session_id) => {
+            let session = state
+                .application
+                .get_chat_session(&context, session_id)
+                .await
+                .map_err(session_error_status)?;
+            if session.repository_id != *repository_id {
+                return Err(StatusCode::BAD_REQUEST);
+            }
+            Some(session.id)
+        }
+        None => match state
+            .application
+            .create_chat_session(&context, repository_id, None)
+            .await
+        {
+            Ok(session) => Some(session.id),
+            Err(e) => {
+                warn!("Failed to create chat session: {}", e);
+                None
+            }
+        },
+    };
+
+    let result = match &session_id {
+        Some(session_id) => {
+            state
+                .application
+                .query_chat_session(&context, session_id, repo_query)
+                .await
+        }
+        None => {
+            state
+                .application
+                .query_repository(&context, repository_id, repo_query)
+                .await
+        }
+    };
+
+    match result {
         Ok(repo_response) => {
             info!("✅ Repository query completed for: {}", repository_id);
 
-            // Convert repository response to chat response format
-            let sources: Vec<SourceDocument> = repo_response
-                .sources
-                .into_iter()
-                .map(|source_path| SourceDocument {
-                    file_path: source_path.clone(),
-                    content: format!("Source: {}", source_path), // TODO: Get actual content
-                    similarity_score: 0.8, // TODO: Get actual similarity score
-                    start_line: None,      // TODO: Get actual line information
-                    end_line: None,        // TODO: Get actual line information
-                    chunk_index: None,     // TODO: Get actual chunk information
-                    metadata: None,        // TODO: Get actual metadata
-                })
-                .collect();
+            // Save query to database if available
+            let query_id = record_query(
+                &state,
+                repository_id,
+                &request.question,
+                &repo_response.answer,
+            )
+            .await;
 
             let response = ChatQueryResponse {
+                branch: answer_branch(&repo_response),
                 answer: repo_response.answer,
-                sources,
+                sources: answer_source_documents(repo_response.sources, repo_response.citations),
                 repository_id: repository_id.clone(),
+                session_id: session_id.clone(),
+                query_id,
                 timestamp: chrono::Utc::now(),
             };
 
-            // Save query to database if available
-            #[cfg(feature = "sqlite")]
-            if let Some(database) = &state.database {
-                if let Err(e) = save_query_to_database(
-                    database,
-                    &response.repository_id,
-                    &request.question,
-                    &response.answer,
-                )
-                .await
-                {
-                    tracing::warn!("Failed to save query to database: {}", e);
-                }
-            }
-
             info!("Chat query completed successfully");
             Ok(Json(response))
         }
+        Err(ApplicationError::RateLimited { message }) => {
+            warn!(
+                "Repository query throttled for {}: {}",
+                repository_id, message
+            );
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        }
         Err(e) => {
             error!("❌ Repository query failed for {}: {}", repository_id, e);
 
@@ -130,25 +259,17 @@ pub async fn chat_query(
             );
 
             // Save failed query to database if available
-            #[cfg(feature = "sqlite")]
-            if let Some(database) = &state.database {
-                if let Err(db_e) = save_query_to_database(
-                    database,
-                    repository_id,
-                    &request.question,
-                    &error_answer,
-                )
-                .await
-                {
-                    tracing::warn!("Failed to save failed query to database: {}", db_e);
-                }
-            }
+            let query_id =
+                record_query(&state, repository_id, &request.question, &error_answer).await;
 
             // Return error response
             let response = ChatQueryResponse {
                 answer: error_answer,
                 sources: vec![],
                 repository_id: repository_id.clone(),
+                session_id,
+                branch: None,
+                query_id,
                 timestamp: chrono::Utc::now(),
             };
 
@@ -157,6 +278,306 @@ pub async fn chat_query(
     }
 }
 
+/// Create chat session
+#[utoipa::path(
+    post,
+    path = "/api/chat/sessions",
+    tag = "Chat",
+    summary = "Create chat session",
+    description = "Start an empty chat session about a repository. Without a title, the first question becomes the title.",
+    request_body = CreateChatSessionRequest,
+    responses(
+        (status = 201, description = "Chat session created successfully", body = ChatSessionResponse),
+        (status = 400, description = "Invalid title"),
+        (status = 403, description = "Access to the repository denied"),
+        (status = 404, description = "Repository not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_chat_session(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    JsonExtractor(request): JsonExtractor<CreateChatSessionRequest>,
+) -> Result<(StatusCode, Json<ChatSessionResponse>), StatusCode> {
+    info!(
+        "Creating chat session for repository {} (user: {})",
+        request.repository_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+    let session = state
+        .application
+        .create_chat_session(&context, &request.repository_id, request.title)
+        .await
+        .map_err(session_error_status)?;
+
+    Ok((StatusCode::CREATED, Json(session.into())))
+}
+
+/// List chat sessions
+#[utoipa::path(
+    get,
+    path = "/api/chat/sessions",
+    tag = "Chat",
+    summary = "List chat sessions",
+    description = "List the chat sessions of the current user, most recently active first",
+    params(ChatSessionQuery),
+    responses(
+        (status = 200, description = "Chat sessions listed successfully", body = ChatSessionListResponse),
+        (status = 403, description = "Anonymous users cannot list chat sessions"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_chat_sessions(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Query(query): Query<ChatSessionQuery>,
+) -> Result<Json<ChatSessionListResponse>, StatusCode> {
+    let context = user_to_permission_context(&user);
+    let sessions = state
+        .application
+        .list_chat_sessions(&context, query.repository_id.as_deref())
+        .await
+        .map_err(session_error_status)?;
+
+    Ok(Json(ChatSessionListResponse {
+        sessions: sessions.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Get chat session
+#[utoipa::path(
+    get,
+    path = "/api/chat/sessions/{session_id}",
+    tag = "Chat",
+    summary = "Get chat session",
+    description = "Get a chat session with its messages",
+    params(
+        ("session_id" = String, Path, description = "Chat session ID")
+    ),
+    responses(
+        (status = 200, description = "Chat session retrieved successfully", body = ChatSessionResponse),
+        (status = 404, description = "Chat session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_chat_session(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(session_id): Path<String>,
+) -> Result<Json<ChatSessionResponse>, StatusCode> {
+    let context = user_to_permission_context(&user);
+    let session = state
+        .application
+        .get_chat_session(&context, &session_id)
+        .await
+        .map_err(session_error_status)?;
+
+    Ok(Json(session.into()))
+}
+
+/// Rename chat session
+#[utoipa::path(
+    patch,
+    path = "/api/chat/sessions/{session_id}",
+    tag = "Chat",
+    summary = "Rename chat session",
+    description = "Replace the title of a chat session",
+    params(
+        ("session_id" = String, Path, description = "Chat session ID")
+    ),
+    request_body = RenameChatSessionRequest,
+    responses(
+        (status = 200, description = "Chat session renamed successfully", body = ChatSessionResponse),
+        (status = 400, description = "Invalid title"),
+        (status = 404, description = "Chat session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn rename_chat_session(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(session_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<RenameChatSessionRequest>,
+) -> Result<Json<ChatSessionResponse>, StatusCode> {
+    let context = user_to_permission_context(&user);
+    let session = state
+        .application
+        .rename_chat_session(&context, &session_id, &request.title)
+        .await
+        .map_err(session_error_status)?;
+
+    Ok(Json(session.into()))
+}
+
+/// Continue chat session
+#[utoipa::path(
+    post,
+    path = "/api/chat/sessions/{session_id}/messages",
+    tag = "Chat",
+    summary = "Continue chat session",
+    description = "Ask a follow-up question in a chat session. The question and answer are appended to the session together with the retrieved sources.",
+    params(
+        ("session_id" = String, Path, description = "Chat session ID")
+    ),
+    request_body = ChatSessionMessageRequest,
+    responses(
+        (status = 200, description = "Question answered successfully", body = ChatQueryResponse),
+        (status = 403, description = "Access to the repository denied"),
+        (status = 404, description = "Chat session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn continue_chat_session(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(session_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<ChatSessionMessageRequest>,
+) -> Result<Json<ChatQueryResponse>, StatusCode> {
+    info!("Continuing chat session {} (user: {})", session_id, user.id);
+
+    let context = user_to_permission_context(&user);
+    let session = state
+        .application
+        .get_chat_session(&context, &session_id)
+        .await
+        .map_err(session_error_status)?;
+
+    let repo_query = wikify_applications::RepositoryQuery {
+        question: request.question.clone(),
+        max_results: request.max_results,
+        parameters: None,
+    };
+    let repo_response = state
+        .application
+        .query_chat_session(&context, &session_id, repo_query)
+        .await
+        .map_err(session_error_status)?;
+
+    let query_id = record_query(
+        &state,
+        &session.repository_id,
+        &request.question,
+        &repo_response.answer,
+    )
+    .await;
+
+    Ok(Json(ChatQueryResponse {
+        branch: answer_branch(&repo_response),
+        answer: repo_response.answer,
+        sources: answer_source_documents(repo_response.sources, repo_response.citations),
+        repository_id: session.repository_id,
+        session_id: Some(session.id),
+        query_id,
+        timestamp: chrono::Utc::now(),
+    }))
+}
+
+/// Regenerate answer
+#[utoipa::path(
+    post,
+    path = "/api/chat/{session_id}/regenerate",
+    tag = "Chat",
+    summary = "Regenerate answer",
+    description = "Answer the last question of a chat session again with different retrieval parameters. Earlier answers are kept in the session; the new answer is recorded as the next branch.",
+    params(
+        ("session_id" = String, Path, description = "Chat session ID")
+    ),
+    request_body = RegenerateAnswerRequest,
+    responses(
+        (status = 200, description = "Question answered again successfully", body = ChatQueryResponse),
+        (status = 400, description = "The session has no question yet, or the repository is not ready"),
+        (status = 403, description = "Access to the repository denied"),
+        (status = 404, description = "Chat session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn regenerate_answer(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(session_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<RegenerateAnswerRequest>,
+) -> Result<Json<ChatQueryResponse>, StatusCode> {
+    info!(
+        "Regenerating last answer of chat session {} (user: {})",
+        session_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+    let parameters = request.similarity_threshold.map(|threshold| {
+        std::collections::HashMap::from([(
+            "similarity_threshold".to_string(),
+            threshold.to_string(),
+        )])
+    });
+
+    let repo_response = state
+        .application
+        .regenerate_chat_answer(&context, &session_id, request.max_results, parameters)
+        .await
+        .map_err(session_error_status)?;
+    let session = state
+        .application
+        .get_chat_session(&context, &session_id)
+        .await
+        .map_err(session_error_status)?;
+
+    let question = session
+        .last_question()
+        .map(|message| message.content.clone())
+        .unwrap_or_default();
+    let query_id = record_query(
+        &state,
+        &session.repository_id,
+        &question,
+        &repo_response.answer,
+    )
+    .await;
+
+    Ok(Json(ChatQueryResponse {
+        branch: answer_branch(&repo_response),
+        answer: repo_response.answer,
+        sources: answer_source_documents(repo_response.sources, repo_response.citations),
+        repository_id: session.repository_id,
+        session_id: Some(session.id),
+        query_id,
+        timestamp: chrono::Utc::now(),
+    }))
+}
+
+/// Delete chat session
+#[utoipa::path(
+    delete,
+    path = "/api/chat/sessions/{session_id}",
+    tag = "Chat",
+    summary = "Delete chat session",
+    description = "Delete a chat session and its messages",
+    params(
+        ("session_id" = String, Path, description = "Chat session ID")
+    ),
+    responses(
+        (status = 204, description = "Chat session deleted successfully"),
+        (status = 404, description = "Chat session not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn delete_chat_session(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Deleting chat session {} (user: {})", session_id, user.id);
+
+    let context = user_to_permission_context(&user);
+    state
+        .application
+        .delete_chat_session(&context, &session_id)
+        .await
+        .map_err(session_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 /// Get query history (SQLite feature only)
 #[cfg(feature = "sqlite")]
 #[utoipa::path(
@@ -164,45 +585,62 @@ pub async fn chat_query(
     path = "/api/history/{repository_id}",
     tag = "Chat",
     summary = "Get query history",
-    description = "Get chat history for a specific repository (requires SQLite feature)",
+    description = "Get the questions asked about a repository, newest first (requires SQLite feature). Use the chat session API for conversations of the current user.",
     params(
-        ("repository_id" = String, Path, description = "Repository ID")
+        ("repository_id" = String, Path, description = "Repository ID"),
+        super::types::PaginationQuery
     ),
     responses(
-        (status = 200, description = "Query history retrieved successfully"),
+        (status = 200, description = "Query history retrieved successfully", body = QueryHistoryResponse),
+        (status = 404, description = "Repository not found"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn get_query_history(
     State(state): State<AppState>,
     crate::auth::RequireQuery(user): crate::auth::RequireQuery,
-    axum::extract::Path(_repository_id): axum::extract::Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    info!("Getting query history for repository (user: {})", user.id);
+    axum::extract::Path(repository_id): axum::extract::Path<String>,
+    Query(pagination): Query<super::types::PaginationQuery>,
+) -> Result<Json<QueryHistoryResponse>, StatusCode> {
+    info!(
+        "Getting query history for repository {} (user: {})",
+        repository_id, user.id
+    );
 
     // Convert to permission context for application layer
-    let _context = user_to_permission_context(&user);
+    let context = user_to_permission_context(&user);
+    state
+        .application
+        .get_repository(&context, &repository_id)
+        .await
+        .map_err(session_error_status)?;
+
     if let Some(database) = &state.database {
-        // For now, get all queries. TODO: Filter by repository_id when session-repository mapping is implemented
-        match database.get_query_history(None, 50).await {
-            Ok(queries) => {
-                let queries_json: Vec<serde_json::Value> = queries
+        match database
+            .get_query_history(Some(&repository_id), &pagination.page())
+            .await
+        {
+            Ok(page) => {
+                let queries: Vec<QueryHistoryEntry> = page
+                    .items
                     .into_iter()
-                    .map(|query| {
-                        serde_json::json!({
-                            "id": query.id,
-                            "repository_id": query.repository_id,
-                            "question": query.question,
-                            "answer": query.answer,
-                            "created_at": query.created_at,
-                        })
+                    .map(|query| QueryHistoryEntry {
+                        id: query.id,
+                        repository_id: query.repository_id,
+                        question: query.question,
+                        answer: query.answer,
+                        created_at: query.created_at,
                     })
                     .collect();
 
-                Ok(Json(serde_json::json!({
-                    "queries": queries_json,
-                    "count": queries_json.len()
-                })))
+                Ok(Json(QueryHistoryResponse {
+                    count: queries.len(),
+                    queries,
+                    total: page.total,
+                    limit: page.limit,
+                    offset: page.offset,
+                    message: None,
+                }))
             }
             Err(e) => {
                 tracing::error!("Failed to get query history: {}", e);
@@ -211,32 +649,199 @@ pub async fn get_query_history(
         }
     } else {
         // 数据库未启用，返回空列表
-        Ok(Json(serde_json::json!({
-            "queries": [],
-            "count": 0,
-            "message": "Database not enabled"
-        })))
+        Ok(Json(QueryHistoryResponse {
+            queries: Vec::new(),
+            count: 0,
+            total: 0,
+            limit: None,
+            offset: 0,
+            message: Some("Database not enabled".to_string()),
+        }))
     }
 }
 
-/// Handle streaming chat queries (placeholder)
+/// Maximum length of feedback comments, in characters
+#[cfg(feature = "sqlite")]
+const MAX_FEEDBACK_COMMENT_LENGTH: usize = 2000;
+
+/// Submit answer feedback (SQLite feature only)
+#[cfg(feature = "sqlite")]
+#[utoipa::path(
+    post,
+    path = "/api/chat/feedback",
+    tag = "Chat",
+    summary = "Submit answer feedback",
+    description = "Rate an answer with thumbs up or down, optionally explaining why and marking the sources that were wrong. The feedback is stored with the query record identified by the `query_id` returned with the answer (requires SQLite feature).",
+    request_body = ChatFeedbackRequest,
+    responses(
+        (status = 201, description = "Feedback recorded successfully", body = ChatFeedbackResponse),
+        (status = 400, description = "Comment or source list too long"),
+        (status = 404, description = "Query record not found"),
+        (status = 503, description = "Database not enabled"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn submit_chat_feedback(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    JsonExtractor(request): JsonExtractor<ChatFeedbackRequest>,
+) -> Result<(StatusCode, Json<ChatFeedbackResponse>), StatusCode> {
+    info!(
+        "Recording {} feedback for query {} (user: {})",
+        request.rating.as_str(),
+        request.query_id,
+        user.id
+    );
+
+    let Some(database) = &state.database else {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let comment = request
+        .comment
+        .map(|comment| comment.trim().to_string())
+        .filter(|comment| !comment.is_empty());
+    if comment
+        .as_ref()
+        .is_some_and(|comment| comment.chars().count() > MAX_FEEDBACK_COMMENT_LENGTH)
+        || request.wrong_sources.len() > 50
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let query = database
+        .get_query(&request.query_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to load query {}: {}", request.query_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Only users who can see the repository may rate answers about it
+    let context = user_to_permission_context(&user);
+    if let Some(repository_id) = &query.repository_id {
+        state
+            .application
+            .get_repository(&context, repository_id)
+            .await
+            .map_err(session_error_status)?;
+    }
+
+    let feedback = crate::simple_database::SimpleFeedback {
+        id: uuid::Uuid::new_v4().to_string(),
+        query_id: query.id,
+        repository_id: query.repository_id,
+        user_id: Some(user.id),
+        rating: request.rating.as_str().to_string(),
+        comment,
+        wrong_sources: request.wrong_sources,
+        created_at: chrono::Utc::now(),
+    };
+    database.save_feedback(&feedback).await.map_err(|e| {
+        error!("Failed to save feedback: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ChatFeedbackResponse {
+            id: feedback.id,
+            query_id: feedback.query_id,
+            rating: request.rating,
+            created_at: feedback.created_at,
+        }),
+    ))
+}
+
+/// List answer feedback (SQLite feature only)
+#[cfg(feature = "sqlite")]
+#[utoipa::path(
+    get,
+    path = "/api/admin/feedback",
+    tag = "Admin",
+    summary = "List answer feedback",
+    description = "List feedback on answers together with the rated question and answer, newest first (requires SQLite feature)",
+    params(ChatFeedbackQuery),
+    responses(
+        (status = 200, description = "Feedback listed successfully", body = ChatFeedbackListResponse),
+        (status = 403, description = "Admin permission required"),
+        (status = 503, description = "Database not enabled"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_chat_feedback(
+    State(state): State<AppState>,
+    crate::auth::AdminUser(user): crate::auth::AdminUser,
+    Query(query): Query<ChatFeedbackQuery>,
+) -> Result<Json<ChatFeedbackListResponse>, StatusCode> {
+    info!("Listing answer feedback (admin user: {})", user.id);
+
+    let Some(database) = &state.database else {
+        return Err(StatusCode::SERVICE_UNAVAILABLE);
+    };
+
+    let entries = database
+        .get_feedback(
+            query.repository_id.as_deref(),
+            query.rating.map(|rating| rating.as_str()),
+            query.limit.unwrap_or(100).clamp(1, 1000),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to list feedback: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let feedback: Vec<ChatFeedbackEntry> = entries
+        .into_iter()
+        .filter_map(|(feedback, rated_query)| {
+            Some(ChatFeedbackEntry {
+                rating: feedback.rating.parse().ok()?,
+                id: feedback.id,
+                query_id: feedback.query_id,
+                repository_id: feedback.repository_id,
+                user_id: feedback.user_id,
+                comment: feedback.comment,
+                wrong_sources: feedback.wrong_sources,
+                created_at: feedback.created_at,
+                question: rated_query.as_ref().map(|q| q.question.clone()),
+                answer: rated_query.map(|q| q.answer),
+            })
+        })
+        .collect();
+
+    Ok(Json(ChatFeedbackListResponse {
+        count: feedback.len(),
+        feedback,
+    }))
+}
+
+/// Handle streaming chat queries
+///
+/// Streams the answer as Server-Sent Events: `chunk` events with parts of
+/// the answer, then a `done` event with the sources and metadata, or an
+/// `error` event. Closing the connection stops the query.
 #[utoipa::path(
     post,
     path = "/api/chat/stream",
     tag = "Chat",
     summary = "Stream chat response",
-    description = "Ask a question and receive streaming response (not yet implemented)",
+    description = "Ask a question and receive the answer as Server-Sent Events: `chunk` events with `content`, then a `done` event with `sources`, `metadata` and `query_id`, or an `error` event with `message`. Streamed answers are not recorded in chat sessions.",
     request_body = ChatQueryRequest,
     responses(
-        (status = 200, description = "Streaming response started"),
-        (status = 501, description = "Not implemented")
+        (status = 200, description = "Streaming response started", content_type = "text/event-stream"),
+        (status = 400, description = "Repository is not ready for querying"),
+        (status = 403, description = "Access to the repository denied"),
+        (status = 404, description = "Repository not found"),
+        (status = 429, description = "Anonymous query limit reached")
     )
 )]
 pub async fn chat_stream(
     State(state): State<AppState>,
     ModeAwareUser(user): ModeAwareUser,
     JsonExtractor(request): JsonExtractor<ChatQueryRequest>,
-) -> Result<impl IntoResponse, StatusCode> {
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
     info!(
         "Starting chat stream for user: {} with repository: {}",
         user.id, request.repository_id
@@ -245,20 +850,84 @@ pub async fn chat_stream(
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
 
-    let repository_id = &request.repository_id;
-
     // Create repository query
     let repo_query = wikify_applications::RepositoryQuery {
         question: request.question.clone(),
         max_results: request.max_results,
-        parameters: None, // TODO: Add support for additional parameters
+        parameters: None,
     };
 
-    // TODO: Implement streaming query when available
-    // For now, return a placeholder response
-    Ok(Json(serde_json::json!({
-        "message": "Streaming queries not yet implemented for repository-based queries",
-        "repository_id": repository_id
-    }))
-    .into_response())
+    let chunks = state
+        .application
+        .stream_query_repository(&context, &request.repository_id, repo_query)
+        .await
+        .map_err(session_error_status)?;
+
+    let stream = create_answer_stream(state, request, chunks);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// Turn streamed answer chunks into Server-Sent Events
+///
+/// The stream ends after the final chunk. When the client disconnects,
+/// axum drops the stream and with it the chunk receiver, which stops the
+/// query.
+fn create_answer_stream(
+    state: AppState,
+    request: ChatQueryRequest,
+    chunks: UnboundedReceiver<QueryStreamChunk>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(
+        Some((state, request, chunks, String::new())),
+        |stream_state| async move {
+            let (state, request, mut chunks, mut answer) = stream_state?;
+            let Some(chunk) = chunks.recv().await else {
+                // The query ended without a final chunk
+                let event = Event::default()
+                    .event("error")
+                    .data(serde_json::json!({ "message": "Query ended unexpectedly" }).to_string());
+                return Some((Ok(event), None));
+            };
+
+            match chunk.chunk_type {
+                QueryChunkType::Content | QueryChunkType::Source => {
+                    answer.push_str(&chunk.content);
+                    let event = Event::default()
+                        .event("chunk")
+                        .data(serde_json::json!({ "content": chunk.content }).to_string());
+                    Some((Ok(event), Some((state, request, chunks, answer))))
+                }
+                QueryChunkType::Complete => {
+                    let query_id =
+                        record_query(&state, &request.repository_id, &request.question, &answer)
+                            .await;
+                    info!(
+                        "✅ Chat stream completed for repository: {}",
+                        request.repository_id
+                    );
+                    let event = Event::default().event("done").data(
+                        serde_json::json!({
+                            "repository_id": request.repository_id,
+                            "sources": to_source_documents(chunk.sources.unwrap_or_default()),
+                            "metadata": chunk.metadata.unwrap_or_default(),
+                            "query_id": query_id,
+                            "timestamp": chrono::Utc::now(),
+                        })
+                        .to_string(),
+                    );
+                    Some((Ok(event), None))
+                }
+                QueryChunkType::Error => {
+                    error!(
+                        "❌ Chat stream failed for {}: {}",
+                        request.repository_id, chunk.content
+                    );
+                    let event = Event::default()
+                        .event("error")
+                        .data(serde_json::json!({ "message": chunk.content }).to_string());
+                    Some((Ok(event), None))
+                }
+            }
+        },
+    )
 }
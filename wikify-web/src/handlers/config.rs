@@ -1,5 +1,6 @@
 //! Configuration management handlers
 
+use super::types::{MessageResponse, ServerConfigResponse};
 use crate::{auth::AdminUser, AppState};
 use axum::{extract::State, http::StatusCode, response::Json, Json as JsonExtractor};
 use tracing::info;
@@ -12,26 +13,39 @@ use tracing::info;
     summary = "Get server configuration",
     description = "Get current server configuration",
     responses(
-        (status = 200, description = "Configuration retrieved successfully")
+        (status = 200, description = "Configuration retrieved successfully", body = ServerConfigResponse)
     )
 )]
-pub async fn get_config(State(state): State<AppState>) -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "host": state.config.host,
-        "port": state.config.port,
-        "dev_mode": state.config.dev_mode,
-    }))
+pub async fn get_config(State(state): State<AppState>) -> Json<ServerConfigResponse> {
+    Json(ServerConfigResponse {
+        host: state.config.host.clone(),
+        port: state.config.port,
+        dev_mode: state.config.dev_mode,
+    })
 }
 
 /// Update server configuration
+#[utoipa::path(
+    post,
+    path = "/api/config",
+    tag = "Configuration",
+    summary = "Update server configuration",
+    description = "Update the server configuration (admin only). Not implemented yet: the request is accepted and nothing changes.",
+    request_body(content = Object, description = "Settings to change"),
+    responses(
+        (status = 200, description = "Request accepted", body = MessageResponse),
+        (status = 403, description = "Admin permission required")
+    )
+)]
 pub async fn update_config(
     State(_state): State<AppState>,
     AdminUser(user): AdminUser,
     JsonExtractor(_request): JsonExtractor<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<MessageResponse>, StatusCode> {
     info!("Updating server configuration (admin user: {})", user.id);
     // Placeholder for configuration update
-    Ok(Json(serde_json::json!({
-        "message": "Configuration update is not yet implemented"
-    })))
+    Ok(Json(MessageResponse {
+        success: false,
+        message: "Configuration update is not yet implemented".to_string(),
+    }))
 }
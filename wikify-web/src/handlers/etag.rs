@@ -0,0 +1,104 @@
+//! Conditional requests with entity tags
+//!
+//! Documents are tagged with a hash of their JSON body. Clients send the tag
+//! back in `If-None-Match` and get `304 Not Modified` without a body while
+//! the document is unchanged, which keeps polling cheap.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+/// Strong entity tag of a response body
+pub fn etag_for(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether `If-None-Match` lists the entity tag, so the client's copy is current
+///
+/// Weak tags match too, since the comparison for `If-None-Match` is weak.
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// JSON response tagged with its entity tag, or `304 Not Modified` when the
+/// client already has it
+pub fn json_with_etag<T: Serialize>(headers: &HeaderMap, value: &T) -> Response {
+    let body = match serde_json::to_vec(value) {
+        Ok(body) => body,
+        Err(e) => {
+            error!("Failed to serialize response: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let etag = etag_for(&body);
+    let etag_header = HeaderValue::from_str(&etag).expect("hex entity tags are valid headers");
+    // Clients may keep the document but must check it is still current
+    let cache_control = HeaderValue::from_static("no-cache");
+
+    if if_none_match(headers, &etag) {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, etag_header),
+                (header::CACHE_CONTROL, cache_control),
+            ],
+        )
+            .into_response();
+    }
+
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+            (header::ETAG, etag_header),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditional_json_response() {
+        let document = serde_json::json!({ "title": "Wiki", "pages": [] });
+        let response = json_with_etag(&HeaderMap::new(), &document);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].clone();
+
+        // The tag only depends on the content
+        let again = json_with_etag(&HeaderMap::new(), &document);
+        assert_eq!(again.headers()[header::ETAG], etag);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(&format!("\"other\", W/{}", etag.to_str().unwrap())).unwrap(),
+        );
+        let cached = json_with_etag(&headers, &document);
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+
+        let changed = json_with_etag(&headers, &serde_json::json!({ "title": "New" }));
+        assert_eq!(changed.status(), StatusCode::OK);
+    }
+}
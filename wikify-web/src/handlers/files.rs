@@ -1,23 +1,152 @@
 //! File operations handlers
 
+use super::etag;
 use super::types::{
-    FileContentResponse, FileTreeResponse, GetFileContentRequest, GetFileTreeRequest,
-    GetReadmeRequest, ReadmeResponse, RepositoryFileInfo,
+    FileContentQuery, FileContentResponse, FileTreeNode, FileTreeQuery, FileTreeResponse,
+    GetFileContentRequest, GetFileTreeRequest, GetReadmeRequest, ReadmeResponse,
+    RepositoryFileContentResponse, RepositoryFileInfo, RepositoryFileTreeResponse,
 };
 use crate::{auth::RequireQuery, AppState};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::{Html, Json},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{Json, Response},
     Json as JsonExtractor,
 };
 use tracing::{error, info};
+use wikify_applications::ApplicationError;
 
 /// Helper function to convert User to PermissionContext for application layer
 fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::PermissionContext {
     user.to_permission_context()
 }
 
+/// Largest part of a file returned for display
+const MAX_FILE_CONTENT_BYTES: usize = 1024 * 1024;
+
+/// Map an application error to the status code returned by the file API
+fn file_error_status(error: ApplicationError) -> StatusCode {
+    match error {
+        ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+        ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+        // File errors from the repository layer only carry a message
+        e if e.to_string().contains("not found") || e.to_string().contains("No such file") => {
+            StatusCode::NOT_FOUND
+        }
+        e => {
+            error!("File request failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// List the files of a repository as a tree
+#[utoipa::path(
+    get,
+    path = "/api/repositories/{repository_id}/files",
+    tag = "Files",
+    description = "Return the files of a repository nested into directories, with their types and sizes. Directory sizes are the total of the files below them. Responses carry an `ETag`; send it in `If-None-Match` to get `304` while the tree is unchanged.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        FileTreeQuery
+    ),
+    responses(
+        (status = 200, description = "File tree retrieved successfully", body = RepositoryFileTreeResponse),
+        (status = 304, description = "File tree unchanged since the ETag in If-None-Match"),
+        (status = 403, description = "No access to the repository"),
+        (status = 404, description = "Repository not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_repository_files(
+    State(state): State<AppState>,
+    RequireQuery(user): RequireQuery,
+    Path(repository_id): Path<String>,
+    Query(query): Query<FileTreeQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    info!("Listing files of repository: {}", repository_id);
+
+    let context = user_to_permission_context(&user);
+    let files = state
+        .application
+        .get_repository_file_tree(&context, &repository_id, query.branch.clone())
+        .await
+        .map_err(file_error_status)?;
+
+    let total_files = files.len();
+    let tree = FileTreeNode::build_tree(files.into_iter().map(RepositoryFileInfo::from).collect());
+    let response = RepositoryFileTreeResponse {
+        repository_id,
+        branch: query.branch,
+        total_files,
+        tree,
+    };
+    Ok(etag::json_with_etag(&headers, &response))
+}
+
+/// Read a repository file for display
+#[utoipa::path(
+    get,
+    path = "/api/repositories/{repository_id}/file",
+    tag = "Files",
+    description = "Return the content of a file with its language and size. Files larger than 1 MiB are cut short and marked as truncated. Responses carry an `ETag`; send it in `If-None-Match` to get `304` while the file is unchanged.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        FileContentQuery
+    ),
+    responses(
+        (status = 200, description = "File content retrieved successfully", body = RepositoryFileContentResponse),
+        (status = 304, description = "File unchanged since the ETag in If-None-Match"),
+        (status = 403, description = "No access to the repository"),
+        (status = 404, description = "Repository or file not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_repository_file(
+    State(state): State<AppState>,
+    RequireQuery(user): RequireQuery,
+    Path(repository_id): Path<String>,
+    Query(query): Query<FileContentQuery>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    info!(
+        "Reading file {} of repository: {}",
+        query.path, repository_id
+    );
+
+    let context = user_to_permission_context(&user);
+    let mut content = state
+        .application
+        .get_repository_file_content(&context, &repository_id, &query.path, query.branch.clone())
+        .await
+        .map_err(file_error_status)?;
+
+    let size = content.len();
+    let truncated = size > MAX_FILE_CONTENT_BYTES;
+    if truncated {
+        let mut end = MAX_FILE_CONTENT_BYTES;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+    }
+    let language = Some(wikify_wiki::snippets::language_for_path(&query.path))
+        .filter(|language| !language.is_empty())
+        .map(str::to_string);
+
+    let response = RepositoryFileContentResponse {
+        repository_id,
+        path: query.path,
+        branch: query.branch,
+        content,
+        language,
+        size,
+        truncated,
+    };
+    Ok(etag::json_with_etag(&headers, &response))
+}
+
 /// Get file tree for repository
 #[utoipa::path(
     post,
@@ -26,6 +155,7 @@ fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::
     request_body = GetFileTreeRequest,
     responses(
         (status = 200, description = "File tree retrieved successfully", body = FileTreeResponse),
+        (status = 304, description = "File tree unchanged since the ETag in If-None-Match"),
         (status = 404, description = "Repository not found"),
         (status = 500, description = "Internal server error")
     )
@@ -33,8 +163,9 @@ fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::
 pub async fn get_file_tree(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
+    headers: HeaderMap,
     JsonExtractor(request): JsonExtractor<GetFileTreeRequest>,
-) -> Result<Json<FileTreeResponse>, StatusCode> {
+) -> Result<Response, StatusCode> {
     info!(
         "Getting file tree for repository: {}",
         request.repository_id
@@ -51,12 +182,13 @@ pub async fn get_file_tree(
             let file_infos: Vec<RepositoryFileInfo> =
                 files.into_iter().map(RepositoryFileInfo::from).collect();
 
-            Ok(Json(FileTreeResponse {
+            let response = FileTreeResponse {
                 repository_id: request.repository_id,
                 branch: request.branch,
                 total_files: file_infos.len(),
                 files: file_infos,
-            }))
+            };
+            Ok(etag::json_with_etag(&headers, &response))
         }
         Err(e) => {
             error!("Failed to get file tree: {}", e);
@@ -77,6 +209,7 @@ pub async fn get_file_tree(
     request_body = GetFileContentRequest,
     responses(
         (status = 200, description = "File content retrieved successfully", body = FileContentResponse),
+        (status = 304, description = "File unchanged since the ETag in If-None-Match"),
         (status = 404, description = "File not found"),
         (status = 500, description = "Internal server error")
     )
@@ -84,8 +217,9 @@ pub async fn get_file_tree(
 pub async fn get_file_content(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
+    headers: HeaderMap,
     JsonExtractor(request): JsonExtractor<GetFileContentRequest>,
-) -> Result<Json<FileContentResponse>, StatusCode> {
+) -> Result<Response, StatusCode> {
     info!(
         "Getting file content for: {}/{}",
         request.repository_id, request.file_path
@@ -107,14 +241,15 @@ pub async fn get_file_content(
                 "File content retrieved successfully: {} bytes",
                 content.len()
             );
-            Ok(Json(FileContentResponse {
+            let response = FileContentResponse {
                 repository_id: request.repository_id,
                 file_path: request.file_path,
                 branch: request.branch,
                 size: content.len(),
                 encoding: "utf-8".to_string(),
                 content,
-            }))
+            };
+            Ok(etag::json_with_etag(&headers, &response))
         }
         Err(e) => {
             error!("Failed to get file content: {}", e);
@@ -172,8 +307,3 @@ pub async fn get_readme(
         }
     }
 }
-
-/// SPA fallback handler
-pub async fn spa_fallback() -> Html<&'static str> {
-    Html(include_str!("../../static/index.html"))
-}
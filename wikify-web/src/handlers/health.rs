@@ -1,7 +1,9 @@
 //! Health check handlers
 
-use super::types::HealthResponse;
-use axum::response::Json;
+use super::types::{HealthResponse, ReadinessResponse};
+use crate::AppState;
+use axum::{extract::State, http::StatusCode, response::Json};
+use wikify_applications::{health, DependencyCheck};
 
 /// Health check endpoint
 #[utoipa::path(
@@ -21,3 +23,68 @@ pub async fn health_check() -> Json<HealthResponse> {
         version: env!("CARGO_PKG_VERSION").to_string(),
     })
 }
+
+/// Liveness check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/health/live",
+    tag = "Health",
+    summary = "Liveness check",
+    description = "Check that the server process answers requests, without checking its dependencies",
+    responses(
+        (status = 200, description = "Server is alive", body = HealthResponse)
+    )
+)]
+pub async fn liveness_check() -> Json<HealthResponse> {
+    health_check().await
+}
+
+/// Readiness check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/health/ready",
+    tag = "Health",
+    summary = "Readiness check",
+    description = "Check the indexing worker, database, repository storage, vector store and LLM provider, reporting the status of each",
+    responses(
+        (status = 200, description = "All dependencies are healthy", body = ReadinessResponse),
+        (status = 503, description = "At least one dependency is unhealthy", body = ReadinessResponse)
+    )
+)]
+pub async fn readiness_check(
+    State(state): State<AppState>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let (mut checks, database) =
+        tokio::join!(state.application.check_readiness(), check_database(&state));
+    checks.push(database);
+
+    let ready = health::all_healthy(&checks);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let response = ReadinessResponse {
+        status: if ready { "ready" } else { "not_ready" }.to_string(),
+        timestamp: chrono::Utc::now(),
+        checks,
+    };
+    (status, Json(response))
+}
+
+/// Check the web database, which is optional
+#[cfg_attr(not(feature = "sqlite"), allow(unused_variables))]
+async fn check_database(state: &AppState) -> DependencyCheck {
+    DependencyCheck::run("database", async {
+        #[cfg(feature = "sqlite")]
+        if let Some(ref database) = state.database {
+            return database
+                .ping()
+                .await
+                .map(|_| None)
+                .map_err(|e| e.to_string());
+        }
+        Ok(Some("No database configured".to_string()))
+    })
+    .await
+}
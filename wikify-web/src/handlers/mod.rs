@@ -2,22 +2,31 @@
 //!
 //! This module contains all the HTTP request handlers organized by functionality.
 
+pub mod admin;
 pub mod chat;
 pub mod config;
+pub mod etag;
 pub mod files;
 pub mod health;
+pub mod organization;
 pub mod repository;
+#[cfg(feature = "research")]
 pub mod research;
 pub mod types;
+pub mod webhook;
 pub mod wiki;
 
 // Re-export all handler functions to maintain API compatibility
+pub use admin::*;
 pub use chat::*;
 pub use config::*;
 pub use files::*;
 pub use health::*;
+pub use organization::*;
 pub use repository::*;
+#[cfg(feature = "research")]
 pub use research::*;
+pub use webhook::*;
 pub use wiki::*;
 
 // Re-export all types for convenience
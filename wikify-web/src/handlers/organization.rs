@@ -0,0 +1,241 @@
+//! Organization handlers
+//!
+//! Repositories, wikis and research history created while sending the
+//! `X-Organization-Id` header belong to that organization and are shared
+//! with its members.
+
+use super::types::{
+    CreateOrganizationRequest, OrganizationListResponse, OrganizationResponse,
+    SetOrganizationMemberRequest,
+};
+use crate::{auth::ModeAwareUser, AppState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Json,
+    Json as JsonExtractor,
+};
+use tracing::{error, info};
+use wikify_applications::ApplicationError;
+
+/// Map an application error to the status code returned by the organization API
+fn organization_error_status(error: ApplicationError) -> StatusCode {
+    match error {
+        ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+        ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+        ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+        e => {
+            error!("Organization request failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// List organizations
+#[utoipa::path(
+    get,
+    path = "/api/organizations",
+    tag = "Organization",
+    summary = "List organizations",
+    description = "List the organizations the current user is a member of (admins see all organizations)",
+    responses(
+        (status = 200, description = "Organizations listed successfully", body = OrganizationListResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_organizations(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+) -> Result<Json<OrganizationListResponse>, StatusCode> {
+    let context = user.to_permission_context();
+    let organizations = state
+        .application
+        .list_organizations(&context)
+        .await
+        .map_err(organization_error_status)?;
+
+    Ok(Json(OrganizationListResponse {
+        organizations: organizations.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Create organization
+#[utoipa::path(
+    post,
+    path = "/api/organizations",
+    tag = "Organization",
+    summary = "Create organization",
+    description = "Create an organization owned by the current user",
+    request_body = CreateOrganizationRequest,
+    responses(
+        (status = 201, description = "Organization created successfully", body = OrganizationResponse),
+        (status = 400, description = "Invalid organization name"),
+        (status = 403, description = "Anonymous users cannot create organizations"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_organization(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    JsonExtractor(request): JsonExtractor<CreateOrganizationRequest>,
+) -> Result<(StatusCode, Json<OrganizationResponse>), StatusCode> {
+    info!("Creating organization {} (user: {})", request.name, user.id);
+
+    let context = user.to_permission_context();
+    let organization = state
+        .application
+        .create_organization(&context, request.name)
+        .await
+        .map_err(organization_error_status)?;
+
+    Ok((StatusCode::CREATED, Json(organization.into())))
+}
+
+/// Get organization
+#[utoipa::path(
+    get,
+    path = "/api/organizations/{organization_id}",
+    tag = "Organization",
+    summary = "Get organization",
+    description = "Get an organization and its members",
+    params(
+        ("organization_id" = String, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 200, description = "Organization retrieved successfully", body = OrganizationResponse),
+        (status = 404, description = "Organization not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_organization(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(organization_id): Path<String>,
+) -> Result<Json<OrganizationResponse>, StatusCode> {
+    let context = user.to_permission_context();
+    let organization = state
+        .application
+        .get_organization(&context, &organization_id)
+        .await
+        .map_err(organization_error_status)?;
+
+    Ok(Json(organization.into()))
+}
+
+/// Delete organization
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{organization_id}",
+    tag = "Organization",
+    summary = "Delete organization",
+    description = "Delete an organization. Only owners can delete an organization, and only once its repositories have been deleted.",
+    params(
+        ("organization_id" = String, Path, description = "Organization ID")
+    ),
+    responses(
+        (status = 204, description = "Organization deleted successfully"),
+        (status = 400, description = "Organization still has repositories"),
+        (status = 403, description = "Owner role required"),
+        (status = 404, description = "Organization not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn delete_organization(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(organization_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    info!(
+        "Deleting organization {} (user: {})",
+        organization_id, user.id
+    );
+
+    let context = user.to_permission_context();
+    state
+        .application
+        .delete_organization(&context, &organization_id)
+        .await
+        .map_err(organization_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Add or update organization member
+#[utoipa::path(
+    put,
+    path = "/api/organizations/{organization_id}/members/{user_id}",
+    tag = "Organization",
+    summary = "Add or update organization member",
+    description = "Add a user to an organization or change their role. Requires the admin role; only owners can grant or revoke the owner role.",
+    params(
+        ("organization_id" = String, Path, description = "Organization ID"),
+        ("user_id" = String, Path, description = "User ID of the member")
+    ),
+    request_body = SetOrganizationMemberRequest,
+    responses(
+        (status = 200, description = "Member saved successfully", body = OrganizationResponse),
+        (status = 400, description = "The last owner cannot be demoted"),
+        (status = 403, description = "Insufficient role in the organization"),
+        (status = 404, description = "Organization not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn set_organization_member(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path((organization_id, member_id)): Path<(String, String)>,
+    JsonExtractor(request): JsonExtractor<SetOrganizationMemberRequest>,
+) -> Result<Json<OrganizationResponse>, StatusCode> {
+    info!(
+        "Setting role of {} in organization {} to {} (user: {})",
+        member_id, organization_id, request.role, user.id
+    );
+
+    let context = user.to_permission_context();
+    let organization = state
+        .application
+        .set_organization_member(&context, &organization_id, &member_id, request.role)
+        .await
+        .map_err(organization_error_status)?;
+
+    Ok(Json(organization.into()))
+}
+
+/// Remove organization member
+#[utoipa::path(
+    delete,
+    path = "/api/organizations/{organization_id}/members/{user_id}",
+    tag = "Organization",
+    summary = "Remove organization member",
+    description = "Remove a user from an organization. Members can always leave; removing someone else requires the admin role.",
+    params(
+        ("organization_id" = String, Path, description = "Organization ID"),
+        ("user_id" = String, Path, description = "User ID of the member")
+    ),
+    responses(
+        (status = 200, description = "Member removed successfully", body = OrganizationResponse),
+        (status = 400, description = "The last owner cannot leave"),
+        (status = 403, description = "Insufficient role in the organization"),
+        (status = 404, description = "Organization or member not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn remove_organization_member(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path((organization_id, member_id)): Path<(String, String)>,
+) -> Result<Json<OrganizationResponse>, StatusCode> {
+    info!(
+        "Removing {} from organization {} (user: {})",
+        member_id, organization_id, user.id
+    );
+
+    let context = user.to_permission_context();
+    let organization = state
+        .application
+        .remove_organization_member(&context, &organization_id, &member_id)
+        .await
+        .map_err(organization_error_status)?;
+
+    Ok(Json(organization.into()))
+}
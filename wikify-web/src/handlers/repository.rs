@@ -1,17 +1,38 @@
 //! Repository management handlers
 
 use super::types::{
-    DeleteRepositoryResponse, InitializeRepositoryRequest, InitializeRepositoryResponse,
-    ReindexResponse,
+    validate_repository_url, BatchItemStatus, BatchRepositoryItem, BatchRepositoryRequest,
+    BatchRepositoryResponse, DeleteRepositoryResponse, IndexingJobsResponse,
+    InitializeRepositoryRequest, InitializeRepositoryResponse, ReindexResponse,
+    RepositoryInfoResponse, RepositoryListQuery, RepositoryListResponse, RepositorySharingResponse,
+    RepositorySummaryResponse, ShareRepositoryRequest, UploadRepositoryForm,
 };
 use crate::{auth::ModeAwareUser, AppState, WebError};
 use axum::{
-    extract::{Path, State},
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
     response::Json,
     Json as JsonExtractor,
 };
 use tracing::{error, info, warn};
+use wikify_applications::ApplicationError;
+
+/// Maximum ratio between the extracted size of an uploaded archive and the upload limit
+const MAX_EXTRACTION_RATIO: u64 = 10;
+
+/// Maximum number of URLs in one batch registration
+const MAX_BATCH_REPOSITORIES: usize = 200;
+
+/// Repository type guessed from its URL
+pub(crate) fn detect_repo_type(url: &str) -> String {
+    if url.contains("github.com") {
+        "github".to_string()
+    } else if url.contains("gitlab.com") {
+        "gitlab".to_string()
+    } else {
+        "local".to_string()
+    }
+}
 
 /// Extract progress numbers from message strings like "Processing 37/53 nodes"
 fn extract_progress_numbers(message: &str) -> (Option<usize>, Option<usize>) {
@@ -37,135 +58,124 @@ fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::
     user.to_permission_context()
 }
 
-/// Helper function for auto-generating wiki after repository indexing
-async fn generate_wiki_for_repository(
+/// Forward application indexing progress for a repository to the web progress
+/// broadcaster, optionally generating the wiki once indexing completes
+pub(crate) fn spawn_progress_forwarding(
     state: &AppState,
-    repository_id: &str,
-    progress_sender: &tokio::sync::broadcast::Sender<crate::state::BroadcastMessage>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    info!("Starting wiki generation for repository: {}", repository_id);
-
-    // Create a local permission context for system operations (has all permissions)
-    let permission_context = wikify_applications::PermissionContext::local();
+    repository_id: String,
+    auto_generate_wiki: bool,
+) {
+    // Start listening to application progress updates and forward to web progress broadcaster
+    let app_progress_receiver = state.application.subscribe_to_repository_progress();
+    let web_progress_sender = state.progress_broadcaster.clone();
+    let repo_id_clone = repository_id.clone();
+    let state_clone = state.clone();
 
-    // Get repository information
-    let repository = match state
-        .application
-        .get_repository(&permission_context, repository_id)
-        .await
-    {
-        Ok(repo) => repo,
-        Err(e) => {
-            let error_msg = format!("Failed to get repository info: {}", e);
-            error!("{}", error_msg);
-            let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-                crate::state::IndexingUpdate::WikiGenerationError {
-                    repository_id: repository_id.to_string(),
-                    error: error_msg,
-                },
-            ));
-            return Err(e.into());
-        }
-    };
+    info!(
+        "Setting up progress forwarding for repository: {}",
+        repository_id
+    );
 
-    // Send initial progress update
-    let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-        crate::state::IndexingUpdate::WikiGenerationProgress {
-            repository_id: repository_id.to_string(),
-            stage: "Initializing wiki generation...".to_string(),
-            percentage: 0.1, // Use 0.0-1.0 range consistently
+    // Send initial IndexStart event
+    let _ = web_progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
+        crate::state::IndexingUpdate::Started {
+            repository_id: repo_id_clone.clone(),
+            total_files: None, // Will be updated when we know the actual count
+            estimated_duration: None, // Will be estimated based on repository size
         },
     ));
 
-    // Generate wiki using wiki service
-    let mut wiki_service = state.wiki_service.write().await;
-    let wiki_config = wikify_wiki::WikiConfig::default();
+    tokio::spawn(async move {
+        let mut receiver = app_progress_receiver;
+        while let Ok(update) = receiver.recv().await {
+            info!("Received application progress update: {:?}", update);
+
+            // Convert application progress to web progress format
+            let web_update = match &update {
+                update if update.status == wikify_applications::IndexingStatus::Indexing => {
+                    // Extract files processed and total from the message if available
+                    let (files_processed, total_files) = extract_progress_numbers(&update.message);
+
+                    crate::state::IndexingUpdate::Progress {
+                        repository_id: update.repository_id.clone(),
+                        stage: update.message.clone(),
+                        percentage: update.progress, // Keep as 0.0-1.0 range
+                        current_item: Some(format!(
+                            "Processing... {:.1}%",
+                            update.progress * 100.0
+                        )),
+                        files_processed,
+                        total_files,
+                    }
+                }
+                update if update.status == wikify_applications::IndexingStatus::Completed => {
+                    let complete_update = crate::state::IndexingUpdate::Complete {
+                        repository_id: update.repository_id.clone(),
+                        total_files: 0, // TODO: Get actual stats
+                        total_chunks: 0,
+                        duration_ms: 0,
+                    };
 
-    // Send progress update for wiki generation start
-    let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-        crate::state::IndexingUpdate::WikiGenerationProgress {
-            repository_id: repository_id.to_string(),
-            stage: "Analyzing repository structure...".to_string(),
-            percentage: 0.3, // Use 0.0-1.0 range consistently
-        },
-    ));
+                    // Auto-generate wiki if requested
+                    if auto_generate_wiki && &update.repository_id == &repo_id_clone {
+                        info!(
+                            "Auto-generating wiki for repository: {}",
+                            update.repository_id
+                        );
+
+                        // Generate wiki in background with system permissions
+                        super::wiki::spawn_wiki_generation(
+                            state_clone.clone(),
+                            wikify_applications::PermissionContext::local(),
+                            update.repository_id.clone(),
+                            wikify_wiki::WikiConfig::default(),
+                        );
+                    }
 
-    match wiki_service
-        .generate_wiki(&repository.url, &wiki_config)
-        .await
-    {
-        Ok(wiki_structure) => {
-            // Send progress update for content generation
-            let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-                crate::state::IndexingUpdate::WikiGenerationProgress {
-                    repository_id: repository_id.to_string(),
-                    stage: "Generating wiki content...".to_string(),
-                    percentage: 0.7, // Use 0.0-1.0 range consistently
-                },
-            ));
-
-            // Extract actual markdown content from the first page, or create a summary
-            let wiki_content = if let Some(first_page) = wiki_structure.pages.first() {
-                first_page.content.clone()
-            } else {
-                format!(
-                    "# {}\n\n{}\n\nNo content pages were generated.",
-                    wiki_structure.title, wiki_structure.description
-                )
+                    complete_update
+                }
+                update if update.status == wikify_applications::IndexingStatus::Failed => {
+                    crate::state::IndexingUpdate::Error {
+                        repository_id: update.repository_id.clone(),
+                        error: update.message.clone(),
+                    }
+                }
+                update if update.status == wikify_applications::IndexingStatus::Cancelled => {
+                    crate::state::IndexingUpdate::Cancelled {
+                        repository_id: update.repository_id.clone(),
+                        progress: update.progress,
+                    }
+                }
+                _ => {
+                    // Handle other statuses as progress updates
+                    let (files_processed, total_files) = extract_progress_numbers(&update.message);
+
+                    crate::state::IndexingUpdate::Progress {
+                        repository_id: update.repository_id.clone(),
+                        stage: update.message.clone(),
+                        percentage: update.progress, // Keep as 0.0-1.0 range
+                        current_item: Some(format!("Status: {:?}", update.status)),
+                        files_processed,
+                        total_files,
+                    }
+                }
             };
 
-            // Send progress update for finalization
-            let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-                crate::state::IndexingUpdate::WikiGenerationProgress {
-                    repository_id: repository_id.to_string(),
-                    stage: "Finalizing wiki generation...".to_string(),
-                    percentage: 0.9, // Use 0.0-1.0 range consistently
-                },
-            ));
-
-            info!(
-                "Successfully generated wiki for repository: {} with {} pages",
-                repository_id,
-                wiki_structure.pages.len()
-            );
-
-            // Cache the generated wiki
-            let cached_wiki = crate::state::CachedWiki {
-                content: wiki_content.clone(),
-                generated_at: chrono::Utc::now(),
-                repository: repository.url.clone(),
-                format: "markdown".to_string(),
-                structure: Some(wiki_structure.clone()),
-            };
+            info!("Sending web progress update: {:?}", web_update);
 
-            let mut wiki_cache = state.wiki_cache.write().await;
-            wiki_cache.insert(repository_id.to_string(), cached_wiki);
-            drop(wiki_cache);
-
-            // Send completion update with actual wiki structure info
-            let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-                crate::state::IndexingUpdate::WikiGenerationComplete {
-                    repository_id: repository_id.to_string(),
-                    wiki_content: wiki_content.clone(),
-                    pages_count: wiki_structure.pages.len(),
-                    sections_count: wiki_structure.sections.len(),
-                },
-            ));
-
-            Ok(())
-        }
-        Err(e) => {
-            let error_msg = format!("Failed to generate wiki: {}", e);
-            error!("{}", error_msg);
-            let _ = progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-                crate::state::IndexingUpdate::WikiGenerationError {
-                    repository_id: repository_id.to_string(),
-                    error: error_msg,
-                },
-            ));
-            Err(e.into())
+            // Forward to web progress broadcaster
+            if let Err(e) =
+                web_progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(web_update))
+            {
+                error!("Failed to send progress update: {}", e);
+            }
         }
-    }
+
+        info!(
+            "Progress forwarding task ended for repository: {}",
+            repo_id_clone
+        );
+    });
 }
 
 /// Initialize repository for processing
@@ -179,6 +189,7 @@ async fn generate_wiki_for_repository(
     responses(
         (status = 200, description = "Repository initialized successfully", body = InitializeRepositoryResponse),
         (status = 409, description = "Repository is already being indexed by another session"),
+        (status = 429, description = "Anonymous repository limit reached"),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -197,25 +208,32 @@ pub async fn initialize_repository(
 
     let auto_index = request.auto_index.unwrap_or(true);
 
+    // Without an explicit token, use the one the user granted at OAuth login
+    let api_token = match request.access_token {
+        Some(token) => Some(token),
+        None => {
+            state
+                .oauth_service
+                .repository_token(&user.id, &request.repository)
+                .await
+        }
+    };
+
     // Use new Repository API
     let repository_options = wikify_applications::RepositoryOptions {
         auto_index,
         metadata: request.metadata,
         access_mode: None, // None means auto-detect
-        api_token: None,
+        api_token,
         extract_metadata: true,
+        mirror_urls: request.mirror_urls.unwrap_or_default(),
+        visibility: request.visibility,
     };
 
-    let repo_type = request.repo_type.clone().unwrap_or_else(|| {
-        // Auto-detect repo type from URL
-        if request.repository.contains("github.com") {
-            "github".to_string()
-        } else if request.repository.contains("gitlab.com") {
-            "gitlab".to_string()
-        } else {
-            "local".to_string()
-        }
-    });
+    let repo_type = request
+        .repo_type
+        .clone()
+        .unwrap_or_else(|| detect_repo_type(&request.repository));
 
     match state
         .application
@@ -230,151 +248,22 @@ pub async fn initialize_repository(
         Ok(repository_id) => {
             info!("Repository initialized successfully: {}", repository_id);
 
-            // Start listening to application progress updates and forward to web progress broadcaster
-            let app_progress_receiver = state.application.subscribe_to_repository_progress();
-            let web_progress_sender = state.progress_broadcaster.clone();
-            let repo_id_clone = repository_id.clone();
-            let auto_generate_wiki = request.auto_generate_wiki.unwrap_or(true);
-            let state_clone = state.clone();
-
-            info!(
-                "Setting up progress forwarding for repository: {}",
-                repository_id
+            spawn_progress_forwarding(
+                &state,
+                repository_id.clone(),
+                request.auto_generate_wiki.unwrap_or(true),
             );
 
-            // Send initial IndexStart event
-            let _ = web_progress_sender.send(crate::state::BroadcastMessage::IndexingUpdate(
-                crate::state::IndexingUpdate::Started {
-                    repository_id: repo_id_clone.clone(),
-                    total_files: None, // Will be updated when we know the actual count
-                    estimated_duration: None, // Will be estimated based on repository size
-                },
-            ));
-
-            tokio::spawn(async move {
-                let mut receiver = app_progress_receiver;
-                while let Ok(update) = receiver.recv().await {
-                    info!("Received application progress update: {:?}", update);
-
-                    // Convert application progress to web progress format
-                    let web_update = match &update {
-                        update
-                            if update.status == wikify_applications::IndexingStatus::Indexing =>
-                        {
-                            // Extract files processed and total from the message if available
-                            let (files_processed, total_files) =
-                                extract_progress_numbers(&update.message);
-
-                            crate::state::IndexingUpdate::Progress {
-                                repository_id: update.repository_id.clone(),
-                                stage: update.message.clone(),
-                                percentage: update.progress, // Keep as 0.0-1.0 range
-                                current_item: Some(format!(
-                                    "Processing... {:.1}%",
-                                    update.progress * 100.0
-                                )),
-                                files_processed,
-                                total_files,
-                            }
-                        }
-                        update
-                            if update.status == wikify_applications::IndexingStatus::Completed =>
-                        {
-                            let complete_update = crate::state::IndexingUpdate::Complete {
-                                repository_id: update.repository_id.clone(),
-                                total_files: 0, // TODO: Get actual stats
-                                total_chunks: 0,
-                                duration_ms: 0,
-                            };
-
-                            // Auto-generate wiki if requested
-                            if auto_generate_wiki && &update.repository_id == &repo_id_clone {
-                                info!(
-                                    "Auto-generating wiki for repository: {}",
-                                    update.repository_id
-                                );
-
-                                // Send wiki generation started update
-                                let _ = web_progress_sender.send(
-                                    crate::state::BroadcastMessage::IndexingUpdate(
-                                        crate::state::IndexingUpdate::WikiGenerationStarted {
-                                            repository_id: update.repository_id.clone(),
-                                        },
-                                    ),
-                                );
-
-                                // Generate wiki in background
-                                let state_for_wiki = state_clone.clone();
-                                let repo_id_for_wiki = update.repository_id.clone();
-                                let progress_sender_for_wiki = web_progress_sender.clone();
-
-                                tokio::spawn(async move {
-                                    if let Err(e) = generate_wiki_for_repository(
-                                        &state_for_wiki,
-                                        &repo_id_for_wiki,
-                                        &progress_sender_for_wiki,
-                                    )
-                                    .await
-                                    {
-                                        error!("Failed to auto-generate wiki: {}", e);
-                                        let _ = progress_sender_for_wiki.send(
-                                            crate::state::BroadcastMessage::IndexingUpdate(
-                                                crate::state::IndexingUpdate::WikiGenerationError {
-                                                    repository_id: repo_id_for_wiki,
-                                                    error: e.to_string(),
-                                                },
-                                            ),
-                                        );
-                                    }
-                                });
-                            }
-
-                            complete_update
-                        }
-                        update if update.status == wikify_applications::IndexingStatus::Failed => {
-                            crate::state::IndexingUpdate::Error {
-                                repository_id: update.repository_id.clone(),
-                                error: update.message.clone(),
-                            }
-                        }
-                        _ => {
-                            // Handle other statuses as progress updates
-                            let (files_processed, total_files) =
-                                extract_progress_numbers(&update.message);
-
-                            crate::state::IndexingUpdate::Progress {
-                                repository_id: update.repository_id.clone(),
-                                stage: update.message.clone(),
-                                percentage: update.progress, // Keep as 0.0-1.0 range
-                                current_item: Some(format!("Status: {:?}", update.status)),
-                                files_processed,
-                                total_files,
-                            }
-                        }
-                    };
-
-                    info!("Sending web progress update: {:?}", web_update);
-
-                    // Forward to web progress broadcaster
-                    if let Err(e) = web_progress_sender
-                        .send(crate::state::BroadcastMessage::IndexingUpdate(web_update))
-                    {
-                        error!("Failed to send progress update: {}", e);
-                    }
-                }
-
-                info!(
-                    "Progress forwarding task ended for repository: {}",
-                    repo_id_clone
-                );
-            });
-
             Ok(Json(InitializeRepositoryResponse {
                 repository_id,
                 status: "success".to_string(),
                 message: "Repository initialized successfully".to_string(),
             }))
         }
+        Err(wikify_applications::ApplicationError::RateLimited { message }) => {
+            warn!("Repository initialization throttled: {}", message);
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        }
         Err(e) => {
             let error_msg = e.to_string();
             error!("Failed to initialize repository: {}", error_msg);
@@ -391,64 +280,307 @@ pub async fn initialize_repository(
     }
 }
 
+/// Register many repositories at once
+#[utoipa::path(
+    post,
+    path = "/api/repositories/batch",
+    tag = "Repository",
+    summary = "Register repositories in bulk",
+    description = "Validate and register a list of repository URLs, queueing each for indexing. URLs are handled one by one, so a bad URL does not stop the others; the response reports the outcome of every URL in request order.",
+    request_body = BatchRepositoryRequest,
+    responses(
+        (status = 200, description = "Batch processed, see the per-URL results", body = BatchRepositoryResponse),
+        (status = 400, description = "No URLs, or more than 200"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn batch_initialize_repositories(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    JsonExtractor(request): JsonExtractor<BatchRepositoryRequest>,
+) -> Result<Json<BatchRepositoryResponse>, StatusCode> {
+    info!(
+        "Registering {} repositories in bulk (user: {})",
+        request.urls.len(),
+        user.id
+    );
+
+    if request.urls.is_empty() || request.urls.len() > MAX_BATCH_REPOSITORIES {
+        warn!(
+            "Rejected batch of {} repositories (at most {})",
+            request.urls.len(),
+            MAX_BATCH_REPOSITORIES
+        );
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let context = user_to_permission_context(&user);
+    let auto_index = request.auto_index.unwrap_or(true);
+    let auto_generate_wiki = request.auto_generate_wiki.unwrap_or(true);
+
+    let mut seen = std::collections::HashSet::new();
+    let mut results = Vec::with_capacity(request.urls.len());
+    for url in request.urls {
+        let url = url.trim().to_string();
+        let mut item = BatchRepositoryItem {
+            url: url.clone(),
+            status: BatchItemStatus::Failed,
+            repository_id: None,
+            error: None,
+        };
+
+        if let Err(message) = validate_repository_url(&url) {
+            item.status = BatchItemStatus::Invalid;
+            item.error = Some(message);
+            results.push(item);
+            continue;
+        }
+        // `.../repo`, `.../repo/` and `.../repo.git` are the same repository
+        let key = url.trim_end_matches('/').trim_end_matches(".git");
+        if !seen.insert(key.to_string()) {
+            item.status = BatchItemStatus::Duplicate;
+            results.push(item);
+            continue;
+        }
+
+        let api_token = match request.access_token {
+            Some(ref token) => Some(token.clone()),
+            None => state.oauth_service.repository_token(&user.id, &url).await,
+        };
+        let repository_options = wikify_applications::RepositoryOptions {
+            auto_index,
+            metadata: None,
+            access_mode: None,
+            api_token,
+            extract_metadata: true,
+            mirror_urls: Vec::new(),
+            visibility: request.visibility,
+        };
+
+        match state
+            .application
+            .add_repository(
+                &context,
+                url.clone(),
+                detect_repo_type(&url),
+                repository_options,
+            )
+            .await
+        {
+            Ok(repository_id) => {
+                spawn_progress_forwarding(&state, repository_id.clone(), auto_generate_wiki);
+                item.status = BatchItemStatus::Created;
+                item.repository_id = Some(repository_id);
+            }
+            Err(e) => {
+                warn!("Failed to register repository {}: {}", url, e);
+                item.error = Some(e.to_string());
+            }
+        }
+        results.push(item);
+    }
+
+    let created = results
+        .iter()
+        .filter(|item| item.status == BatchItemStatus::Created)
+        .count();
+    info!(
+        "Batch registration finished: {} of {} repositories created",
+        created,
+        results.len()
+    );
+
+    Ok(Json(BatchRepositoryResponse {
+        created,
+        failed: results.len() - created,
+        results,
+    }))
+}
+
+/// Upload a local repository as a zip archive
+#[utoipa::path(
+    post,
+    path = "/api/repositories/upload",
+    tag = "Repository",
+    summary = "Upload repository archive",
+    description = "Upload a zip archive of a codebase. The archive is extracted under the wikify base directory and registered as a local directory repository.",
+    request_body(content = UploadRepositoryForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Repository uploaded successfully", body = InitializeRepositoryResponse),
+        (status = 400, description = "Missing or invalid archive"),
+        (status = 413, description = "Archive exceeds the configured upload limit"),
+        (status = 429, description = "Anonymous repository limit reached"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn upload_repository(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    mut multipart: Multipart,
+) -> Result<Json<InitializeRepositoryResponse>, StatusCode> {
+    info!("Receiving repository upload (user: {})", user.id);
+
+    let context = user_to_permission_context(&user);
+    let max_upload_size = state.config.max_upload_size;
+
+    let mut archive: Option<Vec<u8>> = None;
+    let mut name: Option<String> = None;
+    let mut auto_index = true;
+    let mut auto_generate_wiki = true;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        warn!("Invalid multipart upload: {}", e);
+        e.status()
+    })? {
+        let field_name = field.name().unwrap_or_default().to_string();
+        match field_name.as_str() {
+            "file" => {
+                if name.is_none() {
+                    name = field
+                        .file_name()
+                        .map(|f| f.trim_end_matches(".zip").to_string());
+                }
+                let data = field.bytes().await.map_err(|e| {
+                    warn!("Failed to read uploaded archive: {}", e);
+                    e.status()
+                })?;
+                if data.len() > max_upload_size {
+                    return Err(StatusCode::PAYLOAD_TOO_LARGE);
+                }
+                archive = Some(data.to_vec());
+            }
+            "name" => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                if !value.trim().is_empty() {
+                    name = Some(value.trim().to_string());
+                }
+            }
+            "auto_index" => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                auto_index = value.trim().parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+            }
+            "auto_generate_wiki" => {
+                let value = field.text().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+                auto_generate_wiki = value.trim().parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+            }
+            other => {
+                warn!("Ignoring unknown upload field: {}", other);
+            }
+        }
+    }
+
+    let archive = archive.ok_or_else(|| {
+        warn!("Repository upload is missing the 'file' field");
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let repository_options = wikify_applications::RepositoryOptions {
+        auto_index,
+        metadata: None,
+        access_mode: Some(wikify_applications::RepositoryAccessMode::LocalDirectory),
+        api_token: None,
+        extract_metadata: true,
+        mirror_urls: Vec::new(),
+        visibility: None,
+    };
+
+    // Zip archives of source code typically compress well; allow the extracted
+    // tree to grow up to MAX_EXTRACTION_RATIO times the upload limit
+    let max_extracted_size = (max_upload_size as u64).saturating_mul(MAX_EXTRACTION_RATIO);
+
+    match state
+        .application
+        .add_uploaded_repository(
+            &context,
+            archive,
+            name,
+            max_extracted_size,
+            repository_options,
+        )
+        .await
+    {
+        Ok(repository_id) => {
+            info!("Uploaded repository registered: {}", repository_id);
+
+            spawn_progress_forwarding(&state, repository_id.clone(), auto_generate_wiki);
+
+            Ok(Json(InitializeRepositoryResponse {
+                repository_id,
+                status: "success".to_string(),
+                message: "Repository uploaded successfully".to_string(),
+            }))
+        }
+        Err(wikify_applications::ApplicationError::Config { message }) => {
+            warn!("Rejected repository upload: {}", message);
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(wikify_applications::ApplicationError::Permission { message }) => {
+            warn!("Repository upload not permitted: {}", message);
+            Err(StatusCode::FORBIDDEN)
+        }
+        Err(wikify_applications::ApplicationError::RateLimited { message }) => {
+            warn!("Repository upload throttled: {}", message);
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        }
+        Err(e) => {
+            error!("Failed to register uploaded repository: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 /// List user repositories
 #[utoipa::path(
     get,
     path = "/api/repositories",
     tag = "Repository",
     summary = "List repositories",
-    description = "List all repositories accessible to the current user",
+    description = "List the repositories of the current user, or of the organization given in the X-Organization-Id header, newest first unless another order is given",
+    params(RepositoryListQuery),
     responses(
-        (status = 200, description = "Repositories listed successfully"),
+        (status = 200, description = "Repositories listed successfully", body = RepositoryListResponse),
+        (status = 400, description = "Invalid filter"),
+        (status = 403, description = "Insufficient role in the organization"),
+        (status = 404, description = "Organization not found"),
         (status = 500, description = "Internal server error")
     )
 )]
 pub async fn list_repositories(
     State(state): State<AppState>,
     ModeAwareUser(user): ModeAwareUser,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    Query(query): Query<RepositoryListQuery>,
+) -> Result<Json<RepositoryListResponse>, StatusCode> {
     info!("Listing repositories for user: {}", user.id);
 
+    let filter = query.filter().map_err(|e| {
+        warn!("Invalid repository filter: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
 
     // Use new Repository API
-    match state.application.list_repositories(&context).await {
-        Ok(repositories) => {
-            let repo_list: Vec<serde_json::Value> = repositories
+    match state
+        .application
+        .list_repositories(&context, &filter, query.page())
+        .await
+    {
+        Ok(page) => Ok(Json(RepositoryListResponse {
+            repositories: page
+                .items
                 .into_iter()
-                .map(|repo| {
-                    // Convert IndexingStatus to string
-                    let status = match repo.status {
-                        wikify_applications::IndexingStatus::Pending => "pending",
-                        wikify_applications::IndexingStatus::Indexing => "indexing",
-                        wikify_applications::IndexingStatus::Completed => "indexed",
-                        wikify_applications::IndexingStatus::Failed => "failed",
-                        wikify_applications::IndexingStatus::Cancelled => "cancelled",
-                    };
-
-                    serde_json::json!({
-                        "id": repo.id,
-                        "repository": repo.url,
-                        "repo_type": repo.repo_type,
-                        "status": status,
-                        "indexing_progress": repo.progress,
-                        "created_at": repo.created_at,
-                        "last_indexed_at": repo.indexed_at,
-                        "owner": repo.owner_id,
-                        "metadata": repo.metadata
-                    })
-                })
-                .collect();
-
-            let response = serde_json::json!({
-                "repositories": repo_list,
-                "user": user.id,
-                "permissions": user.permissions
-            });
-
-            Ok(Json(response))
-        }
+                .map(RepositorySummaryResponse::from)
+                .collect(),
+            total: page.total,
+            limit: page.limit,
+            offset: page.offset,
+            user: user.id,
+            organization_id: user.organization_id,
+            permissions: user.permissions,
+        })),
+        Err(wikify_applications::ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(wikify_applications::ApplicationError::NotFound { .. }) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
             error!("Failed to list repositories: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -467,7 +599,7 @@ pub async fn list_repositories(
         ("repository_id" = String, Path, description = "Repository ID")
     ),
     responses(
-        (status = 200, description = "Repository information retrieved successfully"),
+        (status = 200, description = "Repository information retrieved successfully", body = RepositoryInfoResponse),
         (status = 404, description = "Repository not found")
     )
 )]
@@ -475,7 +607,7 @@ pub async fn get_repository_info(
     State(state): State<AppState>,
     ModeAwareUser(user): ModeAwareUser,
     Path(repository_id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<RepositoryInfoResponse>, StatusCode> {
     info!(
         "Getting repository info for repository: {} (user: {})",
         repository_id, user.id
@@ -489,18 +621,7 @@ pub async fn get_repository_info(
         .get_repository(&context, &repository_id)
         .await
     {
-        Ok(repository) => {
-            let info = serde_json::json!({
-                "repository_id": repository.id,
-                "url": repository.url,
-                "repo_type": repository.repo_type,
-                "status": repository.status,
-                "created_at": repository.created_at,
-                "last_indexed_at": repository.indexed_at,
-                "progress": repository.progress,
-            });
-            Ok(Json(info))
-        }
+        Ok(repository) => Ok(Json(RepositoryInfoResponse::from(repository))),
         Err(_) => {
             warn!("Repository not found: {}", repository_id);
             Err(StatusCode::NOT_FOUND)
@@ -508,6 +629,47 @@ pub async fn get_repository_info(
     }
 }
 
+/// Get repository metrics
+#[utoipa::path(
+    get,
+    path = "/api/repositories/{repository_id}/metrics",
+    tag = "Repository",
+    summary = "Get repository metrics",
+    description = "Duration and size of the last indexing run, and the number, failures and latency of indexing runs and queries of a repository since the server started",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 200, description = "Repository metrics", body = wikify_applications::RepositoryOperationStats),
+        (status = 403, description = "Access denied"),
+        (status = 404, description = "Repository not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_repository_metrics(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(repository_id): Path<String>,
+) -> Result<Json<wikify_applications::RepositoryOperationStats>, StatusCode> {
+    let context = user_to_permission_context(&user);
+    match state
+        .application
+        .repository_metrics(&context, &repository_id)
+        .await
+    {
+        Ok(metrics) => Ok(Json(metrics)),
+        Err(ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(ApplicationError::NotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!(
+                "Failed to get metrics of repository {}: {}",
+                repository_id, e
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 /// Reindex repository
 #[utoipa::path(
     post,
@@ -563,6 +725,7 @@ pub async fn reindex_repository(
         .await
     {
         Ok(()) => {
+            state.wiki_cache.write().await.remove(&repository_id);
             let response = InitializeRepositoryResponse {
                 repository_id: repository_id.clone(),
                 status: "success".to_string(),
@@ -585,114 +748,188 @@ pub async fn reindex_repository(
     }
 }
 
-/// Delete repository
+/// Cancel repository indexing
 #[utoipa::path(
-    delete,
-    path = "/api/repositories/{repository_id}",
+    post,
+    path = "/api/repositories/{repository_id}/index/cancel",
     tag = "Repository",
-    summary = "Delete repository",
-    description = "Delete a repository and all associated data including sessions, vector data, and database records",
+    summary = "Cancel repository indexing",
+    description = "Cancel the queued or running indexing job of a repository. A queued job is removed right away; a running job stops at its next checkpoint. The repository is marked as cancelled with the progress it reached, and an `IndexCancelled` WebSocket message is sent.",
     params(
-        ("repository_id" = String, Path, description = "Repository ID to delete")
+        ("repository_id" = String, Path, description = "Repository ID whose indexing should be cancelled")
     ),
     responses(
-        (status = 200, description = "Repository deleted successfully", body = DeleteRepositoryResponse),
+        (status = 202, description = "Cancellation requested", body = InitializeRepositoryResponse),
+        (status = 400, description = "Repository is not being indexed"),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Repository not found"),
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn delete_repository(
+pub async fn cancel_indexing(
     State(state): State<AppState>,
     ModeAwareUser(user): ModeAwareUser,
     Path(repository_id): Path<String>,
-) -> Result<Json<DeleteRepositoryResponse>, StatusCode> {
-    info!("Deleting repository: {} (user: {})", repository_id, user.id);
+) -> Result<(StatusCode, Json<InitializeRepositoryResponse>), StatusCode> {
+    info!(
+        "Cancelling indexing for repository: {} (user: {})",
+        repository_id, user.id
+    );
 
-    // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
-    match state.delete_repository(&repository_id).await {
-        Ok(()) => Ok(Json(DeleteRepositoryResponse {
-            status: "success".to_string(),
-            message: "Repository deleted successfully".to_string(),
-            deleted_repository_id: repository_id.clone(),
-        })),
+    match state
+        .application
+        .cancel_indexing(&context, &repository_id)
+        .await
+    {
+        Ok(()) => Ok((
+            StatusCode::ACCEPTED,
+            Json(InitializeRepositoryResponse {
+                repository_id,
+                status: "cancelling".to_string(),
+                message: "Indexing cancellation requested".to_string(),
+            }),
+        )),
         Err(e) => {
-            tracing::error!("Failed to delete repository {}: {}", repository_id, e);
+            warn!(
+                "Failed to cancel indexing for repository {}: {}",
+                repository_id, e
+            );
+            Err(match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+                ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            })
+        }
+    }
+}
+
+/// Share repository
+#[utoipa::path(
+    post,
+    path = "/api/repositories/{repository_id}/share",
+    tag = "Repository",
+    summary = "Share repository",
+    description = "Change the visibility of a repository (`private`, `org` or `public`) and share it with, or stop sharing it with, individual users. Users a repository is shared with can see and query it. Only the owner, admins and admins of the repository's organization can change sharing.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    request_body = ShareRepositoryRequest,
+    responses(
+        (status = 200, description = "Sharing updated", body = RepositorySharingResponse),
+        (status = 400, description = "Organization visibility on a repository outside an organization"),
+        (status = 403, description = "Only the owner can change sharing"),
+        (status = 404, description = "Repository not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn share_repository(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(repository_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<ShareRepositoryRequest>,
+) -> Result<Json<RepositorySharingResponse>, StatusCode> {
+    info!(
+        "Updating sharing of repository {} (user: {})",
+        repository_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+    let repository = state
+        .application
+        .share_repository(
+            &context,
+            &repository_id,
+            request.visibility,
+            &request.share_with,
+            &request.revoke,
+        )
+        .await
+        .map_err(|e| {
+            warn!(
+                "Failed to update sharing of repository {}: {}",
+                repository_id, e
+            );
             match e {
-                WebError::NotFound(_) => Err(StatusCode::NOT_FOUND),
-                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+                ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
             }
+        })?;
+
+    Ok(Json(repository.into()))
+}
+
+/// List indexing jobs
+#[utoipa::path(
+    get,
+    path = "/api/jobs",
+    tag = "Repository",
+    summary = "List indexing jobs",
+    description = "List running and queued indexing jobs. Running jobs come first, followed by queued jobs in the order they will start: user-requested reindexes before background indexing, and otherwise taking turns between users. Users see their own jobs; admins and open mode see all jobs.",
+    responses(
+        (status = 200, description = "Indexing jobs retrieved successfully", body = IndexingJobsResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_indexing_jobs(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+) -> Result<Json<IndexingJobsResponse>, StatusCode> {
+    let context = user_to_permission_context(&user);
+    match state.application.list_indexing_jobs(&context).await {
+        Ok(jobs) => Ok(Json(IndexingJobsResponse::from(jobs))),
+        Err(ApplicationError::Permission { .. }) => Err(StatusCode::FORBIDDEN),
+        Err(e) => {
+            error!("Failed to list indexing jobs: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-/// Get all repositories (SQLite feature only)
-#[cfg(feature = "sqlite")]
+/// Delete repository
 #[utoipa::path(
-    get,
-    path = "/api/repositories",
+    delete,
+    path = "/api/repositories/{repository_id}",
     tag = "Repository",
-    summary = "Get all repositories",
-    description = "Get a list of all repositories (requires SQLite feature)",
+    summary = "Delete repository",
+    description = "Delete a repository and all associated data including sessions, vector data, and database records",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID to delete")
+    ),
     responses(
-        (status = 200, description = "Repositories retrieved successfully"),
+        (status = 200, description = "Repository deleted successfully", body = DeleteRepositoryResponse),
+        (status = 404, description = "Repository not found"),
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn get_repositories(
+pub async fn delete_repository(
     State(state): State<AppState>,
-    crate::auth::RequireQuery(user): crate::auth::RequireQuery,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    info!("Getting repositories list (user: {})", user.id);
+    ModeAwareUser(user): ModeAwareUser,
+    Path(repository_id): Path<String>,
+) -> Result<Json<DeleteRepositoryResponse>, StatusCode> {
+    info!("Deleting repository: {} (user: {})", repository_id, user.id);
 
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
-    if let Some(database) = &state.database {
-        match database.get_repositories().await {
-            Ok(repositories) => {
-                // Get current repository states from application layer
-                let _context = state.create_anonymous_context();
-                // Note: Session-based status checking is no longer available
-                // Repository status is now managed directly by the Repository Manager
-
-                let repos_json: Vec<serde_json::Value> = repositories
-                    .into_iter()
-                    .map(|repo| {
-                        // Repository status is now managed directly, no session lookup needed
-                        let current_status = &repo.status;
-                        let indexing_progress = 0.0; // Progress tracking moved to Repository Manager
-                        let last_activity = repo.created_at.to_rfc3339(); // Use creation time as fallback
-
-                        serde_json::json!({
-                            "id": repo.id,
-                            "name": repo.name,
-                            "repo_path": repo.repo_path,
-                            "repo_type": repo.repo_type,
-                            "status": current_status,
-                            "indexing_progress": indexing_progress,
-                            "created_at": repo.created_at,
-                            "last_indexed_at": repo.last_indexed_at,
-                            "last_activity": last_activity,
-                        })
-                    })
-                    .collect();
-
-                Ok(Json(serde_json::json!({
-                    "repositories": repos_json,
-                    "count": repos_json.len()
-                })))
-            }
-            Err(e) => {
-                tracing::error!("Failed to get repositories: {}", e);
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
+    match state.delete_repository(&repository_id).await {
+        Ok(()) => {
+            state.wiki_cache.write().await.remove(&repository_id);
+            Ok(Json(DeleteRepositoryResponse {
+                status: "success".to_string(),
+                message: "Repository deleted successfully".to_string(),
+                deleted_repository_id: repository_id.clone(),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Failed to delete repository {}: {}", repository_id, e);
+            match e {
+                WebError::NotFound(_) => Err(StatusCode::NOT_FOUND),
+                _ => Err(StatusCode::INTERNAL_SERVER_ERROR),
             }
         }
-    } else {
-        // 数据库未启用，返回空列表
-        Ok(Json(serde_json::json!({
-            "repositories": [],
-            "count": 0,
-            "message": "Database not enabled"
-        })))
     }
 }
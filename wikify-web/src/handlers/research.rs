@@ -1,32 +1,47 @@
 //! Research functionality handlers
 
 use super::types::{
-    ResearchProgressResponse, ResearchTemplateResponse, StartResearchFromTemplateRequest,
-    StartResearchRequest, StartResearchResponse,
+    ResearchActionResponse, ResearchHistoryQuery, ResearchHistoryResponse,
+    ResearchProgressResponse, ResearchRecordResponse, ResearchResultResponse,
+    ResearchSessionListResponse, ResearchSessionSummary, ResearchStatisticsResponse,
+    ResearchTemplateBody, ResearchTemplateListResponse, ResearchTemplateResponse,
+    StartResearchFromTemplateRequest, StartResearchRequest, StartResearchResponse,
 };
 use crate::{
-    auth::{ModeAwareUser, RequireQuery},
+    auth::{ModeAwareUser, RequireDeepResearch, RequireQuery},
     AppState,
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     response::{sse::Event, Json, Sse},
     Json as JsonExtractor,
 };
-use futures_util::stream::{self, Stream};
+use futures_util::stream::{self, Stream, StreamExt};
 use std::convert::Infallible;
 use std::time::Duration;
 use tracing::{error, info, warn};
 use uuid::Uuid;
-use wikify_applications::research::types::ResearchStatus;
-use wikify_applications::{ResearchCategory, ResearchTemplate};
+use wikify_applications::research::{types::ResearchStatus, ResearchHistoryFilters};
+use wikify_applications::{
+    ApplicationError, QuestionType, ResearchCategory, ResearchConfig, ResearchTemplate,
+};
 
 /// Helper function to convert User to PermissionContext for application layer
 fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::PermissionContext {
     user.to_permission_context()
 }
 
+/// Map an application error to the status code returned by the research API
+fn research_error_status(error: ApplicationError) -> StatusCode {
+    match error {
+        ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+        ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+        ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 /// Start research session
 #[utoipa::path(
     post,
@@ -37,6 +52,7 @@ fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::
     request_body = StartResearchRequest,
     responses(
         (status = 200, description = "Research session started successfully", body = StartResearchResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Repository not found"),
         (status = 500, description = "Failed to start research session")
     )
@@ -104,11 +120,7 @@ pub async fn start_research(
         }
         Err(e) => {
             error!("Failed to start research session: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
@@ -119,12 +131,13 @@ pub async fn start_research(
     path = "/api/research/progress/{repository_id}",
     tag = "Research",
     summary = "Get research progress",
-    description = "Get the current progress of a research session",
+    description = "Get the current progress of the most recently started research session for a repository",
     params(
         ("repository_id" = String, Path, description = "Repository ID")
     ),
     responses(
         (status = 200, description = "Research progress retrieved successfully", body = ResearchProgressResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Research session not found"),
         (status = 500, description = "Failed to get research progress")
     )
@@ -142,10 +155,19 @@ pub async fn get_research_progress(
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
 
-    // Get research progress using application layer
+    // Get progress of the repository's latest research session
+    let research_id = state
+        .application
+        .latest_research_session(&context, &repository_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to find research session: {}", e);
+            research_error_status(e)
+        })?;
+
     match state
         .application
-        .get_research_progress(&context, &repository_id)
+        .get_research_progress(&context, &research_id)
         .await
     {
         Ok(progress) => {
@@ -154,11 +176,7 @@ pub async fn get_research_progress(
         }
         Err(e) => {
             error!("Failed to get research progress: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
@@ -175,6 +193,7 @@ pub async fn get_research_progress(
     ),
     responses(
         (status = 200, description = "Research progress retrieved successfully", body = ResearchProgressResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Research session not found"),
         (status = 500, description = "Failed to get research progress")
     )
@@ -204,11 +223,7 @@ pub async fn get_research_progress_by_id(
         }
         Err(e) => {
             error!("Failed to get research progress: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
@@ -224,7 +239,8 @@ pub async fn get_research_progress_by_id(
         ("research_id" = String, Path, description = "Research session ID")
     ),
     responses(
-        (status = 200, description = "Research session stopped successfully"),
+        (status = 200, description = "Research session stopped successfully", body = ResearchActionResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Research session not found"),
         (status = 500, description = "Failed to stop research session")
     )
@@ -233,7 +249,7 @@ pub async fn stop_research(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
     Path(research_id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchActionResponse>, StatusCode> {
     info!(
         "Stopping research session: {} (user: {})",
         research_id, user.id
@@ -250,19 +266,16 @@ pub async fn stop_research(
     {
         Ok(()) => {
             info!("Research session stopped successfully");
-            Ok(Json(serde_json::json!({
-                "status": "stopped",
-                "message": "Research session stopped successfully",
-                "research_id": research_id
-            })))
+            Ok(Json(ResearchActionResponse {
+                status: "stopped".to_string(),
+                message: "Research session stopped successfully".to_string(),
+                research_id: Some(research_id),
+                repository_id: None,
+            }))
         }
         Err(e) => {
             error!("Failed to stop research session: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
@@ -275,14 +288,15 @@ pub async fn stop_research(
     summary = "List research sessions",
     description = "List all research sessions for the current user",
     responses(
-        (status = 200, description = "Research sessions listed successfully"),
+        (status = 200, description = "Research sessions listed successfully", body = ResearchSessionListResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 500, description = "Failed to list research sessions")
     )
 )]
 pub async fn list_research_sessions(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchSessionListResponse>, StatusCode> {
     info!("Listing research sessions (user: {})", user.id);
 
     // Convert to permission context for application layer
@@ -292,24 +306,22 @@ pub async fn list_research_sessions(
     match state.application.list_active_research(&context).await {
         Ok(research_ids) => {
             info!("Research sessions listed successfully");
-            let sessions_json: Vec<serde_json::Value> = research_ids
+            let sessions: Vec<ResearchSessionSummary> = research_ids
                 .into_iter()
-                .map(|research_id| {
-                    serde_json::json!({
-                        "research_id": research_id,
-                        "status": "active"
-                    })
+                .map(|research_id| ResearchSessionSummary {
+                    research_id,
+                    status: "active".to_string(),
                 })
                 .collect();
 
-            Ok(Json(serde_json::json!({
-                "sessions": sessions_json,
-                "count": sessions_json.len()
-            })))
+            Ok(Json(ResearchSessionListResponse {
+                count: sessions.len(),
+                sessions,
+            }))
         }
         Err(e) => {
             error!("Failed to list research sessions: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(research_error_status(e))
         }
     }
 }
@@ -326,7 +338,8 @@ pub async fn list_research_sessions(
     ),
     responses(
         (status = 200, description = "Research iteration completed successfully", body = ResearchProgressResponse),
-        (status = 404, description = "Repository not found"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "No research session for the repository"),
         (status = 500, description = "Failed to execute research iteration")
     )
 )]
@@ -343,10 +356,19 @@ pub async fn research_iteration(
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
 
-    // Execute research iteration using application layer
+    // Execute research iteration for the repository's latest session
+    let research_id = state
+        .application
+        .latest_research_session(&context, &repository_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to find research session: {}", e);
+            research_error_status(e)
+        })?;
+
     match state
         .application
-        .research_iteration(&context, &repository_id, &repository_id) // Using repository_id as research_session_id
+        .research_iteration(&context, &repository_id, &research_id)
         .await
     {
         Ok(progress) => {
@@ -355,11 +377,7 @@ pub async fn research_iteration(
         }
         Err(e) => {
             error!("Failed to execute research iteration: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
@@ -372,28 +390,29 @@ pub async fn research_iteration(
     summary = "List research templates",
     description = "List all available research templates",
     responses(
-        (status = 200, description = "Research templates listed successfully"),
+        (status = 200, description = "Research templates listed successfully", body = ResearchTemplateListResponse),
         (status = 500, description = "Failed to list research templates")
     )
 )]
 pub async fn list_research_templates(
     State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchTemplateListResponse>, StatusCode> {
     info!("Listing research templates");
 
     // List research templates using application layer
     match state.application.list_research_templates().await {
         Ok(templates) => {
             info!("Research templates listed successfully");
-            let templates_json: Vec<ResearchTemplateResponse> = templates
+            let templates: Vec<ResearchTemplateResponse> = templates
                 .into_iter()
                 .map(ResearchTemplateResponse::from)
                 .collect();
 
-            Ok(Json(serde_json::json!({
-                "templates": templates_json,
-                "count": templates_json.len()
-            })))
+            Ok(Json(ResearchTemplateListResponse {
+                count: templates.len(),
+                templates,
+                category: None,
+            }))
         }
         Err(e) => {
             error!("Failed to list research templates: {}", e);
@@ -432,11 +451,7 @@ pub async fn get_research_template(
         }
         Err(e) => {
             error!("Failed to get research template: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
@@ -452,30 +467,18 @@ pub async fn get_research_template(
         ("category" = String, Path, description = "Template category")
     ),
     responses(
-        (status = 200, description = "Research templates listed successfully"),
+        (status = 200, description = "Research templates listed successfully", body = ResearchTemplateListResponse),
         (status = 500, description = "Failed to list research templates")
     )
 )]
 pub async fn list_templates_by_category(
     State(state): State<AppState>,
     Path(category): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchTemplateListResponse>, StatusCode> {
     info!("Listing research templates by category: {}", category);
 
     // Parse category string to ResearchCategory enum
-    let research_category = match category.to_lowercase().as_str() {
-        "security" => ResearchCategory::Security,
-        "architecture" => ResearchCategory::Architecture,
-        "performance" => ResearchCategory::Performance,
-        "documentation" => ResearchCategory::Documentation,
-        "technical" => ResearchCategory::Technical,
-        "business" => ResearchCategory::Business,
-        "custom" => ResearchCategory::Custom,
-        _ => {
-            error!("Invalid category: {}", category);
-            return Err(StatusCode::BAD_REQUEST);
-        }
-    };
+    let research_category = parse_category(&category)?;
 
     // List research templates by category using application layer
     match state
@@ -488,16 +491,16 @@ pub async fn list_templates_by_category(
                 "Research templates listed successfully for category: {}",
                 category
             );
-            let templates_json: Vec<ResearchTemplateResponse> = templates
+            let templates: Vec<ResearchTemplateResponse> = templates
                 .into_iter()
                 .map(ResearchTemplateResponse::from)
                 .collect();
 
-            Ok(Json(serde_json::json!({
-                "templates": templates_json,
-                "category": category,
-                "count": templates_json.len()
-            })))
+            Ok(Json(ResearchTemplateListResponse {
+                count: templates.len(),
+                templates,
+                category: Some(category),
+            }))
         }
         Err(e) => {
             error!("Failed to list research templates by category: {}", e);
@@ -506,6 +509,214 @@ pub async fn list_templates_by_category(
     }
 }
 
+/// Parse a template category name
+fn parse_category(category: &str) -> Result<ResearchCategory, StatusCode> {
+    match category.to_lowercase().as_str() {
+        "security" => Ok(ResearchCategory::Security),
+        "architecture" => Ok(ResearchCategory::Architecture),
+        "performance" => Ok(ResearchCategory::Performance),
+        "documentation" => Ok(ResearchCategory::Documentation),
+        "technical" => Ok(ResearchCategory::Technical),
+        "business" => Ok(ResearchCategory::Business),
+        "custom" => Ok(ResearchCategory::Custom),
+        _ => {
+            error!("Invalid category: {}", category);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Parse a template question type name
+fn parse_question_type(question_type: &str) -> Result<QuestionType, StatusCode> {
+    match question_type.to_lowercase().as_str() {
+        "conceptual" => Ok(QuestionType::Conceptual),
+        "technical" => Ok(QuestionType::Technical),
+        "architectural" => Ok(QuestionType::Architectural),
+        "historical" => Ok(QuestionType::Historical),
+        "comparative" => Ok(QuestionType::Comparative),
+        "diagnostic" => Ok(QuestionType::Diagnostic),
+        "advisory" => Ok(QuestionType::Advisory),
+        _ => {
+            error!("Invalid question type: {}", question_type);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// Build a research template from a request body
+fn template_from_body(
+    id: String,
+    body: ResearchTemplateBody,
+) -> Result<ResearchTemplate, StatusCode> {
+    let category = match body.category {
+        Some(category) => parse_category(&category)?,
+        None => ResearchCategory::Custom,
+    };
+
+    let mut config = ResearchConfig::default();
+    if let Some(overrides) = body.config {
+        config.max_iterations = overrides.max_iterations.unwrap_or(config.max_iterations);
+        config.max_sources_per_iteration = overrides
+            .max_sources_per_iteration
+            .unwrap_or(config.max_sources_per_iteration);
+    }
+
+    let initial_questions = body
+        .questions
+        .into_iter()
+        .map(|question| {
+            Ok(wikify_applications::research::TemplateQuestion {
+                text: question.text,
+                question_type: parse_question_type(&question.question_type)?,
+                priority: question.priority.unwrap_or(5),
+                complexity: question.complexity.unwrap_or(5),
+                keywords: question.keywords,
+            })
+        })
+        .collect::<Result<Vec<_>, StatusCode>>()?;
+
+    Ok(ResearchTemplate {
+        id,
+        name: body.name,
+        description: body.description,
+        category,
+        config,
+        initial_questions,
+        parameters: Default::default(),
+    })
+}
+
+/// Create research template
+#[utoipa::path(
+    post,
+    path = "/api/research/templates",
+    tag = "Research",
+    summary = "Create research template",
+    description = "Add a user-defined research template. Question types must be known and priorities between 1 and 10.",
+    request_body = ResearchTemplateBody,
+    responses(
+        (status = 201, description = "Research template created successfully", body = ResearchTemplateResponse),
+        (status = 400, description = "Invalid template or template ID already in use"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 500, description = "Failed to create research template")
+    )
+)]
+pub async fn create_research_template(
+    State(state): State<AppState>,
+    RequireDeepResearch(user): RequireDeepResearch,
+    JsonExtractor(body): JsonExtractor<ResearchTemplateBody>,
+) -> Result<(StatusCode, Json<ResearchTemplateResponse>), StatusCode> {
+    let id = body.id.clone().ok_or(StatusCode::BAD_REQUEST)?;
+    info!("Creating research template: {} (user: {})", id, user.id);
+
+    let context = user_to_permission_context(&user);
+    let template = template_from_body(id, body)?;
+
+    match state
+        .application
+        .create_research_template(&context, template)
+        .await
+    {
+        Ok(template) => Ok((
+            StatusCode::CREATED,
+            Json(ResearchTemplateResponse::from(template)),
+        )),
+        Err(e) => {
+            error!("Failed to create research template: {}", e);
+            Err(research_error_status(e))
+        }
+    }
+}
+
+/// Update research template
+#[utoipa::path(
+    put,
+    path = "/api/research/templates/{template_id}",
+    tag = "Research",
+    summary = "Update research template",
+    description = "Replace a user-defined research template. Built-in templates cannot be modified.",
+    params(
+        ("template_id" = String, Path, description = "Template ID")
+    ),
+    request_body = ResearchTemplateBody,
+    responses(
+        (status = 200, description = "Research template updated successfully", body = ResearchTemplateResponse),
+        (status = 400, description = "Invalid template or built-in template"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "Template not found"),
+        (status = 500, description = "Failed to update research template")
+    )
+)]
+pub async fn update_research_template(
+    State(state): State<AppState>,
+    RequireDeepResearch(user): RequireDeepResearch,
+    Path(template_id): Path<String>,
+    JsonExtractor(body): JsonExtractor<ResearchTemplateBody>,
+) -> Result<Json<ResearchTemplateResponse>, StatusCode> {
+    info!(
+        "Updating research template: {} (user: {})",
+        template_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+    let template = template_from_body(template_id.clone(), body)?;
+
+    match state
+        .application
+        .update_research_template(&context, &template_id, template)
+        .await
+    {
+        Ok(template) => Ok(Json(ResearchTemplateResponse::from(template))),
+        Err(e) => {
+            error!("Failed to update research template: {}", e);
+            Err(research_error_status(e))
+        }
+    }
+}
+
+/// Delete research template
+#[utoipa::path(
+    delete,
+    path = "/api/research/templates/{template_id}",
+    tag = "Research",
+    summary = "Delete research template",
+    description = "Delete a user-defined research template. Built-in templates cannot be deleted.",
+    params(
+        ("template_id" = String, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 204, description = "Research template deleted successfully"),
+        (status = 400, description = "Built-in template"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "Template not found"),
+        (status = 500, description = "Failed to delete research template")
+    )
+)]
+pub async fn delete_research_template(
+    State(state): State<AppState>,
+    RequireDeepResearch(user): RequireDeepResearch,
+    Path(template_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    info!(
+        "Deleting research template: {} (user: {})",
+        template_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+
+    match state
+        .application
+        .delete_research_template(&context, &template_id)
+        .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            error!("Failed to delete research template: {}", e);
+            Err(research_error_status(e))
+        }
+    }
+}
+
 /// Start research from template
 #[utoipa::path(
     post,
@@ -516,6 +727,7 @@ pub async fn list_templates_by_category(
     request_body = StartResearchFromTemplateRequest,
     responses(
         (status = 200, description = "Research session started successfully", body = StartResearchResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Repository or template not found"),
         (status = 500, description = "Failed to start research session")
     )
@@ -558,70 +770,93 @@ pub async fn start_research_from_template(
         }
         Err(e) => {
             error!("Failed to start research session from template: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
 
 /// Get research history
+#[utoipa::path(
+    get,
+    path = "/api/research/history",
+    tag = "Research",
+    summary = "Get research history",
+    description = "List recorded research sessions, newest first",
+    params(ResearchHistoryQuery),
+    responses(
+        (status = 200, description = "Research history retrieved successfully", body = ResearchHistoryResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 500, description = "Failed to get research history")
+    )
+)]
 pub async fn get_research_history(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    Query(query): Query<ResearchHistoryQuery>,
+) -> Result<Json<ResearchHistoryResponse>, StatusCode> {
     info!("Getting research history (user: {})", user.id);
 
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
 
+    let page = super::types::page_request(query.limit, query.offset);
+    let filters = ResearchHistoryFilters {
+        template_id: query.template_id,
+        offset: Some(page.offset),
+        ..Default::default()
+    };
+
     // Get research history using application layer
     match state
         .application
-        .get_research_history(&context, None, None)
+        .get_research_history(&context, Some(filters), page.limit)
         .await
     {
         Ok(history) => {
             info!("Research history retrieved successfully");
-            let history_json: Vec<serde_json::Value> = history
+            let records: Vec<ResearchRecordResponse> = history
+                .items
                 .into_iter()
-                .map(|record| {
-                    serde_json::json!({
-                        "id": record.session_id,
-                        "repository_id": record.context.repository_id,
-                        "research_question": record.topic,
-                        "status": format!("{:?}", record.status).to_lowercase(),
-                        "created_at": record.created_at,
-                        "updated_at": record.updated_at,
-                        "findings": record.iterations.iter()
-                            .flat_map(|iter| &iter.findings)
-                            .map(|finding| &finding.content)
-                            .collect::<Vec<_>>(),
-                        "metadata": record.metadata
-                    })
-                })
+                .map(ResearchRecordResponse::from)
                 .collect();
 
-            Ok(Json(serde_json::json!({
-                "history": history_json,
-                "count": history_json.len()
-            })))
+            Ok(Json(ResearchHistoryResponse {
+                count: records.len(),
+                history: records,
+                total: history.total,
+                limit: history.limit,
+                offset: history.offset,
+            }))
         }
         Err(e) => {
             error!("Failed to get research history: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(research_error_status(e))
         }
     }
 }
 
 /// Get research record by repository ID
+#[utoipa::path(
+    get,
+    path = "/api/research/history/{repository_id}",
+    tag = "Research",
+    summary = "Get research record",
+    description = "Get the recorded research session for a repository",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 200, description = "Research record retrieved successfully", body = ResearchRecordResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "No research record for the repository"),
+        (status = 500, description = "Failed to get research record")
+    )
+)]
 pub async fn get_research_record(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
     Path(repository_id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchRecordResponse>, StatusCode> {
     info!(
         "Getting research record for repository: {} (user: {})",
         repository_id, user.id
@@ -638,39 +873,37 @@ pub async fn get_research_record(
     {
         Ok(record) => {
             info!("Research record retrieved successfully");
-            let record_json = serde_json::json!({
-                "id": record.session_id,
-                "repository_id": record.context.repository_id,
-                "research_question": record.topic,
-                "status": format!("{:?}", record.status).to_lowercase(),
-                "created_at": record.created_at,
-                "updated_at": record.updated_at,
-                "findings": record.iterations.iter()
-                    .flat_map(|iter| &iter.findings)
-                    .map(|finding| &finding.content)
-                    .collect::<Vec<_>>(),
-                "metadata": record.metadata
-            });
-
-            Ok(Json(record_json))
+            Ok(Json(ResearchRecordResponse::from(record)))
         }
         Err(e) => {
             error!("Failed to get research record: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
 
 /// Delete research record
+#[utoipa::path(
+    delete,
+    path = "/api/research/history/{repository_id}",
+    tag = "Research",
+    summary = "Delete research record",
+    description = "Delete the recorded research session for a repository. Requires repository management permission.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 200, description = "Research record deleted successfully", body = ResearchActionResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "No research record for the repository"),
+        (status = 500, description = "Failed to delete research record")
+    )
+)]
 pub async fn delete_research_record(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
     Path(repository_id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchActionResponse>, StatusCode> {
     info!(
         "Deleting research record for repository: {} (user: {})",
         repository_id, user.id
@@ -687,28 +920,37 @@ pub async fn delete_research_record(
     {
         Ok(()) => {
             info!("Research record deleted successfully");
-            Ok(Json(serde_json::json!({
-                "status": "deleted",
-                "message": "Research record deleted successfully",
-                "repository_id": repository_id
-            })))
+            Ok(Json(ResearchActionResponse {
+                status: "deleted".to_string(),
+                message: "Research record deleted successfully".to_string(),
+                research_id: None,
+                repository_id: Some(repository_id),
+            }))
         }
         Err(e) => {
             error!("Failed to delete research record: {}", e);
-            if e.to_string().contains("not found") {
-                Err(StatusCode::NOT_FOUND)
-            } else {
-                Err(StatusCode::INTERNAL_SERVER_ERROR)
-            }
+            Err(research_error_status(e))
         }
     }
 }
 
 /// Get research statistics
+#[utoipa::path(
+    get,
+    path = "/api/research/statistics",
+    tag = "Research",
+    summary = "Get research statistics",
+    description = "Get aggregate statistics over recorded research sessions",
+    responses(
+        (status = 200, description = "Research statistics retrieved successfully", body = ResearchStatisticsResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 500, description = "Failed to get research statistics")
+    )
+)]
 pub async fn get_research_statistics(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchStatisticsResponse>, StatusCode> {
     info!("Getting research statistics (user: {})", user.id);
 
     // Convert to permission context for application layer
@@ -718,21 +960,11 @@ pub async fn get_research_statistics(
     match state.application.get_research_statistics(&context).await {
         Ok(stats) => {
             info!("Research statistics retrieved successfully");
-            let stats_json = serde_json::json!({
-                "total_sessions": stats.total_sessions,
-                "active_sessions": stats.in_progress_sessions,
-                "completed_sessions": stats.completed_sessions,
-                "failed_sessions": stats.failed_sessions,
-                "average_session_duration": stats.average_duration_seconds,
-                "popular_templates": stats.popular_templates,
-                "activity_by_date": stats.activity_by_date
-            });
-
-            Ok(Json(stats_json))
+            Ok(Json(ResearchStatisticsResponse::from(stats)))
         }
         Err(e) => {
             error!("Failed to get research statistics: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(research_error_status(e))
         }
     }
 }
@@ -751,6 +983,7 @@ pub async fn get_research_statistics(
     request_body = StartResearchRequest,
     responses(
         (status = 200, description = "Streaming research updates", content_type = "text/event-stream"),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Repository not found"),
         (status = 500, description = "Failed to start research session")
     )
@@ -779,28 +1012,36 @@ pub async fn start_deep_research_stream(
             enable_parallel_research: true,
         });
 
-    // Start research session
+    // Start research session, cancelled when the client disconnects
+    let cancel = wikify_core::CancellationToken::new();
     let research_id = match state
         .application
-        .start_research(
+        .start_research_cancellable(
             &context,
             &request.repository_id,
             request.research_question.clone(),
             config,
+            &cancel,
         )
         .await
     {
         Ok(id) => id,
         Err(e) => {
             error!("Failed to start research: {}", e);
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(research_error_status(e));
         }
     };
 
     info!("Started deep research session: {}", research_id);
 
-    // Create streaming response
-    let stream = create_research_progress_stream(state, research_id, request.research_question);
+    // Create streaming response; dropping it stops the research
+    let cancel_on_drop = cancel.drop_guard();
+    let stream =
+        create_research_progress_stream(state, context, research_id, request.research_question)
+            .map(move |event| {
+                let _ = &cancel_on_drop;
+                event
+            });
 
     Ok(Sse::new(stream).keep_alive(
         axum::response::sse::KeepAlive::new()
@@ -812,19 +1053,19 @@ pub async fn start_deep_research_stream(
 /// Create a stream of research progress updates
 fn create_research_progress_stream(
     state: AppState,
+    context: wikify_applications::PermissionContext,
     research_id: String,
     original_query: String,
 ) -> impl Stream<Item = Result<Event, Infallible>> {
     stream::unfold(
-        (state, research_id, original_query, 0u32, false),
-        |(state, research_id, original_query, poll_count, completed)| async move {
+        (state, context, research_id, original_query, 0u32, false),
+        |(state, context, research_id, original_query, poll_count, completed)| async move {
             // If already completed, end the stream
             if completed {
                 return None;
             }
 
-            // Poll for research progress
-            let context = wikify_applications::PermissionContext::local();
+            // Poll for research progress as the user who started it
             match state
                 .application
                 .get_research_progress(&context, &research_id)
@@ -862,14 +1103,28 @@ fn create_research_progress_stream(
                         // Mark as completed to end stream after this event
                         Some((
                             Ok(progress_event),
-                            (state, research_id, original_query, poll_count + 1, true),
+                            (
+                                state,
+                                context,
+                                research_id,
+                                original_query,
+                                poll_count + 1,
+                                true,
+                            ),
                         ))
                     } else {
                         // Continue polling after a delay
                         tokio::time::sleep(Duration::from_millis(1000)).await;
                         Some((
                             Ok(progress_event),
-                            (state, research_id, original_query, poll_count + 1, false),
+                            (
+                                state,
+                                context,
+                                research_id,
+                                original_query,
+                                poll_count + 1,
+                                false,
+                            ),
                         ))
                     }
                 }
@@ -888,7 +1143,14 @@ fn create_research_progress_stream(
                     // End stream after error
                     Some((
                         Ok(error_event),
-                        (state, research_id, original_query, poll_count + 1, true),
+                        (
+                            state,
+                            context,
+                            research_id,
+                            original_query,
+                            poll_count + 1,
+                            true,
+                        ),
                     ))
                 }
             }
@@ -896,6 +1158,79 @@ fn create_research_progress_stream(
     )
 }
 
+/// Stream research progress events
+#[utoipa::path(
+    get,
+    path = "/api/research/{research_id}/events",
+    tag = "Research",
+    summary = "Stream research progress",
+    description = "Stream progress updates of a research session as Server-Sent Events. The first `research_progress` event carries the current progress; the stream ends after the session completes, fails or is cancelled.",
+    params(
+        ("research_id" = String, Path, description = "Research session ID")
+    ),
+    responses(
+        (status = 200, description = "Stream of research_progress events", content_type = "text/event-stream", body = ResearchProgressResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "Research session not found"),
+        (status = 500, description = "Failed to subscribe to research progress")
+    )
+)]
+pub async fn research_progress_events(
+    State(state): State<AppState>,
+    RequireQuery(user): RequireQuery,
+    Path(research_id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    info!(
+        "Streaming research progress for: {} (user: {})",
+        research_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+    let (progress, receiver) = state
+        .application
+        .subscribe_to_research_progress(&context, &research_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to subscribe to research progress: {}", e);
+            research_error_status(e)
+        })?;
+
+    let stream = stream::unfold(
+        (Some(progress), receiver, research_id, false),
+        |(pending, mut receiver, research_id, completed)| async move {
+            if completed {
+                return None;
+            }
+
+            let progress = match pending {
+                Some(progress) => progress,
+                None => loop {
+                    match receiver.recv().await {
+                        Ok(progress) if progress.id == research_id => break progress,
+                        Ok(_) => continue,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(
+                                "Research progress stream for {} skipped {} updates",
+                                research_id, skipped
+                            );
+                            continue;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+                    }
+                },
+            };
+
+            let is_complete = !matches!(progress.status, ResearchStatus::InProgress);
+            let data = serde_json::to_string(&ResearchProgressResponse::from(progress))
+                .unwrap_or_default();
+            let event = Event::default().event("research_progress").data(data);
+            Some((Ok(event), (None, receiver, research_id, is_complete)))
+        },
+    );
+
+    Ok(Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default()))
+}
+
 /// Get detailed research result
 #[utoipa::path(
     get,
@@ -903,8 +1238,12 @@ fn create_research_progress_stream(
     tag = "Research",
     summary = "Get detailed research result",
     description = "Get the complete research result including all iterations and final synthesis",
+    params(
+        ("research_id" = String, Path, description = "Research session ID")
+    ),
     responses(
-        (status = 200, description = "Research result retrieved successfully"),
+        (status = 200, description = "Research result retrieved successfully", body = ResearchResultResponse),
+        (status = 403, description = "Insufficient permissions"),
         (status = 404, description = "Research not found"),
         (status = 500, description = "Failed to get research result")
     )
@@ -913,7 +1252,7 @@ pub async fn get_research_result(
     State(state): State<AppState>,
     RequireQuery(user): RequireQuery,
     Path(research_id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<ResearchResultResponse>, StatusCode> {
     info!(
         "Getting research result for: {} (user: {})",
         research_id, user.id
@@ -926,37 +1265,10 @@ pub async fn get_research_result(
         .get_research_details(&context, &research_id)
         .await
     {
-        Ok(details) => {
-            let response = serde_json::json!({
-                "research_id": research_id,
-                "topic": details.topic,
-                "status": format!("{:?}", details.status),
-                "iterations": details.iterations.iter().map(|iter| serde_json::json!({
-                    "iteration": iter.iteration,
-                    "questions": iter.questions,
-                    "findings": iter.findings,
-                    "new_questions": iter.new_questions,
-                    "partial_synthesis": iter.partial_synthesis,
-                    "confidence": iter.confidence,
-                    "needs_more_research": iter.needs_more_research,
-                    "duration_ms": iter.duration.as_millis()
-                })).collect::<Vec<_>>(),
-                "findings": details.findings,
-                "questions": details.questions,
-                "config": serde_json::json!({
-                    "max_iterations": details.config.max_iterations,
-                    "max_depth": details.config.max_depth,
-                    "confidence_threshold": details.config.confidence_threshold
-                }),
-                "created_at": details.created_at,
-                "updated_at": details.updated_at
-            });
-
-            Ok(Json(response))
-        }
+        Ok(details) => Ok(Json(ResearchResultResponse::new(research_id, details))),
         Err(e) => {
             error!("Failed to get research result: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(research_error_status(e))
         }
     }
 }
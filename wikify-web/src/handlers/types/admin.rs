@@ -0,0 +1,87 @@
+//! Administration types
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use wikify_applications::{UsageExportFormat, UsageGroupBy};
+
+/// Request to garbage collect the clone workspace
+///
+/// Omitted limits fall back to the default retention policy.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct WorkspaceGcRequest {
+    /// Only report what would be removed
+    #[serde(default)]
+    #[schema(example = false)]
+    pub dry_run: bool,
+    /// Maximum total size of all clones in megabytes
+    #[schema(example = 10240)]
+    pub max_total_size_mb: Option<u64>,
+    /// Remove clones unused for this many days
+    #[schema(example = 30)]
+    pub max_age_days: Option<u64>,
+    /// Maximum number of clones to keep
+    pub max_entries: Option<usize>,
+}
+
+/// Result of a clone workspace garbage collection
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkspaceGcResponse {
+    #[schema(example = "success")]
+    pub status: String,
+    pub dry_run: bool,
+    pub scanned: usize,
+    pub removed: Vec<String>,
+    pub freed_bytes: u64,
+    pub remaining_bytes: u64,
+}
+
+/// Request to garbage collect orphaned storage
+///
+/// Retention comes from the server's storage GC settings.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct StorageGcRequest {
+    /// Only report what would be removed
+    #[serde(default)]
+    #[schema(example = false)]
+    pub dry_run: bool,
+}
+
+/// Usage report filters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UsageQuery {
+    /// Group usage by `user`, `repository` or `day` (default `day`)
+    pub group_by: Option<UsageGroupBy>,
+    /// Only include usage of this user
+    pub user_id: Option<String>,
+    /// Only include usage of this repository
+    pub repository_id: Option<String>,
+    /// Only include usage at or after this time (RFC 3339)
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only include usage before this time (RFC 3339)
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Usage export filters
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UsageExportQuery {
+    /// `csv` (default) or `jsonl`
+    pub format: Option<UsageExportFormat>,
+    /// Only include usage of this user
+    pub user_id: Option<String>,
+    /// Only include usage of this repository
+    pub repository_id: Option<String>,
+    /// Only include usage at or after this time (RFC 3339)
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only include usage before this time (RFC 3339)
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Public server settings
+#[derive(Serialize, ToSchema)]
+pub struct ServerConfigResponse {
+    #[schema(example = "127.0.0.1")]
+    pub host: String,
+    #[schema(example = 8080)]
+    pub port: u16,
+    pub dev_mode: bool,
+}
@@ -2,7 +2,8 @@
 
 use super::common::SourceDocument;
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use wikify_applications::{ChatSessionRecord, ChatSessionSummary};
 
 /// Chat query request
 #[derive(Deserialize, ToSchema)]
@@ -11,9 +12,13 @@ pub struct ChatQueryRequest {
     pub repository_id: String,
     #[schema(example = "How does the authentication work?")]
     pub question: String,
+    /// Text attached to the question, such as an error log or a stack trace
     pub context: Option<String>,
     /// Maximum number of results to return
     pub max_results: Option<usize>,
+    /// Chat session to continue; a new session is started when omitted
+    #[schema(example = "session-uuid-string")]
+    pub session_id: Option<String>,
 }
 
 /// Chat query response
@@ -23,5 +28,261 @@ pub struct ChatQueryResponse {
     pub sources: Vec<SourceDocument>,
     #[schema(example = "repo-uuid-string")]
     pub repository_id: String,
+    /// Chat session the question was recorded in
+    #[schema(example = "session-uuid-string")]
+    pub session_id: Option<String>,
+    /// Branch of the answer among the answers to the same question
+    #[schema(example = 0)]
+    pub branch: Option<usize>,
+    /// Query record to reference when sending feedback (null without a database)
+    #[schema(example = "query-uuid-string")]
+    pub query_id: Option<String>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+/// Request to start a chat session
+#[derive(Deserialize, ToSchema)]
+pub struct CreateChatSessionRequest {
+    #[schema(example = "repo-uuid-string")]
+    pub repository_id: String,
+    /// Session title; generated from the first question when omitted
+    #[schema(example = "Authentication flow")]
+    pub title: Option<String>,
+}
+
+/// Request to rename a chat session
+#[derive(Deserialize, ToSchema)]
+pub struct RenameChatSessionRequest {
+    #[schema(example = "Authentication flow")]
+    pub title: String,
+}
+
+/// Question asked in an existing chat session
+#[derive(Deserialize, ToSchema)]
+pub struct ChatSessionMessageRequest {
+    #[schema(example = "And where are tokens refreshed?")]
+    pub question: String,
+    /// Maximum number of results to return
+    pub max_results: Option<usize>,
+}
+
+/// Request to answer the last question of a chat session again
+#[derive(Deserialize, ToSchema)]
+pub struct RegenerateAnswerRequest {
+    /// Maximum number of results to retrieve
+    pub max_results: Option<usize>,
+    /// Minimum similarity of retrieved chunks (0.0 to 1.0)
+    #[schema(example = 0.5)]
+    pub similarity_threshold: Option<f32>,
+}
+
+/// Chat session filters
+#[derive(Deserialize, IntoParams)]
+pub struct ChatSessionQuery {
+    /// Only include sessions about this repository
+    pub repository_id: Option<String>,
+}
+
+/// Chat session without its messages
+#[derive(Serialize, ToSchema)]
+pub struct ChatSessionSummaryResponse {
+    #[schema(example = "session-uuid-string")]
+    pub id: String,
+    #[schema(example = "repo-uuid-string")]
+    pub repository_id: String,
+    /// Owner of the session (null for sessions of anonymous users)
+    pub user_id: Option<String>,
+    #[schema(example = "How does the authentication work?")]
+    pub title: String,
+    pub message_count: usize,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_activity: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ChatSessionSummary> for ChatSessionSummaryResponse {
+    fn from(summary: ChatSessionSummary) -> Self {
+        Self {
+            id: summary.id,
+            repository_id: summary.repository_id,
+            user_id: summary.user_id,
+            title: summary.title,
+            message_count: summary.message_count,
+            created_at: summary.created_at,
+            last_activity: summary.last_activity,
+        }
+    }
+}
+
+/// Chat sessions of the current user
+#[derive(Serialize, ToSchema)]
+pub struct ChatSessionListResponse {
+    pub sessions: Vec<ChatSessionSummaryResponse>,
+}
+
+/// Message in a chat session
+#[derive(Serialize, ToSchema)]
+pub struct ChatSessionMessage {
+    pub id: String,
+    /// "user" or "assistant"
+    #[schema(example = "user")]
+    pub role: String,
+    pub content: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Retrieval details of answers: sources, confidence and query parameters
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Chat session with its messages
+#[derive(Serialize, ToSchema)]
+pub struct ChatSessionResponse {
+    #[schema(example = "session-uuid-string")]
+    pub id: String,
+    #[schema(example = "repo-uuid-string")]
+    pub repository_id: String,
+    pub user_id: Option<String>,
+    #[schema(example = "How does the authentication work?")]
+    pub title: String,
+    pub messages: Vec<ChatSessionMessage>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_activity: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ChatSessionRecord> for ChatSessionResponse {
+    fn from(session: ChatSessionRecord) -> Self {
+        Self {
+            id: session.id,
+            repository_id: session.repository_id,
+            user_id: session.user_id,
+            title: session.title,
+            messages: session
+                .messages
+                .into_iter()
+                .map(|message| ChatSessionMessage {
+                    id: message.id,
+                    role: message.role,
+                    content: message.content,
+                    timestamp: message.timestamp,
+                    metadata: message.metadata.map(|metadata| serde_json::json!(metadata)),
+                })
+                .collect(),
+            created_at: session.created_at,
+            last_activity: session.last_activity,
+        }
+    }
+}
+
+/// Rating of an answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackRating {
+    Up,
+    Down,
+}
+
+impl FeedbackRating {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackRating::Up => "up",
+            FeedbackRating::Down => "down",
+        }
+    }
+}
+
+impl std::str::FromStr for FeedbackRating {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(FeedbackRating::Up),
+            "down" => Ok(FeedbackRating::Down),
+            other => Err(format!("Unknown feedback rating: {}", other)),
+        }
+    }
+}
+
+/// Feedback on an answer
+#[derive(Deserialize, ToSchema)]
+pub struct ChatFeedbackRequest {
+    /// `query_id` returned with the answer
+    #[schema(example = "query-uuid-string")]
+    pub query_id: String,
+    pub rating: FeedbackRating,
+    /// Free-text explanation
+    #[schema(example = "The answer describes the old login flow")]
+    pub comment: Option<String>,
+    /// Sources the user marked as wrong or irrelevant
+    #[serde(default)]
+    #[schema(example = json!(["src/legacy/auth.rs"]))]
+    pub wrong_sources: Vec<String>,
+}
+
+/// Recorded feedback
+#[derive(Serialize, ToSchema)]
+pub struct ChatFeedbackResponse {
+    #[schema(example = "feedback-uuid-string")]
+    pub id: String,
+    #[schema(example = "query-uuid-string")]
+    pub query_id: String,
+    pub rating: FeedbackRating,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Feedback filters
+#[derive(Deserialize, IntoParams)]
+pub struct ChatFeedbackQuery {
+    /// Only include feedback on answers about this repository
+    pub repository_id: Option<String>,
+    /// Only include feedback with this rating
+    pub rating: Option<FeedbackRating>,
+    /// Maximum number of entries to return (default 100)
+    pub limit: Option<i32>,
+}
+
+/// Feedback together with the question and answer it rates
+#[derive(Serialize, ToSchema)]
+pub struct ChatFeedbackEntry {
+    pub id: String,
+    pub query_id: String,
+    pub repository_id: Option<String>,
+    pub user_id: Option<String>,
+    pub rating: FeedbackRating,
+    pub comment: Option<String>,
+    pub wrong_sources: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Question that was rated (null if its query record was deleted)
+    pub question: Option<String>,
+    /// Answer that was rated (null if its query record was deleted)
+    pub answer: Option<String>,
+}
+
+/// Feedback listing
+#[derive(Serialize, ToSchema)]
+pub struct ChatFeedbackListResponse {
+    pub feedback: Vec<ChatFeedbackEntry>,
+    pub count: usize,
+}
+
+/// Question asked about a repository
+#[derive(Serialize, ToSchema)]
+pub struct QueryHistoryEntry {
+    pub id: String,
+    pub repository_id: Option<String>,
+    pub question: String,
+    pub answer: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Page of the questions asked about a repository
+#[derive(Serialize, ToSchema)]
+pub struct QueryHistoryResponse {
+    pub queries: Vec<QueryHistoryEntry>,
+    /// Questions in this page
+    pub count: usize,
+    /// Questions asked about the repository
+    pub total: usize,
+    pub limit: Option<usize>,
+    pub offset: usize,
+    /// Why the history is empty, when the database is disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
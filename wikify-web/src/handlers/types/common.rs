@@ -1,7 +1,37 @@
 //! Common types used across multiple handlers
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use wikify_applications::PageRequest;
+
+/// Page size of list endpoints when no limit is given
+pub const DEFAULT_PAGE_LIMIT: usize = 100;
+
+/// Largest page size list endpoints return
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// Pagination of list endpoints
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct PaginationQuery {
+    /// Maximum number of items to return (default 100, at most 500)
+    pub limit: Option<usize>,
+    /// Number of items to skip
+    pub offset: Option<usize>,
+}
+
+impl PaginationQuery {
+    pub fn page(&self) -> PageRequest {
+        page_request(self.limit, self.offset)
+    }
+}
+
+/// Page of a list endpoint, applying the default and maximum page size
+pub fn page_request(limit: Option<usize>, offset: Option<usize>) -> PageRequest {
+    PageRequest::new(
+        limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT),
+        offset.unwrap_or(0),
+    )
+}
 
 /// Health check response
 #[derive(Serialize, ToSchema)]
@@ -13,6 +43,33 @@ pub struct HealthResponse {
     pub version: String,
 }
 
+/// Readiness check response
+#[derive(Serialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// `ready` when every dependency is healthy, otherwise `not_ready`
+    #[schema(example = "ready")]
+    pub status: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub checks: Vec<wikify_applications::DependencyCheck>,
+}
+
+/// Outcome of an operation that returns no data
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageResponse {
+    pub success: bool,
+    #[schema(example = "API key deleted successfully")]
+    pub message: String,
+}
+
+impl MessageResponse {
+    pub fn success(message: impl Into<String>) -> Self {
+        Self {
+            success: true,
+            message: message.into(),
+        }
+    }
+}
+
 /// Source document information
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SourceDocument {
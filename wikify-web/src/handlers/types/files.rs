@@ -1,7 +1,7 @@
 //! File operation types
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 /// Request to get file tree
 #[derive(Debug, Deserialize, ToSchema)]
@@ -28,6 +28,130 @@ pub struct RepositoryFileInfo {
     pub sha: Option<String>,
 }
 
+/// Branch to list files from
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FileTreeQuery {
+    /// Branch name, defaulting to the repository's default branch
+    pub branch: Option<String>,
+}
+
+/// Files of a repository as a tree of directories
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RepositoryFileTreeResponse {
+    pub repository_id: String,
+    pub branch: Option<String>,
+    /// Number of files, not counting directories
+    pub total_files: usize,
+    pub tree: Vec<FileTreeNode>,
+}
+
+/// Kind of a file tree node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileNodeType {
+    File,
+    Directory,
+}
+
+/// File or directory in a repository file tree
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FileTreeNode {
+    pub name: String,
+    /// Path relative to the repository root
+    pub path: String,
+    #[serde(rename = "type")]
+    pub node_type: FileNodeType,
+    /// Size in bytes; for directories the total of the files below them
+    pub size: Option<u64>,
+    /// Entries of a directory, directories first and then by name
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[schema(no_recursion)]
+    pub children: Vec<FileTreeNode>,
+}
+
+impl FileTreeNode {
+    /// Nest a flat file listing into directories
+    pub fn build_tree(files: Vec<RepositoryFileInfo>) -> Vec<FileTreeNode> {
+        let mut roots = Vec::new();
+        for file in files {
+            let path = file.path.replace('\\', "/");
+            let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+            let Some((file_name, directories)) = components.split_last() else {
+                continue;
+            };
+
+            let mut level = &mut roots;
+            for (depth, directory) in directories.iter().enumerate() {
+                let index = match level.iter().position(|node: &FileTreeNode| {
+                    node.node_type == FileNodeType::Directory && node.name == *directory
+                }) {
+                    Some(index) => index,
+                    None => {
+                        level.push(FileTreeNode {
+                            name: directory.to_string(),
+                            path: directories[..=depth].join("/"),
+                            node_type: FileNodeType::Directory,
+                            size: None,
+                            children: Vec::new(),
+                        });
+                        level.len() - 1
+                    }
+                };
+                level = &mut level[index].children;
+            }
+            level.push(FileTreeNode {
+                name: file_name.to_string(),
+                path: components.join("/"),
+                node_type: FileNodeType::File,
+                size: file.size,
+                children: Vec::new(),
+            });
+        }
+
+        Self::finish(&mut roots);
+        roots
+    }
+
+    /// Sort the entries and add up directory sizes
+    fn finish(nodes: &mut [FileTreeNode]) {
+        for node in nodes.iter_mut() {
+            if node.node_type == FileNodeType::Directory {
+                Self::finish(&mut node.children);
+                node.size = Some(node.children.iter().filter_map(|child| child.size).sum());
+            }
+        }
+        nodes.sort_by(|a, b| {
+            (a.node_type == FileNodeType::File, &a.name)
+                .cmp(&(b.node_type == FileNodeType::File, &b.name))
+        });
+    }
+}
+
+/// File to read
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct FileContentQuery {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Branch name, defaulting to the repository's default branch
+    pub branch: Option<String>,
+}
+
+/// Content of a repository file with what is needed to display it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RepositoryFileContentResponse {
+    pub repository_id: String,
+    pub path: String,
+    pub branch: Option<String>,
+    /// File content, cut short for large files
+    pub content: String,
+    /// Language for syntax highlighting, detected from the file extension
+    pub language: Option<String>,
+    /// Size of the whole file in bytes
+    pub size: usize,
+    /// Whether `content` holds only the start of the file
+    pub truncated: bool,
+}
+
 /// Request to get file content
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct GetFileContentRequest {
@@ -85,3 +209,38 @@ impl From<wikify_core::RepositoryFile> for RepositoryFileInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64) -> RepositoryFileInfo {
+        RepositoryFileInfo {
+            path: path.to_string(),
+            file_type: "blob".to_string(),
+            size: Some(size),
+            sha: None,
+        }
+    }
+
+    #[test]
+    fn test_build_file_tree() {
+        let tree = FileTreeNode::build_tree(vec![
+            file("src/main.rs", 10),
+            file("README.md", 5),
+            file("src/handlers/mod.rs", 3),
+            file("src/lib.rs", 7),
+        ]);
+
+        let names: Vec<&str> = tree.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, vec!["src", "README.md"]);
+
+        let src = &tree[0];
+        assert_eq!(src.node_type, FileNodeType::Directory);
+        assert_eq!(src.size, Some(20));
+        let names: Vec<&str> = src.children.iter().map(|node| node.name.as_str()).collect();
+        assert_eq!(names, vec!["handlers", "lib.rs", "main.rs"]);
+        assert_eq!(src.children[0].path, "src/handlers");
+        assert_eq!(src.children[0].children[0].path, "src/handlers/mod.rs");
+    }
+}
@@ -2,17 +2,25 @@
 //!
 //! This module contains all the request/response types used by the handlers.
 
+pub mod admin;
 pub mod chat;
 pub mod common;
 pub mod files;
+pub mod organization;
 pub mod repository;
+#[cfg(feature = "research")]
 pub mod research;
+pub mod webhook;
 pub mod wiki;
 
 // Re-export all types for convenience
+pub use admin::*;
 pub use chat::*;
 pub use common::*;
 pub use files::*;
+pub use organization::*;
 pub use repository::*;
+#[cfg(feature = "research")]
 pub use research::*;
+pub use webhook::*;
 pub use wiki::*;
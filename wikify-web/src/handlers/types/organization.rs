@@ -0,0 +1,46 @@
+//! Organization types
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use wikify_applications::{OrgMember, OrgRole, Organization};
+
+/// Request to create an organization
+#[derive(Deserialize, ToSchema)]
+pub struct CreateOrganizationRequest {
+    #[schema(example = "Acme")]
+    pub name: String,
+}
+
+/// Request to add a member or change a member's role
+#[derive(Deserialize, ToSchema)]
+pub struct SetOrganizationMemberRequest {
+    pub role: OrgRole,
+}
+
+/// Organization with its members
+#[derive(Serialize, ToSchema)]
+pub struct OrganizationResponse {
+    #[schema(example = "uuid-string")]
+    pub id: String,
+    #[schema(example = "Acme")]
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub members: Vec<OrgMember>,
+}
+
+impl From<Organization> for OrganizationResponse {
+    fn from(organization: Organization) -> Self {
+        Self {
+            id: organization.id,
+            name: organization.name,
+            created_at: organization.created_at,
+            members: organization.members,
+        }
+    }
+}
+
+/// Organizations of the current user
+#[derive(Serialize, ToSchema)]
+pub struct OrganizationListResponse {
+    pub organizations: Vec<OrganizationResponse>,
+}
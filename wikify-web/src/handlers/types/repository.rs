@@ -1,7 +1,15 @@
 //! Repository-related types
 
+use super::page_request;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use wikify_applications::{
+    IndexingJob, IndexingStatus, JobPriority, JobState, PageRequest, Permission,
+    RepositoryDiskUsage, RepositoryFilter, RepositoryIndex, RepositorySortField,
+    RepositoryVisibility, ShareGrant, SortOrder,
+};
 
 /// Repository initialization request
 #[derive(Deserialize, ToSchema)]
@@ -10,12 +18,142 @@ pub struct InitializeRepositoryRequest {
     pub repository: String,
     #[schema(example = "github")]
     pub repo_type: Option<String>, // "github", "local", etc.
+    /// Token for private repositories (defaults to the token granted at OAuth login)
     pub access_token: Option<String>,
     #[schema(example = true)]
     pub auto_index: Option<bool>, // Whether to automatically index the repository
     #[schema(example = true)]
     pub auto_generate_wiki: Option<bool>, // Whether to automatically generate wiki after indexing
     pub metadata: Option<std::collections::HashMap<String, String>>, // Additional metadata
+    /// Alternate remotes tried in order when the primary URL cannot be accessed
+    pub mirror_urls: Option<Vec<String>>,
+    /// Who can see the repository (defaults to "org" inside an organization, otherwise "private")
+    pub visibility: Option<RepositoryVisibility>,
+}
+
+/// Request to register many repositories at once
+#[derive(Deserialize, ToSchema)]
+pub struct BatchRepositoryRequest {
+    /// Remote repository URLs, such as every repository of a GitHub organization
+    #[schema(example = json!(["https://github.com/user/repo", "https://github.com/user/other"]))]
+    pub urls: Vec<String>,
+    /// Token used for all repositories (defaults to the token granted at OAuth login)
+    pub access_token: Option<String>,
+    #[schema(example = true)]
+    pub auto_index: Option<bool>,
+    #[schema(example = true)]
+    pub auto_generate_wiki: Option<bool>,
+    /// Who can see the repositories (defaults to "org" inside an organization, otherwise "private")
+    pub visibility: Option<RepositoryVisibility>,
+}
+
+/// Check that a URL of a batch points at a remote repository
+pub fn validate_repository_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+    }
+    if parsed.host_str().is_none() {
+        return Err("URL has no host".to_string());
+    }
+    let segments = parsed
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).count())
+        .unwrap_or(0);
+    if segments < 2 {
+        return Err("URL must contain owner and repository name".to_string());
+    }
+    Ok(())
+}
+
+/// What happened to one URL of a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    /// Registered, and queued for indexing when `auto_index` is set
+    Created,
+    /// Not a valid repository URL
+    Invalid,
+    /// Listed earlier in the same batch
+    Duplicate,
+    /// Registration failed
+    Failed,
+}
+
+/// Result for one URL of a batch
+#[derive(Serialize, ToSchema)]
+pub struct BatchRepositoryItem {
+    pub url: String,
+    pub status: BatchItemStatus,
+    pub repository_id: Option<String>,
+    /// Why the repository was not created
+    pub error: Option<String>,
+}
+
+/// Per-URL results of a batch registration, in request order
+#[derive(Serialize, ToSchema)]
+pub struct BatchRepositoryResponse {
+    /// Number of repositories created
+    pub created: usize,
+    /// Number of URLs that were not created
+    pub failed: usize,
+    pub results: Vec<BatchRepositoryItem>,
+}
+
+/// Filters, order and page of the repository list
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct RepositoryListQuery {
+    /// Only include repositories with this status
+    /// (`pending`, `indexing`, `indexed`, `failed` or `cancelled`)
+    pub status: Option<String>,
+    /// Only include repositories of this type, such as `github` or `local`
+    pub repo_type: Option<String>,
+    /// Only include repositories added by this user
+    pub owner: Option<String>,
+    /// Only include repositories whose URL contains this text, ignoring case
+    pub name: Option<String>,
+    /// Field to sort by (default `created_at`)
+    pub sort: Option<RepositorySortField>,
+    /// `asc` or `desc` (default)
+    pub order: Option<SortOrder>,
+    /// Maximum number of items to return (default 100, at most 500)
+    pub limit: Option<usize>,
+    /// Number of items to skip
+    pub offset: Option<usize>,
+}
+
+impl RepositoryListQuery {
+    /// Repository filter of the query, failing on an unknown status
+    pub fn filter(&self) -> Result<RepositoryFilter, String> {
+        Ok(RepositoryFilter {
+            status: self.status.as_deref().map(str::parse).transpose()?,
+            repo_type: self.repo_type.clone(),
+            owner_id: self.owner.clone(),
+            name: self.name.clone().filter(|name| !name.is_empty()),
+            sort: self.sort.unwrap_or_default(),
+            order: self.order.unwrap_or_default(),
+        })
+    }
+
+    pub fn page(&self) -> PageRequest {
+        page_request(self.limit, self.offset)
+    }
+}
+
+/// Multipart form for uploading a repository archive
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct UploadRepositoryForm {
+    /// Zip archive containing the repository
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    /// Display name (defaults to the archive file name)
+    #[schema(example = "my-project")]
+    pub name: Option<String>,
+    #[schema(example = true)]
+    pub auto_index: Option<bool>,
+    #[schema(example = true)]
+    pub auto_generate_wiki: Option<bool>,
 }
 
 /// Repository initialization response
@@ -29,6 +167,119 @@ pub struct InitializeRepositoryResponse {
     pub message: String,
 }
 
+/// Repository in a repository list
+#[derive(Serialize, ToSchema)]
+pub struct RepositorySummaryResponse {
+    #[schema(example = "uuid-string")]
+    pub id: String,
+    /// Repository URL or path
+    #[schema(example = "https://github.com/user/repo")]
+    pub repository: String,
+    #[schema(example = "github")]
+    pub repo_type: String,
+    /// pending, indexing, indexed, failed or cancelled
+    #[schema(example = "indexed")]
+    pub status: String,
+    /// Indexing progress from 0.0 to 1.0
+    pub indexing_progress: f64,
+    pub created_at: DateTime<Utc>,
+    pub last_indexed_at: Option<DateTime<Utc>>,
+    /// User who added the repository (null for anonymous users)
+    pub owner: Option<String>,
+    pub organization_id: Option<String>,
+    pub visibility: RepositoryVisibility,
+    /// Users the repository is shared with
+    pub shared_with: Vec<String>,
+    pub metadata: HashMap<String, String>,
+}
+
+impl From<RepositoryIndex> for RepositorySummaryResponse {
+    fn from(repository: RepositoryIndex) -> Self {
+        let status = match repository.status {
+            IndexingStatus::Pending => "pending",
+            IndexingStatus::Indexing => "indexing",
+            IndexingStatus::Completed => "indexed",
+            IndexingStatus::Failed => "failed",
+            IndexingStatus::Cancelled => "cancelled",
+        };
+
+        Self {
+            id: repository.id,
+            repository: repository.url,
+            repo_type: repository.repo_type,
+            status: status.to_string(),
+            indexing_progress: repository.progress,
+            created_at: repository.created_at,
+            last_indexed_at: repository.indexed_at,
+            owner: repository.owner_id,
+            organization_id: repository.organization_id,
+            visibility: repository.visibility,
+            shared_with: repository
+                .share_grants
+                .into_iter()
+                .map(|grant| grant.user_id)
+                .collect(),
+            metadata: repository.metadata,
+        }
+    }
+}
+
+/// Page of repositories visible to the current user
+#[derive(Serialize, ToSchema)]
+pub struct RepositoryListResponse {
+    pub repositories: Vec<RepositorySummaryResponse>,
+    /// Repositories matching the filters
+    pub total: usize,
+    pub limit: Option<usize>,
+    pub offset: usize,
+    /// ID of the current user
+    pub user: String,
+    /// Organization the list was taken from
+    pub organization_id: Option<String>,
+    /// Permissions of the current user
+    #[schema(value_type = Vec<String>, example = json!(["Query", "GenerateWiki"]))]
+    pub permissions: Vec<Permission>,
+}
+
+/// Repository details
+#[derive(Serialize, ToSchema)]
+pub struct RepositoryInfoResponse {
+    #[schema(example = "uuid-string")]
+    pub repository_id: String,
+    #[schema(example = "https://github.com/user/repo")]
+    pub url: String,
+    #[schema(example = "github")]
+    pub repo_type: String,
+    /// pending, indexing, completed, failed or cancelled
+    #[schema(value_type = String, example = "completed")]
+    pub status: IndexingStatus,
+    pub created_at: DateTime<Utc>,
+    pub last_indexed_at: Option<DateTime<Utc>>,
+    /// Indexing progress from 0.0 to 1.0
+    pub progress: f64,
+    pub owner: Option<String>,
+    pub visibility: RepositoryVisibility,
+    /// Disk space used by the clone, index and cached wikis when last measured
+    pub disk_usage: Option<RepositoryDiskUsage>,
+}
+
+impl From<RepositoryIndex> for RepositoryInfoResponse {
+    fn from(repository: RepositoryIndex) -> Self {
+        Self {
+            repository_id: repository.id,
+            url: repository.url,
+            repo_type: repository.repo_type,
+            status: repository.status,
+            created_at: repository.created_at,
+            last_indexed_at: repository.indexed_at,
+            progress: repository.progress,
+            owner: repository.owner_id,
+            visibility: repository.visibility,
+            disk_usage: repository.disk_usage,
+        }
+    }
+}
+
 /// Repository deletion response
 #[derive(Serialize, ToSchema)]
 pub struct DeleteRepositoryResponse {
@@ -40,6 +291,43 @@ pub struct DeleteRepositoryResponse {
     pub deleted_repository_id: String,
 }
 
+/// Request to change who can see and query a repository
+#[derive(Deserialize, ToSchema)]
+pub struct ShareRepositoryRequest {
+    /// New visibility (unchanged when omitted)
+    pub visibility: Option<RepositoryVisibility>,
+    /// Users to share the repository with
+    #[serde(default)]
+    #[schema(example = json!(["teammate-user-id"]))]
+    pub share_with: Vec<String>,
+    /// Users to stop sharing the repository with
+    #[serde(default)]
+    pub revoke: Vec<String>,
+}
+
+/// Visibility and share grants of a repository
+#[derive(Serialize, ToSchema)]
+pub struct RepositorySharingResponse {
+    #[schema(example = "uuid-string")]
+    pub repository_id: String,
+    pub owner_id: Option<String>,
+    pub organization_id: Option<String>,
+    pub visibility: RepositoryVisibility,
+    pub share_grants: Vec<ShareGrant>,
+}
+
+impl From<RepositoryIndex> for RepositorySharingResponse {
+    fn from(repository: RepositoryIndex) -> Self {
+        Self {
+            repository_id: repository.id,
+            owner_id: repository.owner_id,
+            organization_id: repository.organization_id,
+            visibility: repository.visibility,
+            share_grants: repository.share_grants,
+        }
+    }
+}
+
 /// Reindex response
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ReindexResponse {
@@ -47,3 +335,96 @@ pub struct ReindexResponse {
     pub status: String,
     pub message: String,
 }
+
+/// Indexing job in the queue
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IndexingJobResponse {
+    #[schema(example = "uuid-string")]
+    pub id: String,
+    #[schema(example = "uuid-string")]
+    pub repository_id: String,
+    /// User who requested the job (null for anonymous users)
+    pub user_id: Option<String>,
+    /// "interactive" (user-requested reindex) or "background"
+    #[schema(example = "interactive")]
+    pub priority: String,
+    /// "running" or "queued"
+    #[schema(example = "queued")]
+    pub state: String,
+    /// Position among queued jobs, starting at 1 (null for running jobs)
+    #[schema(example = 1)]
+    pub queue_position: Option<usize>,
+    pub queued_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<IndexingJob> for IndexingJobResponse {
+    fn from(job: IndexingJob) -> Self {
+        Self {
+            id: job.id,
+            repository_id: job.repository_id,
+            user_id: job.user_id,
+            priority: match job.priority {
+                JobPriority::Interactive => "interactive",
+                JobPriority::Background => "background",
+            }
+            .to_string(),
+            state: match job.state {
+                JobState::Running => "running",
+                JobState::Queued => "queued",
+            }
+            .to_string(),
+            queue_position: None,
+            queued_at: job.queued_at,
+            started_at: job.started_at,
+        }
+    }
+}
+
+/// Indexing job list response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IndexingJobsResponse {
+    /// Running jobs followed by queued jobs in the order they will start
+    pub jobs: Vec<IndexingJobResponse>,
+    #[schema(example = 1)]
+    pub running: usize,
+    #[schema(example = 2)]
+    pub queued: usize,
+}
+
+impl From<Vec<IndexingJob>> for IndexingJobsResponse {
+    fn from(jobs: Vec<IndexingJob>) -> Self {
+        let mut queued = 0;
+        let jobs: Vec<IndexingJobResponse> = jobs
+            .into_iter()
+            .map(|job| {
+                let mut response = IndexingJobResponse::from(job);
+                if response.state == "queued" {
+                    queued += 1;
+                    response.queue_position = Some(queued);
+                }
+                response
+            })
+            .collect();
+
+        Self {
+            running: jobs.len() - queued,
+            queued,
+            jobs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_repository_url() {
+        assert!(validate_repository_url("https://github.com/user/repo").is_ok());
+        assert!(validate_repository_url("https://gitlab.example.com/group/repo.git").is_ok());
+        assert!(validate_repository_url("https://github.com/user").is_err());
+        assert!(validate_repository_url("file:///srv/repos/user/repo").is_err());
+        assert!(validate_repository_url("not a url").is_err());
+    }
+}
@@ -1,8 +1,15 @@
 //! Research functionality related types
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
-use wikify_applications::{ResearchCategory, ResearchProgress, ResearchTemplate};
+use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
+use wikify_applications::research::{
+    ResearchContext, ResearchFinding, ResearchHistoryRecord, ResearchIteration, ResearchMetadata,
+    ResearchStatistics,
+};
+use wikify_applications::{
+    ResearchCategory, ResearchConfig, ResearchProgress, ResearchQuestion, ResearchTemplate,
+};
 
 /// Research start request
 #[derive(Deserialize, ToSchema)]
@@ -85,7 +92,7 @@ pub struct ResearchTemplateResponse {
     pub description: String,
     pub category: ResearchCategory,
     pub questions: Vec<String>,
-    pub config: serde_json::Value,
+    pub config: ResearchConfig,
 }
 
 impl From<ResearchTemplate> for ResearchTemplateResponse {
@@ -100,7 +107,197 @@ impl From<ResearchTemplate> for ResearchTemplateResponse {
                 .into_iter()
                 .map(|q| q.text)
                 .collect(),
-            config: serde_json::to_value(template.config).unwrap_or_default(),
+            config: template.config,
+        }
+    }
+}
+
+/// Research templates, optionally of one category
+#[derive(Serialize, ToSchema)]
+pub struct ResearchTemplateListResponse {
+    pub templates: Vec<ResearchTemplateResponse>,
+    /// Category the templates were filtered by
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "security")]
+    pub category: Option<String>,
+    pub count: usize,
+}
+
+/// Result of stopping a research session or deleting a research record
+#[derive(Serialize, ToSchema)]
+pub struct ResearchActionResponse {
+    #[schema(example = "stopped")]
+    pub status: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub research_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository_id: Option<String>,
+}
+
+/// Active research session
+#[derive(Serialize, ToSchema)]
+pub struct ResearchSessionSummary {
+    pub research_id: String,
+    #[schema(example = "active")]
+    pub status: String,
+}
+
+/// Active research sessions of the current user
+#[derive(Serialize, ToSchema)]
+pub struct ResearchSessionListResponse {
+    pub sessions: Vec<ResearchSessionSummary>,
+    pub count: usize,
+}
+
+/// Recorded research session
+#[derive(Serialize, ToSchema)]
+pub struct ResearchRecordResponse {
+    pub id: String,
+    pub repository_id: String,
+    pub research_question: String,
+    #[schema(example = "completed")]
+    pub status: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Findings of all iterations
+    pub findings: Vec<String>,
+    pub metadata: ResearchMetadata,
+}
+
+impl From<ResearchHistoryRecord> for ResearchRecordResponse {
+    fn from(record: ResearchHistoryRecord) -> Self {
+        Self {
+            id: record.session_id,
+            repository_id: record.context.repository_id,
+            research_question: record.topic,
+            status: format!("{:?}", record.status).to_lowercase(),
+            created_at: record.created_at,
+            updated_at: record.updated_at,
+            findings: record
+                .iterations
+                .into_iter()
+                .flat_map(|iteration| iteration.findings)
+                .map(|finding| finding.content)
+                .collect(),
+            metadata: record.metadata,
+        }
+    }
+}
+
+/// Page of recorded research sessions
+#[derive(Serialize, ToSchema)]
+pub struct ResearchHistoryResponse {
+    pub history: Vec<ResearchRecordResponse>,
+    /// Records in this page
+    pub count: usize,
+    /// Records matching the filters
+    pub total: usize,
+    pub limit: Option<usize>,
+    pub offset: usize,
+}
+
+/// Aggregate statistics over recorded research sessions
+#[derive(Serialize, ToSchema)]
+pub struct ResearchStatisticsResponse {
+    pub total_sessions: usize,
+    pub active_sessions: usize,
+    pub completed_sessions: usize,
+    pub failed_sessions: usize,
+    /// Average session duration in seconds
+    pub average_session_duration: Option<f64>,
+    /// `[template_id, sessions]` pairs, most used first
+    #[schema(value_type = Vec<Vec<Object>>, example = json!([["security-analysis", 12]]))]
+    pub popular_templates: Vec<(String, usize)>,
+    /// Sessions started per day (`YYYY-MM-DD`)
+    pub activity_by_date: HashMap<String, usize>,
+}
+
+impl From<ResearchStatistics> for ResearchStatisticsResponse {
+    fn from(stats: ResearchStatistics) -> Self {
+        Self {
+            total_sessions: stats.total_sessions,
+            active_sessions: stats.in_progress_sessions,
+            completed_sessions: stats.completed_sessions,
+            failed_sessions: stats.failed_sessions,
+            average_session_duration: stats.average_duration_seconds,
+            popular_templates: stats.popular_templates,
+            activity_by_date: stats.activity_by_date,
+        }
+    }
+}
+
+/// One iteration of a research session
+#[derive(Serialize, ToSchema)]
+pub struct ResearchIterationResponse {
+    pub iteration: usize,
+    pub questions: Vec<ResearchQuestion>,
+    pub findings: Vec<ResearchFinding>,
+    pub new_questions: Vec<ResearchQuestion>,
+    pub partial_synthesis: String,
+    pub confidence: f64,
+    pub needs_more_research: bool,
+    pub duration_ms: u64,
+}
+
+impl From<ResearchIteration> for ResearchIterationResponse {
+    fn from(iteration: ResearchIteration) -> Self {
+        Self {
+            iteration: iteration.iteration,
+            questions: iteration.questions,
+            findings: iteration.findings,
+            new_questions: iteration.new_questions,
+            partial_synthesis: iteration.partial_synthesis,
+            confidence: iteration.confidence,
+            needs_more_research: iteration.needs_more_research,
+            duration_ms: iteration.duration.as_millis() as u64,
+        }
+    }
+}
+
+/// Limits a research session runs with
+#[derive(Serialize, ToSchema)]
+pub struct ResearchLimitsResponse {
+    pub max_iterations: usize,
+    pub max_depth: usize,
+    pub confidence_threshold: f64,
+}
+
+/// Complete result of a research session
+#[derive(Serialize, ToSchema)]
+pub struct ResearchResultResponse {
+    pub research_id: String,
+    pub topic: String,
+    #[schema(example = "Completed")]
+    pub status: String,
+    pub iterations: Vec<ResearchIterationResponse>,
+    pub findings: Vec<ResearchFinding>,
+    pub questions: Vec<ResearchQuestion>,
+    pub config: ResearchLimitsResponse,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ResearchResultResponse {
+    pub fn new(research_id: String, details: ResearchContext) -> Self {
+        Self {
+            research_id,
+            topic: details.topic,
+            status: format!("{:?}", details.status),
+            iterations: details
+                .iterations
+                .into_iter()
+                .map(ResearchIterationResponse::from)
+                .collect(),
+            findings: details.findings,
+            questions: details.questions,
+            config: ResearchLimitsResponse {
+                max_iterations: details.config.max_iterations,
+                max_depth: details.config.max_depth,
+                confidence_threshold: details.config.confidence_threshold,
+            },
+            created_at: details.created_at,
+            updated_at: details.updated_at,
         }
     }
 }
@@ -115,3 +312,51 @@ pub struct StartResearchFromTemplateRequest {
     pub custom_questions: Option<Vec<String>>,
     pub config_overrides: Option<serde_json::Value>,
 }
+
+/// Research history filters
+#[derive(Deserialize, IntoParams)]
+pub struct ResearchHistoryQuery {
+    /// Only include sessions started from this template
+    pub template_id: Option<String>,
+    /// Maximum number of records to return (default 100, at most 500)
+    pub limit: Option<usize>,
+    /// Number of records to skip
+    pub offset: Option<usize>,
+}
+
+/// User-defined research template
+#[derive(Deserialize, ToSchema)]
+pub struct ResearchTemplateBody {
+    /// Template ID; required when creating a template, ignored when updating one
+    #[schema(example = "api-review")]
+    pub id: Option<String>,
+    #[schema(example = "API Review")]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Template category (default: custom)
+    #[schema(example = "architecture")]
+    pub category: Option<String>,
+    /// Initial research questions
+    pub questions: Vec<ResearchTemplateQuestionBody>,
+    pub config: Option<ResearchConfigRequest>,
+}
+
+/// Initial question of a user-defined research template
+#[derive(Deserialize, ToSchema)]
+pub struct ResearchTemplateQuestionBody {
+    #[schema(example = "Which types make up the public API?")]
+    pub text: String,
+    /// One of conceptual, technical, architectural, historical, comparative,
+    /// diagnostic or advisory
+    #[schema(example = "architectural")]
+    pub question_type: String,
+    /// Priority from 1 to 10 (default: 5)
+    #[schema(example = 8)]
+    pub priority: Option<u8>,
+    /// Expected complexity from 1 to 10 (default: 5)
+    #[schema(example = 5)]
+    pub complexity: Option<u8>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
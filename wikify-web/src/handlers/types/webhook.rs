@@ -0,0 +1,87 @@
+//! Webhook types
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use wikify_applications::{Webhook, WebhookDelivery, WebhookEventKind};
+
+/// Request to register a webhook
+#[derive(Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// URL the events are posted to
+    #[schema(example = "https://example.com/hooks/wikify")]
+    pub url: String,
+    /// Events to send, all of them when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+    /// Repository whose events are sent; omit for every repository (admins only)
+    pub repository_id: Option<String>,
+    /// Key the payloads are signed with, generated when omitted
+    pub secret: Option<String>,
+}
+
+/// Changes to a webhook; omitted fields are kept
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub events: Option<Vec<WebhookEventKind>>,
+    pub secret: Option<String>,
+    /// Inactive webhooks receive no events
+    pub active: Option<bool>,
+}
+
+/// A registered webhook, without its secret
+#[derive(Serialize, ToSchema)]
+pub struct WebhookResponse {
+    #[schema(example = "uuid-string")]
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEventKind>,
+    pub repository_id: Option<String>,
+    pub owner_id: Option<String>,
+    pub active: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            events: webhook.events,
+            repository_id: webhook.repository_id,
+            owner_id: webhook.owner_id,
+            active: webhook.active,
+            created_at: webhook.created_at,
+            updated_at: webhook.updated_at,
+        }
+    }
+}
+
+/// A newly registered webhook with its secret, which is not shown again
+#[derive(Serialize, ToSchema)]
+pub struct WebhookCreatedResponse {
+    #[serde(flatten)]
+    pub webhook: WebhookResponse,
+    /// Key to verify the `X-Wikify-Signature` header with
+    pub secret: String,
+}
+
+/// Webhooks of the current user
+#[derive(Serialize, ToSchema)]
+pub struct WebhookListResponse {
+    pub webhooks: Vec<WebhookResponse>,
+}
+
+/// How many deliveries to list
+#[derive(Deserialize, IntoParams)]
+pub struct WebhookDeliveriesQuery {
+    /// Most recent deliveries to return (default 20, at most 100)
+    pub limit: Option<usize>,
+}
+
+/// Recent delivery attempts of a webhook, newest first
+#[derive(Serialize, ToSchema)]
+pub struct WebhookDeliveryListResponse {
+    pub deliveries: Vec<WebhookDelivery>,
+}
@@ -1,7 +1,7 @@
 //! Wiki generation and management related types
 
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
 /// Wiki generation request
 #[derive(Deserialize, ToSchema)]
@@ -22,6 +22,9 @@ pub struct WikiGenerationConfig {
     pub include_diagrams: Option<bool>,
     #[schema(example = false)]
     pub comprehensive_view: Option<bool>,
+    /// Regenerate even if a wiki is cached for the current commit and config
+    #[schema(example = false)]
+    pub force_regenerate: Option<bool>,
 }
 
 /// Wiki generation response
@@ -35,6 +38,173 @@ pub struct GenerateWikiResponse {
     pub pages_count: usize,
     #[schema(example = 8)]
     pub sections_count: usize,
+    /// Quality report of the generated wiki (see `WikiResponse::quality`).
+    /// `null` while generation runs in the background; the report is sent
+    /// with the `WikiComplete` WebSocket message once it finishes
+    pub quality: Option<serde_json::Value>,
+}
+
+/// Wiki export request
+#[derive(Deserialize, ToSchema)]
+pub struct ExportWikiRequest {
+    /// Export format: `json`, `pdf`, `html`, `markdown` or `mdbook`
+    #[schema(example = "pdf")]
+    pub format: String,
+}
+
+/// Export kept on the server, downloaded from `download_url`
+#[derive(Serialize, ToSchema)]
+pub struct WikiExportJobResponse {
+    #[schema(example = "uuid-string")]
+    pub job_id: String,
+    pub repository_id: String,
+    #[schema(example = "pdf")]
+    pub format: String,
+    /// File name the download is saved as
+    pub file_name: String,
+    pub content_type: String,
+    /// Size of the file in bytes
+    pub size: u64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// URL streaming the file; supports `Range` requests
+    #[schema(example = "/api/wiki/uuid-string/export/uuid-string/download")]
+    pub download_url: String,
+}
+
+/// Wiki page regeneration request
+#[derive(Deserialize, ToSchema)]
+pub struct RegenerateWikiPageRequest {
+    /// Extra instructions for the LLM, e.g. what the previous version was missing
+    #[schema(example = "Add a step-by-step example of configuring a custom provider")]
+    pub instructions: Option<String>,
+}
+
+/// Manual wiki page edit request
+#[derive(Deserialize, ToSchema)]
+pub struct EditWikiPageRequest {
+    /// New page title; the current title is kept when omitted
+    #[schema(example = "Getting Started")]
+    pub title: Option<String>,
+    /// Full Markdown content of the page
+    #[schema(example = "# Getting Started\n\nInstall with `cargo install wikify`.")]
+    pub content: String,
+}
+
+/// Wiki structure template of a repository
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct WikiTemplateBody {
+    /// Template name, for display only
+    #[schema(example = "SDK")]
+    pub name: Option<String>,
+    /// Tone and style instructions applied to every page
+    #[schema(example = "Concise reference style; show a code sample for every public type")]
+    pub tone: Option<String>,
+    /// Sections of the wiki, in display order
+    pub sections: Vec<WikiTemplateSectionBody>,
+    /// Whether the LLM may add pages beyond the required ones (default: true)
+    pub allow_additional_pages: Option<bool>,
+}
+
+/// Section of a wiki structure template
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct WikiTemplateSectionBody {
+    #[schema(example = "getting-started")]
+    pub id: String,
+    #[schema(example = "Getting Started")]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// Pages the section must contain, in order
+    #[serde(default)]
+    pub pages: Vec<WikiTemplatePageBody>,
+}
+
+/// Required page of a wiki structure template
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct WikiTemplatePageBody {
+    #[schema(example = "installation")]
+    pub id: String,
+    #[schema(example = "Installation")]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// `Critical`, `High` (default), `Medium` or `Low`
+    #[schema(example = "High")]
+    pub importance: Option<String>,
+}
+
+/// Archived wiki version
+#[derive(Serialize, ToSchema)]
+pub struct WikiVersionResponse {
+    /// Commit SHA, or the generation timestamp if the commit is unknown
+    #[schema(example = "3f2c1a9e8b7d6c5b4a3928171615141312111009")]
+    pub version_id: String,
+    #[schema(example = "3f2c1a9e8b7d6c5b4a3928171615141312111009")]
+    pub commit_sha: Option<String>,
+    #[schema(example = "2024-01-01T00:00:00Z")]
+    pub generated_at: String,
+    #[schema(example = 25)]
+    pub pages_count: usize,
+    #[schema(example = 8)]
+    pub sections_count: usize,
+}
+
+/// Versions to compare; `to` defaults to the newest version and `from` to the one before it
+#[derive(Deserialize, IntoParams)]
+pub struct WikiDiffQuery {
+    /// Version ID to compare from
+    pub from: Option<String>,
+    /// Version ID to compare to
+    pub to: Option<String>,
+}
+
+/// Differences between two wiki versions
+#[derive(Serialize, ToSchema)]
+pub struct WikiDiffResponse {
+    pub from: WikiVersionResponse,
+    pub to: WikiVersionResponse,
+    /// Pages only in the newer version
+    pub added_pages: Vec<WikiPageRefResponse>,
+    /// Pages only in the older version
+    pub removed_pages: Vec<WikiPageRefResponse>,
+    /// Pages whose title or content changed
+    pub changed_pages: Vec<WikiPageDiffResponse>,
+    #[schema(example = 20)]
+    pub unchanged_pages: usize,
+}
+
+/// Page reference in a wiki diff
+#[derive(Serialize, ToSchema)]
+pub struct WikiPageRefResponse {
+    #[schema(example = "getting-started")]
+    pub id: String,
+    #[schema(example = "Getting Started")]
+    pub title: String,
+}
+
+/// Changes to a single page
+#[derive(Serialize, ToSchema)]
+pub struct WikiPageDiffResponse {
+    #[schema(example = "getting-started")]
+    pub id: String,
+    #[schema(example = "Getting Started")]
+    pub old_title: String,
+    #[schema(example = "Getting Started")]
+    pub new_title: String,
+    pub sections: Vec<WikiSectionDiffResponse>,
+}
+
+/// Changes to a section (Markdown heading) of a page
+#[derive(Serialize, ToSchema)]
+pub struct WikiSectionDiffResponse {
+    #[schema(example = "Installation")]
+    pub heading: String,
+    /// `added`, `removed` or `modified`
+    #[schema(example = "modified")]
+    pub change: String,
+    /// Unified diff of the section text
+    #[schema(example = "@@ -1,2 +1,2 @@\n ## Installation\n-Run make\n+Run cargo build\n")]
+    pub diff: String,
 }
 
 /// Wiki response structure
@@ -48,6 +218,20 @@ pub struct WikiResponse {
     pub description: String,
     pub pages: Vec<WikiPageResponse>,
     pub sections: Vec<serde_json::Value>,
+    /// Table of contents for the sidebar: sections → pages → page headings.
+    /// Entries have `kind` (`section`, `page` or `heading`), `id`, `title`,
+    /// `anchor` (headings only) and `children`
+    pub navigation: Vec<serde_json::Value>,
+    /// Link check summary: `checked_links`, `rewritten_links`, `linked_files`
+    /// and `dangling` links with `page_id`, `text`, `target` and `reason`
+    /// (`unknown_page`, `unknown_anchor` or `unknown_file`)
+    pub link_validation: serde_json::Value,
+    /// Quality report: overall `score` (0-100), `file_coverage`,
+    /// `uncovered_files`, `broken_links`, `empty_sections`,
+    /// `empty_wiki_sections` and `pages` (weakest first) with their `score`,
+    /// `missing_files`, `broken_links`, `empty_sections`, `citations`,
+    /// `citation_density` and `issues`; `null` for wikis that were not scored
+    pub quality: Option<serde_json::Value>,
 }
 
 /// Wiki page response structure
@@ -71,4 +255,7 @@ pub struct WikiPageResponse {
     #[schema(example = "2024-01-01T00:00:00Z")]
     pub generated_at: String,
     pub source_documents: Vec<String>,
+    /// Code examples copied verbatim from the repository: `path`,
+    /// `start_line`, `end_line` and whether they were `verified`
+    pub code_examples: Vec<serde_json::Value>,
 }
@@ -0,0 +1,298 @@
+//! Webhook handlers
+//!
+//! Webhooks are notified of finished indexing, generated wikis and finished
+//! research. Each POST carries the event as JSON, its name in the
+//! `X-Wikify-Event` header and an HMAC-SHA256 of the body, keyed with the
+//! webhook's secret, in the `X-Wikify-Signature` header.
+
+use super::types::{
+    CreateWebhookRequest, UpdateWebhookRequest, WebhookCreatedResponse, WebhookDeliveriesQuery,
+    WebhookDeliveryListResponse, WebhookListResponse, WebhookResponse,
+};
+use crate::{auth::ModeAwareUser, AppState};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    Json as JsonExtractor,
+};
+use tracing::{error, info};
+use wikify_applications::{
+    webhook::DELIVERY_LOG_LIMIT, ApplicationError, NewWebhook, WebhookDelivery, WebhookUpdate,
+};
+
+/// Deliveries listed unless the client asks for another number
+const DEFAULT_DELIVERY_LIMIT: usize = 20;
+
+/// Map an application error to the status code returned by the webhook API
+fn webhook_error_status(error: ApplicationError) -> StatusCode {
+    match error {
+        ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+        ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+        ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+        e => {
+            error!("Webhook request failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// List webhooks
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "Webhook",
+    summary = "List webhooks",
+    description = "List the webhooks registered by the current user (admins see all webhooks)",
+    responses(
+        (status = 200, description = "Webhooks listed successfully", body = WebhookListResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+) -> Result<Json<WebhookListResponse>, StatusCode> {
+    let context = user.to_permission_context();
+    let webhooks = state
+        .application
+        .list_webhooks(&context)
+        .await
+        .map_err(webhook_error_status)?;
+
+    Ok(Json(WebhookListResponse {
+        webhooks: webhooks.into_iter().map(Into::into).collect(),
+    }))
+}
+
+/// Register webhook
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "Webhook",
+    summary = "Register webhook",
+    description = "Register a URL to be notified of `indexing.completed`, `indexing.failed`, `wiki.generated` and `research.finished` events. Webhooks for one repository can be added by its owner; webhooks for every repository require admin permissions. The secret is only returned here.",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook registered successfully", body = WebhookCreatedResponse),
+        (status = 400, description = "Invalid webhook URL"),
+        (status = 403, description = "Not allowed to add webhooks for this repository"),
+        (status = 404, description = "Repository not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    JsonExtractor(request): JsonExtractor<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookCreatedResponse>), StatusCode> {
+    info!("Registering webhook {} (user: {})", request.url, user.id);
+
+    let context = user.to_permission_context();
+    let webhook = state
+        .application
+        .create_webhook(
+            &context,
+            NewWebhook {
+                url: request.url,
+                secret: request.secret,
+                events: request.events,
+                repository_id: request.repository_id,
+            },
+        )
+        .await
+        .map_err(webhook_error_status)?;
+
+    let secret = webhook.secret.clone();
+    Ok((
+        StatusCode::CREATED,
+        Json(WebhookCreatedResponse {
+            webhook: webhook.into(),
+            secret,
+        }),
+    ))
+}
+
+/// Get webhook
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{webhook_id}",
+    tag = "Webhook",
+    summary = "Get webhook",
+    params(
+        ("webhook_id" = String, Path, description = "Webhook ID")
+    ),
+    responses(
+        (status = 200, description = "Webhook retrieved successfully", body = WebhookResponse),
+        (status = 404, description = "Webhook not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(webhook_id): Path<String>,
+) -> Result<Json<WebhookResponse>, StatusCode> {
+    let context = user.to_permission_context();
+    let webhook = state
+        .application
+        .get_webhook(&context, &webhook_id)
+        .await
+        .map_err(webhook_error_status)?;
+
+    Ok(Json(webhook.into()))
+}
+
+/// Update webhook
+#[utoipa::path(
+    patch,
+    path = "/api/webhooks/{webhook_id}",
+    tag = "Webhook",
+    summary = "Update webhook",
+    description = "Change the URL, events, secret or active state of a webhook",
+    params(
+        ("webhook_id" = String, Path, description = "Webhook ID")
+    ),
+    request_body = UpdateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook updated successfully", body = WebhookResponse),
+        (status = 400, description = "Invalid webhook URL"),
+        (status = 404, description = "Webhook not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn update_webhook(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(webhook_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, StatusCode> {
+    info!("Updating webhook {} (user: {})", webhook_id, user.id);
+
+    let context = user.to_permission_context();
+    let webhook = state
+        .application
+        .update_webhook(
+            &context,
+            &webhook_id,
+            WebhookUpdate {
+                url: request.url,
+                secret: request.secret,
+                events: request.events,
+                active: request.active,
+            },
+        )
+        .await
+        .map_err(webhook_error_status)?;
+
+    Ok(Json(webhook.into()))
+}
+
+/// Delete webhook
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{webhook_id}",
+    tag = "Webhook",
+    summary = "Delete webhook",
+    description = "Delete a webhook and its delivery log",
+    params(
+        ("webhook_id" = String, Path, description = "Webhook ID")
+    ),
+    responses(
+        (status = 204, description = "Webhook deleted successfully"),
+        (status = 404, description = "Webhook not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(webhook_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    info!("Deleting webhook {} (user: {})", webhook_id, user.id);
+
+    let context = user.to_permission_context();
+    state
+        .application
+        .delete_webhook(&context, &webhook_id)
+        .await
+        .map_err(webhook_error_status)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// List webhook deliveries
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{webhook_id}/deliveries",
+    tag = "Webhook",
+    summary = "List webhook deliveries",
+    description = "List the most recent delivery attempts of a webhook, newest first, with the posted payload and the response status. The last 100 attempts are kept.",
+    params(
+        ("webhook_id" = String, Path, description = "Webhook ID"),
+        WebhookDeliveriesQuery
+    ),
+    responses(
+        (status = 200, description = "Deliveries listed successfully", body = WebhookDeliveryListResponse),
+        (status = 404, description = "Webhook not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path(webhook_id): Path<String>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<Json<WebhookDeliveryListResponse>, StatusCode> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, DELIVERY_LOG_LIMIT);
+
+    let context = user.to_permission_context();
+    let deliveries = state
+        .application
+        .list_webhook_deliveries(&context, &webhook_id, limit)
+        .await
+        .map_err(webhook_error_status)?;
+
+    Ok(Json(WebhookDeliveryListResponse { deliveries }))
+}
+
+/// Retry webhook delivery
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/{webhook_id}/deliveries/{delivery_id}/retry",
+    tag = "Webhook",
+    summary = "Retry webhook delivery",
+    description = "Post the payload of an earlier delivery to the webhook again, once, and return the new attempt",
+    params(
+        ("webhook_id" = String, Path, description = "Webhook ID"),
+        ("delivery_id" = String, Path, description = "ID of the delivery to repeat")
+    ),
+    responses(
+        (status = 200, description = "Delivery attempted; check `success` for the outcome", body = WebhookDelivery),
+        (status = 404, description = "Webhook or delivery not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn retry_webhook_delivery(
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+    Path((webhook_id, delivery_id)): Path<(String, String)>,
+) -> Result<Json<WebhookDelivery>, StatusCode> {
+    info!(
+        "Retrying delivery {} of webhook {} (user: {})",
+        delivery_id, webhook_id, user.id
+    );
+
+    let context = user.to_permission_context();
+    let delivery = state
+        .application
+        .retry_webhook_delivery(&context, &webhook_id, &delivery_id)
+        .await
+        .map_err(webhook_error_status)?;
+
+    Ok(Json(delivery))
+}
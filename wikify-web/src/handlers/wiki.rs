@@ -1,17 +1,29 @@
 //! Wiki generation and management handlers
 
-use super::types::{GenerateWikiRequest, GenerateWikiResponse, WikiResponse};
+use super::etag;
+use super::types::{
+    EditWikiPageRequest, ExportWikiRequest, GenerateWikiRequest, GenerateWikiResponse,
+    RegenerateWikiPageRequest, WikiDiffQuery, WikiDiffResponse, WikiExportJobResponse,
+    WikiPageDiffResponse, WikiPageRefResponse, WikiPageResponse, WikiResponse,
+    WikiSectionDiffResponse, WikiTemplateBody, WikiTemplatePageBody, WikiTemplateSectionBody,
+    WikiVersionResponse,
+};
 use crate::{
-    auth::{RequireExport, RequireGenerateWiki},
+    auth::{RequireExport, RequireGenerateWiki, RequireQuery},
+    state::{BroadcastMessage, IndexingUpdate},
     AppState,
 };
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
     Json as JsonExtractor,
 };
-use tracing::{error, info};
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
+use tracing::{error, info, warn};
+use wikify_applications::ApplicationError;
+use wikify_core::{CallbackProgress, ProgressUpdate};
 
 /// Helper function to convert User to PermissionContext for application layer
 fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::PermissionContext {
@@ -24,19 +36,18 @@ fn user_to_permission_context(user: &crate::auth::User) -> wikify_applications::
     path = "/api/wiki/generate",
     tag = "Wiki",
     summary = "Generate wiki documentation",
-    description = "Generate comprehensive wiki documentation for a repository",
+    description = "Start generating wiki documentation for a repository in the background. Progress is streamed over the `/ws/wiki` WebSocket and the result can be fetched from `GET /api/wiki/{repository_id}` once complete.",
     request_body = GenerateWikiRequest,
     responses(
-        (status = 200, description = "Wiki generated successfully", body = GenerateWikiResponse),
-        (status = 404, description = "Session not found"),
-        (status = 500, description = "Wiki generation failed")
+        (status = 202, description = "Wiki generation started", body = GenerateWikiResponse),
+        (status = 404, description = "Repository not found")
     )
 )]
 pub async fn generate_wiki(
     State(state): State<AppState>,
     RequireGenerateWiki(user): RequireGenerateWiki,
     JsonExtractor(request): JsonExtractor<GenerateWikiRequest>,
-) -> Result<Json<GenerateWikiResponse>, StatusCode> {
+) -> Result<(StatusCode, Json<GenerateWikiResponse>), StatusCode> {
     info!(
         "Generating wiki for repository: {} (user: {})",
         request.repository_id, user.id
@@ -44,15 +55,15 @@ pub async fn generate_wiki(
 
     // Convert to permission context for application layer
     let context = user_to_permission_context(&user);
-    // Get repository info
-    let repository = match state
+    // Verify the repository exists before starting the background job
+    if state
         .application
         .get_repository(&context, &request.repository_id)
         .await
+        .is_err()
     {
-        Ok(repository) => repository,
-        Err(_) => return Err(StatusCode::NOT_FOUND),
-    };
+        return Err(StatusCode::NOT_FOUND);
+    }
 
     // Create wiki configuration
     let mut wiki_config = wikify_wiki::WikiConfig::default();
@@ -68,16 +79,237 @@ pub async fn generate_wiki(
     if let Some(comprehensive_view) = request.config.comprehensive_view {
         wiki_config.comprehensive_view = comprehensive_view;
     }
+    if let Some(force_regenerate) = request.config.force_regenerate {
+        wiki_config.force_regenerate = force_regenerate;
+    }
+
+    spawn_wiki_generation(state, context, request.repository_id.clone(), wiki_config);
 
-    // TODO: Implement wiki generation through application layer
-    // For now, return a placeholder response
     let response = GenerateWikiResponse {
         wiki_id: request.repository_id.clone(), // Use repository_id as wiki_id
-        status: "not_implemented".to_string(),
+        status: "started".to_string(),
         pages_count: 0,
         sections_count: 0,
+        quality: None,
     };
-    Ok(Json(response))
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+/// Cancel a running wiki generation
+#[utoipa::path(
+    post,
+    path = "/api/wiki/{repository_id}/generate/cancel",
+    tag = "Wiki",
+    summary = "Cancel wiki generation",
+    description = "Stop the wiki generation running in the background for a repository. The stored wiki is left as it was and a `WikiGenerationError` WebSocket message reports the cancellation.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID whose wiki generation should be cancelled")
+    ),
+    responses(
+        (status = 202, description = "Cancellation requested", body = GenerateWikiResponse),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "No wiki generation is running for the repository")
+    )
+)]
+pub async fn cancel_wiki_generation(
+    State(state): State<AppState>,
+    RequireGenerateWiki(user): RequireGenerateWiki,
+    Path(repository_id): Path<String>,
+) -> Result<(StatusCode, Json<GenerateWikiResponse>), StatusCode> {
+    info!(
+        "Cancelling wiki generation for repository: {} (user: {})",
+        repository_id, user.id
+    );
+
+    let context = user_to_permission_context(&user);
+    if let Err(e) = state
+        .application
+        .get_repository(&context, &repository_id)
+        .await
+    {
+        return Err(match e {
+            ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+            _ => StatusCode::NOT_FOUND,
+        });
+    }
+
+    if !state.wiki_generations.cancel(&repository_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(GenerateWikiResponse {
+            wiki_id: repository_id,
+            status: "cancelling".to_string(),
+            pages_count: 0,
+            sections_count: 0,
+            quality: None,
+        }),
+    ))
+}
+
+/// Generate a wiki in the background, broadcasting progress to WebSocket clients
+///
+/// Starting a generation cancels any generation already running for the
+/// same repository.
+pub(crate) fn spawn_wiki_generation(
+    state: AppState,
+    context: wikify_applications::PermissionContext,
+    repository_id: String,
+    config: wikify_wiki::WikiConfig,
+) {
+    let _ = state
+        .progress_broadcaster
+        .send(BroadcastMessage::IndexingUpdate(
+            IndexingUpdate::WikiGenerationStarted {
+                repository_id: repository_id.clone(),
+            },
+        ));
+
+    let (generation_id, cancel) = state.wiki_generations.start(&repository_id);
+    tokio::spawn(async move {
+        let result = run_wiki_generation(&state, &context, &repository_id, config, &cancel).await;
+        state.wiki_generations.finish(&repository_id, generation_id);
+        if let Err(e) = result {
+            error!(
+                "Failed to generate wiki for repository {}: {}",
+                repository_id, e
+            );
+            let _ = state
+                .progress_broadcaster
+                .send(BroadcastMessage::IndexingUpdate(
+                    IndexingUpdate::WikiGenerationError {
+                        repository_id,
+                        error: e.to_string(),
+                    },
+                ));
+        }
+    });
+}
+
+/// Generate and persist a wiki through the application layer
+async fn run_wiki_generation(
+    state: &AppState,
+    context: &wikify_applications::PermissionContext,
+    repository_id: &str,
+    config: wikify_wiki::WikiConfig,
+    cancel: &wikify_core::CancellationToken,
+) -> Result<(), wikify_applications::ApplicationError> {
+    let progress_sender = state.progress_broadcaster.clone();
+    let progress = CallbackProgress::new(|update: &ProgressUpdate| {
+        let _ = progress_sender.send(BroadcastMessage::IndexingUpdate(
+            IndexingUpdate::WikiGenerationProgress {
+                repository_id: repository_id.to_string(),
+                stage: update.to_string(),
+                percentage: update.progress,
+            },
+        ));
+    });
+    let wiki_structure = state
+        .application
+        .generate_wiki_cancellable(context, repository_id, config, &progress, cancel)
+        .await?;
+
+    // Extract actual markdown content from the first page, or create a summary
+    let wiki_content = if let Some(first_page) = wiki_structure.pages.first() {
+        first_page.content.clone()
+    } else {
+        format!(
+            "# {}\n\n{}\n\nNo content pages were generated.",
+            wiki_structure.title, wiki_structure.description
+        )
+    };
+
+    // Store in database if available
+    #[cfg(feature = "sqlite")]
+    if let Some(ref database) = state.database {
+        if let Err(e) = database
+            .store_wiki(repository_id, &wiki_structure, &wiki_content)
+            .await
+        {
+            error!("Failed to store wiki in database: {}", e);
+        }
+    }
+
+    // Also cache in memory for faster access
+    let cached_wiki = crate::state::CachedWiki {
+        content: wiki_content.clone(),
+        generated_at: chrono::Utc::now(),
+        repository: wiki_structure.metadata.repository.clone(),
+        format: "markdown".to_string(),
+        structure: Some(wiki_structure.clone()),
+    };
+    state
+        .wiki_cache
+        .write()
+        .await
+        .insert(repository_id.to_string(), cached_wiki);
+
+    let _ = state
+        .progress_broadcaster
+        .send(BroadcastMessage::IndexingUpdate(
+            IndexingUpdate::WikiGenerationComplete {
+                repository_id: repository_id.to_string(),
+                wiki_content,
+                pages_count: wiki_structure.pages.len(),
+                sections_count: wiki_structure.sections.len(),
+                quality: wiki_structure.quality.clone(),
+            },
+        ));
+
+    Ok(())
+}
+
+/// Convert a wiki structure to the format expected by the frontend
+fn wiki_to_response(wiki_structure: &wikify_wiki::WikiStructure) -> serde_json::Value {
+    serde_json::json!({
+        "id": wiki_structure.id,
+        "title": wiki_structure.title,
+        "description": wiki_structure.description,
+        "commit_sha": wiki_structure.metadata.commit_sha,
+        "generated_at": wiki_structure.metadata.generated_at.to_rfc3339(),
+        "pages": wiki_structure.pages.iter().map(page_to_response).collect::<Vec<_>>(),
+        "sections": wiki_structure.sections.iter().map(|section| {
+            serde_json::json!({
+                "id": section.id,
+                "title": section.title,
+                "description": section.description,
+                "pages": section.pages,
+                "subsections": section.subsections,
+                "importance": "Medium", // Default importance
+                "order": section.order
+            })
+        }).collect::<Vec<_>>(),
+        "navigation": wikify_wiki::NavigationBuilder::new().build(wiki_structure),
+        "link_validation": wiki_structure.link_validation,
+        "quality": wiki_structure.quality
+    })
+}
+
+/// Convert a wiki page to the format expected by the frontend
+fn page_to_response(page: &wikify_wiki::WikiPage) -> serde_json::Value {
+    serde_json::json!({
+        "id": page.id,
+        "title": page.title,
+        "content": page.content,
+        "description": page.description,
+        "importance": format!("{:?}", page.importance),
+        "file_paths": page.file_paths,
+        "related_pages": page.related_pages,
+        "parent_section": page.parent_section,
+        "tags": page.tags,
+        "reading_time": page.reading_time,
+        "generated_at": page.generated_at.to_rfc3339(),
+        "source_documents": page.source_documents.iter().map(|doc| {
+            serde_json::json!({
+                "path": doc.file_path,
+                "title": doc.file_path.split('/').last().unwrap_or(&doc.file_path),
+                "relevance_score": 1.0 // Default relevance score
+            })
+        }).collect::<Vec<_>>(),
+        "code_examples": page.code_examples
+    })
 }
 
 /// Get generated wiki
@@ -86,36 +318,53 @@ pub async fn generate_wiki(
     path = "/api/wiki/{repository_id}",
     tag = "Wiki",
     summary = "Get generated wiki",
-    description = "Retrieve the generated wiki documentation for a repository. No authentication required.",
+    description = "Retrieve the stored wiki documentation for a repository. Returns 404 until a wiki has been generated. The response carries an ETag; sending it back in If-None-Match returns 304 while the wiki is unchanged. No authentication required.",
     params(
         ("repository_id" = String, Path, description = "Repository ID")
     ),
     responses(
         (status = 200, description = "Wiki retrieved successfully", body = WikiResponse),
-        (status = 404, description = "Repository not found"),
-        (status = 500, description = "Failed to generate wiki")
+        (status = 304, description = "Wiki unchanged since the ETag in If-None-Match"),
+        (status = 404, description = "Repository or wiki not found")
     )
 )]
 pub async fn get_wiki(
     State(state): State<AppState>,
     Path(repository_id): Path<String>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
     info!("Getting wiki for repository: {}", repository_id);
 
     // Create anonymous permission context (no authentication required for wiki viewing)
     let context = state.create_anonymous_context();
 
-    // Get repository info (verify repository exists)
-    let repository = match state
+    // Verify repository exists
+    if state
         .application
         .get_repository(&context, &repository_id)
         .await
+        .is_err()
     {
-        Ok(repository) => repository,
-        Err(_) => return Err(StatusCode::NOT_FOUND),
-    };
+        return Err(StatusCode::NOT_FOUND);
+    }
 
-    // Check if wiki is stored in database first
+    // Wikis generated through the application layer are persisted there
+    match state.application.get_wiki(&context, &repository_id).await {
+        Ok(Some(wiki_structure)) => {
+            info!("Returning stored wiki for repository: {}", repository_id);
+            return Ok(etag::json_with_etag(
+                &headers,
+                &wiki_to_response(&wiki_structure),
+            ));
+        }
+        Ok(None) => {}
+        Err(e) => warn!(
+            "Failed to load stored wiki for repository {}: {}",
+            repository_id, e
+        ),
+    }
+
+    // Fall back to wikis stored in the database
     #[cfg(feature = "sqlite")]
     if let Some(ref database) = state.database {
         if let Ok(Some(wiki_record)) = database.get_wiki_by_repository(&repository_id).await {
@@ -129,45 +378,7 @@ pub async fn get_wiki(
                 if let Ok(wiki_structure) =
                     serde_json::from_str::<wikify_wiki::WikiStructure>(structure_json)
                 {
-                    // Convert WikiStructure to frontend format
-                    serde_json::json!({
-                        "id": wiki_structure.id,
-                        "title": wiki_structure.title,
-                        "description": wiki_structure.description,
-                        "pages": wiki_structure.pages.iter().map(|page| {
-                            serde_json::json!({
-                                "id": page.id,
-                                "title": page.title,
-                                "content": page.content,
-                                "description": page.description,
-                                "importance": format!("{:?}", page.importance),
-                                "file_paths": page.file_paths,
-                                "related_pages": page.related_pages,
-                                "parent_section": page.parent_section,
-                                "tags": page.tags,
-                                "reading_time": page.reading_time,
-                                "generated_at": page.generated_at.to_rfc3339(),
-                                "source_documents": page.source_documents.iter().map(|doc| {
-                                    serde_json::json!({
-                                        "path": doc.file_path,
-                                        "title": doc.file_path.split('/').last().unwrap_or(&doc.file_path),
-                                        "relevance_score": 1.0 // Default relevance score
-                                    })
-                                }).collect::<Vec<_>>()
-                            })
-                        }).collect::<Vec<_>>(),
-                        "sections": wiki_structure.sections.iter().map(|section| {
-                            serde_json::json!({
-                                "id": section.id,
-                                "title": section.title,
-                                "description": section.description,
-                                "pages": section.pages,
-                                "subsections": section.subsections,
-                                "importance": "Medium", // Default importance
-                                "order": section.order
-                            })
-                        }).collect::<Vec<_>>()
-                    })
+                    wiki_to_response(&wiki_structure)
                 } else {
                     // Fallback to simple format
                     serde_json::json!({
@@ -199,7 +410,7 @@ pub async fn get_wiki(
                 return Err(StatusCode::NOT_FOUND);
             };
 
-            return Ok(Json(wiki_response));
+            return Ok(etag::json_with_etag(&headers, &wiki_response));
         }
     }
 
@@ -251,7 +462,7 @@ pub async fn get_wiki(
                 }).collect::<Vec<_>>()
             });
 
-            return Ok(Json(wiki_response));
+            return Ok(etag::json_with_etag(&headers, &wiki_response));
         } else {
             // Legacy cached content without structure - return not found
             info!(
@@ -263,111 +474,537 @@ pub async fn get_wiki(
     }
     drop(wiki_cache);
 
-    // If no cached wiki, try to generate one
     info!(
-        "No cached wiki found, generating new wiki for repository: {}",
+        "No wiki has been generated for repository: {}",
         repository_id
     );
+    Err(StatusCode::NOT_FOUND)
+}
+
+/// List archived wiki versions
+#[utoipa::path(
+    get,
+    path = "/api/wiki/{repository_id}/versions",
+    tag = "Wiki",
+    summary = "List wiki versions",
+    description = "List the archived wikis of a repository, newest first. Each version is pinned to the commit it was generated from; regenerating at the same commit replaces that version. No authentication required.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 200, description = "Wiki versions", body = Vec<WikiVersionResponse>),
+        (status = 404, description = "Repository not found")
+    )
+)]
+pub async fn list_wiki_versions(
+    State(state): State<AppState>,
+    Path(repository_id): Path<String>,
+) -> Result<Json<Vec<WikiVersionResponse>>, StatusCode> {
+    // Create anonymous permission context (no authentication required for wiki viewing)
+    let context = state.create_anonymous_context();
 
-    // Generate wiki using wiki service
-    let mut wiki_service = state.wiki_service.write().await;
-    let wiki_config = wikify_wiki::WikiConfig::default();
-    match wiki_service
-        .generate_wiki(&repository.url, &wiki_config)
+    // Verify repository exists
+    if state
+        .application
+        .get_repository(&context, &repository_id)
         .await
+        .is_err()
     {
-        Ok(wiki_structure) => {
-            // Extract actual markdown content from the first page
-            let wiki_content = wiki_structure
-                .pages
-                .first()
-                .map(|page| page.content.clone())
-                .unwrap_or_else(|| {
-                    // This should not happen since we check for empty pages in the generator
-                    error!("Wiki structure has no pages despite passing validation");
-                    String::new()
-                });
-            let generated_at = chrono::Utc::now();
-
-            // Store in database if available
-            #[cfg(feature = "sqlite")]
-            if let Some(ref database) = state.database {
-                if let Err(e) = database
-                    .store_wiki(&repository_id, &wiki_structure, &wiki_content)
-                    .await
-                {
-                    error!("Failed to store wiki in database: {}", e);
-                    // Continue with memory cache as fallback
-                }
-            }
+        return Err(StatusCode::NOT_FOUND);
+    }
 
-            // Also cache in memory for faster access
-            let cached_wiki = crate::state::CachedWiki {
-                content: wiki_content.clone(),
-                generated_at,
-                repository: repository.url.clone(),
-                format: "markdown".to_string(),
-                structure: Some(wiki_structure.clone()),
-            };
+    let versions = state
+        .application
+        .list_wiki_versions(&context, &repository_id)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to list wiki versions for repository {}: {}",
+                repository_id, e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-            let mut wiki_cache = state.wiki_cache.write().await;
-            wiki_cache.insert(repository_id.clone(), cached_wiki);
-            drop(wiki_cache);
+    Ok(Json(
+        versions.into_iter().map(version_to_response).collect(),
+    ))
+}
 
-            info!(
-                "Wiki generated and cached for repository: {}",
-                repository_id
+/// Convert a wiki version summary to its API representation
+fn version_to_response(version: wikify_wiki::WikiVersion) -> WikiVersionResponse {
+    WikiVersionResponse {
+        version_id: version.version_id,
+        commit_sha: version.commit_sha,
+        generated_at: version.generated_at.to_rfc3339(),
+        pages_count: version.pages_count,
+        sections_count: version.sections_count,
+    }
+}
+
+/// Get an archived wiki version
+#[utoipa::path(
+    get,
+    path = "/api/wiki/{repository_id}/versions/{version_id}",
+    tag = "Wiki",
+    summary = "Get wiki version",
+    description = "Retrieve an archived wiki by version ID (the commit SHA it was generated from), in the same format as `GET /api/wiki/{repository_id}`. No authentication required.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        ("version_id" = String, Path, description = "Version ID, usually a commit SHA")
+    ),
+    responses(
+        (status = 200, description = "Wiki version retrieved successfully", body = WikiResponse),
+        (status = 404, description = "Repository or version not found")
+    )
+)]
+pub async fn get_wiki_version(
+    State(state): State<AppState>,
+    Path((repository_id, version_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    // Create anonymous permission context (no authentication required for wiki viewing)
+    let context = state.create_anonymous_context();
+
+    match state
+        .application
+        .get_wiki_version(&context, &repository_id, &version_id)
+        .await
+    {
+        Ok(Some(wiki_structure)) => Ok(Json(wiki_to_response(&wiki_structure))),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!(
+                "Failed to load wiki version {} for repository {}: {}",
+                version_id, repository_id, e
             );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
 
-            // Convert WikiStructure to the format expected by frontend
-            let wiki_response = serde_json::json!({
-                "id": wiki_structure.id,
-                "title": wiki_structure.title,
-                "description": wiki_structure.description,
-                "pages": wiki_structure.pages.iter().map(|page| {
-                    serde_json::json!({
-                        "id": page.id,
-                        "title": page.title,
-                        "content": page.content,
-                        "description": page.description,
-                        "importance": format!("{:?}", page.importance),
-                        "file_paths": page.file_paths,
-                        "related_pages": page.related_pages,
-                        "parent_section": page.parent_section,
-                        "tags": page.tags,
-                        "reading_time": page.reading_time,
-                        "generated_at": page.generated_at.to_rfc3339(),
-                        "source_documents": page.source_documents.iter().map(|doc| {
-                            serde_json::json!({
-                                "path": doc.file_path,
-                                "title": doc.file_path.split('/').last().unwrap_or(&doc.file_path),
-                                "relevance_score": 1.0 // Default relevance score
-                            })
-                        }).collect::<Vec<_>>()
-                    })
-                }).collect::<Vec<_>>(),
-                "sections": wiki_structure.sections.iter().map(|section| {
-                    serde_json::json!({
-                        "id": section.id,
-                        "title": section.title,
-                        "description": section.description,
-                        "pages": section.pages,
-                        "subsections": section.subsections,
-                        "importance": "Medium", // Default importance
-                        "order": section.order
+/// Compare two wiki versions
+#[utoipa::path(
+    get,
+    path = "/api/wiki/{repository_id}/diff",
+    tag = "Wiki",
+    summary = "Diff wiki versions",
+    description = "Compare two archived wiki versions: pages added or removed, and for changed pages a unified diff of every changed section. Without parameters the newest version is compared with the one before it. No authentication required.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        WikiDiffQuery
+    ),
+    responses(
+        (status = 200, description = "Differences between the versions", body = WikiDiffResponse),
+        (status = 404, description = "Repository or version not found")
+    )
+)]
+pub async fn diff_wiki_versions(
+    State(state): State<AppState>,
+    Path(repository_id): Path<String>,
+    Query(query): Query<WikiDiffQuery>,
+) -> Result<Json<WikiDiffResponse>, StatusCode> {
+    // Create anonymous permission context (no authentication required for wiki viewing)
+    let context = state.create_anonymous_context();
+
+    let diff = state
+        .application
+        .diff_wiki_versions(
+            &context,
+            &repository_id,
+            query.from.as_deref(),
+            query.to.as_deref(),
+        )
+        .await
+        .map_err(|e| match e {
+            ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+            e => {
+                error!(
+                    "Failed to diff wiki versions for repository {}: {}",
+                    repository_id, e
+                );
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    let page_ref = |page: wikify_wiki::diff::PageRef| WikiPageRefResponse {
+        id: page.id,
+        title: page.title,
+    };
+    Ok(Json(WikiDiffResponse {
+        from: version_to_response(diff.from),
+        to: version_to_response(diff.to),
+        added_pages: diff.added_pages.into_iter().map(page_ref).collect(),
+        removed_pages: diff.removed_pages.into_iter().map(page_ref).collect(),
+        changed_pages: diff
+            .changed_pages
+            .into_iter()
+            .map(|page| WikiPageDiffResponse {
+                id: page.id,
+                old_title: page.old_title,
+                new_title: page.new_title,
+                sections: page
+                    .sections
+                    .into_iter()
+                    .map(|section| WikiSectionDiffResponse {
+                        heading: section.heading,
+                        change: match section.change {
+                            wikify_wiki::diff::ChangeKind::Added => "added",
+                            wikify_wiki::diff::ChangeKind::Removed => "removed",
+                            wikify_wiki::diff::ChangeKind::Modified => "modified",
+                        }
+                        .to_string(),
+                        diff: section.diff,
                     })
-                }).collect::<Vec<_>>()
-            });
+                    .collect(),
+            })
+            .collect(),
+        unchanged_pages: diff.unchanged_pages,
+    }))
+}
 
-            Ok(Json(wiki_response))
-        }
-        Err(e) => {
+/// Regenerate a single wiki page
+#[utoipa::path(
+    post,
+    path = "/api/wiki/{repository_id}/pages/{page_id}/regenerate",
+    tag = "Wiki",
+    summary = "Regenerate wiki page",
+    description = "Regenerate one page of the stored wiki instead of the whole wiki, optionally with extra instructions for the LLM. The updated page replaces the old one and is returned once generation finishes.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        ("page_id" = String, Path, description = "Wiki page ID")
+    ),
+    request_body = RegenerateWikiPageRequest,
+    responses(
+        (status = 200, description = "Page regenerated", body = WikiPageResponse),
+        (status = 404, description = "Repository, wiki or page not found"),
+        (status = 500, description = "Page generation failed")
+    )
+)]
+pub async fn regenerate_wiki_page(
+    State(state): State<AppState>,
+    RequireGenerateWiki(user): RequireGenerateWiki,
+    Path((repository_id, page_id)): Path<(String, String)>,
+    JsonExtractor(request): JsonExtractor<RegenerateWikiPageRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    info!(
+        "Regenerating wiki page {} for repository: {} (user: {})",
+        page_id, repository_id, user.id
+    );
+
+    // Convert to permission context for application layer
+    let context = user_to_permission_context(&user);
+    let page = state
+        .application
+        .regenerate_wiki_page(&context, &repository_id, &page_id, request.instructions)
+        .await
+        .map_err(|e| {
             error!(
-                "Failed to generate wiki for repository {}: {}",
+                "Failed to regenerate wiki page {} for repository {}: {}",
+                page_id, repository_id, e
+            );
+            match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+
+    // The stored wiki is authoritative; drop the now stale in-memory copy
+    state.wiki_cache.write().await.remove(&repository_id);
+
+    Ok(Json(page_to_response(&page)))
+}
+
+/// Edit a wiki page by hand
+#[utoipa::path(
+    put,
+    path = "/api/wiki/{repository_id}/pages/{page_id}",
+    tag = "Wiki",
+    summary = "Edit wiki page",
+    description = "Replace the content (and optionally the title) of a generated page. Edits are stored separately from generated content and survive regeneration: sections changed by hand keep their text, the rest picks up the regenerated content.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        ("page_id" = String, Path, description = "Wiki page ID")
+    ),
+    request_body = EditWikiPageRequest,
+    responses(
+        (status = 200, description = "Page updated", body = WikiPageResponse),
+        (status = 404, description = "Repository, wiki or page not found")
+    )
+)]
+pub async fn edit_wiki_page(
+    State(state): State<AppState>,
+    RequireGenerateWiki(user): RequireGenerateWiki,
+    Path((repository_id, page_id)): Path<(String, String)>,
+    JsonExtractor(request): JsonExtractor<EditWikiPageRequest>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    info!(
+        "Editing wiki page {} for repository: {} (user: {})",
+        page_id, repository_id, user.id
+    );
+
+    // Convert to permission context for application layer
+    let context = user_to_permission_context(&user);
+    let page = state
+        .application
+        .edit_wiki_page(
+            &context,
+            &repository_id,
+            &page_id,
+            request.title,
+            request.content,
+        )
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to edit wiki page {} for repository {}: {}",
+                page_id, repository_id, e
+            );
+            match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+
+    // The stored wiki is authoritative; drop the now stale in-memory copy
+    state.wiki_cache.write().await.remove(&repository_id);
+
+    Ok(Json(page_to_response(&page)))
+}
+
+/// Get the structure template of a repository's wiki
+#[utoipa::path(
+    get,
+    path = "/api/wiki/{repository_id}/template",
+    tag = "Wiki",
+    summary = "Get wiki template",
+    description = "Get the structure template (sections, required pages, tone) used when generating the repository's wiki.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 200, description = "Wiki template", body = WikiTemplateBody),
+        (status = 404, description = "No template set for this repository")
+    )
+)]
+pub async fn get_wiki_template(
+    State(state): State<AppState>,
+    RequireQuery(user): RequireQuery,
+    Path(repository_id): Path<String>,
+) -> Result<Json<WikiTemplateBody>, StatusCode> {
+    let context = user_to_permission_context(&user);
+    let template = state
+        .application
+        .get_wiki_template(&context, &repository_id)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to get wiki template for repository {}: {}",
                 repository_id, e
             );
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(template_to_body(template)))
+}
+
+/// Set the structure template of a repository's wiki
+#[utoipa::path(
+    put,
+    path = "/api/wiki/{repository_id}/template",
+    tag = "Wiki",
+    summary = "Set wiki template",
+    description = "Set the structure template used whenever the repository's wiki is generated: a skeleton of sections with the pages each must contain, whether the LLM may add pages of its own, and tone instructions for page content.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    request_body = WikiTemplateBody,
+    responses(
+        (status = 200, description = "Template saved", body = WikiTemplateBody),
+        (status = 400, description = "Invalid template"),
+        (status = 404, description = "Repository not found")
+    )
+)]
+pub async fn set_wiki_template(
+    State(state): State<AppState>,
+    RequireGenerateWiki(user): RequireGenerateWiki,
+    Path(repository_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<WikiTemplateBody>,
+) -> Result<Json<WikiTemplateBody>, StatusCode> {
+    info!(
+        "Setting wiki template for repository: {} (user: {})",
+        repository_id, user.id
+    );
+
+    let template = template_from_body(request)?;
+    let context = user_to_permission_context(&user);
+    state
+        .application
+        .set_wiki_template(&context, &repository_id, template.clone())
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to set wiki template for repository {}: {}",
+                repository_id, e
+            );
+            match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+                ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+
+    Ok(Json(template_to_body(template)))
+}
+
+/// Remove the structure template of a repository's wiki
+#[utoipa::path(
+    delete,
+    path = "/api/wiki/{repository_id}/template",
+    tag = "Wiki",
+    summary = "Remove wiki template",
+    description = "Remove the repository's wiki template; later generations let the LLM choose the structure.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 204, description = "Template removed")
+    )
+)]
+pub async fn delete_wiki_template(
+    State(state): State<AppState>,
+    RequireGenerateWiki(user): RequireGenerateWiki,
+    Path(repository_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let context = user_to_permission_context(&user);
+    state
+        .application
+        .remove_wiki_template(&context, &repository_id)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to remove wiki template for repository {}: {}",
+                repository_id, e
+            );
+            match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Purge the wiki generation cache of a repository
+#[utoipa::path(
+    delete,
+    path = "/api/wiki/{repository_id}/cache",
+    tag = "Wiki",
+    summary = "Purge wiki cache",
+    description = "Drop cached wikis of the repository so the next generation runs from scratch even for an unchanged commit and config. The stored wiki and its versions are kept.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID")
+    ),
+    responses(
+        (status = 204, description = "Cache purged"),
+        (status = 404, description = "Repository not found")
+    )
+)]
+pub async fn purge_wiki_cache(
+    State(state): State<AppState>,
+    RequireGenerateWiki(user): RequireGenerateWiki,
+    Path(repository_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let context = user_to_permission_context(&user);
+    state
+        .application
+        .purge_wiki_cache(&context, &repository_id)
+        .await
+        .map_err(|e| {
+            error!(
+                "Failed to purge wiki cache for repository {}: {}",
+                repository_id, e
+            );
+            match e {
+                ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+                ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+    state.wiki_cache.write().await.remove(&repository_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Convert a template request body, rejecting unknown importance levels
+fn template_from_body(body: WikiTemplateBody) -> Result<wikify_wiki::WikiTemplate, StatusCode> {
+    let mut sections = Vec::with_capacity(body.sections.len());
+    for section in body.sections {
+        let mut pages = Vec::with_capacity(section.pages.len());
+        for page in section.pages {
+            let importance = match page.importance.as_deref().map(str::to_lowercase).as_deref() {
+                None | Some("high") => wikify_wiki::ImportanceLevel::High,
+                Some("critical") => wikify_wiki::ImportanceLevel::Critical,
+                Some("medium") => wikify_wiki::ImportanceLevel::Medium,
+                Some("low") => wikify_wiki::ImportanceLevel::Low,
+                Some(other) => {
+                    warn!("Invalid page importance in wiki template: {}", other);
+                    return Err(StatusCode::BAD_REQUEST);
+                }
+            };
+            pages.push(wikify_wiki::TemplatePage {
+                id: page.id,
+                title: page.title,
+                description: page.description,
+                importance,
+            });
         }
+        sections.push(wikify_wiki::TemplateSection {
+            id: section.id,
+            title: section.title,
+            description: section.description,
+            pages,
+        });
+    }
+
+    Ok(wikify_wiki::WikiTemplate {
+        name: body.name,
+        tone: body.tone,
+        sections,
+        allow_additional_pages: body.allow_additional_pages.unwrap_or(true),
+    })
+}
+
+/// Convert a template to its API representation
+fn template_to_body(template: wikify_wiki::WikiTemplate) -> WikiTemplateBody {
+    WikiTemplateBody {
+        name: template.name,
+        tone: template.tone,
+        sections: template
+            .sections
+            .into_iter()
+            .map(|section| WikiTemplateSectionBody {
+                id: section.id,
+                title: section.title,
+                description: section.description,
+                pages: section
+                    .pages
+                    .into_iter()
+                    .map(|page| WikiTemplatePageBody {
+                        id: page.id,
+                        title: page.title,
+                        description: page.description,
+                        importance: Some(format!("{:?}", page.importance)),
+                    })
+                    .collect(),
+            })
+            .collect(),
+        allow_additional_pages: Some(template.allow_additional_pages),
     }
 }
 
@@ -377,30 +1014,147 @@ pub async fn get_wiki(
     path = "/api/wiki/{repository_id}/export",
     tag = "Wiki",
     summary = "Export wiki",
-    description = "Export generated wiki in various formats (not yet implemented)",
+    description = "Export the stored wiki. `json` returns the raw wiki structure directly. `pdf` (a document with cover page, table of contents and syntax-highlighted code blocks; requires Typst on the server), `html` and `markdown` (zipped sites) are written to the server and returned as an export job, whose file is fetched from `GET /api/wiki/{repository_id}/export/{job_id}/download`. Exports are kept for 24 hours.",
     params(
         ("repository_id" = String, Path, description = "Repository ID")
     ),
+    request_body = ExportWikiRequest,
     responses(
-        (status = 200, description = "Wiki exported successfully"),
-        (status = 501, description = "Not implemented")
+        (status = 200, description = "Exported wiki JSON"),
+        (status = 201, description = "Export written and ready to download", body = WikiExportJobResponse),
+        (status = 400, description = "Unsupported export format"),
+        (status = 404, description = "No wiki generated for this repository"),
+        (status = 500, description = "Export failed")
     )
 )]
 pub async fn export_wiki(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     RequireExport(user): RequireExport,
-    Path(_repository_id): Path<String>,
-    JsonExtractor(_request): JsonExtractor<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    Path(repository_id): Path<String>,
+    JsonExtractor(request): JsonExtractor<ExportWikiRequest>,
+) -> Result<Response, StatusCode> {
     info!(
-        "Exporting wiki for repository: {} (user: {})",
-        _repository_id, user.id
+        "Exporting wiki for repository: {} as {} (user: {})",
+        repository_id, request.format, user.id
     );
 
+    let format: wikify_wiki::ExportFormat = request
+        .format
+        .parse()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
     // Convert to permission context for application layer
-    let _context = user_to_permission_context(&user);
-    // Placeholder for wiki export
-    Ok(Json(serde_json::json!({
-        "message": "Wiki export is not yet implemented"
-    })))
+    let context = user_to_permission_context(&user);
+
+    // Large artifacts are kept on disk and downloaded separately
+    if format != wikify_wiki::ExportFormat::Json {
+        let job = state
+            .application
+            .create_wiki_export(&context, &repository_id, format)
+            .await
+            .map_err(|e| export_error_status(&repository_id, e))?;
+
+        let download_url = format!(
+            "{}/api/wiki/{}/export/{}/download",
+            state.config.base_path, repository_id, job.id
+        );
+        return Ok((
+            StatusCode::CREATED,
+            Json(WikiExportJobResponse {
+                job_id: job.id,
+                repository_id: job.repository_id,
+                format: job.format,
+                file_name: job.file_name,
+                content_type: job.content_type,
+                size: job.size,
+                created_at: job.created_at,
+                download_url,
+            }),
+        )
+            .into_response());
+    }
+
+    let exported = state
+        .application
+        .export_wiki(&context, &repository_id, format)
+        .await
+        .map_err(|e| export_error_status(&repository_id, e))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, exported.content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", exported.file_name),
+            ),
+        ],
+        exported.data,
+    )
+        .into_response())
+}
+
+/// Download a wiki export
+#[utoipa::path(
+    get,
+    path = "/api/wiki/{repository_id}/export/{job_id}/download",
+    tag = "Wiki",
+    summary = "Download wiki export",
+    description = "Stream the file of an export created with `POST /api/wiki/{repository_id}/export`. Supports `Range` requests, so interrupted downloads can be resumed.",
+    params(
+        ("repository_id" = String, Path, description = "Repository ID"),
+        ("job_id" = String, Path, description = "Export job ID")
+    ),
+    responses(
+        (status = 200, description = "Export file"),
+        (status = 206, description = "Requested range of the export file"),
+        (status = 403, description = "Insufficient permissions"),
+        (status = 404, description = "Export not found or expired"),
+        (status = 416, description = "Requested range not satisfiable"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn download_wiki_export(
+    State(state): State<AppState>,
+    RequireExport(user): RequireExport,
+    Path((repository_id, job_id)): Path<(String, String)>,
+    request: Request,
+) -> Result<Response, StatusCode> {
+    let context = user_to_permission_context(&user);
+    let job = state
+        .application
+        .get_wiki_export(&context, &repository_id, &job_id)
+        .await
+        .map_err(|e| export_error_status(&repository_id, e))?;
+
+    info!(
+        "Downloading wiki export {} of repository {} (user: {})",
+        job.id, repository_id, user.id
+    );
+
+    // ServeFile answers Range and conditional requests and streams the file
+    let Ok(response) = ServeFile::new(&job.path).oneshot(request).await;
+
+    let mut response = response.into_response();
+    if let Ok(disposition) =
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", job.file_name))
+    {
+        response
+            .headers_mut()
+            .insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    Ok(response)
+}
+
+/// Map an export error to the status code returned by the export endpoints
+fn export_error_status(repository_id: &str, error: ApplicationError) -> StatusCode {
+    error!(
+        "Failed to export wiki for repository {}: {}",
+        repository_id, error
+    );
+    match error {
+        ApplicationError::Permission { .. } => StatusCode::FORBIDDEN,
+        ApplicationError::NotFound { .. } => StatusCode::NOT_FOUND,
+        ApplicationError::Config { .. } => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
 }
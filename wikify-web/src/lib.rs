@@ -3,13 +3,22 @@
 //! This module provides a web interface for Wikify, similar to DeepWiki's architecture.
 
 pub mod auth;
+pub mod cors;
+pub mod csrf;
+pub mod frontend;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod handlers;
+pub mod mcp;
 pub mod middleware;
 pub mod openapi;
 pub mod routes;
 pub mod server;
 pub mod state;
 pub mod templates;
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(feature = "websocket")]
 pub mod websocket;
 
 // Database support (optional)
@@ -20,84 +29,154 @@ pub mod simple_database;
 pub use server::WikifyServer;
 pub use state::AppState;
 
-use axum::{
-    extract::DefaultBodyLimit,
-    http::{
-        header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
-        HeaderValue, Method,
-    },
-    Router,
-};
+use axum::{extract::DefaultBodyLimit, Router};
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 
 /// Create the main application router
 pub fn create_app(state: AppState) -> Router {
-    // Configure CORS with environment variable support
-    let cors = {
-        // Get allowed origins from environment variable or use defaults
-        let allowed_origins = std::env::var("WIKIFY_CORS_ORIGINS").unwrap_or_else(|_| {
-            // Default origins for development (Vite default port)
-            "http://localhost:5173,http://127.0.0.1:5173".to_string()
-        });
-
-        let cors_layer = CorsLayer::new()
-            .allow_methods([
-                Method::GET,
-                Method::POST,
-                Method::PUT,
-                Method::DELETE,
-                Method::OPTIONS,
-            ])
-            .allow_credentials(true)
-            .allow_headers([AUTHORIZATION, ACCEPT, CONTENT_TYPE]);
-
-        // Parse and add each origin
-        let origins: Vec<HeaderValue> = allowed_origins
-            .split(',')
-            .filter_map(|origin| {
-                let origin = origin.trim();
-                if origin.is_empty() {
-                    return None;
-                }
-                match origin.parse::<HeaderValue>() {
-                    Ok(header_value) => {
-                        tracing::info!("Added CORS origin: {}", origin);
-                        Some(header_value)
-                    }
-                    Err(e) => {
-                        tracing::warn!("Invalid CORS origin format '{}': {}", origin, e);
-                        None
-                    }
-                }
-            })
-            .collect();
-
-        // Add all origins at once
-        cors_layer.allow_origin(origins)
-    };
+    // The CORS settings were validated when the server started
+    let cors = cors::cors_layer(&state.config).unwrap_or_else(|e| {
+        tracing::error!("{}, allowing no cross-origin requests", e);
+        CorsLayer::new()
+    });
 
     // Create the main router
-    Router::new()
+    let base_path = state.config.base_path.clone();
+    let routes = Router::new()
         // API routes
-        .nest("/api", routes::api_routes(state.clone()))
-        // WebSocket routes
-        .nest("/ws", routes::websocket_routes().with_state(state.clone()))
+        .nest("/api", routes::api_routes(state.clone()));
+    // WebSocket routes
+    #[cfg(feature = "websocket")]
+    let routes = routes.nest(
+        "/ws",
+        routes::websocket_routes(state.clone()).with_state(state.clone()),
+    );
+    let routes = routes
         // OpenAPI documentation routes
-        .nest("/api-docs", routes::openapi_routes())
+        .nest("/api-docs", routes::openapi_routes(&base_path))
         // Static file serving
         .nest("/static", routes::static_routes())
-        // Frontend routes (SPA fallback)
-        .fallback(handlers::spa_fallback)
+        // Frontend files and routes (SPA fallback)
+        .fallback(frontend::serve_frontend);
+
+    // Serve everything under the configured prefix, sending the root there
+    let router = if base_path.is_empty() {
+        routes
+    } else {
+        let target = format!("{}/", base_path);
+        Router::new().nest(&base_path, routes).route(
+            "/",
+            axum::routing::get(move || async move { axum::response::Redirect::temporary(&target) }),
+        )
+    };
+
+    router
         // Add middleware
         .layer(axum::middleware::from_fn(
             middleware::user_context_middleware,
         ))
-        .layer(TraceLayer::new_for_http())
+        .layer(axum::middleware::from_fn_with_state(
+            std::sync::Arc::new(state.config.clone()),
+            csrf::csrf_middleware,
+        ))
+        .layer(TraceLayer::new_for_http().make_span_with(middleware::request_span))
+        // Runs before tracing so the request span carries the request ID
+        .layer(axum::middleware::from_fn(middleware::request_id_middleware))
         .layer(cors)
-        .layer(DefaultBodyLimit::max(10 * 1024 * 1024)) // 10MB max body size
+        // Routes taking uploads set their own, larger limit
+        .layer(DefaultBodyLimit::max(state.config.max_body_size))
         .with_state(state)
 }
 
+/// Configuration file of the server, whose LLM, embedding and retrieval
+/// settings are shared with the CLI
+pub const CONFIG_FILE: &str = "config/wikify.toml";
+
+/// Default maximum size of a request body outside upload routes (2MB)
+pub const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Default maximum size of an uploaded repository archive (100MB)
+pub const DEFAULT_MAX_UPLOAD_SIZE: usize = 100 * 1024 * 1024;
+
+/// Default time limit of a request (30 seconds)
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Default time limit of chat, wiki generation and research requests (10 minutes)
+pub const DEFAULT_LONG_REQUEST_TIMEOUT_SECS: u64 = 600;
+
+/// Default number of repositories indexed at the same time
+pub const DEFAULT_MAX_CONCURRENT_INDEXING: usize = 3;
+
+/// Parse a human readable size such as "10MB", "512KB" or "1048576" into bytes
+pub fn parse_size(value: &str) -> Option<usize> {
+    let value = value.trim().to_uppercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: usize = number.parse().ok()?;
+
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "KB" | "K" => 1024,
+        "MB" | "M" => 1024 * 1024,
+        "GB" | "G" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    number.checked_mul(multiplier)
+}
+
+/// Default time between scheduled usage exports (1 hour)
+pub const DEFAULT_USAGE_EXPORT_INTERVAL_SECS: u64 = 3600;
+
+/// Parse a human readable duration such as "30s", "15m", "6h", "1d" or "3600" into seconds
+pub fn parse_duration_secs(value: &str) -> Option<u64> {
+    let value = value.trim().to_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number.parse().ok()?;
+
+    let multiplier = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+
+    number.checked_mul(multiplier).filter(|&secs| secs > 0)
+}
+
+/// Parse a comma-separated list, ignoring empty entries
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Normalize a path prefix to `/prefix` form, or an empty string for the root
+pub fn normalize_base_path(value: &str) -> String {
+    let trimmed = value.trim().trim_matches('/');
+    if trimmed.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", trimmed)
+    }
+}
+
+/// Parse a daily budget in US dollars, ignoring values that are not positive
+fn parse_budget(value: Option<String>) -> Option<f64> {
+    value
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|&budget| budget > 0.0)
+}
+
 /// Configuration for the web server
 #[derive(Debug, Clone)]
 pub struct WebConfig {
@@ -105,6 +184,22 @@ pub struct WebConfig {
     pub host: String,
     /// Server port
     pub port: u16,
+    /// Port of the gRPC API (needs the `grpc` feature), disabled when unset
+    pub grpc_port: Option<u16>,
+    /// PEM certificate chain to serve HTTPS with (needs the `tls` feature)
+    pub tls_cert_path: Option<String>,
+    /// PEM private key of the certificate
+    pub tls_key_path: Option<String>,
+    /// Domains to obtain a Let's Encrypt certificate for, instead of a certificate file
+    pub acme_domains: Vec<String>,
+    /// Contact email for the ACME account
+    pub acme_email: Option<String>,
+    /// Directory ACME certificates and account keys are cached in
+    pub acme_cache_dir: Option<String>,
+    /// Port answering plain HTTP with redirects to HTTPS, when serving HTTPS
+    pub http_redirect_port: Option<u16>,
+    /// Path prefix the whole app is served under, such as `/wikify` (empty at the root)
+    pub base_path: String,
     /// Enable development mode
     pub dev_mode: bool,
     /// Static files directory
@@ -113,6 +208,63 @@ pub struct WebConfig {
     pub database_url: Option<String>,
     /// Permission mode (open, restricted, local)
     pub permission_mode: Option<String>,
+    /// Maximum size in bytes of a request body, such as a JSON payload
+    pub max_body_size: usize,
+    /// Maximum size in bytes of an uploaded repository archive
+    pub max_upload_size: usize,
+    /// Seconds a request may take before `504 Gateway Timeout` is returned
+    pub request_timeout_secs: u64,
+    /// Seconds chat, wiki generation, research and upload requests may take
+    pub long_request_timeout_secs: u64,
+    /// Maximum number of repositories indexed at the same time
+    pub max_concurrent_indexing: usize,
+    /// Files loaded and split at the same time while indexing one repository
+    /// (None = one per CPU)
+    pub indexing_workers: Option<usize>,
+    /// Megabytes clones, indexes and cached wikis may take before indexing is refused
+    pub max_disk_usage_mb: Option<u64>,
+    /// Daily budget in US dollars for all model usage
+    pub daily_budget: Option<f64>,
+    /// Daily budget in US dollars for each user
+    pub user_daily_budget: Option<f64>,
+    /// Daily budget in US dollars for each repository
+    pub repository_daily_budget: Option<f64>,
+    /// Format of scheduled usage exports (csv or jsonl)
+    pub usage_export_format: Option<String>,
+    /// Directory scheduled usage exports are written to
+    pub usage_export_dir: Option<String>,
+    /// Metering webhook scheduled usage exports are posted to
+    pub usage_export_webhook: Option<String>,
+    /// Bearer token sent to the metering webhook
+    pub usage_export_webhook_token: Option<String>,
+    /// Seconds between scheduled usage exports
+    pub usage_export_interval_secs: u64,
+    /// Seconds between storage garbage collections, disabled when unset
+    pub storage_gc_interval_secs: Option<u64>,
+    /// Seconds an orphaned clone, wiki cache entry or vector index is kept after its last change
+    pub storage_gc_grace_period_secs: Option<u64>,
+    /// Seconds cached wikis are kept, even of existing repositories
+    pub storage_gc_wiki_cache_retention_secs: Option<u64>,
+    /// Identify clients by `X-Forwarded-For`/`X-Real-IP` (only behind a trusted proxy)
+    pub trust_proxy_headers: bool,
+    /// Origins allowed to call the API from a browser; `*` only in development mode.
+    /// Empty for the Vite dev server (`cors::DEFAULT_CORS_ORIGINS`)
+    pub cors_origins: Vec<String>,
+    /// Methods allowed for cross-origin requests, empty for `cors::DEFAULT_CORS_METHODS`
+    pub cors_methods: Vec<String>,
+    /// Whether cross-origin requests may carry credentials (default true)
+    pub cors_allow_credentials: Option<bool>,
+    /// Mark cookies `Secure`; defaults to whether the server serves HTTPS itself,
+    /// so set it behind a proxy terminating TLS
+    pub cookie_secure: Option<bool>,
+    /// Queries per hour allowed to each anonymous client in open mode
+    pub anonymous_queries_per_hour: Option<u32>,
+    /// Repositories each anonymous client may add in open mode
+    pub anonymous_max_repositories: Option<u32>,
+    /// Largest repository, in MB, an anonymous client may add in open mode
+    pub anonymous_max_repository_size_mb: Option<u64>,
+    /// Format of server logs (pretty, compact or json), the plain text format when unset
+    pub log_format: Option<String>,
 }
 
 impl Default for WebConfig {
@@ -120,10 +272,45 @@ impl Default for WebConfig {
         Self {
             host: "127.0.0.1".to_string(),
             port: 8080,
+            grpc_port: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            acme_domains: Vec::new(),
+            acme_email: None,
+            acme_cache_dir: None,
+            http_redirect_port: None,
+            base_path: String::new(),
             dev_mode: false,
             static_dir: None,
             database_url: Some("sqlite:./data/wikify.db".to_string()), // 启用文件 SQLite 数据库
             permission_mode: Some("open".to_string()),                 // 默认开放模式
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            max_upload_size: DEFAULT_MAX_UPLOAD_SIZE,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            long_request_timeout_secs: DEFAULT_LONG_REQUEST_TIMEOUT_SECS,
+            max_concurrent_indexing: DEFAULT_MAX_CONCURRENT_INDEXING,
+            indexing_workers: None,
+            max_disk_usage_mb: None,
+            daily_budget: None,
+            user_daily_budget: None,
+            repository_daily_budget: None,
+            usage_export_format: None,
+            usage_export_dir: None,
+            usage_export_webhook: None,
+            usage_export_webhook_token: None,
+            usage_export_interval_secs: DEFAULT_USAGE_EXPORT_INTERVAL_SECS,
+            storage_gc_interval_secs: None,
+            storage_gc_grace_period_secs: None,
+            storage_gc_wiki_cache_retention_secs: None,
+            trust_proxy_headers: false,
+            cors_origins: Vec::new(),
+            cors_methods: Vec::new(),
+            cors_allow_credentials: None,
+            cookie_secure: None,
+            anonymous_queries_per_hour: None,
+            anonymous_max_repositories: None,
+            anonymous_max_repository_size_mb: None,
+            log_format: None,
         }
     }
 }
@@ -135,7 +322,7 @@ impl WebConfig {
         let mut config = Self::default();
 
         // Try to load from config file
-        if let Ok(file_config) = Self::from_file("config/wikify.toml") {
+        if let Ok(file_config) = Self::from_file(CONFIG_FILE) {
             config = config.merge_with(file_config);
         }
 
@@ -153,13 +340,113 @@ impl WebConfig {
                 .unwrap_or_else(|_| "8080".to_string())
                 .parse()
                 .unwrap_or(8080),
+            grpc_port: std::env::var("WIKIFY_GRPC_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            tls_cert_path: std::env::var("WIKIFY_TLS_CERT").ok(),
+            tls_key_path: std::env::var("WIKIFY_TLS_KEY").ok(),
+            acme_domains: std::env::var("WIKIFY_ACME_DOMAINS")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+            acme_email: std::env::var("WIKIFY_ACME_EMAIL").ok(),
+            acme_cache_dir: std::env::var("WIKIFY_ACME_CACHE_DIR").ok(),
+            http_redirect_port: std::env::var("WIKIFY_HTTP_REDIRECT_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
             dev_mode: std::env::var("WIKIFY_DEV_MODE")
                 .unwrap_or_else(|_| "false".to_string())
                 .parse()
                 .unwrap_or(false),
+            base_path: std::env::var("WIKIFY_BASE_PATH")
+                .map(|v| normalize_base_path(&v))
+                .unwrap_or_default(),
             static_dir: std::env::var("WIKIFY_STATIC_DIR").ok(),
             database_url: std::env::var("DATABASE_URL").ok(),
             permission_mode: std::env::var("WIKIFY_PERMISSION_MODE").ok(),
+            max_body_size: std::env::var("WIKIFY_MAX_BODY_SIZE")
+                .ok()
+                .and_then(|v| parse_size(&v))
+                .filter(|&n| n > 0)
+                .unwrap_or(DEFAULT_MAX_BODY_SIZE),
+            max_upload_size: std::env::var("WIKIFY_MAX_UPLOAD_SIZE")
+                .ok()
+                .and_then(|v| parse_size(&v))
+                .unwrap_or(DEFAULT_MAX_UPLOAD_SIZE),
+            request_timeout_secs: std::env::var("WIKIFY_REQUEST_TIMEOUT")
+                .ok()
+                .and_then(|v| parse_duration_secs(&v))
+                .filter(|&n| n > 0)
+                .unwrap_or(DEFAULT_REQUEST_TIMEOUT_SECS),
+            long_request_timeout_secs: std::env::var("WIKIFY_LONG_REQUEST_TIMEOUT")
+                .ok()
+                .and_then(|v| parse_duration_secs(&v))
+                .filter(|&n| n > 0)
+                .unwrap_or(DEFAULT_LONG_REQUEST_TIMEOUT_SECS),
+            max_concurrent_indexing: std::env::var("WIKIFY_MAX_CONCURRENT_INDEXING")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n| n > 0)
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_INDEXING),
+            indexing_workers: std::env::var("WIKIFY_INDEXING_WORKERS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n| n > 0),
+            max_disk_usage_mb: std::env::var("WIKIFY_MAX_DISK_USAGE_MB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|&n| n > 0),
+            daily_budget: parse_budget(std::env::var("WIKIFY_DAILY_BUDGET").ok()),
+            user_daily_budget: parse_budget(std::env::var("WIKIFY_USER_DAILY_BUDGET").ok()),
+            repository_daily_budget: parse_budget(
+                std::env::var("WIKIFY_REPOSITORY_DAILY_BUDGET").ok(),
+            ),
+            usage_export_format: std::env::var("WIKIFY_USAGE_EXPORT_FORMAT").ok(),
+            usage_export_dir: std::env::var("WIKIFY_USAGE_EXPORT_DIR").ok(),
+            usage_export_webhook: std::env::var("WIKIFY_USAGE_EXPORT_WEBHOOK").ok(),
+            usage_export_webhook_token: std::env::var("WIKIFY_USAGE_EXPORT_WEBHOOK_TOKEN").ok(),
+            usage_export_interval_secs: std::env::var("WIKIFY_USAGE_EXPORT_INTERVAL")
+                .ok()
+                .and_then(|v| parse_duration_secs(&v))
+                .unwrap_or(DEFAULT_USAGE_EXPORT_INTERVAL_SECS),
+            storage_gc_interval_secs: std::env::var("WIKIFY_STORAGE_GC_INTERVAL")
+                .ok()
+                .and_then(|v| parse_duration_secs(&v)),
+            storage_gc_grace_period_secs: std::env::var("WIKIFY_STORAGE_GC_GRACE_PERIOD")
+                .ok()
+                .and_then(|v| parse_duration_secs(&v)),
+            storage_gc_wiki_cache_retention_secs: std::env::var(
+                "WIKIFY_STORAGE_GC_WIKI_CACHE_RETENTION",
+            )
+            .ok()
+            .and_then(|v| parse_duration_secs(&v)),
+            trust_proxy_headers: std::env::var("WIKIFY_TRUST_PROXY_HEADERS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
+            cors_origins: std::env::var("WIKIFY_CORS_ORIGINS")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+            cors_methods: std::env::var("WIKIFY_CORS_METHODS")
+                .map(|v| parse_list(&v))
+                .unwrap_or_default(),
+            cors_allow_credentials: std::env::var("WIKIFY_CORS_ALLOW_CREDENTIALS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            cookie_secure: std::env::var("WIKIFY_COOKIE_SECURE")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            anonymous_queries_per_hour: std::env::var("WIKIFY_ANONYMOUS_QUERIES_PER_HOUR")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            anonymous_max_repositories: std::env::var("WIKIFY_ANONYMOUS_MAX_REPOSITORIES")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            anonymous_max_repository_size_mb: std::env::var(
+                "WIKIFY_ANONYMOUS_MAX_REPOSITORY_SIZE_MB",
+            )
+            .ok()
+            .and_then(|v| v.parse().ok()),
+            log_format: std::env::var("WIKIFY_LOG_FORMAT").ok(),
         }
     }
 
@@ -180,12 +467,92 @@ impl WebConfig {
             if let Some(port) = server.get("port").and_then(|v| v.as_integer()) {
                 config.port = port as u16;
             }
+            if let Some(port) = server.get("grpc_port").and_then(|v| v.as_integer()) {
+                config.grpc_port = Some(port as u16);
+            }
             if let Some(dev_mode) = server.get("dev_mode").and_then(|v| v.as_bool()) {
                 config.dev_mode = dev_mode;
             }
+            if let Some(base_path) = server.get("base_path").and_then(|v| v.as_str()) {
+                config.base_path = normalize_base_path(base_path);
+            }
             if let Some(static_dir) = server.get("static_dir").and_then(|v| v.as_str()) {
                 config.static_dir = Some(static_dir.to_string());
             }
+            if let Some(size) = server
+                .get("max_body_size")
+                .and_then(|v| v.as_str())
+                .and_then(parse_size)
+                .filter(|&n| n > 0)
+            {
+                config.max_body_size = size;
+            }
+            if let Some(size) = server
+                .get("max_upload_size")
+                .and_then(|v| v.as_str())
+                .and_then(parse_size)
+            {
+                config.max_upload_size = size;
+            }
+            if let Some(secs) = server
+                .get("request_timeout")
+                .and_then(|v| v.as_str())
+                .and_then(parse_duration_secs)
+                .filter(|&n| n > 0)
+            {
+                config.request_timeout_secs = secs;
+            }
+            if let Some(secs) = server
+                .get("long_request_timeout")
+                .and_then(|v| v.as_str())
+                .and_then(parse_duration_secs)
+                .filter(|&n| n > 0)
+            {
+                config.long_request_timeout_secs = secs;
+            }
+            if let Some(trust) = server.get("trust_proxy_headers").and_then(|v| v.as_bool()) {
+                config.trust_proxy_headers = trust;
+            }
+        }
+
+        // Parse TLS section
+        if let Some(tls) = toml_value.get("tls") {
+            let string = |key: &str| tls.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            config.tls_cert_path = string("cert");
+            config.tls_key_path = string("key");
+            config.acme_email = string("acme_email");
+            config.acme_cache_dir = string("acme_cache_dir");
+            if let Some(domains) = tls.get("acme_domains").and_then(|v| v.as_array()) {
+                config.acme_domains = domains
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(str::to_string)
+                    .collect();
+            }
+            if let Some(port) = tls.get("http_redirect_port").and_then(|v| v.as_integer()) {
+                config.http_redirect_port = Some(port as u16);
+            }
+        }
+
+        // Parse CORS section
+        if let Some(cors) = toml_value.get("cors") {
+            let list = |key: &str| {
+                cors.get(key).and_then(|v| v.as_array()).map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|v| v.as_str())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+            };
+            config.cors_origins = list("origins").unwrap_or_default();
+            config.cors_methods = list("methods").unwrap_or_default();
+            config.cors_allow_credentials = cors.get("allow_credentials").and_then(|v| v.as_bool());
+        }
+
+        // Parse security section
+        if let Some(security) = toml_value.get("security") {
+            config.cookie_secure = security.get("cookie_secure").and_then(|v| v.as_bool());
         }
 
         // Parse database section
@@ -195,6 +562,65 @@ impl WebConfig {
             }
         }
 
+        // Parse indexing section
+        if let Some(indexing) = toml_value.get("indexing") {
+            if let Some(max) = indexing
+                .get("max_concurrent")
+                .and_then(|v| v.as_integer())
+                .filter(|&n| n > 0)
+            {
+                config.max_concurrent_indexing = max as usize;
+            }
+            if let Some(workers) = indexing
+                .get("workers")
+                .and_then(|v| v.as_integer())
+                .filter(|&n| n > 0)
+            {
+                config.indexing_workers = Some(workers as usize);
+            }
+            if let Some(max) = indexing
+                .get("max_disk_usage_mb")
+                .and_then(|v| v.as_integer())
+                .filter(|&n| n > 0)
+            {
+                config.max_disk_usage_mb = Some(max as u64);
+            }
+        }
+
+        // Parse usage section
+        if let Some(usage) = toml_value.get("usage") {
+            let budget = |key: &str| {
+                usage
+                    .get(key)
+                    .and_then(|v| v.as_float().or_else(|| v.as_integer().map(|n| n as f64)))
+                    .filter(|&budget| budget > 0.0)
+            };
+            config.daily_budget = budget("daily_budget");
+            config.user_daily_budget = budget("user_daily_budget");
+            config.repository_daily_budget = budget("repository_daily_budget");
+
+            let string = |key: &str| usage.get(key).and_then(|v| v.as_str()).map(str::to_string);
+            config.usage_export_format = string("export_format");
+            config.usage_export_dir = string("export_dir");
+            config.usage_export_webhook = string("export_webhook");
+            config.usage_export_webhook_token = string("export_webhook_token");
+            if let Some(secs) = string("export_interval").and_then(|v| parse_duration_secs(&v)) {
+                config.usage_export_interval_secs = secs;
+            }
+        }
+
+        // Parse storage GC section
+        if let Some(gc) = toml_value.get("storage_gc") {
+            let duration = |key: &str| {
+                gc.get(key)
+                    .and_then(|v| v.as_str())
+                    .and_then(parse_duration_secs)
+            };
+            config.storage_gc_interval_secs = duration("interval");
+            config.storage_gc_grace_period_secs = duration("grace_period");
+            config.storage_gc_wiki_cache_retention_secs = duration("wiki_cache_retention");
+        }
+
         // Parse permissions section
         if let Some(permissions) = toml_value.get("permissions") {
             if let Some(mode) = permissions.get("mode").and_then(|v| v.as_str()) {
@@ -202,6 +628,27 @@ impl WebConfig {
             }
         }
 
+        // Parse anonymous limits section
+        if let Some(anonymous) = toml_value.get("anonymous") {
+            let limit = |key: &str| {
+                anonymous
+                    .get(key)
+                    .and_then(|v| v.as_integer())
+                    .filter(|&n| n >= 0)
+            };
+            config.anonymous_queries_per_hour = limit("queries_per_hour").map(|n| n as u32);
+            config.anonymous_max_repositories = limit("max_repositories").map(|n| n as u32);
+            config.anonymous_max_repository_size_mb =
+                limit("max_repository_size_mb").map(|n| n as u64);
+        }
+
+        // Parse logging section
+        if let Some(logging) = toml_value.get("logging") {
+            if let Some(format) = logging.get("log_format").and_then(|v| v.as_str()) {
+                config.log_format = Some(format.to_string());
+            }
+        }
+
         Ok(config)
     }
 
@@ -213,7 +660,31 @@ impl WebConfig {
         if other.port != 8080 || self.port == 8080 {
             self.port = other.port;
         }
+        if other.grpc_port.is_some() {
+            self.grpc_port = other.grpc_port;
+        }
+        if other.tls_cert_path.is_some() {
+            self.tls_cert_path = other.tls_cert_path;
+        }
+        if other.tls_key_path.is_some() {
+            self.tls_key_path = other.tls_key_path;
+        }
+        if !other.acme_domains.is_empty() {
+            self.acme_domains = other.acme_domains;
+        }
+        if other.acme_email.is_some() {
+            self.acme_email = other.acme_email;
+        }
+        if other.acme_cache_dir.is_some() {
+            self.acme_cache_dir = other.acme_cache_dir;
+        }
+        if other.http_redirect_port.is_some() {
+            self.http_redirect_port = other.http_redirect_port;
+        }
         self.dev_mode = other.dev_mode || self.dev_mode;
+        if !other.base_path.is_empty() {
+            self.base_path = other.base_path;
+        }
         if other.static_dir.is_some() {
             self.static_dir = other.static_dir;
         }
@@ -223,14 +694,156 @@ impl WebConfig {
         if other.permission_mode.is_some() {
             self.permission_mode = other.permission_mode;
         }
+        if other.max_body_size != DEFAULT_MAX_BODY_SIZE {
+            self.max_body_size = other.max_body_size;
+        }
+        if other.max_upload_size != DEFAULT_MAX_UPLOAD_SIZE {
+            self.max_upload_size = other.max_upload_size;
+        }
+        if other.request_timeout_secs != DEFAULT_REQUEST_TIMEOUT_SECS {
+            self.request_timeout_secs = other.request_timeout_secs;
+        }
+        if other.long_request_timeout_secs != DEFAULT_LONG_REQUEST_TIMEOUT_SECS {
+            self.long_request_timeout_secs = other.long_request_timeout_secs;
+        }
+        if other.max_concurrent_indexing != DEFAULT_MAX_CONCURRENT_INDEXING {
+            self.max_concurrent_indexing = other.max_concurrent_indexing;
+        }
+        if other.indexing_workers.is_some() {
+            self.indexing_workers = other.indexing_workers;
+        }
+        if other.max_disk_usage_mb.is_some() {
+            self.max_disk_usage_mb = other.max_disk_usage_mb;
+        }
+        if other.daily_budget.is_some() {
+            self.daily_budget = other.daily_budget;
+        }
+        if other.user_daily_budget.is_some() {
+            self.user_daily_budget = other.user_daily_budget;
+        }
+        if other.repository_daily_budget.is_some() {
+            self.repository_daily_budget = other.repository_daily_budget;
+        }
+        if other.usage_export_format.is_some() {
+            self.usage_export_format = other.usage_export_format;
+        }
+        if other.usage_export_dir.is_some() {
+            self.usage_export_dir = other.usage_export_dir;
+        }
+        if other.usage_export_webhook.is_some() {
+            self.usage_export_webhook = other.usage_export_webhook;
+        }
+        if other.usage_export_webhook_token.is_some() {
+            self.usage_export_webhook_token = other.usage_export_webhook_token;
+        }
+        if other.usage_export_interval_secs != DEFAULT_USAGE_EXPORT_INTERVAL_SECS {
+            self.usage_export_interval_secs = other.usage_export_interval_secs;
+        }
+        if other.storage_gc_interval_secs.is_some() {
+            self.storage_gc_interval_secs = other.storage_gc_interval_secs;
+        }
+        if other.storage_gc_grace_period_secs.is_some() {
+            self.storage_gc_grace_period_secs = other.storage_gc_grace_period_secs;
+        }
+        if other.storage_gc_wiki_cache_retention_secs.is_some() {
+            self.storage_gc_wiki_cache_retention_secs = other.storage_gc_wiki_cache_retention_secs;
+        }
+        self.trust_proxy_headers = other.trust_proxy_headers || self.trust_proxy_headers;
+        if !other.cors_origins.is_empty() {
+            self.cors_origins = other.cors_origins;
+        }
+        if !other.cors_methods.is_empty() {
+            self.cors_methods = other.cors_methods;
+        }
+        if other.cors_allow_credentials.is_some() {
+            self.cors_allow_credentials = other.cors_allow_credentials;
+        }
+        if other.cookie_secure.is_some() {
+            self.cookie_secure = other.cookie_secure;
+        }
+        if other.anonymous_queries_per_hour.is_some() {
+            self.anonymous_queries_per_hour = other.anonymous_queries_per_hour;
+        }
+        if other.anonymous_max_repositories.is_some() {
+            self.anonymous_max_repositories = other.anonymous_max_repositories;
+        }
+        if other.anonymous_max_repository_size_mb.is_some() {
+            self.anonymous_max_repository_size_mb = other.anonymous_max_repository_size_mb;
+        }
+        if other.log_format.is_some() {
+            self.log_format = other.log_format;
+        }
         self
     }
 
+    /// Scheduled usage exports, one per configured target
+    ///
+    /// An unknown export format is logged and falls back to CSV.
+    pub fn usage_exports(&self) -> Vec<wikify_applications::UsageExportConfig> {
+        use wikify_applications::{UsageExportConfig, UsageExportFormat, UsageExportTarget};
+
+        let format = match self.usage_export_format.as_deref().map(str::parse) {
+            Some(Ok(format)) => format,
+            Some(Err(e)) => {
+                tracing::warn!("{}, exporting usage as CSV", e);
+                UsageExportFormat::Csv
+            }
+            None => UsageExportFormat::default(),
+        };
+        let interval = std::time::Duration::from_secs(self.usage_export_interval_secs);
+
+        let mut targets = Vec::new();
+        if let Some(dir) = &self.usage_export_dir {
+            targets.push(UsageExportTarget::Directory(dir.into()));
+        }
+        if let Some(url) = &self.usage_export_webhook {
+            targets.push(UsageExportTarget::Webhook {
+                url: url.clone(),
+                token: self.usage_export_webhook_token.clone(),
+            });
+        }
+
+        targets
+            .into_iter()
+            .map(|target| UsageExportConfig {
+                format,
+                target,
+                interval,
+            })
+            .collect()
+    }
+
+    /// Storage GC schedule and retention, with defaults for unset values
+    pub fn storage_gc(&self) -> wikify_applications::StorageGcConfig {
+        let mut gc = wikify_applications::StorageGcConfig::default();
+        if let Some(secs) = self.storage_gc_interval_secs {
+            gc.interval = std::time::Duration::from_secs(secs);
+        }
+        if let Some(secs) = self.storage_gc_grace_period_secs {
+            gc.grace_period = std::time::Duration::from_secs(secs);
+        }
+        if let Some(secs) = self.storage_gc_wiki_cache_retention_secs {
+            gc.wiki_cache_retention = Some(std::time::Duration::from_secs(secs));
+        }
+        gc
+    }
+
     /// Get the server address
     pub fn address(&self) -> String {
         format!("{}:{}", self.host, self.port)
     }
 
+    /// Whether the permission mode requires authenticated users
+    ///
+    /// `restricted` is the application permission mode; `private` and
+    /// `enterprise` are accepted as aliases for it.
+    pub fn requires_auth(&self) -> bool {
+        matches!(
+            self.permission_mode.as_deref(),
+            Some("restricted" | "private" | "enterprise")
+        )
+    }
+
     /// Get the database URL with path expansion
     pub fn database_url(&self) -> String {
         let url = self
@@ -256,6 +869,26 @@ impl WebConfig {
 
         url
     }
+
+    /// Whether the server serves HTTPS, from a certificate file or ACME
+    pub fn tls_enabled(&self) -> bool {
+        self.tls_cert_path.is_some() || !self.acme_domains.is_empty()
+    }
+
+    /// Whether cookies are only sent over HTTPS
+    pub fn secure_cookies(&self) -> bool {
+        self.cookie_secure.unwrap_or_else(|| self.tls_enabled())
+    }
+
+    /// File of the SQLite database, unless the database is in memory
+    pub fn database_path(&self) -> Option<std::path::PathBuf> {
+        let url = self.database_url.as_deref()?;
+        if url.contains(":memory:") {
+            return None;
+        }
+        let path = url.strip_prefix("sqlite:")?;
+        Some(std::path::PathBuf::from(path.trim_start_matches("//")))
+    }
 }
 
 /// Error types for the web server
@@ -287,17 +920,52 @@ pub enum WebError {
 
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
 }
 
 /// Result type for web operations
 pub type WebResult<T> = Result<T, WebError>;
 
 /// Initialize logging for the web server
-pub fn init_logging() {
+///
+/// With `log_format = "json"`, each line is a JSON object carrying the
+/// `request_id`, `repository_id` and `operation` of the spans it was logged
+/// in, for log pipelines such as Loki or ELK.
+pub fn init_logging(config: &WebConfig) {
+    let format = match config.log_format.as_deref().map(str::parse) {
+        Some(Ok(format)) => format,
+        Some(Err(e)) => {
+            eprintln!("⚠️  {}, logging as text", e);
+            wikify_core::LogFormat::Pretty
+        }
+        None => wikify_core::LogFormat::Pretty,
+    };
+    let builder = tracing_subscriber::fmt().with_env_filter(
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| "wikify_web=debug,tower_http=debug,axum=debug".into()),
+    );
+    match format {
+        wikify_core::LogFormat::Json => builder
+            .fmt_fields(tracing_subscriber::fmt::format::JsonFields::new())
+            .event_format(wikify_core::JsonFormat::default())
+            .init(),
+        wikify_core::LogFormat::Compact => builder.compact().init(),
+        wikify_core::LogFormat::Pretty => builder.init(),
+    }
+}
+
+/// Initialize logging to stderr, for when stdout carries a protocol
+pub fn init_stderr_logging() {
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "wikify_web=debug,tower_http=debug,axum=debug".into()),
+                .unwrap_or_else(|_| "wikify_web=info".into()),
         )
+        .with_writer(std::io::stderr)
         .init();
 }
@@ -3,8 +3,10 @@
 //! A web interface for Wikify - AI-powered repository documentation and chat.
 
 use clap::Parser;
+use wikify_applications::PermissionContext;
+use wikify_web::mcp::{self, McpServer};
 use wikify_web::server::WikifyServerBuilder;
-use wikify_web::{init_logging, WebConfig};
+use wikify_web::{init_logging, init_stderr_logging, AppState, WebConfig, WebResult};
 
 /// Wikify Web Server - AI-powered repository documentation and chat interface
 #[derive(Parser)]
@@ -35,6 +37,10 @@ struct Args {
     /// Log level (error, warn, info, debug, trace)
     #[arg(long, default_value = "info")]
     log_level: String,
+
+    /// Serve the Model Context Protocol on stdin/stdout instead of HTTP
+    #[arg(long)]
+    mcp_stdio: bool,
 }
 
 #[tokio::main]
@@ -47,9 +53,6 @@ async fn main() {
         "RUST_LOG",
         format!("wikify_web={},tower_http=debug", args.log_level),
     );
-    init_logging();
-
-    println!("🔧 Starting Wikify Web Server initialization...");
 
     // Load environment variables
     dotenvy::dotenv().ok();
@@ -64,6 +67,20 @@ async fn main() {
     config.static_dir = args.static_dir;
     config.database_url = args.database_url;
 
+    if args.mcp_stdio {
+        // Stdout carries the protocol, so everything else goes to stderr
+        init_stderr_logging();
+        if let Err(e) = serve_mcp_stdio(config).await {
+            eprintln!("❌ MCP server failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    init_logging(&config);
+
+    println!("🔧 Starting Wikify Web Server initialization...");
+
     // Print startup information
     println!("🚀 Starting Wikify Web Server");
     println!("📍 Server: http://{}:{}", config.host, config.port);
@@ -80,8 +97,8 @@ async fn main() {
     // Check for required environment variables
     let mut missing_vars = Vec::new();
 
-    if std::env::var("OPENAI_API_KEY").is_err()
-        && std::env::var("ANTHROPIC_API_KEY").is_err()
+    if wikify_core::secrets::secret("OPENAI_API_KEY").is_none()
+        && wikify_core::secrets::secret("ANTHROPIC_API_KEY").is_none()
         && std::env::var("OLLAMA_HOST").is_err()
     {
         missing_vars.push("LLM API key (OPENAI_API_KEY, ANTHROPIC_API_KEY, or OLLAMA_HOST)");
@@ -101,13 +118,12 @@ async fn main() {
     let mut builder = WikifyServerBuilder::new()
         .host(config.host.clone())
         .port(config.port)
-        .dev_mode(config.dev_mode)
-        .static_dir(
-            config
-                .static_dir
-                .clone()
-                .unwrap_or_else(|| "static".to_string()),
-        );
+        .dev_mode(config.dev_mode);
+
+    // Files in the static directory override the embedded frontend
+    if let Some(static_dir) = &config.static_dir {
+        builder = builder.static_dir(static_dir.clone());
+    }
 
     // Only set database URL if it's provided
     if let Some(db_url) = &config.database_url {
@@ -135,6 +151,14 @@ async fn main() {
     println!("✅ Server shut down gracefully");
 }
 
+/// Serve MCP over stdio as the local user
+async fn serve_mcp_stdio(config: WebConfig) -> WebResult<()> {
+    let state = AppState::new(config).await?;
+    let server = McpServer::new(state.application.clone());
+    mcp::stdio::serve(server, PermissionContext::local()).await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,146 @@
+//! Model Context Protocol (MCP) server
+//!
+//! Lets MCP clients such as Claude Desktop and IDE assistants ask questions
+//! about repositories, search their code and read their wikis as tools.
+//! Messages are JSON-RPC 2.0 and are carried either over stdin/stdout
+//! ([`stdio`]) or over HTTP with server-sent events ([`sse`]).
+
+pub mod sse;
+pub mod stdio;
+mod tools;
+
+pub use sse::McpSessions;
+
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use wikify_applications::{PermissionContext, WikifyApplication};
+
+/// MCP protocol revision implemented by the server
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// JSON-RPC error returned for a request
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+/// Answers MCP messages using the application layer
+#[derive(Clone)]
+pub struct McpServer {
+    application: Arc<WikifyApplication>,
+}
+
+impl McpServer {
+    pub fn new(application: Arc<WikifyApplication>) -> Self {
+        Self { application }
+    }
+
+    /// Handle one JSON-RPC message on behalf of the caller in `context`
+    ///
+    /// Returns the response to send back, or `None` for notifications and
+    /// for responses sent by the client.
+    pub async fn handle_message(
+        &self,
+        context: &PermissionContext,
+        message: &str,
+    ) -> Option<Value> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, format!("Invalid JSON: {}", e)),
+                ))
+            }
+        };
+
+        let id = message.get("id").cloned();
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests we never send need no answer
+            if message.get("result").is_some() || message.get("error").is_some() {
+                return None;
+            }
+            return Some(error_response(
+                id.unwrap_or(Value::Null),
+                RpcError::new(INVALID_REQUEST, "Message has no method"),
+            ));
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let Some(id) = id else {
+            debug!("MCP notification: {}", method);
+            return None;
+        };
+
+        let response = match self.handle_request(context, method, params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => {
+                warn!("MCP request {} failed: {}", method, error.message);
+                error_response(id, error)
+            }
+        };
+        Some(response)
+    }
+
+    async fn handle_request(
+        &self,
+        context: &PermissionContext,
+        method: &str,
+        params: Value,
+    ) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": {
+                    "name": "wikify",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "instructions": "Use list_repositories to find repository IDs, then ask_repository, search_code or get_wiki_page.",
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+            "tools/call" => {
+                let name = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| RpcError::invalid_params("Missing tool name"))?;
+                let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+                tools::call(&self.application, context, name, &arguments).await
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method: {}", method),
+            )),
+        }
+    }
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": error.code, "message": error.message },
+    })
+}
@@ -0,0 +1,177 @@
+//! MCP over HTTP with server-sent events
+//!
+//! A client opens `GET /api/mcp/sse` and receives an `endpoint` event with
+//! the URL to post its messages to. Each posted message is answered with
+//! `202 Accepted` and its response arrives on the event stream as a
+//! `message` event. Sessions belong to the user who opened them.
+
+use super::McpServer;
+use crate::{auth::RequireQuery, AppState};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive},
+        Sse,
+    },
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use utoipa::IntoParams;
+
+/// Path clients post their messages to
+const MESSAGE_PATH: &str = "/api/mcp/message";
+
+/// Open MCP sessions, keyed by session ID
+#[derive(Clone, Default)]
+pub struct McpSessions {
+    sessions: Arc<Mutex<HashMap<String, McpSession>>>,
+}
+
+struct McpSession {
+    user_id: String,
+    sender: mpsc::UnboundedSender<Value>,
+}
+
+impl McpSessions {
+    fn open(&self, user_id: &str) -> (SessionGuard, mpsc::UnboundedReceiver<Value>) {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.sessions.lock().unwrap().insert(
+            session_id.clone(),
+            McpSession {
+                user_id: user_id.to_string(),
+                sender,
+            },
+        );
+        let guard = SessionGuard {
+            sessions: self.clone(),
+            session_id,
+        };
+        (guard, receiver)
+    }
+
+    /// Sender of a session, if it is open and belongs to the user
+    fn sender(
+        &self,
+        session_id: &str,
+        user_id: &str,
+    ) -> Result<mpsc::UnboundedSender<Value>, StatusCode> {
+        let sessions = self.sessions.lock().unwrap();
+        let session = sessions.get(session_id).ok_or(StatusCode::NOT_FOUND)?;
+        if session.user_id != user_id {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        Ok(session.sender.clone())
+    }
+}
+
+/// Closes a session when its event stream is dropped
+struct SessionGuard {
+    sessions: McpSessions,
+    session_id: String,
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        self.sessions
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(&self.session_id);
+        info!("MCP session closed: {}", self.session_id);
+    }
+}
+
+/// Session a posted message belongs to
+#[derive(Deserialize, IntoParams)]
+pub struct McpMessageQuery {
+    /// Session ID from the `endpoint` event
+    pub session_id: String,
+}
+
+/// Open an MCP session
+#[utoipa::path(
+    get,
+    path = "/api/mcp/sse",
+    tag = "MCP",
+    summary = "Open an MCP session",
+    description = "Open a Model Context Protocol session over server-sent events. The first `endpoint` event holds the URL to post JSON-RPC messages to; responses arrive as `message` events.",
+    responses(
+        (status = 200, description = "Event stream opened", content_type = "text/event-stream"),
+        (status = 401, description = "Authentication required")
+    )
+)]
+pub async fn mcp_sse(
+    State(state): State<AppState>,
+    RequireQuery(user): RequireQuery,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (guard, receiver) = state.mcp_sessions.open(&user.id);
+    info!(
+        "MCP session opened: {} (user: {})",
+        guard.session_id, user.id
+    );
+
+    let endpoint = Event::default().event("endpoint").data(format!(
+        "{}{}?session_id={}",
+        state.config.base_path, MESSAGE_PATH, guard.session_id
+    ));
+    let messages = stream::unfold((guard, receiver), |(guard, mut receiver)| async move {
+        let message = receiver.recv().await?;
+        let event = Event::default().event("message").data(message.to_string());
+        Some((Ok(event), (guard, receiver)))
+    });
+
+    let events = stream::once(async { Ok(endpoint) }).chain(messages);
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Post a message to an MCP session
+#[utoipa::path(
+    post,
+    path = "/api/mcp/message",
+    tag = "MCP",
+    summary = "Send an MCP message",
+    description = "Post a JSON-RPC message to an open MCP session. The response is sent on the session's event stream.",
+    params(McpMessageQuery),
+    request_body(content = String, content_type = "application/json"),
+    responses(
+        (status = 202, description = "Message accepted"),
+        (status = 403, description = "Session belongs to another user"),
+        (status = 404, description = "Session not found or closed")
+    )
+)]
+pub async fn mcp_message(
+    State(state): State<AppState>,
+    RequireQuery(user): RequireQuery,
+    Query(query): Query<McpMessageQuery>,
+    body: String,
+) -> StatusCode {
+    let sender = match state.mcp_sessions.sender(&query.session_id, &user.id) {
+        Ok(sender) => sender,
+        Err(status) => {
+            warn!(
+                "Rejected MCP message for session {}: {}",
+                query.session_id, status
+            );
+            return status;
+        }
+    };
+
+    // Answer in the background, as tool calls can take as long as an LLM answer
+    let server = McpServer::new(state.application.clone());
+    let context = user.to_permission_context();
+    tokio::spawn(async move {
+        if let Some(response) = server.handle_message(&context, &body).await {
+            let _ = sender.send(response);
+        }
+    });
+
+    StatusCode::ACCEPTED
+}
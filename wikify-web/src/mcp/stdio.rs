@@ -0,0 +1,36 @@
+//! MCP over stdin and stdout
+//!
+//! Each line on stdin is one JSON-RPC message and each response is written
+//! as one line to stdout, which is how desktop clients launch local MCP
+//! servers. Logs must go to stderr so they do not mix with the responses.
+
+use super::McpServer;
+use std::io;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tracing::info;
+use wikify_applications::PermissionContext;
+
+/// Serve MCP requests from stdin until it is closed
+///
+/// Requests run with `context`, usually [`PermissionContext::local`] since
+/// the client is the local user who started the process.
+pub async fn serve(server: McpServer, context: PermissionContext) -> io::Result<()> {
+    info!("MCP server listening on stdio");
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_message(&context, &line).await {
+            let mut output = response.to_string();
+            output.push('\n');
+            stdout.write_all(output.as_bytes()).await?;
+            stdout.flush().await?;
+        }
+    }
+
+    info!("MCP client closed stdin, shutting down");
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//! Tools offered to MCP clients
+
+use super::RpcError;
+use serde_json::{json, Value};
+use wikify_applications::{
+    CodeSearchResult, PageRequest, PermissionContext, RepositoryFilter, RepositoryQuery,
+    WikifyApplication,
+};
+
+/// Default and maximum number of `search_code` results
+const DEFAULT_SEARCH_RESULTS: usize = 10;
+const MAX_SEARCH_RESULTS: usize = 50;
+
+/// Tool descriptions returned by `tools/list`
+pub(super) fn definitions() -> Value {
+    json!([
+        {
+            "name": "list_repositories",
+            "description": "List the repositories you can query, with their IDs and indexing status.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "ask_repository",
+            "description": "Ask a question about an indexed repository. The answer is generated from the repository's code and documents and lists the files it is based on.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repository_id": { "type": "string", "description": "Repository ID from list_repositories" },
+                    "question": { "type": "string", "description": "Question about the repository" },
+                },
+                "required": ["repository_id", "question"],
+            },
+        },
+        {
+            "name": "search_code",
+            "description": "Find the code and documentation chunks of an indexed repository that are most relevant to a query, without generating an answer.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repository_id": { "type": "string", "description": "Repository ID from list_repositories" },
+                    "query": { "type": "string", "description": "What to look for, in natural language or as identifiers" },
+                    "max_results": { "type": "integer", "minimum": 1, "maximum": MAX_SEARCH_RESULTS, "default": DEFAULT_SEARCH_RESULTS },
+                },
+                "required": ["repository_id", "query"],
+            },
+        },
+        {
+            "name": "get_wiki_page",
+            "description": "Read a page of a repository's generated wiki. Without a page, lists the pages of the wiki.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "repository_id": { "type": "string", "description": "Repository ID from list_repositories" },
+                    "page": { "type": "string", "description": "Page ID or title" },
+                },
+                "required": ["repository_id"],
+            },
+        },
+    ])
+}
+
+/// Run a tool for `tools/call`
+///
+/// Failures of the tool itself are reported in the result with `isError`,
+/// so the model can see them; only malformed calls are JSON-RPC errors.
+pub(super) async fn call(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    name: &str,
+    arguments: &Value,
+) -> Result<Value, RpcError> {
+    let output = match name {
+        "list_repositories" => list_repositories(application, context).await,
+        "ask_repository" => {
+            let repository_id = required_str(arguments, "repository_id")?;
+            let question = required_str(arguments, "question")?;
+            ask_repository(application, context, repository_id, question).await
+        }
+        "search_code" => {
+            let repository_id = required_str(arguments, "repository_id")?;
+            let query = required_str(arguments, "query")?;
+            let max_results = arguments
+                .get("max_results")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_SEARCH_RESULTS, |n| n as usize)
+                .clamp(1, MAX_SEARCH_RESULTS);
+            search_code(application, context, repository_id, query, max_results).await
+        }
+        "get_wiki_page" => {
+            let repository_id = required_str(arguments, "repository_id")?;
+            let page = arguments.get("page").and_then(Value::as_str);
+            get_wiki_page(application, context, repository_id, page).await
+        }
+        _ => return Err(RpcError::invalid_params(format!("Unknown tool: {}", name))),
+    };
+
+    let (text, is_error) = match output {
+        Ok(text) => (text, false),
+        Err(message) => (message, true),
+    };
+    Ok(json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": is_error,
+    }))
+}
+
+fn required_str<'a>(arguments: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    arguments
+        .get(name)
+        .and_then(Value::as_str)
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| RpcError::invalid_params(format!("Missing argument: {}", name)))
+}
+
+async fn list_repositories(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+) -> Result<String, String> {
+    let page = application
+        .list_repositories(context, &RepositoryFilter::default(), PageRequest::all())
+        .await
+        .map_err(|e| e.to_string())?;
+    if page.items.is_empty() {
+        return Ok("No repositories have been added yet.".to_string());
+    }
+
+    let lines: Vec<String> = page
+        .items
+        .iter()
+        .map(|repository| {
+            format!(
+                "- {} ({}): {:?}",
+                repository.id, repository.url, repository.status
+            )
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+async fn ask_repository(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository_id: &str,
+    question: &str,
+) -> Result<String, String> {
+    let query = RepositoryQuery {
+        question: question.to_string(),
+        max_results: None,
+        parameters: None,
+    };
+    let response = application
+        .query_repository(context, repository_id, query)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut text = response.answer;
+    if !response.sources.is_empty() {
+        text.push_str("\n\nSources:");
+        for source in &response.sources {
+            text.push_str(&format!("\n- {}", source));
+        }
+    }
+    Ok(text)
+}
+
+async fn search_code(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository_id: &str,
+    query: &str,
+    max_results: usize,
+) -> Result<String, String> {
+    let query = RepositoryQuery {
+        question: query.to_string(),
+        max_results: Some(max_results),
+        parameters: None,
+    };
+    let results = application
+        .search_repository(context, repository_id, query)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(format_search_results(&results))
+}
+
+/// Search results as markdown, one fenced block per chunk
+fn format_search_results(results: &[CodeSearchResult]) -> String {
+    if results.is_empty() {
+        return "No matching code found.".to_string();
+    }
+
+    results
+        .iter()
+        .map(|result| {
+            let path = result.file_path.as_deref().unwrap_or("unknown file");
+            let language = wikify_wiki::snippets::language_for_path(path);
+            format!(
+                "## {} (score {:.2})\n```{}\n{}\n```",
+                path,
+                result.score,
+                language,
+                result.content.trim_end()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+async fn get_wiki_page(
+    application: &WikifyApplication,
+    context: &PermissionContext,
+    repository_id: &str,
+    page: Option<&str>,
+) -> Result<String, String> {
+    let wiki = application
+        .get_wiki(context, repository_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            format!(
+                "No wiki has been generated for repository {}",
+                repository_id
+            )
+        })?;
+
+    let Some(page) = page else {
+        let mut text = format!("# {}\n\nPages:", wiki.title);
+        for wiki_page in &wiki.pages {
+            text.push_str(&format!("\n- {}: {}", wiki_page.id, wiki_page.title));
+        }
+        return Ok(text);
+    };
+
+    let wiki_page = wiki
+        .pages
+        .iter()
+        .find(|wiki_page| wiki_page.id == page)
+        .or_else(|| {
+            wiki.pages
+                .iter()
+                .find(|wiki_page| wiki_page.title.eq_ignore_ascii_case(page))
+        })
+        .ok_or_else(|| format!("No wiki page {} in repository {}", page, repository_id))?;
+    Ok(format!("# {}\n\n{}", wiki_page.title, wiki_page.content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_definitions_and_search_output() {
+        let definitions = definitions();
+        let names: Vec<&str> = definitions
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "list_repositories",
+                "ask_repository",
+                "search_code",
+                "get_wiki_page"
+            ]
+        );
+
+        let output = format_search_results(&[CodeSearchResult {
+            file_path: Some("src/main.rs".to_string()),
+            content: "fn main() {}\n".to_string(),
+            score: 0.9,
+        }]);
+        assert_eq!(
+            output,
+            "## src/main.rs (score 0.90)\n```rust\nfn main() {}\n```"
+        );
+        assert_eq!(format_search_results(&[]), "No matching code found.");
+    }
+}
@@ -1,15 +1,33 @@
 // Wikify Web Middleware
 // 简单的用户上下文中间件，支持可选的用户隔离
 
-use crate::{auth::jwt::JwtService, auth::User, AppState};
+use crate::{
+    auth::jwt::{JwtService, TokenType},
+    auth::User,
+    AppState,
+};
 use axum::{
+    body::Body,
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Json, Response},
 };
 use serde::{Deserialize, Serialize};
-use tracing::{debug, warn};
+use std::time::Duration;
+use tracing::{debug, warn, Span};
+
+/// Header carrying the ID of a request
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request ID accepted from a client
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Largest error body the request ID is added to
+const MAX_ERROR_BODY_SIZE: usize = 1024 * 1024;
 
 /// 用户上下文信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -144,11 +162,151 @@ pub fn get_user_context(request: &Request) -> UserContext {
         .unwrap_or_default()
 }
 
+/// ID of the request being handled, stored in the request extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// Reuse the `X-Request-Id` sent by the client or a proxy, or generate one
+    ///
+    /// IDs that are too long or contain unusual characters are replaced, so
+    /// they are safe to log and echo back.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LENGTH
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+            })
+            .map(|id| Self(id.to_string()))
+            .unwrap_or_else(|| Self(uuid::Uuid::new_v4().to_string()))
+    }
+}
+
+/// Assign every request an ID and return it in the `X-Request-Id` header
+///
+/// Error responses with a JSON object body also get a `request_id` field,
+/// so a failed call can be matched with the server logs.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = RequestId::from_headers(request.headers());
+    request.extensions_mut().insert(request_id.clone());
+
+    let mut response = next.run(request).await;
+    if response.status().is_client_error() || response.status().is_server_error() {
+        response = add_request_id_to_error(response, &request_id.0).await;
+    }
+    if let Ok(value) = HeaderValue::from_str(&request_id.0) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Answer `504 Gateway Timeout` when a handler takes longer than the limit
+///
+/// Only the time until the response starts counts, so streamed responses
+/// and WebSocket connections are not cut off.
+pub async fn timeout_middleware(
+    State(limit): State<Duration>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!(
+                "{} {} timed out after {} seconds",
+                method,
+                path,
+                limit.as_secs()
+            );
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(serde_json::json!({
+                    "error": "timeout",
+                    "message": format!("Request took longer than {} seconds", limit.as_secs()),
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Tracing span of a request, tagged with its ID
+pub fn request_span(request: &Request) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|request_id| request_id.0.as_str())
+        .unwrap_or_default();
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %request_id,
+    )
+}
+
+/// Add a `request_id` field to a JSON object error body
+async fn add_request_id_to_error(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_SIZE).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Failed to read error response body: {}", e);
+            parts.headers.remove(CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut object)) => {
+            object.insert("request_id".to_string(), request_id.into());
+            serde_json::Value::Object(object).to_string().into()
+        }
+        _ => bytes,
+    };
+    parts.headers.remove(CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum::http::{HeaderMap, HeaderName, HeaderValue};
 
+    #[test]
+    fn test_request_id_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderValue::from_static("req-123"),
+        );
+        assert_eq!(RequestId::from_headers(&headers).0, "req-123");
+
+        headers.insert(
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderValue::from_static("bad id"),
+        );
+        let generated = RequestId::from_headers(&headers);
+        assert_ne!(generated.0, "bad id");
+        assert!(uuid::Uuid::parse_str(&generated.0).is_ok());
+    }
+
     #[test]
     fn test_default_user_context() {
         let headers = HeaderMap::new();
@@ -207,6 +365,32 @@ mod tests {
 
         assert_eq!(user_id, None);
     }
+
+    #[tokio::test]
+    async fn test_slow_handlers_time_out() {
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "done"
+                }),
+            )
+            .route("/fast", get(|| async { "done" }))
+            .layer(axum::middleware::from_fn_with_state(
+                Duration::from_millis(20),
+                timeout_middleware,
+            ));
+
+        let request = |uri| Request::get(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request("/slow")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let response = app.oneshot(request("/fast")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
 
 /// Authentication middleware that requires valid JWT token or API key
@@ -228,39 +412,46 @@ pub async fn auth_middleware(
         return Ok(next.run(request).await);
     }
 
-    let headers = request.headers();
-
     // Extract Authorization header
-    let auth_header = headers
+    let auth_header = request
+        .headers()
         .get("Authorization")
         .and_then(|header| header.to_str().ok());
 
+    let user = authenticate(&state, auth_header).await?;
+    request.extensions_mut().insert(user);
+    Ok(next.run(request).await)
+}
+
+/// User identified by an `Authorization` header value
+///
+/// Accepts `Bearer <access token>`, `Bearer <API key>` and `ApiKey <API key>`.
+pub async fn authenticate(state: &AppState, auth_header: Option<&str>) -> Result<User, StatusCode> {
     match auth_header {
         Some(auth_str) if auth_str.starts_with("Bearer ") => {
             let token = &auth_str[7..]; // Remove "Bearer " prefix
 
             // Try JWT token first
             match JwtService::verify_token(token) {
+                Ok(claims) if claims.token_type != TokenType::Access => {
+                    debug!("Refresh token used for authentication: {}", claims.sub);
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
                 Ok(claims) => {
                     debug!("Valid JWT token for user: {}", claims.sub);
 
                     // Try to get user from user service
-                    match state.user_service.get_user_by_id(&claims.sub).await {
-                        Some(user_data) => {
-                            // Convert to our User type and add to request extensions
-                            let user = crate::auth::User::new(
-                                user_data.id.clone(),
-                                user_data.display_name.clone(),
-                                user_data.permissions.clone(),
-                            );
-                            request.extensions_mut().insert(user);
-                            return Ok(next.run(request).await);
-                        }
+                    return match state.user_service.get_user_by_id(&claims.sub).await {
+                        Some(user_data) => Ok(User::new(
+                            user_data.id.clone(),
+                            user_data.display_name.clone(),
+                            user_data.permissions.clone(),
+                        )),
                         None => {
                             warn!("User not found for valid token: {}", claims.sub);
-                            return Err(StatusCode::UNAUTHORIZED);
+                            Err(StatusCode::UNAUTHORIZED)
                         }
-                    }
+                    };
                 }
                 Err(_) => {
                     // JWT validation failed, try as API key
@@ -268,62 +459,11 @@ pub async fn auth_middleware(
                 }
             }
 
-            // Try API key authentication
-            match state.api_key_service.authenticate_api_key(token).await {
-                Ok(Some(user_data)) => {
-                    debug!(
-                        "Valid API key authentication for: {:?}",
-                        user_data.display_name
-                    );
-
-                    // Convert to our User type and add to request extensions
-                    let user = crate::auth::User::new(
-                        user_data.id.clone(),
-                        user_data.display_name.clone(),
-                        user_data.permissions.clone(),
-                    );
-                    request.extensions_mut().insert(user);
-                    Ok(next.run(request).await)
-                }
-                Ok(None) => {
-                    debug!("API key not found");
-                    Err(StatusCode::UNAUTHORIZED)
-                }
-                Err(e) => {
-                    debug!("API key authentication failed: {}", e);
-                    Err(StatusCode::UNAUTHORIZED)
-                }
-            }
+            authenticate_api_key(state, token).await
         }
         Some(auth_str) if auth_str.starts_with("ApiKey ") => {
             let api_key = &auth_str[7..]; // Remove "ApiKey " prefix
-
-            // Try API key authentication
-            match state.api_key_service.authenticate_api_key(api_key).await {
-                Ok(Some(user_data)) => {
-                    debug!(
-                        "Valid API key authentication for: {:?}",
-                        user_data.display_name
-                    );
-
-                    // Convert to our User type and add to request extensions
-                    let user = crate::auth::User::new(
-                        user_data.id.clone(),
-                        user_data.display_name.clone(),
-                        user_data.permissions.clone(),
-                    );
-                    request.extensions_mut().insert(user);
-                    Ok(next.run(request).await)
-                }
-                Ok(None) => {
-                    debug!("API key not found");
-                    Err(StatusCode::UNAUTHORIZED)
-                }
-                Err(e) => {
-                    debug!("API key authentication failed: {}", e);
-                    Err(StatusCode::UNAUTHORIZED)
-                }
-            }
+            authenticate_api_key(state, api_key).await
         }
         _ => {
             debug!("Missing or invalid Authorization header");
@@ -331,3 +471,27 @@ pub async fn auth_middleware(
         }
     }
 }
+
+async fn authenticate_api_key(state: &AppState, api_key: &str) -> Result<User, StatusCode> {
+    match state.api_key_service.authenticate_api_key(api_key).await {
+        Ok(Some(user_data)) => {
+            debug!(
+                "Valid API key authentication for: {:?}",
+                user_data.display_name
+            );
+            Ok(User::new(
+                user_data.id.clone(),
+                user_data.display_name.clone(),
+                user_data.permissions.clone(),
+            ))
+        }
+        Ok(None) => {
+            debug!("API key not found");
+            Err(StatusCode::UNAUTHORIZED)
+        }
+        Err(e) => {
+            debug!("API key authentication failed: {}", e);
+            Err(StatusCode::UNAUTHORIZED)
+        }
+    }
+}
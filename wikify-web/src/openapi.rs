@@ -3,20 +3,54 @@
 //! This module defines the complete OpenAPI specification for the Wikify API.
 
 use utoipa::{
-    openapi::security::{ApiKey, ApiKeyValue, SecurityScheme},
+    openapi::{
+        security::{ApiKey, ApiKeyValue, SecurityScheme},
+        Server,
+    },
     Modify, OpenApi,
 };
 
+#[cfg(feature = "research")]
+use crate::handlers::types::{
+    ResearchActionResponse, ResearchHistoryResponse, ResearchIterationResponse,
+    ResearchLimitsResponse, ResearchProgressResponse, ResearchRecordResponse,
+    ResearchResultResponse, ResearchSessionListResponse, ResearchSessionSummary,
+    ResearchStatisticsResponse, ResearchTemplateBody, ResearchTemplateListResponse,
+    ResearchTemplateQuestionBody, ResearchTemplateResponse, StartResearchFromTemplateRequest,
+    StartResearchRequest, StartResearchResponse,
+};
 use crate::{
     auth::{
-        handlers::{AuthFeatures, AuthStatusResponse},
+        api_keys::{ApiKeyResponse, CreateApiKeyRequest},
+        handlers::{
+            ApiKeyCreatedResponse, ApiKeyListResponse, AuthFeatures, AuthStatusResponse,
+            CurrentUserResponse, LogoutResponse,
+        },
+        jwt::TokenPair,
+        oauth::OAuthProvidersResponse,
         users::{AuthResponse, LoginRequest, RefreshRequest, RegisterRequest},
     },
     handlers::types::{
-        ChatQueryRequest, ChatQueryResponse, DeleteRepositoryResponse, GenerateWikiRequest,
-        GenerateWikiResponse, HealthResponse, InitializeRepositoryRequest,
-        InitializeRepositoryResponse, ResearchProgressResponse, SourceDocument,
-        StartResearchFromTemplateRequest, StartResearchRequest, WikiGenerationConfig,
+        BatchItemStatus, BatchRepositoryItem, BatchRepositoryRequest, BatchRepositoryResponse,
+        ChatFeedbackEntry, ChatFeedbackListResponse, ChatFeedbackRequest, ChatFeedbackResponse,
+        ChatQueryRequest, ChatQueryResponse, ChatSessionListResponse, ChatSessionMessage,
+        ChatSessionMessageRequest, ChatSessionResponse, ChatSessionSummaryResponse,
+        CreateChatSessionRequest, CreateOrganizationRequest, CreateWebhookRequest,
+        DeleteRepositoryResponse, EditWikiPageRequest, ExportWikiRequest, FeedbackRating,
+        FileContentResponse, FileTreeNode, FileTreeResponse, GenerateWikiRequest,
+        GenerateWikiResponse, GetFileContentRequest, GetFileTreeRequest, GetReadmeRequest,
+        HealthResponse, IndexingJobResponse, IndexingJobsResponse, InitializeRepositoryRequest,
+        InitializeRepositoryResponse, MessageResponse, OrganizationListResponse,
+        OrganizationResponse, QueryHistoryEntry, QueryHistoryResponse, ReadinessResponse,
+        ReadmeResponse, RegenerateAnswerRequest, RegenerateWikiPageRequest,
+        RenameChatSessionRequest, RepositoryFileContentResponse, RepositoryFileInfo,
+        RepositoryFileTreeResponse, RepositoryInfoResponse, RepositoryListResponse,
+        RepositorySharingResponse, RepositorySummaryResponse, ServerConfigResponse,
+        SetOrganizationMemberRequest, ShareRepositoryRequest, SourceDocument, StorageGcRequest,
+        UpdateWebhookRequest, UploadRepositoryForm, WebhookCreatedResponse,
+        WebhookDeliveryListResponse, WebhookListResponse, WebhookResponse, WikiExportJobResponse,
+        WikiGenerationConfig, WikiTemplateBody, WikiTemplatePageBody, WikiTemplateSectionBody,
+        WorkspaceGcRequest, WorkspaceGcResponse,
     },
 };
 
@@ -42,109 +76,327 @@ use crate::{
     paths(
         // Health endpoints
         crate::handlers::health_check,
+        crate::handlers::liveness_check,
+        crate::handlers::readiness_check,
 
         // Authentication endpoints
         crate::auth::handlers::get_auth_status,
         crate::auth::handlers::register_user,
         crate::auth::handlers::login_user,
         crate::auth::handlers::refresh_token,
+        crate::auth::handlers::list_oauth_providers,
+        crate::auth::handlers::start_oauth_login,
+        crate::auth::handlers::oauth_callback,
+        crate::auth::handlers::start_oidc_login,
+        crate::auth::handlers::oidc_callback,
+        crate::auth::handlers::revoke_oauth_token,
+        crate::auth::handlers::get_current_user,
+        crate::auth::handlers::logout_user,
+        crate::auth::handlers::create_api_key,
+        crate::auth::handlers::list_api_keys,
+        crate::auth::handlers::delete_api_key,
 
         // Repository management
         crate::handlers::initialize_repository,
+        crate::handlers::batch_initialize_repositories,
+        crate::handlers::upload_repository,
         crate::handlers::list_repositories,
         crate::handlers::get_repository_info,
+        crate::handlers::get_repository_metrics,
         crate::handlers::delete_repository,
         crate::handlers::reindex_repository,
+        crate::handlers::cancel_indexing,
+        crate::handlers::share_repository,
+        crate::handlers::list_indexing_jobs,
+
+        // Organizations
+        crate::handlers::list_organizations,
+        crate::handlers::create_organization,
+        crate::handlers::get_organization,
+        crate::handlers::delete_organization,
+        crate::handlers::set_organization_member,
+        crate::handlers::remove_organization_member,
+
+        // Webhooks
+        crate::handlers::list_webhooks,
+        crate::handlers::create_webhook,
+        crate::handlers::get_webhook,
+        crate::handlers::update_webhook,
+        crate::handlers::delete_webhook,
+        crate::handlers::list_webhook_deliveries,
+        crate::handlers::retry_webhook_delivery,
 
         // Chat endpoints
         crate::handlers::chat_query,
         crate::handlers::chat_stream,
+        crate::handlers::create_chat_session,
+        crate::handlers::list_chat_sessions,
+        crate::handlers::get_chat_session,
+        crate::handlers::rename_chat_session,
+        crate::handlers::continue_chat_session,
+        crate::handlers::regenerate_answer,
+        crate::handlers::delete_chat_session,
 
         // Wiki generation
         crate::handlers::generate_wiki,
+        crate::handlers::cancel_wiki_generation,
         crate::handlers::get_wiki,
+        crate::handlers::list_wiki_versions,
+        crate::handlers::get_wiki_version,
+        crate::handlers::diff_wiki_versions,
         crate::handlers::export_wiki,
+        crate::handlers::download_wiki_export,
+        crate::handlers::regenerate_wiki_page,
+        crate::handlers::edit_wiki_page,
+        crate::handlers::get_wiki_template,
+        crate::handlers::set_wiki_template,
+        crate::handlers::delete_wiki_template,
+        crate::handlers::purge_wiki_cache,
 
         // Configuration
         crate::handlers::get_config,
+        crate::handlers::update_config,
 
-        // Research endpoints
-        crate::handlers::start_research,
-        crate::handlers::research_iteration,
-        crate::handlers::get_research_progress,
-        crate::handlers::get_research_progress_by_id,
-        crate::handlers::stop_research,
+        // Administration
+        crate::handlers::collect_workspace_garbage,
+        crate::handlers::collect_storage_garbage,
+        crate::handlers::get_storage_usage,
+        crate::handlers::get_usage,
+        crate::handlers::export_usage,
+        crate::handlers::get_stats,
 
-        // Research template endpoints
-        crate::handlers::list_research_templates,
-        crate::handlers::get_research_template,
-        crate::handlers::list_templates_by_category,
-        crate::handlers::start_research_from_template,
+        // MCP endpoints
+        crate::mcp::sse::mcp_sse,
+        crate::mcp::sse::mcp_message,
 
-        // Research history endpoints (TODO: Add utoipa::path annotations)
-        // crate::handlers::get_research_history,
-        // crate::handlers::get_research_record,
-        // crate::handlers::delete_research_record,
-        // crate::handlers::get_research_statistics,
+        // WebSocket endpoints (conditionally included)
+        #[cfg(feature = "websocket")]
+        crate::websocket::unified_handler,
+        #[cfg(feature = "websocket")]
+        crate::websocket::wiki_handler,
+        #[cfg(feature = "websocket")]
+        crate::websocket::index_handler,
+        #[cfg(feature = "websocket")]
+        crate::websocket::chat::chat_handler,
 
         // File operations endpoints
+        crate::handlers::get_repository_files,
+        crate::handlers::get_repository_file,
         crate::handlers::get_file_tree,
         crate::handlers::get_file_content,
         crate::handlers::get_readme,
 
         // SQLite-only endpoints (conditionally included)
         #[cfg(feature = "sqlite")]
-        crate::handlers::get_repositories,
-        #[cfg(feature = "sqlite")]
         crate::handlers::get_query_history,
+        #[cfg(feature = "sqlite")]
+        crate::handlers::submit_chat_feedback,
+        #[cfg(feature = "sqlite")]
+        crate::handlers::list_chat_feedback,
     ),
     components(
         schemas(
             // Authentication schemas
             AuthStatusResponse,
+            OAuthProvidersResponse,
             AuthFeatures,
             RegisterRequest,
             LoginRequest,
             RefreshRequest,
             AuthResponse,
+            TokenPair,
+            CurrentUserResponse,
+            LogoutResponse,
+            CreateApiKeyRequest,
+            ApiKeyResponse,
+            ApiKeyCreatedResponse,
+            ApiKeyListResponse,
             // Other schemas
             HealthResponse,
+            ReadinessResponse,
+            MessageResponse,
+            ServerConfigResponse,
+            wikify_applications::DependencyCheck,
             InitializeRepositoryRequest,
+            BatchRepositoryRequest,
+            BatchRepositoryResponse,
+            BatchRepositoryItem,
+            BatchItemStatus,
             InitializeRepositoryResponse,
+            UploadRepositoryForm,
             DeleteRepositoryResponse,
+            RepositorySummaryResponse,
+            RepositoryListResponse,
+            RepositoryInfoResponse,
+            IndexingJobsResponse,
+            IndexingJobResponse,
+            ShareRepositoryRequest,
+            RepositorySharingResponse,
+            wikify_applications::RepositoryVisibility,
+            wikify_applications::ShareGrant,
+            CreateOrganizationRequest,
+            SetOrganizationMemberRequest,
+            OrganizationResponse,
+            OrganizationListResponse,
+            wikify_applications::OrgMember,
+            wikify_applications::OrgRole,
+            CreateWebhookRequest,
+            UpdateWebhookRequest,
+            WebhookResponse,
+            WebhookCreatedResponse,
+            WebhookListResponse,
+            WebhookDeliveryListResponse,
+            wikify_applications::WebhookEventKind,
+            wikify_applications::WebhookDelivery,
+            wikify_applications::WebhookEvent,
             ChatQueryRequest,
             ChatQueryResponse,
+            ChatSessionSummaryResponse,
+            ChatSessionListResponse,
+            ChatSessionMessage,
+            ChatSessionResponse,
+            CreateChatSessionRequest,
+            RenameChatSessionRequest,
+            ChatSessionMessageRequest,
+            RegenerateAnswerRequest,
+            FeedbackRating,
+            ChatFeedbackRequest,
+            ChatFeedbackResponse,
+            ChatFeedbackEntry,
+            ChatFeedbackListResponse,
+            QueryHistoryEntry,
+            QueryHistoryResponse,
             SourceDocument,
+            ExportWikiRequest,
+            WikiExportJobResponse,
             GenerateWikiRequest,
             GenerateWikiResponse,
+            RegenerateWikiPageRequest,
+            EditWikiPageRequest,
+            WikiTemplateBody,
+            WikiTemplateSectionBody,
+            WikiTemplatePageBody,
             WikiGenerationConfig,
-            StartResearchRequest,
-            ResearchProgressResponse,
-            StartResearchFromTemplateRequest,
-            // File operation schemas (TODO: Add when properly imported)
-            // GetFileTreeRequest,
-            // FileTreeResponse,
-            // RepositoryFileInfo,
-            // GetFileContentRequest,
-            // FileContentResponse,
-            // GetReadmeRequest,
-            // ReadmeResponse,
+            WorkspaceGcRequest,
+            WorkspaceGcResponse,
+            StorageGcRequest,
+            wikify_applications::StorageGcReport,
+            wikify_applications::StorageSweep,
+            wikify_applications::StorageUsageReport,
+            wikify_applications::RepositoryStorageUsage,
+            wikify_applications::RepositoryDiskUsage,
+            wikify_applications::UsageReport,
+            wikify_applications::SystemStats,
+            wikify_applications::RepositoryStatusCounts,
+            wikify_applications::IndexStats,
+            wikify_applications::RepositoryIndexSize,
+            wikify_applications::OperationStats,
+            wikify_applications::RepositoryOperationStats,
+            wikify_applications::StorageStats,
+            wikify_applications::UsageAggregate,
+            wikify_applications::UsageGroupBy,
+            wikify_applications::BudgetWarning,
+            wikify_applications::UsageExportFormat,
+            wikify_applications::RepositorySortField,
+            wikify_applications::SortOrder,
+            // File operation schemas
+            GetFileTreeRequest,
+            FileTreeResponse,
+            RepositoryFileInfo,
+            RepositoryFileTreeResponse,
+            FileTreeNode,
+            RepositoryFileContentResponse,
+            GetFileContentRequest,
+            FileContentResponse,
+            GetReadmeRequest,
+            ReadmeResponse,
         )
     ),
     tags(
         (name = "Health", description = "Health check endpoints"),
         (name = "Authentication", description = "User authentication and authorization"),
         (name = "Repository", description = "Repository management operations"),
+        (name = "Organization", description = "Organizations and member roles"),
+        (name = "Webhook", description = "Outbound webhooks for lifecycle events"),
         (name = "Chat", description = "AI chat and query operations"),
         (name = "Wiki", description = "Wiki generation and management"),
         (name = "Research", description = "Deep research and investigation operations"),
+        (name = "Files", description = "Repository file browsing"),
+        (name = "MCP", description = "Model Context Protocol server for AI agents"),
+        (name = "WebSocket", description = "Real-time progress and streaming chat over WebSocket"),
         (name = "Session", description = "Session management operations"),
         (name = "Configuration", description = "Server configuration operations"),
+        (name = "Admin", description = "Server administration operations"),
     ),
-    modifiers(&SecurityAddon)
+    modifiers(&FeatureAddon, &SecurityAddon)
 )]
 pub struct ApiDoc;
 
+/// Research endpoints, only built with the research feature
+#[cfg(feature = "research")]
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        // Research endpoints
+        crate::handlers::start_research,
+        crate::handlers::research_iteration,
+        crate::handlers::get_research_progress,
+        crate::handlers::get_research_progress_by_id,
+        crate::handlers::stop_research,
+        crate::handlers::list_research_sessions,
+        crate::handlers::start_deep_research_stream,
+        crate::handlers::get_research_result,
+        crate::handlers::research_progress_events,
+
+        // Research template endpoints
+        crate::handlers::list_research_templates,
+        crate::handlers::get_research_template,
+        crate::handlers::list_templates_by_category,
+        crate::handlers::start_research_from_template,
+        crate::handlers::create_research_template,
+        crate::handlers::update_research_template,
+        crate::handlers::delete_research_template,
+
+        // Research history endpoints
+        crate::handlers::get_research_history,
+        crate::handlers::get_research_record,
+        crate::handlers::delete_research_record,
+        crate::handlers::get_research_statistics,
+    ),
+    components(
+        schemas(
+            StartResearchRequest,
+            StartResearchResponse,
+            ResearchProgressResponse,
+            ResearchActionResponse,
+            ResearchSessionSummary,
+            ResearchSessionListResponse,
+            ResearchTemplateResponse,
+            ResearchTemplateListResponse,
+            ResearchRecordResponse,
+            ResearchHistoryResponse,
+            ResearchStatisticsResponse,
+            ResearchIterationResponse,
+            ResearchLimitsResponse,
+            ResearchResultResponse,
+            StartResearchFromTemplateRequest,
+            ResearchTemplateBody,
+            ResearchTemplateQuestionBody,
+        )
+    )
+)]
+struct ResearchApiDoc;
+
+/// Adds the endpoints of enabled optional features
+pub struct FeatureAddon;
+
+impl Modify for FeatureAddon {
+    fn modify(&self, _openapi: &mut utoipa::openapi::OpenApi) {
+        #[cfg(feature = "research")]
+        _openapi.merge(ResearchApiDoc::openapi());
+    }
+}
+
 /// Security configuration for the API
 pub struct SecurityAddon;
 
@@ -159,6 +411,25 @@ impl Modify for SecurityAddon {
     }
 }
 
+/// OpenAPI specification of an app served under `base_path`
+///
+/// Server URLs get the prefix appended, and a relative server comes first so
+/// "Try it out" in Swagger UI calls the host the docs were loaded from.
+pub fn openapi_with_base_path(base_path: &str) -> utoipa::openapi::OpenApi {
+    let mut openapi = ApiDoc::openapi();
+    if base_path.is_empty() {
+        return openapi;
+    }
+
+    let mut servers = vec![Server::new(base_path)];
+    for mut server in openapi.servers.take().unwrap_or_default() {
+        server.url = format!("{}{}", server.url.trim_end_matches('/'), base_path);
+        servers.push(server);
+    }
+    openapi.servers = Some(servers);
+    openapi
+}
+
 /// Get the OpenAPI specification as JSON
 pub fn get_openapi_json() -> String {
     ApiDoc::openapi().to_pretty_json().unwrap()
@@ -181,6 +452,84 @@ mod tests {
         assert!(!openapi.paths.paths.is_empty());
     }
 
+    /// `(method, path)` of every route declared in `routes.rs`
+    ///
+    /// Reads the route definitions instead of the built router, which axum
+    /// cannot list, so new routes are caught without starting a server.
+    fn declared_routes() -> Vec<(&'static str, String)> {
+        let source = include_str!("routes.rs");
+        let mut routes = Vec::new();
+        for (function, prefix) in [("fn api_routes", "/api"), ("fn websocket_routes", "/ws")] {
+            let body = &source[source.find(function).unwrap()..];
+            let body = &body[..body.find("\n}\n").unwrap()];
+            for route in body.split(".route(").skip(1) {
+                let path = route.split('"').nth(1).unwrap();
+                let path = format!("{}{}", prefix, path.trim_end_matches('/'));
+                for method in ["get", "post", "put", "delete", "patch"] {
+                    let call = format!("{}(", method);
+                    let called = route.match_indices(&call).any(|(index, _)| {
+                        let before = route[..index].chars().next_back();
+                        !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+                    });
+                    if called {
+                        routes.push((method, path.clone()));
+                    }
+                }
+            }
+        }
+        routes
+    }
+
+    // Routes of optional features are only in the spec when they are enabled
+    #[cfg(all(feature = "sqlite", feature = "research", feature = "websocket"))]
+    #[test]
+    fn test_every_route_is_documented() {
+        let openapi = ApiDoc::openapi();
+        let routes = declared_routes();
+        assert!(routes.len() > 50, "route parsing found too few routes");
+
+        let missing: Vec<String> = routes
+            .iter()
+            .filter(|(method, path)| {
+                let Some(item) = openapi.paths.paths.get(path) else {
+                    return true;
+                };
+                let operation = match *method {
+                    "get" => &item.get,
+                    "post" => &item.post,
+                    "put" => &item.put,
+                    "delete" => &item.delete,
+                    _ => &item.patch,
+                };
+                operation.is_none()
+            })
+            .map(|(method, path)| format!("{} {}", method.to_uppercase(), path))
+            .collect();
+        assert!(
+            missing.is_empty(),
+            "routes missing from the OpenAPI spec: {:?}",
+            missing
+        );
+    }
+
+    #[test]
+    fn test_openapi_with_base_path() {
+        let openapi = openapi_with_base_path("/wikify");
+        let urls: Vec<_> = openapi
+            .servers
+            .unwrap()
+            .into_iter()
+            .map(|server| server.url)
+            .collect();
+        assert_eq!(urls[0], "/wikify");
+        assert!(urls.contains(&"http://localhost:8080/wikify".to_string()));
+
+        assert_eq!(
+            openapi_with_base_path("").servers,
+            ApiDoc::openapi().servers
+        );
+    }
+
     #[test]
     fn test_openapi_json() {
         let json = get_openapi_json();
@@ -2,46 +2,113 @@
 //!
 //! This module defines all the routes for the web application.
 
-use crate::{auth, handlers, openapi, websocket, AppState};
+#[cfg(feature = "websocket")]
+use crate::websocket;
+use crate::{auth, handlers, openapi, AppState};
 use axum::{
+    extract::{DefaultBodyLimit, State},
     response::Json,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
+use std::time::Duration;
 use tower_http::services::ServeDir;
-use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
+/// Time limit of health checks and other quick endpoints
+const HEALTH_TIMEOUT_SECS: u64 = 5;
+
 /// Create API routes
 pub fn api_routes(_state: AppState) -> Router<AppState> {
-    // Public routes (no authentication required)
-    let public_routes = Router::new()
+    let config = &_state.config;
+    let timeout = |seconds: u64| {
+        axum::middleware::from_fn_with_state(
+            Duration::from_secs(seconds),
+            crate::middleware::timeout_middleware,
+        )
+    };
+
+    // Cheap endpoints polled by load balancers and the frontend
+    let quick_routes = Router::new()
         // Health check
         .route("/health", get(handlers::health_check))
+        .route("/health/live", get(handlers::liveness_check))
+        .route("/health/ready", get(handlers::readiness_check))
+        // Configuration (public read)
+        .route("/config", get(handlers::get_config))
+        .route_layer(timeout(HEALTH_TIMEOUT_SECS));
+
+    // Public routes (no authentication required)
+    let public_routes = Router::new()
         // Authentication endpoints
         .route("/auth/status", get(auth::handlers::get_auth_status))
         .route("/auth/register", post(auth::handlers::register_user))
         .route("/auth/login", post(auth::handlers::login_user))
         .route("/auth/refresh", post(auth::handlers::refresh_token))
-        // Research template endpoints (public read)
+        // OAuth login with GitHub/GitLab
         .route(
-            "/research/templates",
-            get(handlers::list_research_templates),
+            "/auth/oauth/providers",
+            get(auth::handlers::list_oauth_providers),
         )
         .route(
-            "/research/templates/{template_id}",
-            get(handlers::get_research_template),
+            "/auth/oauth/{provider}",
+            get(auth::handlers::start_oauth_login),
         )
         .route(
-            "/research/templates/category/{category}",
-            get(handlers::list_templates_by_category),
+            "/auth/oauth/{provider}/callback",
+            get(auth::handlers::oauth_callback),
         )
-        // Configuration (public read)
-        .route("/config", get(handlers::get_config))
+        // Single sign-on with an OIDC identity provider
+        .route("/auth/oidc", get(auth::handlers::start_oidc_login))
+        .route("/auth/oidc/callback", get(auth::handlers::oidc_callback))
         // Repository listing (public in open mode, protected by middleware)
         .route("/repositories", get(handlers::list_repositories))
         // Wiki viewing (public access)
-        .route("/wiki/{repository_id}", get(handlers::get_wiki));
+        .route("/wiki/{repository_id}", get(handlers::get_wiki))
+        .route(
+            "/wiki/{repository_id}/versions",
+            get(handlers::list_wiki_versions),
+        )
+        .route(
+            "/wiki/{repository_id}/versions/{version_id}",
+            get(handlers::get_wiki_version),
+        )
+        .route(
+            "/wiki/{repository_id}/diff",
+            get(handlers::diff_wiki_versions),
+        )
+        .route_layer(timeout(config.request_timeout_secs))
+        .merge(quick_routes);
+
+    // Routes waiting for model calls, exports or large uploads
+    let long_running_routes = Router::new()
+        .route(
+            "/repositories/upload",
+            post(handlers::upload_repository).layer(DefaultBodyLimit::max(config.max_upload_size)),
+        )
+        // RAG endpoints (requires Query permission)
+        .route("/chat", post(handlers::chat_query))
+        .route("/chat/stream", post(handlers::chat_stream))
+        .route(
+            "/chat/sessions/{session_id}/messages",
+            post(handlers::continue_chat_session),
+        )
+        .route(
+            "/chat/{session_id}/regenerate",
+            post(handlers::regenerate_answer),
+        )
+        // Wiki generation (requires GenerateWiki permission)
+        .route("/wiki/generate", post(handlers::generate_wiki))
+        .route(
+            "/wiki/{repository_id}/generate/cancel",
+            post(handlers::cancel_wiki_generation),
+        )
+        .route("/wiki/{repository_id}/export", post(handlers::export_wiki))
+        .route(
+            "/wiki/{repository_id}/pages/{page_id}/regenerate",
+            post(handlers::regenerate_wiki_page),
+        )
+        .route_layer(timeout(config.long_request_timeout_secs));
 
     // Protected routes (authentication required)
     let protected_routes = Router::new()
@@ -55,8 +122,16 @@ pub fn api_routes(_state: AppState) -> Router<AppState> {
             "/auth/api-keys/{key_id}",
             delete(auth::handlers::delete_api_key),
         )
+        .route(
+            "/auth/oauth/{provider}/token",
+            delete(auth::handlers::revoke_oauth_token),
+        )
         // Repository management (requires GenerateWiki permission)
         .route("/repositories", post(handlers::initialize_repository))
+        .route(
+            "/repositories/batch",
+            post(handlers::batch_initialize_repositories),
+        )
         .route(
             "/repositories/{repository_id}",
             get(handlers::get_repository_info),
@@ -65,67 +140,126 @@ pub fn api_routes(_state: AppState) -> Router<AppState> {
             "/repositories/{repository_id}",
             delete(handlers::delete_repository),
         )
+        .route(
+            "/repositories/{repository_id}/metrics",
+            get(handlers::get_repository_metrics),
+        )
         .route(
             "/repositories/{repository_id}/reindex",
             post(handlers::reindex_repository),
         )
-        // RAG endpoints (requires Query permission)
-        .route("/chat", post(handlers::chat_query))
-        .route("/chat/stream", post(handlers::chat_stream))
-        // Wiki generation (requires GenerateWiki permission)
-        .route("/wiki/generate", post(handlers::generate_wiki))
-        .route("/wiki/{repository_id}/export", post(handlers::export_wiki))
-        // Research endpoints (requires Query permission)
-        .route("/research/start", post(handlers::start_research))
         .route(
-            "/research/deep-stream",
-            post(handlers::start_deep_research_stream),
-        ) // Streaming endpoint
+            "/repositories/{repository_id}/index/cancel",
+            post(handlers::cancel_indexing),
+        )
         .route(
-            "/research/{research_id}/result",
-            get(handlers::get_research_result),
-        ) // Get detailed result
+            "/repositories/{repository_id}/share",
+            post(handlers::share_repository),
+        )
         .route(
-            "/research/iterate/{repository_id}",
-            post(handlers::research_iteration),
+            "/repositories/{repository_id}/files",
+            get(handlers::get_repository_files),
         )
         .route(
-            "/research/progress/{repository_id}",
-            get(handlers::get_research_progress),
+            "/repositories/{repository_id}/file",
+            get(handlers::get_repository_file),
         )
+        .route("/jobs", get(handlers::list_indexing_jobs))
+        // Organizations and their members
+        .route("/organizations", get(handlers::list_organizations))
+        .route("/organizations", post(handlers::create_organization))
         .route(
-            "/research/{research_id}",
-            get(handlers::get_research_progress_by_id),
+            "/organizations/{organization_id}",
+            get(handlers::get_organization),
         )
         .route(
-            "/research/{research_id}/stop",
-            post(handlers::stop_research),
+            "/organizations/{organization_id}",
+            delete(handlers::delete_organization),
         )
-        .route("/research/sessions", get(handlers::list_research_sessions))
         .route(
-            "/research/start-from-template",
-            post(handlers::start_research_from_template),
+            "/organizations/{organization_id}/members/{user_id}",
+            put(handlers::set_organization_member),
         )
-        // Research history endpoints (requires Query permission)
-        .route("/research/history", get(handlers::get_research_history))
         .route(
-            "/research/history/{repository_id}",
-            get(handlers::get_research_record),
+            "/organizations/{organization_id}/members/{user_id}",
+            delete(handlers::remove_organization_member),
         )
+        // Webhooks notified of indexing, wiki and research events
         .route(
-            "/research/history/{repository_id}",
-            delete(handlers::delete_research_record),
+            "/webhooks",
+            get(handlers::list_webhooks).post(handlers::create_webhook),
         )
         .route(
-            "/research/statistics",
-            get(handlers::get_research_statistics),
+            "/webhooks/{webhook_id}",
+            get(handlers::get_webhook)
+                .patch(handlers::update_webhook)
+                .delete(handlers::delete_webhook),
+        )
+        .route(
+            "/webhooks/{webhook_id}/deliveries",
+            get(handlers::list_webhook_deliveries),
+        )
+        .route(
+            "/webhooks/{webhook_id}/deliveries/{delivery_id}/retry",
+            post(handlers::retry_webhook_delivery),
+        )
+        // RAG endpoints (requires Query permission)
+        .route(
+            "/chat/sessions",
+            get(handlers::list_chat_sessions).post(handlers::create_chat_session),
+        )
+        .route(
+            "/chat/sessions/{session_id}",
+            get(handlers::get_chat_session)
+                .patch(handlers::rename_chat_session)
+                .delete(handlers::delete_chat_session),
+        )
+        // Wiki generation (requires GenerateWiki permission)
+        .route(
+            "/wiki/{repository_id}/export/{job_id}/download",
+            get(handlers::download_wiki_export),
+        )
+        .route(
+            "/wiki/{repository_id}/pages/{page_id}",
+            put(handlers::edit_wiki_page),
+        )
+        .route(
+            "/wiki/{repository_id}/template",
+            get(handlers::get_wiki_template),
+        )
+        .route(
+            "/wiki/{repository_id}/template",
+            put(handlers::set_wiki_template),
+        )
+        .route(
+            "/wiki/{repository_id}/template",
+            delete(handlers::delete_wiki_template),
+        )
+        .route(
+            "/wiki/{repository_id}/cache",
+            delete(handlers::purge_wiki_cache),
         )
         // Configuration (admin write)
         .route("/config", post(handlers::update_config))
+        // Administration (admin only)
+        .route(
+            "/admin/workspace/gc",
+            post(handlers::collect_workspace_garbage),
+        )
+        .route("/admin/storage", get(handlers::get_storage_usage))
+        .route("/admin/storage/gc", post(handlers::collect_storage_garbage))
+        .route("/admin/stats", get(handlers::get_stats))
+        .route("/admin/usage", get(handlers::get_usage))
+        .route("/admin/usage/export", get(handlers::export_usage))
+        // Model Context Protocol over server-sent events (query permission required)
+        .route("/mcp/sse", get(crate::mcp::sse::mcp_sse))
+        .route("/mcp/message", post(crate::mcp::sse::mcp_message))
         // File operations (query permission required)
         .route("/files/tree", post(handlers::get_file_tree))
         .route("/files/content", post(handlers::get_file_content))
         .route("/files/readme", post(handlers::get_readme))
+        .route_layer(timeout(config.request_timeout_secs))
+        .merge(long_running_routes)
         // Apply authentication middleware to all protected routes
         .layer(axum::middleware::from_fn_with_state(
             _state.clone(),
@@ -137,17 +271,128 @@ pub fn api_routes(_state: AppState) -> Router<AppState> {
     // Add database-specific routes if SQLite feature is enabled
     #[cfg(feature = "sqlite")]
     {
-        router = router.route("/history/{repository_id}", get(handlers::get_query_history));
+        router = router.merge(
+            Router::new()
+                .route("/history/{repository_id}", get(handlers::get_query_history))
+                .route("/chat/feedback", post(handlers::submit_chat_feedback))
+                .route("/admin/feedback", get(handlers::list_chat_feedback))
+                .route_layer(timeout(config.request_timeout_secs)),
+        );
+    }
+
+    // Add research routes if the research feature is enabled
+    #[cfg(feature = "research")]
+    {
+        let public_research_routes = Router::new()
+            // Research template endpoints (public read)
+            .route(
+                "/research/templates",
+                get(handlers::list_research_templates),
+            )
+            .route(
+                "/research/templates/{template_id}",
+                get(handlers::get_research_template),
+            )
+            .route(
+                "/research/templates/category/{category}",
+                get(handlers::list_templates_by_category),
+            )
+            .route_layer(timeout(config.request_timeout_secs));
+        let long_running_research_routes = Router::new()
+            // Research endpoints (requires Query permission)
+            .route("/research/start", post(handlers::start_research))
+            .route(
+                "/research/deep-stream",
+                post(handlers::start_deep_research_stream),
+            ) // Streaming endpoint
+            .route(
+                "/research/iterate/{repository_id}",
+                post(handlers::research_iteration),
+            )
+            .route(
+                "/research/start-from-template",
+                post(handlers::start_research_from_template),
+            )
+            .route_layer(timeout(config.long_request_timeout_secs));
+        let protected_research_routes = Router::new()
+            // Research endpoints (requires Query permission)
+            .route(
+                "/research/{research_id}/result",
+                get(handlers::get_research_result),
+            ) // Get detailed result
+            .route(
+                "/research/{research_id}/events",
+                get(handlers::research_progress_events),
+            ) // Server-Sent Events
+            .route(
+                "/research/progress/{repository_id}",
+                get(handlers::get_research_progress),
+            )
+            .route(
+                "/research/{research_id}",
+                get(handlers::get_research_progress_by_id),
+            )
+            .route(
+                "/research/{research_id}/stop",
+                post(handlers::stop_research),
+            )
+            .route("/research/sessions", get(handlers::list_research_sessions))
+            // Research template management (requires DeepResearch permission)
+            .route(
+                "/research/templates",
+                post(handlers::create_research_template),
+            )
+            .route(
+                "/research/templates/{template_id}",
+                put(handlers::update_research_template).delete(handlers::delete_research_template),
+            )
+            // Research history endpoints (requires Query permission)
+            .route("/research/history", get(handlers::get_research_history))
+            .route(
+                "/research/history/{repository_id}",
+                get(handlers::get_research_record),
+            )
+            .route(
+                "/research/history/{repository_id}",
+                delete(handlers::delete_research_record),
+            )
+            .route(
+                "/research/statistics",
+                get(handlers::get_research_statistics),
+            )
+            .route_layer(timeout(config.request_timeout_secs))
+            .merge(long_running_research_routes)
+            .layer(axum::middleware::from_fn_with_state(
+                _state.clone(),
+                crate::middleware::auth_middleware,
+            ));
+        router = router
+            .merge(public_research_routes)
+            .merge(protected_research_routes);
     }
 
     router
 }
 
 /// Create WebSocket routes
-pub fn websocket_routes() -> Router<AppState> {
+#[cfg(feature = "websocket")]
+pub fn websocket_routes(state: AppState) -> Router<AppState> {
     Router::new()
         // Unified WebSocket endpoint for all real-time communication
         .route("/", get(websocket::unified_handler))
+        // Wiki generation progress only
+        .route("/wiki", get(websocket::wiki_handler))
+        // Indexing progress of the followed repositories only
+        .route("/index", get(websocket::index_handler))
+        // Streaming chat, several questions per connection (authenticated
+        // outside open mode)
+        .route(
+            "/chat",
+            get(websocket::chat::chat_handler).route_layer(axum::middleware::from_fn_with_state(
+                state,
+                crate::middleware::auth_middleware,
+            )),
+        )
 }
 
 /// Create static file routes
@@ -156,8 +401,10 @@ pub fn static_routes() -> Router<AppState> {
     Router::new().nest_service("/assets", ServeDir::new("wikify-web/static"))
 }
 
-/// Create OpenAPI documentation routes
-pub fn openapi_routes() -> Router<AppState> {
+/// Create OpenAPI documentation routes for an app served under `base_path`
+pub fn openapi_routes(base_path: &str) -> Router<AppState> {
+    let spec_url = format!("{}/api-docs/openapi.json", base_path);
+    let spec = openapi::openapi_with_base_path(base_path);
     Router::new()
         // OpenAPI specification endpoints
         .route("/openapi.json", get(get_openapi_json))
@@ -166,17 +413,17 @@ pub fn openapi_routes() -> Router<AppState> {
         .route("/asyncapi.yaml", get(get_asyncapi_yaml))
         .route("/asyncapi.json", get(get_asyncapi_json))
         // Swagger UI
-        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", openapi::ApiDoc::openapi()))
+        .merge(SwaggerUi::new("/docs").url(spec_url, spec))
 }
 
 /// Get OpenAPI specification as JSON
-async fn get_openapi_json() -> Json<utoipa::openapi::OpenApi> {
-    Json(openapi::ApiDoc::openapi())
+async fn get_openapi_json(State(state): State<AppState>) -> Json<utoipa::openapi::OpenApi> {
+    Json(openapi::openapi_with_base_path(&state.config.base_path))
 }
 
 /// Get OpenAPI specification as YAML
-async fn get_openapi_yaml() -> String {
-    openapi::get_openapi_yaml()
+async fn get_openapi_yaml(State(state): State<AppState>) -> String {
+    serde_yaml::to_string(&openapi::openapi_with_base_path(&state.config.base_path)).unwrap()
 }
 
 /// Get AsyncAPI specification as YAML
@@ -198,12 +445,13 @@ async fn get_asyncapi_json() -> Result<Json<serde_json::Value>, (axum::http::Sta
 
 /// Create all routes combined
 pub fn all_routes(state: AppState) -> Router<AppState> {
-    Router::new()
-        .nest("/api", api_routes(state.clone()))
-        .nest("/ws", websocket_routes())
-        .nest("/api-docs", openapi_routes())
+    let router = Router::new().nest("/api", api_routes(state.clone()));
+    #[cfg(feature = "websocket")]
+    let router = router.nest("/ws", websocket_routes(state.clone()));
+    router
+        .nest("/api-docs", openapi_routes(&state.config.base_path))
         .merge(static_routes())
-        .fallback(handlers::spa_fallback)
+        .fallback(crate::frontend::serve_frontend)
 }
 
 #[cfg(test)]
@@ -231,10 +479,28 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_liveness_route() {
+        let state = AppState::new(WebConfig::default()).await.unwrap();
+        let app = api_routes(state.clone()).with_state(state);
+
+        let response = app
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/health/live")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn test_openapi_json_route() {
         let state = AppState::new(WebConfig::default()).await.unwrap();
-        let app = openapi_routes().with_state(state);
+        let app = openapi_routes("").with_state(state);
 
         let response = app
             .oneshot(
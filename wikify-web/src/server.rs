@@ -4,6 +4,7 @@
 
 use crate::{create_app, AppState, WebConfig, WebError, WebResult};
 use axum::serve;
+use std::net::SocketAddr;
 use std::time::Duration;
 use tokio::net::TcpListener;
 use tracing::{error, info};
@@ -17,6 +18,9 @@ pub struct WikifyServer {
 impl WikifyServer {
     /// Create a new Wikify server
     pub async fn new(config: WebConfig) -> WebResult<Self> {
+        // Fail on invalid CORS settings before anything starts
+        crate::cors::cors_layer(&config)?;
+
         let state = AppState::new(config.clone()).await?;
 
         Ok(Self { config, state })
@@ -25,9 +29,17 @@ impl WikifyServer {
     /// Start the web server
     pub async fn start(self) -> WebResult<()> {
         let address = self.config.address();
+        let scheme = if self.config.tls_enabled() {
+            "https"
+        } else {
+            "http"
+        };
 
         info!("🚀 Starting Wikify Web Server");
-        info!("📍 Server address: http://{}", address);
+        info!(
+            "📍 Server address: {}://{}{}",
+            scheme, address, self.config.base_path
+        );
         info!("🔧 Development mode: {}", self.config.dev_mode);
 
         // Create the application
@@ -38,7 +50,14 @@ impl WikifyServer {
             .await
             .map_err(WebError::Server)?;
 
-        info!("✅ Server listening on http://{}", address);
+        info!(
+            "✅ Server listening on {}://{}{}",
+            scheme, address, self.config.base_path
+        );
+
+        if let Some(grpc_port) = self.config.grpc_port {
+            self.start_grpc(grpc_port).await?;
+        }
 
         // Start cleanup task for old sessions
         let cleanup_state = self.state.clone();
@@ -51,7 +70,20 @@ impl WikifyServer {
         });
 
         // Start the server
-        if let Err(e) = serve(listener, app).await {
+        if self.config.tls_enabled() {
+            if let Some(redirect_port) = self.config.http_redirect_port {
+                self.start_https_redirect(redirect_port).await?;
+            }
+            if let Err(e) = self.serve_tls(listener, app).await {
+                error!("❌ Server error: {}", e);
+                return Err(e);
+            }
+            return Ok(());
+        }
+
+        // Peer addresses identify anonymous clients for rate limiting
+        let service = app.into_make_service_with_connect_info::<SocketAddr>();
+        if let Err(e) = serve(listener, service).await {
             error!("❌ Server error: {}", e);
             return Err(WebError::Server(e));
         }
@@ -59,6 +91,66 @@ impl WikifyServer {
         Ok(())
     }
 
+    /// Serve the application over HTTPS
+    #[cfg(feature = "tls")]
+    async fn serve_tls(&self, listener: TcpListener, app: axum::Router) -> WebResult<()> {
+        crate::tls::serve(&self.config, listener, app).await
+    }
+
+    #[cfg(not(feature = "tls"))]
+    async fn serve_tls(&self, _listener: TcpListener, _app: axum::Router) -> WebResult<()> {
+        Err(WebError::Config(
+            "TLS is configured, but the server was built without the tls feature".to_string(),
+        ))
+    }
+
+    /// Redirect plain HTTP requests to HTTPS in the background
+    #[cfg(feature = "tls")]
+    async fn start_https_redirect(&self, port: u16) -> WebResult<()> {
+        let address = format!("{}:{}", self.config.host, port);
+        let listener = TcpListener::bind(&address)
+            .await
+            .map_err(WebError::Server)?;
+        info!("↪️ Redirecting http://{} to HTTPS", address);
+
+        let https_port = self.config.port;
+        tokio::spawn(async move {
+            if let Err(e) = crate::tls::serve_redirect(listener, https_port).await {
+                error!("❌ HTTP redirect server error: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(not(feature = "tls"))]
+    async fn start_https_redirect(&self, _port: u16) -> WebResult<()> {
+        Ok(())
+    }
+
+    /// Serve the gRPC API in the background
+    #[cfg(feature = "grpc")]
+    async fn start_grpc(&self, port: u16) -> WebResult<()> {
+        let address = format!("{}:{}", self.config.host, port);
+        let listener = TcpListener::bind(&address)
+            .await
+            .map_err(WebError::Server)?;
+        info!("✅ gRPC API listening on {}", address);
+
+        let state = self.state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = crate::grpc::serve(state, listener).await {
+                error!("❌ gRPC server error: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    async fn start_grpc(&self, _port: u16) -> WebResult<()> {
+        tracing::warn!("gRPC port configured, but the server was built without the grpc feature");
+        Ok(())
+    }
+
     /// Get server configuration
     pub fn config(&self) -> &WebConfig {
         &self.config
@@ -107,6 +199,12 @@ impl WikifyServerBuilder {
         self
     }
 
+    /// Serve the app under a path prefix such as `/wikify`
+    pub fn base_path<S: AsRef<str>>(mut self, base_path: S) -> Self {
+        self.config.base_path = crate::normalize_base_path(base_path.as_ref());
+        self
+    }
+
     /// Set database URL
     pub fn database_url<S: Into<String>>(mut self, database_url: S) -> Self {
         self.config.database_url = Some(database_url.into());
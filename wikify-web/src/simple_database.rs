@@ -3,23 +3,145 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqliteConnectOptions, Row, SqlitePool};
+#[cfg(feature = "postgres")]
+use sqlx::PgPool;
+use sqlx::{migrate::Migrator, sqlite::SqliteConnectOptions, Row, SqlitePool};
 
 use crate::{WebError, WebResult};
+use wikify_applications::{Page, PageRequest};
+
+/// SQLite 数据库迁移，按版本号顺序执行（migrations/web 目录；
+/// migrations 目录下的文件属于 wikify-applications 的仓库存储）
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/web");
+
+/// PostgreSQL 数据库迁移（migrations/postgres 目录，仓库存储共用同一套表）
+#[cfg(feature = "postgres")]
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+/// sqlx 记录已执行迁移的表
+const MIGRATIONS_TABLE: &str = "_sqlx_migrations";
+
+/// 数据库连接池，按 DATABASE_URL 的协议选择 SQLite 或 PostgreSQL
+///
+/// 查询统一使用 `$1` 形式的占位符，两种数据库都能识别
+#[derive(Debug, Clone)]
+pub enum DatabasePool {
+    Sqlite(SqlitePool),
+    #[cfg(feature = "postgres")]
+    Postgres(PgPool),
+}
+
+impl DatabasePool {
+    /// SQLite 连接池（会话、用量和 webhook 存储目前只支持 SQLite）
+    pub fn as_sqlite(&self) -> Option<&SqlitePool> {
+        match self {
+            Self::Sqlite(pool) => Some(pool),
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => None,
+        }
+    }
+
+    /// PostgreSQL 连接池
+    #[cfg(feature = "postgres")]
+    pub fn as_postgres(&self) -> Option<&PgPool> {
+        match self {
+            Self::Postgres(pool) => Some(pool),
+            Self::Sqlite(_) => None,
+        }
+    }
+
+    /// 数据库类型名称
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            Self::Sqlite(_) => "sqlite",
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => "postgres",
+        }
+    }
+
+    /// 对应数据库的迁移
+    fn migrator(&self) -> &'static Migrator {
+        match self {
+            Self::Sqlite(_) => &MIGRATOR,
+            #[cfg(feature = "postgres")]
+            Self::Postgres(_) => &POSTGRES_MIGRATOR,
+        }
+    }
+}
+
+/// 在当前的连接池上执行同一段查询代码
+macro_rules! with_pool {
+    ($pool:expr, |$conn:ident| $body:expr) => {
+        match $pool {
+            $crate::simple_database::DatabasePool::Sqlite($conn) => $body,
+            #[cfg(feature = "postgres")]
+            $crate::simple_database::DatabasePool::Postgres($conn) => $body,
+        }
+    };
+}
+pub(crate) use with_pool;
+
+/// 是否为 PostgreSQL 连接地址
+pub fn is_postgres_url(database_url: &str) -> bool {
+    database_url.starts_with("postgres://") || database_url.starts_with("postgresql://")
+}
+
+/// 已执行的迁移
+#[derive(Debug, Default)]
+struct AppliedMigrations {
+    /// 最新的成功迁移
+    latest: Option<i64>,
+    /// 执行失败的迁移
+    failed: Option<i64>,
+}
 
 /// 简化的数据库服务
 pub struct SimpleDatabaseService {
-    pool: SqlitePool,
+    pool: DatabasePool,
 }
 
 impl SimpleDatabaseService {
     /// 获取数据库连接池
-    pub fn pool(&self) -> &SqlitePool {
+    pub fn pool(&self) -> &DatabasePool {
         &self.pool
     }
 
     /// 创建新的数据库服务
     pub async fn new(database_url: &str) -> WebResult<Self> {
+        let pool = if is_postgres_url(database_url) {
+            Self::connect_postgres(database_url).await?
+        } else {
+            DatabasePool::Sqlite(Self::connect_sqlite(database_url).await?)
+        };
+
+        tracing::info!("✅ Database connection established successfully");
+
+        // 更新表结构
+        Self::run_migrations(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    /// 连接 PostgreSQL（地址中可能带有密码，不写入日志）
+    #[cfg(feature = "postgres")]
+    async fn connect_postgres(database_url: &str) -> WebResult<DatabasePool> {
+        tracing::info!("🔗 Attempting to connect to PostgreSQL database");
+        let pool = PgPool::connect(database_url).await.map_err(|e| {
+            tracing::error!("❌ Database connection failed: {}", e);
+            WebError::Database(format!("Failed to connect to database: {}", e))
+        })?;
+        Ok(DatabasePool::Postgres(pool))
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    async fn connect_postgres(_database_url: &str) -> WebResult<DatabasePool> {
+        Err(WebError::Database(
+            "PostgreSQL databases require building with the `postgres` feature".to_string(),
+        ))
+    }
+
+    /// 连接 SQLite，必要时创建数据库文件
+    async fn connect_sqlite(database_url: &str) -> WebResult<SqlitePool> {
         tracing::info!("🔗 Attempting to connect to database: {}", database_url);
 
         // 尝试使用连接选项进行更精细的控制
@@ -72,79 +194,131 @@ impl SimpleDatabaseService {
             })?
         };
 
-        tracing::info!("✅ Database connection established successfully");
+        Ok(pool)
+    }
 
-        // 创建表
-        tracing::info!("🏗️  Creating database tables...");
-        Self::create_tables(&pool).await?;
-        tracing::info!("✅ Database tables created successfully");
+    /// 检查数据库连接是否可用
+    pub async fn ping(&self) -> WebResult<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("SELECT 1").execute(pool).await.map(|_| ())
+        })
+        .map_err(|e| WebError::Database(format!("Database is unreachable: {}", e)))
+    }
 
-        Ok(Self { pool })
+    /// 数据库结构版本，即已应用的最新迁移（尚未迁移时为 None）
+    pub async fn schema_version(&self) -> WebResult<Option<i64>> {
+        Ok(Self::applied_migrations(&self.pool).await?.latest)
     }
 
-    /// 创建数据库表
-    async fn create_tables(pool: &SqlitePool) -> WebResult<()> {
-        tracing::debug!("📋 Creating repositories table...");
-        // 创建仓库表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS repositories (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                repo_path TEXT NOT NULL,
-                repo_type TEXT NOT NULL,
-                status TEXT DEFAULT 'created',
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP,
-                last_indexed_at TEXT
-            )
-            "#,
-        )
-        .execute(pool)
-        .await
-        .map_err(|e| {
-            tracing::error!("❌ Failed to create repositories table: {}", e);
-            WebError::Database(format!("Failed to create repositories table: {}", e))
-        })?;
-        tracing::debug!("✅ Repositories table created successfully");
+    /// 应用尚未执行的迁移
+    ///
+    /// 数据库由更新的版本迁移过、或上次迁移中途失败时拒绝启动，
+    /// 以免旧代码写坏新的表结构
+    async fn run_migrations(pool: &DatabasePool) -> WebResult<()> {
+        let migrator = pool.migrator();
+        let applied = Self::applied_migrations(pool).await?;
+        let supported = migrator.iter().map(|m| m.version).max().unwrap_or(0);
+
+        if let Some(version) = applied.failed {
+            return Err(WebError::Database(format!(
+                "Migration {} of the database failed earlier; restore a backup before starting",
+                version
+            )));
+        }
+        if let Some(latest) = applied.latest.filter(|&latest| latest > supported) {
+            return Err(WebError::Database(format!(
+                "Database schema version {} is newer than this build supports ({}); \
+                 upgrade Wikify or use another database",
+                latest, supported
+            )));
+        }
 
-        tracing::debug!("📋 Creating query_history table...");
-        // 创建查询历史表
-        sqlx::query(
-            r#"
-            CREATE TABLE IF NOT EXISTS query_history (
-                id TEXT PRIMARY KEY,
-                repository_id TEXT,
-                question TEXT NOT NULL,
-                answer TEXT NOT NULL,
-                created_at TEXT DEFAULT CURRENT_TIMESTAMP
-            )
-            "#,
-        )
-        .execute(pool)
-        .await
-        .map_err(|e| {
-            tracing::error!("❌ Failed to create query_history table: {}", e);
-            WebError::Database(format!("Failed to create query_history table: {}", e))
+        with_pool!(pool, |conn| migrator.run(conn).await).map_err(|e| {
+            tracing::error!("❌ Failed to migrate database: {}", e);
+            WebError::Database(format!("Failed to migrate database: {}", e))
         })?;
-        tracing::debug!("✅ Query history table created successfully");
 
+        if applied.latest != Some(supported) {
+            tracing::info!(
+                "✅ Database ({}) migrated from version {} to {}",
+                pool.backend_name(),
+                applied.latest.unwrap_or(0),
+                supported
+            );
+        }
         Ok(())
     }
 
+    /// 读取迁移记录表
+    async fn applied_migrations(pool: &DatabasePool) -> WebResult<AppliedMigrations> {
+        let read_error =
+            |e: sqlx::Error| WebError::Database(format!("Failed to read schema version: {}", e));
+
+        let exists_sql = match pool {
+            DatabasePool::Sqlite(_) => {
+                "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = $1)"
+            }
+            #[cfg(feature = "postgres")]
+            DatabasePool::Postgres(_) => {
+                "SELECT EXISTS (SELECT 1 FROM information_schema.tables \
+                 WHERE table_schema = current_schema() AND table_name = $1)"
+            }
+        };
+        let table_exists = with_pool!(pool, |conn| {
+            sqlx::query_scalar::<_, bool>(exists_sql)
+                .bind(MIGRATIONS_TABLE)
+                .fetch_one(conn)
+                .await
+        })
+        .map_err(read_error)?;
+        if !table_exists {
+            return Ok(AppliedMigrations::default());
+        }
+
+        let sql = format!(
+            "SELECT MAX(CASE WHEN success THEN version END) AS latest, \
+                    MIN(CASE WHEN NOT success THEN version END) AS failed \
+             FROM {}",
+            MIGRATIONS_TABLE
+        );
+        with_pool!(pool, |conn| {
+            let row = sqlx::query(&sql)
+                .fetch_one(conn)
+                .await
+                .map_err(read_error)?;
+            Ok(AppliedMigrations {
+                latest: row.try_get("latest").ok().flatten(),
+                failed: row.try_get("failed").ok().flatten(),
+            })
+        })
+    }
+
     /// 保存仓库信息
     pub async fn save_repository(&self, repo: &SimpleRepository) -> WebResult<()> {
-        sqlx::query(
-            "INSERT OR REPLACE INTO repositories (id, name, repo_path, repo_type, status, created_at, last_indexed_at) VALUES (?, ?, ?, ?, ?, ?, ?)"
-        )
-        .bind(&repo.id)
-        .bind(&repo.name)
-        .bind(&repo.repo_path)
-        .bind(&repo.repo_type)
-        .bind(&repo.status)
-        .bind(repo.created_at.to_rfc3339())
-        .bind(repo.last_indexed_at.as_ref().map(|dt| dt.to_rfc3339()))
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                r#"
+                INSERT INTO repositories (id, name, repo_path, repo_type, status, created_at, last_indexed_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (id) DO UPDATE SET
+                    name = excluded.name,
+                    repo_path = excluded.repo_path,
+                    repo_type = excluded.repo_type,
+                    status = excluded.status,
+                    created_at = excluded.created_at,
+                    last_indexed_at = excluded.last_indexed_at
+                "#
+            )
+            .bind(&repo.id)
+            .bind(&repo.name)
+            .bind(&repo.repo_path)
+            .bind(&repo.repo_type)
+            .bind(&repo.status)
+            .bind(repo.created_at.to_rfc3339())
+            .bind(repo.last_indexed_at.as_ref().map(|dt| dt.to_rfc3339()))
+            .execute(pool)
+            .await
+        })
         .map_err(|e| WebError::Database(format!("Failed to save repository: {}", e)))?;
 
         Ok(())
@@ -152,122 +326,281 @@ impl SimpleDatabaseService {
 
     /// 获取仓库列表
     pub async fn get_repositories(&self) -> WebResult<Vec<SimpleRepository>> {
-        let rows = sqlx::query("SELECT id, name, repo_path, repo_type, status, created_at, last_indexed_at FROM repositories ORDER BY created_at DESC")
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| WebError::Database(format!("Failed to get repositories: {}", e)))?;
-
-        let mut repositories = Vec::new();
-        for row in rows {
-            let created_at_str: String = row
-                .try_get("created_at")
-                .unwrap_or_else(|_| Utc::now().to_rfc3339());
-            let created_at = DateTime::parse_from_rfc3339(&created_at_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            let last_indexed_at = row
-                .try_get::<Option<String>, _>("last_indexed_at")
-                .unwrap_or(None)
-                .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
-                .map(|dt| dt.with_timezone(&Utc));
-
-            repositories.push(SimpleRepository {
-                id: row.try_get("id").unwrap_or_default(),
-                name: row.try_get("name").unwrap_or_default(),
-                repo_path: row.try_get("repo_path").unwrap_or_default(),
-                repo_type: row.try_get("repo_type").unwrap_or_default(),
-                status: row
-                    .try_get("status")
-                    .unwrap_or_else(|_| "created".to_string()),
-                created_at,
-                last_indexed_at,
-            });
-        }
+        with_pool!(&self.pool, |pool| {
+            let rows = sqlx::query("SELECT id, name, repo_path, repo_type, status, created_at, last_indexed_at FROM repositories ORDER BY created_at DESC")
+                .fetch_all(pool)
+                .await
+                .map_err(|e| WebError::Database(format!("Failed to get repositories: {}", e)))?;
+
+            let mut repositories = Vec::new();
+            for row in rows {
+                let created_at_str: String = row
+                    .try_get("created_at")
+                    .unwrap_or_else(|_| Utc::now().to_rfc3339());
+                let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+
+                let last_indexed_at = row
+                    .try_get::<Option<String>, _>("last_indexed_at")
+                    .unwrap_or(None)
+                    .and_then(|s| DateTime::parse_from_rfc3339(&s).ok())
+                    .map(|dt| dt.with_timezone(&Utc));
+
+                repositories.push(SimpleRepository {
+                    id: row.try_get("id").unwrap_or_default(),
+                    name: row.try_get("name").unwrap_or_default(),
+                    repo_path: row.try_get("repo_path").unwrap_or_default(),
+                    repo_type: row.try_get("repo_type").unwrap_or_default(),
+                    status: row
+                        .try_get("status")
+                        .unwrap_or_else(|_| "created".to_string()),
+                    created_at,
+                    last_indexed_at,
+                });
+            }
 
-        Ok(repositories)
+            Ok(repositories)
+        })
     }
 
     /// 删除仓库信息
     pub async fn delete_repository(&self, repository_id: &str) -> WebResult<()> {
-        sqlx::query("DELETE FROM repositories WHERE id = ?")
-            .bind(repository_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| WebError::Database(format!("Failed to delete repository: {}", e)))?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM repositories WHERE id = $1")
+                .bind(repository_id)
+                .execute(pool)
+                .await
+        })
+        .map_err(|e| WebError::Database(format!("Failed to delete repository: {}", e)))?;
 
         Ok(())
     }
 
     /// 保存查询记录
     pub async fn save_query(&self, query: &SimpleQuery) -> WebResult<()> {
-        sqlx::query(
-            "INSERT INTO query_history (id, repository_id, question, answer, created_at) VALUES (?, ?, ?, ?, ?)"
-        )
-        .bind(&query.id)
-        .bind(&query.repository_id)
-        .bind(&query.question)
-        .bind(&query.answer)
-        .bind(query.created_at.to_rfc3339())
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO query_history (id, repository_id, question, answer, created_at) VALUES ($1, $2, $3, $4, $5)"
+            )
+            .bind(&query.id)
+            .bind(&query.repository_id)
+            .bind(&query.question)
+            .bind(&query.answer)
+            .bind(query.created_at.to_rfc3339())
+            .execute(pool)
+            .await
+        })
         .map_err(|e| WebError::Database(format!("Failed to save query: {}", e)))?;
 
         Ok(())
     }
 
-    /// 获取查询历史
+    /// 分页获取查询历史（最新的在前），附带总数
     pub async fn get_query_history(
         &self,
         repository_id: Option<&str>,
-        limit: i32,
-    ) -> WebResult<Vec<SimpleQuery>> {
-        let rows = if let Some(repo_id) = repository_id {
-            sqlx::query("SELECT id, repository_id, question, answer, created_at FROM query_history WHERE repository_id = ? ORDER BY created_at DESC LIMIT ?")
-                .bind(repo_id)
-                .bind(limit)
-                .fetch_all(&self.pool)
-                .await
+        page: &PageRequest,
+    ) -> WebResult<Page<SimpleQuery>> {
+        // 不限制条数时使用最大值，SQLite 和 PostgreSQL 都接受
+        let limit = page.limit.map(|limit| limit as i64).unwrap_or(i64::MAX);
+        let offset = page.offset as i64;
+        let (count_sql, list_sql) = if repository_id.is_some() {
+            (
+                "SELECT COUNT(*) FROM query_history WHERE repository_id = $1",
+                "SELECT id, repository_id, question, answer, created_at FROM query_history WHERE repository_id = $1 ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+            )
         } else {
-            sqlx::query("SELECT id, repository_id, question, answer, created_at FROM query_history ORDER BY created_at DESC LIMIT ?")
+            (
+                "SELECT COUNT(*) FROM query_history",
+                "SELECT id, repository_id, question, answer, created_at FROM query_history ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            )
+        };
+
+        with_pool!(&self.pool, |pool| {
+            let mut count_query = sqlx::query_scalar::<_, i64>(count_sql);
+            let mut list_query = sqlx::query(list_sql);
+            if let Some(repo_id) = repository_id {
+                count_query = count_query.bind(repo_id);
+                list_query = list_query.bind(repo_id);
+            }
+            let total = count_query
+                .fetch_one(pool)
+                .await
+                .map_err(|e| WebError::Database(format!("Failed to count query history: {}", e)))?;
+            let rows = list_query
                 .bind(limit)
-                .fetch_all(&self.pool)
+                .bind(offset)
+                .fetch_all(pool)
                 .await
-        }
-        .map_err(|e| WebError::Database(format!("Failed to get query history: {}", e)))?;
-
-        let mut queries = Vec::new();
-        for row in rows {
-            let created_at_str: String = row
-                .try_get("created_at")
-                .unwrap_or_else(|_| Utc::now().to_rfc3339());
-            let created_at = DateTime::parse_from_rfc3339(&created_at_str)
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(|_| Utc::now());
-
-            queries.push(SimpleQuery {
-                id: row.try_get("id").unwrap_or_default(),
-                repository_id: row.try_get("repository_id").ok(),
-                question: row.try_get("question").unwrap_or_default(),
-                answer: row.try_get("answer").unwrap_or_default(),
-                created_at,
-            });
-        }
+                .map_err(|e| WebError::Database(format!("Failed to get query history: {}", e)))?;
+
+            let mut queries = Vec::new();
+            for row in rows {
+                let created_at_str: String = row
+                    .try_get("created_at")
+                    .unwrap_or_else(|_| Utc::now().to_rfc3339());
+                let created_at = DateTime::parse_from_rfc3339(&created_at_str)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now());
+
+                queries.push(SimpleQuery {
+                    id: row.try_get("id").unwrap_or_default(),
+                    repository_id: row.try_get("repository_id").ok(),
+                    question: row.try_get("question").unwrap_or_default(),
+                    answer: row.try_get("answer").unwrap_or_default(),
+                    created_at,
+                });
+            }
 
-        Ok(queries)
+            Ok(Page {
+                items: queries,
+                total: total as usize,
+                limit: page.limit,
+                offset: page.offset,
+            })
+        })
+    }
+
+    /// 获取单条查询记录
+    pub async fn get_query(&self, query_id: &str) -> WebResult<Option<SimpleQuery>> {
+        with_pool!(&self.pool, |pool| {
+            let row = sqlx::query(
+                "SELECT id, repository_id, question, answer, created_at FROM query_history WHERE id = $1",
+            )
+            .bind(query_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| WebError::Database(format!("Failed to get query: {}", e)))?;
+
+            Ok(row.map(|row| {
+                let created_at_str: String = row
+                    .try_get("created_at")
+                    .unwrap_or_else(|_| Utc::now().to_rfc3339());
+                SimpleQuery {
+                    id: row.try_get("id").unwrap_or_default(),
+                    repository_id: row.try_get("repository_id").ok(),
+                    question: row.try_get("question").unwrap_or_default(),
+                    answer: row.try_get("answer").unwrap_or_default(),
+                    created_at: DateTime::parse_from_rfc3339(&created_at_str)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                }
+            }))
+        })
     }
 
     /// 删除查询历史
     pub async fn delete_query_history(&self, repository_id: &str) -> WebResult<()> {
         // Delete query history for this repository
-        sqlx::query("DELETE FROM query_history WHERE repository_id = ?")
-            .bind(repository_id)
-            .execute(&self.pool)
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM query_history WHERE repository_id = $1")
+                .bind(repository_id)
+                .execute(pool)
+                .await
+        })
+        .map_err(|e| WebError::Database(format!("Failed to delete query history: {}", e)))?;
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM query_feedback WHERE repository_id = $1")
+                .bind(repository_id)
+                .execute(pool)
+                .await
+        })
+        .map_err(|e| WebError::Database(format!("Failed to delete query feedback: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// 保存回答反馈
+    pub async fn save_feedback(&self, feedback: &SimpleFeedback) -> WebResult<()> {
+        let wrong_sources = serde_json::to_string(&feedback.wrong_sources)
+            .map_err(|e| WebError::Database(format!("Failed to serialize feedback: {}", e)))?;
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO query_feedback (id, query_id, repository_id, user_id, rating, comment, wrong_sources, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind(&feedback.id)
+            .bind(&feedback.query_id)
+            .bind(&feedback.repository_id)
+            .bind(&feedback.user_id)
+            .bind(&feedback.rating)
+            .bind(&feedback.comment)
+            .bind(&wrong_sources)
+            .bind(feedback.created_at.to_rfc3339())
+            .execute(pool)
             .await
-            .map_err(|e| WebError::Database(format!("Failed to delete query history: {}", e)))?;
+        })
+        .map_err(|e| WebError::Database(format!("Failed to save feedback: {}", e)))?;
 
         Ok(())
     }
+
+    /// 获取回答反馈，可按仓库和评价过滤，最新的在前
+    pub async fn get_feedback(
+        &self,
+        repository_id: Option<&str>,
+        rating: Option<&str>,
+        limit: i32,
+    ) -> WebResult<Vec<(SimpleFeedback, Option<SimpleQuery>)>> {
+        let parse_time = |value: Option<String>| {
+            value
+                .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(Utc::now)
+        };
+
+        with_pool!(&self.pool, |pool| {
+            let rows = sqlx::query(
+                r#"
+                SELECT f.id, f.query_id, f.repository_id, f.user_id, f.rating, f.comment,
+                    f.wrong_sources, f.created_at,
+                    q.question, q.answer, q.created_at AS query_created_at
+                FROM query_feedback f
+                LEFT JOIN query_history q ON q.id = f.query_id
+                WHERE ($1 IS NULL OR f.repository_id = $1) AND ($2 IS NULL OR f.rating = $2)
+                ORDER BY f.created_at DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(repository_id)
+            .bind(rating)
+            .bind(limit as i64)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| WebError::Database(format!("Failed to get feedback: {}", e)))?;
+
+            let mut feedback = Vec::new();
+            for row in rows {
+                let wrong_sources: String = row.try_get("wrong_sources").unwrap_or_default();
+                let entry = SimpleFeedback {
+                    id: row.try_get("id").unwrap_or_default(),
+                    query_id: row.try_get("query_id").unwrap_or_default(),
+                    repository_id: row.try_get("repository_id").ok().flatten(),
+                    user_id: row.try_get("user_id").ok().flatten(),
+                    rating: row.try_get("rating").unwrap_or_default(),
+                    comment: row.try_get("comment").ok().flatten(),
+                    wrong_sources: serde_json::from_str(&wrong_sources).unwrap_or_default(),
+                    created_at: parse_time(row.try_get("created_at").ok()),
+                };
+
+                // 查询记录可能已被删除
+                let question: Option<String> = row.try_get("question").ok().flatten();
+                let query = question.map(|question| SimpleQuery {
+                    id: entry.query_id.clone(),
+                    repository_id: entry.repository_id.clone(),
+                    question,
+                    answer: row.try_get("answer").unwrap_or_default(),
+                    created_at: parse_time(row.try_get("query_created_at").ok().flatten()),
+                });
+
+                feedback.push((entry, query));
+            }
+
+            Ok(feedback)
+        })
+    }
 }
 
 /// 简化的仓库信息
@@ -291,3 +624,169 @@ pub struct SimpleQuery {
     pub answer: String,
     pub created_at: DateTime<Utc>,
 }
+
+/// 回答反馈
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimpleFeedback {
+    pub id: String,
+    /// 被评价的查询记录
+    pub query_id: String,
+    pub repository_id: Option<String>,
+    pub user_id: Option<String>,
+    /// "up" 或 "down"
+    pub rating: String,
+    pub comment: Option<String>,
+    /// 用户标记为错误的来源文件
+    pub wrong_sources: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_migrations_adopt_existing_database() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", temp.path().join("wikify.db").display());
+
+        // 迁移引入之前创建的数据库
+        let pool = SqlitePool::connect_with(
+            SqliteConnectOptions::new()
+                .filename(temp.path().join("wikify.db"))
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            CREATE TABLE query_history (
+                id TEXT PRIMARY KEY,
+                repository_id TEXT,
+                question TEXT NOT NULL,
+                answer TEXT NOT NULL,
+                created_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool.close().await;
+
+        let database = SimpleDatabaseService::new(&url).await.unwrap();
+        assert_eq!(database.schema_version().await.unwrap(), Some(1));
+        drop(database);
+
+        // 再次启动时没有需要执行的迁移
+        let database = SimpleDatabaseService::new(&url).await.unwrap();
+        assert_eq!(database.schema_version().await.unwrap(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_newer_schema_is_refused() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", temp.path().join("wikify.db").display());
+        let database = SimpleDatabaseService::new(&url).await.unwrap();
+
+        sqlx::query(&format!(
+            "INSERT INTO {} (version, description, success, checksum, execution_time) \
+             VALUES (9999, 'from the future', TRUE, X'00', 0)",
+            MIGRATIONS_TABLE
+        ))
+        .execute(database.pool().as_sqlite().unwrap())
+        .await
+        .unwrap();
+        drop(database);
+
+        assert!(SimpleDatabaseService::new(&url).await.is_err());
+    }
+
+    #[test]
+    fn test_postgres_urls_are_detected() {
+        assert!(is_postgres_url("postgres://wikify@db/wikify"));
+        assert!(is_postgres_url("postgresql://wikify@db/wikify"));
+        assert!(!is_postgres_url("sqlite:./data/wikify.db"));
+    }
+
+    #[tokio::test]
+    async fn test_saving_a_repository_again_updates_it() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", temp.path().join("wikify.db").display());
+        let database = SimpleDatabaseService::new(&url).await.unwrap();
+
+        let mut repo = SimpleRepository {
+            id: "repo-1".to_string(),
+            name: "wikify".to_string(),
+            repo_path: "/src/wikify".to_string(),
+            repo_type: "local".to_string(),
+            status: "created".to_string(),
+            created_at: Utc::now(),
+            last_indexed_at: None,
+        };
+        database.save_repository(&repo).await.unwrap();
+        repo.status = "indexed".to_string();
+        repo.last_indexed_at = Some(Utc::now());
+        database.save_repository(&repo).await.unwrap();
+
+        let repositories = database.get_repositories().await.unwrap();
+        assert_eq!(repositories.len(), 1);
+        assert_eq!(repositories[0].status, "indexed");
+        assert!(repositories[0].last_indexed_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_feedback_is_stored_with_query() {
+        let temp = tempfile::tempdir().unwrap();
+        let url = format!("sqlite:{}", temp.path().join("wikify.db").display());
+        let database = SimpleDatabaseService::new(&url).await.unwrap();
+
+        let query = SimpleQuery {
+            id: "query-1".to_string(),
+            repository_id: Some("repo-1".to_string()),
+            question: "How does login work?".to_string(),
+            answer: "With sessions".to_string(),
+            created_at: Utc::now(),
+        };
+        database.save_query(&query).await.unwrap();
+        assert!(database.get_query("query-1").await.unwrap().is_some());
+        assert!(database.get_query("missing").await.unwrap().is_none());
+
+        database
+            .save_feedback(&SimpleFeedback {
+                id: "feedback-1".to_string(),
+                query_id: query.id.clone(),
+                repository_id: query.repository_id.clone(),
+                user_id: Some("alice".to_string()),
+                rating: "down".to_string(),
+                comment: Some("Login uses JWT".to_string()),
+                wrong_sources: vec!["src/session.rs".to_string()],
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let feedback = database
+            .get_feedback(Some("repo-1"), Some("down"), 10)
+            .await
+            .unwrap();
+        assert_eq!(feedback.len(), 1);
+        assert_eq!(feedback[0].0.wrong_sources, vec!["src/session.rs"]);
+        assert_eq!(
+            feedback[0].1.as_ref().unwrap().question,
+            "How does login work?"
+        );
+        assert!(database
+            .get_feedback(None, Some("up"), 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        database.delete_query_history("repo-1").await.unwrap();
+        assert!(database
+            .get_feedback(None, None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,19 +2,30 @@
 
 use crate::{
     auth::{
-        api_keys::ApiKeyService, database::DatabaseUserStore, users::UserService, users::UserStore,
+        api_keys::ApiKeyService, database::DatabaseUserStore, oauth::OAuthService,
+        oidc::OidcService, users::UserService, users::UserStore,
     },
+    mcp::McpSessions,
     WebConfig, WebError, WebResult,
 };
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::{broadcast, mpsc, RwLock};
 use tracing::{debug, info, warn};
+use wikify_applications::telemetry::{self, TelemetryEvent};
 use wikify_applications::{ApplicationConfig, PermissionContext, UserIdentity, WikifyApplication};
+use wikify_core::CancellationToken;
 use wikify_wiki::WikiService;
 
 #[cfg(feature = "sqlite")]
 use crate::simple_database::SimpleDatabaseService;
+#[cfg(feature = "websocket")]
+use crate::websocket::ConnectionCounter;
+#[cfg(feature = "postgres")]
+use wikify_applications::PostgresRepositoryStorage;
+#[cfg(feature = "sqlite")]
+use wikify_applications::{SqliteSessionStorage, SqliteUsageStorage, SqliteWebhookStorage};
 
 /// Web-specific indexing progress update
 #[derive(Debug, Clone, serde::Serialize)]
@@ -43,6 +54,10 @@ pub enum IndexingUpdate {
         repository_id: String,
         error: String,
     },
+    Cancelled {
+        repository_id: String,
+        progress: f64,
+    },
     WikiGenerationStarted {
         repository_id: String,
     },
@@ -56,6 +71,7 @@ pub enum IndexingUpdate {
         wiki_content: String,
         pages_count: usize,
         sections_count: usize,
+        quality: Option<wikify_wiki::QualityReport>,
     },
     WikiGenerationError {
         repository_id: String,
@@ -91,6 +107,27 @@ pub enum IndexingUpdate {
     },
 }
 
+impl IndexingUpdate {
+    /// Repository whose event stream this update belongs to
+    pub fn repository_id(&self) -> &str {
+        match self {
+            IndexingUpdate::Started { repository_id, .. }
+            | IndexingUpdate::Progress { repository_id, .. }
+            | IndexingUpdate::Complete { repository_id, .. }
+            | IndexingUpdate::Error { repository_id, .. }
+            | IndexingUpdate::Cancelled { repository_id, .. }
+            | IndexingUpdate::WikiGenerationStarted { repository_id }
+            | IndexingUpdate::WikiGenerationProgress { repository_id, .. }
+            | IndexingUpdate::WikiGenerationComplete { repository_id, .. }
+            | IndexingUpdate::WikiGenerationError { repository_id, .. }
+            | IndexingUpdate::ResearchStarted { repository_id, .. }
+            | IndexingUpdate::ResearchProgress { repository_id, .. }
+            | IndexingUpdate::ResearchComplete { repository_id, .. }
+            | IndexingUpdate::ResearchError { repository_id, .. } => repository_id,
+        }
+    }
+}
+
 /// Unified broadcast message for all real-time communication
 #[derive(Debug, Clone, serde::Serialize)]
 #[serde(tag = "type")]
@@ -99,6 +136,7 @@ pub enum BroadcastMessage {
     #[serde(rename = "indexing")]
     IndexingUpdate(IndexingUpdate),
     /// WebSocket message for real-time communication
+    #[cfg(feature = "websocket")]
     #[serde(rename = "websocket")]
     WebSocketMessage {
         #[serde(flatten)]
@@ -106,6 +144,155 @@ pub enum BroadcastMessage {
     },
 }
 
+/// Number of recent events kept per repository for clients that reconnect
+const PROGRESS_REPLAY_BUFFER: usize = 256;
+
+/// A broadcast message as delivered to subscribers
+#[derive(Debug, Clone)]
+pub struct ProgressEvent {
+    /// Position in the repository's event stream, increasing by one per
+    /// event; unset for messages that do not belong to a repository
+    pub event_id: Option<u64>,
+    pub message: BroadcastMessage,
+}
+
+/// Recent events of one repository
+#[derive(Default)]
+struct RepositoryEvents {
+    last_id: u64,
+    recent: VecDeque<ProgressEvent>,
+}
+
+/// Broadcasts progress to WebSocket clients and keeps the recent events of
+/// each repository, so a client that reconnects can replay what it missed
+#[derive(Clone)]
+pub struct ProgressBroadcaster {
+    sender: broadcast::Sender<ProgressEvent>,
+    streams: Arc<Mutex<HashMap<String, RepositoryEvents>>>,
+}
+
+impl ProgressBroadcaster {
+    /// Create a broadcaster buffering up to `capacity` messages per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            streams: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Send a message to all subscribers, giving repository updates the next
+    /// event ID of their repository. Updates are kept for replay even when
+    /// nobody is subscribed.
+    pub fn send(
+        &self,
+        message: BroadcastMessage,
+    ) -> Result<usize, broadcast::error::SendError<ProgressEvent>> {
+        let update = match &message {
+            BroadcastMessage::IndexingUpdate(update) => update,
+            #[cfg(feature = "websocket")]
+            BroadcastMessage::WebSocketMessage { .. } => {
+                return self.sender.send(ProgressEvent {
+                    event_id: None,
+                    message,
+                });
+            }
+        };
+
+        // Sent under the lock so subscribers see each stream in ID order
+        let mut streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        let stream = streams
+            .entry(update.repository_id().to_string())
+            .or_default();
+        stream.last_id += 1;
+        let event = ProgressEvent {
+            event_id: Some(stream.last_id),
+            message,
+        };
+        stream.recent.push_back(event.clone());
+        if stream.recent.len() > PROGRESS_REPLAY_BUFFER {
+            stream.recent.pop_front();
+        }
+        self.sender.send(event)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgressEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of live subscribers
+    pub fn receiver_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Kept events of a repository with an ID after `since`, oldest first
+    pub fn events_since(&self, repository_id: &str, since: u64) -> Vec<ProgressEvent> {
+        let streams = self.streams.lock().unwrap_or_else(|e| e.into_inner());
+        streams
+            .get(repository_id)
+            .map(|stream| {
+                stream
+                    .recent
+                    .iter()
+                    .filter(|event| event.event_id.is_some_and(|id| id > since))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Running wiki generations, keyed by repository ID
+#[derive(Clone, Default)]
+pub struct WikiGenerations {
+    running: Arc<Mutex<HashMap<String, (u64, CancellationToken)>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl WikiGenerations {
+    /// Register a generation, cancelling any earlier one of the repository
+    pub fn start(&self, repository_id: &str) -> (u64, CancellationToken) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        let previous = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(repository_id.to_string(), (id, token.clone()));
+        if let Some((_, previous)) = previous {
+            previous.cancel();
+        }
+        (id, token)
+    }
+
+    /// Cancel the running generation of a repository, if there is one
+    pub fn cancel(&self, repository_id: &str) -> bool {
+        let running = self
+            .running
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(repository_id);
+        match running {
+            Some((_, token)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget a finished generation unless a newer one replaced it
+    pub fn finish(&self, repository_id: &str, id: u64) {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running
+            .get(repository_id)
+            .is_some_and(|(current, _)| *current == id)
+        {
+            running.remove(repository_id);
+        }
+    }
+}
+
 /// Cached wiki content
 #[derive(Debug, Clone)]
 pub struct CachedWiki {
@@ -130,31 +317,68 @@ pub struct AppState {
     pub wiki_service: Arc<RwLock<WikiService>>,
     /// Cache for generated wikis
     pub wiki_cache: Arc<RwLock<HashMap<String, CachedWiki>>>,
+    /// Wiki generations running in the background
+    pub wiki_generations: WikiGenerations,
     /// Progress broadcaster for web-specific indexing updates and real-time communication
-    pub progress_broadcaster: broadcast::Sender<BroadcastMessage>,
+    pub progress_broadcaster: ProgressBroadcaster,
     /// User service for authentication and user management
     pub user_service: UserService,
     /// API Key service for API key management
     pub api_key_service: ApiKeyService,
+    /// OAuth login with GitHub/GitLab
+    pub oauth_service: OAuthService,
+    /// Single sign-on with an OIDC identity provider
+    pub oidc_service: OidcService,
+    /// Open MCP sessions of the server-sent events transport
+    pub mcp_sessions: McpSessions,
+    /// Open WebSocket connections
+    #[cfg(feature = "websocket")]
+    pub websocket_connections: ConnectionCounter,
 }
 
 impl AppState {
     /// Create a new application state
     pub async fn new(config: WebConfig) -> WebResult<Self> {
         // Create application configuration based on web config
-        let app_config = match config.permission_mode.as_deref() {
-            Some("open") => ApplicationConfig::web_open(),
-            Some("restricted") => ApplicationConfig::web_restricted(),
-            _ => ApplicationConfig::web_open(), // Default to open mode
+        let mut app_config = if config.requires_auth() {
+            ApplicationConfig::web_restricted()
+        } else {
+            ApplicationConfig::web_open() // Default to open mode
         };
-
-        // Create the main application service
-        let application = WikifyApplication::new(app_config)
-            .await
-            .map_err(|e| WebError::Config(format!("Failed to create application: {}", e)))?;
-
-        let wiki_service = WikiService::new()
-            .map_err(|e| WebError::Config(format!("Failed to create wiki service: {}", e)))?;
+        // LLM, embedding and retrieval settings, read like the CLI reads them
+        let settings = wikify_core::WikifyConfig::load(crate::CONFIG_FILE, app_config.settings())
+            .map_err(|e| WebError::Config(format!("Invalid settings: {}", e)))?;
+        if let Some(settings) = settings {
+            app_config.apply_settings(&settings);
+        }
+        // The server can still list repositories and wikis without them
+        for issue in app_config.rag.diagnose() {
+            warn!("Configuration problem: {}", issue);
+        }
+        // Opt-in only, see wikify_applications::telemetry
+        if telemetry::telemetry().is_some() {
+            warn!("{}", telemetry::NOTICE);
+            telemetry::record(TelemetryEvent::ServerStarted);
+            telemetry::record(TelemetryEvent::providers(&app_config.rag));
+        }
+        app_config.repository.max_concurrent_indexing = config.max_concurrent_indexing;
+        app_config.repository.indexing_workers = config.indexing_workers;
+        app_config.repository.max_disk_usage_bytes =
+            config.max_disk_usage_mb.map(|mb| mb * 1024 * 1024);
+        app_config.usage.daily_budget = config.daily_budget;
+        app_config.usage.user_daily_budget = config.user_daily_budget;
+        app_config.usage.repository_daily_budget = config.repository_daily_budget;
+        if let Some(limits) = app_config.permissions.anonymous_throttle.as_mut() {
+            if let Some(queries) = config.anonymous_queries_per_hour {
+                limits.queries_per_hour = queries;
+            }
+            if let Some(repositories) = config.anonymous_max_repositories {
+                limits.max_repositories = repositories;
+            }
+            if let Some(size_mb) = config.anonymous_max_repository_size_mb {
+                limits.max_repository_size_mb = size_mb;
+            }
+        }
 
         // Initialize database if configured
         #[cfg(feature = "sqlite")]
@@ -173,8 +397,69 @@ impl AppState {
             None
         };
 
+        // Create the main application service, keeping chat sessions in the
+        // database when one is configured
+        let mut builder = WikifyApplication::builder(app_config);
+        #[cfg(feature = "sqlite")]
+        if let Some(sqlite) = database.as_ref().and_then(|db| db.pool().as_sqlite()) {
+            match SqliteSessionStorage::new(sqlite.clone()).await {
+                Ok(session_storage) => {
+                    builder = builder.with_session_storage(Arc::new(session_storage));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to create database session storage, falling back to memory: {}",
+                        e
+                    );
+                }
+            }
+            match SqliteUsageStorage::new(sqlite.clone()).await {
+                Ok(usage_storage) => {
+                    builder = builder.with_usage_storage(Arc::new(usage_storage));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to create database usage storage, falling back to memory: {}",
+                        e
+                    );
+                }
+            }
+            match SqliteWebhookStorage::new(sqlite.clone()).await {
+                Ok(webhook_storage) => {
+                    builder = builder.with_webhook_storage(Arc::new(webhook_storage));
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to create database webhook storage, falling back to memory: {}",
+                        e
+                    );
+                }
+            }
+        }
+        // With PostgreSQL, instances share repositories; sessions, usage and
+        // webhooks are still kept by each instance
+        #[cfg(feature = "postgres")]
+        if let Some(postgres) = database.as_ref().and_then(|db| db.pool().as_postgres()) {
+            let storage = PostgresRepositoryStorage::new(postgres.clone());
+            builder = builder.with_repository_storage(Arc::new(storage));
+            warn!("Chat sessions, usage and webhooks are kept in memory with PostgreSQL");
+        }
+        for export in config.usage_exports() {
+            builder = builder.with_usage_export(export);
+        }
+        if config.storage_gc_interval_secs.is_some() {
+            builder = builder.with_storage_gc(config.storage_gc());
+        }
+        let application = builder
+            .build()
+            .await
+            .map_err(|e| WebError::Config(format!("Failed to create application: {}", e)))?;
+
+        let wiki_service = WikiService::new()
+            .map_err(|e| WebError::Config(format!("Failed to create wiki service: {}", e)))?;
+
         // Create progress broadcaster with a buffer of 100 messages
-        let (progress_broadcaster, _) = broadcast::channel::<BroadcastMessage>(100);
+        let progress_broadcaster = ProgressBroadcaster::new(100);
 
         // Create user service with appropriate storage backend
         let user_service = {
@@ -208,6 +493,11 @@ impl AppState {
         // Create API key service
         let api_key_service = ApiKeyService::memory();
 
+        // Create OAuth service from the provider credentials in the environment
+        let public_url = format!("http://{}{}", config.address(), config.base_path);
+        let oauth_service = OAuthService::from_env(&public_url);
+        let oidc_service = OidcService::from_env(&public_url);
+
         let state = Self {
             config,
             application: Arc::new(application),
@@ -215,9 +505,15 @@ impl AppState {
             database,
             wiki_service: Arc::new(RwLock::new(wiki_service)),
             wiki_cache: Arc::new(RwLock::new(HashMap::new())),
+            wiki_generations: WikiGenerations::default(),
             progress_broadcaster,
             user_service,
             api_key_service,
+            oauth_service,
+            oidc_service,
+            mcp_sessions: McpSessions::default(),
+            #[cfg(feature = "websocket")]
+            websocket_connections: ConnectionCounter::default(),
         };
 
         info!("Application state initialized successfully");
@@ -231,7 +527,7 @@ impl AppState {
     ) -> PermissionContext {
         match self.config.permission_mode.as_deref() {
             Some("open") => PermissionContext::open(),
-            Some("restricted") => {
+            _ if self.config.requires_auth() => {
                 // Check for authentication headers
                 if let Some(user_id) = headers.get("x-user-id") {
                     let display_name = headers.get("x-user-name").cloned();
@@ -256,14 +552,15 @@ impl AppState {
 
     /// Create permission context for anonymous users
     pub fn create_anonymous_context(&self) -> PermissionContext {
-        match self.config.permission_mode.as_deref() {
-            Some("restricted") => PermissionContext::anonymous(
+        if self.config.requires_auth() {
+            PermissionContext::anonymous(
                 [wikify_applications::Permission::Query]
                     .into_iter()
                     .collect(),
                 wikify_applications::auth::permissions::ResourceLimits::anonymous(),
-            ),
-            _ => PermissionContext::open(),
+            )
+        } else {
+            PermissionContext::open()
         }
     }
 
@@ -278,7 +575,7 @@ impl AppState {
 
     /// Clean up stale data using application layer
     pub async fn cleanup_old_data(&self) {
-        // Note: This is now a no-op since we removed session management
+        // Note: This is a no-op, chat sessions are kept until their repository is deleted
         // Repository cleanup is handled by the application layer
     }
 
@@ -325,9 +622,52 @@ impl AppState {
     }
 
     /// Subscribe to progress updates
-    pub async fn subscribe_to_progress(
-        &self,
-    ) -> tokio::sync::broadcast::Receiver<BroadcastMessage> {
+    pub async fn subscribe_to_progress(&self) -> tokio::sync::broadcast::Receiver<ProgressEvent> {
         self.progress_broadcaster.subscribe()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_are_numbered_per_repository() {
+        let broadcaster = ProgressBroadcaster::new(16);
+        let mut receiver = broadcaster.subscribe();
+        for repository_id in ["repo-1", "repo-2", "repo-1"] {
+            let _ = broadcaster.send(BroadcastMessage::IndexingUpdate(
+                IndexingUpdate::WikiGenerationStarted {
+                    repository_id: repository_id.to_string(),
+                },
+            ));
+        }
+
+        let ids: Vec<Option<u64>> = (0..3)
+            .map(|_| receiver.try_recv().unwrap().event_id)
+            .collect();
+        assert_eq!(ids, vec![Some(1), Some(1), Some(2)]);
+
+        let missed = broadcaster.events_since("repo-1", 1);
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].event_id, Some(2));
+        assert!(broadcaster.events_since("repo-3", 0).is_empty());
+    }
+
+    #[test]
+    fn test_new_wiki_generation_cancels_previous_one() {
+        let generations = WikiGenerations::default();
+        let (first_id, first) = generations.start("repo-1");
+        let (second_id, second) = generations.start("repo-1");
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        // The replaced generation finishing must not forget the new one
+        generations.finish("repo-1", first_id);
+        assert!(generations.cancel("repo-1"));
+        assert!(second.is_cancelled());
+
+        generations.finish("repo-1", second_id);
+        assert!(!generations.cancel("repo-1"));
+    }
+}
@@ -0,0 +1,142 @@
+//! HTTPS without a reverse proxy
+//!
+//! The server either loads a certificate and key from PEM files or obtains
+//! a certificate from Let's Encrypt with ACME (TLS-ALPN-01, so only the
+//! HTTPS port has to be reachable). An optional plain HTTP listener
+//! redirects browsers to the HTTPS address.
+
+use crate::{WebConfig, WebError, WebResult};
+use axum::{
+    extract::Request,
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    routing::any,
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use futures_util::StreamExt;
+use rustls_acme::{caches::DirCache, AcmeConfig};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+/// Where ACME certificates are cached unless configured otherwise
+const DEFAULT_ACME_CACHE_DIR: &str = "./data/acme";
+
+/// Serve `app` over HTTPS on `listener` until the server fails
+pub async fn serve(config: &WebConfig, listener: TcpListener, app: Router) -> WebResult<()> {
+    let listener = listener.into_std().map_err(WebError::Server)?;
+    // Peer addresses identify anonymous clients for rate limiting
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    if !config.acme_domains.is_empty() {
+        let cache_dir = config
+            .acme_cache_dir
+            .as_deref()
+            .unwrap_or(DEFAULT_ACME_CACHE_DIR);
+        let mut acme = AcmeConfig::new(config.acme_domains.clone())
+            .cache(DirCache::new(cache_dir))
+            .directory_lets_encrypt(true);
+        if let Some(email) = &config.acme_email {
+            acme = acme.contact_push(format!("mailto:{}", email));
+        }
+        let mut state = acme.state();
+        let acceptor = state.axum_acceptor(state.default_rustls_config());
+
+        // Drives certificate orders and renewals
+        tokio::spawn(async move {
+            while let Some(event) = state.next().await {
+                match event {
+                    Ok(event) => info!("ACME: {:?}", event),
+                    Err(e) => error!("ACME error: {:?}", e),
+                }
+            }
+        });
+
+        info!(
+            "🔒 Serving HTTPS with Let's Encrypt certificates for {}",
+            config.acme_domains.join(", ")
+        );
+        return axum_server::from_tcp(listener)
+            .acceptor(acceptor)
+            .serve(service)
+            .await
+            .map_err(WebError::Server);
+    }
+
+    let (Some(cert), Some(key)) = (&config.tls_cert_path, &config.tls_key_path) else {
+        return Err(WebError::Config(
+            "TLS needs both a certificate and a key file".to_string(),
+        ));
+    };
+    let rustls_config = RustlsConfig::from_pem_file(cert, key)
+        .await
+        .map_err(|e| WebError::Config(format!("Failed to load TLS certificate: {}", e)))?;
+
+    info!("🔒 Serving HTTPS with certificate {}", cert);
+    axum_server::from_tcp_rustls(listener, rustls_config)
+        .serve(service)
+        .await
+        .map_err(WebError::Server)
+}
+
+/// Answer plain HTTP on `listener` with redirects to HTTPS on `https_port`
+pub async fn serve_redirect(listener: TcpListener, https_port: u16) -> std::io::Result<()> {
+    let app = Router::new().fallback(any(move |request: Request| async move {
+        redirect_to_https(&request, https_port)
+    }));
+    axum::serve(listener, app).await
+}
+
+fn redirect_to_https(request: &Request, https_port: u16) -> Response {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    match host.and_then(|host| https_url(host, https_port, request.uri())) {
+        Some(url) => Redirect::permanent(&url).into_response(),
+        None => (StatusCode::BAD_REQUEST, "Missing Host header").into_response(),
+    }
+}
+
+/// HTTPS URL of a request made to `host` over plain HTTP
+fn https_url(host: &str, https_port: u16, uri: &Uri) -> Option<String> {
+    // Drop the port of the HTTP listener, keeping IPv6 literals intact
+    let hostname = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') && port.parse::<u16>().is_ok() => name,
+        _ => host,
+    };
+    if hostname.is_empty() {
+        return None;
+    }
+
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+    Some(if https_port == 443 {
+        format!("https://{}{}", hostname, path)
+    } else {
+        format!("https://{}:{}{}", hostname, https_port, path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_https_url() {
+        let uri: Uri = "/api/health?verbose=true".parse().unwrap();
+        assert_eq!(
+            https_url("wikify.example.com", 443, &uri).as_deref(),
+            Some("https://wikify.example.com/api/health?verbose=true")
+        );
+        assert_eq!(
+            https_url("wikify.example.com:8080", 8443, &uri).as_deref(),
+            Some("https://wikify.example.com:8443/api/health?verbose=true")
+        );
+        assert_eq!(
+            https_url("[::1]:80", 443, &"/".parse().unwrap()).as_deref(),
+            Some("https://[::1]/")
+        );
+        assert_eq!(https_url("", 443, &uri), None);
+    }
+}
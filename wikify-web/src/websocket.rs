@@ -5,21 +5,110 @@
 //! - Wiki generation progress
 //! - Repository indexing progress
 //! - System notifications
+//!
+//! Indexing, wiki and research updates carry an `event_id` that increases per
+//! repository. A client that reconnects with `?repository_id=...&since=<id>`
+//! first receives the events of that repository it missed.
+//!
+//! Streaming chat has its own protocol on `/ws/chat`, see [`chat`].
+//!
+//! The server pings every connection every 30 seconds and closes those it
+//! has heard nothing from, not even a pong, for 90 seconds, so connections
+//! whose client vanished do not keep their broadcast subscription forever.
 
+pub mod chat;
+
+use crate::state::{BroadcastMessage, ProgressEvent};
 use crate::AppState;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, State,
     },
     response::Response,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
 use tracing::{debug, error, info, warn};
 use uuid;
 
+/// Time between the pings sent to each client
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Connections the client sent nothing on for this long are closed
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Counts the open WebSocket connections
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionCounter {
+    open: Arc<AtomicUsize>,
+}
+
+impl ConnectionCounter {
+    /// Count a new connection until the returned guard is dropped
+    pub fn open(&self) -> ConnectionGuard {
+        self.open.fetch_add(1, Ordering::Relaxed);
+        ConnectionGuard {
+            open: self.open.clone(),
+        }
+    }
+
+    /// Number of open connections
+    pub fn count(&self) -> usize {
+        self.open.load(Ordering::Relaxed)
+    }
+}
+
+/// Keeps a connection counted while it is open
+#[derive(Debug)]
+pub struct ConnectionGuard {
+    open: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.open.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Pings a client regularly and notices when it stopped answering
+pub(crate) struct Heartbeat {
+    interval: Interval,
+    last_seen: Instant,
+}
+
+impl Heartbeat {
+    pub(crate) fn new() -> Self {
+        let mut interval =
+            tokio::time::interval_at(Instant::now() + HEARTBEAT_INTERVAL, HEARTBEAT_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            interval,
+            last_seen: Instant::now(),
+        }
+    }
+
+    /// Note that the client sent something
+    pub(crate) fn seen(&mut self) {
+        self.last_seen = Instant::now();
+    }
+
+    /// Wait until the next ping is due
+    pub(crate) async fn tick(&mut self) {
+        self.interval.tick().await;
+    }
+
+    /// Whether the client has been silent for longer than [`IDLE_TIMEOUT`]
+    pub(crate) fn is_idle(&self) -> bool {
+        self.last_seen.elapsed() >= IDLE_TIMEOUT
+    }
+}
+
 /// Error types for unified error handling
 #[derive(Debug, Clone)]
 pub enum ErrorType {
@@ -86,6 +175,7 @@ pub enum WsMessage {
         pages_count: usize,
         sections_count: usize,
         metadata: Option<WikiMetadata>,
+        quality: Option<wikify_wiki::QualityReport>,
         timestamp: chrono::DateTime<chrono::Utc>,
         id: Option<String>,
     },
@@ -132,6 +222,13 @@ pub enum WsMessage {
         timestamp: chrono::DateTime<chrono::Utc>,
         id: Option<String>,
     },
+    /// Indexing cancelled
+    IndexCancelled {
+        repository_id: String,
+        progress: f64,
+        timestamp: chrono::DateTime<chrono::Utc>,
+        id: Option<String>,
+    },
     /// Research started
     ResearchStart {
         repository_id: String,
@@ -181,6 +278,15 @@ pub enum WsMessage {
     Pong {
         timestamp: chrono::DateTime<chrono::Utc>,
     },
+    /// Follow the events of these repositories
+    Subscribe { repository_ids: Vec<String> },
+    /// Stop following the events of these repositories
+    Unsubscribe { repository_ids: Vec<String> },
+    /// Repositories the connection now follows, `None` meaning all of them
+    Subscribed {
+        repository_ids: Option<Vec<String>>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
     /// General system error
     Error {
         message: String,
@@ -191,6 +297,36 @@ pub enum WsMessage {
     },
 }
 
+impl WsMessage {
+    /// Repository the message is about, if any
+    pub fn repository_id(&self) -> Option<&str> {
+        match self {
+            WsMessage::Chat { repository_id, .. }
+            | WsMessage::ChatResponse { repository_id, .. }
+            | WsMessage::ChatError { repository_id, .. }
+            | WsMessage::WikiGenerate { repository_id, .. }
+            | WsMessage::WikiProgress { repository_id, .. }
+            | WsMessage::WikiComplete { repository_id, .. }
+            | WsMessage::WikiError { repository_id, .. }
+            | WsMessage::IndexStart { repository_id, .. }
+            | WsMessage::IndexProgress { repository_id, .. }
+            | WsMessage::IndexComplete { repository_id, .. }
+            | WsMessage::IndexError { repository_id, .. }
+            | WsMessage::IndexCancelled { repository_id, .. }
+            | WsMessage::ResearchStart { repository_id, .. }
+            | WsMessage::ResearchProgress { repository_id, .. }
+            | WsMessage::ResearchComplete { repository_id, .. }
+            | WsMessage::ResearchError { repository_id, .. } => Some(repository_id),
+            WsMessage::Ping { .. }
+            | WsMessage::Pong { .. }
+            | WsMessage::Subscribe { .. }
+            | WsMessage::Unsubscribe { .. }
+            | WsMessage::Subscribed { .. }
+            | WsMessage::Error { .. } => None,
+        }
+    }
+}
+
 // Use the unified SourceDocument from handlers::types
 use crate::handlers::types::SourceDocument;
 
@@ -270,15 +406,208 @@ where
     create_fn(id)
 }
 
+/// Query parameters of a client resuming after a disconnect
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+pub struct ResumeQuery {
+    /// Repository whose missed events are replayed. On `/ws/index` it is
+    /// also the repository the connection follows.
+    pub repository_id: Option<String>,
+    /// Last event ID the client received for that repository
+    pub since: Option<u64>,
+}
+
 /// Unified WebSocket handler for all real-time communication
-pub async fn unified_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
-    ws.on_upgrade(move |socket| handle_unified_socket(socket, state))
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "WebSocket",
+    summary = "Open the real-time event stream",
+    description = "Upgrade to a WebSocket receiving indexing, wiki generation and research progress. Messages are described in the AsyncAPI document at `/api-docs/asyncapi.yaml`.",
+    params(ResumeQuery),
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket upgrade request")
+    )
+)]
+pub async fn unified_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(resume): Query<ResumeQuery>,
+) -> Response {
+    ws.on_upgrade(move |socket| {
+        handle_unified_socket(socket, state, resume, Subscriptions::All, |_| true)
+    })
+}
+
+/// Wiki WebSocket handler: same protocol as the unified endpoint, but only
+/// wiki generation updates are broadcast to the client
+#[utoipa::path(
+    get,
+    path = "/ws/wiki",
+    tag = "WebSocket",
+    summary = "Open the wiki generation event stream",
+    description = "Same protocol as `/ws`, limited to wiki generation progress, completion and errors.",
+    params(ResumeQuery),
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket upgrade request")
+    )
+)]
+pub async fn wiki_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(resume): Query<ResumeQuery>,
+) -> Response {
+    ws.on_upgrade(move |socket| {
+        handle_unified_socket(socket, state, resume, Subscriptions::All, is_wiki_message)
+    })
+}
+
+/// Indexing WebSocket handler: only indexing updates of the repositories the
+/// client follows are broadcast to it
+#[utoipa::path(
+    get,
+    path = "/ws/index",
+    tag = "WebSocket",
+    summary = "Open the indexing event stream of repositories",
+    description = "Same protocol as `/ws`, limited to indexing progress of the repository given by `repository_id` and of those followed with `Subscribe` messages.",
+    params(ResumeQuery),
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket upgrade request")
+    )
+)]
+pub async fn index_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Query(resume): Query<ResumeQuery>,
+) -> Response {
+    let subscriptions = Subscriptions::Repositories(resume.repository_id.iter().cloned().collect());
+    ws.on_upgrade(move |socket| {
+        handle_unified_socket(socket, state, resume, subscriptions, is_index_message)
+    })
+}
+
+/// Repositories whose broadcast events a connection receives
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Subscriptions {
+    /// Every repository, until the client subscribes to some
+    All,
+    /// Only these repositories
+    Repositories(HashSet<String>),
+}
+
+impl Subscriptions {
+    /// Whether a broadcast message is delivered to the connection. Messages
+    /// that are not about a repository, such as system errors, always are.
+    fn includes(&self, message: &BroadcastMessage) -> bool {
+        let repository_id = match message {
+            BroadcastMessage::IndexingUpdate(update) => Some(update.repository_id()),
+            BroadcastMessage::WebSocketMessage { message } => message.repository_id(),
+        };
+        match (self, repository_id) {
+            (Subscriptions::Repositories(ids), Some(id)) => ids.contains(id),
+            _ => true,
+        }
+    }
+
+    /// Follow more repositories; a connection following all of them then
+    /// only follows these
+    fn subscribe(&mut self, repository_ids: Vec<String>) {
+        match self {
+            Subscriptions::All => {
+                *self = Subscriptions::Repositories(repository_ids.into_iter().collect())
+            }
+            Subscriptions::Repositories(ids) => ids.extend(repository_ids),
+        }
+    }
+
+    /// Stop following repositories. Has no effect while following all of them.
+    fn unsubscribe(&mut self, repository_ids: &[String]) {
+        if let Subscriptions::Repositories(ids) = self {
+            for id in repository_ids {
+                ids.remove(id);
+            }
+        }
+    }
+
+    /// Acknowledgement listing the followed repositories
+    fn to_message(&self) -> WsMessage {
+        let repository_ids = match self {
+            Subscriptions::All => None,
+            Subscriptions::Repositories(ids) => {
+                let mut ids: Vec<String> = ids.iter().cloned().collect();
+                ids.sort();
+                Some(ids)
+            }
+        };
+        WsMessage::Subscribed {
+            repository_ids,
+            timestamp: chrono::Utc::now(),
+        }
+    }
+}
+
+/// Whether a message belongs to wiki generation
+fn is_wiki_message(message: &WsMessage) -> bool {
+    matches!(
+        message,
+        WsMessage::WikiProgress { .. }
+            | WsMessage::WikiComplete { .. }
+            | WsMessage::WikiError { .. }
+    )
+}
+
+/// Whether a message belongs to repository indexing
+fn is_index_message(message: &WsMessage) -> bool {
+    matches!(
+        message,
+        WsMessage::IndexStart { .. }
+            | WsMessage::IndexProgress { .. }
+            | WsMessage::IndexComplete { .. }
+            | WsMessage::IndexError { .. }
+            | WsMessage::IndexCancelled { .. }
+    )
+}
+
+/// Serialize a broadcast message, tagged with its event ID so the client can
+/// resume from it
+fn serialize_event(message: &WsMessage, event_id: Option<u64>) -> serde_json::Result<String> {
+    let mut value = serde_json::to_value(message)?;
+    if let (Some(event_id), Some(object)) = (event_id, value.as_object_mut()) {
+        object.insert("event_id".to_string(), event_id.into());
+    }
+    serde_json::to_string(&value)
+}
+
+/// Whether a live event was already sent while replaying missed events
+fn is_replayed(replayed: &Option<(String, u64)>, event: &ProgressEvent) -> bool {
+    let (Some((repository_id, last_id)), Some(event_id)) = (replayed, event.event_id) else {
+        return false;
+    };
+    event_id <= *last_id
+        && matches!(
+            &event.message,
+            BroadcastMessage::IndexingUpdate(update) if update.repository_id() == repository_id
+        )
 }
 
 /// Unified WebSocket connection handler
-/// Handles all types of WebSocket communication: chat, wiki, indexing, and progress updates
-async fn handle_unified_socket(mut socket: WebSocket, state: AppState) {
-    info!("New unified WebSocket connection established");
+/// Handles all types of WebSocket communication: chat, wiki, indexing, and progress updates.
+/// Broadcast messages are only forwarded when they are about a followed
+/// repository and `broadcast_filter` accepts them.
+async fn handle_unified_socket(
+    mut socket: WebSocket,
+    state: AppState,
+    resume: ResumeQuery,
+    mut subscriptions: Subscriptions,
+    broadcast_filter: fn(&WsMessage) -> bool,
+) {
+    let connection = state.websocket_connections.open();
+    info!(
+        "New unified WebSocket connection established ({} open)",
+        state.websocket_connections.count()
+    );
 
     // Send welcome message
     let welcome = create_message_with_id(|id| {
@@ -304,16 +633,52 @@ async fn handle_unified_socket(mut socket: WebSocket, state: AppState) {
     let mut progress_receiver = state.progress_broadcaster.subscribe();
     info!("Subscribed to progress broadcaster");
 
+    // Replay the events a reconnecting client missed. Subscribing first
+    // means nothing falls between the replay and the live updates.
+    let mut replayed = None;
+    if let (Some(repository_id), Some(since)) = (resume.repository_id, resume.since) {
+        let events = state
+            .progress_broadcaster
+            .events_since(&repository_id, since);
+        info!(
+            "Replaying {} events of repository {} after event {}",
+            events.len(),
+            repository_id,
+            since
+        );
+
+        let mut last_id = since;
+        for event in events {
+            last_id = event.event_id.unwrap_or(last_id);
+            let BroadcastMessage::IndexingUpdate(update) = event.message else {
+                continue;
+            };
+            let Some(ws_msg) = convert_update_to_message(update).filter(broadcast_filter) else {
+                continue;
+            };
+            if let Ok(msg) = serialize_event(&ws_msg, event.event_id) {
+                if socket.send(Message::Text(msg.into())).await.is_err() {
+                    return;
+                }
+            }
+        }
+        replayed = Some((repository_id, last_id));
+    }
+
     // Track sent messages to avoid duplicates
     let mut sent_messages = HashSet::new();
+    let mut heartbeat = Heartbeat::new();
 
     loop {
         tokio::select! {
             // Handle incoming messages from client
             msg_result = socket.recv() => {
+                if let Some(Ok(_)) = msg_result {
+                    heartbeat.seen();
+                }
                 match msg_result {
                     Some(Ok(Message::Text(text))) => {
-                        if let Err(e) = handle_unified_message(&mut socket, &state, &text).await {
+                        if let Err(e) = handle_unified_message(&mut socket, &state, &mut subscriptions, &text).await {
                             error!("Error handling unified message: {}", e);
                             break;
                         }
@@ -334,10 +699,33 @@ async fn handle_unified_socket(mut socket: WebSocket, state: AppState) {
                 }
             }
 
+            // Ping the client, and give up on it once it stopped answering
+            _ = heartbeat.tick() => {
+                if heartbeat.is_idle() {
+                    info!("Closing idle unified WebSocket connection");
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+                if socket.send(Message::Ping(Default::default())).await.is_err() {
+                    info!("Failed to ping unified WebSocket client, connection closed");
+                    break;
+                }
+            }
+
             // Handle broadcast messages from broadcaster
             broadcast_result = progress_receiver.recv() => {
                 match broadcast_result {
-                    Ok(broadcast_msg) => {
+                    Ok(event) => {
+                        // Skip events already sent while replaying
+                        if is_replayed(&replayed, &event) {
+                            continue;
+                        }
+                        // Skip events of repositories the client does not follow
+                        if !subscriptions.includes(&event.message) {
+                            continue;
+                        }
+                        let broadcast_msg = event.message;
+
                         // Create unique message ID to prevent duplicates
                         let message_id = match &broadcast_msg {
                             crate::state::BroadcastMessage::IndexingUpdate(update) => {
@@ -354,6 +742,9 @@ async fn handle_unified_socket(mut socket: WebSocket, state: AppState) {
                                     crate::state::IndexingUpdate::Error { repository_id, .. } => {
                                         format!("error-{}", repository_id)
                                     }
+                                    crate::state::IndexingUpdate::Cancelled { repository_id, .. } => {
+                                        format!("cancelled-{}", repository_id)
+                                    }
                                     crate::state::IndexingUpdate::ResearchStarted { repository_id, research_id, .. } => {
                                         format!("research-started-{}-{}", repository_id, research_id)
                                     }
@@ -397,8 +788,8 @@ async fn handle_unified_socket(mut socket: WebSocket, state: AppState) {
                             }
                         };
 
-                        if let Some(ws_msg) = ws_message {
-                            if let Ok(msg) = serde_json::to_string(&ws_msg) {
+                        if let Some(ws_msg) = ws_message.filter(broadcast_filter) {
+                            if let Ok(msg) = serialize_event(&ws_msg, event.event_id) {
                                 if socket.send(Message::Text(msg.into())).await.is_err() {
                                     error!("Failed to send broadcast message, connection closed");
                                     break;
@@ -419,13 +810,20 @@ async fn handle_unified_socket(mut socket: WebSocket, state: AppState) {
         }
     }
 
-    info!("Unified WebSocket connection ended");
+    drop(progress_receiver);
+    drop(connection);
+    info!(
+        "Unified WebSocket connection ended ({} open, {} progress subscribers)",
+        state.websocket_connections.count(),
+        state.progress_broadcaster.receiver_count()
+    );
 }
 
 /// Handle unified WebSocket messages (chat, wiki generation, etc.)
 async fn handle_unified_message(
     socket: &mut WebSocket,
     state: &AppState,
+    subscriptions: &mut Subscriptions,
     text: &str,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let message: WsMessage = serde_json::from_str(text)?;
@@ -468,6 +866,14 @@ async fn handle_unified_message(
         WsMessage::Pong { .. } => {
             // Acknowledge pong, no action needed (no logging to avoid spam)
         }
+        WsMessage::Subscribe { repository_ids } => {
+            subscriptions.subscribe(repository_ids);
+            send_message(socket, subscriptions.to_message()).await?;
+        }
+        WsMessage::Unsubscribe { repository_ids } => {
+            subscriptions.unsubscribe(&repository_ids);
+            send_message(socket, subscriptions.to_message()).await?;
+        }
         _ => {
             warn!("Received unsupported message type in unified handler");
         }
@@ -573,6 +979,15 @@ fn convert_update_to_message(update: crate::state::IndexingUpdate) -> Option<WsM
             timestamp: chrono::Utc::now(),
             id: None,
         }),
+        crate::state::IndexingUpdate::Cancelled {
+            repository_id,
+            progress,
+        } => Some(WsMessage::IndexCancelled {
+            repository_id,
+            progress,
+            timestamp: chrono::Utc::now(),
+            id: Some(generate_message_id()),
+        }),
         crate::state::IndexingUpdate::WikiGenerationProgress {
             repository_id,
             stage,
@@ -598,6 +1013,7 @@ fn convert_update_to_message(update: crate::state::IndexingUpdate) -> Option<WsM
             repository_id,
             pages_count,
             sections_count,
+            quality,
             ..
         } => {
             Some(WsMessage::WikiComplete {
@@ -610,6 +1026,7 @@ fn convert_update_to_message(update: crate::state::IndexingUpdate) -> Option<WsM
                     total_tokens: 0,      // TODO: Track actual token usage
                     model_used: "default".to_string(),
                 }),
+                quality,
                 timestamp: chrono::Utc::now(),
                 id: None,
             })
@@ -778,11 +1195,14 @@ async fn handle_chat_request(
 }
 
 /// Handle wiki generation requests
+///
+/// Generation runs in the background; progress reaches this socket through the
+/// progress broadcaster like any other wiki generation.
 async fn handle_wiki_request(
     socket: &mut WebSocket,
     state: &AppState,
     repository_id: String,
-    _config: WikiConfig,
+    config: WikiConfig,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!(
         "Processing wiki generation request for repository: {}",
@@ -792,76 +1212,35 @@ async fn handle_wiki_request(
     // Create a local permission context for system operations
     let permission_context = wikify_applications::PermissionContext::local();
 
-    // Get repository information first
-    let repository = match state
+    // Verify the repository exists first
+    if let Err(e) = state
         .application
         .get_repository(&permission_context, &repository_id)
         .await
     {
-        Ok(repo) => repo,
-        Err(e) => {
-            error!("Failed to get repository info: {}", e);
-            send_error_response(
-                socket,
-                repository_id,
-                format!("Repository not found: {}", e),
-                ErrorType::Wiki,
-            )
-            .await?;
-            return Ok(());
-        }
-    };
-
-    // Send wiki generation started message
-    let start_msg = create_message_with_id(|id| WsMessage::WikiProgress {
-        repository_id: repository_id.clone(),
-        progress: 0.1,
-        current_step: "Initializing wiki generation...".to_string(),
-        total_steps: 3,
-        completed_steps: 0,
-        step_details: Some("Setting up wiki generation environment".to_string()),
-        timestamp: chrono::Utc::now(),
-        id: Some(id),
-    });
-    send_message(socket, start_msg).await?;
-
-    // Generate wiki using wiki service
-    let mut wiki_service = state.wiki_service.write().await;
-    let wiki_config = wikify_wiki::WikiConfig::default();
+        error!("Failed to get repository info: {}", e);
+        send_error_response(
+            socket,
+            repository_id,
+            format!("Repository not found: {}", e),
+            ErrorType::Wiki,
+        )
+        .await?;
+        return Ok(());
+    }
 
-    match wiki_service
-        .generate_wiki(&repository.url, &wiki_config)
-        .await
-    {
-        Ok(wiki_structure) => {
-            // Send completion message
-            let complete_msg = create_message_with_id(|id| WsMessage::WikiComplete {
-                repository_id: repository_id.clone(),
-                wiki_id: format!("wiki-{}", chrono::Utc::now().timestamp()),
-                pages_count: wiki_structure.pages.len(),
-                sections_count: wiki_structure.pages.len(), // Use pages count as sections count for now
-                metadata: Some(WikiMetadata {
-                    generation_time: 2.0, // TODO: Track actual generation time
-                    total_tokens: 1500,   // TODO: Track actual token usage
-                    model_used: "default".to_string(),
-                }),
-                timestamp: chrono::Utc::now(),
-                id: Some(id),
-            });
-            send_message(socket, complete_msg).await?;
-        }
-        Err(e) => {
-            error!("Failed to generate wiki: {}", e);
-            send_error_response(
-                socket,
-                repository_id,
-                format!("Wiki generation failed: {}", e),
-                ErrorType::Wiki,
-            )
-            .await?;
-        }
+    let mut wiki_config = wikify_wiki::WikiConfig::default();
+    if let Some(language) = config.language {
+        wiki_config.language = language;
     }
 
+    crate::handlers::wiki::spawn_wiki_generation(
+        state.clone(),
+        permission_context,
+        repository_id,
+        wiki_config,
+    );
+
     Ok(())
 }
 
@@ -891,3 +1270,47 @@ pub async fn broadcast_message(state: &AppState, message: WsMessage) {
         tracing::warn!("Failed to broadcast WebSocket message: {}", e);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::IndexingUpdate;
+
+    fn started(repository_id: &str) -> BroadcastMessage {
+        BroadcastMessage::IndexingUpdate(IndexingUpdate::Started {
+            repository_id: repository_id.to_string(),
+            total_files: None,
+            estimated_duration: None,
+        })
+    }
+
+    #[test]
+    fn test_subscriptions_filter_repositories() {
+        let mut subscriptions = Subscriptions::All;
+        assert!(subscriptions.includes(&started("a")));
+
+        subscriptions.subscribe(vec!["a".to_string(), "b".to_string()]);
+        assert!(subscriptions.includes(&started("a")));
+        assert!(subscriptions.includes(&started("b")));
+        assert!(!subscriptions.includes(&started("c")));
+
+        subscriptions.unsubscribe(&["a".to_string()]);
+        assert!(!subscriptions.includes(&started("a")));
+        assert!(matches!(
+            subscriptions.to_message(),
+            WsMessage::Subscribed { repository_ids: Some(ids), .. } if ids == ["b"]
+        ));
+
+        // Messages about no repository reach every connection
+        let error = BroadcastMessage::WebSocketMessage {
+            message: WsMessage::Error {
+                message: "shutting down".to_string(),
+                code: None,
+                details: None,
+                timestamp: chrono::Utc::now(),
+                id: None,
+            },
+        };
+        assert!(Subscriptions::Repositories(HashSet::new()).includes(&error));
+    }
+}
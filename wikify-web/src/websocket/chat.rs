@@ -0,0 +1,333 @@
+//! Streaming chat over WebSocket
+//!
+//! `/ws/chat` speaks a small JSON protocol. Every question carries an id
+//! chosen by the client, and every reply echoes it, so several questions can
+//! be answered on one connection at the same time:
+//!
+//! - client: `query`, `cancel`, `ping`
+//! - server: `chunk`, `sources`, `done`, `error`, `cancelled`, `pong`
+
+use crate::auth::ModeAwareUser;
+use crate::handlers::chat::{record_query, to_source_documents};
+use crate::handlers::types::SourceDocument;
+use crate::websocket::Heartbeat;
+use crate::AppState;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use wikify_applications::{PermissionContext, QueryChunkType, RepositoryQuery};
+
+/// Questions a single connection may have in flight at once
+const MAX_CONCURRENT_QUERIES: usize = 8;
+
+/// Messages sent by the client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatClientMessage {
+    /// Ask a question about a repository
+    Query {
+        id: String,
+        repository_id: String,
+        question: String,
+        max_results: Option<usize>,
+    },
+    /// Stop answering a question
+    Cancel { id: String },
+    /// Heartbeat
+    Ping,
+}
+
+/// Messages sent by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatServerMessage {
+    /// Next part of an answer
+    Chunk { id: String, content: String },
+    /// Sources the answer is based on, sent right before `done`
+    Sources {
+        id: String,
+        sources: Vec<SourceDocument>,
+    },
+    /// The answer is complete
+    Done {
+        id: String,
+        repository_id: String,
+        metadata: HashMap<String, String>,
+        query_id: Option<String>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+    /// A question failed, or a message could not be handled when `id` is unset
+    Error { id: Option<String>, message: String },
+    /// A question was cancelled by the client
+    Cancelled { id: String },
+    /// Heartbeat response
+    Pong,
+}
+
+/// Chat WebSocket handler
+#[utoipa::path(
+    get,
+    path = "/ws/chat",
+    tag = "WebSocket",
+    summary = "Open a streaming chat connection",
+    description = "Upgrade to a WebSocket for asking several questions on one connection, with answers streamed as they are generated. Authentication is required outside open mode. Messages are described in the AsyncAPI document at `/api-docs/asyncapi.yaml`.",
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket upgrade request"),
+        (status = 401, description = "Authentication required")
+    )
+)]
+pub async fn chat_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    ModeAwareUser(user): ModeAwareUser,
+) -> Response {
+    let context = user.to_permission_context();
+    ws.on_upgrade(move |socket| handle_chat_socket(socket, state, context))
+}
+
+/// Chat connection loop
+///
+/// Each question is answered by its own task. The tasks share one channel
+/// back to this loop, which is the only writer to the socket.
+async fn handle_chat_socket(mut socket: WebSocket, state: AppState, context: PermissionContext) {
+    let _connection = state.websocket_connections.open();
+    info!(
+        "New chat WebSocket connection established ({} open)",
+        state.websocket_connections.count()
+    );
+
+    let (sender, mut outgoing) = mpsc::unbounded_channel::<ChatServerMessage>();
+    let mut queries: HashMap<String, JoinHandle<()>> = HashMap::new();
+    let mut heartbeat = Heartbeat::new();
+
+    loop {
+        tokio::select! {
+            msg_result = socket.recv() => {
+                if let Some(Ok(_)) = msg_result {
+                    heartbeat.seen();
+                }
+                match msg_result {
+                    Some(Ok(Message::Text(text))) => {
+                        handle_client_message(&state, &context, &sender, &mut queries, &text);
+                    }
+                    Some(Ok(Message::Close(_))) | None => {
+                        info!("Chat WebSocket connection closed");
+                        break;
+                    }
+                    Some(Err(e)) => {
+                        warn!("Chat WebSocket error: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            _ = heartbeat.tick() => {
+                if heartbeat.is_idle() {
+                    info!("Closing idle chat WebSocket connection");
+                    let _ = socket.send(Message::Close(None)).await;
+                    break;
+                }
+                if socket.send(Message::Ping(Default::default())).await.is_err() {
+                    warn!("Failed to ping chat client, connection closed");
+                    break;
+                }
+            }
+
+            Some(message) = outgoing.recv() => {
+                let Ok(text) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    warn!("Failed to send chat message, connection closed");
+                    break;
+                }
+            }
+        }
+    }
+
+    // Dropping the chunk receivers stops the queries still running
+    for task in queries.into_values() {
+        task.abort();
+    }
+}
+
+/// Handle one message from the client
+fn handle_client_message(
+    state: &AppState,
+    context: &PermissionContext,
+    sender: &UnboundedSender<ChatServerMessage>,
+    queries: &mut HashMap<String, JoinHandle<()>>,
+    text: &str,
+) {
+    let message = match serde_json::from_str::<ChatClientMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            let _ = sender.send(ChatServerMessage::Error {
+                id: None,
+                message: format!("Invalid message: {}", e),
+            });
+            return;
+        }
+    };
+
+    match message {
+        ChatClientMessage::Query {
+            id,
+            repository_id,
+            question,
+            max_results,
+        } => {
+            queries.retain(|_, task| !task.is_finished());
+            let rejection = if queries.contains_key(&id) {
+                Some(format!("Question {} is already being answered", id))
+            } else if queries.len() >= MAX_CONCURRENT_QUERIES {
+                Some(format!(
+                    "At most {} questions can be answered at once",
+                    MAX_CONCURRENT_QUERIES
+                ))
+            } else {
+                None
+            };
+            if let Some(message) = rejection {
+                let _ = sender.send(ChatServerMessage::Error {
+                    id: Some(id),
+                    message,
+                });
+                return;
+            }
+
+            debug!("Answering question {} for repository {}", id, repository_id);
+            let query = RepositoryQuery {
+                question,
+                max_results: Some(max_results.unwrap_or(5)),
+                parameters: None,
+            };
+            let task = tokio::spawn(answer_question(
+                state.clone(),
+                context.clone(),
+                id.clone(),
+                repository_id,
+                query,
+                sender.clone(),
+            ));
+            queries.insert(id, task);
+        }
+        ChatClientMessage::Cancel { id } => {
+            if let Some(task) = queries.remove(&id) {
+                task.abort();
+                let _ = sender.send(ChatServerMessage::Cancelled { id });
+            }
+        }
+        ChatClientMessage::Ping => {
+            let _ = sender.send(ChatServerMessage::Pong);
+        }
+    }
+}
+
+/// Stream the answer to one question to the connection
+async fn answer_question(
+    state: AppState,
+    context: PermissionContext,
+    id: String,
+    repository_id: String,
+    query: RepositoryQuery,
+    sender: UnboundedSender<ChatServerMessage>,
+) {
+    let question = query.question.clone();
+    let mut chunks = match state
+        .application
+        .stream_query_repository(&context, &repository_id, query)
+        .await
+    {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            let _ = sender.send(ChatServerMessage::Error {
+                id: Some(id),
+                message: e.to_string(),
+            });
+            return;
+        }
+    };
+
+    let mut answer = String::new();
+    while let Some(chunk) = chunks.recv().await {
+        match chunk.chunk_type {
+            QueryChunkType::Content | QueryChunkType::Source => {
+                answer.push_str(&chunk.content);
+                let message = ChatServerMessage::Chunk {
+                    id: id.clone(),
+                    content: chunk.content,
+                };
+                if sender.send(message).is_err() {
+                    // The connection is gone
+                    return;
+                }
+            }
+            QueryChunkType::Complete => {
+                let _ = sender.send(ChatServerMessage::Sources {
+                    id: id.clone(),
+                    sources: to_source_documents(chunk.sources.unwrap_or_default()),
+                });
+                let query_id = record_query(&state, &repository_id, &question, &answer).await;
+                let _ = sender.send(ChatServerMessage::Done {
+                    id,
+                    repository_id,
+                    metadata: chunk.metadata.unwrap_or_default(),
+                    query_id,
+                    timestamp: chrono::Utc::now(),
+                });
+                return;
+            }
+            QueryChunkType::Error => {
+                let _ = sender.send(ChatServerMessage::Error {
+                    id: Some(id),
+                    message: chunk.content,
+                });
+                return;
+            }
+        }
+    }
+
+    let _ = sender.send(ChatServerMessage::Error {
+        id: Some(id),
+        message: "Query ended unexpectedly".to_string(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_messages_are_tagged() {
+        let message: ChatClientMessage = serde_json::from_str(
+            r#"{"type":"query","id":"q1","repository_id":"repo-1","question":"How?"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            message,
+            ChatClientMessage::Query { ref id, max_results: None, .. } if id == "q1"
+        ));
+
+        let chunk = serde_json::to_value(ChatServerMessage::Chunk {
+            id: "q1".to_string(),
+            content: "Hello".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            chunk,
+            serde_json::json!({ "type": "chunk", "id": "q1", "content": "Hello" })
+        );
+    }
+}
@@ -29,6 +29,7 @@ async fn test_api_endpoints_comprehensive() {
     test_wiki_endpoints().await;
 
     // 测试研究端点
+    #[cfg(feature = "research")]
     test_research_endpoints().await;
 
     println!("✅ 所有API端点测试完成！");
@@ -220,6 +221,58 @@ async fn test_repository_endpoints() {
     println!("✅ 仓库管理端点测试完成");
 }
 
+/// 测试索引任务队列端点
+#[tokio::test]
+async fn test_jobs_endpoint() {
+    let app = spawn_app_with_mode("open").await;
+
+    let response = app.get_jobs().await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: serde_json::Value = response.json().await.unwrap();
+    let jobs = body["jobs"].as_array().expect("jobs should be an array");
+    assert_eq!(
+        jobs.len() as u64,
+        body["running"].as_u64().unwrap() + body["queued"].as_u64().unwrap()
+    );
+}
+
+/// 测试取消索引端点
+#[tokio::test]
+async fn test_cancel_indexing_endpoint() {
+    let app = spawn_app_with_mode("open").await;
+
+    // 不存在的仓库
+    let response = app.post_cancel_indexing("no-such-repository").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // 索引中的仓库可以取消，未在索引的仓库返回400
+    let init_request = json!({
+        "repository": "https://github.com/octocat/Hello-World",
+        "repo_type": "github",
+        "auto_generate_wiki": false
+    });
+    let init_response = app.post_repositories(&init_request).await;
+    if init_response.status() != StatusCode::OK {
+        println!(
+            "⚠️  仓库初始化失败，跳过取消测试: {}",
+            init_response.status()
+        );
+        return;
+    }
+    let init_data: serde_json::Value = init_response.json().await.unwrap();
+    let repo_id = init_data["repository_id"].as_str().unwrap();
+
+    let response = app.post_cancel_indexing(repo_id).await;
+    match response.status() {
+        StatusCode::ACCEPTED => {
+            let body: serde_json::Value = response.json().await.unwrap();
+            assert_eq!(body["status"], "cancelling");
+        }
+        status => assert_eq!(status, StatusCode::BAD_REQUEST, "索引已结束时应返回400"),
+    }
+}
+
 /// 测试聊天端点
 async fn test_chat_endpoints() {
     println!("💬 测试聊天端点...");
@@ -312,6 +365,7 @@ async fn test_wiki_endpoints() {
 }
 
 /// 测试研究端点
+#[cfg(feature = "research")]
 async fn test_research_endpoints() {
     println!("🔬 测试研究端点...");
 
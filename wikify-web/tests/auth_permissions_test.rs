@@ -2,6 +2,9 @@
 //!
 //! 参考 zero-to-production 的测试架构，全面测试各种权限模式下的API端点功能
 
+// The permission matrices include the research endpoints
+#![cfg(feature = "research")]
+
 mod helpers;
 
 use axum::http::StatusCode;
@@ -147,6 +147,27 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    /// 获取索引任务队列
+    pub async fn get_jobs(&self) -> reqwest::Response {
+        self.api_client
+            .get(&format!("{}/api/jobs", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// 取消仓库索引
+    pub async fn post_cancel_indexing(&self, repository_id: &str) -> reqwest::Response {
+        self.api_client
+            .post(&format!(
+                "{}/api/repositories/{}/index/cancel",
+                &self.address, repository_id
+            ))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     /// 聊天查询
     pub async fn post_chat<Body>(&self, body: &Body) -> reqwest::Response
     where
@@ -263,6 +284,7 @@ pub async fn spawn_app_with_mode(permission_mode: &str) -> TestApp {
         static_dir: Some("static".to_string()),
         database_url: Some(":memory:".to_string()), // In-memory SQLite for testing
         permission_mode: Some(permission_mode.to_string()),
+        ..Default::default()
     };
 
     info!(
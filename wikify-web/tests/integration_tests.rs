@@ -20,6 +20,7 @@ async fn create_test_server() -> (String, AppState) {
         permission_mode: Some("open".to_string()),
         static_dir: Some("static".to_string()),
         database_url: Some(":memory:".to_string()), // In-memory SQLite for testing
+        ..Default::default()
     };
 
     let state = AppState::new(config.clone()).await.unwrap();
@@ -9,6 +9,7 @@ use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::time::timeout;
 use wikify_applications::auth::permissions::{PermissionMode, ResourceLimits};
+use wikify_applications::{PageRequest, RepositoryFilter};
 use wikify_web::{create_app, AppState, WebConfig};
 
 /// Helper function to create a test server with the new architecture
@@ -20,6 +21,7 @@ async fn create_test_server() -> (String, AppState) {
         static_dir: Some("static".to_string()),
         database_url: Some(":memory:".to_string()), // In-memory SQLite for testing
         permission_mode: Some("open".to_string()),
+        ..Default::default()
     };
 
     let state = AppState::new(config.clone()).await.unwrap();
@@ -209,7 +211,12 @@ async fn test_indexing_starts() {
             std::collections::HashSet::new(),
             ResourceLimits::default(),
         );
-        let repos = state.application.list_repositories(&context).await.unwrap();
+        let repos = state
+            .application
+            .list_repositories(&context, &RepositoryFilter::default(), PageRequest::all())
+            .await
+            .unwrap()
+            .items;
         let our_repo = repos.iter().find(|r| r.id == repository_id);
 
         if let Some(repo) = our_repo {
@@ -325,7 +332,12 @@ async fn test_worker_architecture() {
         std::collections::HashSet::new(),
         ResourceLimits::default(),
     );
-    let repos = state.application.list_repositories(&context).await.unwrap();
+    let repos = state
+        .application
+        .list_repositories(&context, &RepositoryFilter::default(), PageRequest::all())
+        .await
+        .unwrap()
+        .items;
 
     for repo_id in &repository_ids {
         let repo = repos
@@ -19,6 +19,7 @@ async fn create_test_server() -> (String, AppState) {
         static_dir: Some("static".to_string()),
         database_url: Some(":memory:".to_string()),
         permission_mode: Some("open".to_string()),
+        ..Default::default()
     };
 
     let state = AppState::new(config.clone()).await.unwrap();
@@ -1,5 +1,7 @@
 //! Integration tests for research engine functionality
 
+#![cfg(feature = "research")]
+
 use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -352,3 +354,31 @@ async fn test_template_parameter_validation() {
 
     println!("✅ Template parameter validation working correctly");
 }
+
+#[tokio::test]
+async fn test_unknown_research_session_not_found() {
+    let config = WebConfig::default();
+    let state = AppState::new(config).await.unwrap();
+    let mut app = create_app(state);
+
+    let (_user_id, access_token) = create_test_user_and_login(&mut app).await;
+    let research_id = uuid::Uuid::new_v4();
+
+    for (method, uri) in [
+        ("GET", format!("/api/research/{}", research_id)),
+        ("GET", format!("/api/research/{}/result", research_id)),
+        ("GET", format!("/api/research/{}/events", research_id)),
+        ("POST", format!("/api/research/{}/stop", research_id)),
+        ("GET", format!("/api/research/progress/{}", research_id)),
+    ] {
+        let request = create_authenticated_request(method, &uri, None, Some(&access_token)).await;
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::NOT_FOUND,
+            "{} {}",
+            method,
+            uri
+        );
+    }
+}
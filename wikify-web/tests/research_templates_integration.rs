@@ -1,5 +1,7 @@
 //! Integration tests for research template system
 
+#![cfg(feature = "research")]
+
 use axum::{
     body::Body,
     http::{Request, StatusCode},
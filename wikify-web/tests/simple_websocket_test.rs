@@ -1,5 +1,7 @@
 //! Simple WebSocket Message Format Test
 
+#![cfg(feature = "websocket")]
+
 #[tokio::test]
 async fn test_websocket_message_serialization() {
     println!("🧪 Testing WebSocket message serialization");
@@ -6,6 +6,8 @@
 //! - Research completion and result retrieval
 //! - Error handling and recovery
 
+#![cfg(feature = "research")]
+
 use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -3,6 +3,8 @@
 //! This test verifies that the frontend can correctly receive and process
 //! progress updates from the new repository architecture via WebSocket.
 
+#![cfg(feature = "websocket")]
+
 use axum::http::StatusCode;
 use serde_json::json;
 use std::time::Duration;
@@ -18,6 +20,7 @@ async fn create_test_server() -> (String, String, AppState) {
         static_dir: Some("static".to_string()),
         database_url: Some(":memory:".to_string()),
         permission_mode: Some("open".to_string()),
+        ..Default::default()
     };
 
     let state = AppState::new(config.clone()).await.unwrap();
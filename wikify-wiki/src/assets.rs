@@ -0,0 +1,368 @@
+//! Repository images in wiki pages and exports
+//!
+//! Architecture diagrams and screenshots that a repository's READMEs embed
+//! are collected during generation and offered to the LLM, which may embed
+//! them in pages by their repository path. Image sources in generated pages
+//! are resolved to those canonical paths. Exporters copy the images a wiki
+//! uses into a `media/` directory of the bundle and point the pages at the
+//! copies with [`rewrite_asset_images`].
+
+use crate::links::{is_external, normalize_path, rewrite_images};
+use crate::snippets::is_relative_path;
+use crate::types::{WikiPage, WikiStructure};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+use tracing::{debug, warn};
+use wikify_core::WikifyResult;
+
+/// Directory of an export bundle that images are copied to
+pub const MEDIA_DIR: &str = "media";
+
+/// Largest image collected, in bytes
+const MAX_ASSET_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Most images collected from a repository
+const MAX_ASSETS: usize = 50;
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp"];
+
+/// Image from the repository that pages may embed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WikiAsset {
+    /// Path relative to the repository root
+    pub path: String,
+    /// Alt text given where the image is referenced
+    pub alt: String,
+    /// README that references the image
+    pub source: String,
+}
+
+/// Collect the local images referenced by the READMEs of a repository
+///
+/// `files` are the repository's files relative to `repo_path`. Images
+/// outside the repository, missing files and files over 5 MB are skipped.
+pub async fn discover_assets(repo_path: &Path, files: &[String]) -> Vec<WikiAsset> {
+    let mut readmes: Vec<&str> = files
+        .iter()
+        .map(|f| normalize_path(f))
+        .filter(|f| is_readme(f))
+        .collect();
+    // Top-level READMEs first, they describe the project as a whole
+    readmes.sort_by_key(|f| (f.matches('/').count(), *f));
+
+    let mut assets: Vec<WikiAsset> = Vec::new();
+    for readme in readmes {
+        let Ok(content) = fs::read_to_string(repo_path.join(readme)).await else {
+            continue;
+        };
+        let base = Path::new(readme).parent().unwrap_or(Path::new(""));
+        for (source, alt) in image_references(&content) {
+            if assets.len() >= MAX_ASSETS {
+                return assets;
+            }
+            let Some(path) = resolve_reference(base, &source) else {
+                continue;
+            };
+            if !is_image(&path) || assets.iter().any(|a| a.path == path) {
+                continue;
+            }
+            match fs::metadata(repo_path.join(&path)).await {
+                Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_ASSET_BYTES => {
+                    assets.push(WikiAsset {
+                        path,
+                        alt,
+                        source: readme.to_string(),
+                    });
+                }
+                Ok(_) => debug!("Skipping image {} (not a file or too large)", path),
+                Err(_) => debug!("Image {} referenced by {} not found", path, readme),
+            }
+        }
+    }
+    assets
+}
+
+/// Prompt instructions listing the images a page may embed
+pub fn asset_instructions(assets: &[WikiAsset]) -> Option<String> {
+    if assets.is_empty() {
+        return None;
+    }
+    let mut instructions = String::from(
+        "The repository contains these images. Where one illustrates the page, embed it with \
+         Markdown image syntax using its exact path, e.g. ![Architecture](docs/architecture.png). \
+         Do not invent other image paths.\n",
+    );
+    for asset in assets {
+        if asset.alt.is_empty() {
+            instructions.push_str(&format!("- {}\n", asset.path));
+        } else {
+            instructions.push_str(&format!("- {} ({})\n", asset.path, asset.alt));
+        }
+    }
+    Some(instructions)
+}
+
+/// Point the images of a page at the repository paths of known assets
+///
+/// Matches exact paths and path suffixes (`architecture.png` for
+/// `docs/architecture.png`). Returns the number of images rewritten.
+pub fn resolve_page_images(page: &mut WikiPage, assets: &[WikiAsset]) -> usize {
+    if assets.is_empty() {
+        return 0;
+    }
+    let mut resolved = 0;
+    page.content = rewrite_images(&page.content, |_, target| {
+        let asset = find_asset(assets, target)?;
+        (asset.path != target).then(|| {
+            resolved += 1;
+            asset.path.replace(' ', "%20")
+        })
+    });
+    resolved
+}
+
+/// Rewrite the image sources that are repository `paths` for an export
+///
+/// `asset_url` maps an asset path to its location in the export. Other
+/// images are left alone.
+pub fn rewrite_asset_images<F>(markdown: &str, paths: &BTreeSet<String>, asset_url: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    if paths.is_empty() {
+        return markdown.to_string();
+    }
+    rewrite_images(markdown, |_, target| {
+        let path = target.replace("%20", " ");
+        paths.get(normalize_path(&path)).map(|path| asset_url(path))
+    })
+}
+
+/// Copy the repository images a wiki's pages embed into `dest`
+///
+/// Images keep their repository path below `dest`. The repository is read
+/// from the wiki's metadata; nothing is copied when it is not a local
+/// directory. Returns the paths of the copied images.
+pub async fn copy_assets(wiki: &WikiStructure, dest: &Path) -> WikifyResult<BTreeSet<String>> {
+    let mut copied = BTreeSet::new();
+    let repo_path = Path::new(&wiki.metadata.repository);
+    if wiki.assets.is_empty() || !repo_path.is_dir() {
+        return Ok(copied);
+    }
+
+    for path in embedded_assets(wiki) {
+        if !is_relative_path(&path) {
+            continue;
+        }
+        let target = dest.join(&path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        match fs::copy(repo_path.join(&path), &target).await {
+            Ok(_) => {
+                copied.insert(path);
+            }
+            Err(e) => warn!("Failed to copy image {}: {}", path, e),
+        }
+    }
+    debug!("Copied {} images to {:?}", copied.len(), dest);
+    Ok(copied)
+}
+
+/// Paths of the wiki's assets embedded in at least one page
+fn embedded_assets(wiki: &WikiStructure) -> BTreeSet<String> {
+    let mut embedded = BTreeSet::new();
+    for page in &wiki.pages {
+        for event in Parser::new(&page.content) {
+            if let Event::Start(Tag::Image { dest_url, .. }) = event {
+                let path = dest_url.replace("%20", " ");
+                let path = normalize_path(&path);
+                if wiki.assets.iter().any(|a| a.path == path) {
+                    embedded.insert(path.to_string());
+                }
+            }
+        }
+    }
+    embedded
+}
+
+fn find_asset<'a>(assets: &'a [WikiAsset], target: &str) -> Option<&'a WikiAsset> {
+    if is_external(target) {
+        return None;
+    }
+    let target = target.replace("%20", " ");
+    let target = normalize_path(target.split(['#', '?']).next().unwrap_or_default());
+    if target.is_empty() {
+        return None;
+    }
+    if let Some(asset) = assets.iter().find(|a| a.path == target) {
+        return Some(asset);
+    }
+    let suffix = format!("/{}", target);
+    assets
+        .iter()
+        .filter(|a| a.path.ends_with(&suffix))
+        .min_by_key(|a| a.path.len())
+}
+
+fn is_readme(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    name.starts_with("readme") && (name.ends_with(".md") || name.ends_with(".markdown"))
+}
+
+fn is_image(path: &str) -> bool {
+    path.rsplit_once('.')
+        .is_some_and(|(_, ext)| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Markdown images and HTML `<img>` tags of a document, as `(source, alt)`
+fn image_references(markdown: &str) -> Vec<(String, String)> {
+    let mut references = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Image { dest_url, .. }) => {
+                current = Some((dest_url.to_string(), String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, alt)) = current.as_mut() {
+                    alt.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Image) => references.extend(current.take()),
+            Event::Html(html) | Event::InlineHtml(html) => {
+                for tag in html.split("<img").skip(1) {
+                    let tag = tag.split('>').next().unwrap_or_default();
+                    if let Some(src) = html_attribute(tag, "src") {
+                        let alt = html_attribute(tag, "alt").unwrap_or_default();
+                        references.push((src.to_string(), alt.to_string()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    references
+}
+
+/// Value of a quoted attribute of an HTML tag
+fn html_attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(index) = rest.find(name) {
+        let before = rest[..index].chars().next_back();
+        let after = rest[index + name.len()..].trim_start();
+        rest = &rest[index + name.len()..];
+        if !before.is_some_and(char::is_whitespace) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        return value[1..].split(quote).next();
+    }
+    None
+}
+
+/// Repository path of an image referenced from a file in directory `base`
+///
+/// Sources starting with `/` are relative to the repository root, as on
+/// GitHub. Returns `None` for external images and paths leaving the
+/// repository.
+fn resolve_reference(base: &Path, source: &str) -> Option<String> {
+    if is_external(source) {
+        return None;
+    }
+    let source = source.split(['#', '?']).next().unwrap_or_default();
+    let source = source.replace("%20", " ");
+    let joined = match source.strip_prefix('/') {
+        Some(rooted) => PathBuf::from(rooted),
+        None => base.join(&source),
+    };
+
+    let mut parts: Vec<String> = Vec::new();
+    for component in joined.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_discover_assets_from_readmes() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("docs/img")).unwrap();
+        std::fs::write(temp.path().join("docs/img/arch.png"), b"png").unwrap();
+        std::fs::write(temp.path().join("logo.svg"), b"<svg/>").unwrap();
+        std::fs::write(
+            temp.path().join("docs/README.md"),
+            "# Docs\n\n![Architecture](img/arch.png)\n\n\
+             ![Outside](../../secret.png) ![Remote](https://example.com/a.png)\n\n\
+             <p align=\"center\"><img width=\"200\" src=\"/logo.svg\" alt=\"Logo\"></p>\n\n\
+             ![Missing](img/missing.png)\n",
+        )
+        .unwrap();
+
+        let files = vec![
+            "docs/README.md".to_string(),
+            "docs/img/arch.png".to_string(),
+            "logo.svg".to_string(),
+        ];
+        let assets = discover_assets(temp.path(), &files).await;
+        assert_eq!(
+            assets,
+            vec![
+                WikiAsset {
+                    path: "docs/img/arch.png".to_string(),
+                    alt: "Architecture".to_string(),
+                    source: "docs/README.md".to_string(),
+                },
+                WikiAsset {
+                    path: "logo.svg".to_string(),
+                    alt: "Logo".to_string(),
+                    source: "docs/README.md".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_images_are_resolved_and_rewritten_for_export() {
+        let assets = vec![WikiAsset {
+            path: "docs/img/arch.png".to_string(),
+            alt: "Architecture".to_string(),
+            source: "README.md".to_string(),
+        }];
+        let mut page = WikiPage::new("overview".into(), "Overview".into(), String::new());
+        page.content = "![Architecture](./img/arch.png)\n\n![Other](other.png)\n".to_string();
+
+        assert_eq!(resolve_page_images(&mut page, &assets), 1);
+        assert_eq!(
+            page.content,
+            "![Architecture](docs/img/arch.png)\n\n![Other](other.png)\n"
+        );
+
+        let copied = BTreeSet::from(["docs/img/arch.png".to_string()]);
+        assert_eq!(
+            rewrite_asset_images(&page.content, &copied, |path| format!(
+                "{}/{}",
+                MEDIA_DIR, path
+            )),
+            "![Architecture](media/docs/img/arch.png)\n\n![Other](other.png)\n"
+        );
+    }
+}
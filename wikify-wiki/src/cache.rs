@@ -1,37 +1,110 @@
 //! Wiki cache management
 //!
 //! This module handles caching of generated wikis to avoid regeneration.
+//! Cached wikis are keyed by repository, commit SHA and a hash of the
+//! generation config, so a new commit or a changed config misses the cache.
+//! Every stored wiki is also archived as a version, keyed by the commit it was
+//! generated from, so documentation for older releases stays available.
 
-use crate::types::WikiStructure;
+use crate::types::{WikiConfig, WikiStructure};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use serde_json;
 use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::{debug, info, warn};
 use wikify_core::{ErrorContext, WikifyError, WikifyResult};
 
+/// Number of wiki versions kept per repository by default
+pub const DEFAULT_MAX_VERSIONS: usize = 20;
+
+/// Summary of an archived wiki version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiVersion {
+    /// Version identifier: the commit SHA, or the generation timestamp if unknown
+    pub version_id: String,
+    /// Repository commit the wiki was generated from
+    pub commit_sha: Option<String>,
+    /// When the wiki was generated
+    pub generated_at: DateTime<Utc>,
+    /// Number of pages
+    pub pages_count: usize,
+    /// Number of sections
+    pub sections_count: usize,
+}
+
+impl WikiVersion {
+    /// Version summary of a wiki
+    pub fn of(wiki: &WikiStructure) -> Self {
+        Self {
+            version_id: Self::id_of(wiki),
+            commit_sha: wiki.metadata.commit_sha.clone(),
+            generated_at: wiki.metadata.generated_at,
+            pages_count: wiki.pages.len(),
+            sections_count: wiki.sections.len(),
+        }
+    }
+
+    /// Version identifier of a wiki
+    ///
+    /// Wikis generated from the same commit share an identifier, so
+    /// regenerating replaces the version for that commit.
+    pub fn id_of(wiki: &WikiStructure) -> String {
+        wiki.metadata.commit_sha.clone().unwrap_or_else(|| {
+            wiki.metadata
+                .generated_at
+                .format("%Y%m%dT%H%M%SZ")
+                .to_string()
+        })
+    }
+}
+
 /// Cache manager for wiki structures
 pub struct WikiCache {
     cache_dir: PathBuf,
+    max_versions: usize,
 }
 
 impl WikiCache {
     /// Create a new WikiCache instance
     pub fn new() -> WikifyResult<Self> {
         let cache_dir = Self::get_cache_directory()?;
-        Ok(Self { cache_dir })
+        Ok(Self {
+            cache_dir,
+            max_versions: DEFAULT_MAX_VERSIONS,
+        })
     }
 
     /// Create a WikiCache with custom cache directory
     pub fn with_cache_dir<P: AsRef<Path>>(cache_dir: P) -> WikifyResult<Self> {
         let cache_dir = cache_dir.as_ref().to_path_buf();
-        Ok(Self { cache_dir })
+        Ok(Self {
+            cache_dir,
+            max_versions: DEFAULT_MAX_VERSIONS,
+        })
+    }
+
+    /// Set how many versions are kept per repository (oldest are removed first)
+    pub fn with_max_versions(mut self, max_versions: usize) -> Self {
+        self.max_versions = max_versions.max(1);
+        self
+    }
+
+    /// Directory holding the cached wikis
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
     }
 
     /// Store a wiki structure in cache
+    ///
+    /// The entry is keyed by the commit and config recorded in the wiki's
+    /// metadata.
     pub async fn store_wiki(&self, repo_path: &str, wiki: &WikiStructure) -> WikifyResult<()> {
-        let cache_key = self.generate_cache_key(repo_path);
-        let cache_file = self.cache_dir.join(format!("{}.json", cache_key));
+        let cache_file = self.entry_file(
+            repo_path,
+            wiki.metadata.commit_sha.as_deref(),
+            &wiki.metadata.config,
+        );
 
         // Ensure cache directory exists
         if let Some(parent) = cache_file.parent() {
@@ -51,13 +124,104 @@ impl WikiCache {
             "Cached wiki for repository: {} -> {:?}",
             repo_path, cache_file
         );
+
+        self.store_version(repo_path, wiki).await
+    }
+
+    /// Archive a wiki as a version of a repository, pruning the oldest
+    /// versions beyond the configured limit
+    pub async fn store_version(&self, repo_path: &str, wiki: &WikiStructure) -> WikifyResult<()> {
+        let versions_dir = self.versions_dir(repo_path);
+        fs::create_dir_all(&versions_dir).await?;
+
+        let json_content = serde_json::to_string_pretty(wiki).map_err(|e| WikifyError::Config {
+            message: format!("Failed to serialize wiki structure: {}", e),
+            source: Some(Box::new(e)),
+            context: ErrorContext::new("wiki_cache"),
+        })?;
+        let version_id = WikiVersion::id_of(wiki);
+        fs::write(
+            versions_dir.join(format!("{}.json", version_id)),
+            json_content,
+        )
+        .await?;
+        debug!(
+            "Archived wiki version {} for repository: {}",
+            version_id, repo_path
+        );
+
+        for stale in self
+            .list_versions(repo_path)
+            .await?
+            .iter()
+            .skip(self.max_versions)
+        {
+            let _ = fs::remove_file(versions_dir.join(format!("{}.json", stale.version_id))).await;
+            debug!(
+                "Pruned wiki version {} for repository: {}",
+                stale.version_id, repo_path
+            );
+        }
+
         Ok(())
     }
 
-    /// Retrieve a wiki structure from cache
-    pub async fn get_wiki(&self, repo_path: &str) -> WikifyResult<Option<WikiStructure>> {
-        let cache_key = self.generate_cache_key(repo_path);
-        let cache_file = self.cache_dir.join(format!("{}.json", cache_key));
+    /// List the archived versions of a repository's wiki, newest first
+    pub async fn list_versions(&self, repo_path: &str) -> WikifyResult<Vec<WikiVersion>> {
+        let versions_dir = self.versions_dir(repo_path);
+        if !versions_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = fs::read_dir(&versions_dir).await?;
+        let mut versions = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            match Self::read_wiki(&path).await {
+                Ok(wiki) => versions.push(WikiVersion::of(&wiki)),
+                Err(e) => warn!("Skipping unreadable wiki version {:?}: {}", path, e),
+            }
+        }
+
+        versions.sort_by_key(|v| std::cmp::Reverse(v.generated_at));
+        Ok(versions)
+    }
+
+    /// Retrieve an archived version of a repository's wiki
+    pub async fn get_version(
+        &self,
+        repo_path: &str,
+        version_id: &str,
+    ) -> WikifyResult<Option<WikiStructure>> {
+        // Version IDs are commit SHAs or timestamps; anything else is not a file we wrote
+        if version_id.is_empty() || !version_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Ok(None);
+        }
+
+        let path = self
+            .versions_dir(repo_path)
+            .join(format!("{}.json", version_id));
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        Self::read_wiki(&path).await.map(Some)
+    }
+
+    /// Retrieve the wiki generated from a repository commit with a config
+    ///
+    /// Without a commit SHA (not a git repository) the entry is only valid
+    /// while it is newer than the repository directory.
+    pub async fn get_wiki(
+        &self,
+        repo_path: &str,
+        commit_sha: Option<&str>,
+        config: &WikiConfig,
+    ) -> WikifyResult<Option<WikiStructure>> {
+        let cache_file = self.entry_file(repo_path, commit_sha, config);
 
         if !cache_file.exists() {
             debug!("No cache found for repository: {}", repo_path);
@@ -65,61 +229,53 @@ impl WikiCache {
         }
 
         // Check if cache is still valid
-        if !self.is_cache_valid(&cache_file, repo_path).await? {
+        if commit_sha.is_none() && !self.is_cache_valid(&cache_file, repo_path).await? {
             warn!("Cache is outdated for repository: {}", repo_path);
             // Optionally remove outdated cache
             let _ = fs::remove_file(&cache_file).await;
             return Ok(None);
         }
 
-        // Read and deserialize cache file
-        let json_content = fs::read_to_string(&cache_file).await?;
-
-        let wiki_structure: WikiStructure =
-            serde_json::from_str(&json_content).map_err(|e| WikifyError::Config {
-                message: format!("Failed to deserialize wiki structure: {}", e),
-                source: Some(Box::new(e)),
-                context: ErrorContext::new("wiki_cache"),
-            })?;
+        let wiki_structure = Self::read_wiki(&cache_file).await?;
 
         info!("Retrieved cached wiki for repository: {}", repo_path);
         Ok(Some(wiki_structure))
     }
 
-    /// Clear cache for a specific repository
+    /// Drop the cached wikis of a repository, keeping its archived versions
+    ///
+    /// Called when the repository is reindexed or purged by hand, so the
+    /// next generation starts from scratch.
+    pub async fn invalidate(&self, repo_path: &str) -> WikifyResult<()> {
+        let entries_dir = self.entries_dir(repo_path);
+        if entries_dir.exists() {
+            fs::remove_dir_all(&entries_dir).await?;
+            info!("Invalidated wiki cache for repository: {}", repo_path);
+        }
+        Ok(())
+    }
+
+    /// Clear cache and archived versions for a specific repository
     pub async fn clear_wiki(&self, repo_path: &str) -> WikifyResult<()> {
-        let cache_key = self.generate_cache_key(repo_path);
-        let cache_file = self.cache_dir.join(format!("{}.json", cache_key));
+        self.invalidate(repo_path).await?;
 
-        if cache_file.exists() {
-            fs::remove_file(&cache_file).await?;
-            info!("Cleared cache for repository: {}", repo_path);
+        let versions_dir = self.versions_dir(repo_path);
+        if versions_dir.exists() {
+            fs::remove_dir_all(&versions_dir).await?;
         }
 
         Ok(())
     }
 
-    /// List all cached wikis
+    /// List the cache keys of all repositories with cached wikis
     pub async fn list_wikis(&self) -> WikifyResult<Vec<String>> {
-        if !self.cache_dir.exists() {
-            return Ok(Vec::new());
-        }
-
-        let mut entries = fs::read_dir(&self.cache_dir).await?;
-
-        let mut wikis = Vec::new();
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Some(file_stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    // Try to reverse the cache key to get original path
-                    // This is a simple implementation - could be improved
-                    wikis.push(file_stem.to_string());
-                }
-            }
-        }
-
-        Ok(wikis)
+        Ok(self
+            .entry_dirs()
+            .await?
+            .iter()
+            .filter_map(|dir| dir.file_name().and_then(|s| s.to_str()))
+            .map(str::to_string)
+            .collect())
     }
 
     /// Clear all cached wikis
@@ -137,16 +293,18 @@ impl WikiCache {
             return Ok(CacheStats::default());
         }
 
-        let mut entries = fs::read_dir(&self.cache_dir).await?;
-
         let mut total_files = 0;
         let mut total_size = 0;
         let mut oldest_cache: Option<DateTime<Utc>> = None;
         let mut newest_cache: Option<DateTime<Utc>> = None;
 
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+        for dir in self.entry_dirs().await? {
+            let mut entries = fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                    continue;
+                }
                 total_files += 1;
 
                 if let Ok(metadata) = entry.metadata().await {
@@ -174,6 +332,66 @@ impl WikiCache {
         })
     }
 
+    /// Read and deserialize a stored wiki
+    async fn read_wiki(path: &Path) -> WikifyResult<WikiStructure> {
+        let json_content = fs::read_to_string(path).await?;
+
+        let wiki_structure =
+            serde_json::from_str(&json_content).map_err(|e| WikifyError::Config {
+                message: format!("Failed to deserialize wiki structure: {}", e),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("wiki_cache"),
+            })?;
+        Ok(wiki_structure)
+    }
+
+    /// Directory holding the cached wikis of a repository
+    pub fn entries_dir(&self, repo_path: &str) -> PathBuf {
+        self.cache_dir.join(self.generate_cache_key(repo_path))
+    }
+
+    /// Cache file of the wiki generated from a commit with a config
+    fn entry_file(
+        &self,
+        repo_path: &str,
+        commit_sha: Option<&str>,
+        config: &WikiConfig,
+    ) -> PathBuf {
+        // Commit SHAs come from git; anything else must not end up in a path
+        let commit = commit_sha
+            .filter(|sha| !sha.is_empty() && sha.chars().all(|c| c.is_ascii_alphanumeric()))
+            .unwrap_or("worktree");
+        self.entries_dir(repo_path)
+            .join(format!("{}-{}.json", commit, config_hash(config)))
+    }
+
+    /// Per-repository cache directories
+    async fn entry_dirs(&self) -> WikifyResult<Vec<PathBuf>> {
+        if !self.cache_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = fs::read_dir(&self.cache_dir).await?;
+        let mut dirs = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let is_entry_dir = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with("wiki_"));
+            if is_entry_dir && entry.file_type().await?.is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+        Ok(dirs)
+    }
+
+    /// Directory holding the archived versions of a repository's wiki
+    fn versions_dir(&self, repo_path: &str) -> PathBuf {
+        self.cache_dir
+            .join("versions")
+            .join(self.generate_cache_key(repo_path))
+    }
+
     /// Generate a cache key from repository path
     fn generate_cache_key(&self, repo_path: &str) -> String {
         use std::collections::hash_map::DefaultHasher;
@@ -221,6 +439,22 @@ impl WikiCache {
     }
 }
 
+/// Hash of the settings that shape a generated wiki
+///
+/// `force_regenerate` only controls cache use and is left out.
+pub fn config_hash(config: &WikiConfig) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut config = config.clone();
+    config.force_regenerate = false;
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&config)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
 /// Statistics about the wiki cache
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
@@ -264,6 +498,92 @@ mod tests {
         assert_eq!(stats.total_files, 0);
     }
 
+    #[tokio::test]
+    async fn test_versions_are_kept_per_commit() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = WikiCache::with_cache_dir(temp_dir.path())
+            .unwrap()
+            .with_max_versions(2);
+
+        let mut wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/test/repo".to_string(),
+        );
+        for (i, sha) in ["a".repeat(40), "b".repeat(40), "c".repeat(40)]
+            .into_iter()
+            .enumerate()
+        {
+            wiki.metadata.commit_sha = Some(sha);
+            wiki.metadata.generated_at = Utc::now() + chrono::Duration::seconds(i as i64);
+            cache.store_version("repo-1", &wiki).await.unwrap();
+        }
+
+        let versions = cache.list_versions("repo-1").await.unwrap();
+        let ids: Vec<_> = versions.iter().map(|v| v.version_id.as_str()).collect();
+        assert_eq!(ids, vec!["c".repeat(40), "b".repeat(40)]);
+
+        let version = cache
+            .get_version("repo-1", &"b".repeat(40))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(version.metadata.commit_sha, Some("b".repeat(40)));
+        assert!(cache.get_version("repo-1", "../x").await.unwrap().is_none());
+
+        cache.clear_wiki("repo-1").await.unwrap();
+        assert!(cache.list_versions("repo-1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entries_are_keyed_by_commit_and_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let cache = WikiCache::with_cache_dir(temp_dir.path()).unwrap();
+
+        let mut wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/test/repo".to_string(),
+        );
+        let sha = "a".repeat(40);
+        wiki.metadata.commit_sha = Some(sha.clone());
+        let config = wiki.metadata.config.clone();
+        cache.store_wiki("repo-1", &wiki).await.unwrap();
+
+        let hit = cache.get_wiki("repo-1", Some(&sha), &config).await.unwrap();
+        assert_eq!(hit.map(|w| w.id), Some(wiki.id.clone()));
+
+        let mut forced = config.clone();
+        forced.force_regenerate = true;
+        assert!(cache
+            .get_wiki("repo-1", Some(&sha), &forced)
+            .await
+            .unwrap()
+            .is_some());
+
+        let mut other_language = config.clone();
+        other_language.language = "zh".to_string();
+        assert!(cache
+            .get_wiki("repo-1", Some(&sha), &other_language)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(cache
+            .get_wiki("repo-1", Some(&"b".repeat(40)), &config)
+            .await
+            .unwrap()
+            .is_none());
+
+        cache.invalidate("repo-1").await.unwrap();
+        assert!(cache
+            .get_wiki("repo-1", Some(&sha), &config)
+            .await
+            .unwrap()
+            .is_none());
+        // Versions survive invalidation
+        assert_eq!(cache.list_versions("repo-1").await.unwrap().len(), 1);
+    }
+
     #[tokio::test]
     async fn test_cache_key_generation() {
         let cache = WikiCache::new().unwrap();
@@ -0,0 +1,330 @@
+//! Recent changes page from git history
+//!
+//! Summarizes the commits between the commit the previous wiki was generated
+//! from and `HEAD`, grouped by the area of the repository they touch, so the
+//! wiki doubles as lightweight release notes. Pull request titles are used
+//! where the history has them (GitHub merge and squash commits). Like the
+//! dependency graph, the page is built without the LLM.
+
+use crate::types::{ImportanceLevel, WikiPage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tokio::process::Command;
+use tracing::debug;
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// ID of the generated wiki page
+pub const CHANGELOG_PAGE_ID: &str = "recent-changes";
+
+/// Commits summarized when there is no previous commit to start from
+pub const DEFAULT_MAX_COMMITS: usize = 50;
+
+/// Area of changes that touch files at the repository root only
+const ROOT_AREA: &str = "Repository root";
+
+/// Git history between two commits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Changelog {
+    /// Commit the changes start after, if known
+    pub since: Option<String>,
+    /// Commit the changes end at
+    pub until: String,
+    /// Commits, newest first
+    pub entries: Vec<ChangeEntry>,
+}
+
+/// A commit (or merged pull request) in the changelog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    /// Commit SHA
+    pub sha: String,
+    /// Pull request title, or the commit subject
+    pub title: String,
+    /// Pull request number, if the commit merged one
+    pub pull_request: Option<u32>,
+    /// Commit author
+    pub author: String,
+    /// Author date
+    pub date: DateTime<Utc>,
+    /// Files changed by the commit
+    pub files: Vec<String>,
+}
+
+impl ChangeEntry {
+    /// Area of the repository the commit mostly touches: the top-level
+    /// directory with the most changed files
+    pub fn area(&self) -> String {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for file in &self.files {
+            let area = match file.split_once('/') {
+                Some((dir, _)) => dir,
+                None => ROOT_AREA,
+            };
+            *counts.entry(area).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map_or_else(|| ROOT_AREA.to_string(), |(area, _)| area.to_string())
+    }
+
+    fn short_sha(&self) -> &str {
+        &self.sha[..self.sha.len().min(7)]
+    }
+}
+
+impl Changelog {
+    /// Read the first-parent history of a local repository
+    ///
+    /// With a `since` commit known to the repository, all commits after it
+    /// are included; otherwise the last `max_commits`.
+    pub async fn from_git(
+        repo_path: &Path,
+        since: Option<&str>,
+        max_commits: usize,
+    ) -> WikifyResult<Self> {
+        let until = git(repo_path, &["rev-parse", "HEAD"]).await?;
+        let until = until.trim().to_string();
+
+        let since = match since {
+            Some(sha) if is_commit_sha(sha) && sha != until => {
+                let known = git(
+                    repo_path,
+                    &["cat-file", "-e", &format!("{}^{{commit}}", sha)],
+                )
+                .await
+                .is_ok();
+                if !known {
+                    debug!("Previous commit {} not in repository history", sha);
+                }
+                known.then(|| sha.to_string())
+            }
+            _ => None,
+        };
+
+        let range = match &since {
+            Some(since) => format!("{}..{}", since, until),
+            None => until.clone(),
+        };
+        let max_count = format!("--max-count={}", max_commits.max(1));
+        let mut args = vec![
+            "log",
+            "--first-parent",
+            "-m",
+            "--name-only",
+            "--format=%x1e%H%x1f%s%x1f%an%x1f%aI%x1f%b%x1f",
+        ];
+        if since.is_none() {
+            args.push(&max_count);
+        }
+        args.push(&range);
+        let log = git(repo_path, &args).await?;
+
+        Ok(Self {
+            since,
+            until,
+            entries: parse_log(&log),
+        })
+    }
+
+    /// Whether there are no changes to report
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entries grouped by area, busiest area first
+    pub fn by_area(&self) -> Vec<(String, Vec<&ChangeEntry>)> {
+        let mut areas: BTreeMap<String, Vec<&ChangeEntry>> = BTreeMap::new();
+        for entry in &self.entries {
+            areas.entry(entry.area()).or_default().push(entry);
+        }
+        let mut areas: Vec<_> = areas.into_iter().collect();
+        areas.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(&b.0)));
+        areas
+    }
+
+    /// Render the changelog as a wiki page
+    pub fn to_page(&self) -> WikiPage {
+        let until = &self.until[..self.until.len().min(7)];
+        let mut content = String::from("# Recent Changes\n\n");
+        match &self.since {
+            Some(since) => content.push_str(&format!(
+                "Changes since the previous version of this wiki, from `{}` to `{}` \
+                 ({} commits), grouped by the area of the repository they touch.\n\n",
+                &since[..since.len().min(7)],
+                until,
+                self.entries.len()
+            )),
+            None => content.push_str(&format!(
+                "The latest {} commits up to `{}`, grouped by the area of the repository \
+                 they touch.\n\n",
+                self.entries.len(),
+                until
+            )),
+        }
+
+        for (area, entries) in self.by_area() {
+            content.push_str(&format!("## {}\n\n", area));
+            for entry in entries {
+                let title = match entry.pull_request {
+                    Some(number) => format!("{} (#{})", entry.title, number),
+                    None => entry.title.clone(),
+                };
+                content.push_str(&format!(
+                    "- {} — `{}` by {}, {}\n",
+                    title,
+                    entry.short_sha(),
+                    entry.author,
+                    entry.date.format("%Y-%m-%d")
+                ));
+            }
+            content.push('\n');
+        }
+
+        let mut page = WikiPage::new(
+            CHANGELOG_PAGE_ID.to_string(),
+            "Recent Changes".to_string(),
+            "Commits and pull requests since the previous version of the wiki".to_string(),
+        );
+        page.content = content;
+        page.importance = ImportanceLevel::Medium;
+        page.tags = vec!["changelog".to_string(), "release-notes".to_string()];
+        page.estimate_reading_time();
+        page
+    }
+}
+
+/// Parse `git log` output in the format used by [`Changelog::from_git`]
+fn parse_log(log: &str) -> Vec<ChangeEntry> {
+    let mut entries = Vec::new();
+    for record in log.split('\u{1e}').filter(|r| !r.trim().is_empty()) {
+        let fields: Vec<&str> = record.splitn(6, '\u{1f}').collect();
+        let [sha, subject, author, date, body, files] = fields[..] else {
+            continue;
+        };
+        let Ok(date) = DateTime::parse_from_rfc3339(date.trim()) else {
+            continue;
+        };
+
+        let (title, pull_request) = pull_request_title(subject.trim(), body);
+        entries.push(ChangeEntry {
+            sha: sha.trim().to_string(),
+            title,
+            pull_request,
+            author: author.trim().to_string(),
+            date: date.with_timezone(&Utc),
+            files: files
+                .lines()
+                .map(str::trim)
+                .filter(|f| !f.is_empty())
+                .map(str::to_string)
+                .collect(),
+        });
+    }
+    entries
+}
+
+/// Title and number of the pull request a commit merged, if any
+///
+/// Understands GitHub merge commits (`Merge pull request #12 from ...`,
+/// with the title in the body) and squash merges (`Title (#12)`).
+fn pull_request_title(subject: &str, body: &str) -> (String, Option<u32>) {
+    if let Some(rest) = subject.strip_prefix("Merge pull request #") {
+        let number = rest.split_whitespace().next().and_then(|n| n.parse().ok());
+        let title = body
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or(subject);
+        return (title.to_string(), number);
+    }
+
+    if let Some(open) = subject.rfind("(#") {
+        if let Some(number) = subject[open + 2..]
+            .strip_suffix(')')
+            .and_then(|n| n.parse().ok())
+        {
+            return (subject[..open].trim_end().to_string(), Some(number));
+        }
+    }
+
+    (subject.to_string(), None)
+}
+
+fn is_commit_sha(sha: &str) -> bool {
+    matches!(sha.len(), 40 | 64) && sha.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Run git in a repository and return its output
+async fn git(repo_path: &Path, args: &[&str]) -> WikifyResult<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(Box::new(WikifyError::Repository {
+            message: format!(
+                "git {} failed: {}",
+                args.first().copied().unwrap_or_default(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            source: None,
+            context: ErrorContext::new("changelog"),
+        }));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_groups_by_area() {
+        let log = "\u{1e}aaaaaaa1\u{1f}Merge pull request #12 from me/feature\u{1f}Alice\u{1f}\
+                   2024-05-02T10:00:00+02:00\u{1f}Add wiki templates\n\u{1f}\n\n\
+                   wikify-wiki/src/template.rs\nwikify-wiki/src/lib.rs\nREADME.md\n\
+                   \u{1e}bbbbbbb2\u{1f}Fix cache keys (#11)\u{1f}Bob\u{1f}\
+                   2024-05-01T09:00:00Z\u{1f}\u{1f}\n\nwikify-wiki/src/cache.rs\n\
+                   \u{1e}ccccccc3\u{1f}Bump version\u{1f}Bob\u{1f}\
+                   2024-04-30T09:00:00Z\u{1f}\u{1f}\n\nCargo.toml\n";
+
+        let changelog = Changelog {
+            since: None,
+            until: "aaaaaaa1".to_string(),
+            entries: parse_log(log),
+        };
+        let titles: Vec<_> = changelog
+            .entries
+            .iter()
+            .map(|e| (e.title.as_str(), e.pull_request))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Add wiki templates", Some(12)),
+                ("Fix cache keys", Some(11)),
+                ("Bump version", None),
+            ]
+        );
+
+        let areas: Vec<_> = changelog
+            .by_area()
+            .into_iter()
+            .map(|(area, entries)| (area, entries.len()))
+            .collect();
+        assert_eq!(
+            areas,
+            vec![("wikify-wiki".to_string(), 2), (ROOT_AREA.to_string(), 1)]
+        );
+
+        let page = changelog.to_page();
+        assert!(page
+            .content
+            .contains("- Add wiki templates (#12) — `aaaaaaa` by Alice, 2024-05-02\n"));
+    }
+}
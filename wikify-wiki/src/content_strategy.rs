@@ -270,6 +270,7 @@ impl ContentGenerationStrategy {
             reading_time: 0,
             generated_at: Utc::now(),
             source_documents: Vec::new(),
+            code_examples: Vec::new(),
         };
 
         let initial_prompt = MarkdownPrompts::create_direct_markdown_prompt(
@@ -0,0 +1,1029 @@
+//! Dependency graph analysis
+//!
+//! Builds a graph of a repository's internal packages and modules from its
+//! manifests (`Cargo.toml`, `package.json`, `pyproject.toml`, `requirements.txt`)
+//! and source imports, and renders it as a wiki page with Mermaid diagrams.
+//! No LLM is involved, so the same repository always yields the same page.
+
+use crate::types::{ImportanceLevel, WikiPage};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, warn};
+use walkdir::WalkDir;
+use wikify_core::WikifyResult;
+
+/// ID of the generated wiki page
+pub const DEPENDENCY_GRAPH_PAGE_ID: &str = "dependency-graph";
+
+/// Directories never containing first-party sources
+const SKIPPED_DIRS: &[&str] = &[
+    "target",
+    "node_modules",
+    "dist",
+    "build",
+    "out",
+    "vendor",
+    "venv",
+    "__pycache__",
+];
+/// Source files larger than this are not scanned for imports
+const MAX_SOURCE_FILE_SIZE: u64 = 1024 * 1024;
+/// Packages with more modules than this get no module diagram
+const MAX_MODULES_PER_DIAGRAM: usize = 60;
+/// Number of external dependencies listed on the page
+const NOTABLE_EXTERNAL_LIMIT: usize = 30;
+
+/// Package ecosystem a manifest belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Ecosystem {
+    Cargo,
+    Npm,
+    Python,
+}
+
+impl Ecosystem {
+    fn label(&self) -> &'static str {
+        match self {
+            Ecosystem::Cargo => "Cargo",
+            Ecosystem::Npm => "npm",
+            Ecosystem::Python => "Python",
+        }
+    }
+}
+
+/// A package found in the repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Package {
+    /// Package name from its manifest
+    pub name: String,
+    /// Ecosystem of the package
+    pub ecosystem: Ecosystem,
+    /// Package directory relative to the repository root
+    pub path: String,
+    /// Manifest files relative to the repository root
+    pub manifests: Vec<String>,
+    /// Other packages of this repository it depends on
+    pub internal_dependencies: BTreeSet<String>,
+    /// Third-party dependencies with their version requirements
+    pub external_dependencies: BTreeMap<String, String>,
+    /// Top-level modules of the package
+    pub modules: BTreeSet<String>,
+    /// `(from, to)` pairs of modules importing each other
+    pub module_edges: BTreeSet<(String, String)>,
+}
+
+/// A third-party dependency and the packages using it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalDependency {
+    /// Dependency name
+    pub name: String,
+    /// Version requirements across packages
+    pub versions: BTreeSet<String>,
+    /// Packages depending on it
+    pub used_by: BTreeSet<String>,
+}
+
+/// Packages and module relationships of a repository
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    /// Packages keyed by name
+    pub packages: BTreeMap<String, Package>,
+}
+
+impl DependencyGraph {
+    /// Analyze the repository at `repo_path`, ignoring directories named in `excluded_dirs`
+    pub fn analyze(repo_path: &Path, excluded_dirs: &[String]) -> WikifyResult<Self> {
+        let skip = |path: &Path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(|name| {
+                    name.starts_with('.')
+                        || SKIPPED_DIRS.contains(&name)
+                        || excluded_dirs.iter().any(|d| d == name)
+                })
+                .unwrap_or(false)
+        };
+
+        let mut manifests: Vec<PathBuf> = WalkDir::new(repo_path)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !e.file_type().is_dir() || !skip(e.path()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .filter(|p| {
+                matches!(
+                    p.file_name().and_then(|n| n.to_str()),
+                    Some("Cargo.toml" | "package.json" | "pyproject.toml" | "requirements.txt")
+                )
+            })
+            .collect();
+        manifests.sort();
+
+        let mut packages: Vec<(PathBuf, Package)> = Vec::new();
+        let mut cargo_workspace_dependencies = toml::Table::new();
+        let mut parsed_cargo = Vec::new();
+
+        for manifest in &manifests {
+            let dir = manifest.parent().unwrap_or(repo_path).to_path_buf();
+            let relative_manifest = relative_path(repo_path, manifest);
+            let content = match std::fs::read_to_string(manifest) {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!(manifest = %manifest.display(), error = %e, "Failed to read manifest");
+                    continue;
+                }
+            };
+
+            match manifest.file_name().and_then(|n| n.to_str()) {
+                Some("Cargo.toml") => match toml::from_str::<toml::Table>(&content) {
+                    Ok(table) => {
+                        if let Some(deps) = table
+                            .get("workspace")
+                            .and_then(|w| w.get("dependencies"))
+                            .and_then(|d| d.as_table())
+                        {
+                            cargo_workspace_dependencies.extend(deps.clone());
+                        }
+                        parsed_cargo.push((dir, relative_manifest, table));
+                    }
+                    Err(e) => {
+                        warn!(manifest = %relative_manifest, error = %e, "Invalid Cargo.toml")
+                    }
+                },
+                Some("package.json") => match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(json) => {
+                        let name = json["name"]
+                            .as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| directory_name(repo_path, &dir));
+                        let mut package = Package::new(
+                            name,
+                            Ecosystem::Npm,
+                            relative_path(repo_path, &dir),
+                            relative_manifest,
+                        );
+                        for key in ["dependencies", "peerDependencies"] {
+                            if let Some(deps) = json[key].as_object() {
+                                for (dep, version) in deps {
+                                    package.external_dependencies.insert(
+                                        dep.clone(),
+                                        version.as_str().unwrap_or("*").to_string(),
+                                    );
+                                }
+                            }
+                        }
+                        packages.push((dir, package));
+                    }
+                    Err(e) => {
+                        warn!(manifest = %relative_manifest, error = %e, "Invalid package.json")
+                    }
+                },
+                Some("pyproject.toml") => match toml::from_str::<toml::Table>(&content) {
+                    Ok(table) => {
+                        let package =
+                            python_package(&mut packages, repo_path, &dir, relative_manifest);
+                        parse_pyproject(&table, package);
+                    }
+                    Err(e) => {
+                        warn!(manifest = %relative_manifest, error = %e, "Invalid pyproject.toml")
+                    }
+                },
+                Some("requirements.txt") => {
+                    let package = python_package(&mut packages, repo_path, &dir, relative_manifest);
+                    for line in content.lines() {
+                        let line = line.split('#').next().unwrap_or("").trim();
+                        if line.is_empty() || line.starts_with('-') {
+                            continue;
+                        }
+                        let (name, version) = parse_requirement(line);
+                        package.external_dependencies.insert(name, version);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for (dir, relative_manifest, table) in parsed_cargo {
+            // Virtual workspace manifests only contribute shared dependencies
+            let Some(name) = table
+                .get("package")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+            else {
+                continue;
+            };
+            let mut package = Package::new(
+                name.to_string(),
+                Ecosystem::Cargo,
+                relative_path(repo_path, &dir),
+                relative_manifest,
+            );
+            for key in ["dependencies", "build-dependencies"] {
+                if let Some(deps) = table.get(key).and_then(|d| d.as_table()) {
+                    for (key, value) in deps {
+                        // Renamed dependencies name the actual crate in `package`
+                        let name = value
+                            .get("package")
+                            .or_else(|| {
+                                cargo_workspace_dependencies
+                                    .get(key)
+                                    .and_then(|dep| dep.get("package"))
+                            })
+                            .and_then(|p| p.as_str())
+                            .unwrap_or(key);
+                        package.external_dependencies.insert(
+                            name.to_string(),
+                            cargo_requirement(key, value, &cargo_workspace_dependencies),
+                        );
+                    }
+                }
+            }
+            packages.push((dir, package));
+        }
+
+        let package_dirs: HashSet<PathBuf> = packages.iter().map(|(dir, _)| dir.clone()).collect();
+        let mut graph = DependencyGraph::default();
+        for (dir, mut package) in packages {
+            let (modules, edges) = scan_modules(&dir, package.ecosystem, &package_dirs, &skip);
+            package.modules = modules;
+            package.module_edges = edges;
+
+            let name = if graph.packages.contains_key(&package.name) {
+                format!("{} ({})", package.name, package.path)
+            } else {
+                package.name.clone()
+            };
+            package.name = name.clone();
+            graph.packages.insert(name, package);
+        }
+
+        // Dependencies on packages of the same repository are internal
+        let names: HashSet<String> = graph.packages.keys().cloned().collect();
+        for package in graph.packages.values_mut() {
+            let internal: Vec<String> = package
+                .external_dependencies
+                .keys()
+                .filter(|dep| names.contains(*dep) && **dep != package.name)
+                .cloned()
+                .collect();
+            for dep in internal {
+                package.external_dependencies.remove(&dep);
+                package.internal_dependencies.insert(dep);
+            }
+        }
+
+        debug!(
+            packages = graph.packages.len(),
+            "Analyzed repository dependency graph"
+        );
+        Ok(graph)
+    }
+
+    /// Whether no packages were found
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// External dependencies used by the most packages
+    pub fn notable_external_dependencies(&self) -> Vec<ExternalDependency> {
+        let mut dependencies: BTreeMap<&str, ExternalDependency> = BTreeMap::new();
+        for package in self.packages.values() {
+            for (dep, version) in &package.external_dependencies {
+                let entry = dependencies
+                    .entry(dep)
+                    .or_insert_with(|| ExternalDependency {
+                        name: dep.clone(),
+                        versions: BTreeSet::new(),
+                        used_by: BTreeSet::new(),
+                    });
+                entry.versions.insert(version.clone());
+                entry.used_by.insert(package.name.clone());
+            }
+        }
+
+        let mut dependencies: Vec<ExternalDependency> = dependencies.into_values().collect();
+        // Stable sort keeps alphabetical order among equally used dependencies
+        dependencies.sort_by_key(|d| std::cmp::Reverse(d.used_by.len()));
+        dependencies.truncate(NOTABLE_EXTERNAL_LIMIT);
+        dependencies
+    }
+
+    /// Mermaid diagram of the dependencies between packages
+    pub fn package_diagram(&self) -> String {
+        let ids: BTreeMap<&str, String> = self
+            .packages
+            .keys()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), format!("p{}", i)))
+            .collect();
+
+        let mut diagram = String::from("graph LR\n");
+        for name in self.packages.keys() {
+            diagram.push_str(&format!(
+                "    {}[\"{}\"]\n",
+                ids[name.as_str()],
+                mermaid_label(name)
+            ));
+        }
+        for package in self.packages.values() {
+            for dep in &package.internal_dependencies {
+                diagram.push_str(&format!(
+                    "    {} --> {}\n",
+                    ids[package.name.as_str()],
+                    ids[dep.as_str()]
+                ));
+            }
+        }
+        diagram
+    }
+
+    /// Mermaid diagram of the module imports within a package
+    pub fn module_diagram(package: &Package) -> String {
+        let ids: BTreeMap<&str, String> = package
+            .modules
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), format!("m{}", i)))
+            .collect();
+
+        let mut diagram = String::from("graph LR\n");
+        for module in &package.modules {
+            diagram.push_str(&format!(
+                "    {}[\"{}\"]\n",
+                ids[module.as_str()],
+                mermaid_label(module)
+            ));
+        }
+        for (from, to) in &package.module_edges {
+            diagram.push_str(&format!(
+                "    {} --> {}\n",
+                ids[from.as_str()],
+                ids[to.as_str()]
+            ));
+        }
+        diagram
+    }
+
+    /// Render the graph as a wiki page
+    pub fn to_page(&self) -> WikiPage {
+        let mut content = String::from("# Dependency Graph\n\n");
+        content.push_str(
+            "This page is generated from the repository's package manifests and source \
+             imports. It shows how the internal packages and modules depend on each other \
+             and which third-party dependencies they rely on.\n\n",
+        );
+
+        content.push_str("## Packages\n\n");
+        if self.packages.len() > 1 {
+            content.push_str(&format!("```mermaid\n{}```\n\n", self.package_diagram()));
+        }
+        content.push_str("| Package | Ecosystem | Path | Internal dependencies |\n");
+        content.push_str("|---------|-----------|------|-----------------------|\n");
+        for package in self.packages.values() {
+            let internal = if package.internal_dependencies.is_empty() {
+                "-".to_string()
+            } else {
+                package
+                    .internal_dependencies
+                    .iter()
+                    .map(|d| format!("`{}`", d))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            content.push_str(&format!(
+                "| `{}` | {} | `{}` | {} |\n",
+                package.name,
+                package.ecosystem.label(),
+                package.path,
+                internal
+            ));
+        }
+        content.push('\n');
+
+        let module_packages: Vec<&Package> = self
+            .packages
+            .values()
+            .filter(|p| !p.module_edges.is_empty() && p.modules.len() <= MAX_MODULES_PER_DIAGRAM)
+            .collect();
+        if !module_packages.is_empty() {
+            content.push_str("## Modules\n\n");
+            content.push_str("Arrows point from a module to the modules it imports.\n\n");
+            for package in module_packages {
+                content.push_str(&format!(
+                    "### {}\n\n```mermaid\n{}```\n\n",
+                    package.name,
+                    Self::module_diagram(package)
+                ));
+            }
+        }
+
+        let external = self.notable_external_dependencies();
+        if !external.is_empty() {
+            content.push_str("## External Dependencies\n\n");
+            content.push_str("| Dependency | Version | Used by |\n");
+            content.push_str("|------------|---------|---------|\n");
+            for dep in external {
+                content.push_str(&format!(
+                    "| `{}` | {} | {} |\n",
+                    dep.name,
+                    dep.versions.iter().cloned().collect::<Vec<_>>().join(", "),
+                    dep.used_by
+                        .iter()
+                        .map(|u| format!("`{}`", u))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            content.push('\n');
+        }
+
+        let mut page = WikiPage::new(
+            DEPENDENCY_GRAPH_PAGE_ID.to_string(),
+            "Dependency Graph".to_string(),
+            "Internal package and module relationships and notable external dependencies"
+                .to_string(),
+        );
+        page.content = content;
+        page.importance = ImportanceLevel::High;
+        page.tags = vec!["architecture".to_string(), "dependencies".to_string()];
+        page.file_paths = self
+            .packages
+            .values()
+            .flat_map(|p| p.manifests.iter().cloned())
+            .collect();
+        page.estimate_reading_time();
+        page
+    }
+}
+
+impl Package {
+    fn new(name: String, ecosystem: Ecosystem, path: String, manifest: String) -> Self {
+        Self {
+            name,
+            ecosystem,
+            path,
+            manifests: vec![manifest],
+            internal_dependencies: BTreeSet::new(),
+            external_dependencies: BTreeMap::new(),
+            modules: BTreeSet::new(),
+            module_edges: BTreeSet::new(),
+        }
+    }
+}
+
+/// The Python package of a directory, created on first use so that
+/// `pyproject.toml` and `requirements.txt` merge into one package
+fn python_package<'a>(
+    packages: &'a mut Vec<(PathBuf, Package)>,
+    repo_path: &Path,
+    dir: &Path,
+    manifest: String,
+) -> &'a mut Package {
+    let index = match packages
+        .iter()
+        .position(|(d, p)| d == dir && p.ecosystem == Ecosystem::Python)
+    {
+        Some(index) => {
+            packages[index].1.manifests.push(manifest);
+            index
+        }
+        None => {
+            packages.push((
+                dir.to_path_buf(),
+                Package::new(
+                    directory_name(repo_path, dir),
+                    Ecosystem::Python,
+                    relative_path(repo_path, dir),
+                    manifest,
+                ),
+            ));
+            packages.len() - 1
+        }
+    };
+    &mut packages[index].1
+}
+
+/// Read name and dependencies from PEP 621 or Poetry metadata
+fn parse_pyproject(table: &toml::Table, package: &mut Package) {
+    if let Some(project) = table.get("project") {
+        if let Some(name) = project.get("name").and_then(|n| n.as_str()) {
+            package.name = name.to_string();
+        }
+        for requirement in project
+            .get("dependencies")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|r| r.as_str())
+        {
+            let (name, version) = parse_requirement(requirement);
+            package.external_dependencies.insert(name, version);
+        }
+    }
+
+    if let Some(poetry) = table.get("tool").and_then(|t| t.get("poetry")) {
+        if let Some(name) = poetry.get("name").and_then(|n| n.as_str()) {
+            package.name = name.to_string();
+        }
+        if let Some(deps) = poetry.get("dependencies").and_then(|d| d.as_table()) {
+            for (dep, value) in deps.iter().filter(|(dep, _)| *dep != "python") {
+                let version = value
+                    .as_str()
+                    .or_else(|| value.get("version").and_then(|v| v.as_str()))
+                    .unwrap_or("*");
+                package
+                    .external_dependencies
+                    .insert(dep.to_lowercase(), version.to_string());
+            }
+        }
+    }
+}
+
+/// Split a PEP 508 requirement into its normalized name and version specifier
+fn parse_requirement(requirement: &str) -> (String, String) {
+    let requirement = requirement.split(';').next().unwrap_or("").trim();
+    let end = requirement
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = requirement[..end].to_lowercase().replace('_', "-");
+    let rest = requirement[end..].trim();
+    // Skip extras such as `uvicorn[standard]`
+    let rest = match rest.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map(|(_, v)| v).unwrap_or("").trim(),
+        None => rest,
+    };
+    let version = if rest.is_empty() { "*" } else { rest };
+    (name, version.to_string())
+}
+
+/// Version requirement of a Cargo dependency, resolving inherited
+/// (`workspace = true`) entries against the workspace dependencies
+fn cargo_requirement(
+    key: &str,
+    value: &toml::Value,
+    workspace_dependencies: &toml::Table,
+) -> String {
+    if let Some(version) = value.as_str() {
+        return version.to_string();
+    }
+    if value.get("workspace").and_then(|w| w.as_bool()) == Some(true) {
+        return workspace_dependencies
+            .get(key)
+            .map(|dep| cargo_requirement(key, dep, &toml::Table::new()))
+            .unwrap_or_else(|| "workspace".to_string());
+    }
+    if let Some(version) = value.get("version").and_then(|v| v.as_str()) {
+        return version.to_string();
+    }
+    if value.get("path").is_some() {
+        return "path".to_string();
+    }
+    if value.get("git").is_some() {
+        return "git".to_string();
+    }
+    "*".to_string()
+}
+
+/// Find the modules of a package and the imports between them
+fn scan_modules(
+    package_dir: &Path,
+    ecosystem: Ecosystem,
+    package_dirs: &HashSet<PathBuf>,
+    skip: &dyn Fn(&Path) -> bool,
+) -> (BTreeSet<String>, BTreeSet<(String, String)>) {
+    let source_root = match ecosystem {
+        Ecosystem::Cargo => package_dir.join("src"),
+        Ecosystem::Npm | Ecosystem::Python => {
+            let src = package_dir.join("src");
+            if src.is_dir() {
+                src
+            } else {
+                package_dir.to_path_buf()
+            }
+        }
+    };
+    if !source_root.is_dir() {
+        return Default::default();
+    }
+
+    let extensions: &[&str] = match ecosystem {
+        Ecosystem::Cargo => &["rs"],
+        Ecosystem::Npm => &["js", "jsx", "ts", "tsx", "mjs", "cjs", "vue", "svelte"],
+        Ecosystem::Python => &["py"],
+    };
+    let files: Vec<PathBuf> = WalkDir::new(&source_root)
+        .into_iter()
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !e.file_type().is_dir()
+                || !(skip(e.path()) || package_dirs.contains(e.path()))
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.metadata().map(|m| m.len()).unwrap_or(0) <= MAX_SOURCE_FILE_SIZE)
+        .map(|e| e.into_path())
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| extensions.contains(&e))
+        })
+        .collect();
+
+    // Python projects usually have a single top-level package; its submodules
+    // are the interesting units
+    let mut module_root = source_root.clone();
+    let mut python_prefix = Vec::new();
+    if ecosystem == Ecosystem::Python {
+        let top_level: Vec<PathBuf> = std::fs::read_dir(&source_root)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.join("__init__.py").is_file() && !package_dirs.contains(p))
+            .collect();
+        if let [package] = top_level.as_slice() {
+            if let Some(name) = package.file_name().and_then(|n| n.to_str()) {
+                python_prefix.push(name.to_string());
+                module_root = package.clone();
+            }
+        }
+    }
+
+    let mut modules = BTreeSet::new();
+    let mut references = Vec::new();
+    for file in &files {
+        let Ok(relative) = file.strip_prefix(&module_root) else {
+            continue;
+        };
+        let components = path_components(relative);
+        let Some(module) = module_of(&components, ecosystem) else {
+            continue;
+        };
+        modules.insert(module.clone());
+
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let targets: Vec<Vec<String>> = match ecosystem {
+            Ecosystem::Cargo => rust_module_references(&source)
+                .into_iter()
+                .map(|m| vec![m])
+                .collect(),
+            Ecosystem::Npm => js_relative_imports(&source)
+                .into_iter()
+                .filter_map(|spec| resolve_relative(&components[..components.len() - 1], &spec))
+                .collect(),
+            Ecosystem::Python => {
+                let mut package = python_prefix.clone();
+                package.extend_from_slice(&components[..components.len() - 1]);
+                python_imports(&source, &package)
+                    .into_iter()
+                    .filter_map(|target| {
+                        target
+                            .strip_prefix(python_prefix.as_slice())
+                            .map(|t| t.to_vec())
+                    })
+                    .collect()
+            }
+        };
+        for target in targets {
+            if let Some(target) = target_module(&target) {
+                references.push((module.clone(), target));
+            }
+        }
+    }
+
+    let edges = references
+        .into_iter()
+        .filter(|(from, to)| from != to && modules.contains(to))
+        .collect();
+    (modules, edges)
+}
+
+/// Module a source file belongs to, given its path below the module root
+fn module_of(components: &[String], ecosystem: Ecosystem) -> Option<String> {
+    match components {
+        [] => None,
+        [file] => {
+            let stem = file.split('.').next().unwrap_or(file);
+            let entry_point = match ecosystem {
+                Ecosystem::Cargo => matches!(stem, "lib" | "main"),
+                Ecosystem::Python => stem == "__init__",
+                Ecosystem::Npm => false,
+            };
+            (!entry_point && !stem.is_empty()).then(|| stem.to_string())
+        }
+        [dir, ..] => Some(dir.clone()),
+    }
+}
+
+/// Module an import target resolves to
+fn target_module(target: &[String]) -> Option<String> {
+    match target {
+        [] => None,
+        [file] => file.split('.').next().map(str::to_string),
+        [dir, ..] => Some(dir.clone()),
+    }
+}
+
+/// Top-level modules referenced through `crate::` paths
+fn rust_module_references(source: &str) -> BTreeSet<String> {
+    const PREFIX: &str = "crate::";
+    let mut modules = BTreeSet::new();
+    let mut rest = source;
+    while let Some(pos) = rest.find(PREFIX) {
+        let preceded_by_ident = rest[..pos]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$');
+        rest = &rest[pos + PREFIX.len()..];
+        if preceded_by_ident {
+            continue;
+        }
+
+        if let Some(group) = rest.strip_prefix('{') {
+            let mut depth = 0;
+            let mut item_start = 0;
+            for (i, c) in group.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 0 => {
+                        modules.extend(leading_ident(&group[item_start..i]));
+                        break;
+                    }
+                    '}' => depth -= 1,
+                    ',' if depth == 0 => {
+                        modules.extend(leading_ident(&group[item_start..i]));
+                        item_start = i + 1;
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            modules.extend(leading_ident(rest));
+        }
+    }
+    modules.remove("self");
+    modules
+}
+
+/// Identifier at the start of a string, after whitespace
+fn leading_ident(text: &str) -> Option<String> {
+    let text = text.trim_start();
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    (end > 0).then(|| text[..end].to_string())
+}
+
+/// Relative module specifiers of `import`/`export ... from`/`require` statements
+fn js_relative_imports(source: &str) -> Vec<String> {
+    let mut specifiers = Vec::new();
+    for keyword in ["from", "import", "require(", "import("] {
+        let mut rest = source;
+        while let Some(pos) = rest.find(keyword) {
+            let preceded_by_ident = rest[..pos]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.');
+            rest = &rest[pos + keyword.len()..];
+            if preceded_by_ident {
+                continue;
+            }
+
+            let after = rest.trim_start();
+            let Some(quote) = after
+                .chars()
+                .next()
+                .filter(|c| matches!(c, '\'' | '"' | '`'))
+            else {
+                continue;
+            };
+            if let Some(end) = after[1..].find(quote) {
+                let specifier = &after[1..1 + end];
+                if specifier.starts_with('.') {
+                    specifiers.push(specifier.to_string());
+                }
+            }
+        }
+    }
+    specifiers
+}
+
+/// Resolve a relative import against the directory of the importing file;
+/// `None` if it leaves the module root
+fn resolve_relative(dir: &[String], specifier: &str) -> Option<Vec<String>> {
+    let mut components = dir.to_vec();
+    for part in specifier.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            part => components.push(part.to_string()),
+        }
+    }
+    Some(components)
+}
+
+/// Absolute module paths imported by a Python file in `package`
+fn python_imports(source: &str, package: &[String]) -> Vec<Vec<String>> {
+    let mut imports = Vec::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("from ") {
+            let module = rest.split_whitespace().next().unwrap_or("");
+            let dots = module.chars().take_while(|&c| c == '.').count();
+            let name = &module[dots..];
+            let mut target = if dots == 0 {
+                Vec::new()
+            } else {
+                let keep = package.len().checked_sub(dots - 1);
+                match keep {
+                    Some(keep) => package[..keep].to_vec(),
+                    None => continue,
+                }
+            };
+            target.extend(
+                name.split('.')
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_string),
+            );
+            // `from . import module` names the module in the import list
+            if name.is_empty() {
+                if let Some((_, names)) = rest.split_once(" import ") {
+                    for imported in names.split(',') {
+                        if let Some(ident) = leading_ident(imported.trim_start_matches('(')) {
+                            let mut target = target.clone();
+                            target.push(ident);
+                            imports.push(target);
+                        }
+                    }
+                }
+                continue;
+            }
+            imports.push(target);
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            for item in rest.split(',') {
+                let module = item.split_whitespace().next().unwrap_or("");
+                if !module.is_empty() {
+                    imports.push(module.split('.').map(str::to_string).collect());
+                }
+            }
+        }
+    }
+    imports
+}
+
+/// Path components as strings
+fn path_components(path: &Path) -> Vec<String> {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Path relative to the repository root with forward slashes (`.` for the root)
+fn relative_path(repo_path: &Path, path: &Path) -> String {
+    let components = path_components(path.strip_prefix(repo_path).unwrap_or(path));
+    if components.is_empty() {
+        ".".to_string()
+    } else {
+        components.join("/")
+    }
+}
+
+/// Name of a directory, falling back to the repository directory name for the root
+fn directory_name(repo_path: &Path, dir: &Path) -> String {
+    dir.file_name()
+        .or_else(|| repo_path.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "root".to_string())
+}
+
+/// Escape a Mermaid node label
+fn mermaid_label(label: &str) -> String {
+    label.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_rust_module_references() {
+        let source =
+            "use crate::types::WikiPage;\nuse crate::{cache, export::{self, ExportFormat}};\n\
+                      let x = my_crate::ignored::Thing;\nfn f() -> crate::pdf::Output {}\n";
+        let modules: Vec<String> = rust_module_references(source).into_iter().collect();
+        assert_eq!(modules, vec!["cache", "export", "pdf", "types"]);
+    }
+
+    #[test]
+    fn test_analyze_cargo_workspace() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"core\", \"cli\"]\n\n[workspace.dependencies]\nserde = \"1.0\"\n",
+        );
+        write(
+            root,
+            "core/Cargo.toml",
+            "[package]\nname = \"demo-core\"\n\n[dependencies]\nserde = { workspace = true }\n",
+        );
+        write(root, "core/src/lib.rs", "pub mod config;\npub mod error;\n");
+        write(root, "core/src/config.rs", "use crate::error::Error;\n");
+        write(root, "core/src/error.rs", "pub struct Error;\n");
+        write(
+            root,
+            "cli/Cargo.toml",
+            "[package]\nname = \"demo-cli\"\n\n[dependencies]\ndemo-core = { path = \"../core\" }\nclap = \"4.0\"\n",
+        );
+        write(root, "cli/src/main.rs", "fn main() {}\n");
+        write(
+            root,
+            "target/debug/Cargo.toml",
+            "[package]\nname = \"ignored\"\n",
+        );
+
+        let graph = DependencyGraph::analyze(root, &[]).unwrap();
+
+        assert_eq!(
+            graph.packages.keys().collect::<Vec<_>>(),
+            vec!["demo-cli", "demo-core"]
+        );
+        let cli = &graph.packages["demo-cli"];
+        assert!(cli.internal_dependencies.contains("demo-core"));
+        assert_eq!(cli.external_dependencies["clap"], "4.0");
+        let core = &graph.packages["demo-core"];
+        assert_eq!(core.external_dependencies["serde"], "1.0");
+        assert!(core
+            .module_edges
+            .contains(&("config".to_string(), "error".to_string())));
+
+        let page = graph.to_page();
+        assert_eq!(page.id, DEPENDENCY_GRAPH_PAGE_ID);
+        assert!(page.content.contains("p0 --> p1"));
+        assert!(page.content.contains("m0 --> m1"));
+        // Same repository, same page
+        assert_eq!(
+            page.content,
+            DependencyGraph::analyze(root, &[])
+                .unwrap()
+                .to_page()
+                .content
+        );
+    }
+
+    #[test]
+    fn test_analyze_npm_and_python_imports() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        write(
+            root,
+            "web/package.json",
+            r#"{"name": "web", "dependencies": {"react": "^18.0.0"}}"#,
+        );
+        write(
+            root,
+            "web/src/components/App.tsx",
+            "import React from 'react';\nimport { get } from '../api/client';\n",
+        );
+        write(root, "web/src/api/client.ts", "export const get = 1;\n");
+        write(
+            root,
+            "server/requirements.txt",
+            "fastapi[all]>=0.100 # web\n-r dev.txt\n",
+        );
+        write(root, "server/app/__init__.py", "");
+        write(
+            root,
+            "server/app/routes.py",
+            "from .services import users\n",
+        );
+        write(root, "server/app/services/users.py", "import os\n");
+
+        let graph = DependencyGraph::analyze(root, &[]).unwrap();
+
+        let web = &graph.packages["web"];
+        assert!(web
+            .module_edges
+            .contains(&("components".to_string(), "api".to_string())));
+        let server = &graph.packages["server"];
+        assert_eq!(server.external_dependencies["fastapi"], ">=0.100");
+        assert!(server
+            .module_edges
+            .contains(&("routes".to_string(), "services".to_string())));
+    }
+}
@@ -0,0 +1,299 @@
+//! Differences between wiki versions
+//!
+//! Compares two wikis page by page: pages are matched by ID and reported as
+//! added, removed or changed. Changed pages are split into Markdown sections
+//! (by heading) and every changed section carries a unified text diff, so
+//! reviewers can see exactly what documentation changed after a reindex.
+
+use crate::cache::WikiVersion;
+use crate::types::{WikiPage, WikiStructure};
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+use std::collections::{HashMap, HashSet};
+
+/// Lines of unchanged context around each change in section diffs
+const CONTEXT_LINES: usize = 3;
+/// Key of the text before the first heading of a page
+const PREAMBLE: &str = "(introduction)";
+
+/// Page identity in a diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRef {
+    /// Page ID
+    pub id: String,
+    /// Page title
+    pub title: String,
+}
+
+/// How a section changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Changed section of a page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionDiff {
+    /// Section heading (without `#` markers)
+    pub heading: String,
+    /// Kind of change
+    pub change: ChangeKind,
+    /// Unified diff of the section text
+    pub diff: String,
+}
+
+/// Changes to a page present in both versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageDiff {
+    /// Page ID
+    pub id: String,
+    /// Title in the old version
+    pub old_title: String,
+    /// Title in the new version
+    pub new_title: String,
+    /// Sections that were added, removed or modified, in page order
+    pub sections: Vec<SectionDiff>,
+}
+
+/// Differences between two wiki versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiDiff {
+    /// Version compared from
+    pub from: WikiVersion,
+    /// Version compared to
+    pub to: WikiVersion,
+    /// Pages only in the new version
+    pub added_pages: Vec<PageRef>,
+    /// Pages only in the old version
+    pub removed_pages: Vec<PageRef>,
+    /// Pages in both versions whose title or content changed
+    pub changed_pages: Vec<PageDiff>,
+    /// Number of pages identical in both versions
+    pub unchanged_pages: usize,
+}
+
+impl WikiDiff {
+    /// Compare two wikis
+    pub fn between(from: &WikiStructure, to: &WikiStructure) -> Self {
+        let old_pages: HashMap<&str, &WikiPage> =
+            from.pages.iter().map(|p| (p.id.as_str(), p)).collect();
+        let new_ids: HashSet<&str> = to.pages.iter().map(|p| p.id.as_str()).collect();
+
+        let mut diff = WikiDiff {
+            from: WikiVersion::of(from),
+            to: WikiVersion::of(to),
+            added_pages: Vec::new(),
+            removed_pages: from
+                .pages
+                .iter()
+                .filter(|p| !new_ids.contains(p.id.as_str()))
+                .map(PageRef::of)
+                .collect(),
+            changed_pages: Vec::new(),
+            unchanged_pages: 0,
+        };
+
+        for page in &to.pages {
+            match old_pages.get(page.id.as_str()) {
+                None => diff.added_pages.push(PageRef::of(page)),
+                Some(old) if old.title == page.title && old.content == page.content => {
+                    diff.unchanged_pages += 1
+                }
+                Some(old) => diff.changed_pages.push(PageDiff {
+                    id: page.id.clone(),
+                    old_title: old.title.clone(),
+                    new_title: page.title.clone(),
+                    sections: diff_sections(&old.content, &page.content),
+                }),
+            }
+        }
+
+        diff
+    }
+
+    /// Whether the versions have identical pages
+    pub fn is_empty(&self) -> bool {
+        self.added_pages.is_empty()
+            && self.removed_pages.is_empty()
+            && self.changed_pages.is_empty()
+    }
+}
+
+impl PageRef {
+    fn of(page: &WikiPage) -> Self {
+        Self {
+            id: page.id.clone(),
+            title: page.title.clone(),
+        }
+    }
+}
+
+/// Section-level diff of two Markdown documents
+fn diff_sections(old: &str, new: &str) -> Vec<SectionDiff> {
+    let old_sections = split_sections(old);
+    let new_sections = split_sections(new);
+    let old_by_heading: HashMap<&str, &str> = old_sections
+        .iter()
+        .map(|(h, t)| (h.as_str(), t.as_str()))
+        .collect();
+    let new_by_heading: HashMap<&str, &str> = new_sections
+        .iter()
+        .map(|(h, t)| (h.as_str(), t.as_str()))
+        .collect();
+
+    let mut sections = Vec::new();
+    for (heading, text) in &new_sections {
+        let (change, old_text) = match old_by_heading.get(heading.as_str()) {
+            None => (ChangeKind::Added, ""),
+            Some(old_text) if *old_text == text.as_str() => continue,
+            Some(old_text) => (ChangeKind::Modified, *old_text),
+        };
+        sections.push(SectionDiff {
+            heading: heading.clone(),
+            change,
+            diff: unified_diff(old_text, text),
+        });
+    }
+    for (heading, text) in &old_sections {
+        if !new_by_heading.contains_key(heading.as_str()) {
+            sections.push(SectionDiff {
+                heading: heading.clone(),
+                change: ChangeKind::Removed,
+                diff: unified_diff(text, ""),
+            });
+        }
+    }
+    sections
+}
+
+/// Split Markdown into `(heading, text)` sections
+///
+/// Headings inside fenced code blocks are ignored. Repeated headings get a
+/// counter so every section has a unique key.
+pub(crate) fn split_sections(markdown: &str) -> Vec<(String, String)> {
+    let mut sections: Vec<(String, String)> = vec![(PREAMBLE.to_string(), String::new())];
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+        } else if let Some(heading) = parse_heading(trimmed) {
+            let count = seen.entry(heading.clone()).or_insert(0);
+            *count += 1;
+            let key = if *count == 1 {
+                heading
+            } else {
+                format!("{} ({})", heading, count)
+            };
+            sections.push((key, String::new()));
+        }
+
+        let text = &mut sections.last_mut().expect("preamble section").1;
+        text.push_str(line);
+        text.push('\n');
+    }
+
+    // Drop an empty preamble so documents starting with a heading don't report it
+    if sections[0].1.trim().is_empty() {
+        sections.remove(0);
+    }
+    sections
+}
+
+/// Text of an ATX heading line (`## Title`)
+fn parse_heading(line: &str) -> Option<String> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim().to_string())
+}
+
+/// Unified diff of two texts
+fn unified_diff(old: &str, new: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wiki(pages: &[(&str, &str)]) -> WikiStructure {
+        let mut wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/test/repo".to_string(),
+        );
+        for (id, content) in pages {
+            let mut page = WikiPage::new(id.to_string(), id.to_string(), String::new());
+            page.content = content.to_string();
+            wiki.pages.push(page);
+        }
+        wiki
+    }
+
+    #[test]
+    fn test_split_sections_ignores_headings_in_code() {
+        let sections = split_sections("# Title\nIntro\n```sh\n# comment\n```\n## Usage\nRun it\n");
+        let headings: Vec<_> = sections.iter().map(|(h, _)| h.as_str()).collect();
+        assert_eq!(headings, vec!["Title", "Usage"]);
+        assert!(sections[0].1.contains("# comment"));
+    }
+
+    #[test]
+    fn test_wiki_diff() {
+        let old = wiki(&[
+            ("overview", "# Overview\nSame\n"),
+            (
+                "setup",
+                "# Setup\n## Install\nRun make\n## Legacy\nOld notes\n",
+            ),
+            ("removed", "# Removed\n"),
+        ]);
+        let new = wiki(&[
+            ("overview", "# Overview\nSame\n"),
+            (
+                "setup",
+                "# Setup\n## Install\nRun cargo build\n## Config\nEdit the file\n",
+            ),
+            ("added", "# Added\n"),
+        ]);
+
+        let diff = WikiDiff::between(&old, &new);
+        assert_eq!(diff.unchanged_pages, 1);
+        assert_eq!(diff.added_pages[0].id, "added");
+        assert_eq!(diff.removed_pages[0].id, "removed");
+
+        let sections = &diff.changed_pages[0].sections;
+        let changes: Vec<_> = sections
+            .iter()
+            .map(|s| (s.heading.as_str(), s.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("Install", ChangeKind::Modified),
+                ("Config", ChangeKind::Added),
+                ("Legacy", ChangeKind::Removed),
+            ]
+        );
+        assert!(sections[0].diff.contains("-Run make"));
+        assert!(sections[0].diff.contains("+Run cargo build"));
+    }
+}
@@ -0,0 +1,245 @@
+//! Manual edits of generated wiki pages
+//!
+//! Human edits are kept apart from generated content: every edited page
+//! remembers the generated text the edit was based on. When the wiki is
+//! regenerated, edits are merged section by section (by Markdown heading)
+//! into the new content instead of being overwritten: sections the user
+//! changed keep the user's text, untouched sections pick up the regenerated
+//! text, and sections changed on both sides are reported as conflicts.
+
+use crate::diff::split_sections;
+use crate::types::{WikiPage, WikiStructure};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Manual edit of a single page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageEdit {
+    /// Edited page ID
+    pub page_id: String,
+    /// Title set by the user, if changed
+    pub title: Option<String>,
+    /// Edited Markdown content
+    pub content: String,
+    /// Generated content the edit was based on
+    pub base_content: String,
+    /// Who made the edit, if known
+    pub edited_by: Option<String>,
+    /// When the page was last edited
+    pub edited_at: DateTime<Utc>,
+    /// Sections changed both by the user and by the last regeneration;
+    /// the user's text was kept
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+}
+
+/// Manual edits of a wiki, keyed by page ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WikiEdits {
+    pub pages: BTreeMap<String, PageEdit>,
+}
+
+impl WikiEdits {
+    /// Record an edit of a page and apply it to the page
+    ///
+    /// The first edit of a page stores the page's current (generated)
+    /// content as the merge base; later edits keep that base.
+    pub fn edit_page(
+        &mut self,
+        page: &mut WikiPage,
+        title: Option<String>,
+        content: String,
+        edited_by: Option<String>,
+    ) {
+        let base_content = match self.pages.get(&page.id) {
+            Some(edit) => edit.base_content.clone(),
+            None => page.content.clone(),
+        };
+        let title = title.or_else(|| self.pages.get(&page.id).and_then(|e| e.title.clone()));
+
+        if let Some(title) = &title {
+            page.title = title.clone();
+        }
+        page.content = content.clone();
+
+        self.pages.insert(
+            page.id.clone(),
+            PageEdit {
+                page_id: page.id.clone(),
+                title,
+                content,
+                base_content,
+                edited_by,
+                edited_at: Utc::now(),
+                conflicts: Vec::new(),
+            },
+        );
+    }
+
+    /// Merge the edits into a freshly generated wiki
+    ///
+    /// Each edited page of the wiki gets the merged content and the edit is
+    /// rebased onto the regenerated text. Edits of pages missing from the
+    /// wiki are kept in case the page comes back. Returns the IDs of pages
+    /// with conflicts.
+    pub fn apply(&mut self, wiki: &mut WikiStructure) -> Vec<String> {
+        let mut conflicted = Vec::new();
+        for page in &mut wiki.pages {
+            if self.apply_to_page(page) {
+                conflicted.push(page.id.clone());
+            }
+        }
+        conflicted
+    }
+
+    /// Merge the edit of a single freshly generated page, if it was edited
+    ///
+    /// Returns whether the merge had conflicts.
+    pub fn apply_to_page(&mut self, page: &mut WikiPage) -> bool {
+        match self.pages.get_mut(&page.id) {
+            Some(edit) => {
+                edit.rebase(page);
+                !edit.conflicts.is_empty()
+            }
+            None => false,
+        }
+    }
+
+    /// Whether no page has been edited
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+impl PageEdit {
+    /// Merge this edit into the regenerated `page` and rebase onto it
+    fn rebase(&mut self, page: &mut WikiPage) {
+        let regenerated = std::mem::take(&mut page.content);
+        let (content, conflicts) = merge_sections(&self.base_content, &self.content, &regenerated);
+
+        if let Some(title) = &self.title {
+            page.title = title.clone();
+        }
+        page.content = content.clone();
+
+        self.content = content;
+        self.base_content = regenerated;
+        self.conflicts = conflicts;
+    }
+}
+
+/// Three-way merge of Markdown documents at section granularity
+///
+/// Returns the merged text and the headings of conflicting sections.
+fn merge_sections(base: &str, edited: &str, regenerated: &str) -> (String, Vec<String>) {
+    if base == regenerated {
+        return (edited.to_string(), Vec::new());
+    }
+    if base == edited || edited == regenerated {
+        return (regenerated.to_string(), Vec::new());
+    }
+
+    let base_sections: HashMap<String, String> = split_sections(base).into_iter().collect();
+    let edited_sections = split_sections(edited);
+    let regenerated_sections = split_sections(regenerated);
+    let regenerated_by_heading: HashMap<&str, &str> = regenerated_sections
+        .iter()
+        .map(|(h, t)| (h.as_str(), t.as_str()))
+        .collect();
+
+    // Follow the user's section order
+    let mut merged: Vec<(&str, &str)> = Vec::new();
+    let mut conflicts = Vec::new();
+    for (heading, text) in &edited_sections {
+        let regenerated = regenerated_by_heading.get(heading.as_str()).copied();
+        match base_sections.get(heading) {
+            // Untouched by the user: take the regenerated text, or drop the
+            // section if regeneration removed it
+            Some(base) if base == text => {
+                if let Some(regenerated) = regenerated {
+                    merged.push((heading.as_str(), regenerated));
+                }
+            }
+            Some(base) => {
+                if regenerated.is_some_and(|r| r != base.as_str() && r != text.as_str()) {
+                    conflicts.push(heading.clone());
+                }
+                merged.push((heading.as_str(), text.as_str()));
+            }
+            None => merged.push((heading.as_str(), text.as_str())),
+        }
+    }
+
+    // Insert sections new in the regenerated text after their predecessor
+    let edited_headings: HashSet<&str> = edited_sections.iter().map(|(h, _)| h.as_str()).collect();
+    let mut previous: Option<&str> = None;
+    for (heading, text) in &regenerated_sections {
+        if !base_sections.contains_key(heading) && !edited_headings.contains(heading.as_str()) {
+            let position = previous
+                .and_then(|p| merged.iter().position(|(h, _)| *h == p))
+                .map_or(0, |i| i + 1);
+            merged.insert(position, (heading.as_str(), text.as_str()));
+        }
+        previous = Some(heading.as_str());
+    }
+
+    (
+        merged.into_iter().map(|(_, text)| text).collect(),
+        conflicts,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_sections() {
+        let base = "# Setup\nIntro\n## Install\nRun make\n## Usage\nRun it\n";
+        let edited = "# Setup\nIntro\n## Install\nRun make install\n## Usage\nRun it\n";
+        let regenerated =
+            "# Setup\nNew intro\n## Install\nRun cargo build\n## Usage\nRun it\n## Config\nEdit it\n";
+
+        let (merged, conflicts) = merge_sections(base, edited, regenerated);
+        assert_eq!(
+            merged,
+            "# Setup\nNew intro\n## Install\nRun make install\n## Usage\nRun it\n## Config\nEdit it\n"
+        );
+        assert_eq!(conflicts, vec!["Install".to_string()]);
+    }
+
+    #[test]
+    fn test_edits_survive_regeneration() {
+        let mut page = WikiPage::new("setup".to_string(), "Setup".to_string(), String::new());
+        page.content = "# Setup\n## Install\nRun make\n".to_string();
+
+        let mut edits = WikiEdits::default();
+        edits.edit_page(
+            &mut page,
+            Some("Getting Set Up".to_string()),
+            "# Setup\n## Install\nRun make\n## Notes\nAsk in chat\n".to_string(),
+            None,
+        );
+
+        let mut wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/test/repo".to_string(),
+        );
+        page.title = "Setup".to_string();
+        page.content = "# Setup\n## Install\nRun cargo build\n".to_string();
+        wiki.pages.push(page);
+
+        assert!(edits.apply(&mut wiki).is_empty());
+        assert_eq!(wiki.pages[0].title, "Getting Set Up");
+        assert_eq!(
+            wiki.pages[0].content,
+            "# Setup\n## Install\nRun cargo build\n## Notes\nAsk in chat\n"
+        );
+        assert_eq!(
+            edits.pages["setup"].base_content,
+            "# Setup\n## Install\nRun cargo build\n"
+        );
+    }
+}
@@ -2,8 +2,12 @@
 //!
 //! This module handles exporting wiki structures to various formats.
 
+use crate::assets::{copy_assets, rewrite_asset_images, MEDIA_DIR};
+use crate::links::rewrite_page_links;
+use crate::markdown_organizer::{NavigationItem, NavigationKind};
 use crate::types::{WikiPage, WikiStructure};
 use serde_json;
+use std::collections::BTreeSet;
 use std::path::Path;
 use tokio::fs;
 use tracing::{debug, info};
@@ -14,6 +18,8 @@ use wikify_core::{ErrorContext, WikifyError, WikifyResult};
 pub enum ExportFormat {
     /// Export as individual Markdown files
     Markdown,
+    /// Export as an mdBook source tree (`book.toml` and `src/`)
+    MdBook,
     /// Export as a single JSON file
     Json,
     /// Export as HTML files
@@ -22,6 +28,41 @@ pub enum ExportFormat {
     Pdf,
 }
 
+impl ExportFormat {
+    /// Whether the export produces a single file rather than a directory
+    pub fn is_single_file(&self) -> bool {
+        matches!(self, ExportFormat::Json | ExportFormat::Pdf)
+    }
+
+    /// File extension of single-file exports
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown | ExportFormat::MdBook => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+            ExportFormat::Pdf => "pdf",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "mdbook" => Ok(ExportFormat::MdBook),
+            "json" => Ok(ExportFormat::Json),
+            "html" => Ok(ExportFormat::Html),
+            "pdf" => Ok(ExportFormat::Pdf),
+            other => Err(format!(
+                "Unknown export format '{}' (expected markdown, mdbook, json, html or pdf)",
+                other
+            )),
+        }
+    }
+}
+
 /// Wiki exporter that handles different output formats
 pub struct WikiExporter {
     // Future: could add template engines, styling options, etc.
@@ -44,6 +85,7 @@ impl WikiExporter {
 
         match format {
             ExportFormat::Markdown => self.export_markdown(wiki, output_path).await,
+            ExportFormat::MdBook => self.export_mdbook(wiki, output_path).await,
             ExportFormat::Json => self.export_json(wiki, output_path).await,
             ExportFormat::Html => self.export_html(wiki, output_path).await,
             ExportFormat::Pdf => self.export_pdf(wiki, output_path).await,
@@ -62,12 +104,15 @@ impl WikiExporter {
         let index_path = output_path.join("README.md");
         fs::write(&index_path, index_content).await?;
 
+        // Bundle the repository images the pages embed
+        let assets = copy_assets(wiki, &output_path.join(MEDIA_DIR)).await?;
+
         // Export each page as a separate Markdown file
         for page in &wiki.pages {
             let filename = self.sanitize_filename(&format!("{}.md", page.title));
             let page_path = output_path.join(&filename);
 
-            let page_content = self.generate_markdown_page(page, wiki);
+            let page_content = self.generate_markdown_page(page, wiki, &assets);
             fs::write(&page_path, page_content).await?;
 
             debug!("Exported page: {} -> {:?}", page.title, page_path);
@@ -85,15 +130,64 @@ impl WikiExporter {
         Ok(())
     }
 
+    /// Export wiki as an mdBook source tree, ready for `mdbook build`
+    async fn export_mdbook(&self, wiki: &WikiStructure, output_path: &Path) -> WikifyResult<()> {
+        info!("Exporting wiki as mdBook to: {:?}", output_path);
+
+        let src_dir = output_path.join("src");
+        fs::create_dir_all(&src_dir).await?;
+        fs::write(
+            output_path.join("book.toml"),
+            self.generate_book_toml(wiki)?,
+        )
+        .await?;
+
+        let assets = copy_assets(wiki, &src_dir.join(MEDIA_DIR)).await?;
+
+        // Pages are named after their IDs, which are safe in SUMMARY.md links
+        for page in &wiki.pages {
+            let content =
+                rewrite_page_links(wiki, &page.content, |target| self.mdbook_filename(target));
+            let content = rewrite_asset_images(&content, &assets, |path| {
+                format!("{}/{}", MEDIA_DIR, path).replace(' ', "%20")
+            });
+            fs::write(src_dir.join(self.mdbook_filename(page)), content).await?;
+        }
+
+        let introduction = format!("# {}\n\n{}\n", wiki.title, wiki.description);
+        fs::write(src_dir.join("README.md"), introduction).await?;
+        fs::write(
+            src_dir.join("SUMMARY.md"),
+            self.generate_mdbook_summary(wiki),
+        )
+        .await?;
+
+        info!("Successfully exported {} pages as mdBook", wiki.pages.len());
+        Ok(())
+    }
+
     /// Export wiki as JSON
     async fn export_json(&self, wiki: &WikiStructure, output_path: &Path) -> WikifyResult<()> {
         info!("Exporting wiki as JSON to: {:?}", output_path);
 
-        let json_content = serde_json::to_string_pretty(wiki).map_err(|e| WikifyError::Config {
-            message: format!("Failed to serialize wiki to JSON: {}", e),
-            source: Some(Box::new(e)),
-            context: ErrorContext::new("wiki_export"),
-        })?;
+        // The wiki plus its table of contents, so consumers need not rebuild it
+        #[derive(serde::Serialize)]
+        struct JsonExport<'a> {
+            #[serde(flatten)]
+            wiki: &'a WikiStructure,
+            navigation: Vec<crate::NavigationItem>,
+        }
+        let export = JsonExport {
+            wiki,
+            navigation: crate::NavigationBuilder::new().build(wiki),
+        };
+
+        let json_content =
+            serde_json::to_string_pretty(&export).map_err(|e| WikifyError::Config {
+                message: format!("Failed to serialize wiki to JSON: {}", e),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("wiki_export"),
+            })?;
 
         // Ensure parent directory exists
         if let Some(parent) = output_path.parent() {
@@ -106,47 +200,43 @@ impl WikiExporter {
         Ok(())
     }
 
-    /// Export wiki as HTML files
+    /// Export wiki as a static HTML site with navigation and search
     async fn export_html(&self, wiki: &WikiStructure, output_path: &Path) -> WikifyResult<()> {
         info!("Exporting wiki as HTML to: {:?}", output_path);
 
-        // Create output directory
-        fs::create_dir_all(output_path).await?;
-
-        // Create CSS file
-        let css_content = self.generate_css();
-        let css_path = output_path.join("style.css");
-        fs::write(&css_path, css_content).await?;
-
-        // Create index HTML
-        let index_html = self.generate_html_index(wiki);
-        let index_path = output_path.join("index.html");
-        fs::write(&index_path, index_html).await?;
-
-        // Export each page as HTML
-        for page in &wiki.pages {
-            let filename = self.sanitize_filename(&format!("{}.html", page.title));
-            let page_path = output_path.join(&filename);
-
-            let page_html = self.generate_html_page(page, wiki);
-            fs::write(&page_path, page_html).await?;
-
-            debug!("Exported HTML page: {} -> {:?}", page.title, page_path);
-        }
+        crate::site::build_site(wiki, output_path).await?;
 
         info!("Successfully exported {} pages as HTML", wiki.pages.len());
         Ok(())
     }
 
-    /// Export wiki as PDF (placeholder implementation)
-    async fn export_pdf(&self, _wiki: &WikiStructure, _output_path: &Path) -> WikifyResult<()> {
-        // TODO: Implement PDF export using a library like wkhtmltopdf or headless Chrome
-        Err(Box::new(WikifyError::Config {
-            message: "PDF export is not yet implemented".to_string(),
-            source: None,
-            context: ErrorContext::new("wiki_export")
-                .with_suggestion("Use Markdown or HTML export instead"),
-        }))
+    /// Export wiki as a single PDF file with cover page and table of contents
+    async fn export_pdf(&self, wiki: &WikiStructure, output_path: &Path) -> WikifyResult<()> {
+        info!("Exporting wiki as PDF to: {:?}", output_path);
+
+        // Typst resolves image paths relative to the source next to the PDF
+        let stem = output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "wiki".to_string());
+        let media_name = format!("{}-{}", stem, MEDIA_DIR);
+        let media_dir = output_path.with_file_name(&media_name);
+        let assets = copy_assets(wiki, &media_dir).await?;
+
+        let mut wiki = wiki.clone();
+        for page in &mut wiki.pages {
+            page.content = rewrite_asset_images(&page.content, &assets, |path| {
+                format!("{}/{}", media_name, path).replace(' ', "%20")
+            });
+        }
+        let source = crate::pdf::render_typst(&wiki);
+        crate::pdf::compile_pdf(&source, output_path).await?;
+        if !assets.is_empty() {
+            let _ = fs::remove_dir_all(&media_dir).await;
+        }
+
+        info!("Successfully exported {} pages as PDF", wiki.pages.len());
+        Ok(())
     }
 
     /// Generate Markdown index content
@@ -204,8 +294,22 @@ impl WikiExporter {
     }
 
     /// Generate Markdown content for a single page
-    fn generate_markdown_page(&self, page: &WikiPage, _wiki: &WikiStructure) -> String {
-        let mut content = page.content.clone();
+    ///
+    /// `assets` are the repository images copied to the media directory.
+    fn generate_markdown_page(
+        &self,
+        page: &WikiPage,
+        wiki: &WikiStructure,
+        assets: &BTreeSet<String>,
+    ) -> String {
+        // Page files are named after titles here, not IDs
+        let content = rewrite_page_links(wiki, &page.content, |target| {
+            self.sanitize_filename(&format!("{}.md", target.title))
+                .replace(' ', "%20")
+        });
+        let mut content = rewrite_asset_images(&content, assets, |path| {
+            format!("{}/{}", MEDIA_DIR, path).replace(' ', "%20")
+        });
 
         // Add navigation links at the top
         content = format!("[← Back to Index](README.md)\n\n{}", content);
@@ -213,285 +317,111 @@ impl WikiExporter {
         content
     }
 
-    /// Generate table of contents
+    /// Generate table of contents (sections, pages and page headings)
     fn generate_table_of_contents(&self, wiki: &WikiStructure) -> String {
         let mut content = format!("# Table of Contents - {}\n\n", wiki.title);
 
-        // Sort pages by importance and title
-        let mut sorted_pages = wiki.pages.clone();
-        sorted_pages.sort_by(|a, b| {
-            b.importance
-                .cmp(&a.importance)
-                .then_with(|| a.title.cmp(&b.title))
-        });
-
-        for page in &sorted_pages {
-            let filename = self.sanitize_filename(&format!("{}.md", page.title));
-            content.push_str(&format!(
-                "- [{}]({}) ({:?}) - {}\n",
-                page.title, filename, page.importance, page.description
-            ));
-        }
+        let navigation = crate::NavigationBuilder::new().build(wiki);
+        content.push_str(&crate::markdown_organizer::navigation_to_markdown(
+            &navigation,
+            |page_id| {
+                let title = wiki
+                    .pages
+                    .iter()
+                    .find(|p| p.id == page_id)
+                    .map_or(page_id, |p| p.title.as_str());
+                self.sanitize_filename(&format!("{}.md", title))
+            },
+        ));
 
         content
     }
 
-    /// Generate HTML index page
-    fn generate_html_index(&self, wiki: &WikiStructure) -> String {
-        format!(
-            r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>{}</title>
-    <link rel="stylesheet" href="style.css">
-</head>
-<body>
-    <div class="container">
-        <header>
-            <h1>{}</h1>
-            <p class="description">{}</p>
-        </header>
-        
-        <main>
-            <h2>Pages</h2>
-            <div class="page-grid">
-                {}
-            </div>
-        </main>
-        
-        <footer>
-            <p>Generated by Wikify on {}</p>
-        </footer>
-    </div>
-</body>
-</html>"#,
-            wiki.title,
-            wiki.title,
-            wiki.description,
-            wiki.pages
-                .iter()
-                .map(|page| {
-                    let filename = self.sanitize_filename(&format!("{}.html", page.title));
-                    format!(
-                        r#"<div class="page-card">
-                            <h3><a href="{}">{}</a></h3>
-                            <p>{}</p>
-                            <span class="importance {:?}">{:?}</span>
-                        </div>"#,
-                        filename, page.title, page.description, page.importance, page.importance
-                    )
-                })
-                .collect::<Vec<_>>()
-                .join("\n                "),
-            wiki.metadata.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
-        )
-    }
+    /// `book.toml` of an mdBook export
+    fn generate_book_toml(&self, wiki: &WikiStructure) -> WikifyResult<String> {
+        #[derive(serde::Serialize)]
+        struct Book<'a> {
+            title: &'a str,
+            description: &'a str,
+            src: &'a str,
+        }
+        #[derive(serde::Serialize)]
+        struct BookToml<'a> {
+            book: Book<'a>,
+        }
 
-    /// Generate HTML content for a single page
-    fn generate_html_page(&self, page: &WikiPage, wiki: &WikiStructure) -> String {
-        // Convert Markdown to HTML (simplified)
-        let html_content = self.markdown_to_html(&page.content);
-
-        format!(
-            r#"<!DOCTYPE html>
-<html lang="en">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>{} - {}</title>
-    <link rel="stylesheet" href="style.css">
-</head>
-<body>
-    <div class="container">
-        <nav>
-            <a href="index.html">← Back to Index</a>
-        </nav>
-        
-        <main>
-            {}
-        </main>
-        
-        <footer>
-            <p>Generated by Wikify on {}</p>
-        </footer>
-    </div>
-</body>
-</html>"#,
-            page.title,
-            wiki.title,
-            html_content,
-            wiki.metadata.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
-        )
+        let book = BookToml {
+            book: Book {
+                title: &wiki.title,
+                description: &wiki.description,
+                src: "src",
+            },
+        };
+        let content = toml::to_string(&book).map_err(|e| WikifyError::Config {
+            message: format!("Failed to serialize book.toml: {}", e),
+            source: Some(Box::new(e)),
+            context: ErrorContext::new("wiki_export"),
+        })?;
+        Ok(content)
     }
 
-    /// Generate CSS for HTML export
-    fn generate_css(&self) -> String {
-        r#"
-body {
-    font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
-    line-height: 1.6;
-    color: #333;
-    margin: 0;
-    padding: 0;
-    background-color: #f5f5f5;
-}
-
-.container {
-    max-width: 1200px;
-    margin: 0 auto;
-    padding: 20px;
-    background-color: white;
-    box-shadow: 0 0 10px rgba(0,0,0,0.1);
-    min-height: 100vh;
-}
-
-header {
-    text-align: center;
-    margin-bottom: 40px;
-    padding-bottom: 20px;
-    border-bottom: 2px solid #eee;
-}
-
-h1 {
-    color: #2c3e50;
-    margin-bottom: 10px;
-}
-
-.description {
-    font-size: 1.2em;
-    color: #666;
-    margin: 0;
-}
-
-.page-grid {
-    display: grid;
-    grid-template-columns: repeat(auto-fit, minmax(300px, 1fr));
-    gap: 20px;
-    margin-top: 20px;
-}
-
-.page-card {
-    border: 1px solid #ddd;
-    border-radius: 8px;
-    padding: 20px;
-    background-color: #fafafa;
-    transition: transform 0.2s;
-}
-
-.page-card:hover {
-    transform: translateY(-2px);
-    box-shadow: 0 4px 8px rgba(0,0,0,0.1);
-}
-
-.page-card h3 {
-    margin-top: 0;
-    margin-bottom: 10px;
-}
-
-.page-card a {
-    text-decoration: none;
-    color: #3498db;
-}
-
-.page-card a:hover {
-    text-decoration: underline;
-}
-
-.importance {
-    display: inline-block;
-    padding: 4px 8px;
-    border-radius: 4px;
-    font-size: 0.8em;
-    font-weight: bold;
-    text-transform: uppercase;
-}
-
-.importance.High {
-    background-color: #e74c3c;
-    color: white;
-}
-
-.importance.Medium {
-    background-color: #f39c12;
-    color: white;
-}
-
-.importance.Low {
-    background-color: #95a5a6;
-    color: white;
-}
-
-nav {
-    margin-bottom: 20px;
-}
-
-nav a {
-    text-decoration: none;
-    color: #3498db;
-    font-weight: bold;
-}
-
-nav a:hover {
-    text-decoration: underline;
-}
-
-footer {
-    margin-top: 40px;
-    padding-top: 20px;
-    border-top: 1px solid #eee;
-    text-align: center;
-    color: #666;
-    font-size: 0.9em;
-}
-
-pre {
-    background-color: #f8f8f8;
-    border: 1px solid #ddd;
-    border-radius: 4px;
-    padding: 15px;
-    overflow-x: auto;
-}
-
-code {
-    background-color: #f8f8f8;
-    padding: 2px 4px;
-    border-radius: 3px;
-    font-family: 'Monaco', 'Consolas', monospace;
-}
+    /// `SUMMARY.md` of an mdBook export
+    ///
+    /// Top-level sections become parts, nested sections draft chapters that
+    /// group their pages. Page headings are left to mdBook's own navigation.
+    fn generate_mdbook_summary(&self, wiki: &WikiStructure) -> String {
+        fn chapters(
+            exporter: &WikiExporter,
+            wiki: &WikiStructure,
+            out: &mut String,
+            items: &[NavigationItem],
+            depth: usize,
+        ) {
+            for item in items {
+                let indent = "  ".repeat(depth);
+                match item.kind {
+                    NavigationKind::Section => {
+                        out.push_str(&format!("{}- [{}]()\n", indent, item.title));
+                        chapters(exporter, wiki, out, &item.children, depth + 1);
+                    }
+                    NavigationKind::Page => {
+                        if let Some(page) = wiki.pages.iter().find(|p| p.id == item.id) {
+                            out.push_str(&format!(
+                                "{}- [{}]({})\n",
+                                indent,
+                                item.title,
+                                exporter.mdbook_filename(page)
+                            ));
+                        }
+                    }
+                    NavigationKind::Heading => {}
+                }
+            }
+        }
 
-blockquote {
-    border-left: 4px solid #3498db;
-    margin: 0;
-    padding-left: 20px;
-    color: #666;
-}
-"#
-        .to_string()
+        let mut summary = String::from("# Summary\n\n[Introduction](README.md)\n");
+        let mut in_part = false;
+        for item in crate::NavigationBuilder::new().build(wiki) {
+            if item.kind == NavigationKind::Section {
+                summary.push_str(&format!("\n# {}\n\n", item.title));
+                chapters(self, wiki, &mut summary, &item.children, 0);
+                in_part = true;
+            } else {
+                // Pages outside sections come last; keep them out of the last part
+                if in_part {
+                    summary.push_str("\n# Other Pages\n\n");
+                    in_part = false;
+                }
+                chapters(self, wiki, &mut summary, std::slice::from_ref(&item), 0);
+            }
+        }
+        summary
     }
 
-    /// Simple Markdown to HTML conversion (placeholder)
-    fn markdown_to_html(&self, markdown: &str) -> String {
-        // This is a very basic implementation
-        // In a real implementation, you'd use a proper Markdown parser like pulldown-cmark
-        markdown
-            .lines()
-            .map(|line| {
-                if let Some(stripped) = line.strip_prefix("# ") {
-                    format!("<h1>{}</h1>", stripped)
-                } else if let Some(stripped) = line.strip_prefix("## ") {
-                    format!("<h2>{}</h2>", stripped)
-                } else if let Some(stripped) = line.strip_prefix("### ") {
-                    format!("<h3>{}</h3>", stripped)
-                } else if line.trim().is_empty() {
-                    "<br>".to_string()
-                } else {
-                    format!("<p>{}</p>", line)
-                }
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
+    /// File name of a page in an mdBook export
+    fn mdbook_filename(&self, page: &WikiPage) -> String {
+        self.sanitize_filename(&format!("{}.md", page.id))
+            .replace(' ', "-")
     }
 
     /// Sanitize filename for filesystem compatibility
@@ -520,11 +450,7 @@ mod tests {
     use crate::types::{ImportanceLevel, WikiPage, WikiStructure};
     use tempfile::TempDir;
 
-    #[tokio::test]
-    async fn test_markdown_export() {
-        let exporter = WikiExporter::new().unwrap();
-        let temp_dir = TempDir::new().unwrap();
-
+    fn test_wiki() -> WikiStructure {
         let mut wiki = WikiStructure::new(
             "Test Wiki".to_string(),
             "A test wiki".to_string(),
@@ -544,9 +470,18 @@ mod tests {
             reading_time: 1,
             generated_at: chrono::Utc::now(),
             source_documents: vec![],
+            code_examples: vec![],
         };
 
         wiki.pages.push(page);
+        wiki
+    }
+
+    #[tokio::test]
+    async fn test_markdown_export() {
+        let exporter = WikiExporter::new().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let wiki = test_wiki();
 
         let result = exporter
             .export(
@@ -562,6 +497,29 @@ mod tests {
         assert!(temp_dir.path().join("Test Page.md").exists());
     }
 
+    #[tokio::test]
+    async fn test_mdbook_export() {
+        let exporter = WikiExporter::new().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let wiki = test_wiki();
+
+        exporter
+            .export(
+                &wiki,
+                ExportFormat::MdBook,
+                temp_dir.path().to_str().unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let book = std::fs::read_to_string(temp_dir.path().join("book.toml")).unwrap();
+        assert!(book.contains("title = \"Test Wiki\""));
+        let summary = std::fs::read_to_string(temp_dir.path().join("src/SUMMARY.md")).unwrap();
+        assert!(summary.contains("[Introduction](README.md)"));
+        assert!(summary.contains("- [Test Page](test-page.md)"));
+        assert!(temp_dir.path().join("src/test-page.md").exists());
+    }
+
     #[test]
     fn test_filename_sanitization() {
         let exporter = WikiExporter::new().unwrap();
@@ -2,14 +2,48 @@
 //!
 //! This module contains the core logic for generating wiki structures and content.
 
+use crate::assets::{asset_instructions, discover_assets, resolve_page_images, WikiAsset};
+use crate::changelog::{Changelog, CHANGELOG_PAGE_ID, DEFAULT_MAX_COMMITS};
+use crate::dependency_graph::{DependencyGraph, DEPENDENCY_GRAPH_PAGE_ID};
+use crate::links::LinkResolver;
+use crate::quality::QualityReport;
+use crate::snippets::{SnippetExtractor, SNIPPET_INSTRUCTIONS};
 use crate::types::*;
 use serde_json::Value;
 use std::collections::HashMap;
+use tokio::process::Command;
 use wikify_core::{DocumentInfo, ErrorContext, WikifyError, WikifyResult};
 use wikify_rag::{RagConfig, RagError, RagPipeline, RagQuery};
 
 use chrono::Utc;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+/// Commit SHA a repository is currently at
+///
+/// Local checkouts report their `HEAD`; for remote URLs the remote `HEAD` is
+/// looked up, which is what a fresh clone indexes. Returns `None` if the
+/// repository is not a git repository or cannot be reached.
+pub async fn resolve_commit_sha(repo_path: &str) -> Option<String> {
+    let mut cmd = Command::new("git");
+    if std::path::Path::new(repo_path).is_dir() {
+        cmd.arg("-C").arg(repo_path).args(["rev-parse", "HEAD"]);
+    } else {
+        cmd.args(["ls-remote", repo_path, "HEAD"])
+            .env("GIT_TERMINAL_PROMPT", "0");
+    }
+
+    let output = cmd.output().await.ok()?;
+    if !output.status.success() {
+        debug!("Could not resolve commit of repository: {}", repo_path);
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let sha = stdout.split_whitespace().next()?;
+    // SHA-1 or SHA-256 object names
+    (matches!(sha.len(), 40 | 64) && sha.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| sha.to_string())
+}
 
 /// Main wiki generator that orchestrates the wiki creation process
 pub struct WikiGenerator {
@@ -34,11 +68,11 @@ impl WikiGenerator {
         rag_config.retrieval.max_context_length = 20000; // Larger context for detailed pages
 
         // Auto-detect LLM provider
-        if std::env::var("OPENAI_API_KEY").is_ok() {
+        if wikify_core::secrets::secret("OPENAI_API_KEY").is_some() {
             rag_config.llm = wikify_rag::llm_client::configs::openai_gpt4o_mini();
             rag_config.embeddings.provider = "openai".to_string();
             rag_config.embeddings.model = "text-embedding-3-small".to_string();
-        } else if std::env::var("ANTHROPIC_API_KEY").is_ok() {
+        } else if wikify_core::secrets::secret("ANTHROPIC_API_KEY").is_some() {
             rag_config.llm = wikify_rag::llm_client::configs::anthropic_claude_haiku();
         } else {
             rag_config.llm = wikify_rag::llm_client::configs::ollama_llama3(None);
@@ -74,6 +108,20 @@ impl WikiGenerator {
             wiki_structure.pages.len()
         );
 
+        // Images the READMEs embed, which pages may embed too
+        let files = if std::path::Path::new(repo_path).is_dir() {
+            self.get_directory_structure(repo_path, config).await?
+        } else {
+            Vec::new()
+        };
+        wiki_structure.assets = discover_assets(std::path::Path::new(repo_path), &files).await;
+        if !wiki_structure.assets.is_empty() {
+            info!(
+                "Found {} images referenced by READMEs",
+                wiki_structure.assets.len()
+            );
+        }
+
         // Step 2: Generate content for each page
         info!(
             "Generating content for {} pages...",
@@ -86,7 +134,13 @@ impl WikiGenerator {
 
             // Generate content for this page
             let generated_page = self
-                .generate_page_content(&wiki_structure.pages[i], repo_path, config)
+                .generate_page(
+                    &wiki_structure.pages[i],
+                    repo_path,
+                    config,
+                    None,
+                    &wiki_structure.assets,
+                )
                 .await?;
 
             // Update the page in the structure
@@ -99,6 +153,35 @@ impl WikiGenerator {
             );
         }
 
+        // Pin the wiki to the commit it documents
+        wiki_structure.metadata.commit_sha = resolve_commit_sha(repo_path).await;
+
+        // Step 3: Add the dependency graph, derived from manifests rather than the LLM
+        if config.include_diagrams && std::path::Path::new(repo_path).is_dir() {
+            self.add_dependency_graph_page(&mut wiki_structure, repo_path, config);
+        }
+
+        // Step 4: Resolve links between pages and to repository files
+        let validation = LinkResolver::new(&wiki_structure)
+            .with_files(&files)
+            .resolve(&mut wiki_structure);
+        if !validation.is_clean() {
+            warn!(
+                "{} of {} wiki links could not be resolved",
+                validation.dangling.len(),
+                validation.checked_links
+            );
+        }
+
+        // Step 5: Score the pages and report where the documentation is weak
+        let quality = QualityReport::evaluate(&wiki_structure, &files);
+        info!(
+            "Wiki quality score: {}/100 ({} pages below 60)",
+            quality.score,
+            quality.weak_pages(60).count()
+        );
+        wiki_structure.quality = Some(quality);
+
         info!(
             "Wiki generation completed successfully - {} pages with content",
             wiki_structure.pages.len()
@@ -119,7 +202,27 @@ impl WikiGenerator {
             self.initialize_rag(config).await?;
         }
 
-        // Index the repository using RAG pipeline
+        self.index_repository(repo_path).await?;
+
+        // Get repository information
+        let repo_info = self.analyze_repository_structure(repo_path, config).await?;
+
+        // Generate wiki structure using LLM
+        let wiki_structure = self
+            .generate_wiki_structure_with_llm(&repo_info, config)
+            .await?;
+
+        info!(
+            "Generated wiki structure with {} pages and {} sections",
+            wiki_structure.pages.len(),
+            wiki_structure.sections.len()
+        );
+
+        Ok(wiki_structure)
+    }
+
+    /// Index the repository in the RAG pipeline
+    async fn index_repository(&mut self, repo_path: &str) -> WikifyResult<()> {
         info!("Indexing repository for wiki generation");
         if let Some(ref mut rag_pipeline) = self.rag_pipeline {
             rag_pipeline
@@ -141,34 +244,70 @@ impl WikiGenerator {
             }));
         }
 
-        // Get repository information
-        let repo_info = self.analyze_repository_structure(repo_path, config).await?;
+        Ok(())
+    }
 
-        // Generate wiki structure using LLM
-        let wiki_structure = self
-            .generate_wiki_structure_with_llm(&repo_info, config)
-            .await?;
+    /// Generate content for a specific wiki page
+    pub async fn generate_page_content(
+        &mut self,
+        page: &WikiPage,
+        repo_path: &str,
+        config: &WikiConfig,
+    ) -> WikifyResult<WikiPage> {
+        self.generate_page(page, repo_path, config, None, &[]).await
+    }
 
-        info!(
-            "Generated wiki structure with {} pages and {} sections",
-            wiki_structure.pages.len(),
-            wiki_structure.sections.len()
-        );
+    /// Regenerate a single page of an existing wiki
+    ///
+    /// Indexes the repository first if the RAG pipeline has not been set up yet.
+    /// `instructions` are appended to the generation prompt, e.g. to ask for
+    /// more examples or a different focus. `assets` are the wiki's images the
+    /// page may embed.
+    pub async fn regenerate_page(
+        &mut self,
+        page: &WikiPage,
+        repo_path: &str,
+        config: &WikiConfig,
+        instructions: Option<&str>,
+        assets: &[WikiAsset],
+    ) -> WikifyResult<WikiPage> {
+        if self.rag_pipeline.is_none() {
+            self.initialize_rag(config).await?;
+            self.index_repository(repo_path).await?;
+        }
 
-        Ok(wiki_structure)
+        self.generate_page(page, repo_path, config, instructions, assets)
+            .await
     }
 
-    /// Generate content for a specific wiki page
-    pub async fn generate_page_content(
+    /// Generate page content with optional extra prompt instructions and
+    /// repository images to embed
+    async fn generate_page(
         &mut self,
         page: &WikiPage,
-        _repo_path: &str,
+        repo_path: &str,
         config: &WikiConfig,
+        instructions: Option<&str>,
+        assets: &[WikiAsset],
     ) -> WikifyResult<WikiPage> {
         info!("Generating content for page: {}", page.title);
 
+        // Code examples can only be copied from a local checkout
+        let local_repo = std::path::Path::new(repo_path).is_dir();
+
         // Create a comprehensive prompt for this page
-        let prompt = self.create_page_generation_prompt(page, config);
+        let mut prompt = self.create_page_generation_prompt(page, config);
+        if local_repo {
+            prompt.push_str("\n\n");
+            prompt.push_str(SNIPPET_INSTRUCTIONS);
+        }
+        if let Some(asset_instructions) = asset_instructions(assets) {
+            prompt.push_str("\n\n");
+            prompt.push_str(&asset_instructions);
+        }
+        if let Some(instructions) = instructions.map(str::trim).filter(|i| !i.is_empty()) {
+            prompt.push_str(&format!("\n\nAdditional instructions:\n{}", instructions));
+        }
 
         // Query RAG system for relevant information
         let query = RagQuery {
@@ -176,6 +315,7 @@ impl WikiGenerator {
             context: None,
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         let rag_response = {
@@ -232,6 +372,24 @@ impl WikiGenerator {
                 }
             })
             .collect();
+
+        // Replace code references with the verbatim lines from the repository
+        if local_repo {
+            let files: Vec<String> = generated_page
+                .file_paths
+                .iter()
+                .chain(generated_page.source_documents.iter().map(|d| &d.file_path))
+                .map(|path| {
+                    let path = path.strip_prefix(repo_path).unwrap_or(path);
+                    path.trim_start_matches('/').to_string()
+                })
+                .collect();
+            SnippetExtractor::new(repo_path)
+                .with_files(files)
+                .extract(&mut generated_page)
+                .await;
+        }
+        resolve_page_images(&mut generated_page, assets);
         generated_page.estimate_reading_time();
 
         info!(
@@ -244,6 +402,68 @@ impl WikiGenerator {
         Ok(generated_page)
     }
 
+    /// Append a dependency graph page built from the repository's manifests and imports
+    fn add_dependency_graph_page(
+        &self,
+        wiki_structure: &mut WikiStructure,
+        repo_path: &str,
+        config: &WikiConfig,
+    ) {
+        if wiki_structure
+            .pages
+            .iter()
+            .any(|p| p.id == DEPENDENCY_GRAPH_PAGE_ID)
+        {
+            return;
+        }
+
+        match DependencyGraph::analyze(std::path::Path::new(repo_path), &config.excluded_dirs) {
+            Ok(graph) if !graph.is_empty() => {
+                info!(
+                    "Adding dependency graph page ({} packages)",
+                    graph.packages.len()
+                );
+                wiki_structure.pages.push(graph.to_page());
+                wiki_structure.metadata.stats.total_pages = wiki_structure.pages.len();
+            }
+            Ok(_) => debug!("No package manifests found, skipping dependency graph"),
+            Err(e) => warn!("Failed to analyze dependency graph: {}", e),
+        }
+    }
+
+    /// Append a "Recent Changes" page summarizing the commits since `since`
+    ///
+    /// Without a previous commit the latest commits are summarized. Nothing
+    /// is added for repositories that are not local git checkouts.
+    pub async fn add_changelog_page(
+        &self,
+        wiki_structure: &mut WikiStructure,
+        repo_path: &str,
+        since: Option<&str>,
+    ) {
+        if wiki_structure
+            .pages
+            .iter()
+            .any(|p| p.id == CHANGELOG_PAGE_ID)
+        {
+            return;
+        }
+
+        match Changelog::from_git(std::path::Path::new(repo_path), since, DEFAULT_MAX_COMMITS).await
+        {
+            Ok(changelog) if !changelog.is_empty() => {
+                info!(
+                    "Adding recent changes page ({} commits)",
+                    changelog.entries.len()
+                );
+                wiki_structure.pages.push(changelog.to_page());
+                wiki_structure.metadata.stats.total_pages = wiki_structure.pages.len();
+            }
+            Ok(_) => debug!("No new commits, skipping recent changes page"),
+            Err(e) => warn!("Failed to read git history: {}", e),
+        }
+    }
+
     /// Analyze repository structure to understand the codebase
     async fn analyze_repository_structure(
         &self,
@@ -304,6 +524,7 @@ impl WikiGenerator {
             context: None,
             filters: None,
             retrieval_config: None,
+            attachment: None,
         };
 
         let response = {
@@ -344,7 +565,7 @@ impl WikiGenerator {
     fn create_structure_analysis_prompt(
         &self,
         repo_info: &RepositoryInfo,
-        _config: &WikiConfig,
+        config: &WikiConfig,
     ) -> String {
         let readme_section = if let Some(readme) = &repo_info.readme_content {
             format!("README Content:\n{}\n\n", readme)
@@ -367,11 +588,16 @@ impl WikiGenerator {
             String::new()
         };
 
+        let template_section = match &config.template {
+            Some(template) => format!("{}\n", template.structure_instructions()),
+            None => String::new(),
+        };
+
         format!(
             r#"You are an expert technical writer and software architect. Analyze this repository and create a comprehensive wiki structure.
 
 Repository: {}
-{}{}{}
+{}{}{}{}
 
 Please analyze this codebase and create a wiki structure with the following requirements:
 
@@ -408,7 +634,7 @@ Return your analysis in the following JSON format:
 }}
 
 Focus on creating practical, useful documentation that would help developers understand and work with this codebase."#,
-            repo_info.name, readme_section, languages_section, files_section
+            repo_info.name, readme_section, languages_section, files_section, template_section
         )
     }
 
@@ -419,6 +645,10 @@ Focus on creating practical, useful documentation that would help developers und
         } else {
             String::new()
         };
+        let tone = match config.template.as_ref().and_then(|t| t.tone_instructions()) {
+            Some(tone) => format!("\n{}\n", tone),
+            None => String::new(),
+        };
 
         format!(
             r#"Generate comprehensive technical documentation for: {}
@@ -441,8 +671,8 @@ Structure the content with:
 - Best Practices
 - Related Information
 
-Generate detailed, accurate documentation that would be valuable for developers working with this codebase."#,
-            page.title, page.description, files_context, config.language
+Generate detailed, accurate documentation that would be valuable for developers working with this codebase.{}"#,
+            page.title, page.description, files_context, config.language, tone
         )
     }
 
@@ -595,6 +825,11 @@ Generate detailed, accurate documentation that would be valuable for developers
             }
         }
 
+        // Enforce the repository's structure template, if any
+        if let Some(template) = &config.template {
+            template.apply(&mut wiki_structure);
+        }
+
         // If no pages were generated, return an error instead of creating fallback content
         if wiki_structure.pages.is_empty() {
             debug!("No pages found in LLM response, wiki generation failed");
@@ -3,27 +3,53 @@
 //! This module provides functionality to generate comprehensive wiki documentation
 //! from code repositories using RAG (Retrieval-Augmented Generation).
 
+pub mod assets;
 pub mod cache;
+pub mod changelog;
 pub mod content_strategy;
+pub mod dependency_graph;
+pub mod diff;
+pub mod edits;
 pub mod enhanced_prompts;
 pub mod export;
 pub mod generator;
+pub mod links;
 pub mod markdown_organizer;
+pub mod pdf;
 pub mod priority_system;
+pub mod publish;
+pub mod quality;
+pub mod site;
+pub mod snippets;
 pub mod structured_generator;
+pub mod template;
 pub mod types;
 
 // Re-export main types and functions
-pub use cache::WikiCache;
+pub use assets::WikiAsset;
+pub use cache::{config_hash, WikiCache, WikiVersion};
+pub use changelog::{ChangeEntry, Changelog};
 pub use content_strategy::ContentGenerationStrategy;
+pub use dependency_graph::DependencyGraph;
+pub use diff::WikiDiff;
+pub use edits::{PageEdit, WikiEdits};
 pub use enhanced_prompts::{MarkdownPrompts, ResearchPrompts};
 pub use export::{ExportFormat, WikiExporter};
 pub use generator::WikiGenerator;
-pub use markdown_organizer::{MarkdownOptions, MarkdownOrganizer};
+pub use links::{DanglingLink, DanglingReason, LinkResolver, LinkValidation};
+pub use markdown_organizer::{
+    MarkdownOptions, MarkdownOrganizer, NavigationBuilder, NavigationItem, NavigationKind,
+};
 pub use priority_system::{ContentPriorityAnalyzer, ContentPriorityResult};
+pub use publish::{GitHubWikiPublisher, PublishReport};
+pub use quality::{PageQuality, QualityReport};
+pub use snippets::{CodeExample, SnippetExtractor};
 pub use structured_generator::StructuredWikiGenerator;
+pub use template::{TemplatePage, TemplateSection, WikiTemplate};
 pub use types::*;
 
+use wikify_core::{ProgressReporter, ProgressStage, ProgressUpdate};
+
 /// Enhanced Wiki service with intelligent content generation
 ///
 /// This service orchestrates wiki generation using advanced strategies inspired by DeepWiki,
@@ -55,12 +81,18 @@ impl WikiService {
         })
     }
 
-    /// Get cached wiki if available
+    /// Get the cached wiki for the repository's current commit and `config`, if available
     pub async fn get_cached_wiki(
         &self,
         repo_path: &str,
+        config: &WikiConfig,
     ) -> Result<Option<WikiStructure>, Box<dyn std::error::Error + Send + Sync>> {
-        match self.cache.get_wiki(repo_path).await {
+        let commit_sha = generator::resolve_commit_sha(repo_path).await;
+        match self
+            .cache
+            .get_wiki(repo_path, commit_sha.as_deref(), config)
+            .await
+        {
             Ok(Some(wiki)) => Ok(Some(wiki)),
             Ok(None) => Ok(None),
             Err(e) => {
@@ -72,12 +104,64 @@ impl WikiService {
     }
 
     /// Generate a complete wiki for a repository using the original generator
+    ///
+    /// A wiki cached for the same commit and config is returned as is unless
+    /// `config.force_regenerate` is set.
     pub async fn generate_wiki(
         &mut self,
         repo_path: &str,
         config: &WikiConfig,
     ) -> Result<WikiStructure, Box<dyn std::error::Error + Send + Sync>> {
-        println!("🔍 Initializing wiki generator...");
+        self.generate_wiki_cancellable(
+            repo_path,
+            config,
+            &wikify_core::NoProgress,
+            &wikify_core::CancellationToken::new(),
+        )
+        .await
+    }
+
+    /// Generate a complete wiki, reporting progress, until `cancel` is triggered
+    ///
+    /// Progress stops at 90% so callers can report their own finishing steps.
+    /// Cancelling drops the LLM call in flight and returns
+    /// [`wikify_core::WikifyError::Cancelled`]; nothing is cached.
+    pub async fn generate_wiki_cancellable(
+        &mut self,
+        repo_path: &str,
+        config: &WikiConfig,
+        progress: &dyn ProgressReporter,
+        cancel: &wikify_core::CancellationToken,
+    ) -> Result<WikiStructure, Box<dyn std::error::Error + Send + Sync>> {
+        tokio::select! {
+            result = self.generate_wiki_internal(repo_path, config, progress) => result,
+            _ = cancel.cancelled() => {
+                tracing::info!(repository = repo_path, "Wiki generation cancelled");
+                Err(Box::new(wikify_core::WikifyError::cancelled("generate_wiki")))
+            }
+        }
+    }
+
+    async fn generate_wiki_internal(
+        &mut self,
+        repo_path: &str,
+        config: &WikiConfig,
+        progress: &dyn ProgressReporter,
+    ) -> Result<WikiStructure, Box<dyn std::error::Error + Send + Sync>> {
+        if !config.force_regenerate {
+            if let Some(wiki) = self.get_cached_wiki(repo_path, config).await? {
+                progress.report(
+                    &ProgressUpdate::new(ProgressStage::Finalizing, 0.9)
+                        .with_message("Using cached wiki for the current commit"),
+                );
+                return Ok(wiki);
+            }
+        }
+
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Starting, 0.1)
+                .with_message("Initializing wiki generator"),
+        );
 
         // Initialize RAG pipeline in generator
         self.generator
@@ -85,28 +169,62 @@ impl WikiService {
             .await
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
-        println!("📝 Generating wiki structure...");
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Generating, 0.3)
+                .with_message("Generating wiki structure"),
+        );
 
         // Use the professional generator
-        let wiki = self
+        let mut wiki = self
             .generator
             .generate_wiki(repo_path, config)
             .await
             .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
 
+        // Summarize what changed since the previously generated version
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Finalizing, 0.8)
+                .with_message("Writing changelog and caching the wiki"),
+        );
+        if std::path::Path::new(repo_path).is_dir() {
+            let since = self
+                .previous_commit(repo_path, wiki.metadata.commit_sha.as_deref())
+                .await;
+            self.generator
+                .add_changelog_page(&mut wiki, repo_path, since.as_deref())
+                .await;
+        }
+
         // Cache the generated wiki
         if let Err(e) = self.cache.store_wiki(repo_path, &wiki).await {
             tracing::warn!("Failed to cache wiki: {}", e);
         }
 
-        println!(
-            "✅ Generated {} pages and {} sections",
-            wiki.pages.len(),
-            wiki.sections.len()
+        progress.report(
+            &ProgressUpdate::new(ProgressStage::Finalizing, 0.9).with_message(format!(
+                "Generated {} pages and {} sections",
+                wiki.pages.len(),
+                wiki.sections.len()
+            )),
         );
         Ok(wiki)
     }
 
+    /// Commit of the newest archived wiki version generated from a commit other than `current`
+    async fn previous_commit(&self, repo_path: &str, current: Option<&str>) -> Option<String> {
+        let versions = match self.cache.list_versions(repo_path).await {
+            Ok(versions) => versions,
+            Err(e) => {
+                tracing::warn!("Failed to list wiki versions: {}", e);
+                return None;
+            }
+        };
+        versions
+            .into_iter()
+            .filter_map(|v| v.commit_sha)
+            .find(|sha| Some(sha.as_str()) != current)
+    }
+
     /// Generate wiki using intelligent strategy (DeepWiki-inspired approach)
     pub async fn generate_intelligent_wiki(
         &mut self,
@@ -177,6 +295,59 @@ impl WikiService {
         Ok(wiki)
     }
 
+    /// Regenerate a single page of an existing wiki
+    ///
+    /// The page is replaced in `wiki` and the updated wiki is cached. Optional
+    /// `instructions` are passed to the LLM alongside the usual page prompt.
+    pub async fn regenerate_page(
+        &mut self,
+        wiki: &mut WikiStructure,
+        page_id: &str,
+        repo_path: &str,
+        instructions: Option<&str>,
+    ) -> Result<WikiPage, Box<dyn std::error::Error + Send + Sync>> {
+        let index = wiki
+            .pages
+            .iter()
+            .position(|p| p.id == page_id)
+            .ok_or_else(|| format!("Page not found: {}", page_id))?;
+
+        let page = self
+            .generator
+            .regenerate_page(
+                &wiki.pages[index],
+                repo_path,
+                &wiki.metadata.config,
+                instructions,
+                &wiki.assets,
+            )
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
+        wiki.pages[index] = page;
+
+        // The new content may link to other pages and files differently
+        LinkResolver::new(wiki).resolve(wiki);
+        let page = wiki.pages[index].clone();
+
+        // Cache the updated wiki
+        if let Err(e) = self.cache.store_wiki(repo_path, wiki).await {
+            tracing::warn!("Failed to cache wiki: {}", e);
+        }
+
+        Ok(page)
+    }
+
+    /// Drop the cached wikis of a repository so the next generation starts fresh
+    pub async fn invalidate_cache(
+        &self,
+        repo_path: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.cache
+            .invalidate(repo_path)
+            .await
+            .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+    }
+
     /// Export wiki to various formats
     pub async fn export_wiki(
         &self,
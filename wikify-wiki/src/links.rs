@@ -0,0 +1,588 @@
+//! Cross-page link resolution and validation
+//!
+//! Generated pages link to other pages and to repository files in many
+//! shapes: `./page-id.md`, a bare page title, `#heading` anchors, or source
+//! citations such as `[main.rs:45-67]()`. The resolver matches them against
+//! the wiki's pages, the headings of those pages and the files of the
+//! repository, rewrites them to canonical targets and records the links it
+//! could not resolve in a [`LinkValidation`] summary on the wiki.
+//!
+//! Canonical targets are `./{page file}.md#anchor` for pages (see
+//! [`page_filename`]) and `path/to/file.rs#L45-L67` for repository files.
+//! Exporters that use other file names map page targets with
+//! [`rewrite_page_links`].
+
+use crate::markdown_organizer::{heading_anchor, page_filename, page_headings};
+use crate::types::{WikiPage, WikiStructure};
+use pulldown_cmark::{Event, LinkType, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+
+/// Summary of the internal links of a wiki
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LinkValidation {
+    /// Links to pages, headings and repository files found in page content
+    pub checked_links: usize,
+    /// Links rewritten to their canonical target
+    pub rewritten_links: usize,
+    /// Links that could not be resolved; they are left as written
+    pub dangling: Vec<DanglingLink>,
+    /// Repository files the wiki links to
+    pub linked_files: BTreeSet<String>,
+}
+
+/// Link that could not be resolved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DanglingLink {
+    /// Page containing the link
+    pub page_id: String,
+    /// Link text
+    pub text: String,
+    /// Link target as written
+    pub target: String,
+    /// Why the link could not be resolved
+    pub reason: DanglingReason,
+}
+
+/// Reason a link could not be resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DanglingReason {
+    /// No page matches the target
+    UnknownPage,
+    /// The page exists but has no such heading
+    UnknownAnchor,
+    /// No repository file matches the target
+    UnknownFile,
+}
+
+impl LinkValidation {
+    /// Whether every link was resolved
+    pub fn is_clean(&self) -> bool {
+        self.dangling.is_empty()
+    }
+}
+
+/// Outcome of resolving a single link target
+enum Resolution {
+    /// External link, left alone and not counted
+    External,
+    /// Canonical target, with the repository file it points to if any
+    Resolved {
+        target: String,
+        file: Option<String>,
+    },
+    Dangling(DanglingReason),
+}
+
+/// Resolves and validates the links of a wiki
+pub struct LinkResolver {
+    /// Page ID by normalized page ID and title
+    pages: HashMap<String, String>,
+    /// Heading anchors by page ID
+    anchors: HashMap<String, HashSet<String>>,
+    /// Known repository files, sorted
+    files: Vec<String>,
+}
+
+impl LinkResolver {
+    /// Create a resolver for the pages of a wiki
+    ///
+    /// Files referenced by page metadata (including code examples) and
+    /// files the wiki already links to are known; add the full file list of the repository with
+    /// [`LinkResolver::with_files`].
+    pub fn new(wiki: &WikiStructure) -> Self {
+        let mut pages = HashMap::new();
+        for page in &wiki.pages {
+            pages.insert(page_key(&page.id), page.id.clone());
+        }
+        for page in &wiki.pages {
+            pages
+                .entry(page_key(&page.title))
+                .or_insert_with(|| page.id.clone());
+        }
+
+        let anchors = wiki
+            .pages
+            .iter()
+            .map(|page| {
+                let anchors = page_headings(&page.content)
+                    .into_iter()
+                    .map(|h| h.anchor)
+                    .collect();
+                (page.id.clone(), anchors)
+            })
+            .collect();
+
+        let files = wiki
+            .pages
+            .iter()
+            .flat_map(|page| {
+                page.file_paths
+                    .iter()
+                    .chain(page.source_documents.iter().map(|doc| &doc.file_path))
+                    .chain(page.code_examples.iter().map(|example| &example.path))
+            })
+            .chain(wiki.link_validation.linked_files.iter())
+            .cloned();
+
+        Self {
+            pages,
+            anchors,
+            files: Vec::new(),
+        }
+        .with_files(files)
+    }
+
+    /// Add repository files links may point to
+    pub fn with_files<I, S>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut known: HashSet<String> = self.files.drain(..).collect();
+        for file in files {
+            let file = normalize_path(file.as_ref());
+            if !file.is_empty() && file != "unknown" {
+                known.insert(file.to_string());
+            }
+        }
+        self.files = known.into_iter().collect();
+        self.files.sort();
+        self
+    }
+
+    /// Resolve the links of every page, rewriting them in place
+    ///
+    /// The summary replaces the wiki's previous link validation.
+    pub fn resolve<'w>(&self, wiki: &'w mut WikiStructure) -> &'w LinkValidation {
+        let mut validation = LinkValidation::default();
+        for page in &mut wiki.pages {
+            let page_id = page.id.clone();
+            page.content = rewrite_links(&page.content, |text, target| {
+                match self.resolve_target(&page_id, text, target) {
+                    Resolution::External => None,
+                    Resolution::Resolved {
+                        target: resolved,
+                        file,
+                    } => {
+                        validation.checked_links += 1;
+                        validation.linked_files.extend(file);
+                        if resolved == target {
+                            return None;
+                        }
+                        validation.rewritten_links += 1;
+                        Some(resolved)
+                    }
+                    Resolution::Dangling(reason) => {
+                        validation.checked_links += 1;
+                        validation.dangling.push(DanglingLink {
+                            page_id: page_id.clone(),
+                            text: text.to_string(),
+                            target: target.to_string(),
+                            reason,
+                        });
+                        None
+                    }
+                }
+            });
+        }
+
+        wiki.link_validation = validation;
+        &wiki.link_validation
+    }
+
+    fn resolve_target(&self, page_id: &str, text: &str, target: &str) -> Resolution {
+        let target = target.trim();
+        if is_external(target) {
+            return Resolution::External;
+        }
+
+        let (path, anchor) = match target.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (target, None),
+        };
+        let path = normalize_path(path);
+
+        if path.is_empty() {
+            return match anchor {
+                Some(anchor) => self.page_target(page_id, Some(anchor), true),
+                // Empty targets are source citations: `[main.rs:45-67]()`
+                None => self.resolve_citation(text),
+            };
+        }
+
+        if let Some(stem) = path.strip_suffix(".md") {
+            if let Some(id) = self.find_page(stem) {
+                return self.page_target(id, anchor, id == page_id && anchor.is_some());
+            }
+        }
+        if let Some(resolution) = self.file_target(path, anchor) {
+            return resolution;
+        }
+        if let Some(id) = self.find_page(path) {
+            return self.page_target(id, anchor, false);
+        }
+
+        Resolution::Dangling(if path.ends_with(".md") || !looks_like_file(path) {
+            DanglingReason::UnknownPage
+        } else {
+            DanglingReason::UnknownFile
+        })
+    }
+
+    fn resolve_citation(&self, text: &str) -> Resolution {
+        let text = text.trim().trim_matches('`');
+        if let Some(resolution) = self.file_target(text, None) {
+            return resolution;
+        }
+        if let Some(id) = self.find_page(text) {
+            return self.page_target(id, None, false);
+        }
+
+        Resolution::Dangling(if looks_like_file(split_line_range(text).0) {
+            DanglingReason::UnknownFile
+        } else {
+            DanglingReason::UnknownPage
+        })
+    }
+
+    /// Link to a page (or, for `same_page`, to an anchor of the current page)
+    fn page_target(&self, page_id: &str, anchor: Option<&str>, same_page: bool) -> Resolution {
+        let base = if same_page {
+            String::new()
+        } else {
+            format!("./{}", page_filename(page_id))
+        };
+
+        let anchor = match anchor.filter(|a| !a.is_empty()) {
+            Some(anchor) => anchor,
+            None => {
+                return Resolution::Resolved {
+                    target: base,
+                    file: None,
+                }
+            }
+        };
+        match self.find_anchor(page_id, anchor) {
+            Some(anchor) => Resolution::Resolved {
+                target: format!("{}#{}", base, anchor),
+                file: None,
+            },
+            None => Resolution::Dangling(DanglingReason::UnknownAnchor),
+        }
+    }
+
+    /// Link to a repository file, keeping a line range if there is one
+    fn file_target(&self, path: &str, anchor: Option<&str>) -> Option<Resolution> {
+        let (path, lines) = split_line_range(path);
+        let file = self.find_file(path)?;
+
+        let lines = lines
+            .map(|lines| match lines.split_once('-') {
+                Some((start, end)) => format!("L{}-L{}", start, end),
+                None => format!("L{}", lines),
+            })
+            .or_else(|| anchor.filter(|a| is_line_anchor(a)).map(str::to_string));
+        let target = match lines {
+            Some(lines) => format!("{}#{}", file, lines),
+            None => file.to_string(),
+        };
+        Some(Resolution::Resolved {
+            target,
+            file: Some(file.to_string()),
+        })
+    }
+
+    fn find_page(&self, name: &str) -> Option<&str> {
+        self.pages.get(&page_key(name)).map(String::as_str)
+    }
+
+    fn find_anchor(&self, page_id: &str, anchor: &str) -> Option<String> {
+        let anchors = self.anchors.get(page_id)?;
+        if anchors.contains(anchor) {
+            return Some(anchor.to_string());
+        }
+        let anchor = heading_anchor(&anchor.replace("%20", " "));
+        anchors.contains(&anchor).then_some(anchor)
+    }
+
+    /// Exact path, or the shortest known path ending with it
+    fn find_file(&self, path: &str) -> Option<&str> {
+        let path = normalize_path(path);
+        if path.is_empty() {
+            return None;
+        }
+        if let Ok(index) = self.files.binary_search_by(|f| f.as_str().cmp(path)) {
+            return Some(&self.files[index]);
+        }
+
+        let suffix = format!("/{}", path);
+        self.files
+            .iter()
+            .filter(|f| f.ends_with(&suffix))
+            .min_by_key(|f| f.len())
+            .map(String::as_str)
+    }
+}
+
+/// Rewrite canonical page links (`./{page file}.md#anchor`) for an export
+///
+/// `page_link` maps a page to its link target in the export; anchors are
+/// kept. Other links are left alone.
+pub fn rewrite_page_links<F>(wiki: &WikiStructure, markdown: &str, page_link: F) -> String
+where
+    F: Fn(&WikiPage) -> String,
+{
+    let pages: HashMap<String, &WikiPage> = wiki
+        .pages
+        .iter()
+        .map(|page| (page_filename(&page.id), page))
+        .collect();
+
+    rewrite_links(markdown, |_, target| {
+        let (path, anchor) = match target.split_once('#') {
+            Some((path, anchor)) => (path, Some(anchor)),
+            None => (target, None),
+        };
+        let page = pages.get(path.strip_prefix("./")?)?;
+        Some(match anchor {
+            Some(anchor) => format!("{}#{}", page_link(page), anchor),
+            None => page_link(page),
+        })
+    })
+}
+
+/// Replace the targets of inline links
+///
+/// `rewrite` gets the link text and target and returns the new target, or
+/// `None` to keep the link as written.
+fn rewrite_links<F>(markdown: &str, rewrite: F) -> String
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    rewrite_destinations(markdown, false, rewrite)
+}
+
+/// Replace the sources of inline images
+///
+/// Like [`rewrite_links`], with the alt text in place of the link text.
+pub(crate) fn rewrite_images<F>(markdown: &str, rewrite: F) -> String
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    rewrite_destinations(markdown, true, rewrite)
+}
+
+/// Replace the destinations of inline links, or of inline images if `images`
+fn rewrite_destinations<F>(markdown: &str, images: bool, mut rewrite: F) -> String
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    let mut replacements: Vec<(Range<usize>, String)> = Vec::new();
+    let mut current: Option<(String, Range<usize>, String)> = None;
+
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link {
+                link_type: LinkType::Inline,
+                dest_url,
+                ..
+            }) if !images => current = Some((dest_url.to_string(), range, String::new())),
+            Event::Start(Tag::Image {
+                link_type: LinkType::Inline,
+                dest_url,
+                ..
+            }) if images => current = Some((dest_url.to_string(), range, String::new())),
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, link_text)) = current.as_mut() {
+                    link_text.push_str(&text);
+                }
+            }
+            Event::End(end @ (TagEnd::Link | TagEnd::Image))
+                if images == (end == TagEnd::Image) =>
+            {
+                let Some((target, range, text)) = current.take() else {
+                    continue;
+                };
+                let Some(span) = destination_span(markdown, range, &target) else {
+                    continue;
+                };
+                if let Some(new_target) = rewrite(&text, &target) {
+                    replacements.push((span, new_target));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if replacements.is_empty() {
+        return markdown.to_string();
+    }
+    let mut output = String::with_capacity(markdown.len());
+    let mut position = 0;
+    for (span, target) in replacements {
+        output.push_str(&markdown[position..span.start]);
+        output.push_str(&target);
+        position = span.end;
+    }
+    output.push_str(&markdown[position..]);
+    output
+}
+
+/// Byte range of the destination within the source of an inline link
+///
+/// Returns `None` when the destination is written differently from its
+/// parsed form (escapes, entities), so such links are never rewritten.
+fn destination_span(markdown: &str, link: Range<usize>, target: &str) -> Option<Range<usize>> {
+    let source = &markdown[link.clone()];
+    let mut start = source.rfind("](")? + 2;
+    start += source[start..].len() - source[start..].trim_start().len();
+    if source[start..].starts_with('<') {
+        start += 1;
+    }
+    source[start..]
+        .starts_with(target)
+        .then_some(link.start + start..link.start + start + target.len())
+}
+
+pub(crate) fn is_external(target: &str) -> bool {
+    target.contains("://")
+        || target.starts_with("//")
+        || target.starts_with("mailto:")
+        || target.starts_with("tel:")
+        || target.starts_with("data:")
+}
+
+/// Path without `./` prefixes or leading slashes
+pub(crate) fn normalize_path(path: &str) -> &str {
+    let mut path = path.trim();
+    loop {
+        if let Some(rest) = path.strip_prefix("./") {
+            path = rest;
+        } else if let Some(rest) = path.strip_prefix('/') {
+            path = rest;
+        } else {
+            return path;
+        }
+    }
+}
+
+/// Lookup key of a page ID, title or file stem: lowercase words joined by `-`
+fn page_key(name: &str) -> String {
+    name.replace("%20", " ")
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Split `main.rs:45-67` into the path and the line range
+pub(crate) fn split_line_range(text: &str) -> (&str, Option<&str>) {
+    match text.rsplit_once(':') {
+        Some((path, lines)) if is_line_range(lines, "") => (path, Some(lines)),
+        _ => (text, None),
+    }
+}
+
+/// Whether an anchor is a line reference such as `L12` or `L45-L67`
+fn is_line_anchor(anchor: &str) -> bool {
+    is_line_range(anchor, "L")
+}
+
+/// Whether `text` is `{prefix}N` or `{prefix}N-{prefix}M`
+fn is_line_range(text: &str, prefix: &str) -> bool {
+    text.split('-').count() <= 2
+        && text.split('-').all(|part| {
+            part.strip_prefix(prefix)
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        })
+}
+
+/// Whether a link target looks like a file path rather than a page name
+fn looks_like_file(path: &str) -> bool {
+    if path.contains('/') {
+        return true;
+    }
+    match path.rsplit_once('.') {
+        Some((name, extension)) => {
+            !name.is_empty()
+                && (1..=5).contains(&extension.len())
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wiki() -> WikiStructure {
+        let mut wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/test/repo".to_string(),
+        );
+        let mut setup = WikiPage::new(
+            "getting-started".to_string(),
+            "Getting Started".to_string(),
+            String::new(),
+        );
+        setup.content = "# Getting Started\n\n## Install the CLI\n\nRun it.\n".to_string();
+        setup.file_paths = vec!["src/main.rs".to_string()];
+        let mut usage = WikiPage::new("usage".to_string(), "Usage".to_string(), String::new());
+        usage.content = "# Usage\n\n\
+            See [setup](./Getting-Started.md#Install-the-CLI) and [the guide](Getting%20Started).\n\
+            Sources: [main.rs:45-67](), [lib.rs:3]()\n\
+            Also [config](./configuration.md), [docs](https://example.com) and [top](#usage).\n"
+            .to_string();
+        wiki.pages = vec![setup, usage];
+        wiki
+    }
+
+    #[test]
+    fn test_resolves_and_rewrites_links() {
+        let mut wiki = wiki();
+        let validation = LinkResolver::new(&wiki)
+            .with_files(["src/lib.rs", "benches/main.rs"])
+            .resolve(&mut wiki)
+            .clone();
+
+        assert_eq!(
+            wiki.pages[1].content,
+            "# Usage\n\n\
+             See [setup](./getting-started.md#install-the-cli) and [the guide](./getting-started.md).\n\
+             Sources: [main.rs:45-67](src/main.rs#L45-L67), [lib.rs:3](src/lib.rs#L3)\n\
+             Also [config](./configuration.md), [docs](https://example.com) and [top](#usage).\n"
+        );
+        assert_eq!(validation.checked_links, 6);
+        assert_eq!(validation.rewritten_links, 4);
+        assert_eq!(validation.dangling.len(), 1);
+        assert_eq!(validation.dangling[0].target, "./configuration.md");
+        assert_eq!(validation.dangling[0].reason, DanglingReason::UnknownPage);
+        assert_eq!(
+            validation.linked_files.iter().collect::<Vec<_>>(),
+            vec!["src/lib.rs", "src/main.rs"]
+        );
+
+        // Resolving again is stable
+        let before = wiki.pages[1].content.clone();
+        let validation = LinkResolver::new(&wiki).resolve(&mut wiki);
+        assert_eq!(validation.rewritten_links, 0);
+        assert_eq!(wiki.pages[1].content, before);
+    }
+
+    #[test]
+    fn test_rewrite_page_links_for_export() {
+        let wiki = wiki();
+        let markdown = "[a](./getting-started.md#install-the-cli) [b](./other.md) [c](#x)";
+        let rewritten = rewrite_page_links(&wiki, markdown, |page| format!("{}.html", page.title));
+        assert_eq!(
+            rewritten,
+            "[a](Getting Started.html#install-the-cli) [b](./other.md) [c](#x)"
+        );
+    }
+}
@@ -3,10 +3,15 @@
 //! This module handles the final organization of markdown content that has been
 //! directly generated by LLMs. It focuses on file management, metadata, and export.
 
-use crate::types::{ImportanceLevel, WikiPage, WikiStructure};
-use std::collections::HashMap;
+use crate::types::{ImportanceLevel, WikiPage, WikiSection, WikiStructure};
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use tracing::{debug, info};
 
+/// Deepest page heading level included in the navigation by default
+const DEFAULT_MAX_HEADING_LEVEL: u8 = 3;
+
 /// Configuration options for markdown file organization
 #[derive(Debug, Clone)]
 pub struct MarkdownOptions {
@@ -28,6 +33,316 @@ impl Default for MarkdownOptions {
     }
 }
 
+/// Kind of entry in the wiki navigation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NavigationKind {
+    Section,
+    Page,
+    Heading,
+}
+
+/// Entry in the wiki navigation tree (sections → pages → headings)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NavigationItem {
+    /// Kind of entry
+    pub kind: NavigationKind,
+    /// Section ID for sections, page ID for pages and their headings
+    pub id: String,
+    /// Display title
+    pub title: String,
+    /// Anchor of a heading within its page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    /// Nested entries
+    #[serde(default)]
+    pub children: Vec<NavigationItem>,
+}
+
+/// Heading of a page with its anchor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageHeading {
+    /// Heading level (1-6)
+    pub level: u8,
+    /// Plain heading text
+    pub text: String,
+    /// Anchor, unique within the page (GitHub-style)
+    pub anchor: String,
+}
+
+/// Builds the hierarchical table of contents of a wiki
+///
+/// Sections are nested by their parent/subsection links and sorted by
+/// order, pages follow their section, and every page lists its headings so
+/// readers can jump into the middle of a page. Pages outside any section
+/// come last.
+#[derive(Debug, Clone)]
+pub struct NavigationBuilder {
+    max_heading_level: u8,
+}
+
+impl Default for NavigationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NavigationBuilder {
+    /// Create a builder including page headings down to level 3
+    pub fn new() -> Self {
+        Self {
+            max_heading_level: DEFAULT_MAX_HEADING_LEVEL,
+        }
+    }
+
+    /// Set the deepest heading level listed under pages (1 lists no headings)
+    pub fn with_max_heading_level(mut self, level: u8) -> Self {
+        self.max_heading_level = level;
+        self
+    }
+
+    /// Build the navigation tree of a wiki
+    pub fn build(&self, wiki: &WikiStructure) -> Vec<NavigationItem> {
+        let mut roots: Vec<&WikiSection> = wiki
+            .sections
+            .iter()
+            .filter(|s| match &s.parent_section {
+                Some(parent) => !wiki.sections.iter().any(|p| p.id == *parent),
+                None => true,
+            })
+            .collect();
+        roots.sort_by_key(|s| s.order);
+
+        let mut listed_pages = HashSet::new();
+        let mut visited_sections = HashSet::new();
+        let mut items: Vec<NavigationItem> = roots
+            .into_iter()
+            .filter_map(|section| {
+                self.section_item(wiki, section, &mut listed_pages, &mut visited_sections)
+            })
+            .collect();
+
+        for page in &wiki.pages {
+            if !listed_pages.contains(page.id.as_str()) {
+                items.push(self.page_item(page));
+            }
+        }
+        items
+    }
+
+    /// Navigation entry of a section, or `None` if it contains no pages
+    fn section_item<'a>(
+        &self,
+        wiki: &'a WikiStructure,
+        section: &'a WikiSection,
+        listed_pages: &mut HashSet<&'a str>,
+        visited_sections: &mut HashSet<&'a str>,
+    ) -> Option<NavigationItem> {
+        // Guard against cycles in malformed section links
+        if !visited_sections.insert(section.id.as_str()) {
+            return None;
+        }
+
+        let mut children = Vec::new();
+        for page_id in &section.pages {
+            if let Some(page) = wiki.pages.iter().find(|p| p.id == *page_id) {
+                if listed_pages.insert(page.id.as_str()) {
+                    children.push(self.page_item(page));
+                }
+            }
+        }
+
+        let mut subsections: Vec<&WikiSection> = wiki
+            .sections
+            .iter()
+            .filter(|s| {
+                section.subsections.contains(&s.id)
+                    || s.parent_section.as_deref() == Some(section.id.as_str())
+            })
+            .collect();
+        subsections.sort_by_key(|s| s.order);
+        for subsection in subsections {
+            if let Some(item) = self.section_item(wiki, subsection, listed_pages, visited_sections)
+            {
+                children.push(item);
+            }
+        }
+
+        if children.is_empty() {
+            return None;
+        }
+        Some(NavigationItem {
+            kind: NavigationKind::Section,
+            id: section.id.clone(),
+            title: section.title.clone(),
+            anchor: None,
+            children,
+        })
+    }
+
+    /// Navigation entry of a page with its headings
+    fn page_item(&self, page: &WikiPage) -> NavigationItem {
+        let mut headings = page_headings(&page.content);
+        // A leading top-level heading repeats the page title
+        if headings.first().is_some_and(|h| h.level == 1) {
+            headings.remove(0);
+        }
+        headings.retain(|h| h.level <= self.max_heading_level);
+
+        NavigationItem {
+            kind: NavigationKind::Page,
+            id: page.id.clone(),
+            title: page.title.clone(),
+            anchor: None,
+            children: nest_headings(&page.id, &headings),
+        }
+    }
+}
+
+/// Nest a flat list of headings by level
+fn nest_headings(page_id: &str, headings: &[PageHeading]) -> Vec<NavigationItem> {
+    let mut items = Vec::new();
+    let mut index = 0;
+    while index < headings.len() {
+        let heading = &headings[index];
+        let end = headings[index + 1..]
+            .iter()
+            .position(|h| h.level <= heading.level)
+            .map_or(headings.len(), |offset| index + 1 + offset);
+        items.push(NavigationItem {
+            kind: NavigationKind::Heading,
+            id: page_id.to_string(),
+            title: heading.text.clone(),
+            anchor: Some(heading.anchor.clone()),
+            children: nest_headings(page_id, &headings[index + 1..end]),
+        });
+        index = end;
+    }
+    items
+}
+
+/// Headings of a Markdown document in order, with unique anchors
+pub fn page_headings(markdown: &str) -> Vec<PageHeading> {
+    let mut headings = Vec::new();
+    let mut used_anchors = HashSet::new();
+    let mut current: Option<(u8, String)> = None;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((heading_level(level), String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, heading_text)) = current.as_mut() {
+                    heading_text.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, text)) = current.take() {
+                    let text = text.trim().to_string();
+                    let anchor = unique_anchor(&text, &mut used_anchors);
+                    headings.push(PageHeading {
+                        level,
+                        text,
+                        anchor,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+pub(crate) fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// GitHub-style heading anchor, deduplicated with `-1`, `-2`, ... suffixes
+fn unique_anchor(text: &str, used: &mut HashSet<String>) -> String {
+    let base = heading_anchor(text);
+
+    let mut anchor = base.clone();
+    let mut suffix = 0;
+    while used.contains(&anchor) {
+        suffix += 1;
+        anchor = format!("{}-{}", base, suffix);
+    }
+    used.insert(anchor.clone());
+    anchor
+}
+
+/// GitHub-style anchor of a heading text
+pub(crate) fn heading_anchor(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Markdown file name of a page, as used by the organizer and page links
+pub fn page_filename(page_id: &str) -> String {
+    format!("{}.md", page_id.replace(' ', "-").to_lowercase())
+}
+
+/// Render a navigation tree as a nested Markdown list
+///
+/// `page_link` maps a page ID to the link target of the page; heading
+/// anchors are appended to it.
+pub fn navigation_to_markdown<F>(items: &[NavigationItem], page_link: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    fn render<F: Fn(&str) -> String>(
+        out: &mut String,
+        items: &[NavigationItem],
+        depth: usize,
+        page_link: &F,
+    ) {
+        for item in items {
+            let indent = "  ".repeat(depth);
+            match item.kind {
+                NavigationKind::Section => {
+                    out.push_str(&format!("{}- **{}**\n", indent, item.title));
+                }
+                NavigationKind::Page => {
+                    out.push_str(&format!(
+                        "{}- [{}]({})\n",
+                        indent,
+                        item.title,
+                        page_link(&item.id)
+                    ));
+                }
+                NavigationKind::Heading => {
+                    out.push_str(&format!(
+                        "{}- [{}]({}#{})\n",
+                        indent,
+                        item.title,
+                        page_link(&item.id),
+                        item.anchor.as_deref().unwrap_or_default()
+                    ));
+                }
+            }
+            render(out, &item.children, depth + 1, page_link);
+        }
+    }
+
+    let mut out = String::new();
+    render(&mut out, items, 0, &page_link);
+    out
+}
+
 /// Simplified markdown file organizer for wiki content
 pub struct MarkdownOrganizer {
     /// Generation options
@@ -53,7 +368,7 @@ impl MarkdownOrganizer {
         // Organize individual pages (content is already markdown)
         for page in &wiki.pages {
             let content = self.finalize_page_content(page);
-            let filename = format!("{}.md", page.id.replace(' ', "-").to_lowercase());
+            let filename = page_filename(&page.id);
             markdown_files.insert(filename, content);
         }
 
@@ -107,7 +422,7 @@ impl MarkdownOrganizer {
         let mut footer = String::from("\n\n---\n\n## 🔗 Related Pages\n\n");
 
         for related_page in &page.related_pages {
-            let filename = format!("{}.md", related_page.replace(' ', "-").to_lowercase());
+            let filename = page_filename(related_page);
             footer.push_str(&format!("- [{}](./{})\n", related_page, filename));
         }
 
@@ -182,7 +497,7 @@ impl MarkdownOrganizer {
         if !critical_pages.is_empty() {
             content.push_str("## 🔥 Essential Reading\n\n");
             for page in critical_pages {
-                let filename = format!("{}.md", page.id.replace(' ', "-").to_lowercase());
+                let filename = page_filename(&page.id);
                 content.push_str(&format!(
                     "- [{}](./{}) - {}\n",
                     page.title, filename, page.description
@@ -194,7 +509,7 @@ impl MarkdownOrganizer {
         if !high_pages.is_empty() {
             content.push_str("## 📚 Important Documentation\n\n");
             for page in high_pages {
-                let filename = format!("{}.md", page.id.replace(' ', "-").to_lowercase());
+                let filename = page_filename(&page.id);
                 content.push_str(&format!(
                     "- [{}](./{}) - {}\n",
                     page.title, filename, page.description
@@ -206,7 +521,7 @@ impl MarkdownOrganizer {
         if !medium_pages.is_empty() {
             content.push_str("## 📖 Additional Resources\n\n");
             for page in medium_pages {
-                let filename = format!("{}.md", page.id.replace(' ', "-").to_lowercase());
+                let filename = page_filename(&page.id);
                 content.push_str(&format!(
                     "- [{}](./{}) - {}\n",
                     page.title, filename, page.description
@@ -218,7 +533,7 @@ impl MarkdownOrganizer {
         if !low_pages.is_empty() {
             content.push_str("## 📝 Reference\n\n");
             for page in low_pages {
-                let filename = format!("{}.md", page.id.replace(' ', "-").to_lowercase());
+                let filename = page_filename(&page.id);
                 content.push_str(&format!(
                     "- [{}](./{}) - {}\n",
                     page.title, filename, page.description
@@ -230,44 +545,76 @@ impl MarkdownOrganizer {
     /// Generate navigation file
     fn generate_navigation_file(&self, wiki: &WikiStructure) -> String {
         let mut nav = String::new();
-        nav.push_str("# 🧭 Navigation\n\n");
-        nav.push_str("Quick links to all documentation pages.\n\n");
-
-        // Group by sections if available
-        if !wiki.sections.is_empty() {
-            for section in &wiki.sections {
-                nav.push_str(&format!("## {}\n\n", section.title));
-                for page_id in &section.pages {
-                    if let Some(page) = wiki.pages.iter().find(|p| p.id == *page_id) {
-                        let link = format!("{}.md", page.id.replace(' ', "-").to_lowercase());
-                        let emoji = self.get_page_emoji(page);
-                        nav.push_str(&format!(
-                            "- {} [{}]({}) - {}\n",
-                            emoji, page.title, link, page.description
-                        ));
-                    }
-                }
-                nav.push('\n');
-            }
+        if self.options.use_emoji {
+            nav.push_str("# 🧭 Navigation\n\n");
         } else {
-            // Fallback to importance-based grouping
-            self.add_pages_by_importance(&mut nav, wiki);
+            nav.push_str("# Navigation\n\n");
         }
+        nav.push_str("Quick links to all documentation pages.\n\n");
+
+        let items = NavigationBuilder::new().build(wiki);
+        nav.push_str(&navigation_to_markdown(&items, page_filename));
 
         nav
     }
+}
 
-    /// Get emoji for page based on importance
-    fn get_page_emoji(&self, page: &WikiPage) -> &'static str {
-        if !self.options.use_emoji {
-            return "";
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_headings_have_unique_anchors() {
+        let headings = page_headings(
+            "# Setup\n## Install `cargo`\n```sh\n# not a heading\n```\n## Usage\n## Usage\n",
+        );
+        let anchors: Vec<_> = headings.iter().map(|h| h.anchor.as_str()).collect();
+        assert_eq!(anchors, vec!["setup", "install-cargo", "usage", "usage-1"]);
+        assert_eq!(headings[1].text, "Install cargo");
+    }
 
-        match page.importance {
-            ImportanceLevel::Critical => "🔥",
-            ImportanceLevel::High => "📚",
-            ImportanceLevel::Medium => "📖",
-            ImportanceLevel::Low => "📝",
+    #[test]
+    fn test_navigation_nests_sections_pages_and_headings() {
+        let mut wiki = WikiStructure::new(
+            "Test Wiki".to_string(),
+            "A test wiki".to_string(),
+            "/test/repo".to_string(),
+        );
+        for (id, content) in [
+            (
+                "setup",
+                "# Setup\n## Install\n### From source\n## Configure\n",
+            ),
+            ("faq", "## Why?\n"),
+        ] {
+            let mut page = WikiPage::new(id.to_string(), id.to_string(), String::new());
+            page.content = content.to_string();
+            wiki.pages.push(page);
         }
+        wiki.sections.push(WikiSection {
+            id: "start".to_string(),
+            title: "Getting Started".to_string(),
+            description: String::new(),
+            pages: vec!["setup".to_string()],
+            subsections: Vec::new(),
+            parent_section: None,
+            order: 1,
+        });
+
+        let items = NavigationBuilder::new().build(&wiki);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].kind, NavigationKind::Section);
+        assert_eq!(items[1].id, "faq");
+
+        let setup = &items[0].children[0];
+        assert_eq!(setup.children.len(), 2);
+        assert_eq!(setup.children[0].title, "Install");
+        assert_eq!(
+            setup.children[0].children[0].anchor.as_deref(),
+            Some("from-source")
+        );
+
+        let markdown = navigation_to_markdown(&items, |id| format!("{}.md", id));
+        assert!(markdown.contains("  - [setup](setup.md)\n    - [Install](setup.md#install)\n"));
     }
 }
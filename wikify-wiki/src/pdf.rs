@@ -0,0 +1,468 @@
+//! PDF rendering for wiki export
+//!
+//! Wikis are rendered to a Typst document (cover page, table of contents and one
+//! chapter per page) which is then compiled to PDF with the `typst` CLI. Typst
+//! highlights fenced code blocks natively based on their language tag.
+
+use crate::types::{WikiPage, WikiStructure};
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use std::path::Path;
+use tokio::fs;
+use tracing::{debug, info};
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// Environment variable overriding the Typst binary used for PDF export
+pub const TYPST_BIN_ENV: &str = "WIKIFY_TYPST_BIN";
+
+/// Render a wiki as Typst source
+pub fn render_typst(wiki: &WikiStructure) -> String {
+    let mut out = String::new();
+
+    // Document setup
+    out.push_str(&format!(
+        "#set document(title: {})\n",
+        typst_string(&wiki.title)
+    ));
+    out.push_str("#set page(paper: \"a4\", margin: 2.5cm)\n");
+    out.push_str("#set text(size: 11pt)\n");
+    out.push_str("#set heading(numbering: \"1.1\")\n");
+    out.push_str("#show heading.where(level: 1): it => { pagebreak(weak: true); it }\n");
+    out.push_str(
+        "#show raw.where(block: true): block.with(fill: luma(245), inset: 8pt, radius: 4pt, width: 100%)\n\n",
+    );
+
+    // Cover page
+    out.push_str("#align(center + horizon)[\n");
+    out.push_str(&format!(
+        "  #text(size: 28pt, weight: \"bold\")[{}]\n",
+        escape_markup(&wiki.title)
+    ));
+    if !wiki.description.is_empty() {
+        out.push_str("  #v(1em)\n");
+        out.push_str(&format!(
+            "  #text(size: 14pt)[{}]\n",
+            escape_markup(&wiki.description)
+        ));
+    }
+    out.push_str("  #v(2em)\n");
+    out.push_str(&format!(
+        "  #text(size: 10pt, fill: gray)[{} \\ Generated by Wikify on {}]\n",
+        escape_markup(&wiki.metadata.repository),
+        wiki.metadata.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+    out.push_str("]\n\n");
+
+    // Table of contents: sections, pages and the top-level headings of pages
+    out.push_str("#pagebreak()\n");
+    out.push_str("#outline(title: \"Table of Contents\", depth: 3)\n\n");
+    out.push_str("#set page(numbering: \"1\")\n");
+    out.push_str("#counter(page).update(1)\n\n");
+
+    // Pages, grouped by section when the wiki has sections
+    let mut sections: Vec<_> = wiki.sections.iter().collect();
+    sections.sort_by_key(|s| s.order);
+
+    let mut rendered = std::collections::HashSet::new();
+    for section in &sections {
+        let pages: Vec<&WikiPage> = section
+            .pages
+            .iter()
+            .filter_map(|id| wiki.pages.iter().find(|p| p.id == *id))
+            .filter(|p| rendered.insert(p.id.clone()))
+            .collect();
+        if pages.is_empty() {
+            continue;
+        }
+
+        out.push_str(&format!("= {}\n\n", escape_markup(&section.title)));
+        if !section.description.is_empty() {
+            out.push_str(&format!("{}\n\n", escape_markup(&section.description)));
+        }
+        for page in pages {
+            render_page(&mut out, page, 2);
+        }
+    }
+
+    let orphan_pages: Vec<&WikiPage> = wiki
+        .pages
+        .iter()
+        .filter(|p| !rendered.contains(&p.id))
+        .collect();
+    if !orphan_pages.is_empty() {
+        // Without sections every page is a chapter of its own
+        let level = if rendered.is_empty() {
+            1
+        } else {
+            out.push_str("= Other Pages\n\n");
+            2
+        };
+        for page in orphan_pages {
+            render_page(&mut out, page, level);
+        }
+    }
+
+    out
+}
+
+/// Compile Typst source to a PDF file
+///
+/// The `.typ` source is written next to the PDF and removed after a
+/// successful compilation, so it can be inspected when compilation fails.
+pub async fn compile_pdf(source: &str, output_path: &Path) -> WikifyResult<()> {
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let source_path = output_path.with_extension("typ");
+    fs::write(&source_path, source).await?;
+
+    let typst_bin = std::env::var(TYPST_BIN_ENV).unwrap_or_else(|_| "typst".to_string());
+    debug!("Compiling {:?} with {}", source_path, typst_bin);
+
+    let output = tokio::process::Command::new(&typst_bin)
+        .arg("compile")
+        .arg(&source_path)
+        .arg(output_path)
+        .output()
+        .await
+        .map_err(|e| {
+            Box::new(WikifyError::Config {
+                message: format!("Failed to run Typst ({}): {}", typst_bin, e),
+                source: Some(Box::new(e)),
+                context: ErrorContext::new("wiki_export")
+                    .with_operation("export_pdf")
+                    .with_suggestion("Install Typst from https://typst.app")
+                    .with_suggestion(&format!(
+                        "Or set {} to the path of the typst binary",
+                        TYPST_BIN_ENV
+                    )),
+            })
+        })?;
+
+    if !output.status.success() {
+        return Err(Box::new(WikifyError::WikiGeneration {
+            message: format!(
+                "Typst failed to compile {}: {}",
+                source_path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            source: None,
+            context: ErrorContext::new("wiki_export").with_operation("export_pdf"),
+        }));
+    }
+
+    let _ = fs::remove_file(&source_path).await;
+    info!("Compiled PDF: {:?}", output_path);
+    Ok(())
+}
+
+/// Render a single page with its title at the given heading level
+fn render_page(out: &mut String, page: &WikiPage, level: usize) {
+    out.push_str(&format!(
+        "{} {}\n\n",
+        "=".repeat(level),
+        escape_markup(&page.title)
+    ));
+    out.push_str(&markdown_to_typst(&page.content, level));
+    out.push('\n');
+}
+
+/// Convert Markdown to Typst markup
+///
+/// Headings are nested below `base_level`; a leading top-level heading is
+/// dropped because the page title is already rendered by the caller.
+pub fn markdown_to_typst(markdown: &str, base_level: usize) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut out = String::new();
+    let mut lists: Vec<bool> = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+    // Path and alt text of a local image
+    let mut image: Option<(String, String)> = None;
+    let mut in_table_head = false;
+    let mut skipping_title = false;
+    let mut first_event = true;
+
+    for event in Parser::new_ext(markdown, options) {
+        let is_first = std::mem::replace(&mut first_event, false);
+
+        if skipping_title {
+            if let Event::End(TagEnd::Heading(_)) = event {
+                skipping_title = false;
+            }
+            continue;
+        }
+
+        if code_block.is_some() {
+            match event {
+                Event::Text(text) => {
+                    if let Some((_, code)) = code_block.as_mut() {
+                        code.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    let (lang, code) = code_block.take().unwrap_or_default();
+                    ensure_newline(&mut out);
+                    out.push_str(&format!("#raw({}, block: true", typst_string(&code)));
+                    if !lang.is_empty() {
+                        out.push_str(&format!(", lang: {}", typst_string(&lang)));
+                    }
+                    out.push_str(")\n\n");
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if image.is_some() {
+            match event {
+                Event::Text(text) | Event::Code(text) => {
+                    if let Some((_, alt)) = image.as_mut() {
+                        alt.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::Image) => {
+                    let (path, alt) = image.take().unwrap_or_default();
+                    let figure = format!("image({}, width: 80%)", typst_string(&path));
+                    if alt.trim().is_empty() {
+                        out.push_str(&format!("#box({})", figure));
+                    } else {
+                        out.push_str(&format!(
+                            "#figure({}, caption: [{}])",
+                            figure,
+                            escape_markup(alt.trim())
+                        ));
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Heading {
+                    level: HeadingLevel::H1,
+                    ..
+                } if is_first => skipping_title = true,
+                Tag::Heading { level, .. } => {
+                    ensure_newline(&mut out);
+                    out.push_str(&"=".repeat(base_level + level as usize - 1));
+                    out.push(' ');
+                }
+                Tag::Paragraph => {
+                    if lists.is_empty() {
+                        ensure_newline(&mut out);
+                    }
+                }
+                Tag::BlockQuote(_) => {
+                    ensure_newline(&mut out);
+                    out.push_str("#quote(block: true)[\n");
+                }
+                Tag::CodeBlock(kind) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(info) => {
+                            info.split_whitespace().next().unwrap_or("").to_string()
+                        }
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code_block = Some((lang, String::new()));
+                }
+                Tag::List(start) => {
+                    ensure_newline(&mut out);
+                    lists.push(start.is_some());
+                }
+                Tag::Item => {
+                    ensure_newline(&mut out);
+                    let depth = lists.len().max(1);
+                    let marker = if lists.last().copied().unwrap_or(false) {
+                        "+"
+                    } else {
+                        "-"
+                    };
+                    out.push_str(&"  ".repeat(depth - 1));
+                    out.push_str(marker);
+                    out.push(' ');
+                }
+                Tag::Emphasis => out.push_str("#emph["),
+                Tag::Strong => out.push_str("#strong["),
+                Tag::Strikethrough => out.push_str("#strike["),
+                // Local images are bundled next to the source; remote ones stay links
+                Tag::Image { dest_url, .. } if !dest_url.contains("://") => {
+                    image = Some((dest_url.replace("%20", " "), String::new()));
+                }
+                Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. } => {
+                    out.push_str(&format!("#link({})[", typst_string(&dest_url)));
+                }
+                Tag::Table(alignments) => {
+                    ensure_newline(&mut out);
+                    out.push_str(&format!("#table(columns: {},\n", alignments.len().max(1)));
+                }
+                Tag::TableHead => in_table_head = true,
+                Tag::TableCell => {
+                    out.push_str(if in_table_head { "  [#strong[" } else { "  [" });
+                }
+                _ => {}
+            },
+            Event::End(tag) => match tag {
+                TagEnd::Heading(_) => out.push_str("\n\n"),
+                TagEnd::Paragraph => {
+                    if lists.is_empty() {
+                        out.push_str("\n\n");
+                    } else {
+                        out.push(' ');
+                    }
+                }
+                TagEnd::BlockQuote(_) => {
+                    ensure_newline(&mut out);
+                    out.push_str("]\n\n");
+                }
+                TagEnd::List(_) => {
+                    lists.pop();
+                    if lists.is_empty() {
+                        ensure_newline(&mut out);
+                        out.push('\n');
+                    }
+                }
+                TagEnd::Item => ensure_newline(&mut out),
+                TagEnd::Emphasis
+                | TagEnd::Strong
+                | TagEnd::Strikethrough
+                | TagEnd::Link
+                | TagEnd::Image => out.push(']'),
+                TagEnd::Table => out.push_str(")\n\n"),
+                TagEnd::TableHead => in_table_head = false,
+                TagEnd::TableCell => {
+                    out.push_str(if in_table_head { "]],\n" } else { "],\n" });
+                }
+                _ => {}
+            },
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                out.push_str(&escape_markup(&text));
+            }
+            Event::Code(code) => out.push_str(&format!("#raw({})", typst_string(&code))),
+            Event::SoftBreak => out.push(' '),
+            Event::HardBreak => out.push_str(" \\\n"),
+            Event::Rule => {
+                ensure_newline(&mut out);
+                out.push_str("#line(length: 100%)\n\n");
+            }
+            Event::TaskListMarker(checked) => out.push_str(if checked { "☑ " } else { "☐ " }),
+            _ => {}
+        }
+    }
+
+    out
+}
+
+/// Start a new line unless the output is already at one
+fn ensure_newline(out: &mut String) {
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+/// Escape text so it is rendered literally in Typst markup
+fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' | '#' | '*' | '_' | '`' | '$' | '<' | '>' | '@' | '[' | ']' | '~' | '=' | '-'
+            | '+' | '/' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' | '\r' => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Quote text as a Typst string literal
+fn typst_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => {}
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_markup() {
+        assert_eq!(
+            escape_markup("see http://x.io #1 *a*"),
+            "see http:\\/\\/x.io \\#1 \\*a\\*"
+        );
+    }
+
+    #[test]
+    fn test_code_blocks_are_highlighted_raw() {
+        let typst = markdown_to_typst("```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n", 1);
+        assert_eq!(
+            typst,
+            "#raw(\"fn main() {\\n    println!(\\\"hi\\\");\\n}\\n\", block: true, lang: \"rust\")\n\n"
+        );
+    }
+
+    #[test]
+    fn test_headings_are_nested_below_page_title() {
+        let typst = markdown_to_typst("# Title\n\nIntro\n\n## Usage\n\n- one\n- two\n", 2);
+        assert_eq!(typst, "Intro\n\n=== Usage\n\n- one\n- two\n\n");
+    }
+
+    #[test]
+    fn test_local_images_become_figures() {
+        let typst = markdown_to_typst(
+            "![Architecture](wiki-media/docs/arch%20v2.png) ![Badge](https://x.io/b.svg)\n",
+            1,
+        );
+        assert_eq!(
+            typst,
+            "#figure(image(\"wiki-media/docs/arch v2.png\", width: 80%), caption: [Architecture]) \
+             #link(\"https://x.io/b.svg\")[Badge]\n\n"
+        );
+    }
+
+    #[test]
+    fn test_render_typst_has_cover_and_outline() {
+        let mut wiki = WikiStructure::new(
+            "Demo".to_string(),
+            "A demo wiki".to_string(),
+            "/tmp/demo".to_string(),
+        );
+        wiki.pages.push(WikiPage {
+            id: "overview".to_string(),
+            title: "Overview".to_string(),
+            content: "# Overview\n\nHello".to_string(),
+            description: String::new(),
+            importance: crate::types::ImportanceLevel::High,
+            file_paths: vec![],
+            related_pages: vec![],
+            parent_section: None,
+            tags: vec![],
+            reading_time: 1,
+            generated_at: chrono::Utc::now(),
+            source_documents: vec![],
+            code_examples: vec![],
+        });
+
+        let typst = render_typst(&wiki);
+        assert!(typst.contains("#text(size: 28pt, weight: \"bold\")[Demo]"));
+        assert!(typst.contains("#outline("));
+        assert!(typst.contains("= Overview\n\nHello\n\n"));
+    }
+}
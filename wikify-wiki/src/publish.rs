@@ -0,0 +1,475 @@
+//! Publishing generated wikis to hosted wiki repositories
+//!
+//! GitHub stores a repository's native wiki in a separate `{repo}.wiki.git`
+//! repository. The publisher clones it, replaces the pages it generated on the
+//! previous run with the current ones, and pushes a single commit. Pages that
+//! were written by hand are left untouched.
+
+use crate::links::rewrite_page_links;
+use crate::types::{WikiPage, WikiStructure};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::process::Command;
+use tracing::{debug, info};
+use url::Url;
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// File in the wiki repository listing the pages generated by Wikify
+pub const MANIFEST_FILE: &str = ".wikify-pages";
+
+/// Result of publishing a wiki
+#[derive(Debug, Clone)]
+pub struct PublishReport {
+    /// Browser URL of the published wiki
+    pub wiki_url: String,
+    /// Number of pages written (including Home and sidebar)
+    pub pages_published: usize,
+    /// Whether a commit was pushed (false when the wiki was already up to date)
+    pub changed: bool,
+}
+
+/// Publishes generated wikis to a GitHub repository's native wiki
+#[derive(Debug, Clone)]
+pub struct GitHubWikiPublisher {
+    token: Option<String>,
+    author_name: String,
+    author_email: String,
+    commit_message: String,
+}
+
+impl Default for GitHubWikiPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitHubWikiPublisher {
+    /// Create a publisher using the `GITHUB_TOKEN` secret for authentication
+    /// if set
+    pub fn new() -> Self {
+        Self {
+            token: wikify_core::secrets::secret("GITHUB_TOKEN"),
+            author_name: "Wikify".to_string(),
+            author_email: "wikify@users.noreply.github.com".to_string(),
+            commit_message: "Update wiki generated by Wikify".to_string(),
+        }
+    }
+
+    /// Set the token used to push to the wiki repository
+    pub fn with_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Set the commit author
+    pub fn with_author<N: Into<String>, E: Into<String>>(mut self, name: N, email: E) -> Self {
+        self.author_name = name.into();
+        self.author_email = email.into();
+        self
+    }
+
+    /// Set the commit message
+    pub fn with_commit_message<S: Into<String>>(mut self, message: S) -> Self {
+        self.commit_message = message.into();
+        self
+    }
+
+    /// Publish a wiki to the native wiki of the given GitHub repository
+    pub async fn publish(
+        &self,
+        wiki: &WikiStructure,
+        repo_url: &str,
+    ) -> WikifyResult<PublishReport> {
+        let (owner, repo) = parse_github_repository(repo_url)?;
+        let wiki_url = format!("https://github.com/{}/{}/wiki", owner, repo);
+        let remote_url = match &self.token {
+            Some(token) => format!(
+                "https://x-access-token:{}@github.com/{}/{}.wiki.git",
+                token, owner, repo
+            ),
+            None => format!("https://github.com/{}/{}.wiki.git", owner, repo),
+        };
+
+        let work_dir =
+            std::env::temp_dir().join(format!("wikify-wiki-publish-{}", uuid::Uuid::new_v4()));
+        let result = self
+            .publish_in(wiki, &remote_url, &wiki_url, &work_dir)
+            .await;
+        let _ = fs::remove_dir_all(&work_dir).await;
+        result
+    }
+
+    async fn publish_in(
+        &self,
+        wiki: &WikiStructure,
+        remote_url: &str,
+        wiki_url: &str,
+        work_dir: &Path,
+    ) -> WikifyResult<PublishReport> {
+        info!("Publishing wiki to {}", wiki_url);
+
+        self.git(
+            None,
+            &["clone", "--depth", "1", remote_url, &path_arg(work_dir)],
+        )
+        .await
+        .map_err(|e| {
+            Box::new(WikifyError::Repository {
+                message: format!("Failed to clone wiki repository: {}", e),
+                source: None,
+                context: ErrorContext::new("wiki_publisher")
+                    .with_operation("publish")
+                    .with_suggestion(
+                        "GitHub only creates the wiki repository after the first page is \
+                             saved; create a page in the repository's Wiki tab first",
+                    )
+                    .with_suggestion("Set GITHUB_TOKEN to a token with write access"),
+            })
+        })?;
+
+        let pages = render_pages(wiki);
+        write_pages(work_dir, &pages).await?;
+
+        self.git(Some(work_dir), &["add", "--all"]).await?;
+        let status = self.git(Some(work_dir), &["status", "--porcelain"]).await?;
+        if status.trim().is_empty() {
+            info!("Wiki at {} is already up to date", wiki_url);
+            return Ok(PublishReport {
+                wiki_url: wiki_url.to_string(),
+                pages_published: pages.len(),
+                changed: false,
+            });
+        }
+
+        let name = format!("user.name={}", self.author_name);
+        let email = format!("user.email={}", self.author_email);
+        self.git(
+            Some(work_dir),
+            &[
+                "-c",
+                &name,
+                "-c",
+                &email,
+                "commit",
+                "-m",
+                &self.commit_message,
+            ],
+        )
+        .await?;
+        self.git(Some(work_dir), &["push", "origin", "HEAD"])
+            .await?;
+
+        info!("Published {} wiki pages to {}", pages.len(), wiki_url);
+        Ok(PublishReport {
+            wiki_url: wiki_url.to_string(),
+            pages_published: pages.len(),
+            changed: true,
+        })
+    }
+
+    /// Run a git command, returning its stdout
+    async fn git(&self, dir: Option<&Path>, args: &[&str]) -> WikifyResult<String> {
+        let mut cmd = Command::new("git");
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+        cmd.args(args).env("GIT_TERMINAL_PROMPT", "0");
+
+        let output = cmd.output().await.map_err(|e| WikifyError::Git {
+            message: format!("Failed to execute git: {}", e),
+            context: ErrorContext::new("wiki_publisher")
+                .with_suggestion("Ensure git is installed and accessible"),
+        })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Box::new(WikifyError::Git {
+                message: format!(
+                    "git {} failed: {}",
+                    args.first().copied().unwrap_or_default(),
+                    self.redact(stderr.trim())
+                ),
+                context: ErrorContext::new("wiki_publisher"),
+            }));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Remove the token from text that may echo the remote URL
+    fn redact(&self, text: &str) -> String {
+        match &self.token {
+            Some(token) => text.replace(token.as_str(), "***"),
+            None => text.to_string(),
+        }
+    }
+}
+
+/// Extract owner and repository name from a GitHub URL
+fn parse_github_repository(repo_url: &str) -> WikifyResult<(String, String)> {
+    let invalid = || {
+        Box::new(WikifyError::Validation {
+            message: format!("Not a GitHub repository URL: {}", repo_url),
+            field: Some("repo_url".to_string()),
+            context: ErrorContext::new("wiki_publisher")
+                .with_suggestion("Use a URL like https://github.com/owner/repo"),
+        })
+    };
+
+    // SSH form: git@github.com:owner/repo.git
+    let path = if let Some(path) = repo_url.strip_prefix("git@github.com:") {
+        path.to_string()
+    } else {
+        let url = Url::parse(repo_url).map_err(|_| invalid())?;
+        if url.host_str() != Some("github.com") {
+            return Err(invalid());
+        }
+        url.path().to_string()
+    };
+
+    let mut segments = path.trim_matches('/').split('/');
+    match (segments.next(), segments.next()) {
+        (Some(owner), Some(repo)) if !owner.is_empty() && !repo.is_empty() => {
+            let repo = repo.strip_suffix(".git").unwrap_or(repo);
+            Ok((owner.to_string(), repo.to_string()))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// GitHub wiki page name for a title (the file name without `.md`)
+///
+/// GitHub shows hyphens in page file names as spaces.
+pub fn github_wiki_page_name(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .chars()
+        .filter(|c| {
+            !matches!(
+                c,
+                '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#'
+            )
+        })
+        .collect()
+}
+
+/// Render all wiki files as (file name, content) pairs
+fn render_pages(wiki: &WikiStructure) -> Vec<(String, String)> {
+    let mut used = HashSet::new();
+    let mut names = Vec::new();
+    for page in &wiki.pages {
+        let base = github_wiki_page_name(&page.title);
+        let base = if base.is_empty() {
+            page.id.clone()
+        } else {
+            base
+        };
+        // Home and _Sidebar are reserved for the index and navigation
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while name.eq_ignore_ascii_case("Home")
+            || name.eq_ignore_ascii_case("_Sidebar")
+            || !used.insert(name.to_lowercase())
+        {
+            name = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+        names.push((page, name));
+    }
+
+    let mut files = vec![
+        ("Home.md".to_string(), render_home(wiki, &names)),
+        ("_Sidebar.md".to_string(), render_sidebar(wiki, &names)),
+    ];
+    let links: HashMap<&str, &str> = names
+        .iter()
+        .map(|(page, name)| (page.id.as_str(), name.as_str()))
+        .collect();
+    for (page, name) in &names {
+        // GitHub wiki links address pages by name, without `.md`
+        let content = rewrite_page_links(wiki, &page.content, |target| {
+            links
+                .get(target.id.as_str())
+                .copied()
+                .unwrap_or_default()
+                .to_string()
+        });
+        files.push((format!("{}.md", name), content));
+    }
+    files
+}
+
+/// Landing page with the wiki description and an index of all pages
+fn render_home(wiki: &WikiStructure, names: &[(&WikiPage, String)]) -> String {
+    let mut content = format!("# {}\n\n{}\n\n", wiki.title, wiki.description);
+    content.push_str(&render_index(wiki, names, "##"));
+    content.push_str(&format!(
+        "\n---\n\n*Generated by Wikify on {}*\n",
+        wiki.metadata.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+    content
+}
+
+/// Sidebar navigation shown next to every wiki page
+fn render_sidebar(wiki: &WikiStructure, names: &[(&WikiPage, String)]) -> String {
+    format!("**[[Home]]**\n\n{}", render_index(wiki, names, "####"))
+}
+
+/// Page links grouped by section
+fn render_index(wiki: &WikiStructure, names: &[(&WikiPage, String)], heading: &str) -> String {
+    let link = |page_id: &str| {
+        names
+            .iter()
+            .find(|(page, _)| page.id == page_id)
+            .map(|(page, name)| format!("- [{}]({})\n", page.title, name))
+    };
+
+    let mut content = String::new();
+    let mut listed = HashSet::new();
+    let mut sections: Vec<_> = wiki.sections.iter().collect();
+    sections.sort_by_key(|s| s.order);
+
+    for section in sections {
+        let links: Vec<String> = section
+            .pages
+            .iter()
+            .filter(|id| listed.insert(id.as_str()))
+            .filter_map(|id| link(id))
+            .collect();
+        if links.is_empty() {
+            continue;
+        }
+        content.push_str(&format!("{} {}\n\n", heading, section.title));
+        content.push_str(&links.concat());
+        content.push('\n');
+    }
+
+    let others: Vec<String> = names
+        .iter()
+        .filter(|(page, _)| !listed.contains(page.id.as_str()))
+        .filter_map(|(page, _)| link(&page.id))
+        .collect();
+    if !others.is_empty() {
+        if !content.is_empty() {
+            content.push_str(&format!("{} Other Pages\n\n", heading));
+        }
+        content.push_str(&others.concat());
+    }
+
+    content
+}
+
+/// Replace the previously generated pages with the new ones and update the manifest
+async fn write_pages(work_dir: &Path, pages: &[(String, String)]) -> WikifyResult<()> {
+    let manifest_path = work_dir.join(MANIFEST_FILE);
+    if let Ok(previous) = fs::read_to_string(&manifest_path).await {
+        for file_name in previous.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let path: PathBuf = work_dir.join(file_name);
+            // Only plain file names are ever written to the manifest
+            if path.parent() == Some(work_dir) {
+                let _ = fs::remove_file(&path).await;
+            }
+        }
+    }
+
+    for (file_name, content) in pages {
+        fs::write(work_dir.join(file_name), content).await?;
+        debug!("Wrote wiki page {}", file_name);
+    }
+
+    let manifest: Vec<&str> = pages.iter().map(|(name, _)| name.as_str()).collect();
+    fs::write(&manifest_path, manifest.join("\n") + "\n").await?;
+    Ok(())
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_github_repository() {
+        assert_eq!(
+            parse_github_repository("https://github.com/Latias94/wikify.git").unwrap(),
+            ("Latias94".to_string(), "wikify".to_string())
+        );
+        assert_eq!(
+            parse_github_repository("git@github.com:Latias94/wikify.git").unwrap(),
+            ("Latias94".to_string(), "wikify".to_string())
+        );
+        assert!(parse_github_repository("https://gitlab.com/owner/repo").is_err());
+        assert!(parse_github_repository("https://github.com/owner").is_err());
+    }
+
+    #[test]
+    fn test_page_names_avoid_reserved_and_duplicates() {
+        assert_eq!(
+            github_wiki_page_name("Getting Started: Setup"),
+            "Getting-Started-Setup"
+        );
+
+        let mut wiki = WikiStructure::new(
+            "Demo".to_string(),
+            "Demo wiki".to_string(),
+            "/tmp/demo".to_string(),
+        );
+        for (id, title) in [("a", "Home"), ("b", "Usage"), ("c", "usage")] {
+            wiki.pages.push(WikiPage {
+                id: id.to_string(),
+                title: title.to_string(),
+                content: format!("# {}", title),
+                description: String::new(),
+                importance: crate::types::ImportanceLevel::Medium,
+                file_paths: vec![],
+                related_pages: vec![],
+                parent_section: None,
+                tags: vec![],
+                reading_time: 1,
+                generated_at: chrono::Utc::now(),
+                source_documents: vec![],
+                code_examples: vec![],
+            });
+        }
+
+        let names: Vec<String> = render_pages(&wiki).into_iter().map(|(n, _)| n).collect();
+        assert_eq!(
+            names,
+            vec![
+                "Home.md",
+                "_Sidebar.md",
+                "Home-2.md",
+                "Usage.md",
+                "usage-2.md"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_pages_replaces_previous_generation_only() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::write(dir.join("Old.md"), "old").await.unwrap();
+        fs::write(dir.join("Manual.md"), "manual").await.unwrap();
+        fs::write(dir.join(MANIFEST_FILE), "Old.md\n")
+            .await
+            .unwrap();
+
+        write_pages(dir, &[("New.md".to_string(), "new".to_string())])
+            .await
+            .unwrap();
+
+        assert!(!dir.join("Old.md").exists());
+        assert!(dir.join("Manual.md").exists());
+        assert!(dir.join("New.md").exists());
+        assert_eq!(
+            fs::read_to_string(dir.join(MANIFEST_FILE)).await.unwrap(),
+            "New.md\n"
+        );
+    }
+}
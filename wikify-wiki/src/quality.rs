@@ -0,0 +1,369 @@
+//! Wiki quality scoring
+//!
+//! After generation every page is scored on the things that make generated
+//! documentation untrustworthy: links that lead nowhere, headings with
+//! nothing under them, files cited that do not exist and claims that are not
+//! backed by links to the source. The wiki as a whole is also scored on how
+//! much of the repository its pages cover. The resulting [`QualityReport`]
+//! lists the weakest pages first, so it doubles as a gap report.
+//!
+//! Pages built without the LLM (the dependency graph and recent changes
+//! pages) are not scored.
+
+use crate::changelog::CHANGELOG_PAGE_ID;
+use crate::dependency_graph::DEPENDENCY_GRAPH_PAGE_ID;
+use crate::links::{is_external, normalize_path};
+use crate::markdown_organizer::heading_level;
+use crate::types::{WikiPage, WikiStructure};
+use pulldown_cmark::{Event, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// Source citations per 1,000 words below which a page loses points
+pub const MIN_CITATION_DENSITY: f64 = 3.0;
+
+/// Uncovered repository files listed in a report
+const MAX_LISTED_FILES: usize = 50;
+
+/// Quality report of a wiki
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityReport {
+    /// Overall score from 0 to 100
+    pub score: u8,
+    /// Share of the repository's files referenced by at least one page
+    /// (0.0-1.0); `None` when the repository's files are not known
+    pub file_coverage: Option<f64>,
+    /// Files in the repository
+    pub total_files: usize,
+    /// Repository files referenced by at least one page
+    pub referenced_files: usize,
+    /// Repository files no page references, up to 50
+    pub uncovered_files: Vec<String>,
+    /// Links that could not be resolved, across all pages
+    pub broken_links: usize,
+    /// Empty headings across all pages
+    pub empty_sections: usize,
+    /// Wiki sections without pages or subsections
+    pub empty_wiki_sections: Vec<String>,
+    /// Scored pages, weakest first
+    pub pages: Vec<PageQuality>,
+}
+
+/// Quality of a single page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageQuality {
+    pub page_id: String,
+    pub title: String,
+    /// Score from 0 to 100
+    pub score: u8,
+    /// Files the page's metadata references that are not in the repository
+    pub missing_files: Vec<String>,
+    /// Links on the page that could not be resolved
+    pub broken_links: usize,
+    /// Headings with no content before the next heading of the same or a
+    /// higher level
+    pub empty_sections: Vec<String>,
+    /// Links to repository files
+    pub citations: usize,
+    /// Citations per 1,000 words
+    pub citation_density: f64,
+    /// Human-readable summary of what lowered the score
+    pub issues: Vec<String>,
+}
+
+impl QualityReport {
+    /// Score a wiki against the files of its repository
+    ///
+    /// Broken links are taken from the wiki's link validation, so links
+    /// should be resolved first. Without `repo_files` coverage and missing
+    /// files are not checked.
+    pub fn evaluate<S: AsRef<str>>(wiki: &WikiStructure, repo_files: &[S]) -> Self {
+        let repo_files: BTreeSet<&str> = repo_files
+            .iter()
+            .map(|f| normalize_path(f.as_ref()))
+            .filter(|f| !f.is_empty())
+            .collect();
+        let known_files: HashSet<&str> = repo_files
+            .iter()
+            .copied()
+            .chain(wiki.link_validation.linked_files.iter().map(String::as_str))
+            .collect();
+
+        let mut pages: Vec<PageQuality> = wiki
+            .pages
+            .iter()
+            .filter(|page| {
+                ![DEPENDENCY_GRAPH_PAGE_ID, CHANGELOG_PAGE_ID].contains(&page.id.as_str())
+            })
+            .map(|page| score_page(wiki, page, &repo_files, &known_files))
+            .collect();
+        pages.sort_by(|a, b| {
+            a.score
+                .cmp(&b.score)
+                .then_with(|| a.page_id.cmp(&b.page_id))
+        });
+
+        let referenced: HashSet<&str> = wiki
+            .pages
+            .iter()
+            .flat_map(|page| {
+                page.file_paths
+                    .iter()
+                    .chain(page.code_examples.iter().map(|example| &example.path))
+            })
+            .map(|f| normalize_path(f))
+            .chain(wiki.link_validation.linked_files.iter().map(String::as_str))
+            .filter(|f| repo_files.contains(f))
+            .collect();
+        let file_coverage =
+            (!repo_files.is_empty()).then(|| referenced.len() as f64 / repo_files.len() as f64);
+        let uncovered_files = repo_files
+            .iter()
+            .filter(|f| !referenced.contains(*f))
+            .take(MAX_LISTED_FILES)
+            .map(|f| f.to_string())
+            .collect();
+
+        let empty_wiki_sections: Vec<String> = wiki
+            .sections
+            .iter()
+            .filter(|s| s.pages.is_empty() && s.subsections.is_empty())
+            .map(|s| s.title.clone())
+            .collect();
+
+        let mut score = if pages.is_empty() {
+            0.0
+        } else {
+            pages.iter().map(|p| p.score as f64).sum::<f64>() / pages.len() as f64
+        };
+        if let Some(coverage) = file_coverage {
+            score = score * 0.8 + coverage * 20.0;
+        }
+        score -= (empty_wiki_sections.len() as f64 * 5.0).min(20.0);
+
+        Self {
+            score: score.round().clamp(0.0, 100.0) as u8,
+            file_coverage,
+            total_files: repo_files.len(),
+            referenced_files: referenced.len(),
+            uncovered_files,
+            broken_links: wiki.link_validation.dangling.len(),
+            empty_sections: pages.iter().map(|p| p.empty_sections.len()).sum(),
+            empty_wiki_sections,
+            pages,
+        }
+    }
+
+    /// Pages scoring below `threshold`, weakest first
+    pub fn weak_pages(&self, threshold: u8) -> impl Iterator<Item = &PageQuality> {
+        self.pages.iter().filter(move |p| p.score < threshold)
+    }
+}
+
+fn score_page(
+    wiki: &WikiStructure,
+    page: &WikiPage,
+    repo_files: &BTreeSet<&str>,
+    known_files: &HashSet<&str>,
+) -> PageQuality {
+    let missing_files: Vec<String> = if repo_files.is_empty() {
+        Vec::new()
+    } else {
+        page.file_paths
+            .iter()
+            .map(|f| normalize_path(f))
+            .filter(|f| !f.is_empty() && *f != "unknown" && !repo_files.contains(f))
+            .map(str::to_string)
+            .collect()
+    };
+    let broken_links = wiki
+        .link_validation
+        .dangling
+        .iter()
+        .filter(|link| link.page_id == page.id)
+        .count();
+    let empty_sections = empty_headings(&page.content);
+    let citations = count_citations(&page.content, known_files);
+    let words = page.word_count();
+    let citation_density = if words == 0 {
+        0.0
+    } else {
+        citations as f64 * 1000.0 / words as f64
+    };
+
+    let mut issues = Vec::new();
+    let score = if !page.has_content() {
+        issues.push("Page has no content".to_string());
+        0.0
+    } else {
+        let mut score = 100.0;
+        if broken_links > 0 {
+            score -= (broken_links as f64 * 10.0).min(30.0);
+            issues.push(format!("{} broken link(s)", broken_links));
+        }
+        if !empty_sections.is_empty() {
+            score -= (empty_sections.len() as f64 * 5.0).min(20.0);
+            issues.push(format!("Empty sections: {}", empty_sections.join(", ")));
+        }
+        if !missing_files.is_empty() && !page.file_paths.is_empty() {
+            score -= 20.0 * missing_files.len() as f64 / page.file_paths.len() as f64;
+            issues.push(format!(
+                "References files not in the repository: {}",
+                missing_files.join(", ")
+            ));
+        }
+        if citation_density < MIN_CITATION_DENSITY {
+            score -= 30.0 * (1.0 - citation_density / MIN_CITATION_DENSITY);
+            issues.push(format!(
+                "Few source citations ({:.1} per 1,000 words)",
+                citation_density
+            ));
+        }
+        score
+    };
+
+    PageQuality {
+        page_id: page.id.clone(),
+        title: page.title.clone(),
+        score: score.round().clamp(0.0, 100.0) as u8,
+        missing_files,
+        broken_links,
+        empty_sections,
+        citations,
+        citation_density,
+        issues,
+    }
+}
+
+/// Headings followed by nothing but a heading of the same or a higher level
+fn empty_headings(markdown: &str) -> Vec<String> {
+    let mut empty = Vec::new();
+    // Level, text and whether content followed the current heading
+    let mut open: Option<(u8, String, bool)> = None;
+    let mut in_heading = false;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                let level = heading_level(level);
+                if let Some((open_level, text, false)) = open.take() {
+                    if level <= open_level {
+                        empty.push(text);
+                    }
+                }
+                open = Some((level, String::new(), false));
+                in_heading = true;
+            }
+            Event::End(TagEnd::Heading(_)) => in_heading = false,
+            Event::Text(text) | Event::Code(text) if in_heading => {
+                if let Some((_, heading, _)) = open.as_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            Event::Start(Tag::Paragraph | Tag::List(_) | Tag::CodeBlock(_) | Tag::Table(_))
+            | Event::Start(Tag::BlockQuote(_) | Tag::HtmlBlock)
+            | Event::Rule => {
+                if let Some((_, _, has_content)) = open.as_mut() {
+                    *has_content = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((_, text, false)) = open {
+        empty.push(text);
+    }
+    empty
+        .into_iter()
+        .map(|text| text.trim().to_string())
+        .collect()
+}
+
+/// Links to repository files in a page
+fn count_citations(markdown: &str, known_files: &HashSet<&str>) -> usize {
+    Parser::new(markdown)
+        .filter(|event| match event {
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                let path = dest_url.split('#').next().unwrap_or_default();
+                !is_external(dest_url) && known_files.contains(normalize_path(path))
+            }
+            _ => false,
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::{DanglingLink, DanglingReason};
+
+    fn page(id: &str, content: &str, files: &[&str]) -> WikiPage {
+        let mut page = WikiPage::new(id.to_string(), id.to_string(), String::new());
+        page.content = content.to_string();
+        page.file_paths = files.iter().map(|f| f.to_string()).collect();
+        page
+    }
+
+    #[test]
+    fn test_page_issues_lower_the_score() {
+        let mut wiki = WikiStructure::new("Repo".into(), String::new(), "repo".into());
+        wiki.pages.push(page(
+            "overview",
+            "# Overview\n\nThe entry point is [main](src/main.rs#L1-L5).\n",
+            &["src/main.rs"],
+        ));
+        wiki.pages.push(page(
+            "config",
+            "# Config\n\n## Options\n\n## Files\n\nSee [missing](./nowhere.md).\n",
+            &["src/config.rs", "src/gone.rs"],
+        ));
+        wiki.link_validation.dangling.push(DanglingLink {
+            page_id: "config".to_string(),
+            text: "missing".to_string(),
+            target: "./nowhere.md".to_string(),
+            reason: DanglingReason::UnknownPage,
+        });
+
+        let report =
+            QualityReport::evaluate(&wiki, &["src/main.rs", "src/config.rs", "src/lib.rs"]);
+
+        let overview = report
+            .pages
+            .iter()
+            .find(|p| p.page_id == "overview")
+            .unwrap();
+        assert_eq!(overview.score, 100);
+        assert_eq!(overview.citations, 1);
+        assert!(overview.issues.is_empty());
+
+        let config = &report.pages[0];
+        assert_eq!(config.page_id, "config");
+        assert_eq!(config.broken_links, 1);
+        assert_eq!(config.empty_sections, vec!["Options".to_string()]);
+        assert_eq!(config.missing_files, vec!["src/gone.rs".to_string()]);
+        assert_eq!(config.citations, 0);
+        // 10 for the link, 5 for the section, 10 for the missing file, 30 for no citations
+        assert_eq!(config.score, 45);
+        assert_eq!(report.weak_pages(60).count(), 1);
+    }
+
+    #[test]
+    fn test_file_coverage() {
+        let mut wiki = WikiStructure::new("Repo".into(), String::new(), "repo".into());
+        wiki.pages.push(page(
+            "overview",
+            "# Overview\n\nText.\n",
+            &["./src/main.rs"],
+        ));
+
+        let report = QualityReport::evaluate(&wiki, &["src/main.rs", "src/lib.rs"]);
+        assert_eq!(report.file_coverage, Some(0.5));
+        assert_eq!(report.referenced_files, 1);
+        assert_eq!(report.uncovered_files, vec!["src/lib.rs".to_string()]);
+
+        let report = QualityReport::evaluate::<&str>(&wiki, &[]);
+        assert_eq!(report.file_coverage, None);
+        assert!(report.pages[0].missing_files.is_empty());
+    }
+}
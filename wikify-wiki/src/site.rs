@@ -0,0 +1,881 @@
+//! Static HTML site export
+//!
+//! Builds a site that can be served from any static host (e.g. GitHub Pages):
+//! every page shares a sidebar with navigation and a search box, code blocks
+//! are highlighted with highlight.js and ```` ```mermaid ```` blocks are
+//! rendered as diagrams. The search index is computed at export time and
+//! shipped as a script, so searching works without a server (even from
+//! `file://`).
+
+use crate::assets::{copy_assets, rewrite_asset_images, MEDIA_DIR};
+use crate::links::rewrite_page_links;
+use crate::markdown_organizer::{page_headings, NavigationBuilder, NavigationItem, NavigationKind};
+use crate::types::{WikiPage, WikiStructure};
+use pulldown_cmark::{html, CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+use tokio::fs;
+use tracing::{debug, info};
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// Weight of a term occurring in a page title relative to one in the body
+const TITLE_WEIGHT: u32 = 10;
+/// Maximum length of search result excerpts, in characters
+const EXCERPT_LENGTH: usize = 200;
+/// Common words not worth indexing
+const STOP_WORDS: &[&str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on", "or",
+    "that", "the", "this", "to", "was", "with",
+];
+
+const HIGHLIGHT_JS: &str = "https://cdn.jsdelivr.net/gh/highlightjs/cdn-release@11/build";
+const MERMAID_JS: &str = "https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs";
+
+/// A document in the search index
+#[derive(Debug, Serialize)]
+struct SearchDoc {
+    title: String,
+    url: String,
+    excerpt: String,
+}
+
+/// Client-side search index: documents plus an inverted index of
+/// `term -> [[document index, score], ...]`
+#[derive(Debug, Serialize)]
+struct SearchIndex {
+    docs: Vec<SearchDoc>,
+    terms: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+/// Build a static site for a wiki in `output_path`
+pub async fn build_site(wiki: &WikiStructure, output_path: &Path) -> WikifyResult<()> {
+    let assets = output_path.join("assets");
+    fs::create_dir_all(&assets).await?;
+
+    let urls = page_urls(wiki);
+
+    fs::write(assets.join("style.css"), STYLE_CSS).await?;
+    fs::write(assets.join("search.js"), SEARCH_JS).await?;
+    let index = serde_json::to_string(&build_search_index(wiki, &urls)).map_err(|e| {
+        WikifyError::Config {
+            message: format!("Failed to serialize search index: {}", e),
+            source: Some(Box::new(e)),
+            context: ErrorContext::new("wiki_export"),
+        }
+    })?;
+    fs::write(
+        assets.join("search-index.js"),
+        format!("window.WIKIFY_SEARCH_INDEX = {};\n", index),
+    )
+    .await?;
+    // Serve files as-is on GitHub Pages
+    fs::write(output_path.join(".nojekyll"), "").await?;
+
+    let media = copy_assets(wiki, &output_path.join(MEDIA_DIR)).await?;
+
+    fs::write(output_path.join("index.html"), render_index(wiki, &urls)).await?;
+    for page in &wiki.pages {
+        let url = &urls[&page.id];
+        fs::write(
+            output_path.join(url),
+            render_page(wiki, page, &urls, &media),
+        )
+        .await?;
+        debug!("Exported HTML page: {} -> {}", page.title, url);
+    }
+
+    info!(
+        "Built static site with {} pages in {:?}",
+        wiki.pages.len(),
+        output_path
+    );
+    Ok(())
+}
+
+/// Unique HTML file name for every page, keyed by page ID
+fn page_urls(wiki: &WikiStructure) -> HashMap<String, String> {
+    let mut used = HashSet::new();
+    used.insert("index".to_string());
+
+    let mut urls = HashMap::new();
+    for page in &wiki.pages {
+        let mut base = slugify(&page.title);
+        if base.is_empty() {
+            base = slugify(&page.id);
+        }
+        if base.is_empty() {
+            base = "page".to_string();
+        }
+
+        let mut slug = base.clone();
+        let mut suffix = 2;
+        while !used.insert(slug.clone()) {
+            slug = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+        urls.insert(page.id.clone(), format!("{}.html", slug));
+    }
+    urls
+}
+
+/// Lowercase, hyphen-separated ASCII slug
+fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Pages in navigation order: by section, then pages without a section
+fn ordered_sections(wiki: &WikiStructure) -> Vec<(Option<&str>, Vec<&WikiPage>)> {
+    let mut sections: Vec<_> = wiki.sections.iter().collect();
+    sections.sort_by_key(|s| s.order);
+
+    let mut listed = HashSet::new();
+    let mut groups = Vec::new();
+    for section in sections {
+        let pages: Vec<&WikiPage> = section
+            .pages
+            .iter()
+            .filter_map(|id| wiki.pages.iter().find(|p| p.id == *id))
+            .filter(|p| listed.insert(p.id.as_str()))
+            .collect();
+        if !pages.is_empty() {
+            groups.push((Some(section.title.as_str()), pages));
+        }
+    }
+
+    let others: Vec<&WikiPage> = wiki
+        .pages
+        .iter()
+        .filter(|p| !listed.contains(p.id.as_str()))
+        .collect();
+    if !others.is_empty() {
+        let title = if groups.is_empty() {
+            None
+        } else {
+            Some("Other Pages")
+        };
+        groups.push((title, others));
+    }
+    groups
+}
+
+/// Sidebar navigation, highlighting the current page and listing its headings
+fn render_nav(
+    wiki: &WikiStructure,
+    urls: &HashMap<String, String>,
+    current: Option<&str>,
+) -> String {
+    let navigation = NavigationBuilder::new().build(wiki);
+    let (sections, pages): (Vec<_>, Vec<_>) = navigation
+        .into_iter()
+        .partition(|item| item.kind == NavigationKind::Section);
+
+    let mut nav = String::new();
+    for section in &sections {
+        nav.push_str("<div class=\"nav-section\">");
+        nav.push_str(&format!("<h4>{}</h4>", escape_html(&section.title)));
+        render_nav_items(&mut nav, &section.children, urls, current);
+        nav.push_str("</div>");
+    }
+    if !pages.is_empty() {
+        nav.push_str("<div class=\"nav-section\">");
+        if !sections.is_empty() {
+            nav.push_str("<h4>Other Pages</h4>");
+        }
+        render_nav_items(&mut nav, &pages, urls, current);
+        nav.push_str("</div>");
+    }
+    nav
+}
+
+/// Nested navigation list; headings are only shown for the current page
+fn render_nav_items(
+    nav: &mut String,
+    items: &[NavigationItem],
+    urls: &HashMap<String, String>,
+    current: Option<&str>,
+) {
+    nav.push_str("<ul>");
+    for item in items {
+        match item.kind {
+            NavigationKind::Section => {
+                nav.push_str(&format!(
+                    "<li class=\"nav-subsection\"><span>{}</span>",
+                    escape_html(&item.title)
+                ));
+                render_nav_items(nav, &item.children, urls, current);
+            }
+            NavigationKind::Page => {
+                let active = current == Some(item.id.as_str());
+                nav.push_str(&format!(
+                    "<li><a href=\"{}\"{}>{}</a>",
+                    urls[&item.id],
+                    if active { " class=\"active\"" } else { "" },
+                    escape_html(&item.title)
+                ));
+                if active && !item.children.is_empty() {
+                    render_nav_items(nav, &item.children, urls, current);
+                }
+            }
+            NavigationKind::Heading => {
+                nav.push_str(&format!(
+                    "<li class=\"nav-heading\"><a href=\"{}#{}\">{}</a>",
+                    urls[&item.id],
+                    item.anchor.as_deref().unwrap_or_default(),
+                    escape_html(&item.title)
+                ));
+                if !item.children.is_empty() {
+                    render_nav_items(nav, &item.children, urls, current);
+                }
+            }
+        }
+        nav.push_str("</li>");
+    }
+    nav.push_str("</ul>");
+}
+
+/// Shared page layout
+fn render_layout(wiki: &WikiStructure, title: &str, nav: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{title}</title>
+    <link rel="stylesheet" href="assets/style.css">
+    <link rel="stylesheet" href="{highlight}/styles/github.min.css">
+</head>
+<body>
+    <aside class="sidebar">
+        <a class="site-title" href="index.html">{wiki_title}</a>
+        <div class="search">
+            <input id="search-input" type="search" placeholder="Search..." autocomplete="off">
+            <ul id="search-results"></ul>
+        </div>
+        <nav>{nav}</nav>
+    </aside>
+    <main class="content">
+        <article>
+{body}
+        </article>
+        <footer>Generated by Wikify on {generated_at}</footer>
+    </main>
+    <script src="assets/search-index.js"></script>
+    <script src="assets/search.js"></script>
+    <script src="{highlight}/highlight.min.js"></script>
+    <script>if (window.hljs) {{ hljs.highlightAll(); }}</script>
+    <script type="module">
+        import mermaid from "{mermaid}";
+        mermaid.initialize({{ startOnLoad: true }});
+    </script>
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        highlight = HIGHLIGHT_JS,
+        mermaid = MERMAID_JS,
+        wiki_title = escape_html(&wiki.title),
+        nav = nav,
+        body = body,
+        generated_at = wiki.metadata.generated_at.format("%Y-%m-%d %H:%M:%S UTC"),
+    )
+}
+
+/// Landing page listing all pages with their descriptions
+fn render_index(wiki: &WikiStructure, urls: &HashMap<String, String>) -> String {
+    let mut body = format!(
+        "<h1>{}</h1>\n<p class=\"description\">{}</p>\n",
+        escape_html(&wiki.title),
+        escape_html(&wiki.description)
+    );
+    for (title, pages) in ordered_sections(wiki) {
+        if let Some(title) = title {
+            body.push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
+        }
+        body.push_str("<div class=\"page-grid\">\n");
+        for page in pages {
+            body.push_str(&format!(
+                "<a class=\"page-card\" href=\"{}\"><h3>{}</h3><p>{}</p></a>\n",
+                urls[&page.id],
+                escape_html(&page.title),
+                escape_html(&page.description)
+            ));
+        }
+        body.push_str("</div>\n");
+    }
+
+    render_layout(wiki, &wiki.title, &render_nav(wiki, urls, None), &body)
+}
+
+/// A single wiki page, with `media` the repository images copied to the site
+fn render_page(
+    wiki: &WikiStructure,
+    page: &WikiPage,
+    urls: &HashMap<String, String>,
+    media: &BTreeSet<String>,
+) -> String {
+    let content = rewrite_page_links(wiki, &page.content, |target| {
+        urls.get(&target.id).cloned().unwrap_or_default()
+    });
+    let content = rewrite_asset_images(&content, media, |path| {
+        format!("{}/{}", MEDIA_DIR, path).replace(' ', "%20")
+    });
+    render_layout(
+        wiki,
+        &format!("{} - {}", page.title, wiki.title),
+        &render_nav(wiki, urls, Some(&page.id)),
+        &markdown_to_html(&content),
+    )
+}
+
+/// Render Markdown to HTML
+///
+/// Mermaid blocks become `<pre class="mermaid">` for client-side rendering,
+/// headings get the anchors used by the wiki navigation and raw HTML in the
+/// source is escaped rather than passed through.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_FOOTNOTES;
+
+    let mut anchors = page_headings(markdown).into_iter().map(|h| h.anchor);
+    let mut mermaid: Option<String> = None;
+    let events = Parser::new_ext(markdown, options).filter_map(|event| {
+        if let Some(diagram) = mermaid.as_mut() {
+            return match event {
+                Event::Text(text) => {
+                    diagram.push_str(&text);
+                    None
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    let diagram = mermaid.take().unwrap_or_default();
+                    Some(Event::Html(
+                        format!("<pre class=\"mermaid\">{}</pre>\n", escape_html(&diagram)).into(),
+                    ))
+                }
+                _ => None,
+            };
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(ref lang)))
+                if lang.split_whitespace().next() == Some("mermaid") =>
+            {
+                mermaid = Some(String::new());
+                None
+            }
+            Event::Start(Tag::Heading {
+                level,
+                classes,
+                attrs,
+                ..
+            }) => Some(Event::Start(Tag::Heading {
+                level,
+                id: anchors.next().map(Into::into),
+                classes,
+                attrs,
+            })),
+            Event::Html(text) | Event::InlineHtml(text) => Some(Event::Text(text)),
+            event => Some(event),
+        }
+    });
+
+    let mut output = String::new();
+    html::push_html(&mut output, events);
+    output
+}
+
+/// Build the client-side search index
+fn build_search_index(wiki: &WikiStructure, urls: &HashMap<String, String>) -> SearchIndex {
+    let mut docs = Vec::new();
+    let mut terms: BTreeMap<String, Vec<(usize, u32)>> = BTreeMap::new();
+
+    for (doc, page) in wiki.pages.iter().enumerate() {
+        let text = plain_text(&page.content);
+
+        let mut scores: BTreeMap<String, u32> = BTreeMap::new();
+        for token in tokenize(&page.title) {
+            *scores.entry(token).or_default() += TITLE_WEIGHT;
+        }
+        for token in tokenize(&text) {
+            *scores.entry(token).or_default() += 1;
+        }
+        for (term, score) in scores {
+            terms.entry(term).or_default().push((doc, score));
+        }
+
+        let excerpt_source = if page.description.is_empty() {
+            text.as_str()
+        } else {
+            page.description.as_str()
+        };
+        docs.push(SearchDoc {
+            title: page.title.clone(),
+            url: urls[&page.id].clone(),
+            excerpt: excerpt(excerpt_source),
+        });
+    }
+
+    SearchIndex { docs, terms }
+}
+
+/// Plain text of a Markdown document
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Text(t) | Event::Code(t) => {
+                text.push_str(&t);
+                text.push(' ');
+            }
+            Event::SoftBreak | Event::HardBreak | Event::End(_) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Lowercased search terms of a text, without stop words
+///
+/// Must match the tokenizer in `search.js`.
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.chars().count() >= 2 && !STOP_WORDS.contains(t))
+        .map(str::to_string)
+        .collect()
+}
+
+/// First characters of a text, cut at a word boundary
+fn excerpt(text: &str) -> String {
+    if text.chars().count() <= EXCERPT_LENGTH {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(EXCERPT_LENGTH).collect();
+    let cut = cut.rsplit_once(' ').map(|(head, _)| head).unwrap_or(&cut);
+    format!("{}…", cut)
+}
+
+/// Escape text for use in HTML content and attribute values
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const SEARCH_JS: &str = r#"(function () {
+    var index = window.WIKIFY_SEARCH_INDEX || { docs: [], terms: {} };
+    var stopWords = ["an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it",
+        "of", "on", "or", "that", "the", "this", "to", "was", "with"];
+    var termList = Object.keys(index.terms);
+
+    // Must match `tokenize` in the exporter
+    function tokenize(text) {
+        return text.toLowerCase().split(/[^\p{L}\p{N}]+/u).filter(function (t) {
+            return Array.from(t).length >= 2 && stopWords.indexOf(t) < 0;
+        });
+    }
+
+    // Every query token must prefix-match at least one indexed term
+    function search(query) {
+        var tokens = tokenize(query);
+        if (tokens.length === 0) return [];
+        var scores = null;
+        tokens.forEach(function (token) {
+            var tokenScores = {};
+            termList.forEach(function (term) {
+                if (term.indexOf(token) !== 0) return;
+                // Exact matches rank above prefix matches
+                var boost = term === token ? 2 : 1;
+                index.terms[term].forEach(function (posting) {
+                    tokenScores[posting[0]] = (tokenScores[posting[0]] || 0) + posting[1] * boost;
+                });
+            });
+            if (scores === null) {
+                scores = tokenScores;
+            } else {
+                Object.keys(scores).forEach(function (doc) {
+                    if (tokenScores[doc] === undefined) delete scores[doc];
+                    else scores[doc] += tokenScores[doc];
+                });
+            }
+        });
+        return Object.keys(scores)
+            .sort(function (a, b) { return scores[b] - scores[a]; })
+            .slice(0, 10)
+            .map(function (doc) { return index.docs[doc]; });
+    }
+
+    function render(results, list) {
+        list.innerHTML = "";
+        results.forEach(function (doc) {
+            var item = document.createElement("li");
+            var link = document.createElement("a");
+            link.href = doc.url;
+            var title = document.createElement("strong");
+            title.textContent = doc.title;
+            var excerpt = document.createElement("span");
+            excerpt.textContent = doc.excerpt;
+            link.appendChild(title);
+            link.appendChild(excerpt);
+            item.appendChild(link);
+            list.appendChild(item);
+        });
+    }
+
+    document.addEventListener("DOMContentLoaded", function () {
+        var input = document.getElementById("search-input");
+        var list = document.getElementById("search-results");
+        if (!input || !list) return;
+        input.addEventListener("input", function () {
+            render(search(input.value), list);
+        });
+        input.addEventListener("keydown", function (event) {
+            if (event.key === "Enter" && list.firstChild) {
+                window.location.href = list.firstChild.firstChild.href;
+            }
+        });
+    });
+})();
+"#;
+
+const STYLE_CSS: &str = r#"* {
+    box-sizing: border-box;
+}
+
+body {
+    font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif;
+    line-height: 1.6;
+    color: #24292f;
+    margin: 0;
+    display: flex;
+    min-height: 100vh;
+}
+
+.sidebar {
+    width: 280px;
+    flex-shrink: 0;
+    padding: 20px;
+    border-right: 1px solid #e1e4e8;
+    background-color: #f6f8fa;
+    position: sticky;
+    top: 0;
+    height: 100vh;
+    overflow-y: auto;
+}
+
+.site-title {
+    display: block;
+    font-size: 1.3em;
+    font-weight: bold;
+    color: #24292f;
+    text-decoration: none;
+    margin-bottom: 16px;
+}
+
+.search {
+    position: relative;
+    margin-bottom: 20px;
+}
+
+#search-input {
+    width: 100%;
+    padding: 6px 10px;
+    border: 1px solid #d0d7de;
+    border-radius: 6px;
+    font-size: 0.95em;
+}
+
+#search-results {
+    list-style: none;
+    margin: 4px 0 0;
+    padding: 0;
+    position: absolute;
+    left: 0;
+    right: 0;
+    z-index: 10;
+    background-color: white;
+    box-shadow: 0 4px 12px rgba(0, 0, 0, 0.15);
+    border-radius: 6px;
+    max-height: 60vh;
+    overflow-y: auto;
+}
+
+#search-results a {
+    display: block;
+    padding: 8px 10px;
+    color: #24292f;
+    text-decoration: none;
+    border-bottom: 1px solid #eee;
+}
+
+#search-results a:hover {
+    background-color: #f6f8fa;
+}
+
+#search-results span {
+    display: block;
+    font-size: 0.8em;
+    color: #57606a;
+}
+
+.nav-section h4 {
+    margin: 16px 0 6px;
+    font-size: 0.8em;
+    text-transform: uppercase;
+    color: #57606a;
+}
+
+.nav-section ul {
+    list-style: none;
+    margin: 0;
+    padding: 0;
+}
+
+.nav-section a {
+    display: block;
+    padding: 3px 8px;
+    border-radius: 4px;
+    color: #0969da;
+    text-decoration: none;
+}
+
+.nav-section a:hover,
+.nav-section a.active {
+    background-color: #ddf4ff;
+}
+
+.nav-section ul ul {
+    padding-left: 12px;
+}
+
+.nav-subsection > span {
+    display: block;
+    padding: 6px 8px 2px;
+    font-weight: 600;
+    color: #24292f;
+}
+
+.nav-heading a {
+    font-size: 0.9em;
+    color: #57606a;
+}
+
+.content {
+    flex: 1;
+    min-width: 0;
+    padding: 32px 48px;
+    max-width: 960px;
+}
+
+.description {
+    font-size: 1.2em;
+    color: #57606a;
+}
+
+.page-grid {
+    display: grid;
+    grid-template-columns: repeat(auto-fill, minmax(260px, 1fr));
+    gap: 16px;
+}
+
+.page-card {
+    display: block;
+    border: 1px solid #d0d7de;
+    border-radius: 8px;
+    padding: 16px;
+    color: inherit;
+    text-decoration: none;
+}
+
+.page-card:hover {
+    border-color: #0969da;
+}
+
+.page-card h3 {
+    margin: 0 0 8px;
+    color: #0969da;
+}
+
+.page-card p {
+    margin: 0;
+    color: #57606a;
+}
+
+pre {
+    background-color: #f6f8fa;
+    border-radius: 6px;
+    padding: 16px;
+    overflow-x: auto;
+}
+
+pre code.hljs {
+    padding: 0;
+    background: none;
+}
+
+code {
+    font-family: 'SFMono-Regular', Consolas, 'Liberation Mono', monospace;
+    font-size: 0.9em;
+}
+
+pre.mermaid {
+    background: none;
+    text-align: center;
+}
+
+table {
+    border-collapse: collapse;
+}
+
+th, td {
+    border: 1px solid #d0d7de;
+    padding: 6px 12px;
+}
+
+blockquote {
+    border-left: 4px solid #d0d7de;
+    margin: 0;
+    padding-left: 16px;
+    color: #57606a;
+}
+
+footer {
+    margin-top: 48px;
+    padding-top: 16px;
+    border-top: 1px solid #e1e4e8;
+    color: #57606a;
+    font-size: 0.85em;
+}
+
+@media (max-width: 768px) {
+    body {
+        flex-direction: column;
+    }
+
+    .sidebar {
+        width: 100%;
+        height: auto;
+        position: static;
+        border-right: none;
+        border-bottom: 1px solid #e1e4e8;
+    }
+
+    .content {
+        padding: 20px;
+    }
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ImportanceLevel;
+
+    fn page(id: &str, title: &str, content: &str) -> WikiPage {
+        WikiPage {
+            id: id.to_string(),
+            title: title.to_string(),
+            content: content.to_string(),
+            description: String::new(),
+            importance: ImportanceLevel::Medium,
+            file_paths: vec![],
+            related_pages: vec![],
+            parent_section: None,
+            tags: vec![],
+            reading_time: 1,
+            generated_at: chrono::Utc::now(),
+            source_documents: vec![],
+            code_examples: vec![],
+        }
+    }
+
+    #[test]
+    fn test_markdown_to_html_renders_mermaid_and_escapes_html() {
+        let html = markdown_to_html(
+            "```mermaid\ngraph TD\n  A --> B\n```\n\n```rust\nfn main() {}\n```\n\n<script>x</script>\n",
+        );
+        assert!(html.contains("<pre class=\"mermaid\">graph TD\n  A --&gt; B\n</pre>"));
+        assert!(html.contains("<code class=\"language-rust\">"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_search_index_weights_titles() {
+        let mut wiki =
+            WikiStructure::new("Demo".to_string(), String::new(), "/tmp/demo".to_string());
+        wiki.pages
+            .push(page("a", "Installation", "Run the installer"));
+        wiki.pages
+            .push(page("b", "Usage", "Check the installation first"));
+        let urls = page_urls(&wiki);
+
+        let index = build_search_index(&wiki, &urls);
+        assert_eq!(index.docs[0].url, "installation.html");
+        assert_eq!(index.terms["installation"], vec![(0, TITLE_WEIGHT), (1, 1)]);
+        assert!(!index.terms.contains_key("the"));
+    }
+
+    #[tokio::test]
+    async fn test_build_site_writes_pages_and_assets() {
+        let temp = tempfile::tempdir().unwrap();
+        let mut wiki = WikiStructure::new(
+            "Demo".to_string(),
+            "A demo".to_string(),
+            "/tmp/demo".to_string(),
+        );
+        wiki.pages
+            .push(page("a", "Getting Started", "# Getting Started\n\nHello"));
+        wiki.pages.push(page("b", "Getting Started", "Again"));
+
+        build_site(&wiki, temp.path()).await.unwrap();
+
+        for file in [
+            "index.html",
+            "getting-started.html",
+            "getting-started-2.html",
+            "assets/style.css",
+            "assets/search.js",
+            "assets/search-index.js",
+            ".nojekyll",
+        ] {
+            assert!(temp.path().join(file).exists(), "missing {}", file);
+        }
+        let index = std::fs::read_to_string(temp.path().join("index.html")).unwrap();
+        assert!(index.contains("href=\"getting-started-2.html\""));
+    }
+
+    #[test]
+    fn test_page_nav_links_headings_to_anchors() {
+        let mut wiki =
+            WikiStructure::new("Demo".to_string(), String::new(), "/tmp/demo".to_string());
+        wiki.pages.push(page(
+            "a",
+            "Setup",
+            "# Setup\n\n## Install the CLI\n\nRun it",
+        ));
+        wiki.pages.push(page("b", "Usage", "## Commands"));
+        let urls = page_urls(&wiki);
+
+        let html = render_page(&wiki, &wiki.pages[0], &urls, &BTreeSet::new());
+        assert!(html.contains("<h2 id=\"install-the-cli\">Install the CLI</h2>"));
+        assert!(html.contains("href=\"setup.html#install-the-cli\""));
+        // Headings of other pages stay collapsed
+        assert!(!html.contains("usage.html#commands"));
+    }
+}
@@ -0,0 +1,379 @@
+//! Verified code examples
+//!
+//! Instead of letting the LLM paraphrase code, page prompts ask it to
+//! reference code by file path and line range in the info string of a
+//! fenced block, e.g. ```` ```rust src/main.rs:10-25 ````. The extractor
+//! replaces the body of such blocks with the exact lines from the
+//! repository, adds a link back to the source and records every example in
+//! the page metadata. Blocks whose file or lines cannot be read are left as
+//! written and recorded as unverified.
+
+use crate::links::split_line_range;
+use crate::types::WikiPage;
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Longest example copied into a page by default, in lines
+const DEFAULT_MAX_LINES: usize = 60;
+
+/// Prompt instructions for referencing code instead of writing it
+pub const SNIPPET_INSTRUCTIONS: &str = "Do not paraphrase or retype code from the repository. \
+To show code from a file, write a fenced code block whose info string is the language followed by \
+the file path and line range, e.g. ```rust src/main.rs:10-25, and leave its body empty; the exact \
+lines are inserted automatically. Keep examples short (under 40 lines).";
+
+/// Code example copied verbatim from the repository into a page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeExample {
+    /// File path relative to the repository root
+    pub path: String,
+    /// First line of the example (1-based)
+    pub start_line: usize,
+    /// Last line of the example (inclusive)
+    pub end_line: usize,
+    /// Whether the lines were read from the repository; unverified
+    /// examples keep the text written by the LLM
+    pub verified: bool,
+}
+
+/// Snippet reference found in a page
+struct SnippetRef {
+    /// Byte range of the whole code block
+    block: Range<usize>,
+    /// Indentation of the block's lines (inside list items)
+    indent: String,
+    language: String,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+}
+
+/// Pulls referenced code snippets from a repository into wiki pages
+pub struct SnippetExtractor {
+    repo_path: PathBuf,
+    /// Known repository files, to resolve bare file names
+    files: Vec<String>,
+    max_lines: usize,
+}
+
+impl SnippetExtractor {
+    /// Create an extractor for a local repository
+    pub fn new<P: AsRef<Path>>(repo_path: P) -> Self {
+        Self {
+            repo_path: repo_path.as_ref().to_path_buf(),
+            files: Vec::new(),
+            max_lines: DEFAULT_MAX_LINES,
+        }
+    }
+
+    /// Repository files that bare file names (`main.rs`) may refer to
+    pub fn with_files<I, S>(mut self, files: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.files.extend(files.into_iter().map(Into::into));
+        self
+    }
+
+    /// Longest example to copy; longer ranges are cut short
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+
+    /// Fill the snippet references of a page with the referenced lines
+    ///
+    /// The page's code examples are replaced with the ones found. Returns
+    /// the number of verified examples.
+    pub async fn extract(&self, page: &mut WikiPage) -> usize {
+        let references = find_references(&page.content);
+        if references.is_empty() {
+            page.code_examples.clear();
+            return 0;
+        }
+
+        let mut sources: HashMap<String, Option<String>> = HashMap::new();
+        let mut examples = Vec::with_capacity(references.len());
+        let mut replacements = Vec::new();
+        for reference in references {
+            let path = self
+                .resolve_path(&reference.path)
+                .unwrap_or_else(|| reference.path.clone());
+            if !sources.contains_key(&path) {
+                let source = self.read(&path).await;
+                sources.insert(path.clone(), source);
+            }
+
+            let lines = sources[&path]
+                .as_deref()
+                .and_then(|source| self.lines(source, reference.start_line, reference.end_line));
+            match lines {
+                Some((code, end_line)) => {
+                    let mut block = render_block(&reference, &path, &code, end_line);
+                    if !page.content[reference.block.clone()].ends_with('\n') {
+                        block.pop();
+                    }
+                    replacements.push((reference.block.clone(), block));
+                    examples.push(CodeExample {
+                        path,
+                        start_line: reference.start_line,
+                        end_line,
+                        verified: true,
+                    });
+                }
+                None => {
+                    warn!(
+                        "Code example {}:{}-{} on page '{}' could not be read",
+                        reference.path, reference.start_line, reference.end_line, page.id
+                    );
+                    examples.push(CodeExample {
+                        path,
+                        start_line: reference.start_line,
+                        end_line: reference.end_line,
+                        verified: false,
+                    });
+                }
+            }
+        }
+
+        let mut content = String::with_capacity(page.content.len());
+        let mut position = 0;
+        for (block, replacement) in replacements {
+            content.push_str(&page.content[position..block.start]);
+            content.push_str(&replacement);
+            position = block.end;
+        }
+        content.push_str(&page.content[position..]);
+        page.content = content;
+
+        let verified = examples.iter().filter(|e| e.verified).count();
+        debug!(
+            "Extracted {} of {} code examples for page '{}'",
+            verified,
+            examples.len(),
+            page.id
+        );
+        page.code_examples = examples;
+        verified
+    }
+
+    /// Repository-relative path of a referenced file, if it exists
+    fn resolve_path(&self, path: &str) -> Option<String> {
+        let path = path.trim_start_matches("./");
+        if !is_relative_path(path) {
+            return None;
+        }
+        if self.repo_path.join(path).is_file() {
+            return Some(path.to_string());
+        }
+
+        let suffix = format!("/{}", path);
+        self.files
+            .iter()
+            .map(|f| f.trim_start_matches("./"))
+            .filter(|f| f.ends_with(&suffix) && is_relative_path(f))
+            .min_by_key(|f| f.len())
+            .map(str::to_string)
+    }
+
+    async fn read(&self, path: &str) -> Option<String> {
+        if !is_relative_path(path) {
+            return None;
+        }
+        tokio::fs::read_to_string(self.repo_path.join(path))
+            .await
+            .ok()
+    }
+
+    /// Lines `start..=end` of `source`, cut to the maximum length
+    ///
+    /// Returns the code and the last line actually included.
+    fn lines(&self, source: &str, start: usize, end: usize) -> Option<(String, usize)> {
+        let total = source.lines().count();
+        if start == 0 || start > end || start > total {
+            return None;
+        }
+        let end = end.min(total).min(start + self.max_lines - 1);
+        let code = source
+            .lines()
+            .skip(start - 1)
+            .take(end + 1 - start)
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some((code, end))
+    }
+}
+
+/// Fenced code blocks with a `path:start-end` reference in their info string
+fn find_references(markdown: &str) -> Vec<SnippetRef> {
+    let mut references = Vec::new();
+    for (event, range) in Parser::new(markdown).into_offset_iter() {
+        let Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) = event else {
+            continue;
+        };
+        let mut tokens = info.split_whitespace();
+        let first = tokens.next().unwrap_or_default();
+        let (language, reference) = if split_line_range(first).1.is_some() {
+            (None, first)
+        } else {
+            match tokens.next() {
+                Some(reference) => (Some(first), reference),
+                None => continue,
+            }
+        };
+
+        let (path, Some(lines)) = split_line_range(reference) else {
+            continue;
+        };
+        let (start_line, end_line) = match lines.split_once('-') {
+            Some((start, end)) => (start.parse().ok(), end.parse().ok()),
+            None => (lines.parse().ok(), lines.parse().ok()),
+        };
+        let (Some(start_line), Some(end_line)) = (start_line, end_line) else {
+            continue;
+        };
+
+        let line_start = markdown[..range.start].rfind('\n').map_or(0, |i| i + 1);
+        let indent: String = markdown[line_start..range.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        references.push(SnippetRef {
+            block: range,
+            indent,
+            language: language
+                .map(str::to_string)
+                .unwrap_or_else(|| language_for_path(path).to_string()),
+            path: path.to_string(),
+            start_line,
+            end_line,
+        });
+    }
+    references
+}
+
+/// Code block with the verbatim code and a link to its source lines
+fn render_block(reference: &SnippetRef, path: &str, code: &str, end_line: usize) -> String {
+    // The fence must be longer than any backtick run in the code
+    let longest_run = code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+
+    let lines = if end_line == reference.start_line {
+        reference.start_line.to_string()
+    } else {
+        format!("{}-{}", reference.start_line, end_line)
+    };
+    let anchor = if end_line == reference.start_line {
+        format!("L{}", reference.start_line)
+    } else {
+        format!("L{}-L{}", reference.start_line, end_line)
+    };
+
+    let mut block = format!("{}{}\n", fence, reference.language);
+    for line in code.lines() {
+        block.push_str(&reference.indent);
+        block.push_str(line);
+        block.push('\n');
+    }
+    block.push_str(&format!(
+        "{indent}{fence}\n\n{indent}*Source: [{path}:{lines}]({path}#{anchor})*\n",
+        indent = reference.indent,
+    ));
+    block
+}
+
+/// Whether a path stays inside the repository
+pub(crate) fn is_relative_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Code block language for a file extension
+pub fn language_for_path(path: &str) -> &str {
+    let extension = path.rsplit_once('.').map_or("", |(_, ext)| ext);
+    match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" => "typescript",
+        "tsx" => "tsx",
+        "jsx" => "jsx",
+        "go" => "go",
+        "java" => "java",
+        "kt" => "kotlin",
+        "rb" => "ruby",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "hpp" => "cpp",
+        "sh" | "bash" => "bash",
+        "yml" | "yaml" => "yaml",
+        "toml" => "toml",
+        "json" => "json",
+        "md" => "markdown",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_extract_replaces_referenced_lines() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(
+            temp.path().join("src/main.rs"),
+            "use std::env;\n\nfn main() {\n    println!(\"hi\");\n}\n",
+        )
+        .unwrap();
+
+        let mut page = WikiPage::new("usage".to_string(), "Usage".to_string(), String::new());
+        page.content = "# Usage\n\n```rust main.rs:3-9\nfn main() { /* paraphrased */ }\n```\n\n\
+                        ```src/missing.rs:1-2\n```\n"
+            .to_string();
+
+        let extractor = SnippetExtractor::new(temp.path()).with_files(["src/main.rs"]);
+        assert_eq!(extractor.extract(&mut page).await, 1);
+        assert_eq!(
+            page.content,
+            "# Usage\n\n```rust\nfn main() {\n    println!(\"hi\");\n}\n```\n\n\
+             *Source: [src/main.rs:3-5](src/main.rs#L3-L5)*\n\n\
+             ```src/missing.rs:1-2\n```\n"
+        );
+        assert_eq!(
+            page.code_examples,
+            vec![
+                CodeExample {
+                    path: "src/main.rs".to_string(),
+                    start_line: 3,
+                    end_line: 5,
+                    verified: true,
+                },
+                CodeExample {
+                    path: "src/missing.rs".to_string(),
+                    start_line: 1,
+                    end_line: 2,
+                    verified: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_references_outside_the_repository_are_rejected() {
+        assert!(is_relative_path("src/main.rs"));
+        assert!(!is_relative_path("../secrets.txt"));
+        assert!(!is_relative_path("/etc/passwd"));
+    }
+}
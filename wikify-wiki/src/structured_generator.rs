@@ -116,8 +116,12 @@ impl StructuredWikiGenerator {
 
         // Create enhanced structured prompt for wiki generation
         let file_tree = ""; // TODO: Generate actual file tree
-        let structure_prompt =
+        let mut structure_prompt =
             MarkdownPrompts::create_enhanced_structure_prompt(repo_info, config, file_tree);
+        if let Some(template) = &config.template {
+            structure_prompt.push_str("\n\n");
+            structure_prompt.push_str(&template.structure_instructions());
+        }
 
         let query = wikify_rag::create_simple_query(&structure_prompt);
 
@@ -131,7 +135,7 @@ impl StructuredWikiGenerator {
             })?;
 
         // Parse the structured response
-        self.parse_wiki_structure_response(&response.answer, repo_info)
+        self.parse_wiki_structure_response(&response.answer, repo_info, config)
     }
 
     /// Parse the XML-structured wiki response
@@ -139,9 +143,19 @@ impl StructuredWikiGenerator {
         &self,
         _response: &str,
         repo_info: &RepositoryInfo,
+        config: &WikiConfig,
     ) -> WikifyResult<WikiStructure> {
         info!("Parsing structured wiki response...");
 
+        // A repository template defines the structure outright
+        if let Some(template) = &config.template {
+            return Ok(template.skeleton(
+                repo_info.title.clone(),
+                repo_info.description.clone(),
+                repo_info.title.clone(),
+            ));
+        }
+
         // For now, create a basic structure
         // TODO: Implement proper XML parsing
         let mut wiki_structure = WikiStructure::new(
@@ -446,12 +460,16 @@ impl StructuredWikiGenerator {
         rag_pipeline: &RagPipeline,
     ) -> WikifyResult<String> {
         // Use enhanced prompt for direct markdown generation
-        let markdown_prompt = MarkdownPrompts::create_direct_markdown_prompt(
+        let mut markdown_prompt = MarkdownPrompts::create_direct_markdown_prompt(
             page,
             &page.file_paths,
             repo_info,
             config,
         );
+        if let Some(tone) = config.template.as_ref().and_then(|t| t.tone_instructions()) {
+            markdown_prompt.push_str("\n\n");
+            markdown_prompt.push_str(&tone);
+        }
 
         let query = wikify_rag::create_simple_query(&markdown_prompt);
 
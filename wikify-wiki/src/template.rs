@@ -0,0 +1,372 @@
+//! Custom wiki structure templates
+//!
+//! A template shapes the generated wiki for a repository: a skeleton of
+//! sections with the pages each of them must contain, whether the LLM may
+//! add pages of its own, and tone instructions applied to every page. This
+//! lets an SDK get a reference-style wiki while an internal service gets
+//! runbooks and architecture notes.
+
+use crate::types::{ImportanceLevel, WikiPage, WikiSection, WikiStructure};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use wikify_core::{ErrorContext, WikifyError, WikifyResult};
+
+/// Wiki structure template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiTemplate {
+    /// Template name, for display only
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tone and style instructions for page content
+    #[serde(default)]
+    pub tone: Option<String>,
+    /// Sections of the wiki, in display order
+    #[serde(default)]
+    pub sections: Vec<TemplateSection>,
+    /// Whether the LLM may add pages beyond the required ones
+    #[serde(default = "default_allow_additional_pages")]
+    pub allow_additional_pages: bool,
+}
+
+/// Section of a wiki template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateSection {
+    /// Section ID
+    pub id: String,
+    /// Section title
+    pub title: String,
+    /// What the section covers
+    #[serde(default)]
+    pub description: String,
+    /// Pages the section must contain, in order
+    #[serde(default)]
+    pub pages: Vec<TemplatePage>,
+}
+
+/// Required page of a wiki template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplatePage {
+    /// Page ID
+    pub id: String,
+    /// Page title
+    pub title: String,
+    /// What the page should cover
+    #[serde(default)]
+    pub description: String,
+    /// Importance of the page
+    #[serde(default = "default_importance")]
+    pub importance: ImportanceLevel,
+}
+
+fn default_allow_additional_pages() -> bool {
+    true
+}
+
+fn default_importance() -> ImportanceLevel {
+    ImportanceLevel::High
+}
+
+impl WikiTemplate {
+    /// Check that the template is usable: sections exist and IDs are unique
+    pub fn validate(&self) -> WikifyResult<()> {
+        let invalid = |message: String| {
+            Box::new(WikifyError::Config {
+                message,
+                source: None,
+                context: ErrorContext::new("wiki_template"),
+            })
+        };
+
+        if self.sections.is_empty() {
+            return Err(invalid("Wiki template has no sections".to_string()));
+        }
+
+        let mut section_ids = HashSet::new();
+        let mut page_ids = HashSet::new();
+        for section in &self.sections {
+            if section.id.trim().is_empty() || section.title.trim().is_empty() {
+                return Err(invalid(
+                    "Wiki template sections need an ID and a title".to_string(),
+                ));
+            }
+            if !section_ids.insert(section.id.as_str()) {
+                return Err(invalid(format!(
+                    "Duplicate template section: {}",
+                    section.id
+                )));
+            }
+            for page in &section.pages {
+                if page.id.trim().is_empty() || page.title.trim().is_empty() {
+                    return Err(invalid(format!(
+                        "Pages of template section {} need an ID and a title",
+                        section.id
+                    )));
+                }
+                if !page_ids.insert(page.id.as_str()) {
+                    return Err(invalid(format!("Duplicate template page: {}", page.id)));
+                }
+            }
+        }
+
+        if !self.allow_additional_pages && page_ids.is_empty() {
+            return Err(invalid(
+                "Wiki template allows no additional pages but requires none".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// All required pages, in template order
+    pub fn pages(&self) -> impl Iterator<Item = &TemplatePage> {
+        self.sections.iter().flat_map(|s| s.pages.iter())
+    }
+
+    /// Instructions describing the template for the structure prompt
+    pub fn structure_instructions(&self) -> String {
+        let mut instructions = String::from(
+            "Follow this documentation template. Use exactly these sections (with their IDs) \
+             and include every listed page with its ID:\n",
+        );
+        for section in &self.sections {
+            instructions.push_str(&format!("- Section \"{}\" ({})", section.title, section.id));
+            if !section.description.is_empty() {
+                instructions.push_str(&format!(": {}", section.description));
+            }
+            instructions.push('\n');
+            for page in &section.pages {
+                instructions.push_str(&format!(
+                    "  - Page \"{}\" ({}, {} importance)",
+                    page.title,
+                    page.id,
+                    page.importance.as_str()
+                ));
+                if !page.description.is_empty() {
+                    instructions.push_str(&format!(": {}", page.description));
+                }
+                instructions.push('\n');
+            }
+        }
+        if self.allow_additional_pages {
+            instructions.push_str(
+                "You may add further pages to these sections where the codebase warrants it.\n",
+            );
+        } else {
+            instructions.push_str("Do not add any pages beyond the ones listed.\n");
+        }
+        instructions
+    }
+
+    /// Tone instructions for page prompts, if any
+    pub fn tone_instructions(&self) -> Option<String> {
+        self.tone
+            .as_deref()
+            .map(str::trim)
+            .filter(|tone| !tone.is_empty())
+            .map(|tone| format!("Tone and style: {}", tone))
+    }
+
+    /// Build a wiki structure containing exactly the template skeleton
+    pub fn skeleton(
+        &self,
+        title: String,
+        description: String,
+        repository: String,
+    ) -> WikiStructure {
+        let mut wiki = WikiStructure::new(title, description, repository);
+        self.apply(&mut wiki);
+        wiki
+    }
+
+    /// Reshape a generated wiki structure to follow the template
+    ///
+    /// Missing required pages and sections are added, template sections are
+    /// placed first in template order and own their required pages, and
+    /// pages outside the template are dropped unless additional pages are
+    /// allowed.
+    pub fn apply(&self, wiki: &mut WikiStructure) {
+        // Required pages take the template's title and importance
+        for template_page in self.pages() {
+            match wiki.pages.iter_mut().find(|p| p.id == template_page.id) {
+                Some(page) => {
+                    page.title = template_page.title.clone();
+                    page.importance = template_page.importance.clone();
+                    if !template_page.description.is_empty() {
+                        page.description = template_page.description.clone();
+                    }
+                }
+                None => {
+                    let mut page = WikiPage::new(
+                        template_page.id.clone(),
+                        template_page.title.clone(),
+                        template_page.description.clone(),
+                    );
+                    page.importance = template_page.importance.clone();
+                    wiki.pages.push(page);
+                }
+            }
+        }
+
+        if !self.allow_additional_pages {
+            let required: HashSet<&str> = self.pages().map(|p| p.id.as_str()).collect();
+            wiki.pages.retain(|p| required.contains(p.id.as_str()));
+            let template_sections: HashSet<&str> =
+                self.sections.iter().map(|s| s.id.as_str()).collect();
+            wiki.sections
+                .retain(|s| template_sections.contains(s.id.as_str()));
+        }
+
+        // Required pages belong to their template section only
+        let required: HashSet<&str> = self.pages().map(|p| p.id.as_str()).collect();
+        for section in &mut wiki.sections {
+            section.pages.retain(|id| !required.contains(id.as_str()));
+        }
+
+        let mut sections = Vec::with_capacity(wiki.sections.len() + self.sections.len());
+        for template_section in &self.sections {
+            let mut section = match wiki
+                .sections
+                .iter()
+                .position(|s| s.id == template_section.id)
+            {
+                Some(index) => wiki.sections.remove(index),
+                None => WikiSection {
+                    id: template_section.id.clone(),
+                    title: String::new(),
+                    description: String::new(),
+                    pages: Vec::new(),
+                    subsections: Vec::new(),
+                    parent_section: None,
+                    order: 0,
+                },
+            };
+            section.title = template_section.title.clone();
+            if !template_section.description.is_empty() {
+                section.description = template_section.description.clone();
+            }
+            section.parent_section = None;
+            let mut pages: Vec<String> = template_section
+                .pages
+                .iter()
+                .map(|p| p.id.clone())
+                .collect();
+            pages.append(&mut section.pages);
+            section.pages = pages;
+            sections.push(section);
+        }
+        sections.append(&mut wiki.sections);
+
+        // Keep page IDs in sections consistent with the remaining pages
+        let page_ids: HashSet<&str> = wiki.pages.iter().map(|p| p.id.as_str()).collect();
+        for (index, section) in sections.iter_mut().enumerate() {
+            section.pages.retain(|id| page_ids.contains(id.as_str()));
+            section.order = index as u32 + 1;
+        }
+        for page in &mut wiki.pages {
+            page.parent_section = sections
+                .iter()
+                .find(|s| s.pages.contains(&page.id))
+                .map(|s| s.id.clone());
+        }
+
+        wiki.root_sections = sections
+            .iter()
+            .filter(|s| s.parent_section.is_none())
+            .map(|s| s.id.clone())
+            .collect();
+        wiki.sections = sections;
+        wiki.metadata.stats.total_pages = wiki.pages.len();
+        wiki.metadata.stats.total_sections = wiki.sections.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sdk_template() -> WikiTemplate {
+        WikiTemplate {
+            name: Some("SDK".to_string()),
+            tone: Some("Concise, reference style".to_string()),
+            sections: vec![
+                TemplateSection {
+                    id: "start".to_string(),
+                    title: "Getting Started".to_string(),
+                    description: String::new(),
+                    pages: vec![TemplatePage {
+                        id: "installation".to_string(),
+                        title: "Installation".to_string(),
+                        description: "Installing the SDK".to_string(),
+                        importance: ImportanceLevel::Critical,
+                    }],
+                },
+                TemplateSection {
+                    id: "reference".to_string(),
+                    title: "API Reference".to_string(),
+                    description: String::new(),
+                    pages: vec![TemplatePage {
+                        id: "client".to_string(),
+                        title: "Client".to_string(),
+                        description: String::new(),
+                        importance: ImportanceLevel::High,
+                    }],
+                },
+            ],
+            allow_additional_pages: true,
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_pages() {
+        let mut template = sdk_template();
+        assert!(template.validate().is_ok());
+
+        template.sections[1].pages[0].id = "installation".to_string();
+        assert!(template.validate().is_err());
+    }
+
+    #[test]
+    fn test_apply_reshapes_generated_structure() {
+        let mut wiki = WikiStructure::new(
+            "SDK".to_string(),
+            "An SDK".to_string(),
+            "/test/repo".to_string(),
+        );
+        for id in ["client", "internals"] {
+            wiki.pages
+                .push(WikiPage::new(id.to_string(), id.to_string(), String::new()));
+        }
+        wiki.sections.push(WikiSection {
+            id: "misc".to_string(),
+            title: "Misc".to_string(),
+            description: String::new(),
+            pages: vec!["client".to_string(), "internals".to_string()],
+            subsections: Vec::new(),
+            parent_section: None,
+            order: 1,
+        });
+
+        let mut template = sdk_template();
+        template.apply(&mut wiki);
+
+        let sections: Vec<_> = wiki
+            .sections
+            .iter()
+            .map(|s| (s.id.as_str(), s.pages.clone()))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                ("start", vec!["installation".to_string()]),
+                ("reference", vec!["client".to_string()]),
+                ("misc", vec!["internals".to_string()]),
+            ]
+        );
+        assert_eq!(wiki.root_sections, vec!["start", "reference", "misc"]);
+
+        template.allow_additional_pages = false;
+        template.apply(&mut wiki);
+        let page_ids: Vec<_> = wiki.pages.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(page_ids, vec!["client", "installation"]);
+        assert_eq!(wiki.sections.len(), 2);
+    }
+}
@@ -2,6 +2,11 @@
 //!
 //! This module defines the core data structures used for wiki generation.
 
+use crate::assets::WikiAsset;
+use crate::links::LinkValidation;
+use crate::quality::QualityReport;
+use crate::snippets::CodeExample;
+use crate::template::WikiTemplate;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use wikify_core::DocumentInfo;
@@ -31,6 +36,9 @@ pub struct WikiConfig {
     pub min_importance: ImportanceLevel,
     /// Whether to generate comprehensive view
     pub comprehensive_view: bool,
+    /// Structure template (sections, required pages, tone) to follow
+    #[serde(default)]
+    pub template: Option<WikiTemplate>,
 }
 
 /// Template styles for wiki generation
@@ -74,6 +82,15 @@ pub struct WikiStructure {
     pub root_sections: Vec<String>,
     /// Metadata about the wiki
     pub metadata: WikiMetadata,
+    /// Result of the last cross-page link resolution
+    #[serde(default)]
+    pub link_validation: LinkValidation,
+    /// Quality report from the last generation, if scored
+    #[serde(default)]
+    pub quality: Option<QualityReport>,
+    /// Repository images pages may embed, referenced by their repository path
+    #[serde(default)]
+    pub assets: Vec<WikiAsset>,
 }
 
 /// Individual wiki page
@@ -103,6 +120,9 @@ pub struct WikiPage {
     pub generated_at: DateTime<Utc>,
     /// Source documents used for generation
     pub source_documents: Vec<DocumentInfo>,
+    /// Code examples copied verbatim from the repository
+    #[serde(default)]
+    pub code_examples: Vec<CodeExample>,
 }
 
 /// Hierarchical section for organizing pages
@@ -137,6 +157,9 @@ pub struct WikiMetadata {
     pub stats: WikiStats,
     /// Version of wikify used
     pub wikify_version: String,
+    /// Repository commit the wiki was generated from, if known
+    #[serde(default)]
+    pub commit_sha: Option<String>,
 }
 
 /// Statistics about wiki generation
@@ -227,6 +250,7 @@ impl Default for WikiConfig {
             included_files: None,
             min_importance: ImportanceLevel::Low,
             comprehensive_view: false,
+            template: None,
         }
     }
 }
@@ -254,7 +278,11 @@ impl WikiStructure {
                     estimated_cost: 0.0,
                 },
                 wikify_version: env!("CARGO_PKG_VERSION").to_string(),
+                commit_sha: None,
             },
+            link_validation: LinkValidation::default(),
+            quality: None,
+            assets: Vec::new(),
         }
     }
 
@@ -301,6 +329,7 @@ impl WikiPage {
             reading_time: 0,
             generated_at: Utc::now(),
             source_documents: Vec::new(),
+            code_examples: Vec::new(),
         }
     }
 